@@ -0,0 +1,17 @@
+extern crate timely_communication;
+
+use std::net::TcpListener;
+
+/// Runs a coordinator for processes started with `--rendezvous ADDR --listen ADDR -n NUM`.
+///
+/// Usage: comm_rendezvous ADDR NUM
+fn main() {
+    let address = std::env::args().nth(1).expect("coordinator address required");
+    let processes = std::env::args().nth(2).expect("number of processes required").parse::<usize>().expect("invalid number of processes");
+
+    let listener = TcpListener::bind(&address).expect("failed to bind coordinator address");
+    let addresses = timely_communication::rendezvous::serve(listener, processes).expect("rendezvous failed");
+    for (index, address) in addresses.iter().enumerate() {
+        println!("process {}: {}", index, address);
+    }
+}
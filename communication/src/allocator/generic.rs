@@ -9,11 +9,13 @@ use std::collections::VecDeque;
 
 use crate::allocator::thread::ThreadBuilder;
 use crate::allocator::process::ProcessBuilder as TypedProcessBuilder;
-use crate::allocator::{Allocate, AllocateBuilder, Event, Thread, Process};
+use crate::allocator::{Allocate, AllocateBuilder, Event, Thread, Process, BoxedPush, BoxedPull};
 use crate::allocator::zero_copy::allocator_process::{ProcessBuilder, ProcessAllocator};
 use crate::allocator::zero_copy::allocator::{TcpBuilder, TcpAllocator};
 
-use crate::{Push, Pull, Data, Message};
+use std::any::Any;
+
+use crate::codec::Codec;
 
 /// Enumerates known implementors of `Allocate`.
 /// Passes trait method calls on to members.
@@ -47,13 +49,13 @@ impl Generic {
             Generic::ZeroCopy(z) => z.peers(),
         }
     }
-    /// Constructs several send endpoints and one receive endpoint.
-    fn allocate<T: Data>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>) {
+    /// Constructs several send endpoints and one receive endpoint, serializing with `C`.
+    fn allocate_with<T: Any+Send+Sync+'static, C: Codec<T>>(&mut self, identifier: usize) -> (Vec<BoxedPush<T>>, BoxedPull<T>) {
         match self {
-            Generic::Thread(t) => t.allocate(identifier),
-            Generic::Process(p) => p.allocate(identifier),
-            Generic::ProcessBinary(pb) => pb.allocate(identifier),
-            Generic::ZeroCopy(z) => z.allocate(identifier),
+            Generic::Thread(t) => t.allocate_with::<T, C>(identifier),
+            Generic::Process(p) => p.allocate_with::<T, C>(identifier),
+            Generic::ProcessBinary(pb) => pb.allocate_with::<T, C>(identifier),
+            Generic::ZeroCopy(z) => z.allocate_with::<T, C>(identifier),
         }
     }
     /// Perform work before scheduling operators.
@@ -87,8 +89,8 @@ impl Generic {
 impl Allocate for Generic {
     fn index(&self) -> usize { self.index() }
     fn peers(&self) -> usize { self.peers() }
-    fn allocate<T: Data>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>) {
-        self.allocate(identifier)
+    fn allocate_with<T: Any+Send+Sync+'static, C: Codec<T>>(&mut self, identifier: usize) -> (Vec<BoxedPush<T>>, BoxedPull<T>) {
+        self.allocate_with::<T, C>(identifier)
     }
 
     fn receive(&mut self) { self.receive(); }
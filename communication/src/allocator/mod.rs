@@ -15,10 +15,19 @@ pub mod generic;
 
 pub mod canary;
 pub mod counters;
+pub mod simulation;
 
 pub mod zero_copy;
 
+use std::any::Any;
+
 use crate::{Data, Push, Pull, Message};
+use crate::codec::{Codec, Native};
+
+/// A boxed endpoint into which messages of a channel are pushed.
+pub type BoxedPush<T> = Box<dyn Push<Message<T>>>;
+/// A boxed endpoint from which messages of a channel are pulled.
+pub type BoxedPull<T> = Box<dyn Pull<Message<T>>>;
 
 /// A proto-allocator, which implements `Send` and can be completed with `build`.
 ///
@@ -43,7 +52,13 @@ pub trait Allocate {
     /// The number of workers in the communication group.
     fn peers(&self) -> usize;
     /// Constructs several send endpoints and one receive endpoint.
-    fn allocate<T: Data>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>);
+    fn allocate<T: Data>(&mut self, identifier: usize) -> (Vec<BoxedPush<T>>, BoxedPull<T>) {
+        self.allocate_with::<T, Native>(identifier)
+    }
+    /// Constructs several send endpoints and one receive endpoint, serializing with `C`.
+    ///
+    /// Allocators that do not serialize their messages ignore the codec.
+    fn allocate_with<T: Any+Send+Sync+'static, C: Codec<T>>(&mut self, identifier: usize) -> (Vec<BoxedPush<T>>, BoxedPull<T>);
     /// A shared queue of communication events with channel identifier.
     ///
     /// It is expected that users of the channel allocator will regularly
@@ -9,8 +9,9 @@ use std::collections::{HashMap, VecDeque};
 use crossbeam_channel::{Sender, Receiver};
 
 use crate::allocator::thread::{ThreadBuilder};
-use crate::allocator::{Allocate, AllocateBuilder, Event, Thread};
+use crate::allocator::{Allocate, AllocateBuilder, Event, Thread, BoxedPush, BoxedPull};
 use crate::{Push, Pull, Message};
+use crate::codec::Codec;
 use crate::buzzer::Buzzer;
 
 /// An allocator for inter-thread, intra-process communication
@@ -110,7 +111,7 @@ impl Process {
 impl Allocate for Process {
     fn index(&self) -> usize { self.index }
     fn peers(&self) -> usize { self.peers }
-    fn allocate<T: Any+Send+Sync+'static>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>) {
+    fn allocate_with<T: Any+Send+Sync+'static, C: Codec<T>>(&mut self, identifier: usize) -> (Vec<BoxedPush<T>>, BoxedPull<T>) {
 
         // this is race-y global initialisation of all channels for all workers, performed by the
         // first worker that enters this critical section
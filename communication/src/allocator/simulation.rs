@@ -0,0 +1,256 @@
+//! A simulated network of workers in one thread, with seeded delivery order, delays, and drops.
+//!
+//! A [`Simulation`] connects some number of [`Simulated`] allocators, all of which must be used
+//! from the same thread. Messages sent between workers are held by the simulation until it
+//! delivers them, when the host calls [`Simulation::advance`]. The simulation keeps a virtual
+//! clock that each call to `advance` moves forward by one tick, and delays each message by a
+//! number of ticks chosen at random, up to a configured maximum. Messages due at the same tick
+//! are delivered in a random interleaving, and messages may be dropped with a configured
+//! probability. All choices are drawn from a generator seeded by the configuration, so that a
+//! simulation replays exactly from its seed.
+//!
+//! Messages from one worker to another on the same channel are delivered in the order they were
+//! sent, as a connection between processes would deliver them. Messages are not serialized.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::any::Any;
+
+use crate::allocator::{Allocate, Event, BoxedPush, BoxedPull};
+use crate::{Push, Pull, Message};
+use crate::codec::Codec;
+
+/// The choices of a simulated network.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationConfig {
+    /// The seed from which all choices are drawn.
+    pub seed: u64,
+    /// The greatest number of ticks by which a message may be delayed.
+    pub max_delay: u64,
+    /// The probability with which each message between different workers is dropped.
+    pub drop_rate: f64,
+}
+
+impl SimulationConfig {
+    /// A network seeded by `seed`, which delivers every message at the next tick.
+    pub fn new(seed: u64) -> Self {
+        SimulationConfig { seed, max_delay: 0, drop_rate: 0.0 }
+    }
+    /// Delays each message by up to `ticks` ticks.
+    pub fn max_delay(mut self, ticks: u64) -> Self {
+        self.max_delay = ticks;
+        self
+    }
+    /// Drops each message between different workers with probability `rate`.
+    ///
+    /// Workers rely on the delivery of every message, and a computation whose messages are dropped
+    /// generally fails to complete. Drops are meant for testing that such failures are detected.
+    pub fn drop_rate(mut self, rate: f64) -> Self {
+        self.drop_rate = rate;
+        self
+    }
+}
+
+// Identifies a channel and a pair of workers: `(channel, source, target)`.
+type Link = (usize, usize, usize);
+
+// Delivers a message to its recipient.
+type Delivery = Box<dyn FnOnce()>;
+
+// A worker's queue of communication events.
+type Events = Rc<RefCell<VecDeque<(usize, Event)>>>;
+
+struct Network {
+    config: SimulationConfig,
+    state: u64,
+    now: u64,
+    // Deliveries due at each tick, each with its link, in the order they were sent.
+    in_flight: BTreeMap<u64, Vec<(Link, Delivery)>>,
+    // The tick of the latest delivery scheduled on each link, which later deliveries may not precede.
+    latest: HashMap<Link, u64>,
+    dropped: usize,
+    // For each channel, the queue of received messages of each worker.
+    channels: HashMap<usize, Box<dyn Any>>,
+    events: Vec<Events>,
+}
+
+impl Network {
+    // The splitmix64 generator, which advances the state and returns the next value.
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A value chosen uniformly from `0 .. bound`.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn send(&mut self, link: Link, delivery: Delivery) {
+        let (_, source, target) = link;
+        if source != target && self.config.drop_rate > 0.0 {
+            let threshold = (self.config.drop_rate.clamp(0.0, 1.0) * (u64::MAX as f64)) as u64;
+            if self.next() < threshold || threshold == u64::MAX {
+                self.dropped += 1;
+                return;
+            }
+        }
+        let delay = self.below(self.config.max_delay + 1);
+        let latest = self.latest.entry(link).or_insert(0);
+        let due = ::std::cmp::max(self.now + delay, *latest);
+        *latest = due;
+        self.in_flight.entry(due).or_default().push((link, delivery));
+    }
+}
+
+/// A simulated network connecting workers in one thread.
+pub struct Simulation {
+    network: Rc<RefCell<Network>>,
+}
+
+impl Simulation {
+    /// Allocates a network of `peers` workers, and an allocator for each of them.
+    pub fn new(peers: usize, config: SimulationConfig) -> (Self, Vec<Simulated>) {
+        let events = (0 .. peers).map(|_| Rc::new(RefCell::new(VecDeque::new()))).collect::<Vec<_>>();
+        let network = Rc::new(RefCell::new(Network {
+            state: config.seed,
+            config,
+            now: 0,
+            in_flight: BTreeMap::new(),
+            latest: HashMap::new(),
+            dropped: 0,
+            channels: HashMap::new(),
+            events: events.clone(),
+        }));
+        let allocators = events.into_iter().enumerate().map(|(index, events)| {
+            Simulated { index, peers, events, network: network.clone() }
+        }).collect();
+        (Simulation { network }, allocators)
+    }
+
+    /// The order in which to step the workers during the current tick, chosen at random.
+    pub fn order(&self) -> Vec<usize> {
+        let mut network = self.network.borrow_mut();
+        let mut order = (0 .. network.events.len()).collect::<Vec<_>>();
+        for index in (1 .. order.len()).rev() {
+            let other = network.below(index as u64 + 1) as usize;
+            order.swap(index, other);
+        }
+        order
+    }
+
+    /// Delivers the messages due at the current tick, and moves the clock to the next tick.
+    ///
+    /// Messages are delivered in a random interleaving that respects the order of each link.
+    pub fn advance(&self) {
+        let mut network = self.network.borrow_mut();
+        let now = network.now;
+        let mut links = BTreeMap::<Link, VecDeque<Delivery>>::new();
+        while network.in_flight.keys().next().map(|due| *due <= now).unwrap_or(false) {
+            let due = *network.in_flight.keys().next().unwrap();
+            for (link, delivery) in network.in_flight.remove(&due).unwrap() {
+                links.entry(link).or_default().push_back(delivery);
+            }
+        }
+        let mut links = links.into_values().collect::<Vec<_>>();
+        let mut deliveries = Vec::new();
+        while !links.is_empty() {
+            let index = network.below(links.len() as u64) as usize;
+            deliveries.push(links[index].pop_front().unwrap());
+            if links[index].is_empty() {
+                links.swap_remove(index);
+            }
+        }
+        network.now += 1;
+        drop(network);
+        for delivery in deliveries {
+            delivery();
+        }
+    }
+
+    /// The current tick.
+    pub fn now(&self) -> u64 { self.network.borrow().now }
+    /// The number of messages sent but neither delivered nor dropped.
+    pub fn in_flight(&self) -> usize { self.network.borrow().in_flight.values().map(|d| d.len()).sum() }
+    /// The number of messages dropped.
+    pub fn dropped(&self) -> usize { self.network.borrow().dropped }
+}
+
+/// An allocator for a worker of a simulated network.
+pub struct Simulated {
+    index: usize,
+    peers: usize,
+    events: Events,
+    network: Rc<RefCell<Network>>,
+}
+
+impl Allocate for Simulated {
+    fn index(&self) -> usize { self.index }
+    fn peers(&self) -> usize { self.peers }
+    fn allocate_with<T: Any+Send+Sync+'static, C: Codec<T>>(&mut self, identifier: usize) -> (Vec<BoxedPush<T>>, BoxedPull<T>) {
+        let mut network = self.network.borrow_mut();
+        let peers = self.peers;
+        let inboxes = network.channels
+            .entry(identifier)
+            .or_insert_with(|| {
+                let inboxes = (0 .. peers).map(|_| Rc::new(RefCell::new(VecDeque::<Message<T>>::new()))).collect::<Vec<_>>();
+                Box::new(inboxes)
+            })
+            .downcast_ref::<Vec<Rc<RefCell<VecDeque<Message<T>>>>>>()
+            .expect("failed to correctly cast channel")
+            .clone();
+
+        let pushers = inboxes.iter().enumerate().map(|(target, inbox)| {
+            Box::new(Pusher {
+                link: (identifier, self.index, target),
+                inbox: inbox.clone(),
+                events: network.events[target].clone(),
+                network: self.network.clone(),
+            }) as Box<dyn Push<Message<T>>>
+        }).collect();
+        let puller = Box::new(Puller { inbox: inboxes[self.index].clone(), current: None });
+        (pushers, puller)
+    }
+    fn events(&self) -> &Rc<RefCell<VecDeque<(usize, Event)>>> {
+        &self.events
+    }
+}
+
+/// The push half of a simulated channel, which sends messages through the network.
+struct Pusher<T> {
+    link: Link,
+    inbox: Rc<RefCell<VecDeque<T>>>,
+    events: Events,
+    network: Rc<RefCell<Network>>,
+}
+
+impl<T: 'static> Push<T> for Pusher<T> {
+    fn push(&mut self, element: &mut Option<T>) {
+        if let Some(element) = element.take() {
+            let inbox = self.inbox.clone();
+            let events = self.events.clone();
+            let channel = self.link.0;
+            self.network.borrow_mut().send(self.link, Box::new(move || {
+                inbox.borrow_mut().push_back(element);
+                events.borrow_mut().push_back((channel, Event::Pushed(1)));
+            }));
+        }
+    }
+}
+
+/// The pull half of a simulated channel, which receives the messages delivered to a worker.
+struct Puller<T> {
+    inbox: Rc<RefCell<VecDeque<T>>>,
+    current: Option<T>,
+}
+
+impl<T> Pull<T> for Puller<T> {
+    fn pull(&mut self) -> &mut Option<T> {
+        self.current = self.inbox.borrow_mut().pop_front();
+        &mut self.current
+    }
+}
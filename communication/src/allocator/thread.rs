@@ -5,10 +5,13 @@ use std::cell::RefCell;
 use std::time::Duration;
 use std::collections::VecDeque;
 
-use crate::allocator::{Allocate, AllocateBuilder, Event};
+use crate::allocator::{Allocate, AllocateBuilder, Event, BoxedPush, BoxedPull};
 use crate::allocator::counters::Pusher as CountPusher;
 use crate::allocator::counters::Puller as CountPuller;
+use std::any::Any;
+
 use crate::{Push, Pull, Message};
+use crate::codec::Codec;
 
 /// Builder for single-threaded allocator.
 pub struct ThreadBuilder;
@@ -28,7 +31,7 @@ pub struct Thread {
 impl Allocate for Thread {
     fn index(&self) -> usize { 0 }
     fn peers(&self) -> usize { 1 }
-    fn allocate<T: 'static>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>) {
+    fn allocate_with<T: Any+Send+Sync+'static, C: Codec<T>>(&mut self, identifier: usize) -> (Vec<BoxedPush<T>>, BoxedPull<T>) {
         let (pusher, puller) = Thread::new_from(identifier, self.events.clone());
         (vec![Box::new(pusher)], Box::new(puller))
     }
@@ -8,13 +8,17 @@ use bytes::arc::Bytes;
 
 use crate::networking::MessageHeader;
 
-use crate::{Allocate, Message, Data, Push, Pull};
+use std::any::Any;
+
+use crate::{Allocate, Message, Push};
+use crate::codec::Codec;
 use crate::allocator::AllocateBuilder;
+use crate::allocator::{BoxedPush, BoxedPull};
 use crate::allocator::Event;
 use crate::allocator::canary::Canary;
 
 use super::bytes_exchange::{BytesPull, SendEndpoint, MergeQueue};
-use super::push_pull::{Pusher, PullerInner};
+use super::push_pull::{Pusher, PullerInner, Reassembly};
 
 /// Builds an instance of a TcpAllocator.
 ///
@@ -28,6 +32,7 @@ pub struct TcpBuilder<A: AllocateBuilder> {
     peers:  usize,                      // number of peer allocators.
     futures:   Vec<Receiver<MergeQueue>>,  // to receive queues to each network thread.
     promises:   Vec<Sender<MergeQueue>>,    // to send queues from each network thread.
+    max_record: Option<usize>,              // largest serialized record sent to other processes.
 }
 
 /// Creates a vector of builders, sharing appropriate state.
@@ -69,6 +74,7 @@ pub fn new_vector<A: AllocateBuilder>(
                 peers: threads * processes,
                 promises,
                 futures,
+                max_record: None,
             }})
         .collect();
 
@@ -77,6 +83,14 @@ pub fn new_vector<A: AllocateBuilder>(
 
 impl<A: AllocateBuilder> TcpBuilder<A> {
 
+    /// Limits the serialized size of each record sent to other processes to `max_record` bytes.
+    ///
+    /// Larger records are reported on standard error and dropped, rather than sent.
+    pub fn max_record(mut self, max_record: Option<usize>) -> Self {
+        self.max_record = max_record;
+        self
+    }
+
     /// Builds a `TcpAllocator`, instantiating `Rc<RefCell<_>>` elements.
     pub fn build(self) -> TcpAllocator<A::Allocator> {
 
@@ -110,6 +124,8 @@ impl<A: AllocateBuilder> TcpBuilder<A> {
             sends,
             recvs,
             to_local: HashMap::new(),
+            reassembly: Reassembly::default(),
+            max_record: self.max_record,
         }
     }
 }
@@ -131,12 +147,14 @@ pub struct TcpAllocator<A: Allocate> {
     sends:      Vec<Rc<RefCell<SendEndpoint<MergeQueue>>>>,     // sends[x] -> goes to process x.
     recvs:      Vec<MergeQueue>,                                // recvs[x] <- from process x.
     to_local:   HashMap<usize, Rc<RefCell<VecDeque<Bytes>>>>,   // to worker-local typed pullers.
+    reassembly: Reassembly,                                     // records received in chunks.
+    max_record: Option<usize>,                                  // largest serialized record sent.
 }
 
 impl<A: Allocate> Allocate for TcpAllocator<A> {
     fn index(&self) -> usize { self.index }
     fn peers(&self) -> usize { self.peers }
-    fn allocate<T: Data>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>) {
+    fn allocate_with<T: Any+Send+Sync+'static, C: Codec<T>>(&mut self, identifier: usize) -> (Vec<BoxedPush<T>>, BoxedPull<T>) {
 
         // Assume and enforce in-order identifier allocation.
         if let Some(bound) = self.channel_id_bound {
@@ -149,7 +167,7 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
 
         // Inner exchange allocations.
         let inner_peers = self.inner.peers();
-        let (mut inner_sends, inner_recv) = self.inner.allocate(identifier);
+        let (mut inner_sends, inner_recv) = self.inner.allocate_with::<T, C>(identifier);
 
         for target_index in 0 .. self.peers() {
 
@@ -167,11 +185,12 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
                     target:     target_index,
                     length:     0,
                     seqno:      0,
+                    remaining:  0,
                 };
 
                 // create, box, and stash new process_binary pusher.
                 if process_id > self.index / inner_peers { process_id -= 1; }
-                pushes.push(Box::new(Pusher::new(header, self.sends[process_id].clone())));
+                pushes.push(Box::new(Pusher::<T, C, _>::new(header, self.sends[process_id].clone()).max_record(self.max_record)));
             }
         }
 
@@ -183,7 +202,7 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
 
         use crate::allocator::counters::Puller as CountPuller;
         let canary = Canary::new(identifier, self.canaries.clone());
-        let puller = Box::new(CountPuller::new(PullerInner::new(inner_recv, channel, canary), identifier, self.events().clone()));
+        let puller = Box::new(CountPuller::new(PullerInner::<T, C>::new(inner_recv, channel, canary), identifier, self.events().clone()));
 
         (pushes, puller, )
     }
@@ -227,6 +246,12 @@ impl<A: Allocate> Allocate for TcpAllocator<A> {
                     let mut peel = bytes.extract_to(header.required_bytes());
                     let _ = peel.extract_to(::std::mem::size_of::<MessageHeader>());
 
+                    // Chunks of a record are held back until the record is complete.
+                    let peel = match self.reassembly.accept(&header, peel) {
+                        Some(peel) => peel,
+                        None => continue,
+                    };
+
                     // Increment message count for channel.
                     // Safe to do this even if the channel has been dropped.
                     events.push_back((header.channel, Event::Pushed(1)));
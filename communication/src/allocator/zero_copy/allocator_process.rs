@@ -3,19 +3,25 @@
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::{VecDeque, HashMap, hash_map::Entry};
+use std::sync::Arc;
 use crossbeam_channel::{Sender, Receiver};
 
 use bytes::arc::Bytes;
 
 use crate::networking::MessageHeader;
 
-use crate::{Allocate, Message, Data, Push, Pull};
-use crate::allocator::{AllocateBuilder, Event};
+use std::any::Any;
+
+use crate::{Allocate, Message, Push};
+use crate::codec::Codec;
+use crate::allocator::{AllocateBuilder, Event, BoxedPush, BoxedPull};
 use crate::allocator::canary::Canary;
 
 use super::bytes_exchange::{BytesPull, SendEndpoint, MergeQueue};
+use super::bytes_slab::BytesSlab;
+use super::placement::{BufferAllocator, HeapAllocator};
 
-use super::push_pull::{Pusher, Puller};
+use super::push_pull::{Pusher, Puller, Reassembly};
 
 /// Builds an instance of a ProcessAllocator.
 ///
@@ -28,6 +34,7 @@ pub struct ProcessBuilder {
     peers:  usize,                      // number of peer allocators.
     pushers: Vec<Receiver<MergeQueue>>, // for pushing bytes at other workers.
     pullers: Vec<Sender<MergeQueue>>,   // for pulling bytes from other workers.
+    buffers: Arc<dyn BufferAllocator>,  // for allocating buffers read by other workers.
 }
 
 impl ProcessBuilder {
@@ -35,6 +42,11 @@ impl ProcessBuilder {
     ///
     /// This method requires access to a byte exchanger, from which it mints channels.
     pub fn new_vector(count: usize) -> Vec<ProcessBuilder> {
+        Self::new_vector_in(count, Arc::new(HeapAllocator))
+    }
+
+    /// Creates a vector of builders, whose buffers for each worker are allocated by `buffers`.
+    pub fn new_vector_in(count: usize, buffers: Arc<dyn BufferAllocator>) -> Vec<ProcessBuilder> {
 
         // Channels for the exchange of `MergeQueue` endpoints.
         let (pullers_vec, pushers_vec) = crate::promise_futures(count, count);
@@ -49,6 +61,7 @@ impl ProcessBuilder {
                     peers: count,
                     pushers,
                     pullers,
+                    buffers: buffers.clone(),
                 }
             )
             .collect()
@@ -68,9 +81,9 @@ impl ProcessBuilder {
 
         // Extract pusher commitments.
         let mut sends = Vec::with_capacity(self.peers);
-        for pusher in self.pushers.into_iter() {
+        for (target, pusher) in self.pushers.into_iter().enumerate() {
             let queue = pusher.recv().expect("Failed to receive MergeQueue");
-            let sendpoint = SendEndpoint::new(queue);
+            let sendpoint = SendEndpoint::new_in(queue, BytesSlab::new_in(20, self.buffers.clone(), target));
             sends.push(Rc::new(RefCell::new(sendpoint)));
         }
 
@@ -84,6 +97,7 @@ impl ProcessBuilder {
             sends,
             recvs,
             to_local: HashMap::new(),
+            reassembly: Reassembly::default(),
         }
     }
 }
@@ -114,12 +128,13 @@ pub struct ProcessAllocator {
     sends:      Vec<Rc<RefCell<SendEndpoint<MergeQueue>>>>, // sends[x] -> goes to thread x.
     recvs:      Vec<MergeQueue>,                            // recvs[x] <- from thread x.
     to_local:   HashMap<usize, Rc<RefCell<VecDeque<Bytes>>>>,          // to worker-local typed pullers.
+    reassembly: Reassembly,                                             // records received in chunks.
 }
 
 impl Allocate for ProcessAllocator {
     fn index(&self) -> usize { self.index }
     fn peers(&self) -> usize { self.peers }
-    fn allocate<T: Data>(&mut self, identifier: usize) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>) {
+    fn allocate_with<T: Any+Send+Sync+'static, C: Codec<T>>(&mut self, identifier: usize) -> (Vec<BoxedPush<T>>, BoxedPull<T>) {
 
         // Assume and enforce in-order identifier allocation.
         if let Some(bound) = self.channel_id_bound {
@@ -138,10 +153,11 @@ impl Allocate for ProcessAllocator {
                 target:     target_index,
                 length:     0,
                 seqno:      0,
+                remaining:  0,
             };
 
             // create, box, and stash new process_binary pusher.
-            pushes.push(Box::new(Pusher::new(header, self.sends[target_index].clone())));
+            pushes.push(Box::new(Pusher::<T, C, _>::new(header, self.sends[target_index].clone())));
         }
 
         let channel =
@@ -152,7 +168,7 @@ impl Allocate for ProcessAllocator {
 
         use crate::allocator::counters::Puller as CountPuller;
         let canary = Canary::new(identifier, self.canaries.clone());
-        let puller = Box::new(CountPuller::new(Puller::new(channel, canary), identifier, self.events().clone()));
+        let puller = Box::new(CountPuller::new(Puller::<T, C>::new(channel, canary), identifier, self.events().clone()));
 
         (pushes, puller)
     }
@@ -192,7 +208,13 @@ impl Allocate for ProcessAllocator {
 
                     // Get the header and payload, ditch the header.
                     let mut peel = bytes.extract_to(header.required_bytes());
-                    let _ = peel.extract_to(40);
+                    let _ = peel.extract_to(::std::mem::size_of::<MessageHeader>());
+
+                    // Chunks of a record are held back until the record is complete.
+                    let peel = match self.reassembly.accept(&header, peel) {
+                        Some(peel) => peel,
+                        None => continue,
+                    };
 
                     // Increment message count for channel.
                     // Safe to do this even if the channel has been dropped.
@@ -148,6 +148,13 @@ impl<P: BytesPush> SendEndpoint<P> {
             buffer: BytesSlab::new(20),
         }
     }
+    /// Allocates a new `BytesSendEndpoint` from a shared queue, writing into buffers from `buffer`.
+    pub fn new_in(queue: P, buffer: BytesSlab) -> Self {
+        SendEndpoint {
+            send: queue,
+            buffer,
+        }
+    }
     /// Makes the next `bytes` bytes valid.
     ///
     /// The current implementation also sends the bytes, to ensure early visibility.
@@ -1,7 +1,11 @@
 //! A large binary allocation for writing and sharing.
 
+use std::sync::Arc;
+
 use bytes::arc::Bytes;
 
+use super::placement::{BufferAllocator, HeapAllocator};
+
 /// A large binary allocation for writing and sharing.
 ///
 /// A bytes slab wraps a `Bytes` and maintains a valid (written) length, and supports writing after
@@ -13,17 +17,25 @@ pub struct BytesSlab {
     stash:          Vec<Bytes>,                 // reclaimed and resuable buffers.
     shift:          usize,                      // current buffer allocation size.
     valid:          usize,                      // buffer[..valid] are valid bytes.
+    allocator:      Arc<dyn BufferAllocator>,   // source of new buffers.
+    reader:         usize,                      // worker that reads the buffers.
 }
 
 impl BytesSlab {
     /// Allocates a new `BytesSlab` with an initial size determined by a shift.
     pub fn new(shift: usize) -> Self {
+        Self::new_in(shift, Arc::new(HeapAllocator), 0)
+    }
+    /// Allocates a new `BytesSlab` whose buffers, read by worker `reader`, come from `allocator`.
+    pub fn new_in(shift: usize, allocator: Arc<dyn BufferAllocator>, reader: usize) -> Self {
         BytesSlab {
-            buffer: Bytes::from(vec![0u8; 1 << shift].into_boxed_slice()),
+            buffer: Bytes::from(allocator.allocate(1 << shift, reader)),
             in_progress: Vec::new(),
             stash: Vec::new(),
             shift,
             valid: 0,
+            allocator,
+            reader,
         }
     }
     /// The empty region of the slab.
@@ -82,7 +94,7 @@ impl BytesSlab {
                 self.in_progress.retain(|x| x.is_some());
             }
 
-            let new_buffer = self.stash.pop().unwrap_or_else(|| Bytes::from(vec![0; 1 << self.shift].into_boxed_slice()));
+            let new_buffer = self.stash.pop().unwrap_or_else(|| Bytes::from(self.allocator.allocate(1 << self.shift, self.reader)));
             let old_buffer = ::std::mem::replace(&mut self.buffer, new_buffer);
 
             self.buffer[.. self.valid].copy_from_slice(&old_buffer[.. self.valid]);
@@ -3,7 +3,9 @@
 use std::sync::Arc;
 // use crate::allocator::Process;
 use crate::allocator::process::ProcessBuilder;
-use crate::networking::create_sockets;
+use crate::compression::Compression;
+use crate::networking::{create_sockets_binding, exchange_worker_counts, negotiate_compression, plain_halves, ConnectionOptions, StreamHalves};
+use super::mpi::Communicator;
 use super::tcp::{send_loop, recv_loop};
 use super::allocator::{TcpBuilder, new_vector};
 
@@ -38,11 +40,12 @@ pub fn initialize_networking(
     my_index: usize,
     threads: usize,
     noisy: bool,
+    options: ConnectionOptions,
     log_sender: Box<dyn Fn(CommunicationSetup)->Option<Logger<CommunicationEvent, CommunicationSetup>>+Send+Sync>)
 -> ::std::io::Result<(Vec<TcpBuilder<ProcessBuilder>>, CommsGuard)>
 {
-    let sockets = create_sockets(addresses, my_index, noisy)?;
-    initialize_networking_from_sockets(sockets, my_index, threads, log_sender)
+    let sockets = create_sockets_binding(addresses, options.bind.clone(), my_index, noisy, options.retry)?;
+    initialize_networking_from_sockets_with(sockets, my_index, threads, options, log_sender)
 }
 
 /// Initialize send and recv threads from sockets.
@@ -53,9 +56,28 @@ pub fn initialize_networking(
 /// It is important that the `sockets` argument contain sockets for each remote process, in order, and
 /// with position `my_index` set to `None`.
 pub fn initialize_networking_from_sockets(
+    sockets: Vec<Option<std::net::TcpStream>>,
+    my_index: usize,
+    threads: usize,
+    log_sender: Box<dyn Fn(CommunicationSetup)->Option<Logger<CommunicationEvent, CommunicationSetup>>+Send+Sync>)
+-> ::std::io::Result<(Vec<TcpBuilder<ProcessBuilder>>, CommsGuard)>
+{
+    initialize_networking_from_sockets_with(sockets, my_index, threads, ConnectionOptions::default(), log_sender)
+}
+
+/// Initialize send and recv threads from sockets, using the indicated connection options.
+///
+/// Each connection uses `options.compression` only if the remote process prefers it as well,
+/// and otherwise sends uncompressed data. The preferences are exchanged on the sockets before
+/// any other data, and so all processes must use this method or `initialize_networking_from_sockets`.
+///
+/// If `options.upgrade` is supplied, each socket is first converted by it, and all further
+/// traffic (including the compression preferences) is sent through the resulting halves.
+pub fn initialize_networking_from_sockets_with(
     mut sockets: Vec<Option<std::net::TcpStream>>,
     my_index: usize,
     threads: usize,
+    options: ConnectionOptions,
     log_sender: Box<dyn Fn(CommunicationSetup)->Option<Logger<CommunicationEvent, CommunicationSetup>>+Send+Sync>)
 -> ::std::io::Result<(Vec<TcpBuilder<ProcessBuilder>>, CommsGuard)>
 {
@@ -66,25 +88,72 @@ pub fn initialize_networking_from_sockets(
         }
     }
 
+    let mut streams = Vec::with_capacity(sockets.len());
+    for (index, socket) in sockets.into_iter().enumerate() {
+        streams.push(match (socket, options.upgrade.as_ref()) {
+            (Some(socket), Some(upgrade)) => Some(upgrade(socket, my_index, index)?),
+            (Some(socket), None) => Some(plain_halves(socket)?),
+            (None, _) => None,
+        });
+    }
+
+    initialize_networking_from_streams(streams, my_index, threads, options.compression, options.max_record, log_sender)
+}
+
+/// Initialize send and recv threads over connections carried by MPI messages.
+///
+/// The index of this process is the rank of `communicator`, and the number of processes its size.
+/// All processes must call this method with the same number of threads.
+pub fn initialize_mpi<C: Communicator + ?Sized>(
+    communicator: Arc<C>,
+    threads: usize,
+    compression: Compression,
+    log_sender: Box<dyn Fn(CommunicationSetup)->Option<Logger<CommunicationEvent, CommunicationSetup>>+Send+Sync>)
+-> ::std::io::Result<(Vec<TcpBuilder<ProcessBuilder>>, CommsGuard)>
+{
+    let my_index = communicator.rank();
+    let streams = super::mpi::halves(communicator);
+    initialize_networking_from_streams(streams, my_index, threads, compression, None, log_sender)
+}
+
+/// Initialize send and recv threads from the halves of a connection to each other process.
+///
+/// The `streams` argument must contain halves for each remote process, in order, and with position
+/// `my_index` set to `None`. The numbers of workers and then compression preferences are exchanged
+/// through the halves before any other data, and initialization fails if the numbers differ. If
+/// `max_record` is supplied, workers drop larger serialized records rather than send them.
+pub fn initialize_networking_from_streams(
+    mut streams: Vec<Option<StreamHalves>>,
+    my_index: usize,
+    threads: usize,
+    compression: Compression,
+    max_record: Option<usize>,
+    log_sender: Box<dyn Fn(CommunicationSetup)->Option<Logger<CommunicationEvent, CommunicationSetup>>+Send+Sync>)
+-> ::std::io::Result<(Vec<TcpBuilder<ProcessBuilder>>, CommsGuard)>
+{
+    exchange_worker_counts(&mut streams[..], threads)?;
+    let compressions = negotiate_compression(&mut streams[..], compression)?;
+
     let log_sender = Arc::new(log_sender);
-    let processes = sockets.len();
+    let processes = streams.len();
 
     let process_allocators = crate::allocator::process::Process::new_vector(threads);
     let (builders, promises, futures) = new_vector(process_allocators, my_index, processes);
+    let builders = builders.into_iter().map(|builder| builder.max_record(max_record)).collect::<Vec<_>>();
 
     let mut promises_iter = promises.into_iter();
     let mut futures_iter = futures.into_iter();
 
-    let mut send_guards = Vec::with_capacity(sockets.len());
-    let mut recv_guards = Vec::with_capacity(sockets.len());
+    let mut send_guards = Vec::with_capacity(streams.len());
+    let mut recv_guards = Vec::with_capacity(streams.len());
 
     // for each process, if a stream exists (i.e. not local) ...
-    for (index, stream) in sockets.into_iter().enumerate().filter_map(|(i, s)| s.map(|s| (i, s))) {
+    for (index, (reader, writer)) in streams.into_iter().enumerate().filter_map(|(i, s)| s.map(|s| (i, s))) {
+        let compression = compressions[index];
         let remote_recv = promises_iter.next().unwrap();
 
         {
             let log_sender = log_sender.clone();
-            let stream = stream.try_clone()?;
             let join_guard =
             ::std::thread::Builder::new()
                 .name(format!("timely:send-{}", index))
@@ -96,7 +165,7 @@ pub fn initialize_networking_from_sockets(
                         remote: Some(index),
                     });
 
-                    send_loop(stream, remote_recv, my_index, index, logger);
+                    send_loop(writer, remote_recv, my_index, index, compression, logger);
                 })?;
 
             send_guards.push(join_guard);
@@ -107,7 +176,6 @@ pub fn initialize_networking_from_sockets(
         {
             // let remote_sends = remote_sends.clone();
             let log_sender = log_sender.clone();
-            let stream = stream.try_clone()?;
             let join_guard =
             ::std::thread::Builder::new()
                 .name(format!("timely:recv-{}", index))
@@ -117,7 +185,7 @@ pub fn initialize_networking_from_sockets(
                         sender: false,
                         remote: Some(index),
                     });
-                    recv_loop(stream, remote_send, threads * my_index, my_index, index, logger);
+                    recv_loop(reader, remote_send, threads * my_index, my_index, index, compression, logger);
                 })?;
 
             recv_guards.push(join_guard);
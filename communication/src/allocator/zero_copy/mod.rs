@@ -5,9 +5,13 @@
 //! raw binary data they initial received.
 
 pub mod bytes_slab;
+pub mod placement;
 pub mod bytes_exchange;
 pub mod tcp;
 pub mod allocator;
 pub mod allocator_process;
 pub mod initialize;
-pub mod push_pull;
\ No newline at end of file
+pub mod push_pull;
+pub mod mpi;
+#[cfg(target_os = "linux")]
+pub mod shm;
\ No newline at end of file
@@ -0,0 +1,128 @@
+//! Connections between processes carried by MPI point-to-point messages.
+//!
+//! On clusters managed by HPC schedulers, processes are often launched by `mpirun` and expected
+//! to communicate through MPI rather than through sockets of their own. This module carries the
+//! byte streams between timely processes as MPI messages, so that the zero-copy allocator runs
+//! unchanged over MPI: each process is identified by its rank, and the bytes sent to each other
+//! rank are delivered as a sequence of messages with a dedicated tag.
+//!
+//! Timely does not link against an MPI implementation. Instead, the MPI communicator is supplied
+//! through the [`Communicator`] trait, whose methods correspond to `MPI_Comm_rank`,
+//! `MPI_Comm_size`, `MPI_Send`, and `MPI_Recv`, and which is straightforward to implement with
+//! bindings such as the `mpi` crate. The send and receive threads of each connection call the
+//! communicator concurrently, and so MPI must be initialized with `MPI_THREAD_MULTIPLE`.
+//!
+//! ```ignore
+//! struct World(mpi::topology::SimpleCommunicator);
+//!
+//! impl timely_communication::allocator::zero_copy::mpi::Communicator for World {
+//!     fn rank(&self) -> usize { self.0.rank() as usize }
+//!     fn size(&self) -> usize { self.0.size() as usize }
+//!     fn send(&self, destination: usize, tag: i32, bytes: &[u8]) {
+//!         self.0.process_at_rank(destination as i32).send_with_tag(bytes, tag);
+//!     }
+//!     fn receive(&self, source: usize, tag: i32) -> Vec<u8> {
+//!         self.0.process_at_rank(source as i32).receive_vec_with_tag(tag).0
+//!     }
+//! }
+//! ```
+
+use std::io::{Read, Write, Result};
+use std::sync::Arc;
+
+use crate::networking::{StreamHalves, WriteHalf};
+
+/// The tag of the messages that carry timely's traffic.
+pub const DATA_TAG: i32 = 0x7469;
+
+/// The number of bytes a writer accumulates before sending them as a message.
+pub const MESSAGE_SIZE: usize = 1 << 20;
+
+/// An MPI communicator, through which processes exchange point-to-point messages.
+pub trait Communicator: Send + Sync + 'static {
+    /// The rank of this process, which is its timely process index.
+    fn rank(&self) -> usize;
+    /// The number of processes.
+    fn size(&self) -> usize;
+    /// Sends `bytes` to the process at rank `destination`, with `tag`.
+    fn send(&self, destination: usize, tag: i32, bytes: &[u8]);
+    /// Receives the next message from the process at rank `source` with `tag`, blocking until it arrives.
+    fn receive(&self, source: usize, tag: i32) -> Vec<u8>;
+}
+
+/// Produces the halves of a connection to each other process, with `None` for this process.
+///
+/// Messages from one process to another must be received in the order they were sent, which
+/// MPI guarantees for messages with the same source, destination, tag, and communicator.
+pub fn halves<C: Communicator + ?Sized>(communicator: Arc<C>) -> Vec<Option<StreamHalves>> {
+    let rank = communicator.rank();
+    (0 .. communicator.size()).map(|remote| {
+        if remote == rank {
+            None
+        }
+        else {
+            let reader = MpiReader { communicator: communicator.clone(), source: remote, message: Vec::new(), offset: 0, closed: false };
+            let writer = MpiWriter { communicator: communicator.clone(), destination: remote, buffer: Vec::new() };
+            Some((Box::new(reader) as Box<dyn Read + Send>, Box::new(writer) as Box<dyn WriteHalf>))
+        }
+    }).collect()
+}
+
+/// Reads the bytes sent by another process, one message at a time.
+struct MpiReader<C: Communicator + ?Sized> {
+    communicator: Arc<C>,
+    source: usize,
+    message: Vec<u8>,
+    offset: usize,
+    // An empty message indicates the end of the stream.
+    closed: bool,
+}
+
+impl<C: Communicator + ?Sized> Read for MpiReader<C> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        while self.offset == self.message.len() && !self.closed {
+            self.message = self.communicator.receive(self.source, DATA_TAG);
+            self.offset = 0;
+            self.closed = self.message.is_empty();
+        }
+        let length = std::cmp::min(buf.len(), self.message.len() - self.offset);
+        buf[.. length].copy_from_slice(&self.message[self.offset .. self.offset + length]);
+        self.offset += length;
+        Ok(length)
+    }
+}
+
+/// Sends bytes to another process, accumulating them into messages of up to `MESSAGE_SIZE` bytes.
+struct MpiWriter<C: Communicator + ?Sized> {
+    communicator: Arc<C>,
+    destination: usize,
+    buffer: Vec<u8>,
+}
+
+impl<C: Communicator + ?Sized> Write for MpiWriter<C> {
+    fn write(&mut self, bytes: &[u8]) -> Result<usize> {
+        self.buffer.extend_from_slice(bytes);
+        if self.buffer.len() >= MESSAGE_SIZE {
+            self.flush()?;
+        }
+        Ok(bytes.len())
+    }
+    fn flush(&mut self) -> Result<()> {
+        if !self.buffer.is_empty() {
+            self.communicator.send(self.destination, DATA_TAG, &self.buffer[..]);
+            self.buffer.clear();
+        }
+        Ok(())
+    }
+}
+
+impl<C: Communicator + ?Sized> WriteHalf for MpiWriter<C> {
+    fn shutdown(&mut self) -> Result<()> {
+        self.flush()?;
+        self.communicator.send(self.destination, DATA_TAG, &[]);
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+//! Placement of the buffers into which workers serialize data.
+//!
+//! A worker serializes the data it sends to each other worker into buffers it allocates, and the
+//! receiving worker reads the data from these buffers in place. By default the buffers are taken
+//! from the heap, and reside wherever the operating system first places their pages; on machines
+//! with several NUMA nodes this is usually the node of the sending worker.
+//!
+//! A [`BufferAllocator`] is asked for each buffer along with the index of the worker that will
+//! read it. The [`NumaPolicy`] allocator asks the operating system to place each buffer on the
+//! NUMA node of its reader, and counts the buffers it placed so that the placement can be checked.
+//! NUMA placement is only available on Linux; elsewhere the policy allocates from the heap and
+//! counts each buffer as failed.
+//!
+//! Buffers are only allocated as channels are created and as their volume grows, and are recycled
+//! once their readers are done with them, so allocation is not on the path of each message.
+//!
+//! # Examples
+//! ```
+//! use std::sync::Arc;
+//! use timely_communication::{Allocate, Config};
+//! use timely_communication::allocator::zero_copy::placement::NumaPolicy;
+//!
+//! // Place the buffers of all workers on node zero, which exists on every machine.
+//! let policy = Arc::new(NumaPolicy::new(|_worker| 0));
+//! let config = Config::ProcessBinary { threads: 2, buffers: policy.clone() };
+//!
+//! let guards = timely_communication::initialize(config, |mut allocator| {
+//!     let (mut senders, mut receiver) = allocator.allocate::<u64>(0);
+//!     for sender in senders.iter_mut() {
+//!         sender.send(timely_communication::Message::from_typed(allocator.index() as u64));
+//!         sender.done();
+//!     }
+//!     let mut received = 0;
+//!     while received < allocator.peers() {
+//!         allocator.receive();
+//!         if receiver.recv().is_some() { received += 1; }
+//!         allocator.release();
+//!     }
+//! }).unwrap();
+//! guards.join().into_iter().for_each(|result| result.unwrap());
+//!
+//! // Each worker allocated a buffer for each reader.
+//! let placement = policy.placement();
+//! assert!(placement[&0].buffers >= 4);
+//! if cfg!(target_os = "linux") {
+//!     assert_eq!(placement[&0].resident + placement[&0].failed, placement[&0].buffers);
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Allocates the buffers into which data are serialized for a worker.
+pub trait BufferAllocator: Send + Sync {
+    /// Allocates a zeroed buffer of `bytes` bytes, to be read by worker `reader`.
+    fn allocate(&self, bytes: usize, reader: usize) -> Box<[u8]>;
+}
+
+/// Allocates buffers from the heap, without regard for their readers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeapAllocator;
+
+impl BufferAllocator for HeapAllocator {
+    fn allocate(&self, bytes: usize, _reader: usize) -> Box<[u8]> {
+        vec![0u8; bytes].into_boxed_slice()
+    }
+}
+
+/// Counts of the buffers a [`NumaPolicy`] placed on one NUMA node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NodePlacement {
+    /// The number of buffers allocated for readers on the node.
+    pub buffers: u64,
+    /// The number of bytes in these buffers.
+    pub bytes: u64,
+    /// The number of these buffers the operating system reports as resident on the node.
+    pub resident: u64,
+    /// The number of these buffers that could not be bound to the node.
+    pub failed: u64,
+}
+
+/// Places each buffer on the NUMA node of the worker that reads it.
+///
+/// The node of each worker is determined by a supplied function of the worker index. The policy
+/// prefers rather than requires the node, so buffers are placed elsewhere if the node is out of
+/// memory. Buffers that cannot be bound, for example because the node does not exist, are still
+/// allocated, and are counted as failed.
+pub struct NumaPolicy {
+    node_of: Arc<dyn Fn(usize)->usize+Send+Sync>,
+    placement: Mutex<BTreeMap<usize, NodePlacement>>,
+}
+
+impl NumaPolicy {
+    /// Creates a policy placing the buffers read by worker `index` on node `node_of(index)`.
+    pub fn new<F: Fn(usize)->usize+Send+Sync+'static>(node_of: F) -> Self {
+        NumaPolicy {
+            node_of: Arc::new(node_of),
+            placement: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Creates a policy assigning workers to `nodes` nodes in turn.
+    ///
+    /// This matches workers whose threads are each pinned to a core, when consecutive cores are
+    /// spread across the nodes.
+    pub fn round_robin(nodes: usize) -> Self {
+        assert!(nodes > 0, "round_robin requires at least one node");
+        Self::new(move |index| index % nodes)
+    }
+
+    /// The counts of placed buffers, by node.
+    pub fn placement(&self) -> BTreeMap<usize, NodePlacement> {
+        self.placement.lock().expect("placement counts poisoned").clone()
+    }
+}
+
+impl BufferAllocator for NumaPolicy {
+    fn allocate(&self, bytes: usize, reader: usize) -> Box<[u8]> {
+        let node = (self.node_of)(reader);
+        let mut buffer = vec![0u8; bytes].into_boxed_slice();
+        let resident = bind(&mut buffer, node);
+        let mut placement = self.placement.lock().expect("placement counts poisoned");
+        let counts = placement.entry(node).or_default();
+        counts.buffers += 1;
+        counts.bytes += bytes as u64;
+        match resident {
+            Ok(on_node) => if on_node == Some(node) { counts.resident += 1; },
+            Err(_) => counts.failed += 1,
+        }
+        buffer
+    }
+}
+
+impl std::fmt::Debug for NumaPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NumaPolicy").field("placement", &self.placement()).finish()
+    }
+}
+
+// Memory policy constants, from `linux/mempolicy.h`.
+#[cfg(target_os = "linux")]
+const MPOL_PREFERRED: libc::c_int = 1;
+#[cfg(target_os = "linux")]
+const MPOL_MF_MOVE: libc::c_uint = 1 << 1;
+#[cfg(target_os = "linux")]
+const MPOL_F_NODE: libc::c_ulong = 1 << 0;
+#[cfg(target_os = "linux")]
+const MPOL_F_ADDR: libc::c_ulong = 1 << 1;
+
+/// Binds the whole pages of `buffer` to `node`, and reports the node on which they reside.
+///
+/// Pages the buffer shares with other allocations are left alone, and if the buffer contains no
+/// whole pages there is nothing to bind, and the resident node is `None`.
+#[cfg(target_os = "linux")]
+fn bind(buffer: &mut [u8], node: usize) -> std::io::Result<Option<usize>> {
+
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let address = buffer.as_mut_ptr() as usize;
+    let start = (address + page - 1) & !(page - 1);
+    let end = (address + buffer.len()) & !(page - 1);
+    if end <= start { return Ok(None); }
+
+    let bits = 8 * std::mem::size_of::<libc::c_ulong>();
+    let mut mask = vec![0 as libc::c_ulong; node / bits + 1];
+    mask[node / bits] |= 1 << (node % bits);
+
+    // The kernel reads one bit fewer than `maxnode`, hence the extra bit.
+    let result = unsafe {
+        libc::syscall(libc::SYS_mbind, start, end - start, MPOL_PREFERRED, mask.as_ptr(), mask.len() * bits + 1, MPOL_MF_MOVE)
+    };
+    if result != 0 { return Err(std::io::Error::last_os_error()); }
+
+    // Fault in the first page, in order to ask where it resides.
+    unsafe { std::ptr::write_volatile(start as *mut u8, 0); }
+    let mut resident: libc::c_int = -1;
+    let result = unsafe {
+        libc::syscall(libc::SYS_get_mempolicy, &mut resident as *mut libc::c_int, std::ptr::null_mut::<libc::c_ulong>(), 0, start, MPOL_F_NODE | MPOL_F_ADDR)
+    };
+    if result != 0 { return Err(std::io::Error::last_os_error()); }
+    Ok(Some(resident as usize))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn bind(_buffer: &mut [u8], _node: usize) -> std::io::Result<Option<usize>> {
+    Err(std::io::Error::new(std::io::ErrorKind::Other, "NUMA placement is only available on Linux"))
+}
@@ -2,60 +2,143 @@
 
 use std::rc::Rc;
 use std::cell::RefCell;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
 
 use bytes::arc::Bytes;
 
 use crate::allocator::canary::Canary;
 use crate::networking::MessageHeader;
 
-use crate::{Data, Push, Pull};
+use crate::{Push, Pull};
 use crate::allocator::Message;
+use crate::codec::Codec;
 
 use super::bytes_exchange::{BytesPush, SendEndpoint};
 
+/// The largest message, including its header, that a pusher writes into a shared buffer.
+///
+/// Records whose messages would be larger are sent in chunks of at most this size, and
+/// reassembled by the receiving allocator, so that buffers do not grow to fit them.
+pub const MAX_MESSAGE_BYTES: usize = 1 << 20;
+
+const HEADER_BYTES: usize = ::std::mem::size_of::<MessageHeader>();
+
 /// An adapter into which one may push elements of type `T`.
 ///
 /// This pusher has a fixed MessageHeader, and access to a SharedByteBuffer which it uses to
-/// acquire buffers for serialization.
-pub struct Pusher<T, P: BytesPush> {
+/// acquire buffers for serialization. Elements are serialized using the codec `C`.
+pub struct Pusher<T, C, P: BytesPush> {
     header:     MessageHeader,
     sender:     Rc<RefCell<SendEndpoint<P>>>,
-    phantom:    ::std::marker::PhantomData<T>,
+    max_record: Option<usize>,
+    chunk:      Vec<u8>,
+    phantom:    ::std::marker::PhantomData<(T, C)>,
 }
 
-impl<T, P: BytesPush> Pusher<T, P> {
+impl<T, C, P: BytesPush> Pusher<T, C, P> {
     /// Creates a new `Pusher` from a header and shared byte buffer.
-    pub fn new(header: MessageHeader, sender: Rc<RefCell<SendEndpoint<P>>>) -> Pusher<T, P> {
+    pub fn new(header: MessageHeader, sender: Rc<RefCell<SendEndpoint<P>>>) -> Pusher<T, C, P> {
         Pusher {
             header,
             sender,
+            max_record: None,
+            chunk:      Vec::new(),
             phantom:    ::std::marker::PhantomData,
         }
     }
+    /// Limits the serialized size of each record to `max_record` bytes, if supplied.
+    ///
+    /// A larger record is dropped rather than sent, and reported on standard error with its
+    /// channel and the sizes involved.
+    pub fn max_record(mut self, max_record: Option<usize>) -> Self {
+        self.max_record = max_record;
+        self
+    }
 }
 
-impl<T:Data, P: BytesPush> Push<Message<T>> for Pusher<T, P> {
+impl<T, C: Codec<T>, P: BytesPush> Push<Message<T>> for Pusher<T, C, P> {
     #[inline]
     fn push(&mut self, element: &mut Option<Message<T>>) {
         if let Some(ref mut element) = *element {
 
             // determine byte lengths and build header.
             let mut header = self.header;
-            self.header.seqno += 1;
-            header.length = element.length_in_bytes();
+            header.length = C::length_in_bytes(element);
             assert!(header.length > 0);
 
-            // acquire byte buffer and write header, element.
+            if let Some(limit) = self.max_record {
+                if header.length > limit {
+                    eprintln!("timely: dropped record of {} bytes on channel {}, which exceeds the limit of {} bytes", header.length, header.channel, limit);
+                    return;
+                }
+            }
+
             let mut borrow = self.sender.borrow_mut();
-            {
-                let mut bytes = borrow.reserve(header.required_bytes());
-                assert!(bytes.len() >= header.required_bytes());
-                let writer = &mut bytes;
-                header.write_to(writer).expect("failed to write header!");
-                element.into_bytes(writer);
+            if header.required_bytes() <= MAX_MESSAGE_BYTES {
+                self.header.seqno += 1;
+                // acquire byte buffer and write header, element.
+                {
+                    let mut bytes = borrow.reserve(header.required_bytes());
+                    assert!(bytes.len() >= header.required_bytes());
+                    let writer = &mut bytes;
+                    header.write_to(writer).expect("failed to write header!");
+                    C::into_bytes(element, writer);
+                }
+                borrow.make_valid(header.required_bytes());
+            }
+            else {
+                // serialize the record once, and send it in chunks that each fit a message.
+                self.chunk.clear();
+                self.chunk.reserve(header.length);
+                C::into_bytes(element, &mut self.chunk);
+                let chunk_bytes = MAX_MESSAGE_BYTES - HEADER_BYTES;
+                let chunks = self.chunk.len().div_ceil(chunk_bytes);
+                for (index, payload) in self.chunk.chunks(chunk_bytes).enumerate() {
+                    let mut header = self.header;
+                    self.header.seqno += 1;
+                    header.length = payload.len();
+                    header.remaining = chunks - index - 1;
+                    {
+                        let mut bytes = borrow.reserve(header.required_bytes());
+                        let writer = &mut bytes;
+                        header.write_to(writer).expect("failed to write header!");
+                        writer.write_all(payload).expect("failed to write chunk!");
+                    }
+                    borrow.make_valid(header.required_bytes());
+                }
+                // retain only a modest allocation between oversized records.
+                self.chunk.clear();
+                self.chunk.shrink_to(MAX_MESSAGE_BYTES);
             }
-            borrow.make_valid(header.required_bytes());
+        }
+    }
+}
+
+/// Reassembles the payloads of records that were sent in chunks.
+///
+/// Allocators receive the messages of each channel and source in the order they were sent, and
+/// present each message here along with its header. Complete payloads are returned at once, and
+/// chunks are accumulated until their record is complete.
+#[derive(Default)]
+pub struct Reassembly {
+    partial: HashMap<(usize, usize), Vec<u8>>,
+}
+
+impl Reassembly {
+    /// Accepts the payload of a message, and returns the payload of a record if one is complete.
+    pub fn accept(&mut self, header: &MessageHeader, payload: Bytes) -> Option<Bytes> {
+        let key = (header.channel, header.source);
+        if header.remaining == 0 && !self.partial.contains_key(&key) {
+            return Some(payload);
+        }
+        let partial = self.partial.entry(key).or_insert_with(|| Vec::with_capacity((header.remaining + 1) * header.length));
+        partial.extend_from_slice(&payload[..]);
+        if header.remaining == 0 {
+            self.partial.remove(&key).map(Bytes::from)
+        }
+        else {
+            None
         }
     }
 }
@@ -66,31 +149,33 @@ impl<T:Data, P: BytesPush> Push<Message<T>> for Pusher<T, P> {
 /// not the most efficient thing possible, which would probably instead be something
 /// like the `bytes` crate (../bytes/) which provides an exclusive view of a shared
 /// allocation.
-pub struct Puller<T> {
+pub struct Puller<T, C> {
     _canary: Canary,
     current: Option<Message<T>>,
     receiver: Rc<RefCell<VecDeque<Bytes>>>,    // source of serialized buffers
+    phantom: ::std::marker::PhantomData<C>,
 }
 
-impl<T:Data> Puller<T> {
+impl<T, C: Codec<T>> Puller<T, C> {
     /// Creates a new `Puller` instance from a shared queue.
-    pub fn new(receiver: Rc<RefCell<VecDeque<Bytes>>>, _canary: Canary) -> Puller<T> {
+    pub fn new(receiver: Rc<RefCell<VecDeque<Bytes>>>, _canary: Canary) -> Puller<T, C> {
         Puller {
             _canary,
             current: None,
             receiver,
+            phantom: ::std::marker::PhantomData,
         }
     }
 }
 
-impl<T:Data> Pull<Message<T>> for Puller<T> {
+impl<T, C: Codec<T>> Pull<Message<T>> for Puller<T, C> {
     #[inline]
     fn pull(&mut self) -> &mut Option<Message<T>> {
         self.current =
         self.receiver
             .borrow_mut()
             .pop_front()
-            .map(|bytes| unsafe { Message::from_bytes(bytes) });
+            .map(C::from_bytes);
 
         &mut self.current
     }
@@ -102,14 +187,15 @@ impl<T:Data> Pull<Message<T>> for Puller<T> {
 /// not the most efficient thing possible, which would probably instead be something
 /// like the `bytes` crate (../bytes/) which provides an exclusive view of a shared
 /// allocation.
-pub struct PullerInner<T> {
+pub struct PullerInner<T, C> {
     inner: Box<dyn Pull<Message<T>>>,               // inner pullable (e.g. intra-process typed queue)
     _canary: Canary,
     current: Option<Message<T>>,
     receiver: Rc<RefCell<VecDeque<Bytes>>>,     // source of serialized buffers
+    phantom: ::std::marker::PhantomData<C>,
 }
 
-impl<T:Data> PullerInner<T> {
+impl<T, C: Codec<T>> PullerInner<T, C> {
     /// Creates a new `PullerInner` instance from a shared queue.
     pub fn new(inner: Box<dyn Pull<Message<T>>>, receiver: Rc<RefCell<VecDeque<Bytes>>>, _canary: Canary) -> Self {
         PullerInner {
@@ -117,11 +203,12 @@ impl<T:Data> PullerInner<T> {
             _canary,
             current: None,
             receiver,
+            phantom: ::std::marker::PhantomData,
         }
     }
 }
 
-impl<T:Data> Pull<Message<T>> for PullerInner<T> {
+impl<T, C: Codec<T>> Pull<Message<T>> for PullerInner<T, C> {
     #[inline]
     fn pull(&mut self) -> &mut Option<Message<T>> {
 
@@ -134,7 +221,7 @@ impl<T:Data> Pull<Message<T>> for PullerInner<T> {
             self.receiver
                 .borrow_mut()
                 .pop_front()
-                .map(|bytes| unsafe { Message::from_bytes(bytes) });
+                .map(C::from_bytes);
 
             &mut self.current
         }
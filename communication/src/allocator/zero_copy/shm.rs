@@ -0,0 +1,283 @@
+//! Shared-memory connections between processes on the same machine.
+//!
+//! Processes that find their peer on the same host replace the TCP connection between them
+//! with a pair of memory-mapped ring buffers, one for each direction. The TCP connection is
+//! used only to exchange the names of the backing files; afterwards bytes move between the
+//! processes by copying into and out of the shared mapping, and a process only enters the
+//! kernel (through a futex) when it must wait for its peer.
+//!
+//! Connections to other machines, and connections for which the shared mapping cannot be
+//! established, fall back to whatever the TCP connection would otherwise have used.
+//!
+//! Unlike a socket, a ring buffer does not observe the failure of the remote process. A
+//! process that stops draining its rings will cause its peer to block rather than to fail.
+
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write, Result, Error, ErrorKind};
+use std::net::TcpStream;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+
+use crate::networking::{plain_halves, StreamHalves, StreamUpgrade, WriteHalf};
+
+/// Number of data bytes in each ring buffer.
+pub const RING_CAPACITY: usize = 1 << 22;
+
+/// Produces a connection upgrade that uses shared memory for co-located peers.
+///
+/// Connections to peers on other hosts, or for which shared memory could not be set up, are
+/// passed to `fallback`, or used as plain TCP connections if it is `None`.
+pub fn upgrade(fallback: Option<Box<StreamUpgrade>>) -> Box<StreamUpgrade> {
+    Box::new(move |socket: TcpStream, my_index: usize, remote: usize| {
+        let local = socket.peer_addr()?.ip() == socket.local_addr()?.ip();
+        let halves = if local { connect(&socket, my_index, remote)? } else { None };
+        match (halves, fallback.as_ref()) {
+            (Some(halves), _) => Ok(halves),
+            (None, Some(fallback)) => fallback(socket, my_index, remote),
+            (None, None) => plain_halves(socket),
+        }
+    })
+}
+
+/// Exchanges ring buffers over `socket`, returning `None` if either process failed.
+fn connect(socket: &TcpStream, my_index: usize, remote: usize) -> Result<Option<StreamHalves>> {
+
+    let mut socket = socket;
+
+    let directory = if Path::new("/dev/shm").is_dir() { PathBuf::from("/dev/shm") } else { std::env::temp_dir() };
+    let nonce = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let path = directory.join(format!("timely-{}-{}-{}-{}", std::process::id(), my_index, remote, nonce));
+
+    let outgoing = Ring::create(&path)?;
+
+    // Announce our ring, and attempt to map the remote ring.
+    let name = path.to_string_lossy().into_owned().into_bytes();
+    socket.write_all(&(name.len() as u64).to_le_bytes())?;
+    socket.write_all(&name[..])?;
+
+    let mut length = [0u8; 8];
+    socket.read_exact(&mut length)?;
+    let mut name = vec![0u8; u64::from_le_bytes(length) as usize];
+    socket.read_exact(&mut name[..])?;
+    let incoming = String::from_utf8(name).ok().and_then(|name| Ring::open(Path::new(&name)).ok());
+
+    // Confirm success with each other, after which the files are no longer needed.
+    socket.write_all(&[incoming.is_some() as u8])?;
+    let mut confirm = [0u8; 1];
+    socket.read_exact(&mut confirm)?;
+    std::fs::remove_file(&path)?;
+
+    match incoming {
+        Some(incoming) if confirm[0] == 1 => {
+            Ok(Some((Box::new(RingReader { ring: incoming }), Box::new(RingWriter { ring: outgoing }))))
+        },
+        _ => Ok(None),
+    }
+}
+
+/// Aligns its contents to a cache line, so that the two processes do not contend.
+#[repr(C, align(64))]
+struct Padded<T>(T);
+
+/// Coordination state at the start of each mapping.
+///
+/// The file is created zero-filled, which is a valid initial state for each field.
+#[repr(C)]
+struct RingHeader {
+    /// Total bytes ever written; advanced only by the writer.
+    written: Padded<AtomicU64>,
+    /// Total bytes ever read; advanced only by the reader.
+    read: Padded<AtomicU64>,
+    /// Incremented when data is written, and waited on by the reader.
+    data_seq: Padded<AtomicU32>,
+    /// Non-zero while the reader may be waiting on `data_seq`.
+    reader_waiting: Padded<AtomicU32>,
+    /// Incremented when data is read, and waited on by the writer.
+    space_seq: Padded<AtomicU32>,
+    /// Non-zero while the writer may be waiting on `space_seq`.
+    writer_waiting: Padded<AtomicU32>,
+    /// Non-zero once the writer has shut down.
+    closed: Padded<AtomicU32>,
+}
+
+const HEADER_BYTES: usize = std::mem::size_of::<RingHeader>();
+
+/// A memory-mapped ring buffer with one writing and one reading process.
+struct Ring {
+    base: *mut u8,
+}
+
+// The mapping is shared memory, and all coordination happens through atomics in the header.
+unsafe impl Send for Ring { }
+
+impl Ring {
+    fn create(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).create_new(true).open(path)?;
+        file.set_len((HEADER_BYTES + RING_CAPACITY) as u64)?;
+        Ring::map(&file)
+    }
+
+    fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        if file.metadata()?.len() != (HEADER_BYTES + RING_CAPACITY) as u64 {
+            return Err(Error::new(ErrorKind::InvalidData, "shared memory ring has unexpected size"));
+        }
+        Ring::map(&file)
+    }
+
+    fn map(file: &File) -> Result<Self> {
+        let base = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                HEADER_BYTES + RING_CAPACITY,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if base == libc::MAP_FAILED {
+            return Err(Error::last_os_error());
+        }
+        Ok(Ring { base: base as *mut u8 })
+    }
+
+    fn header(&self) -> &RingHeader {
+        unsafe { &*(self.base as *const RingHeader) }
+    }
+
+    /// Copies `bytes` into the ring starting at logical position `position`.
+    fn copy_in(&self, position: u64, bytes: &[u8]) {
+        let offset = (position as usize) % RING_CAPACITY;
+        let first = bytes.len().min(RING_CAPACITY - offset);
+        unsafe {
+            let data = self.base.add(HEADER_BYTES);
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), data.add(offset), first);
+            std::ptr::copy_nonoverlapping(bytes[first..].as_ptr(), data, bytes.len() - first);
+        }
+    }
+
+    /// Copies bytes out of the ring starting at logical position `position`.
+    fn copy_out(&self, position: u64, bytes: &mut [u8]) {
+        let offset = (position as usize) % RING_CAPACITY;
+        let first = bytes.len().min(RING_CAPACITY - offset);
+        unsafe {
+            let data = self.base.add(HEADER_BYTES);
+            std::ptr::copy_nonoverlapping(data.add(offset), bytes.as_mut_ptr(), first);
+            std::ptr::copy_nonoverlapping(data, bytes[first..].as_mut_ptr(), bytes.len() - first);
+        }
+    }
+}
+
+impl Drop for Ring {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.base as *mut libc::c_void, HEADER_BYTES + RING_CAPACITY); }
+    }
+}
+
+/// Waits on `seq` until `ready` holds, announcing the wait through `waiting`.
+///
+/// The wait is bounded, and callers are expected to re-test their condition in a loop.
+fn wait(seq: &AtomicU32, waiting: &AtomicU32, ready: impl Fn() -> bool) {
+    let observed = seq.load(Ordering::SeqCst);
+    waiting.store(1, Ordering::SeqCst);
+    if !ready() {
+        let timeout = libc::timespec { tv_sec: 0, tv_nsec: 100_000_000 };
+        unsafe {
+            libc::syscall(libc::SYS_futex, seq as *const AtomicU32, libc::FUTEX_WAIT, observed, &timeout as *const libc::timespec);
+        }
+    }
+    waiting.store(0, Ordering::SeqCst);
+}
+
+/// Advances `seq`, and wakes a waiter if `waiting` indicates there may be one.
+fn notify(seq: &AtomicU32, waiting: &AtomicU32) {
+    seq.fetch_add(1, Ordering::SeqCst);
+    if waiting.load(Ordering::SeqCst) != 0 {
+        unsafe {
+            libc::syscall(libc::SYS_futex, seq as *const AtomicU32, libc::FUTEX_WAKE, i32::MAX);
+        }
+    }
+}
+
+/// The reading half of a shared-memory connection.
+struct RingReader {
+    ring: Ring,
+}
+
+impl Read for RingReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let header = self.ring.header();
+        let read = header.read.0.load(Ordering::Relaxed);
+        loop {
+            let closed = header.closed.0.load(Ordering::SeqCst) != 0;
+            let available = (header.written.0.load(Ordering::SeqCst) - read) as usize;
+            if available > 0 {
+                let count = available.min(buf.len());
+                self.ring.copy_out(read, &mut buf[.. count]);
+                header.read.0.store(read + count as u64, Ordering::SeqCst);
+                notify(&header.space_seq.0, &header.writer_waiting.0);
+                return Ok(count);
+            }
+            if closed {
+                return Ok(0);
+            }
+            wait(&header.data_seq.0, &header.reader_waiting.0, || {
+                header.written.0.load(Ordering::SeqCst) > read || header.closed.0.load(Ordering::SeqCst) != 0
+            });
+        }
+    }
+}
+
+/// The writing half of a shared-memory connection.
+struct RingWriter {
+    ring: Ring,
+}
+
+impl Write for RingWriter {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let header = self.ring.header();
+        let written = header.written.0.load(Ordering::Relaxed);
+        loop {
+            let space = RING_CAPACITY - (written - header.read.0.load(Ordering::SeqCst)) as usize;
+            if space > 0 {
+                let count = space.min(buf.len());
+                self.ring.copy_in(written, &buf[.. count]);
+                header.written.0.store(written + count as u64, Ordering::SeqCst);
+                notify(&header.data_seq.0, &header.reader_waiting.0);
+                return Ok(count);
+            }
+            wait(&header.space_seq.0, &header.writer_waiting.0, || {
+                written - header.read.0.load(Ordering::SeqCst) < RING_CAPACITY as u64
+            });
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl WriteHalf for RingWriter {
+    fn shutdown(&mut self) -> Result<()> {
+        let header = self.ring.header();
+        header.closed.0.store(1, Ordering::SeqCst);
+        notify(&header.data_seq.0, &header.reader_waiting.0);
+        Ok(())
+    }
+}
+
+impl Drop for RingWriter {
+    fn drop(&mut self) {
+        // Unblock the reader even if we did not shut down cleanly; it will observe the
+        // end of the stream and react as it would to a closed socket.
+        let _ = self.shutdown();
+    }
+}
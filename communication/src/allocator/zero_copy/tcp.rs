@@ -1,30 +1,39 @@
 //!
 
-use std::io::{Read, Write};
-use std::net::TcpStream;
+use std::collections::HashMap;
+use std::io::{Read, Write, BufReader};
 use crossbeam_channel::{Sender, Receiver};
 
-use crate::networking::MessageHeader;
+use abomonation::decode;
+
+use crate::compression::Compression;
+use crate::networking::{MessageHeader, WriteHalf};
 
 use super::bytes_slab::BytesSlab;
 use super::bytes_exchange::MergeQueue;
 
 use logging_core::Logger;
 
-use crate::logging::{CommunicationEvent, CommunicationSetup, MessageEvent, StateEvent};
+use crate::logging::{CommunicationEvent, CommunicationSetup, MessageEvent, StateEvent, CompressionEvent, PeerFailedEvent};
+
+const HEADER_BYTES: usize = ::std::mem::size_of::<MessageHeader>();
 
-/// Repeatedly reads from a TcpStream and carves out messages.
+/// Repeatedly reads from a connection and carves out messages.
 ///
 /// The intended communication pattern is a sequence of (header, message)^* for valid
 /// messages, followed by a header for a zero length message indicating the end of stream.
 /// If the stream ends without being shut down, the receive thread panics in an attempt to
 /// take down the computation and cause the failures to cascade.
-pub fn recv_loop(
-    mut reader: TcpStream,
+///
+/// If `compression` is other than `Compression::None`, each header is instead followed by
+/// the length of the transmitted payload and the possibly compressed payload itself.
+pub fn recv_loop<R: Read>(
+    mut reader: R,
     targets: Vec<Receiver<MergeQueue>>,
     worker_offset: usize,
     process: usize,
     remote: usize,
+    compression: Compression,
     mut logger: Option<Logger<CommunicationEvent, CommunicationSetup>>)
 {
     // Log the receive thread's start.
@@ -32,7 +41,16 @@ pub fn recv_loop(
 
     let mut targets: Vec<MergeQueue> = targets.into_iter().map(|x| x.recv().expect("Failed to receive MergeQueue")).collect();
 
+    if compression != Compression::None {
+        recv_compressed(reader, &mut targets, worker_offset, process, remote, compression, &mut logger);
+        if let Some(logger) = logger.as_mut() {
+            logger.log(StateEvent { send: false, process, remote, start: false, });
+        }
+        return;
+    }
+
     let mut buffer = BytesSlab::new(20);
+    let mut sequences = Sequences::default();
 
     // Where we stash Bytes before handing them off.
     let mut stageds = Vec::with_capacity(targets.len());
@@ -64,7 +82,9 @@ pub fn recv_loop(
             },
         };
 
-        assert!(read > 0);
+        if read == 0 {
+            peer_failed(&mut logger, process, remote, "connection closed without shutdown");
+        }
         buffer.make_valid(read);
 
         // Consume complete messages from the front of self.buffer.
@@ -80,6 +100,7 @@ pub fn recv_loop(
             });
 
             if header.length > 0 {
+                sequences.check(&header, &mut logger, process, remote);
                 stageds[header.target - worker_offset].push(bytes);
             }
             else {
@@ -107,16 +128,169 @@ pub fn recv_loop(
     logger.as_mut().map(|l| l.log(StateEvent { send: false, process, remote, start: false, }));
 }
 
-/// Repeatedly sends messages into a TcpStream.
+/// Reads compressed messages from a connection, and hands them off decompressed.
+///
+/// Unlike the uncompressed case, message boundaries must be found before the payload can be
+/// decompressed, and so messages are read one at a time through a buffered reader.
+fn recv_compressed<R: Read>(
+    reader: R,
+    targets: &mut [MergeQueue],
+    worker_offset: usize,
+    process: usize,
+    remote: usize,
+    compression: Compression,
+    logger: &mut Option<Logger<CommunicationEvent, CommunicationSetup>>)
+{
+    use crate::allocator::zero_copy::bytes_exchange::BytesPush;
+
+    let mut reader = BufReader::with_capacity(1 << 16, reader);
+    let mut buffer = BytesSlab::new(20);
+    let mut received = Vec::new();
+    let mut totals = HashMap::new();
+    let mut sequences = Sequences::default();
+
+    let mut stageds = Vec::with_capacity(targets.len());
+    for _ in 0 .. targets.len() {
+        stageds.push(Vec::new());
+    }
+
+    loop {
+        let mut header_bytes = [0u8; HEADER_BYTES];
+        if reader.read_exact(&mut header_bytes).is_err() {
+            peer_failed(logger, process, remote, "connection closed without shutdown");
+        }
+        let header = *unsafe { decode::<MessageHeader>(&mut header_bytes) }.expect("Failed to decode message header").0;
+
+        // Record message receipt.
+        if let Some(logger) = logger.as_mut() {
+            logger.log(MessageEvent { is_send: false, header, });
+        }
+
+        if header.length == 0 {
+            // Shutting down; confirm absence of subsequent data.
+            if reader.read(&mut [0u8]).expect("read failure") > 0 {
+                panic!("Clean shutdown followed by data.");
+            }
+            break;
+        }
+
+        sequences.check(&header, logger, process, remote);
+
+        let mut length = [0u8; 8];
+        if reader.read_exact(&mut length).is_err() {
+            peer_failed(logger, process, remote, "connection closed mid-message");
+        }
+        received.resize(u64::from_le_bytes(length) as usize, 0);
+        if reader.read_exact(&mut received[..]).is_err() {
+            peer_failed(logger, process, remote, "connection closed mid-message");
+        }
+
+        buffer.ensure_capacity(header.required_bytes());
+        let empty = buffer.empty();
+        empty[.. HEADER_BYTES].copy_from_slice(&header_bytes);
+        let payload = &mut empty[HEADER_BYTES .. header.required_bytes()];
+        // Payloads that would not shrink are sent as they are.
+        if received.len() == header.length {
+            payload.copy_from_slice(&received[..]);
+        }
+        else if compression.decompress(&received[..], payload).ok() != Some(header.length) {
+            peer_failed(logger, process, remote, "malformed compressed payload");
+        }
+        buffer.make_valid(header.required_bytes());
+        stageds[header.target - worker_offset].push(buffer.extract(header.required_bytes()));
+
+        log_compression(logger, &mut totals, false, remote, header.channel, header.length, received.len());
+
+        // Pass bytes along to targets once we have caught up with the socket.
+        if reader.buffer().is_empty() {
+            for (index, staged) in stageds.iter_mut().enumerate() {
+                targets[index].extend(staged.drain(..));
+            }
+        }
+    }
+
+    for (index, staged) in stageds.iter_mut().enumerate() {
+        targets[index].extend(staged.drain(..));
+    }
+}
+
+/// Expected sequence numbers for each (channel, source, target) triple.
+///
+/// Each pusher numbers its messages consecutively from zero, and the connection delivers
+/// them in order, so any other sequence number indicates lost or duplicated data.
+#[derive(Default)]
+struct Sequences {
+    expected: HashMap<(usize, usize, usize), usize>,
+}
+
+impl Sequences {
+    fn check(
+        &mut self,
+        header: &MessageHeader,
+        logger: &mut Option<Logger<CommunicationEvent, CommunicationSetup>>,
+        process: usize,
+        remote: usize)
+    {
+        let expected = self.expected.entry((header.channel, header.source, header.target)).or_insert(0);
+        if header.seqno != *expected {
+            let reason = if header.seqno < *expected { "duplicate message" } else { "missing message" };
+            peer_failed(logger, process, remote, reason);
+        }
+        *expected += 1;
+    }
+}
+
+/// Reports the failure of the connection to `remote`, and takes down the receive thread.
+///
+/// Panicking poisons the queues shared with workers, which causes the failure to cascade
+/// through the computation. The connection is not re-established, as the messages it lost
+/// are no longer held by the sending process and could not be replayed.
+fn peer_failed(
+    logger: &mut Option<Logger<CommunicationEvent, CommunicationSetup>>,
+    process: usize,
+    remote: usize,
+    reason: &str) -> !
+{
+    if let Some(logger) = logger.as_mut() {
+        logger.log(PeerFailedEvent { process, remote });
+        logger.flush();
+    }
+    panic!("Connection from process {} failed: {}", remote, reason);
+}
+
+/// Accumulates and logs the byte counts for a message on a compressed connection.
+fn log_compression(
+    logger: &mut Option<Logger<CommunicationEvent, CommunicationSetup>>,
+    totals: &mut HashMap<usize, (usize, usize)>,
+    is_send: bool,
+    remote: usize,
+    channel: usize,
+    uncompressed: usize,
+    compressed: usize)
+{
+    if let Some(logger) = logger.as_mut() {
+        let total = totals.entry(channel).or_insert((0, 0));
+        total.0 += uncompressed;
+        total.1 += compressed;
+        logger.log(CompressionEvent { is_send, remote, channel, uncompressed: total.0, compressed: total.1 });
+    }
+}
+
+/// Repeatedly sends messages into a connection.
 ///
 /// The intended communication pattern is a sequence of (header, message)^* for valid
 /// messages, followed by a header for a zero length message indicating the end of stream.
-pub fn send_loop(
+///
+/// If `compression` is other than `Compression::None`, each header is instead followed by
+/// the length of the transmitted payload and the payload itself, compressed if this makes
+/// it smaller.
+pub fn send_loop<W: WriteHalf>(
     // TODO: Maybe we don't need BufWriter with consolidation in writes.
-    writer: TcpStream,
+    writer: W,
     sources: Vec<Sender<MergeQueue>>,
     process: usize,
     remote: usize,
+    compression: Compression,
     mut logger: Option<Logger<CommunicationEvent, CommunicationSetup>>)
 {
 
@@ -132,6 +306,8 @@ pub fn send_loop(
 
     let mut writer = ::std::io::BufWriter::with_capacity(1 << 16, writer);
     let mut stash = Vec::new();
+    let mut compressed = Vec::new();
+    let mut totals = HashMap::new();
 
     while !sources.is_empty() {
 
@@ -158,6 +334,28 @@ pub fn send_loop(
             // TODO: Could do scatter/gather write here.
             for mut bytes in stash.drain(..) {
 
+                if compression != Compression::None {
+                    let mut offset = 0;
+                    while let Some(header) = MessageHeader::try_read(&mut bytes[offset..]) {
+                        if let Some(logger) = logger.as_mut() {
+                            logger.log(MessageEvent { is_send: true, header, });
+                        }
+
+                        let payload = &bytes[offset + HEADER_BYTES .. offset + header.required_bytes()];
+                        compressed.clear();
+                        compression.compress(payload, &mut compressed);
+                        let sent = if compressed.len() < payload.len() { &compressed[..] } else { payload };
+
+                        writer.write_all(&bytes[offset .. offset + HEADER_BYTES]).expect("Write failure in send_loop.");
+                        writer.write_all(&(sent.len() as u64).to_le_bytes()).expect("Write failure in send_loop.");
+                        writer.write_all(sent).expect("Write failure in send_loop.");
+
+                        log_compression(&mut logger, &mut totals, true, remote, header.channel, header.length, sent.len());
+                        offset += header.required_bytes();
+                    }
+                    continue;
+                }
+
                 // Record message sends.
                 logger.as_mut().map(|logger| {
                     let mut offset = 0;
@@ -181,10 +379,11 @@ pub fn send_loop(
         target:     0,
         length:     0,
         seqno:      0,
+        remaining:  0,
     };
     header.write_to(&mut writer).expect("Failed to write header!");
     writer.flush().expect("Failed to flush writer.");
-    writer.get_mut().shutdown(::std::net::Shutdown::Write).expect("Write shutdown failed");
+    writer.get_mut().shutdown().expect("Write shutdown failed");
     logger.as_mut().map(|logger| logger.log(MessageEvent { is_send: true, header }));
 
     // Log the send thread's end.
@@ -0,0 +1,101 @@
+//! Serialization strategies for channel contents.
+//!
+//! Channels that cross thread or process boundaries must serialize their messages. By default
+//! this uses the serialization implied by the `Data` trait (Abomonation, or bincode when the
+//! `bincode` feature is enabled), but channels may be allocated with any `Codec` through
+//! `Allocate::allocate_with`. The `Bincode` codec allows types that implement `serde`'s traits,
+//! but not `Abomonation`, to move between processes.
+
+use std::io::Write;
+
+use bytes::arc::Bytes;
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{Data, Message};
+
+/// Methods for serializing and deserializing messages of type `T`.
+pub trait Codec<T>: 'static {
+    /// The number of bytes required to serialize the message.
+    fn length_in_bytes(message: &Message<T>) -> usize;
+    /// Writes the binary representation of the message into `writer`.
+    fn into_bytes<W: Write>(message: &Message<T>, writer: &mut W);
+    /// Reconstructs a message from its binary representation.
+    fn from_bytes(bytes: Bytes) -> Message<T>;
+}
+
+/// The serialization implied by the `Data` trait.
+pub struct Native;
+
+impl<T: Data> Codec<T> for Native {
+    #[inline]
+    fn length_in_bytes(message: &Message<T>) -> usize { message.length_in_bytes() }
+    #[inline]
+    fn into_bytes<W: Write>(message: &Message<T>, writer: &mut W) { message.into_bytes(writer) }
+    #[allow(unused_unsafe)]
+    #[inline]
+    fn from_bytes(bytes: Bytes) -> Message<T> { unsafe { Message::from_bytes(bytes) } }
+}
+
+/// Serialization using `serde` and `bincode`.
+///
+/// Received messages are decoded into owned typed data, which costs a copy relative to
+/// Abomonation's in-place decoding, but is safe for all types implementing `serde`'s traits.
+///
+/// Each encoded message is followed by up to seven zero bytes, so that its length is a multiple
+/// of eight bytes and the messages which follow it in shared buffers remain aligned for codecs
+/// that decode in place. Decoding ignores the padding, but earlier versions of this codec wrote
+/// none, and so processes exchanging messages with this codec must run the same version.
+///
+/// # Examples
+/// ```
+/// use std::collections::HashMap;
+/// use timely_bytes::arc::Bytes;
+/// use timely_communication::Message;
+/// use timely_communication::codec::{Codec, Bincode};
+///
+/// let mut map = HashMap::new();
+/// map.insert("hello".to_string(), vec![Some(1), None]);
+///
+/// let message = Message::from_typed(map.clone());
+/// let mut buffer = Vec::with_capacity(Bincode::length_in_bytes(&message));
+/// Bincode::into_bytes(&message, &mut buffer);
+/// assert_eq!(buffer.len(), Bincode::length_in_bytes(&message));
+/// assert_eq!(buffer.len() % 8, 0);
+///
+/// let decoded: Message<HashMap<String, Vec<Option<u64>>>> = Bincode::from_bytes(Bytes::from(buffer));
+/// assert_eq!(*decoded, map);
+/// ```
+pub struct Bincode;
+
+impl<T: Serialize+DeserializeOwned+'static> Codec<T> for Bincode {
+    fn length_in_bytes(message: &Message<T>) -> usize {
+        let length = ::bincode::serialized_size(&**message).expect("bincode::serialized_size() failed") as usize;
+        (length + 7) & !7
+    }
+    fn into_bytes<W: Write>(message: &Message<T>, writer: &mut W) {
+        let mut counting = Counting { writer: &mut *writer, written: 0 };
+        ::bincode::serialize_into(&mut counting, &**message).expect("bincode::serialize_into() failed");
+        let padding = ((counting.written + 7) & !7) - counting.written;
+        writer.write_all(&[0u8; 8][.. padding]).expect("failed to write padding");
+    }
+    fn from_bytes(bytes: Bytes) -> Message<T> {
+        Message::from_typed(::bincode::deserialize(&bytes[..]).expect("bincode::deserialize() failed"))
+    }
+}
+
+// Counts the bytes written through it, from which the padding of a message is determined.
+struct Counting<W> {
+    writer: W,
+    written: usize,
+}
+
+impl<W: Write> Write for Counting<W> {
+    fn write(&mut self, buf: &[u8]) -> ::std::io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.written += written;
+        Ok(written)
+    }
+    fn flush(&mut self) -> ::std::io::Result<()> {
+        self.writer.flush()
+    }
+}
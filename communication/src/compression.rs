@@ -0,0 +1,228 @@
+//! Compression of message payloads sent between processes.
+//!
+//! Each process states a preferred `Compression` when it initializes networking, and the
+//! preferences are exchanged as part of the connection handshake. A connection compresses
+//! its traffic only if both ends prefer the same scheme, and otherwise falls back to sending
+//! uncompressed bytes.
+//!
+//! Message headers are always sent uncompressed; only the payload that follows each header
+//! is compressed, and only when doing so actually reduces its size.
+//!
+//! # Examples
+//! ```
+//! use timely_communication::compression::Compression;
+//!
+//! let data = b"timely timely timely timely timely timely timely".to_vec();
+//!
+//! let mut compressed = Vec::new();
+//! Compression::Lz4.compress(&data, &mut compressed);
+//! assert!(compressed.len() < data.len());
+//!
+//! let mut decompressed = vec![0u8; data.len()];
+//! assert_eq!(Compression::Lz4.decompress(&compressed, &mut decompressed).unwrap(), data.len());
+//! assert_eq!(decompressed, data);
+//!
+//! // Truncated payloads are reported as errors.
+//! assert!(Compression::Lz4.decompress(&compressed[.. compressed.len() - 4], &mut decompressed).is_err());
+//! ```
+
+use std::io;
+use std::str::FromStr;
+
+/// Compression schemes available for network traffic.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// Send bytes unmodified.
+    #[default]
+    None,
+    /// Compress payloads using the LZ4 block format.
+    Lz4,
+}
+
+impl Compression {
+    /// The code used to announce this scheme in the connection handshake.
+    pub(crate) fn code(self) -> u64 {
+        match self {
+            Compression::None => 0,
+            Compression::Lz4 => 1,
+        }
+    }
+
+    /// Interprets a code received in the connection handshake.
+    ///
+    /// Unrecognized codes, for example from a newer peer, are read as `Compression::None`.
+    pub(crate) fn from_code(code: u64) -> Self {
+        match code {
+            1 => Compression::Lz4,
+            _ => Compression::None,
+        }
+    }
+
+    /// The scheme to use on a connection between two processes with these preferences.
+    pub fn negotiate(self, other: Compression) -> Compression {
+        if self == other { self } else { Compression::None }
+    }
+
+    /// Appends the compressed form of `input` to `output`.
+    pub fn compress(self, input: &[u8], output: &mut Vec<u8>) {
+        match self {
+            Compression::None => output.extend_from_slice(input),
+            Compression::Lz4 => lz4::compress(input, output),
+        }
+    }
+
+    /// Decompresses `input` into `output`, returning the number of bytes written.
+    ///
+    /// Returns an error of kind `InvalidData` if `input` is malformed or would not fit in `output`.
+    pub fn decompress(self, input: &[u8], output: &mut [u8]) -> io::Result<usize> {
+        let written = match self {
+            Compression::None => {
+                output.get_mut(.. input.len()).map(|output| output.copy_from_slice(input)).map(|()| input.len())
+            },
+            Compression::Lz4 => lz4::decompress(input, output),
+        };
+        written.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("malformed {:?} payload of {} bytes", self, input.len())))
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "none" => Ok(Compression::None),
+            "lz4" => Ok(Compression::Lz4),
+            _ => Err(format!("unrecognized compression: {} (expected \"none\" or \"lz4\")", name)),
+        }
+    }
+}
+
+/// A compact implementation of the LZ4 block format.
+///
+/// The compressor uses a single-probe hash table, which favors speed over ratio in the same
+/// way as the reference implementation's fast mode. The output is a valid LZ4 block, and can
+/// be read by any conforming decoder.
+mod lz4 {
+
+    const MIN_MATCH: usize = 4;
+    const MF_LIMIT: usize = 12;
+    const LAST_LITERALS: usize = 5;
+    const HASH_LOG: usize = 12;
+    const MAX_OFFSET: usize = 0xFFFF;
+
+    #[inline]
+    fn read_u32(bytes: &[u8], position: usize) -> u32 {
+        let mut word = [0u8; 4];
+        word.copy_from_slice(&bytes[position .. position + 4]);
+        u32::from_le_bytes(word)
+    }
+
+    #[inline]
+    fn hash(sequence: u32) -> usize {
+        (sequence.wrapping_mul(2654435761) >> (32 - HASH_LOG)) as usize
+    }
+
+    fn write_length(output: &mut Vec<u8>, mut length: usize) {
+        while length >= 255 {
+            output.push(255);
+            length -= 255;
+        }
+        output.push(length as u8);
+    }
+
+    fn write_sequence(output: &mut Vec<u8>, literals: &[u8], matched: Option<(usize, usize)>) {
+        let match_code = matched.map(|(_, length)| length - MIN_MATCH).unwrap_or(0);
+        output.push(((literals.len().min(15) as u8) << 4) | (match_code.min(15) as u8));
+        if literals.len() >= 15 {
+            write_length(output, literals.len() - 15);
+        }
+        output.extend_from_slice(literals);
+        if let Some((offset, _)) = matched {
+            output.extend_from_slice(&(offset as u16).to_le_bytes());
+            if match_code >= 15 {
+                write_length(output, match_code - 15);
+            }
+        }
+    }
+
+    pub fn compress(input: &[u8], output: &mut Vec<u8>) {
+        let mut table = vec![0usize; 1 << HASH_LOG];
+        let mut anchor = 0;
+        let mut position = 0;
+
+        if input.len() > MF_LIMIT {
+            let limit = input.len() - MF_LIMIT;
+            let match_limit = input.len() - LAST_LITERALS;
+            while position < limit {
+                let sequence = read_u32(input, position);
+                let slot = &mut table[hash(sequence)];
+                // Table entries are offset by one, so that zero indicates an empty slot.
+                let candidate = ::std::mem::replace(slot, position + 1);
+                if candidate > 0 && position - (candidate - 1) <= MAX_OFFSET && read_u32(input, candidate - 1) == sequence {
+                    let candidate = candidate - 1;
+                    let mut length = MIN_MATCH;
+                    while position + length < match_limit && input[candidate + length] == input[position + length] {
+                        length += 1;
+                    }
+                    write_sequence(output, &input[anchor .. position], Some((position - candidate, length)));
+                    position += length;
+                    anchor = position;
+                }
+                else {
+                    position += 1;
+                }
+            }
+        }
+
+        write_sequence(output, &input[anchor ..], None);
+    }
+
+    fn read_length(input: &[u8], position: &mut usize) -> Option<usize> {
+        let mut length = 0;
+        loop {
+            let byte = *input.get(*position)?;
+            *position += 1;
+            length += byte as usize;
+            if byte != 255 {
+                return Some(length);
+            }
+        }
+    }
+
+    pub fn decompress(input: &[u8], output: &mut [u8]) -> Option<usize> {
+        let mut ipos = 0;
+        let mut opos: usize = 0;
+        loop {
+            let token = *input.get(ipos)?;
+            ipos += 1;
+
+            let mut literals = (token >> 4) as usize;
+            if literals == 15 {
+                literals += read_length(input, &mut ipos)?;
+            }
+            let source = input.get(ipos .. ipos.checked_add(literals)?)?;
+            output.get_mut(opos .. opos.checked_add(literals)?)?.copy_from_slice(source);
+            ipos += literals;
+            opos += literals;
+
+            // The final sequence consists only of literals.
+            if ipos == input.len() {
+                return Some(opos);
+            }
+
+            let offset = u16::from_le_bytes([*input.get(ipos)?, *input.get(ipos + 1)?]) as usize;
+            ipos += 2;
+            let mut length = (token & 15) as usize + MIN_MATCH;
+            if token & 15 == 15 {
+                length += read_length(input, &mut ipos)?;
+            }
+            if offset == 0 || offset > opos || opos.checked_add(length)? > output.len() {
+                return None;
+            }
+            // Matches may overlap the bytes they produce, so copy one byte at a time.
+            for index in opos .. opos + length {
+                output[index] = output[index - offset];
+            }
+            opos += length;
+        }
+    }
+}
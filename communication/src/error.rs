@@ -0,0 +1,104 @@
+//! Errors in the initialization of communication.
+
+use std::fmt;
+use std::io;
+
+/// A failure to assemble the communication infrastructure of a computation.
+#[derive(Debug)]
+pub enum Error {
+    /// The configuration cannot be realized, as for a process index with no address.
+    Config(String),
+    /// This process could not accept connections on its address.
+    Bind {
+        /// The address on which this process would accept connections.
+        address: String,
+        /// The cause of the failure.
+        error: io::Error,
+    },
+    /// This process could not connect to another process.
+    Connect {
+        /// The index of the other process.
+        process: usize,
+        /// The cause of the failure.
+        error: io::Error,
+    },
+    /// A connection did not complete the handshake, as for a connection from another program.
+    Handshake(String),
+    /// Another process hosts a different number of workers than this process.
+    WorkerCount {
+        /// The index of the other process.
+        process: usize,
+        /// The number of workers of this process.
+        expected: usize,
+        /// The number of workers of the other process.
+        found: usize,
+    },
+    /// Another failure to communicate with other processes.
+    Io(io::Error),
+    /// A worker could not be started.
+    Spawn(String),
+}
+
+impl Error {
+    /// Recovers an error carried by `error`, if any, and otherwise wraps `error`.
+    ///
+    /// Networking functions report failures as `io::Error`s, some of which carry a more specific
+    /// `Error` as their payload.
+    pub fn from_io(error: io::Error) -> Error {
+        if error.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            *error.into_inner().and_then(|inner| inner.downcast().ok()).expect("payload checked to be an Error")
+        }
+        else {
+            Error::Io(error)
+        }
+    }
+
+    /// Wraps `self` in an `io::Error`, recoverable with `Error::from_io`.
+    pub(crate) fn into_io(self) -> io::Error {
+        let kind = match &self {
+            Error::Bind { error, .. } | Error::Connect { error, .. } | Error::Io(error) => error.kind(),
+            Error::Handshake(_) | Error::WorkerCount { .. } => io::ErrorKind::InvalidData,
+            Error::Config(_) => io::ErrorKind::InvalidInput,
+            Error::Spawn(_) => io::ErrorKind::Other,
+        };
+        io::Error::new(kind, self)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Config(reason) => write!(f, "invalid configuration: {}", reason),
+            Error::Bind { address, error } => write!(f, "failed to accept connections on {}: {}", address, error),
+            Error::Connect { process, error } => write!(f, "failed to connect to process {}: {}", process, error),
+            Error::Handshake(reason) => write!(f, "failed handshake: {}", reason),
+            Error::WorkerCount { process, expected, found } => {
+                write!(f, "process {} hosts {} workers, where this process hosts {}", process, found, expected)
+            },
+            Error::Io(error) => write!(f, "failed to initialize networking: {}", error),
+            Error::Spawn(reason) => write!(f, "failed to start worker: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Bind { error, .. } | Error::Connect { error, .. } | Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Error {
+        Error::from_io(error)
+    }
+}
+
+/// Describes the error, for callers that report errors as strings.
+impl From<Error> for String {
+    fn from(error: Error) -> String {
+        error.to_string()
+    }
+}
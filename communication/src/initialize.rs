@@ -11,7 +11,13 @@ use std::any::Any;
 
 use crate::allocator::thread::ThreadBuilder;
 use crate::allocator::{AllocateBuilder, Process, Generic, GenericBuilder};
-use crate::allocator::zero_copy::initialize::initialize_networking;
+use crate::allocator::zero_copy::allocator_process::ProcessBuilder;
+use crate::allocator::zero_copy::initialize::{initialize_mpi, initialize_networking, initialize_networking_from_sockets};
+use crate::allocator::zero_copy::mpi::Communicator;
+use crate::allocator::zero_copy::placement::BufferAllocator;
+use crate::compression::Compression;
+use crate::error::Error;
+use crate::networking::{Backoff, ConnectionOptions, PeerAddress, StreamUpgrade, Transport};
 
 use crate::logging::{CommunicationSetup, CommunicationEvent};
 use logging_core::Logger;
@@ -23,6 +29,13 @@ pub enum Config {
     Thread,
     /// Use one process with an indicated number of threads.
     Process(usize),
+    /// Use one process with an indicated number of threads, which exchange serialized data.
+    ProcessBinary {
+        /// Number of worker threads
+        threads: usize,
+        /// Allocator of the buffers into which data for each worker are serialized
+        buffers: Arc<dyn BufferAllocator>,
+    },
     /// Expect multiple processes.
     Cluster {
         /// Number of per-process worker threads
@@ -31,8 +44,21 @@ pub enum Config {
         process: usize,
         /// Addresses of all processes
         addresses: Vec<String>,
+        /// Address on which this process accepts connections, if not its address in `addresses`
+        bind: Option<String>,
         /// Verbosely report connection process
         report: bool,
+        /// Schedule for retrying failed connection attempts while the computation starts; connections
+        /// lost later are not re-established
+        retry: Backoff,
+        /// Preferred compression for connections to other processes
+        compression: Compression,
+        /// Optional conversion of each connection, for example to authenticate and encrypt it with TLS
+        upgrade: Option<Box<StreamUpgrade>>,
+        /// Mechanism for moving bytes between processes
+        transport: Transport,
+        /// Largest serialized size of a single record sent to other processes, if limited; larger records are dropped
+        max_record: Option<usize>,
         /// Closure to create a new logger for a communication thread
         log_fn: Box<dyn Fn(CommunicationSetup) -> Option<Logger<CommunicationEvent, CommunicationSetup>> + Send + Sync>,
     }
@@ -55,6 +81,11 @@ impl Config {
         opts.optopt("n", "processes", "number of processes", "NUM");
         opts.optopt("h", "hostfile", "text file whose lines are process addresses", "FILE");
         opts.optflag("r", "report", "reports connection progress");
+        opts.optopt("", "compression", "compression for network traffic (none, lz4)", "NAME");
+        opts.optopt("", "transport", "transport between processes (tcp, shm)", "NAME");
+        opts.optopt("", "rendezvous", "coordinator from which to learn process addresses, instead of a hostfile", "ADDR");
+        opts.optopt("", "listen", "address of this process, registered with the coordinator", "ADDR");
+        opts.optopt("", "bind", "address on which this process accepts connections, if not its advertised address", "ADDR");
     }
 
     /// Instantiates a configuration based upon the parsed options in `matches`.
@@ -71,14 +102,30 @@ impl Config {
         let process = matches.opt_get_default("p", 0_usize).map_err(|e| e.to_string())?;
         let processes = matches.opt_get_default("n", 1_usize).map_err(|e| e.to_string())?;
         let report = matches.opt_present("report");
+        let compression = matches.opt_get_default("compression", Compression::None)?;
+        let transport = matches.opt_get_default("transport", Transport::Tcp)?;
 
         if processes > 1 {
             let mut addresses = Vec::new();
-            if let Some(hosts) = matches.opt_str("h") {
+            let mut process = process;
+            let mut bind = matches.opt_str("bind");
+            if let Some(coordinator) = matches.opt_str("rendezvous") {
+                let listen = matches.opt_str("listen").ok_or("--rendezvous requires --listen")?;
+                let requested = if matches.opt_present("p") { Some(process) } else { None };
+                let (index, members) = crate::rendezvous::register(&coordinator, &listen, processes, requested, Backoff::default())
+                    .map_err(|e| e.to_string())?;
+                process = index;
+                addresses = members;
+            }
+            else if let Some(hosts) = matches.opt_str("h") {
                 let file = ::std::fs::File::open(hosts.clone()).map_err(|e| e.to_string())?;
                 let reader = ::std::io::BufReader::new(file);
                 for line in reader.lines().take(processes) {
-                    addresses.push(line.map_err(|e| e.to_string())?);
+                    let peer: PeerAddress = line.map_err(|e| e.to_string())?.parse()?;
+                    if addresses.len() == process && bind.is_none() {
+                        bind = peer.bind.clone();
+                    }
+                    addresses.push(peer.advertised);
                 }
                 if addresses.len() < processes {
                     return Err(format!("could only read {} addresses from {}, but -n: {}", addresses.len(), hosts, processes));
@@ -95,7 +142,13 @@ impl Config {
                 threads,
                 process,
                 addresses,
+                bind,
                 report,
+                retry: Backoff::default(),
+                compression,
+                upgrade: None,
+                transport,
+                max_record: None,
                 log_fn: Box::new( | _ | None),
             })
         } else if threads > 1 {
@@ -119,8 +172,51 @@ impl Config {
         Config::from_matches(&matches)
     }
 
+    /// Constructs the configuration of process `process` of a cluster whose processes have the
+    /// addresses `peers`, each running `threads` workers.
+    ///
+    /// This is how orchestration layers that already know the addresses of the processes can
+    /// configure them, without writing a hostfile. Other settings take their default values, and
+    /// may be changed by matching on the result.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely_communication::Config;
+    /// use timely_communication::networking::PeerAddress;
+    ///
+    /// let peers = vec![
+    ///     PeerAddress::new("10.0.0.1:2101").bind("0.0.0.0:2101"),
+    ///     PeerAddress::new("10.0.0.2:2101").bind("0.0.0.0:2101"),
+    /// ];
+    /// let config = Config::cluster(4, 1, peers);
+    /// ```
+    pub fn cluster(threads: usize, process: usize, peers: Vec<PeerAddress>) -> Config {
+        let bind = peers.get(process).and_then(|peer| peer.bind.clone());
+        Config::Cluster {
+            threads,
+            process,
+            addresses: peers.into_iter().map(|peer| peer.advertised).collect(),
+            bind,
+            report: false,
+            retry: Backoff::default(),
+            compression: Compression::None,
+            upgrade: None,
+            transport: Transport::Tcp,
+            max_record: None,
+            log_fn: Box::new(|_| None),
+        }
+    }
+
     /// Attempts to assemble the described communication infrastructure.
+    ///
+    /// Failures are described as strings; [`try_assemble`](Config::try_assemble) reports them as
+    /// an [`Error`] instead.
     pub fn try_build(self) -> Result<(Vec<GenericBuilder>, Box<dyn Any+Send>), String> {
+        Ok(self.try_assemble()?)
+    }
+
+    /// Attempts to assemble the described communication infrastructure, reporting why it could not.
+    pub fn try_assemble(self) -> Result<(Vec<GenericBuilder>, Box<dyn Any+Send>), Error> {
         match self {
             Config::Thread => {
                 Ok((vec![GenericBuilder::Thread(ThreadBuilder)], Box::new(())))
@@ -128,18 +224,66 @@ impl Config {
             Config::Process(threads) => {
                 Ok((Process::new_vector(threads).into_iter().map(|x| GenericBuilder::Process(x)).collect(), Box::new(())))
             },
-            Config::Cluster { threads, process, addresses, report, log_fn } => {
-                match initialize_networking(addresses, process, threads, report, log_fn) {
-                    Ok((stuff, guard)) => {
-                        Ok((stuff.into_iter().map(|x| GenericBuilder::ZeroCopy(x)).collect(), Box::new(guard)))
-                    },
-                    Err(err) => Err(format!("failed to initialize networking: {}", err))
-                }
+            Config::ProcessBinary { threads, buffers } => {
+                Ok((ProcessBuilder::new_vector_in(threads, buffers).into_iter().map(GenericBuilder::ProcessBinary).collect(), Box::new(())))
+            },
+            Config::Cluster { threads, process, addresses, bind, report, retry, compression, upgrade, transport, max_record, log_fn } => {
+                let upgrade = match transport {
+                    Transport::Tcp => upgrade,
+                    #[cfg(target_os = "linux")]
+                    Transport::SharedMemory => Some(crate::allocator::zero_copy::shm::upgrade(upgrade)),
+                    #[cfg(not(target_os = "linux"))]
+                    Transport::SharedMemory => return Err(Error::Config("shared memory transport is only available on Linux".to_owned())),
+                };
+                let (stuff, guard) = initialize_networking(addresses, process, threads, report, ConnectionOptions { retry, compression, upgrade, bind, max_record }, log_fn)?;
+                Ok((stuff.into_iter().map(GenericBuilder::ZeroCopy).collect(), Box::new(guard)))
             },
         }
     }
 }
 
+/// Assembles the communication infrastructure of `processes` processes of `threads` workers each,
+/// all within this process.
+///
+/// The processes are connected by loopback sockets, and exchange data through the same allocators,
+/// serialization, and send and receive threads as processes on different hosts. All connections
+/// are established before this method returns. Element `index` of the result contains the
+/// builders and guard of process `index`, as `Config::try_build` would produce for it.
+///
+/// Failures are described as strings; [`try_assemble_in_process`] reports them as an [`Error`].
+#[allow(clippy::type_complexity)]
+pub fn try_build_in_process(processes: usize, threads: usize) -> Result<Vec<(Vec<GenericBuilder>, Box<dyn Any+Send>)>, String> {
+    Ok(try_assemble_in_process(processes, threads)?)
+}
+
+/// Assembles the communication infrastructure of `processes` processes within this process, as
+/// [`try_build_in_process`] does, reporting why it could not.
+#[allow(clippy::type_complexity)]
+pub fn try_assemble_in_process(processes: usize, threads: usize) -> Result<Vec<(Vec<GenericBuilder>, Box<dyn Any+Send>)>, Error> {
+    let sockets = crate::networking::loopback_sockets(processes)?;
+    // Processes exchange preferences on connection, and so must initialize concurrently.
+    let handles = sockets.into_iter().enumerate().map(|(index, sockets)| {
+        thread::spawn(move || initialize_networking_from_sockets(sockets, index, threads, Box::new(|_| None)))
+    }).collect::<Vec<_>>();
+    handles.into_iter().map(|handle| {
+        let (stuff, guard) = handle.join().map_err(|err| Error::Spawn(format!("{:?}", err)))??;
+        let builders = stuff.into_iter().map(GenericBuilder::ZeroCopy).collect();
+        Ok((builders, Box::new(guard) as Box<dyn Any+Send>))
+    }).collect()
+}
+
+/// Assembles the communication infrastructure of this process, connected to the others by MPI.
+///
+/// The index of this process is the rank of `communicator`, and the number of processes is its
+/// size; each process hosts `threads` workers. Data between processes are carried by MPI
+/// point-to-point messages, through the same allocators and serialization as other clusters.
+/// See the [`mpi`](crate::allocator::zero_copy::mpi) module for the requirements on `communicator`.
+pub fn try_build_mpi(communicator: Arc<dyn Communicator>, threads: usize, compression: Compression) -> Result<(Vec<GenericBuilder>, Box<dyn Any+Send>), String> {
+    let (stuff, guard) = initialize_mpi(communicator, threads, compression, Box::new(|_| None))
+        .map_err(|err| String::from(Error::from(err)))?;
+    Ok((stuff.into_iter().map(GenericBuilder::ZeroCopy).collect(), Box::new(guard)))
+}
+
 /// Initializes communication and executes a distributed computation.
 ///
 /// This method allocates an `allocator::Generic` for each thread, spawns local worker threads,
@@ -209,8 +353,30 @@ pub fn initialize<T:Send+'static, F: Fn(Generic)->T+Send+Sync+'static>(
     config: Config,
     func: F,
 ) -> Result<WorkerGuards<T>,String> {
-    let (allocators, others) = config.try_build()?;
-    initialize_from(allocators, others, func)
+    Ok(try_initialize(config, func)?)
+}
+
+/// Initializes communication and executes a distributed computation, as [`initialize`] does,
+/// reporting why it could not as an [`Error`].
+///
+/// # Examples
+/// ```
+/// use timely_communication::{Config, Error};
+/// use timely_communication::networking::PeerAddress;
+///
+/// // process 1 of a single process has no address.
+/// let config = Config::cluster(1, 1, vec![PeerAddress::new("127.0.0.1:1")]);
+/// match timely_communication::try_initialize(config, |_allocator| ()) {
+///     Err(Error::Config(reason)) => println!("misconfigured: {}", reason),
+///     _ => panic!("initialization should fail"),
+/// }
+/// ```
+pub fn try_initialize<T:Send+'static, F: Fn(Generic)->T+Send+Sync+'static>(
+    config: Config,
+    func: F,
+) -> Result<WorkerGuards<T>,Error> {
+    let (allocators, others) = config.try_assemble()?;
+    try_initialize_from(allocators, others, func)
 }
 
 /// Initializes computation and runs a distributed computation.
@@ -269,6 +435,21 @@ pub fn initialize_from<A, T, F>(
     others: Box<dyn Any+Send>,
     func: F,
 ) -> Result<WorkerGuards<T>,String>
+where
+    A: AllocateBuilder+'static,
+    T: Send+'static,
+    F: Fn(<A as AllocateBuilder>::Allocator)->T+Send+Sync+'static
+{
+    Ok(try_initialize_from(builders, others, func)?)
+}
+
+/// Initializes computation and runs a distributed computation with explicit allocators, as
+/// [`initialize_from`] does, reporting why it could not as an [`Error`].
+pub fn try_initialize_from<A, T, F>(
+    builders: Vec<A>,
+    others: Box<dyn Any+Send>,
+    func: F,
+) -> Result<WorkerGuards<T>,Error>
 where
     A: AllocateBuilder+'static,
     T: Send+'static,
@@ -284,21 +465,94 @@ where
                                 let communicator = builder.build();
                                 (*clone)(communicator)
                             })
-                            .map_err(|e| format!("{:?}", e))?);
+                            .map_err(|e| Error::Spawn(e.to_string()))?);
+    }
+
+    Ok(WorkerGuards { guards, spawned: Vec::new(), others })
+}
+
+/// The work of one worker, to be run to completion by a spawner.
+pub type WorkerTask = Box<dyn FnOnce()+Send+'static>;
+
+/// Initializes computation and runs a distributed computation on threads provided by `spawner`.
+///
+/// This version of `initialize_from` does not spawn worker threads, but calls `spawner` with the
+/// index and the task of each worker, in order. The spawner must arrange for each task to run to
+/// completion on a thread of its choosing, for example by spawning it on a thread pool or by
+/// handing it to a thread the caller already manages, and returns an error if it cannot. The
+/// workers of a computation communicate with one another, and so their tasks must all run at the
+/// same time, each on its own thread; a spawner that runs tasks one after another, or fewer at
+/// once than there are workers, may hang the computation.
+///
+/// The results of the workers are collected from their tasks, and the returned `WorkerGuards`
+/// joins them as for [`initialize_from`], although it holds no thread handles. A task that panics
+/// produces an error for its worker, and a task that is dropped without running produces an error
+/// when the guards are joined.
+///
+/// # Examples
+/// ```
+/// use timely_communication::Allocate;
+///
+/// let builders = timely_communication::allocator::process::Process::new_vector(2);
+///
+/// // runs each worker on a thread named by the caller.
+/// let guards = timely_communication::initialize_with_spawner(builders, Box::new(()), |index, task| {
+///     std::thread::Builder::new()
+///         .name(format!("embedder:{}", index))
+///         .spawn(task)
+///         .map(|_| ())
+///         .map_err(|e| e.to_string())
+/// }, |allocator| {
+///     allocator.index()
+/// });
+///
+/// let results = guards.unwrap().join();
+/// assert_eq!(results, vec![Ok(0), Ok(1)]);
+/// ```
+pub fn initialize_with_spawner<A, T, F, S>(
+    builders: Vec<A>,
+    others: Box<dyn Any+Send>,
+    mut spawner: S,
+    func: F,
+) -> Result<WorkerGuards<T>,String>
+where
+    A: AllocateBuilder+'static,
+    T: Send+'static,
+    F: Fn(<A as AllocateBuilder>::Allocator)->T+Send+Sync+'static,
+    S: FnMut(usize, WorkerTask)->Result<(), String>,
+{
+    let logic = Arc::new(func);
+    let mut spawned = Vec::new();
+    for (index, builder) in builders.into_iter().enumerate() {
+        let clone = logic.clone();
+        let (send, recv) = std::sync::mpsc::sync_channel(1);
+        spawner(index, Box::new(move || {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                let communicator = builder.build();
+                (*clone)(communicator)
+            }));
+            // The guards may have been dropped, with no one to receive the result.
+            let _ = send.send(result);
+        }))?;
+        spawned.push(recv);
     }
 
-    Ok(WorkerGuards { guards, others })
+    Ok(WorkerGuards { guards: Vec::new(), spawned, others })
 }
 
 /// Maintains `JoinHandle`s for worker threads.
 pub struct WorkerGuards<T:Send+'static> {
     guards: Vec<::std::thread::JoinHandle<T>>,
+    // The results of workers run by a spawner, rather than on threads of our own.
+    spawned: Vec<std::sync::mpsc::Receiver<thread::Result<T>>>,
     others: Box<dyn Any+Send>,
 }
 
 impl<T:Send+'static> WorkerGuards<T> {
 
     /// Returns a reference to the indexed guard.
+    ///
+    /// Workers run by a spawner, as for [`initialize_with_spawner`], have no guards.
     pub fn guards(&self) -> &[std::thread::JoinHandle<T>] {
         &self.guards[..]
     }
@@ -310,18 +564,31 @@ impl<T:Send+'static> WorkerGuards<T> {
 
     /// Waits on the worker threads and returns the results they produce.
     pub fn join(mut self) -> Vec<Result<T, String>> {
+        let spawned = self.spawned.drain(..).map(receive_spawned).collect::<Vec<_>>();
         self.guards
             .drain(..)
             .map(|guard| guard.join().map_err(|e| format!("{:?}", e)))
+            .chain(spawned)
             .collect()
     }
 }
 
+// Waits for the result of a worker run by a spawner.
+fn receive_spawned<T>(recv: std::sync::mpsc::Receiver<thread::Result<T>>) -> Result<T, String> {
+    match recv.recv() {
+        Ok(result) => result.map_err(|e| format!("{:?}", e)),
+        Err(_) => Err("worker task dropped without running".to_owned()),
+    }
+}
+
 impl<T:Send+'static> Drop for WorkerGuards<T> {
     fn drop(&mut self) {
         for guard in self.guards.drain(..) {
             guard.join().expect("Worker panic");
         }
+        for recv in self.spawned.drain(..) {
+            receive_spawned(recv).expect("Worker panic");
+        }
         // println!("WORKER THREADS JOINED");
     }
 }
@@ -77,9 +77,7 @@
 
 #[cfg(feature = "getopts")]
 extern crate getopts;
-#[cfg(feature = "bincode")]
-extern crate bincode;
-#[cfg(feature = "bincode")]
+extern crate bincode_dep as bincode;
 extern crate serde;
 
 extern crate abomonation;
@@ -87,6 +85,8 @@ extern crate abomonation;
 
 extern crate timely_bytes as bytes;
 extern crate timely_logging as logging_core;
+#[cfg(target_os = "linux")]
+extern crate libc;
 
 pub mod allocator;
 pub mod networking;
@@ -94,6 +94,10 @@ pub mod initialize;
 pub mod logging;
 pub mod message;
 pub mod buzzer;
+pub mod codec;
+pub mod compression;
+pub mod rendezvous;
+pub mod error;
 
 use std::any::Any;
 
@@ -104,8 +108,9 @@ use abomonation::Abomonation;
 
 pub use allocator::Generic as Allocator;
 pub use allocator::Allocate;
-pub use initialize::{initialize, initialize_from, Config, WorkerGuards};
+pub use initialize::{initialize, initialize_from, initialize_with_spawner, try_initialize, try_initialize_from, try_build_in_process, try_assemble_in_process, try_build_mpi, Config, WorkerGuards, WorkerTask};
 pub use message::Message;
+pub use error::Error;
 
 /// A composite trait for types that may be used with channels.
 #[cfg(not(feature = "bincode"))]
@@ -18,6 +18,10 @@ pub enum CommunicationEvent {
     Message(MessageEvent),
     /// A state transition.
     State(StateEvent),
+    /// Compression statistics for a channel.
+    Compression(CompressionEvent),
+    /// A failed connection to a remote process.
+    PeerFailed(PeerFailedEvent),
 }
 
 /// An observed message.
@@ -42,9 +46,45 @@ pub struct StateEvent {
     pub start: bool,
 }
 
+/// Running byte counts for a channel on a compressed connection.
+///
+/// One event is recorded for each message sent or received on a connection that uses
+/// compression, and reports the totals for the message's channel so far.
+#[derive(Abomonation, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct CompressionEvent {
+    /// true for send event, false for receive event
+    pub is_send: bool,
+    /// The remote process id.
+    pub remote: usize,
+    /// index of channel.
+    pub channel: usize,
+    /// Total payload bytes before compression.
+    pub uncompressed: usize,
+    /// Total payload bytes as transmitted.
+    pub compressed: usize,
+}
+
+/// The connection from a remote process failed.
+///
+/// Recorded by the receive thread when the connection ends without a clean shutdown, or when
+/// messages arrive out of sequence. The computation is then taken down.
+#[derive(Abomonation, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct PeerFailedEvent {
+    /// The host process id.
+    pub process: usize,
+    /// The remote process id.
+    pub remote: usize,
+}
+
 impl From<MessageEvent> for CommunicationEvent {
     fn from(v: MessageEvent) -> CommunicationEvent { CommunicationEvent::Message(v) }
 }
 impl From<StateEvent> for CommunicationEvent {
     fn from(v: StateEvent) -> CommunicationEvent { CommunicationEvent::State(v) }
 }
+impl From<CompressionEvent> for CommunicationEvent {
+    fn from(v: CompressionEvent) -> CommunicationEvent { CommunicationEvent::Compression(v) }
+}
+impl From<PeerFailedEvent> for CommunicationEvent {
+    fn from(v: PeerFailedEvent) -> CommunicationEvent { CommunicationEvent::PeerFailed(v) }
+}
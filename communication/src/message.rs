@@ -1,5 +1,6 @@
 //! Types wrapping typed data.
 
+use std::any::Any;
 use std::sync::Arc;
 use bytes::arc::Bytes;
 use abomonation;
@@ -43,8 +44,12 @@ impl<'a, T: Clone+'a> RefOrMut<'a, T> {
 }
 
 /// A wrapped message which may be either typed or binary data.
+///
+/// A message may also carry an annotation, which moves with the message between the workers of a
+/// process but is not serialized, and so is absent from messages received from other processes.
 pub struct Message<T> {
     payload: MessageContents<T>,
+    annotation: Option<Arc<dyn Any+Send+Sync>>,
 }
 
 /// Possible returned representations from a channel.
@@ -60,11 +65,11 @@ enum MessageContents<T> {
 impl<T> Message<T> {
     /// Wrap a typed item as a message.
     pub fn from_typed(typed: T) -> Self {
-        Message { payload: MessageContents::Owned(typed) }
+        Message { payload: MessageContents::Owned(typed), annotation: None }
     }
     /// Wrap a shared typed item as a message.
     pub fn from_arc(typed: Arc<T>) -> Self {
-        Message { payload: MessageContents::Arc(typed) }
+        Message { payload: MessageContents::Arc(typed), annotation: None }
     }
     /// Destructures and returns any typed data.
     pub fn if_typed(self) -> Option<T> {
@@ -82,6 +87,36 @@ impl<T> Message<T> {
             MessageContents::Arc(_) => None,
         }
     }
+    /// Indicates whether the message is backed by a received binary buffer.
+    ///
+    /// Binary messages are decoded in place, and references to their contents point
+    /// directly into the receive buffer. Mutable access requires a copy, which can be
+    /// avoided by reading through `Deref` or `as_ref_or_mut`.
+    pub fn is_binary(&self) -> bool {
+        match &self.payload {
+            MessageContents::Binary(_) => true,
+            MessageContents::Owned(_) => false,
+            MessageContents::Arc(_) => false,
+        }
+    }
+    /// Returns the serialized receive buffer backing the message, if it is binary.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match &self.payload {
+            MessageContents::Binary(bytes) => Some(bytes.as_bytes()),
+            MessageContents::Owned(_) => None,
+            MessageContents::Arc(_) => None,
+        }
+    }
+    /// Returns the annotation carried with the message, if any.
+    pub fn annotation(&self) -> Option<&Arc<dyn Any+Send+Sync>> {
+        self.annotation.as_ref()
+    }
+    /// Sets the annotation carried with the message.
+    ///
+    /// The annotation is not serialized, and does not reach other processes.
+    pub fn set_annotation(&mut self, annotation: Option<Arc<dyn Any+Send+Sync>>) {
+        self.annotation = annotation;
+    }
     /// Returns an immutable or mutable typed reference.
     ///
     /// This method returns a mutable reference if the underlying data are typed Rust
@@ -106,9 +141,25 @@ impl<T: Data> Message<T> {
     /// This method is unsafe, in that `Abomonated::new()` is unsafe: it presumes that
     /// the binary data can be safely decoded, which is unsafe for e.g. UTF8 data and
     /// enumerations (perhaps among many other types).
+    ///
+    /// The resulting message borrows its contents from `bytes`, without copying them.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely_bytes::arc::Bytes;
+    /// use timely_communication::Message;
+    ///
+    /// let mut buffer = Vec::new();
+    /// Message::from_typed(vec![1u64, 2, 3]).into_bytes(&mut buffer);
+    ///
+    /// let message = unsafe { Message::<Vec<u64>>::from_bytes(Bytes::from(buffer)) };
+    /// assert!(message.is_binary());
+    /// assert_eq!(&message[..], &[1, 2, 3]);
+    /// assert_eq!(message.to_typed(), vec![1, 2, 3]);
+    /// ```
     pub unsafe fn from_bytes(bytes: Bytes) -> Self {
         let abomonated = abomonation::abomonated::Abomonated::new(bytes).expect("Abomonated::new() failed.");
-        Message { payload: MessageContents::Binary(abomonated) }
+        Message { payload: MessageContents::Binary(abomonated), annotation: None }
     }
 
     /// The number of bytes required to serialize the data.
@@ -141,7 +192,7 @@ impl<T: Data> Message<T> {
     /// Wrap bytes as a message.
     pub fn from_bytes(bytes: Bytes) -> Self {
         let typed = ::bincode::deserialize(&bytes[..]).expect("bincode::deserialize() failed");
-        Message { payload: MessageContents::Owned(typed) }
+        Message { payload: MessageContents::Owned(typed), annotation: None }
     }
 
     /// The number of bytes required to serialize the data.
@@ -186,6 +237,14 @@ impl<T> ::std::ops::Deref for Message<T> {
 }
 
 impl<T: Clone> Message<T> {
+    /// Produces an owned copy of the wrapped element, leaving the message intact.
+    ///
+    /// This is the escape hatch for consumers that need to retain data past the lifetime
+    /// of a borrowed binary message; it copies even if the message is owned. Unlike
+    /// `into_typed`, it does not consume the message.
+    pub fn to_typed(&self) -> T {
+        (**self).clone()
+    }
     /// Produces a typed instance of the wrapped element.
     pub fn into_typed(self) -> T {
         match self.payload {
@@ -1,8 +1,7 @@
 //! Networking code for sending and receiving fixed size `Vec<u8>` between machines.
 
-use std::io;
-use std::io::{Read, Result};
-use std::net::{TcpListener, TcpStream};
+use std::io::{Read, Write, Result};
+use std::net::{Shutdown, TcpListener, TcpStream};
 use std::sync::Arc;
 use std::thread;
 use std::thread::sleep;
@@ -10,6 +9,9 @@ use std::time::Duration;
 
 use abomonation::{encode, decode};
 
+use crate::compression::Compression;
+use crate::error::Error;
+
 // This constant is sent along immediately after establishing a TCP stream, so
 // that it is easy to sniff out Timely traffic when it is multiplexed with
 // other traffic on the same port.
@@ -29,6 +31,11 @@ pub struct MessageHeader {
     pub length:     usize,
     /// sequence number.
     pub seqno:      usize,
+    /// number of messages that follow this one to complete its record, if it was sent in chunks.
+    ///
+    /// This field lengthens the header from five to six words, and so processes whose headers
+    /// differ in this respect cannot exchange messages.
+    pub remaining:  usize,
 }
 
 impl MessageHeader {
@@ -59,17 +66,155 @@ impl MessageHeader {
     }
 }
 
+/// Schedule for retrying connections to other processes.
+///
+/// Connection attempts that fail, including those that connect but fail the handshake, are
+/// retried after a delay that starts at `initial` and doubles after each failure, up to
+/// `maximum`. If `attempts` is set, establishing the connection fails after that many retries.
+///
+/// The schedule applies while establishing connections. A connection lost during the
+/// computation is not re-established; the receiving thread reports it as a
+/// `PeerFailedEvent` and takes down the computation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Backoff {
+    /// Delay before the first retry.
+    pub initial: Duration,
+    /// Largest delay between retries.
+    pub maximum: Duration,
+    /// Number of retries before giving up, or `None` to retry indefinitely.
+    pub attempts: Option<usize>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial: Duration::from_millis(100),
+            maximum: Duration::from_secs(5),
+            attempts: None,
+        }
+    }
+}
+
+impl Backoff {
+    /// The sequence of delays between attempts.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely_communication::networking::Backoff;
+    ///
+    /// let backoff = Backoff {
+    ///     initial: Duration::from_millis(100),
+    ///     maximum: Duration::from_millis(300),
+    ///     attempts: Some(4),
+    /// };
+    ///
+    /// let delays = backoff.delays().map(|d| d.as_millis()).collect::<Vec<_>>();
+    /// assert_eq!(delays, vec![100, 200, 300, 300]);
+    /// ```
+    pub fn delays(&self) -> impl Iterator<Item=Duration> {
+        let maximum = self.maximum;
+        let delays = ::std::iter::successors(Some(self.initial.min(maximum)), move |delay| Some((*delay * 2).min(maximum)));
+        delays.take(self.attempts.unwrap_or(usize::MAX))
+    }
+}
+
+/// Options for establishing and using connections between processes.
+#[derive(Default)]
+pub struct ConnectionOptions {
+    /// Schedule for retrying failed connection attempts while connecting; connections lost
+    /// afterwards are not re-established.
+    pub retry: Backoff,
+    /// Preferred compression, used for connections to processes that prefer it too.
+    pub compression: Compression,
+    /// Optional conversion of each connection before use.
+    pub upgrade: Option<Box<StreamUpgrade>>,
+    /// The address on which to accept connections, if not this process's address.
+    pub bind: Option<String>,
+    /// The largest serialized size of a single record sent to other processes, if limited.
+    ///
+    /// Larger records are reported on standard error and dropped, rather than sent.
+    pub max_record: Option<usize>,
+}
+
+/// The addresses of a process in a cluster.
+///
+/// Other processes connect to the process at its advertised address, on which the process also
+/// listens unless it has a separate bind address. A process behind network address translation,
+/// or in a container, may listen on a local address, for example `0.0.0.0:2101`, and advertise
+/// the address through which it is reached.
+///
+/// In text, for example the lines of a hostfile, a peer is its advertised address, optionally
+/// followed by whitespace and its bind address.
+///
+/// # Examples
+/// ```
+/// use timely_communication::networking::PeerAddress;
+///
+/// let peer: PeerAddress = "host0.example.com:2101 0.0.0.0:2101".parse().unwrap();
+/// assert_eq!(peer, PeerAddress::new("host0.example.com:2101").bind("0.0.0.0:2101"));
+/// assert_eq!(peer.bind_address(), "0.0.0.0:2101");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PeerAddress {
+    /// The address at which other processes connect to the process.
+    pub advertised: String,
+    /// The address on which the process accepts connections, if not its advertised address.
+    pub bind: Option<String>,
+}
+
+impl PeerAddress {
+    /// A process that listens on, and is reached at, `advertised`.
+    pub fn new<S: Into<String>>(advertised: S) -> Self {
+        PeerAddress { advertised: advertised.into(), bind: None }
+    }
+    /// Sets the address on which the process accepts connections.
+    pub fn bind<S: Into<String>>(mut self, bind: S) -> Self {
+        self.bind = Some(bind.into());
+        self
+    }
+    /// The address on which the process accepts connections.
+    pub fn bind_address(&self) -> &str {
+        self.bind.as_ref().unwrap_or(&self.advertised)
+    }
+}
+
+impl std::str::FromStr for PeerAddress {
+    type Err = String;
+    fn from_str(text: &str) -> std::result::Result<Self, Self::Err> {
+        let mut words = text.split_whitespace();
+        let advertised = words.next().ok_or_else(|| "empty peer address".to_owned())?;
+        let bind = words.next();
+        if let Some(extra) = words.next() {
+            return Err(format!("unexpected text after peer address: {}", extra));
+        }
+        Ok(PeerAddress { advertised: advertised.to_owned(), bind: bind.map(|b| b.to_owned()) })
+    }
+}
+
 /// Creates socket connections from a list of host addresses.
 ///
 /// The item at index i in the resulting vec, is a Some(TcpSocket) to process i, except
 /// for item `my_index` which is None (no socket to self).
-pub fn create_sockets(addresses: Vec<String>, my_index: usize, noisy: bool) -> Result<Vec<Option<TcpStream>>> {
+pub fn create_sockets(addresses: Vec<String>, my_index: usize, noisy: bool, retry: Backoff) -> Result<Vec<Option<TcpStream>>> {
+    create_sockets_binding(addresses, None, my_index, noisy, retry)
+}
 
+/// Creates socket connections from a list of host addresses, accepting connections on `bind`.
+///
+/// This method behaves as `create_sockets`, except that connections from other processes are
+/// accepted on `bind`, if supplied, rather than on the address of this process in `addresses`.
+pub fn create_sockets_binding(addresses: Vec<String>, bind: Option<String>, my_index: usize, noisy: bool, retry: Backoff) -> Result<Vec<Option<TcpStream>>> {
+
+    if my_index >= addresses.len() {
+        return Err(Error::Config(format!("process {} has no address among {} addresses", my_index, addresses.len())).into_io());
+    }
+    let bind = bind.unwrap_or_else(|| addresses[my_index].clone());
     let hosts1 = Arc::new(addresses);
     let hosts2 = hosts1.clone();
 
-    let start_task = thread::spawn(move || start_connections(hosts1, my_index, noisy));
-    let await_task = thread::spawn(move || await_connections(hosts2, my_index, noisy));
+    let start_task = thread::spawn(move || start_connections(hosts1, my_index, noisy, retry));
+    let await_task = thread::spawn(move || await_connections_on(&bind, hosts2, my_index, noisy));
 
     let mut results = start_task.join().unwrap()?;
     results.push(None);
@@ -83,47 +228,210 @@ pub fn create_sockets(addresses: Vec<String>, my_index: usize, noisy: bool) -> R
 
 
 /// Result contains connections [0, my_index - 1].
-pub fn start_connections(addresses: Arc<Vec<String>>, my_index: usize, noisy: bool) -> Result<Vec<Option<TcpStream>>> {
-    let results = addresses.iter().take(my_index).enumerate().map(|(index, address)| {
+pub fn start_connections(addresses: Arc<Vec<String>>, my_index: usize, noisy: bool, retry: Backoff) -> Result<Vec<Option<TcpStream>>> {
+    addresses.iter().take(my_index).enumerate().map(|(index, address)| {
+        let mut delays = retry.delays();
         loop {
-            match TcpStream::connect(address) {
-                Ok(mut stream) => {
-                    stream.set_nodelay(true).expect("set_nodelay call failed");
-                    unsafe { encode(&HANDSHAKE_MAGIC, &mut stream) }.expect("failed to encode/send handshake magic");
-                    unsafe { encode(&(my_index as u64), &mut stream) }.expect("failed to encode/send worker index");
+            let connection = TcpStream::connect(address).and_then(|mut stream| {
+                stream.set_nodelay(true)?;
+                unsafe { encode(&HANDSHAKE_MAGIC, &mut stream) }?;
+                unsafe { encode(&(my_index as u64), &mut stream) }?;
+                Ok(stream)
+            });
+            match connection {
+                Ok(stream) => {
                     if noisy { println!("worker {}:\tconnection to worker {}", my_index, index); }
-                    break Some(stream);
+                    break Ok(Some(stream));
                 },
                 Err(error) => {
-                    println!("worker {}:\terror connecting to worker {}: {}; retrying", my_index, index, error);
-                    sleep(Duration::from_secs(1));
+                    match delays.next() {
+                        Some(delay) => {
+                            println!("worker {}:\terror connecting to worker {}: {}; retrying", my_index, index, error);
+                            sleep(delay);
+                        },
+                        None => {
+                            break Err(Error::Connect { process: index, error }.into_io());
+                        },
+                    }
                 },
             }
         }
-    }).collect();
-
-    Ok(results)
+    }).collect()
 }
 
 /// Result contains connections [my_index + 1, addresses.len() - 1].
+///
+/// Connections that close before completing the handshake are assumed to be retried by the
+/// connecting process, and are ignored.
 pub fn await_connections(addresses: Arc<Vec<String>>, my_index: usize, noisy: bool) -> Result<Vec<Option<TcpStream>>> {
+    let bind = addresses[my_index].clone();
+    await_connections_on(&bind, addresses, my_index, noisy)
+}
+
+/// Result contains connections [my_index + 1, addresses.len() - 1], accepted on `bind`.
+pub fn await_connections_on(bind: &str, addresses: Arc<Vec<String>>, my_index: usize, noisy: bool) -> Result<Vec<Option<TcpStream>>> {
     let mut results: Vec<_> = (0..(addresses.len() - my_index - 1)).map(|_| None).collect();
-    let listener = TcpListener::bind(&addresses[my_index][..])?;
+    let listener = TcpListener::bind(bind).map_err(|error| Error::Bind { address: bind.to_owned(), error }.into_io())?;
 
-    for _ in (my_index + 1) .. addresses.len() {
+    let mut connected = my_index + 1;
+    while connected < addresses.len() {
         let mut stream = listener.accept()?.0;
         stream.set_nodelay(true).expect("set_nodelay call failed");
         let mut buffer = [0u8;16];
-        stream.read_exact(&mut buffer)?;
+        if let Err(error) = stream.read_exact(&mut buffer) {
+            println!("worker {}:\tincomplete handshake: {}; awaiting retry", my_index, error);
+            continue;
+        }
         let (magic, mut buffer) = unsafe { decode::<u64>(&mut buffer) }.expect("failed to decode magic");
         if magic != &HANDSHAKE_MAGIC {
-            return Err(io::Error::new(io::ErrorKind::InvalidData,
-                "received incorrect timely handshake"));
+            return Err(Error::Handshake("received incorrect timely handshake".to_owned()).into_io());
         }
         let identifier = unsafe { decode::<u64>(&mut buffer) }.expect("failed to decode worker index").0.clone() as usize;
-        results[identifier - my_index - 1] = Some(stream);
+        if identifier <= my_index || identifier >= addresses.len() {
+            return Err(Error::Handshake(format!("connection from process {}, which should not connect to process {}", identifier, my_index)).into_io());
+        }
+        if results[identifier - my_index - 1].replace(stream).is_none() {
+            connected += 1;
+        }
         if noisy { println!("worker {}:\tconnection from worker {}", my_index, identifier); }
     }
 
     Ok(results)
 }
+
+/// Connects `processes` processes in this process over loopback sockets.
+///
+/// Each pair of processes is connected by a socket accepted on an ephemeral port of `127.0.0.1`,
+/// so that no addresses need be chosen and connections are established before the call returns.
+/// Element `index` of the result contains the sockets of process `index`, suitable for
+/// `initialize_networking_from_sockets`.
+#[allow(clippy::needless_range_loop)]
+pub fn loopback_sockets(processes: usize) -> Result<Vec<Vec<Option<TcpStream>>>> {
+    let mut sockets: Vec<Vec<Option<TcpStream>>> = (0 .. processes).map(|_| (0 .. processes).map(|_| None).collect()).collect();
+    for lower in 0 .. processes {
+        for upper in lower + 1 .. processes {
+            let listener = TcpListener::bind("127.0.0.1:0")?;
+            let connected = TcpStream::connect(listener.local_addr()?)?;
+            let accepted = listener.accept()?.0;
+            connected.set_nodelay(true)?;
+            accepted.set_nodelay(true)?;
+            sockets[lower][upper] = Some(connected);
+            sockets[upper][lower] = Some(accepted);
+        }
+    }
+    Ok(sockets)
+}
+
+/// Mechanisms for moving bytes between processes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transport {
+    /// Connect all processes with TCP.
+    #[default]
+    Tcp,
+    /// Connect processes on the same host through shared memory, and others with TCP.
+    ///
+    /// This transport is only available on Linux.
+    SharedMemory,
+}
+
+impl std::str::FromStr for Transport {
+    type Err = String;
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name {
+            "tcp" => Ok(Transport::Tcp),
+            "shm" => Ok(Transport::SharedMemory),
+            _ => Err(format!("unrecognized transport: {} (expected \"tcp\" or \"shm\")", name)),
+        }
+    }
+}
+
+/// The writing half of a connection to another process.
+pub trait WriteHalf: Write + Send {
+    /// Indicates to the remote process that no further data will be written.
+    ///
+    /// The remote process must observe the end of its stream, even while the reading half of
+    /// this connection remains open.
+    fn shutdown(&mut self) -> Result<()>;
+}
+
+impl WriteHalf for TcpStream {
+    fn shutdown(&mut self) -> Result<()> {
+        TcpStream::shutdown(self, Shutdown::Write)
+    }
+}
+
+impl<W: WriteHalf + ?Sized> WriteHalf for Box<W> {
+    fn shutdown(&mut self) -> Result<()> {
+        (**self).shutdown()
+    }
+}
+
+/// Independently owned reading and writing halves of a connection to another process.
+pub type StreamHalves = (Box<dyn Read + Send>, Box<dyn WriteHalf>);
+
+/// Converts a connected socket into the halves timely communicates over.
+///
+/// The arguments are the socket, the index of this process, and the index of the remote
+/// process; the process with the larger index initiated the connection. The upgrade runs
+/// before any timely data is exchanged, and is the place to layer on authentication and
+/// encryption, for example by running a TLS handshake and sharing the resulting session
+/// between the two halves.
+pub type StreamUpgrade = dyn Fn(TcpStream, usize, usize) -> Result<StreamHalves> + Send + Sync;
+
+/// Splits a socket into halves without modifying the traffic.
+pub fn plain_halves(stream: TcpStream) -> Result<StreamHalves> {
+    Ok((Box::new(stream.try_clone()?), Box::new(stream)))
+}
+
+/// Exchanges the numbers of workers of each connected process, which must be equal.
+///
+/// Each process announces its number of workers on every connection, and then reads that of the
+/// remote process, failing with [`Error::WorkerCount`] if the two differ. Processes that disagree
+/// would otherwise route records to workers that do not exist.
+///
+/// The counts are the first bytes of each connection after the handshake, as eight little-endian
+/// bytes, and precede the exchange of compression preferences. This changes the connection
+/// protocol: processes that exchange counts cannot connect to processes that do not.
+pub fn exchange_worker_counts(streams: &mut [Option<StreamHalves>], threads: usize) -> Result<()> {
+    // Announce first to all processes, so that no process blocks on a read before writing.
+    for (_, writer) in streams.iter_mut().flatten() {
+        writer.write_all(&(threads as u64).to_le_bytes())?;
+        writer.flush()?;
+    }
+
+    for (process, stream) in streams.iter_mut().enumerate() {
+        if let Some((reader, _)) = stream {
+            let mut buffer = [0u8; 8];
+            reader.read_exact(&mut buffer)?;
+            let found = u64::from_le_bytes(buffer) as usize;
+            if found != threads {
+                return Err(Error::WorkerCount { process, expected: threads, found }.into_io());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Exchanges compression preferences with each connected process.
+///
+/// Each process announces its preferred compression on every connection, and then reads the
+/// preference of the remote process. The result indicates, for each connection, the compression
+/// that both ends have agreed upon, which is `Compression::None` unless the preferences match.
+pub fn negotiate_compression(streams: &mut [Option<StreamHalves>], preferred: Compression) -> Result<Vec<Compression>> {
+    // Announce first to all processes, so that no process blocks on a read before writing.
+    for (_, writer) in streams.iter_mut().flatten() {
+        writer.write_all(&preferred.code().to_le_bytes())?;
+        writer.flush()?;
+    }
+
+    streams.iter_mut().map(|stream| {
+        match stream {
+            Some((reader, _)) => {
+                let mut buffer = [0u8; 8];
+                reader.read_exact(&mut buffer)?;
+                Ok(preferred.negotiate(Compression::from_code(u64::from_le_bytes(buffer))))
+            },
+            None => Ok(Compression::None),
+        }
+    }).collect()
+}
@@ -0,0 +1,174 @@
+//! Discovery of cluster membership through a coordinator.
+//!
+//! Rather than supplying every process with the full list of addresses, each process may
+//! register its own address with a coordinator, and receive in return its index and the
+//! addresses of all processes. This suits schedulers that place processes dynamically, where
+//! addresses are only known once processes start.
+//!
+//! The coordinator is run with `serve`, and needs to know only how many processes to expect.
+//! Processes may request a specific index, or accept whichever index is free; the coordinator
+//! replies to all processes once the expected number have registered.
+//!
+//! # Examples
+//! ```
+//! use std::net::TcpListener;
+//! use timely_communication::networking::Backoff;
+//! use timely_communication::rendezvous::{serve, register};
+//!
+//! let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+//! let coordinator = listener.local_addr().unwrap().to_string();
+//! let server = std::thread::spawn(move || serve(listener, 2));
+//!
+//! let clients = (0 .. 2).map(|i| {
+//!     let coordinator = coordinator.clone();
+//!     std::thread::spawn(move || {
+//!         let address = format!("127.0.0.1:{}", 3100 + i);
+//!         register(&coordinator, &address, 2, None, Backoff::default()).unwrap()
+//!     })
+//! }).collect::<Vec<_>>();
+//!
+//! let results = clients.into_iter().map(|c| c.join().unwrap()).collect::<Vec<_>>();
+//! assert_ne!(results[0].0, results[1].0);
+//! assert_eq!(results[0].1, results[1].1);
+//! assert_eq!(server.join().unwrap().unwrap(), results[0].1);
+//! ```
+
+use std::io::{Read, Write, Result, Error, ErrorKind};
+use std::net::{TcpListener, TcpStream};
+use std::thread::sleep;
+
+use crate::networking::Backoff;
+
+// Sent first on each registration, to reject connections from other services.
+const RENDEZVOUS_MAGIC: u64 = 0x7e2d_0b41_c9a5_33f1;
+
+// Indicates that a process will accept any index.
+const ANY_INDEX: u64 = u64::MAX;
+
+fn write_u64<W: Write>(writer: &mut W, value: u64) -> Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn write_string<W: Write>(writer: &mut W, string: &str) -> Result<()> {
+    write_u64(writer, string.len() as u64)?;
+    writer.write_all(string.as_bytes())
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String> {
+    let mut bytes = vec![0u8; read_u64(reader)? as usize];
+    reader.read_exact(&mut bytes[..])?;
+    String::from_utf8(bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+}
+
+fn invalid(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+/// Coordinates the registration of `processes` processes, and returns their addresses.
+///
+/// Each registered process receives its index and the addresses of all processes, in order.
+/// Registrations that do not agree on the number of processes, or that request an index that
+/// is out of range or already taken, are rejected by closing their connection.
+pub fn serve(listener: TcpListener, processes: usize) -> Result<Vec<String>> {
+
+    let mut requested: Vec<Option<(TcpStream, String)>> = (0 .. processes).map(|_| None).collect();
+    let mut flexible = Vec::new();
+    let mut registered = 0;
+
+    while registered < processes {
+        let mut stream = listener.accept()?.0;
+        let registration = (|| -> Result<(u64, String)> {
+            if read_u64(&mut stream)? != RENDEZVOUS_MAGIC {
+                return Err(invalid("received incorrect rendezvous handshake".to_owned()));
+            }
+            let expected = read_u64(&mut stream)? as usize;
+            if expected != processes {
+                return Err(invalid(format!("registration expects {} processes, rather than {}", expected, processes)));
+            }
+            let index = read_u64(&mut stream)?;
+            let address = read_string(&mut stream)?;
+            Ok((index, address))
+        })();
+
+        match registration {
+            Ok((ANY_INDEX, address)) => {
+                flexible.push((stream, address));
+                registered += 1;
+            },
+            Ok((index, address)) if (index as usize) < processes && requested[index as usize].is_none() => {
+                requested[index as usize] = Some((stream, address));
+                registered += 1;
+            },
+            Ok((index, address)) => {
+                eprintln!("rendezvous: rejected {} requesting unavailable index {}", address, index);
+            },
+            Err(error) => {
+                eprintln!("rendezvous: rejected registration: {}", error);
+            },
+        }
+    }
+
+    // Assign flexible registrations to the remaining indices, in order of arrival.
+    let mut flexible = flexible.into_iter();
+    for slot in requested.iter_mut().filter(|slot| slot.is_none()) {
+        *slot = flexible.next();
+    }
+
+    let mut members: Vec<(TcpStream, String)> = requested.into_iter().map(|slot| slot.expect("unassigned index")).collect();
+    let addresses: Vec<String> = members.iter().map(|(_, address)| address.clone()).collect();
+
+    for (index, (stream, _)) in members.iter_mut().enumerate() {
+        write_u64(stream, index as u64)?;
+        write_u64(stream, addresses.len() as u64)?;
+        for address in addresses.iter() {
+            write_string(stream, address)?;
+        }
+        stream.flush()?;
+    }
+
+    Ok(addresses)
+}
+
+/// Registers `address` with the coordinator, and returns this process's index and all addresses.
+///
+/// The coordinator may not yet be running, and connection attempts are retried according to
+/// `retry`. If `index` is `None` the coordinator assigns any free index.
+pub fn register(coordinator: &str, address: &str, processes: usize, index: Option<usize>, retry: Backoff) -> Result<(usize, Vec<String>)> {
+
+    let mut delays = retry.delays();
+    let mut stream = loop {
+        match TcpStream::connect(coordinator) {
+            Ok(stream) => break stream,
+            Err(error) => {
+                match delays.next() {
+                    Some(delay) => {
+                        println!("error connecting to coordinator {}: {}; retrying", coordinator, error);
+                        sleep(delay);
+                    },
+                    None => return Err(Error::new(error.kind(), format!("failed to connect to coordinator {}: {}", coordinator, error))),
+                }
+            },
+        }
+    };
+
+    write_u64(&mut stream, RENDEZVOUS_MAGIC)?;
+    write_u64(&mut stream, processes as u64)?;
+    write_u64(&mut stream, index.map(|i| i as u64).unwrap_or(ANY_INDEX))?;
+    write_string(&mut stream, address)?;
+    stream.flush()?;
+
+    let assigned = read_u64(&mut stream)? as usize;
+    let count = read_u64(&mut stream)? as usize;
+    if count != processes || assigned >= processes {
+        return Err(invalid(format!("coordinator assigned index {} of {} processes", assigned, count)));
+    }
+    let addresses = (0 .. count).map(|_| read_string(&mut stream)).collect::<Result<Vec<_>>>()?;
+
+    Ok((assigned, addresses))
+}
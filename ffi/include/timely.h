@@ -0,0 +1,62 @@
+/*
+ * A C interface for building and driving simple timely dataflows.
+ *
+ * Pipelines carry uint64_t records with uint64_t timestamps. Each operator calls a host function
+ * with a host-supplied context pointer. Objects returned by this interface are owned by the host
+ * and are released by the matching _free function. See the documentation of the timely_ffi crate
+ * for details.
+ */
+
+#ifndef TIMELY_H
+#define TIMELY_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct TimelyWorker TimelyWorker;
+typedef struct TimelyPipeline TimelyPipeline;
+typedef struct TimelyDataflow TimelyDataflow;
+
+typedef uint64_t (*TimelyMapFn)(void *context, uint64_t record);
+typedef bool (*TimelyFilterFn)(void *context, uint64_t record);
+typedef uint64_t (*TimelyKeyFn)(void *context, uint64_t record);
+typedef void (*TimelyInspectFn)(void *context, uint64_t time, uint64_t record);
+typedef void (*TimelyMainFn)(void *context, TimelyWorker *worker);
+
+/* Workers. */
+TimelyWorker *timely_worker_new(void);
+void timely_worker_free(TimelyWorker *worker);
+size_t timely_worker_index(const TimelyWorker *worker);
+size_t timely_worker_peers(const TimelyWorker *worker);
+bool timely_worker_step(TimelyWorker *worker);
+bool timely_execute(size_t threads, TimelyMainFn main, void *context);
+
+/* Pipelines. */
+TimelyPipeline *timely_pipeline_new(TimelyWorker *worker);
+void timely_pipeline_free(TimelyPipeline *pipeline);
+bool timely_pipeline_map(TimelyPipeline *pipeline, TimelyMapFn map, void *context);
+bool timely_pipeline_filter(TimelyPipeline *pipeline, TimelyFilterFn filter, void *context);
+bool timely_pipeline_exchange(TimelyPipeline *pipeline, TimelyKeyFn key, void *context);
+bool timely_pipeline_inspect(TimelyPipeline *pipeline, TimelyInspectFn inspect, void *context);
+TimelyDataflow *timely_pipeline_build(TimelyPipeline *pipeline);
+
+/* Dataflows. */
+bool timely_dataflow_send(TimelyDataflow *dataflow, uint64_t record);
+bool timely_dataflow_advance_to(TimelyDataflow *dataflow, uint64_t time);
+uint64_t timely_dataflow_time(const TimelyDataflow *dataflow);
+bool timely_dataflow_less_than(const TimelyDataflow *dataflow, uint64_t time);
+void timely_dataflow_free(TimelyDataflow *dataflow);
+
+/* Errors: whether a function called on this thread has panicked since the last call. */
+bool timely_panicked(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,370 @@
+//! A C interface for building and driving simple timely dataflows.
+//!
+//! The interface lets programs in other languages build pipelines of `uint64_t` records and
+//! timestamps, whose operators call back into the host program, and drive them to completion. It
+//! is a foundation for bindings to other languages, and is declared for C in `include/timely.h`.
+//!
+//! A pipeline starts at an input, is extended by maps, filters, exchanges, and inspections, each
+//! calling a function pointer with a host-supplied context pointer, and is built into a dataflow.
+//! The dataflow accepts records and advances its input, and reports through its probe whether
+//! the pipeline has caught up with some time. Dataflows only make progress as their worker steps.
+//!
+//! A worker is either created by [`timely_worker_new`], in which case it runs in the calling
+//! thread and only steps when [`timely_worker_step`] is called, or presented to the host by
+//! [`timely_execute`], which runs several workers in threads of their own.
+//!
+//! Each object returned by the interface is owned by the host, and must be released by the
+//! matching `_free` function. Functions accepting null pointers do nothing, and return `false`,
+//! zero, or null as appropriate. Panics do not unwind into the host: a function that panics
+//! returns as if presented with null pointers, and [`timely_panicked`] reports that it did.
+//!
+//! # Examples
+//!
+//! The interface is equally usable from Rust.
+//!
+//! ```
+//! use std::os::raw::c_void;
+//! use timely_ffi::*;
+//!
+//! extern "C" fn double(_context: *mut c_void, record: u64) -> u64 { record * 2 }
+//! extern "C" fn sum(context: *mut c_void, _time: u64, record: u64) {
+//!     unsafe { *(context as *mut u64) += record; }
+//! }
+//!
+//! let mut total = 0u64;
+//! unsafe {
+//!     let worker = timely_worker_new();
+//!     let pipeline = timely_pipeline_new(worker);
+//!     timely_pipeline_map(pipeline, double, std::ptr::null_mut());
+//!     timely_pipeline_inspect(pipeline, sum, &mut total as *mut u64 as *mut c_void);
+//!     let dataflow = timely_pipeline_build(pipeline);
+//!
+//!     for round in 0 .. 10 {
+//!         timely_dataflow_send(dataflow, round);
+//!         timely_dataflow_advance_to(dataflow, round + 1);
+//!         while timely_dataflow_less_than(dataflow, round + 1) {
+//!             timely_worker_step(worker);
+//!         }
+//!     }
+//!
+//!     timely_dataflow_free(dataflow);
+//!     while timely_worker_step(worker) { }
+//!     timely_worker_free(worker);
+//! }
+//! assert_eq!(total, 90);
+//! ```
+
+#![forbid(missing_docs)]
+
+use std::cell::Cell;
+use std::os::raw::c_void;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use timely::communication::Allocator;
+use timely::communication::allocator::Generic;
+use timely::communication::allocator::thread::Thread;
+use timely::dataflow::{InputHandle, ProbeHandle};
+use timely::dataflow::operators::{Input, Map, Filter, Exchange, Inspect, Probe};
+use timely::worker::Worker;
+use timely::WorkerConfig;
+
+/// A function applied to each record, producing a record.
+pub type TimelyMapFn = extern "C" fn(context: *mut c_void, record: u64) -> u64;
+/// A function applied to each record, indicating whether to retain it.
+pub type TimelyFilterFn = extern "C" fn(context: *mut c_void, record: u64) -> bool;
+/// A function applied to each record, producing the key by which it is routed to a worker.
+pub type TimelyKeyFn = extern "C" fn(context: *mut c_void, record: u64) -> u64;
+/// A function applied to each record, along with its timestamp.
+pub type TimelyInspectFn = extern "C" fn(context: *mut c_void, time: u64, record: u64);
+/// A function invoked with a worker, on each worker's thread.
+pub type TimelyMainFn = extern "C" fn(context: *mut c_void, worker: *mut TimelyWorker);
+
+/// A host-supplied pointer, passed back to the host's functions.
+#[derive(Copy, Clone)]
+struct Context(*mut c_void);
+
+// The host is responsible for the use of its contexts from other threads.
+unsafe impl Send for Context { }
+unsafe impl Sync for Context { }
+
+/// A timely dataflow worker.
+pub struct TimelyWorker {
+    worker: Worker<Allocator>,
+}
+
+/// A pipeline under construction, from an input through a sequence of operators.
+pub struct TimelyPipeline {
+    worker: Worker<Allocator>,
+    stages: Vec<Stage>,
+}
+
+/// An operator of a pipeline, and the context with which to call the host.
+enum Stage {
+    Map(TimelyMapFn, Context),
+    Filter(TimelyFilterFn, Context),
+    Exchange(TimelyKeyFn, Context),
+    Inspect(TimelyInspectFn, Context),
+}
+
+/// A built pipeline, with its input and a probe of its output.
+pub struct TimelyDataflow {
+    input: InputHandle<u64, u64>,
+    probe: ProbeHandle<u64>,
+}
+
+/// Creates a single worker, which only steps when `timely_worker_step` is called.
+#[no_mangle]
+pub extern "C" fn timely_worker_new() -> *mut TimelyWorker {
+    guard(std::ptr::null_mut(), || {
+        let worker = Worker::new(WorkerConfig::default(), Generic::Thread(Thread::new()));
+        Box::into_raw(Box::new(TimelyWorker { worker }))
+    })
+}
+
+/// Releases a worker, which must no longer have incomplete dataflows.
+///
+/// # Safety
+///
+/// `worker` must be null or returned by `timely_worker_new` or presented by `timely_execute`, and
+/// not already released.
+#[no_mangle]
+pub unsafe extern "C" fn timely_worker_free(worker: *mut TimelyWorker) {
+    guard((), || if !worker.is_null() { drop(Box::from_raw(worker)); })
+}
+
+/// The index of the worker, out of the number of peers.
+///
+/// # Safety
+///
+/// `worker` must be null or a live worker.
+#[no_mangle]
+pub unsafe extern "C" fn timely_worker_index(worker: *const TimelyWorker) -> usize {
+    guard(0, || worker.as_ref().map(|w| w.worker.index()).unwrap_or(0))
+}
+
+/// The number of workers in the computation.
+///
+/// # Safety
+///
+/// `worker` must be null or a live worker.
+#[no_mangle]
+pub unsafe extern "C" fn timely_worker_peers(worker: *const TimelyWorker) -> usize {
+    guard(0, || worker.as_ref().map(|w| w.worker.peers()).unwrap_or(0))
+}
+
+/// Steps the worker once, without waiting for events, and indicates if dataflows remain.
+///
+/// # Safety
+///
+/// `worker` must be null or a live worker.
+#[no_mangle]
+pub unsafe extern "C" fn timely_worker_step(worker: *mut TimelyWorker) -> bool {
+    guard(false, || worker.as_mut().map(|w| w.worker.step()).unwrap_or(false))
+}
+
+/// Runs `main` on each of `threads` workers, in threads of their own, and awaits their completion.
+///
+/// Each invocation of `main` owns the worker it is presented, and must release it. Any dataflows
+/// remaining once `main` returns are run to completion before the thread exits. The `context`
+/// pointer is shared by all invocations, which may run concurrently. Returns `false` if the
+/// workers could not be started or if any of them panicked.
+///
+/// # Safety
+///
+/// `context` must be usable from each worker's thread, for as long as the workers run.
+#[no_mangle]
+pub unsafe extern "C" fn timely_execute(threads: usize, main: TimelyMainFn, context: *mut c_void) -> bool {
+    let context = Context(context);
+    guard(false, || {
+        let guards = timely::execute(timely::Config::process(threads.max(1)), move |worker| {
+            let context = context;
+            let handle = Box::into_raw(Box::new(TimelyWorker { worker: worker.clone() }));
+            main(context.0, handle);
+        });
+        match guards {
+            Ok(guards) => guards.join().into_iter().all(|result| result.is_ok()),
+            Err(_) => false,
+        }
+    })
+}
+
+/// Starts a pipeline at a new input of `worker`.
+///
+/// The pipeline is not part of a dataflow until built by `timely_pipeline_build`.
+///
+/// # Safety
+///
+/// `worker` must be null or a live worker.
+#[no_mangle]
+pub unsafe extern "C" fn timely_pipeline_new(worker: *mut TimelyWorker) -> *mut TimelyPipeline {
+    guard(std::ptr::null_mut(), || match worker.as_ref() {
+        Some(w) => Box::into_raw(Box::new(TimelyPipeline { worker: w.worker.clone(), stages: Vec::new() })),
+        None => std::ptr::null_mut(),
+    })
+}
+
+/// Releases a pipeline without building it.
+///
+/// # Safety
+///
+/// `pipeline` must be null or a live pipeline.
+#[no_mangle]
+pub unsafe extern "C" fn timely_pipeline_free(pipeline: *mut TimelyPipeline) {
+    guard((), || if !pipeline.is_null() { drop(Box::from_raw(pipeline)); })
+}
+
+/// Replaces each record of the pipeline with `map(context, record)`.
+///
+/// # Safety
+///
+/// `pipeline` must be null or a live pipeline, and `context` must remain valid while the
+/// dataflow runs.
+#[no_mangle]
+pub unsafe extern "C" fn timely_pipeline_map(pipeline: *mut TimelyPipeline, map: TimelyMapFn, context: *mut c_void) -> bool {
+    push_stage(pipeline, Stage::Map(map, Context(context)))
+}
+
+/// Retains the records of the pipeline for which `filter(context, record)` is true.
+///
+/// # Safety
+///
+/// `pipeline` must be null or a live pipeline, and `context` must remain valid while the
+/// dataflow runs.
+#[no_mangle]
+pub unsafe extern "C" fn timely_pipeline_filter(pipeline: *mut TimelyPipeline, filter: TimelyFilterFn, context: *mut c_void) -> bool {
+    push_stage(pipeline, Stage::Filter(filter, Context(context)))
+}
+
+/// Routes each record of the pipeline to the worker indicated by `key(context, record)`.
+///
+/// Records with equal keys arrive at the same worker.
+///
+/// # Safety
+///
+/// `pipeline` must be null or a live pipeline, and `context` must remain valid while the
+/// dataflow runs.
+#[no_mangle]
+pub unsafe extern "C" fn timely_pipeline_exchange(pipeline: *mut TimelyPipeline, key: TimelyKeyFn, context: *mut c_void) -> bool {
+    push_stage(pipeline, Stage::Exchange(key, Context(context)))
+}
+
+/// Calls `inspect(context, time, record)` for each record of the pipeline.
+///
+/// # Safety
+///
+/// `pipeline` must be null or a live pipeline, and `context` must remain valid while the
+/// dataflow runs.
+#[no_mangle]
+pub unsafe extern "C" fn timely_pipeline_inspect(pipeline: *mut TimelyPipeline, inspect: TimelyInspectFn, context: *mut c_void) -> bool {
+    push_stage(pipeline, Stage::Inspect(inspect, Context(context)))
+}
+
+/// Builds the pipeline into a dataflow of its worker, releasing the pipeline.
+///
+/// # Safety
+///
+/// `pipeline` must be null or a live pipeline, which is no longer live once this returns.
+#[no_mangle]
+pub unsafe extern "C" fn timely_pipeline_build(pipeline: *mut TimelyPipeline) -> *mut TimelyDataflow {
+    if pipeline.is_null() { return std::ptr::null_mut(); }
+    let TimelyPipeline { mut worker, stages } = *Box::from_raw(pipeline);
+    guard(std::ptr::null_mut(), move || {
+        let mut input = InputHandle::new();
+        let mut probe = ProbeHandle::new();
+        worker.dataflow(|scope| {
+            let mut stream = scope.input_from(&mut input);
+            for stage in stages {
+                stream = match stage {
+                    Stage::Map(map, context) => stream.map(move |x| map(context.0, x)),
+                    Stage::Filter(filter, context) => stream.filter(move |x| filter(context.0, *x)),
+                    Stage::Exchange(key, context) => stream.exchange(move |x| key(context.0, *x)),
+                    Stage::Inspect(inspect, context) => stream.inspect_time(move |t, x| inspect(context.0, *t, *x)),
+                };
+            }
+            stream.probe_with(&mut probe);
+        });
+        Box::into_raw(Box::new(TimelyDataflow { input, probe }))
+    })
+}
+
+/// Introduces `record` at the current time of the dataflow's input.
+///
+/// # Safety
+///
+/// `dataflow` must be null or a live dataflow.
+#[no_mangle]
+pub unsafe extern "C" fn timely_dataflow_send(dataflow: *mut TimelyDataflow, record: u64) -> bool {
+    guard(false, || dataflow.as_mut().map(|d| d.input.send(record)).is_some())
+}
+
+/// Advances the dataflow's input to `time`, which must not be less than its current time.
+///
+/// # Safety
+///
+/// `dataflow` must be null or a live dataflow.
+#[no_mangle]
+pub unsafe extern "C" fn timely_dataflow_advance_to(dataflow: *mut TimelyDataflow, time: u64) -> bool {
+    guard(false, || match dataflow.as_mut() {
+        Some(d) if *d.input.time() <= time => { d.input.advance_to(time); true },
+        _ => false,
+    })
+}
+
+/// The current time of the dataflow's input.
+///
+/// # Safety
+///
+/// `dataflow` must be null or a live dataflow.
+#[no_mangle]
+pub unsafe extern "C" fn timely_dataflow_time(dataflow: *const TimelyDataflow) -> u64 {
+    guard(0, || dataflow.as_ref().map(|d| *d.input.time()).unwrap_or(0))
+}
+
+/// Indicates whether records at times less than `time` may yet reach the end of the pipeline.
+///
+/// # Safety
+///
+/// `dataflow` must be null or a live dataflow.
+#[no_mangle]
+pub unsafe extern "C" fn timely_dataflow_less_than(dataflow: *const TimelyDataflow, time: u64) -> bool {
+    guard(false, || dataflow.as_ref().map(|d| d.probe.less_than(&time)).unwrap_or(false))
+}
+
+/// Closes the dataflow's input and releases the dataflow.
+///
+/// The worker continues to run the dataflow until it has processed the records sent to it.
+///
+/// # Safety
+///
+/// `dataflow` must be null or a live dataflow, which is no longer live once this returns.
+#[no_mangle]
+pub unsafe extern "C" fn timely_dataflow_free(dataflow: *mut TimelyDataflow) {
+    guard((), || if !dataflow.is_null() { drop(Box::from_raw(dataflow)); })
+}
+
+/// Indicates whether a function called on this thread has panicked since this was last called.
+///
+/// A function that panics returns `false`, zero, or null. The objects presented to it may be left
+/// in an inconsistent state, and should only be released.
+#[no_mangle]
+pub extern "C" fn timely_panicked() -> bool {
+    PANICKED.with(|panicked| panicked.replace(false))
+}
+
+thread_local! {
+    /// Whether a function called on this thread has panicked, since `timely_panicked` was called.
+    static PANICKED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `body`, returning `failed` in place of a panic, which must not unwind into the host.
+fn guard<R>(failed: R, body: impl FnOnce() -> R) -> R {
+    catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|_| {
+        PANICKED.with(|panicked| panicked.set(true));
+        failed
+    })
+}
+
+/// Appends `stage` to the pipeline, if it is not null.
+unsafe fn push_stage(pipeline: *mut TimelyPipeline, stage: Stage) -> bool {
+    guard(false, || pipeline.as_mut().map(|p| p.stages.push(stage)).is_some())
+}
@@ -0,0 +1,117 @@
+extern crate timely_ffi;
+
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use timely_ffi::*;
+
+// Counts of records inspected, by worker.
+struct Counts {
+    seen: [AtomicU64; 2],
+    misrouted: AtomicU64,
+}
+
+extern "C" fn even(_context: *mut c_void, record: u64) -> bool { record & 1 == 0 }
+extern "C" fn key(_context: *mut c_void, record: u64) -> u64 { record / 2 }
+
+extern "C" fn main_0(_context: *mut c_void, _worker: *mut TimelyWorker) { }
+
+// Each worker counts the records it sees, and whether they were routed to it by key.
+extern "C" fn main_2(context: *mut c_void, worker: *mut TimelyWorker) {
+    unsafe {
+        let index = timely_worker_index(worker);
+        let peers = timely_worker_peers(worker);
+        let pipeline = timely_pipeline_new(worker);
+        timely_pipeline_filter(pipeline, even, std::ptr::null_mut());
+        timely_pipeline_exchange(pipeline, key, std::ptr::null_mut());
+        timely_pipeline_inspect(pipeline, inspect(index), context);
+        let dataflow = timely_pipeline_build(pipeline);
+
+        for record in 0 .. 100 {
+            if record as usize % peers == index {
+                timely_dataflow_send(dataflow, record);
+            }
+        }
+        timely_dataflow_advance_to(dataflow, 1);
+        while timely_dataflow_less_than(dataflow, 1) {
+            timely_worker_step(worker);
+        }
+
+        timely_dataflow_free(dataflow);
+        while timely_worker_step(worker) { }
+        timely_worker_free(worker);
+    }
+}
+
+fn inspect(index: usize) -> TimelyInspectFn {
+    extern "C" fn at_0(context: *mut c_void, _time: u64, record: u64) { observe(context, 0, record) }
+    extern "C" fn at_1(context: *mut c_void, _time: u64, record: u64) { observe(context, 1, record) }
+    if index == 0 { at_0 } else { at_1 }
+}
+
+fn observe(context: *mut c_void, index: usize, record: u64) {
+    let counts = unsafe { &*(context as *const Counts) };
+    counts.seen[index].fetch_add(1, Ordering::SeqCst);
+    if (record / 2) % 2 != index as u64 {
+        counts.misrouted.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// Two workers filter and exchange records through host callbacks.
+#[test]
+fn execute_2w() {
+    let counts = Counts { seen: [AtomicU64::new(0), AtomicU64::new(0)], misrouted: AtomicU64::new(0) };
+    let context = &counts as *const Counts as *mut c_void;
+    assert!(unsafe { timely_execute(2, main_2, context) });
+    assert_eq!(counts.seen[0].load(Ordering::SeqCst) + counts.seen[1].load(Ordering::SeqCst), 50);
+    assert_eq!(counts.misrouted.load(Ordering::SeqCst), 0);
+}
+
+// Null arguments are ignored rather than dereferenced.
+#[test]
+fn null_arguments() {
+    unsafe {
+        assert!(timely_pipeline_new(std::ptr::null_mut()).is_null());
+        assert!(!timely_pipeline_filter(std::ptr::null_mut(), even, std::ptr::null_mut()));
+        assert!(timely_pipeline_build(std::ptr::null_mut()).is_null());
+        assert!(!timely_dataflow_send(std::ptr::null_mut(), 0));
+        assert!(!timely_worker_step(std::ptr::null_mut()));
+        assert!(timely_execute(1, main_0, std::ptr::null_mut()));
+    }
+}
+
+// A pipeline built from within a callback of a running dataflow, which the worker cannot install.
+struct Nested {
+    pipeline: *mut TimelyPipeline,
+    built: *mut TimelyDataflow,
+    panicked: bool,
+}
+
+extern "C" fn build_nested(context: *mut c_void, record: u64) -> u64 {
+    let nested = unsafe { &mut *(context as *mut Nested) };
+    if !nested.pipeline.is_null() {
+        nested.built = unsafe { timely_pipeline_build(std::mem::replace(&mut nested.pipeline, std::ptr::null_mut())) };
+        nested.panicked = timely_panicked();
+    }
+    record
+}
+
+// Panics are caught at the interface, rather than unwinding into the host.
+#[test]
+fn panics_are_caught() {
+    unsafe {
+        let worker = timely_worker_new();
+        let mut nested = Nested { pipeline: timely_pipeline_new(worker), built: std::ptr::null_mut(), panicked: false };
+        let pipeline = timely_pipeline_new(worker);
+        timely_pipeline_map(pipeline, build_nested, &mut nested as *mut Nested as *mut c_void);
+        let dataflow = timely_pipeline_build(pipeline);
+        timely_dataflow_send(dataflow, 0);
+        timely_dataflow_advance_to(dataflow, 1);
+        timely_worker_step(worker);
+        assert!(nested.built.is_null());
+        assert!(nested.panicked);
+        assert!(!timely_panicked());
+        timely_dataflow_free(dataflow);
+        timely_worker_free(worker);
+    }
+}
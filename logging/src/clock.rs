@@ -0,0 +1,175 @@
+//! The clock from which timely reads instants.
+//!
+//! Loggers, and the workers and operators of timely, read instants through a [`Clock`]. The
+//! [`SystemClock`] reads the clock above, and a [`MockClock`] moves only when its owner advances
+//! it, which lets tests of time-dependent behavior control time exactly. Simulations may advance a
+//! mock clock as their own notion of time passes, and hosts without a system clock may supply a
+//! clock of their own, advanced from a callback that knows the time.
+//!
+//! On most targets [`Instant`] is `std::time::Instant`. On `wasm32-unknown-unknown`, which has no
+//! system clock, it is instead a time elapsed since an origin that clocks define, with the methods
+//! of `std::time::Instant` other than `now` and `elapsed`. There the [`SystemClock`] panics when
+//! read, and workers must be given another clock, such as a [`MockClock`].
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// A source of instants.
+pub trait Clock: Debug+Send+Sync+'static {
+    /// The current instant of the clock.
+    fn now(&self) -> Instant;
+    /// The time elapsed from `earlier` to the current instant, or zero if `earlier` is later.
+    fn elapsed(&self, earlier: Instant) -> Duration {
+        self.now().saturating_duration_since(earlier)
+    }
+}
+
+/// The clock of [`Instant::now`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    fn now(&self) -> Instant { Instant::now() }
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    fn now(&self) -> Instant { panic!("this target has no system clock; supply another clock") }
+}
+
+/// A clock that moves only when advanced.
+///
+/// Clones of a mock clock share its time, so that a test may hold one clone and advance it while
+/// workers read another.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use timely_logging::clock::{Clock, MockClock};
+///
+/// let clock = MockClock::new();
+/// let start = clock.now();
+/// clock.advance(Duration::from_secs(5));
+/// assert_eq!(clock.elapsed(start), Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    start: Instant,
+    // Nanoseconds since `start`.
+    elapsed: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    /// A clock reading the current instant of the system clock, until it is advanced.
+    ///
+    /// On targets without a system clock, the clock instead starts from the origin of [`Instant`].
+    pub fn new() -> Self {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        let start = Instant::now();
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        let start = Instant::default();
+        MockClock { start, elapsed: Arc::new(AtomicU64::new(0)) }
+    }
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.elapsed.fetch_add(duration.as_nanos() as u64, Ordering::SeqCst);
+    }
+    /// Moves the clock to `elapsed` since it was created.
+    ///
+    /// The clock never moves backwards, and requests to do so are ignored.
+    pub fn advance_to(&self, elapsed: Duration) {
+        self.elapsed.fetch_max(elapsed.as_nanos() as u64, Ordering::SeqCst);
+    }
+    /// The time the clock has been advanced since it was created.
+    pub fn elapsed_since_start(&self) -> Duration {
+        Duration::from_nanos(self.elapsed.load(Ordering::SeqCst))
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self { MockClock::new() }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant { self.start + self.elapsed_since_start() }
+}
+
+/// The clock shared by default, which is the [`SystemClock`].
+pub fn system() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use std::time::Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub use self::origin::Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod origin {
+
+    use std::ops::{Add, AddAssign, Sub, SubAssign};
+    use std::time::Duration;
+
+    /// An instant, as the time elapsed since an origin.
+    ///
+    /// The default instant is the origin, from which clocks build the instants they read.
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct Instant(Duration);
+
+    impl Instant {
+        /// The time elapsed from `earlier` to this instant, or zero if `earlier` is later.
+        pub fn duration_since(&self, earlier: Instant) -> Duration {
+            self.saturating_duration_since(earlier)
+        }
+        /// The time elapsed from `earlier` to this instant, or `None` if `earlier` is later.
+        pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+            self.0.checked_sub(earlier.0)
+        }
+        /// The time elapsed from `earlier` to this instant, or zero if `earlier` is later.
+        pub fn saturating_duration_since(&self, earlier: Instant) -> Duration {
+            self.0.saturating_sub(earlier.0)
+        }
+        /// The instant `duration` after this one, if it can be represented.
+        pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+            self.0.checked_add(duration).map(Instant)
+        }
+        /// The instant `duration` before this one, if it can be represented.
+        pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+            self.0.checked_sub(duration).map(Instant)
+        }
+    }
+
+    impl Add<Duration> for Instant {
+        type Output = Instant;
+        fn add(self, duration: Duration) -> Instant {
+            self.checked_add(duration).expect("overflow when adding duration to instant")
+        }
+    }
+
+    impl AddAssign<Duration> for Instant {
+        fn add_assign(&mut self, duration: Duration) {
+            *self = *self + duration;
+        }
+    }
+
+    impl Sub<Duration> for Instant {
+        type Output = Instant;
+        fn sub(self, duration: Duration) -> Instant {
+            self.checked_sub(duration).expect("overflow when subtracting duration from instant")
+        }
+    }
+
+    impl SubAssign<Duration> for Instant {
+        fn sub_assign(&mut self, duration: Duration) {
+            *self = *self - duration;
+        }
+    }
+
+    impl Sub<Instant> for Instant {
+        type Output = Duration;
+        fn sub(self, other: Instant) -> Duration {
+            self.duration_since(other)
+        }
+    }
+}
@@ -1,17 +1,27 @@
 
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::any::Any;
 use std::collections::HashMap;
-use std::time::{Instant, Duration};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod clock;
+
+use crate::clock::{Clock, Instant};
+
+// A logger of some type, and the controls that do not need its type.
+type Entry = (Box<dyn Any>, Box<dyn Control>);
 
 pub struct Registry<Id> {
     /// A worker-specific identifier.
     id: Id,
     /// A map from names to typed loggers.
-    map: HashMap<String, (Box<dyn Any>, Box<dyn Flush>)>,
+    map: HashMap<String, Entry>,
     /// An instant common to all logging statements.
     time: Instant,
+    /// The clock from which loggers read the time of their events.
+    clock: Arc<dyn Clock>,
 }
 
 impl<Id: Clone+'static> Registry<Id> {
@@ -31,7 +41,47 @@ impl<Id: Clone+'static> Registry<Id> {
         name: &str,
         action: F) -> Option<Box<dyn Any>>
     {
-        let logger = Logger::<T, Id>::new(self.time.clone(), Duration::default(), self.id.clone(), action);
+        let logger = Logger::<T, Id>::new(self.time, Duration::default(), self.id.clone(), action).with_clock(self.clock.clone());
+        self.insert_logger(name, logger)
+    }
+
+    /// Binds a log name to an action on those log events accepted by `filter`.
+    ///
+    /// Events rejected by the filter are discarded as they are logged, and neither buffered nor
+    /// presented to the action. See [`Sampler`] for a filter accepting a fraction of events.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    /// use std::time::Instant;
+    /// use timely_logging::{Registry, Sampler};
+    ///
+    /// let accepted = Rc::new(Cell::new(0));
+    /// let counter = accepted.clone();
+    /// let mut registry = Registry::new(Instant::now(), 0);
+    /// let mut sampler = Sampler::new(0.5, 7);
+    /// registry.insert_filtered::<u64,_,_>("numbers", move |x| x % 2 == 0 && sampler.sample(), move |_time, data| {
+    ///     assert!(data.iter().all(|(_, _, x)| x % 2 == 0));
+    ///     counter.set(counter.get() + data.len());
+    /// });
+    ///
+    /// let mut logger = registry.get::<u64>("numbers").unwrap();
+    /// logger.log_many(0 .. 100u64);
+    /// logger.flush();
+    /// assert!(0 < accepted.get() && accepted.get() < 50);
+    /// ```
+    pub fn insert_filtered<T, P, F>(
+        &mut self,
+        name: &str,
+        filter: P,
+        action: F) -> Option<Box<dyn Any>>
+    where
+        T: 'static,
+        P: FnMut(&T)->bool+'static,
+        F: FnMut(&Duration, &mut Vec<(Duration, Id, T)>)+'static,
+    {
+        let logger = Logger::<T, Id>::new(self.time, Duration::default(), self.id.clone(), action).with_clock(self.clock.clone()).with_filter(filter);
         self.insert_logger(name, logger)
     }
 
@@ -44,14 +94,84 @@ impl<Id: Clone+'static> Registry<Id> {
         self.map.insert(name.to_owned(), (Box::new(logger.clone()), Box::new(logger))).map(|x| x.0)
     }
 
-    /// Removes a bound logger.
+    /// Directs the events of the logger bound to `name` to `action`, or binds a new logger.
+    ///
+    /// Unlike `insert`, which leaves loggers already handed out writing to the old action, this
+    /// redirects every handle of the bound logger, including those held by running dataflows.
+    /// Events buffered by the logger are first flushed to the old action, which is then dropped,
+    /// closing its stream. Returns `true` if a logger of type `T` was bound to `name`, and `false`
+    /// if a new logger was bound, which only handles retrieved hereafter will use.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use std::time::Instant;
+    /// use timely_logging::Registry;
+    ///
+    /// let (old, new) = (Rc::new(RefCell::new(Vec::new())), Rc::new(RefCell::new(Vec::new())));
+    /// let mut registry = Registry::new(Instant::now(), 0);
+    /// let sink = old.clone();
+    /// registry.insert::<u64,_>("numbers", move |_time, data| sink.borrow_mut().extend(data.drain(..).map(|x| x.2)));
+    ///
+    /// let logger = registry.get::<u64>("numbers").unwrap();
+    /// logger.log(0u64);
+    /// let sink = new.clone();
+    /// assert!(registry.replace::<u64,_>("numbers", move |_time, data| sink.borrow_mut().extend(data.drain(..).map(|x| x.2))));
+    /// logger.log(1u64);
+    /// drop(logger);
+    ///
+    /// assert_eq!(*old.borrow(), vec![0]);
+    /// assert_eq!(*new.borrow(), vec![1]);
+    /// ```
+    pub fn replace<T: 'static, F: FnMut(&Duration, &mut Vec<(Duration, Id, T)>)+'static>(
+        &mut self,
+        name: &str,
+        action: F) -> bool
+    {
+        match self.map.get(name).and_then(|entry| entry.0.downcast_ref::<Logger<T, Id>>()) {
+            Some(logger) => {
+                logger.redirect(action);
+                true
+            },
+            None => {
+                self.insert(name, action);
+                false
+            },
+        }
+    }
+
+    /// Pauses or resumes the logger bound to `name`, and all of its handles.
+    ///
+    /// A paused logger flushes the events it has buffered, and discards events logged until it
+    /// is resumed, while its action continues to learn of the passage of time. Returns `false`
+    /// if no logger is bound to `name`.
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        match self.map.get_mut(name) {
+            Some(entry) => {
+                entry.1.set_enabled(enabled);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Indicates whether a logger bound to `name` accepts events, if one is bound.
+    pub fn is_enabled(&self, name: &str) -> Option<bool> {
+        self.map.get(name).map(|entry| entry.1.is_enabled())
+    }
+
+    /// Removes a bound logger, and closes its stream.
     ///
     /// This is intended primarily to close a logging stream and let the associated writer
-    /// communicate that the stream is closed to any consumers. If a binding is not removed,
-    /// then the stream cannot be complete as in principle anyone could acquire a handle to
-    /// the logger and start further logging.
+    /// communicate that the stream is closed to any consumers. The logger flushes the events it
+    /// has buffered and drops its action, and its handles, including those held by running
+    /// dataflows, discard events logged hereafter.
     pub fn remove(&mut self, name: &str) -> Option<Box<dyn Any>> {
-        self.map.remove(name).map(|x| x.0)
+        self.map.remove(name).map(|(logger, mut control)| {
+            control.close();
+            logger
+        })
     }
 
     /// Retrieves a shared logger, if one has been inserted.
@@ -67,10 +187,19 @@ impl<Id: Clone+'static> Registry<Id> {
         Registry {
             id,
             time,
+            clock: clock::system(),
             map: HashMap::new(),
         }
     }
 
+    /// Reads the time of events logged by loggers inserted hereafter from `clock`.
+    ///
+    /// The clock should be the one from which `time`, supplied to `new`, was read.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Flushes all registered logs.
     pub fn flush(&mut self) {
         <Self as Flush>::flush(self);
@@ -85,13 +214,22 @@ impl<Id> Flush for Registry<Id> {
     }
 }
 
+// A predicate on events, shared by the clones of a logger.
+type Filter<T> = Rc<RefCell<dyn FnMut(&T)->bool>>;
+
+// The destination of events, shared by the clones of a logger, and absent once closed.
+type Action<T, E> = Rc<RefCell<Option<Box<dyn FnMut(&Duration, &mut Vec<(Duration, E, T)>)>>>>;
+
 /// A buffering logger.
 pub struct Logger<T, E> {
     id:     E,
     time:   Instant,                                                    // common instant used for all loggers.
+    clock:  Arc<dyn Clock>,                                             // clock from which `time` was read.
     offset: Duration,                                                   // offset to allow re-calibration.
-    action: Rc<RefCell<dyn FnMut(&Duration, &mut Vec<(Duration, E, T)>)>>,  // action to take on full log buffers.
+    action: Action<T, E>,                                               // action to take on full log buffers.
     buffer: Rc<RefCell<Vec<(Duration, E, T)>>>,                         // shared buffer; not obviously best design.
+    filter: Option<Filter<T>>,                                          // events to retain, if not all.
+    enabled: Rc<Cell<bool>>,                                            // whether events are accepted.
 }
 
 impl<T, E: Clone> Clone for Logger<T, E> {
@@ -99,9 +237,12 @@ impl<T, E: Clone> Clone for Logger<T, E> {
         Logger {
             id: self.id.clone(),
             time: self.time,
-            offset: self.offset.clone(),
+            clock: self.clock.clone(),
+            offset: self.offset,
             action: self.action.clone(),
             buffer: self.buffer.clone(),
+            filter: self.filter.clone(),
+            enabled: self.enabled.clone(),
         }
     }
 }
@@ -115,12 +256,64 @@ impl<T, E: Clone> Logger<T, E> {
         Logger {
             id,
             time,
+            clock: clock::system(),
             offset,
-            action: Rc::new(RefCell::new(action)),
+            action: Rc::new(RefCell::new(Some(Box::new(action)))),
             buffer: Rc::new(RefCell::new(Vec::with_capacity(1024))),
+            filter: None,
+            enabled: Rc::new(Cell::new(true)),
         }
     }
 
+    /// Reads the time of logged events from `clock`, from which `time` should have been read.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Retains only those logged events accepted by `filter`.
+    ///
+    /// The filter is shared by clones of the returned logger, and replaces any existing filter.
+    pub fn with_filter<P: FnMut(&T)->bool+'static>(mut self, filter: P) -> Self {
+        self.filter = Some(Rc::new(RefCell::new(filter)));
+        self
+    }
+
+    /// Directs the events of this logger and its clones to `action`.
+    ///
+    /// Buffered events are first flushed to the current action, which is then dropped.
+    pub fn redirect<F>(&self, action: F)
+    where
+        F: FnMut(&Duration, &mut Vec<(Duration, E, T)>)+'static
+    {
+        self.flush_buffer();
+        *self.action.borrow_mut() = Some(Box::new(action));
+    }
+
+    /// Pauses or resumes this logger and its clones.
+    ///
+    /// Pausing flushes buffered events, and events logged while paused are discarded.
+    pub fn set_enabled(&self, enabled: bool) {
+        if !enabled {
+            self.flush_buffer();
+        }
+        self.enabled.set(enabled);
+    }
+
+    /// Indicates whether this logger accepts events.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.get()
+    }
+
+    /// Flushes buffered events and drops the action, closing the stream.
+    ///
+    /// This logger and its clones discard events logged hereafter.
+    pub fn close(&self) {
+        self.flush_buffer();
+        self.enabled.set(false);
+        self.action.borrow_mut().take();
+    }
+
     /// Logs an event.
     ///
     /// The event has its timestamp recorded at the moment of logging, but it may be delayed
@@ -150,15 +343,21 @@ impl<T, E: Clone> Logger<T, E> {
     pub fn log_many<I>(&self, events: I)
     where I: IntoIterator, I::Item: Into<T>
     {
+        if !self.enabled.get() { return; }
         let mut buffer = self.buffer.borrow_mut();
-        let elapsed = self.time.elapsed() + self.offset;
+        let mut filter = self.filter.as_ref().map(|filter| filter.borrow_mut());
+        let elapsed = self.clock.elapsed(self.time) + self.offset;
         for event in events {
-            buffer.push((elapsed.clone(), self.id.clone(), event.into()));
+            let event = event.into();
+            if let Some(filter) = filter.as_mut() {
+                if !(**filter)(&event) { continue; }
+            }
+            buffer.push((elapsed, self.id.clone(), event));
             if buffer.len() == buffer.capacity() {
                 // Would call `self.flush()`, but for `RefCell` panic.
-                let mut action = self.action.borrow_mut();
-                let action = &mut *action;
-                (*action)(&elapsed, &mut *buffer);
+                if let Some(action) = self.action.borrow_mut().as_mut() {
+                    (*action)(&elapsed, &mut *buffer);
+                }
                 // The buffer clear could plausibly be removed, changing the semantics but allowing users
                 // to do in-place updates without forcing them to take ownership.
                 buffer.clear();
@@ -176,6 +375,32 @@ impl<T, E: Clone> Logger<T, E> {
     }
 }
 
+impl<T, E> Logger<T, E> {
+    // Flushes logged messages, through a shared reference.
+    fn flush_buffer(&self) {
+        let mut buffer = self.buffer.borrow_mut();
+        let mut action = self.action.borrow_mut();
+        let elapsed = self.clock.elapsed(self.time) + self.offset;
+        if let Some(action) = action.as_mut() {
+            if !buffer.is_empty() {
+                (*action)(&elapsed, &mut *buffer);
+                buffer.clear();
+                // NB: This does not re-allocate any specific size if the buffer has been
+                // taken. The intent is that the geometric growth in `log_many` should be
+                // enough to ensure that we do not send too many small buffers, nor do we
+                // allocate too large buffers when they are not needed.
+            }
+            else {
+                // Avoid swapping resources for empty buffers.
+                (*action)(&elapsed, &mut Vec::new());
+            }
+        }
+        else {
+            buffer.clear();
+        }
+    }
+}
+
 /// Bit weird, because we only have to flush on the *last* drop, but this should be ok.
 impl<T, E> Drop for Logger<T, E> {
     fn drop(&mut self) {
@@ -186,6 +411,37 @@ impl<T, E> Drop for Logger<T, E> {
     }
 }
 
+/// A filter accepting a fraction of events, chosen pseudo-randomly.
+///
+/// Samplers with the same rate and seed accept the same positions of the sequence of events
+/// they are presented.
+#[derive(Debug, Clone)]
+pub struct Sampler {
+    threshold: u64,
+    state: u64,
+}
+
+impl Sampler {
+    /// Creates a sampler accepting each event with probability `rate`, from the seed `seed`.
+    ///
+    /// Rates at most zero accept no events, and rates at least one accept all events.
+    pub fn new(rate: f64, seed: u64) -> Self {
+        let threshold = if rate >= 1.0 { u64::MAX } else if rate > 0.0 { (rate * (u64::MAX as f64)) as u64 } else { 0 };
+        // A zero state would remain zero.
+        Sampler { threshold, state: seed | 1 }
+    }
+
+    /// Indicates whether to accept the next event.
+    pub fn sample(&mut self) -> bool {
+        if self.threshold == u64::MAX { return true; }
+        // xorshift64*
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d) < self.threshold
+    }
+}
+
 /// Types that can be flushed.
 trait Flush {
     /// Flushes buffered data.
@@ -194,21 +450,22 @@ trait Flush {
 
 impl<T, E> Flush for Logger<T, E> {
     fn flush(&mut self) {
-        let mut buffer = self.buffer.borrow_mut();
-        let mut action = self.action.borrow_mut();
-        let action = &mut *action;
-        let elapsed = self.time.elapsed() + self.offset;
-        if !buffer.is_empty() {
-            (*action)(&elapsed, &mut *buffer);
-            buffer.clear();
-            // NB: This does not re-allocate any specific size if the buffer has been
-            // taken. The intent is that the geometric growth in `log_many` should be
-            // enough to ensure that we do not send too many small buffers, nor do we
-            // allocate too large buffers when they are not needed.
-        }
-        else {
-            // Avoid swapping resources for empty buffers.
-            (*action)(&elapsed, &mut Vec::new());
-        }
+        self.flush_buffer();
     }
 }
+
+/// Loggers controlled by a registry, whatever the type of their events.
+trait Control: Flush {
+    /// Pauses or resumes the logger.
+    fn set_enabled(&mut self, enabled: bool);
+    /// Indicates whether the logger accepts events.
+    fn is_enabled(&self) -> bool;
+    /// Closes the logger's stream.
+    fn close(&mut self);
+}
+
+impl<T, E: Clone> Control for Logger<T, E> {
+    fn set_enabled(&mut self, enabled: bool) { Logger::set_enabled(self, enabled); }
+    fn is_enabled(&self) -> bool { Logger::is_enabled(self) }
+    fn close(&mut self) { Logger::close(self); }
+}
@@ -0,0 +1,75 @@
+//! Times building reachability trackers, and propagating pointstamps through them.
+//!
+//! The graph is a chain of `operators` operators, each with `ports` inputs and outputs, fed from
+//! and draining to a scope with one input and `outputs` outputs. Every port of an operator has the
+//! same path summaries to the scope outputs, which the tracker shares between them.
+//!
+//! Run with `cargo bench -p timely --bench reachability -- [operators] [ports] [outputs] [rounds]`.
+
+extern crate timely;
+
+use std::time::Instant;
+
+use timely::progress::frontier::Antichain;
+use timely::progress::{Source, Target};
+use timely::progress::reachability::Builder;
+
+fn main() {
+
+    // Arguments after `--bench`, which cargo passes to the benchmark.
+    let mut args = std::env::args().skip(1).filter(|arg| arg != "--bench");
+    let operators = args.next().and_then(|arg| arg.parse::<usize>().ok()).unwrap_or(1_000);
+    let ports = args.next().and_then(|arg| arg.parse::<usize>().ok()).unwrap_or(4);
+    let outputs = args.next().and_then(|arg| arg.parse::<usize>().ok()).unwrap_or(16);
+    let rounds = args.next().and_then(|arg| arg.parse::<usize>().ok()).unwrap_or(100);
+
+    let timer = Instant::now();
+    for _ in 0 .. rounds {
+        graph(operators, ports, outputs).build(None);
+    }
+    println!("build: {:?} per tracker", timer.elapsed() / rounds as u32);
+
+    let (mut tracker, _) = graph(operators, ports, outputs).build(None);
+    let timer = Instant::now();
+    for round in 0 .. rounds {
+        for operator in 1 ..= operators {
+            for port in 0 .. ports {
+                tracker.update_source(Source::new(operator, port), round, 1);
+                if round > 0 {
+                    tracker.update_source(Source::new(operator, port), round - 1, -1);
+                }
+            }
+        }
+        tracker.propagate_all();
+        for changes in tracker.pushed_output().iter_mut() {
+            changes.clear();
+        }
+        tracker.pushed().clear();
+    }
+    println!("propagate: {:?} per round", timer.elapsed() / rounds as u32);
+}
+
+// A chain of `operators` operators with `ports` inputs and outputs each, connected port to port,
+// within a scope of one input and `outputs` outputs.
+fn graph(operators: usize, ports: usize, outputs: usize) -> Builder<usize> {
+
+    let mut builder = Builder::<usize>::new();
+    builder.add_node(0, outputs, 1, vec![vec![Antichain::new()]; outputs]);
+    for operator in 1 ..= operators {
+        let summary = (0 .. ports).map(|input| {
+            (0 .. ports).map(|output| if input == output { Antichain::from_elem(0) } else { Antichain::new() }).collect()
+        }).collect();
+        builder.add_node(operator, ports, ports, summary);
+    }
+
+    for port in 0 .. ports {
+        builder.add_edge(Source::new(0, 0), Target::new(1, port));
+        for operator in 1 .. operators {
+            builder.add_edge(Source::new(operator, port), Target::new(operator + 1, port));
+        }
+        for output in 0 .. outputs {
+            builder.add_edge(Source::new(operators, port), Target::new(0, output));
+        }
+    }
+    builder
+}
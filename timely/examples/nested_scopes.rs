@@ -0,0 +1,52 @@
+extern crate timely;
+
+use timely::dataflow::Scope;
+use timely::dataflow::operators::{Input, Map, Concatenate, Enter, Leave, Probe};
+
+// Builds a dataflow of `branches` chains of `length` operators, each nested three regions deep,
+// and reports the time to build it and to advance its frontier.
+fn main() {
+    timely::execute_from_args(std::env::args(), |worker| {
+
+        let timer = std::time::Instant::now();
+
+        let mut args = std::env::args();
+        args.next();
+
+        let branches = args.next().map(|x| x.parse::<usize>().unwrap()).unwrap_or(100);
+        let length = args.next().map(|x| x.parse::<usize>().unwrap()).unwrap_or(100);
+        let rounds = args.next().map(|x| x.parse::<usize>().unwrap()).unwrap_or(100);
+
+        let (mut input, probe) = worker.dataflow(|scope| {
+            let (input, stream) = scope.new_input::<u64>();
+            let outputs = (0 .. branches).map(|_| {
+                scope.region(|outer| {
+                    let stream = stream.enter(outer);
+                    outer.region(|middle| {
+                        let stream = stream.enter(middle);
+                        middle.region(|inner| {
+                            let mut stream = stream.enter(inner);
+                            for _ in 0 .. length {
+                                stream = stream.map(|x| x);
+                            }
+                            stream.leave()
+                        }).leave()
+                    }).leave()
+                })
+            }).collect::<Vec<_>>();
+            let probe = scope.concatenate(outputs).probe();
+            (input, probe)
+        });
+
+        println!("{:?}\tdataflow built ({} x {} operators)", timer.elapsed(), branches, length);
+
+        for round in 1 .. rounds + 1 {
+            input.send(round as u64);
+            input.advance_to(round);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+
+        println!("{:?}\t{} rounds complete", timer.elapsed(), rounds);
+
+    }).unwrap();
+}
@@ -0,0 +1,97 @@
+extern crate timely;
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use timely::dataflow::{ProbeHandle, Scope, Stream};
+use timely::dataflow::channels::pact::{ParallelizationContract, Pipeline};
+use timely::dataflow::operators::{Probe, UnorderedInput};
+use timely::dataflow::operators::generic::Operator;
+
+// Measures the latency with which epochs complete, when an operator that works through a few
+// batches each time it is scheduled receives them in arrival order, and smallest time first.
+//
+// Each round opens an epoch, sends a batch at each open epoch, newest first, and closes the
+// oldest epoch. The operator falls behind, and in arrival order works on new epochs while old
+// epochs wait to complete.
+//
+//     cargo run --release --example prioritized -- <rounds> <open epochs> <batches per schedule>
+fn main() {
+
+    let mut args = std::env::args();
+    args.next();
+    let rounds: u64 = args.next().unwrap_or("300".to_owned()).parse().expect("rounds must be a u64");
+    let open: u64 = args.next().unwrap_or("10".to_owned()).parse().expect("open epochs must be a u64");
+    let per_schedule: usize = args.next().unwrap_or("4".to_owned()).parse().expect("batches per schedule must be a usize");
+
+    for prioritized in [false, true] {
+        let latencies = measure(rounds, open, per_schedule, prioritized);
+        let mean = latencies.iter().sum::<Duration>() / latencies.len() as u32;
+        let max = latencies.iter().max().unwrap();
+        println!("{}:\tmean {:?}\tmax {:?}", if prioritized { "prioritized" } else { "arrival" }, mean, max);
+    }
+}
+
+// The latencies, from the close of each epoch to its completion.
+fn measure(rounds: u64, open: u64, per_schedule: usize, prioritized: bool) -> Vec<Duration> {
+    timely::execute_directly(move |worker| {
+
+        let mut probe = ProbeHandle::new();
+        let (mut input, cap) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, stream) = scope.new_unordered_input::<u64>();
+            let stream = if prioritized { work(&stream, Pipeline.prioritized(), per_schedule) }
+                         else { work(&stream, Pipeline, per_schedule) };
+            stream.probe_with(&mut probe);
+            input
+        });
+
+        let mut caps = VecDeque::new();
+        let mut closed = VecDeque::new();
+        let mut latencies = Vec::new();
+        for round in 0 .. rounds + open {
+            if round < rounds {
+                caps.push_back(cap.delayed(&round));
+                for cap in caps.iter().rev() {
+                    input.session(cap.clone()).give_iterator(0 .. 1024);
+                }
+            }
+            if round >= open || round >= rounds {
+                if let Some(cap) = caps.pop_front() {
+                    closed.push_back((*cap.time(), Instant::now()));
+                }
+            }
+            worker.step();
+            while closed.front().map(|(time, _)| !probe.less_equal(time)).unwrap_or(false) {
+                latencies.push(closed.pop_front().unwrap().1.elapsed());
+            }
+        }
+        drop(cap);
+        while !closed.is_empty() {
+            worker.step();
+            while closed.front().map(|(time, _)| !probe.less_equal(time)).unwrap_or(false) {
+                latencies.push(closed.pop_front().unwrap().1.elapsed());
+            }
+        }
+        latencies
+    })
+}
+
+// Sums the squares of records, `per_schedule` batches at a time.
+fn work<G: Scope<Timestamp=u64>, P: ParallelizationContract<u64, u64>>(stream: &Stream<G, u64>, pact: P, per_schedule: usize) -> Stream<G, u64> {
+    let handle = stream.scope();
+    stream.unary(pact, "Work", move |_cap, info| {
+        let activator = handle.activator_for(&info.address[..]);
+        move |input, output| {
+            for _ in 0 .. per_schedule {
+                match input.next() {
+                    Some((time, data)) => {
+                        let sum = data.iter().map(|x| (0 .. *x).map(|y| std::hint::black_box(y * y)).sum::<u64>()).sum();
+                        output.session(&time).give(sum);
+                    },
+                    None => return,
+                }
+            }
+            activator.activate();
+        }
+    })
+}
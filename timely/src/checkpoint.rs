@@ -0,0 +1,483 @@
+//! Consistent snapshots of operator state, keyed by timestamp frontiers.
+//!
+//! A checkpoint is requested of a dataflow at a frontier `F`, through `Worker::checkpoint`.
+//! Each operator that registered a [`StateHandle`] writes its state once its input frontier has
+//! reached `F`, and the worker commits the checkpoint to its [`Store`] once all of its handles
+//! have written. The checkpoint is complete once every worker has committed it.
+//!
+//! The state an operator writes must reflect exactly the records at times not greater or equal
+//! to an element of `F`. Once an input frontier has reached `F` no record at such a time can
+//! still be in flight towards the operator, and so a snapshot of operator state captures the
+//! contents of the channels as well. Records at later times, whether stashed by the operator or
+//! still in flight, are not part of the checkpoint; they are expected to be produced again by the
+//! inputs of a dataflow restored from the checkpoint. Operators that apply records in time order
+//! should consult their handle before applying records at times in advance of `F`.
+//!
+//! Checkpoint identifiers are allocated in the order requests are made, and all workers must
+//! request the same checkpoints in the same order, as they must construct the same dataflows.
+//!
+//! A dataflow may later be constructed from a checkpoint all workers committed, through
+//! `Worker::dataflow_restored`. Its operators retrieve their state with `StateHandle::restored`,
+//! and its inputs start at the checkpoint's frontier.
+//!
+//! # Examples
+//! ```
+//! use std::collections::HashMap;
+//! use timely::dataflow::{InputHandle, ProbeHandle, Scope, Stream};
+//! use timely::dataflow::operators::{Input, Inspect, Probe};
+//! use timely::dataflow::operators::generic::operator::Operator;
+//! use timely::dataflow::channels::pact::Pipeline;
+//! use timely::checkpoint::{MemoryStore, StateHandle, Store};
+//! use timely::progress::Antichain;
+//!
+//! // Counts records, saving the count for checkpoints before applying later records.
+//! fn count<G: Scope<Timestamp=u64>>(stream: &Stream<G, u64>) -> Stream<G, u64> {
+//!     let mut scope = stream.scope();
+//!     stream.unary_frontier(Pipeline, "Count", |_capability, info| {
+//!         let state = StateHandle::new(&mut scope, "count", &info.address);
+//!         let mut count: u64 = state.restored().expect("failed to restore state").unwrap_or(0);
+//!         let mut stash = HashMap::new();
+//!         move |input, output| {
+//!             input.for_each(|time, data| {
+//!                 stash.entry(time.time().clone()).or_insert((time.retain(), 0)).1 += data.len() as u64;
+//!             });
+//!             let mut complete = stash.keys().filter(|t| !input.frontier().less_equal(t)).cloned().collect::<Vec<_>>();
+//!             complete.sort();
+//!             for time in complete {
+//!                 while let Some((checkpoint, _)) = state.next_ready(&[time]) {
+//!                     state.save(checkpoint, &count).expect("failed to save state");
+//!                 }
+//!                 let (capability, records) = stash.remove(&time).unwrap();
+//!                 count += records;
+//!                 output.session(&capability).give(count);
+//!             }
+//!             while let Some((checkpoint, _)) = state.next_ready(&input.frontier().frontier()) {
+//!                 state.save(checkpoint, &count).expect("failed to save state");
+//!             }
+//!         }
+//!     })
+//! }
+//!
+//! let store = MemoryStore::default();
+//! let mut config = timely::Config::thread();
+//! config.worker = config.worker.checkpoint_store(store.clone());
+//!
+//! timely::execute(config, move |worker| {
+//!
+//!     // Checkpoint the count at time 5, but fail after introducing the record at time 7.
+//!     let mut input = InputHandle::new();
+//!     let dataflow = worker.next_dataflow_index();
+//!     let probe = worker.dataflow(|scope| count(&scope.input_from(&mut input)).probe());
+//!     let checkpoint = worker.checkpoint(dataflow, Antichain::from_elem(5u64));
+//!     for round in 0 .. 8u64 {
+//!         input.send(round);
+//!         input.advance_to(round + 1);
+//!         worker.step_while(|| probe.less_than(input.time()));
+//!     }
+//!     assert!(worker.checkpoint_committed(checkpoint));
+//!     assert!(store.is_committed(checkpoint, 0).unwrap());
+//!     assert_eq!(timely::checkpoint::load::<u64>(&store, checkpoint, 0, "count").unwrap(), Some(5));
+//!     worker.drop_dataflow(dataflow);
+//!
+//!     // Resume from the checkpoint, re-introducing records from time 5 on.
+//!     let mut input = InputHandle::new();
+//!     let mut probe = ProbeHandle::new();
+//!     worker.dataflow_restored(checkpoint, |scope| {
+//!         count(&scope.input_from(&mut input))
+//!             .inspect(|count| assert!(*count > 5))
+//!             .probe_with(&mut probe);
+//!     });
+//!     assert_eq!(*input.time(), 5);
+//!     for round in 5 .. 10u64 {
+//!         input.send(round);
+//!         input.advance_to(round + 1);
+//!         worker.step_while(|| probe.less_than(input.time()));
+//!     }
+//! }).unwrap();
+//! ```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::fs;
+use std::io::{Error, ErrorKind, Result, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use crate::ExchangeData;
+use crate::bytes::arc::Bytes;
+use crate::communication::Message;
+use crate::communication::codec::{Codec, Native};
+use crate::dataflow::Scope;
+use crate::progress::{Antichain, Timestamp};
+use crate::scheduling::Activator;
+
+/// The name under which each worker records the frontier of a checkpoint it commits.
+pub const FRONTIER: &str = "#frontier";
+
+/// Durable storage for checkpointed state.
+///
+/// State is stored as bytes under a checkpoint identifier, a worker index, and a name that
+/// is unique among the state handles of the worker's dataflow. A worker commits a checkpoint
+/// once it has written all of its state, and the state of a checkpoint should only be trusted
+/// once all workers have committed it.
+pub trait Store: Debug+Send+Sync {
+    /// Records `bytes` as the state `name` of `worker` in `checkpoint`.
+    fn put(&self, checkpoint: u64, worker: usize, name: &str, bytes: Vec<u8>) -> Result<()>;
+    /// Retrieves the state `name` of `worker` in `checkpoint`, if it was recorded.
+    fn get(&self, checkpoint: u64, worker: usize, name: &str) -> Result<Option<Vec<u8>>>;
+    /// Records that `worker` has written all of its state for `checkpoint`.
+    fn commit(&self, checkpoint: u64, worker: usize) -> Result<()>;
+    /// Indicates whether `worker` has committed `checkpoint`.
+    fn is_committed(&self, checkpoint: u64, worker: usize) -> Result<bool>;
+    /// Removes the state and commit of `worker` in `checkpoint`, if any.
+    ///
+    /// A worker discards a checkpoint before writing it, as a restored computation reuses the
+    /// identifiers of checkpoints that followed the one it was restored from.
+    ///
+    /// The default implementation does nothing, which suits stores that do not outlive one
+    /// computation. Other stores should override it, as otherwise a reused identifier remains
+    /// committed while its state is overwritten.
+    fn discard(&self, _checkpoint: u64, _worker: usize) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A store that keeps checkpoints in memory, shared by all of its clones.
+///
+/// The store does not survive the process, and is intended for testing and for recovery
+/// from failures that do not take down the process.
+#[derive(Debug, Default, Clone)]
+pub struct MemoryStore {
+    inner: Arc<Mutex<MemoryStoreInner>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryStoreInner {
+    state: HashMap<(u64, usize, String), Vec<u8>>,
+    committed: HashSet<(u64, usize)>,
+}
+
+impl Store for MemoryStore {
+    fn put(&self, checkpoint: u64, worker: usize, name: &str, bytes: Vec<u8>) -> Result<()> {
+        self.inner.lock().expect("poisoned store").state.insert((checkpoint, worker, name.to_owned()), bytes);
+        Ok(())
+    }
+    fn get(&self, checkpoint: u64, worker: usize, name: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.inner.lock().expect("poisoned store").state.get(&(checkpoint, worker, name.to_owned())).cloned())
+    }
+    fn commit(&self, checkpoint: u64, worker: usize) -> Result<()> {
+        self.inner.lock().expect("poisoned store").committed.insert((checkpoint, worker));
+        Ok(())
+    }
+    fn is_committed(&self, checkpoint: u64, worker: usize) -> Result<bool> {
+        Ok(self.inner.lock().expect("poisoned store").committed.contains(&(checkpoint, worker)))
+    }
+    fn discard(&self, checkpoint: u64, worker: usize) -> Result<()> {
+        let mut inner = self.inner.lock().expect("poisoned store");
+        inner.committed.remove(&(checkpoint, worker));
+        inner.state.retain(|(c, w, _), _| (*c, *w) != (checkpoint, worker));
+        Ok(())
+    }
+}
+
+/// A store that keeps checkpoints as files in a directory.
+///
+/// The state `name` of `worker` in `checkpoint` is written to `checkpoint/worker/name` under
+/// the directory, and a commit is recorded by the file `checkpoint/worker.committed`. Files are
+/// written under a temporary name and then renamed, so that a partially written file is never
+/// mistaken for state.
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    directory: PathBuf,
+}
+
+impl FileStore {
+    /// Stores checkpoints under `directory`, which is created if it does not exist.
+    pub fn new<P: Into<PathBuf>>(directory: P) -> Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+        Ok(FileStore { directory })
+    }
+
+    fn state_path(&self, checkpoint: u64, worker: usize, name: &str) -> PathBuf {
+        // Escape bytes that may not be valid in file names.
+        let mut escaped = String::new();
+        for byte in name.bytes() {
+            if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' || byte == b'.' {
+                escaped.push(byte as char);
+            }
+            else {
+                escaped.push_str(&format!("%{:02x}", byte));
+            }
+        }
+        self.directory.join(checkpoint.to_string()).join(worker.to_string()).join(escaped)
+    }
+
+    fn commit_path(&self, checkpoint: u64, worker: usize) -> PathBuf {
+        self.directory.join(checkpoint.to_string()).join(format!("{}.committed", worker))
+    }
+
+    fn write_file(path: PathBuf, bytes: &[u8]) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temporary = path.with_extension("partial");
+        let mut file = fs::File::create(&temporary)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(temporary, path)
+    }
+}
+
+impl Store for FileStore {
+    fn put(&self, checkpoint: u64, worker: usize, name: &str, bytes: Vec<u8>) -> Result<()> {
+        FileStore::write_file(self.state_path(checkpoint, worker, name), &bytes[..])
+    }
+    fn get(&self, checkpoint: u64, worker: usize, name: &str) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.state_path(checkpoint, worker, name)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+    fn commit(&self, checkpoint: u64, worker: usize) -> Result<()> {
+        FileStore::write_file(self.commit_path(checkpoint, worker), &[])
+    }
+    fn is_committed(&self, checkpoint: u64, worker: usize) -> Result<bool> {
+        Ok(self.commit_path(checkpoint, worker).exists())
+    }
+    fn discard(&self, checkpoint: u64, worker: usize) -> Result<()> {
+        // Remove the commit first, so that the state is never trusted while partially removed.
+        for result in [fs::remove_file(self.commit_path(checkpoint, worker)), fs::remove_dir_all(self.directory.join(checkpoint.to_string()).join(worker.to_string()))] {
+            match result {
+                Err(error) if error.kind() != ErrorKind::NotFound => return Err(error),
+                _ => { },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Serializes `state` as it would be sent between processes.
+fn encode<S: ExchangeData>(state: &S) -> Vec<u8> {
+    let message = Message::from_typed(state.clone());
+    let mut bytes = Vec::with_capacity(<Native as Codec<S>>::length_in_bytes(&message));
+    <Native as Codec<S>>::into_bytes(&message, &mut bytes);
+    bytes
+}
+
+/// Reads the state `name` of `worker` in `checkpoint`, as written by `StateHandle::save`.
+pub fn load<S: ExchangeData>(store: &dyn Store, checkpoint: u64, worker: usize, name: &str) -> Result<Option<S>> {
+    Ok(store.get(checkpoint, worker, name)?.map(|bytes| {
+        let message: Message<S> = <Native as Codec<S>>::from_bytes(Bytes::from(bytes));
+        message.to_typed()
+    }))
+}
+
+/// The checkpoint state of a worker, shared by its dataflows.
+pub struct Checkpoints {
+    store: Option<Arc<dyn Store>>,
+    index: usize,
+    next_checkpoint: u64,
+    // Maps each dataflow index to an `Rc<RefCell<Coordinator<T>>>` for its timestamp `T`.
+    coordinators: HashMap<usize, Box<dyn Any>>,
+    // The dataflow under construction from a checkpoint, the checkpoint, and its `Antichain<T>`.
+    restoring: Option<(usize, u64, Box<dyn Any>)>,
+}
+
+impl Checkpoints {
+    /// Checkpoint state for worker `index`, writing to `store`.
+    pub(crate) fn new(store: Option<Arc<dyn Store>>, index: usize) -> Self {
+        Checkpoints { store, index, next_checkpoint: 0, coordinators: HashMap::new(), restoring: None }
+    }
+
+    /// The store to which checkpoints are written, if one is configured.
+    pub fn store(&self) -> Option<&Arc<dyn Store>> {
+        self.store.as_ref()
+    }
+
+    /// The checkpoint coordinator for `dataflow`, whose timestamp must be `T`.
+    fn coordinator<T: Timestamp>(&mut self, dataflow: usize) -> Rc<RefCell<Coordinator<T>>> {
+        self.coordinators
+            .entry(dataflow)
+            .or_insert_with(|| Box::new(Rc::new(RefCell::new(Coordinator::<T>::new()))))
+            .downcast_ref::<Rc<RefCell<Coordinator<T>>>>()
+            .unwrap_or_else(|| panic!("checkpoint state of dataflow {} must use the dataflow's timestamp", dataflow))
+            .clone()
+    }
+
+    /// Requests a checkpoint of `dataflow` at `frontier`, and returns its identifier.
+    pub(crate) fn request<T: Timestamp>(&mut self, dataflow: usize, frontier: Antichain<T>) -> u64 {
+        let store = self.store.clone().expect("checkpoint requested without a configured store");
+        let checkpoint = self.next_checkpoint;
+        self.next_checkpoint += 1;
+        store.discard(checkpoint, self.index).expect("failed to discard checkpoint");
+        let coordinator = self.coordinator::<T>(dataflow);
+        let mut coordinator = coordinator.borrow_mut();
+        let pending = coordinator.handles.iter().map(|(name, _)| name.clone()).collect::<HashSet<_>>();
+        coordinator.requests.push(Request { checkpoint, frontier, pending });
+        for (_, activator) in coordinator.handles.iter() {
+            activator.activate();
+        }
+        coordinator.commit_if_complete(checkpoint, &*store, self.index).expect("failed to commit checkpoint");
+        checkpoint
+    }
+
+    /// Indicates whether this worker has committed `checkpoint`.
+    pub(crate) fn committed(&self, checkpoint: u64) -> bool {
+        self.store.as_ref().map(|store| store.is_committed(checkpoint, self.index).unwrap_or(false)).unwrap_or(false)
+    }
+
+    /// Prepares to construct `dataflow` from `checkpoint`, and returns the checkpoint's frontier.
+    ///
+    /// Subsequently requested checkpoints receive identifiers following `checkpoint`.
+    pub(crate) fn begin_restore<T: Timestamp>(&mut self, dataflow: usize, checkpoint: u64, peers: usize) -> Antichain<T> {
+        let store = self.store.clone().expect("checkpoint restored without a configured store");
+        for worker in 0 .. peers {
+            if !store.is_committed(checkpoint, worker).expect("failed to read checkpoint") {
+                panic!("checkpoint {} was not committed by worker {}", checkpoint, worker);
+            }
+        }
+        let elements = load::<Vec<T>>(&*store, checkpoint, self.index, FRONTIER)
+            .expect("failed to read checkpoint")
+            .unwrap_or_else(|| panic!("checkpoint {} has no frontier", checkpoint));
+        let frontier = Antichain::from(elements);
+        self.next_checkpoint = self.next_checkpoint.max(checkpoint + 1);
+        self.restoring = Some((dataflow, checkpoint, Box::new(frontier.clone())));
+        frontier
+    }
+
+    /// Discards the requests and registered state of a dropped dataflow.
+    pub(crate) fn forget(&mut self, dataflow: usize) {
+        self.coordinators.remove(&dataflow);
+    }
+
+    /// Concludes the construction of a dataflow from a checkpoint.
+    pub(crate) fn end_restore(&mut self) {
+        self.restoring = None;
+    }
+
+    /// The checkpoint and its frontier, if `dataflow` is being constructed from a checkpoint.
+    ///
+    /// Inputs use the frontier to resume from the checkpoint.
+    pub fn restoring<T: Timestamp>(&self, dataflow: usize) -> Option<(u64, Antichain<T>)> {
+        match &self.restoring {
+            Some((index, checkpoint, frontier)) if *index == dataflow => {
+                frontier.downcast_ref::<Antichain<T>>().map(|frontier| (*checkpoint, frontier.clone()))
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Checkpoint requests and registered state of one dataflow.
+struct Coordinator<T: Timestamp> {
+    handles: Vec<(String, Activator)>,
+    requests: Vec<Request<T>>,
+}
+
+struct Request<T: Timestamp> {
+    checkpoint: u64,
+    frontier: Antichain<T>,
+    // Names of handles that have not yet written their state.
+    pending: HashSet<String>,
+}
+
+impl<T: Timestamp> Coordinator<T> {
+    fn new() -> Self {
+        Coordinator { handles: Vec::new(), requests: Vec::new() }
+    }
+
+    /// Commits `checkpoint` if all handles have written their state.
+    fn commit_if_complete(&mut self, checkpoint: u64, store: &dyn Store, worker: usize) -> Result<()> {
+        if let Some(position) = self.requests.iter().position(|r| r.checkpoint == checkpoint && r.pending.is_empty()) {
+            let request = self.requests.remove(position);
+            store.put(checkpoint, worker, FRONTIER, encode(&request.frontier.elements().to_vec()))?;
+            store.commit(checkpoint, worker)?;
+        }
+        Ok(())
+    }
+}
+
+/// A registration of operator state to be included in checkpoints.
+///
+/// An operator creates a handle when it is constructed, and consults it whenever it runs to
+/// learn of checkpoints whose frontier its input has reached. It must then save its state for
+/// each such checkpoint, in the order they are reported.
+pub struct StateHandle<T: Timestamp> {
+    name: String,
+    worker: usize,
+    store: Option<Arc<dyn Store>>,
+    coordinator: Rc<RefCell<Coordinator<T>>>,
+    restored: Option<u64>,
+}
+
+impl<T: Timestamp> StateHandle<T> {
+    /// Registers state `name` of the operator at `address` in the dataflow of `scope`.
+    ///
+    /// The operator at `address` is activated when a checkpoint is requested. State must be
+    /// registered in the root scope of a dataflow, and names must be unique within it.
+    pub fn new<G: Scope<Timestamp=T>>(scope: &G, name: &str, address: &[usize]) -> Self {
+        let dataflow = scope.addr()[0];
+        let activator = scope.activator_for(address);
+        let (store, worker, coordinator, restored) = {
+            let mut checkpoints = scope.checkpoints();
+            let restored = checkpoints.restoring::<T>(dataflow).map(|(checkpoint, _)| checkpoint);
+            (checkpoints.store.clone(), checkpoints.index, checkpoints.coordinator::<T>(dataflow), restored)
+        };
+        {
+            let mut borrow = coordinator.borrow_mut();
+            if borrow.handles.iter().any(|(other, _)| other == name) {
+                panic!("checkpoint state {:?} registered twice in dataflow {}", name, dataflow);
+            }
+            borrow.handles.push((name.to_owned(), activator));
+        }
+        StateHandle { name: name.to_owned(), worker, store, coordinator, restored }
+    }
+
+    /// The state saved under this name in the checkpoint the dataflow was constructed from.
+    ///
+    /// Returns `None` if the dataflow was not constructed with `Worker::dataflow_restored`,
+    /// or if the checkpoint has no state of this name for this worker.
+    pub fn restored<S: ExchangeData>(&self) -> Result<Option<S>> {
+        match (self.restored, self.store.as_ref()) {
+            (Some(checkpoint), Some(store)) => load(&**store, checkpoint, self.worker, &self.name),
+            _ => Ok(None),
+        }
+    }
+
+    /// The name under which the state is stored.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The earliest requested checkpoint not yet saved whose frontier `frontier` has reached.
+    ///
+    /// The input frontier has reached a checkpoint frontier when each of its elements is
+    /// greater or equal to some element of the checkpoint frontier.
+    pub fn next_ready(&self, frontier: &[T]) -> Option<(u64, Antichain<T>)> {
+        self.coordinator
+            .borrow()
+            .requests
+            .iter()
+            .filter(|request| request.pending.contains(&self.name))
+            .find(|request| frontier.iter().all(|time| request.frontier.less_equal(time)))
+            .map(|request| (request.checkpoint, request.frontier.clone()))
+    }
+
+    /// Saves `state` as this operator's contribution to `checkpoint`.
+    ///
+    /// The worker commits the checkpoint once all of its handles have saved their state.
+    pub fn save<S: ExchangeData>(&self, checkpoint: u64, state: &S) -> Result<()> {
+        let store = self.store.as_ref().ok_or_else(|| Error::other("no checkpoint store configured"))?;
+        store.put(checkpoint, self.worker, &self.name, encode(state))?;
+        let mut coordinator = self.coordinator.borrow_mut();
+        if let Some(request) = coordinator.requests.iter_mut().find(|r| r.checkpoint == checkpoint) {
+            request.pending.remove(&self.name);
+        }
+        coordinator.commit_if_complete(checkpoint, &**store, self.worker)
+    }
+}
@@ -0,0 +1,170 @@
+//! Credit-based flow control between an operator output and the operators it feeds.
+//!
+//! An operator may produce records faster than the operators it feeds consume them, and the
+//! records then accumulate in the channels between them. A [`Credits`] bounds the number of
+//! records in those channels: the producing operator takes a credit for each record it sends,
+//! through a [`CreditedSession`](crate::dataflow::channels::pushers::buffer::CreditedSession),
+//! and the consuming operator grants the credit back as it pulls the record from its input,
+//! which it reads through the [`Credited`](crate::dataflow::channels::pact::Credited) pact.
+//!
+//! When credits run out the session refuses records, returning them in a [`YieldNow`], and the
+//! producer should return from its logic. The producer is activated once credits are granted,
+//! so that it resumes only when there is room for its records.
+//!
+//! Credits are local to a worker, and bound the records a producer sends to consumers on the
+//! same worker.
+//!
+//! # Examples
+//! ```
+//! use std::cell::Cell;
+//! use std::rc::Rc;
+//! use timely::dataflow::channels::credits::Credits;
+//! use timely::dataflow::channels::pact::Credited;
+//! use timely::dataflow::operators::generic::{source, Operator};
+//! use timely::scheduling::Scheduler;
+//!
+//! timely::execute_directly(|worker| {
+//!     let credits = Credits::new(100);
+//!     let sent = Rc::new(Cell::new(0u64));
+//!     let received = Rc::new(Cell::new(0u64));
+//!     let (sent2, received2) = (sent.clone(), received.clone());
+//!
+//!     worker.dataflow::<u64,_,_>(|scope| {
+//!         let producer_credits = credits.clone();
+//!         let handle = scope.clone();
+//!         source(scope, "Producer", move |capability, info| {
+//!             producer_credits.activate_on_grant(handle.activator_for(&info.address[..]));
+//!             let mut capability = Some(capability);
+//!             move |output| {
+//!                 if let Some(cap) = capability.as_ref() {
+//!                     let mut session = output.credited_session(cap, &producer_credits);
+//!                     while sent2.get() < 10_000 {
+//!                         if session.give(sent2.get()).is_err() { return; }
+//!                         sent2.set(sent2.get() + 1);
+//!                     }
+//!                 }
+//!                 capability = None;
+//!             }
+//!         })
+//!         .sink(Credited::new(&credits), "Consumer", move |input| {
+//!             input.for_each(|_time, data| {
+//!                 received2.set(received2.get() + data.len() as u64);
+//!                 // No more records are in flight than there are credits.
+//!                 assert!(sent.get() - received2.get() <= 100);
+//!             });
+//!         });
+//!     });
+//!     worker.step_while(|| received.get() < 10_000);
+//! });
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::communication::Pull;
+use crate::dataflow::channels::Bundle;
+use crate::scheduling::Activator;
+
+/// A bound on the records in flight from an operator output to the operators it feeds.
+///
+/// Clones share their credits.
+#[derive(Clone)]
+pub struct Credits {
+    state: Rc<RefCell<CreditState>>,
+}
+
+struct CreditState {
+    available: usize,
+    limit: usize,
+    // Whether a record was refused since credits were last granted.
+    starved: bool,
+    activator: Option<Activator>,
+}
+
+impl Credits {
+    /// Allocates `limit` credits, permitting up to `limit` records in flight.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    pub fn new(limit: usize) -> Self {
+        assert!(limit > 0, "credits must permit at least one record in flight");
+        Credits {
+            state: Rc::new(RefCell::new(CreditState {
+                available: limit,
+                limit,
+                starved: false,
+                activator: None,
+            })),
+        }
+    }
+
+    /// Activates the producing operator with `activator` once credits are granted after it was
+    /// refused a record.
+    pub fn activate_on_grant(&self, activator: Activator) {
+        self.state.borrow_mut().activator = Some(activator);
+    }
+
+    /// The credits available.
+    pub fn available(&self) -> usize {
+        self.state.borrow().available
+    }
+
+    /// The number of credits allocated.
+    pub fn limit(&self) -> usize {
+        self.state.borrow().limit
+    }
+
+    /// Takes a credit, returning false if none are available.
+    pub fn try_take(&self) -> bool {
+        let mut state = self.state.borrow_mut();
+        if state.available > 0 {
+            state.available -= 1;
+            true
+        }
+        else {
+            state.starved = true;
+            false
+        }
+    }
+
+    /// Grants `count` credits back, up to the limit, activating the producer if it was refused.
+    pub fn grant(&self, count: usize) {
+        let mut state = self.state.borrow_mut();
+        state.available = (state.available + count).min(state.limit);
+        if state.starved && state.available > 0 {
+            state.starved = false;
+            if let Some(activator) = state.activator.as_ref() {
+                activator.activate();
+            }
+        }
+    }
+}
+
+/// The signal that credits have run out and the producer should yield, with the refused record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YieldNow<D>(pub D);
+
+/// Wraps a puller, granting a credit for each record pulled.
+pub struct CreditPuller<P> {
+    puller: P,
+    credits: Credits,
+}
+
+impl<P> CreditPuller<P> {
+    /// Wraps `puller`, granting credits to `credits`.
+    pub fn new(puller: P, credits: Credits) -> Self {
+        CreditPuller { puller, credits }
+    }
+}
+
+impl<T, D, P: Pull<Bundle<T, D>>> Pull<Bundle<T, D>> for CreditPuller<P> {
+    #[inline]
+    fn pull(&mut self) -> &mut Option<Bundle<T, D>> {
+        let result = self.puller.pull();
+        if let Some(bundle) = result.as_ref() {
+            self.credits.grant(bundle.data.len());
+        }
+        result
+    }
+}
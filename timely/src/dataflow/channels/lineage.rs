@@ -0,0 +1,178 @@
+//! The lineage of batches, for tracing where records entered a dataflow.
+//!
+//! When a worker is configured with [`Config::trace_lineage`](crate::worker::Config::trace_lineage),
+//! each batch of records carries a [`Lineage`] naming the operator and worker at which its records
+//! entered the dataflow, and a sequence number distinguishing the batches that worker started.
+//! Operators that send records without having received any, such as inputs and sources, start a
+//! new lineage with each batch they send. Operators that send records while processing a batch
+//! send them with the lineage of that batch, and so the lineage follows records through stock
+//! operators, exchanges, and scopes. Lineage is traced per batch rather than per record: records
+//! an operator holds and sends later carry the lineage of the batch it received most recently.
+//!
+//! The lineage of the batch an operator received most recently is reported by [`Lineage::current`],
+//! for example within the closures of [`inspect_core`](crate::dataflow::operators::Inspect::inspect_core),
+//! and is included in the [`MessagesEvent`](crate::logging::MessagesEvent)s logged as batches are
+//! sent and received.
+//!
+//! Lineage travels alongside batches rather than within them, and is not serialized: the wire
+//! format of batches is the same whether or not lineage is traced. Batches received from other
+//! processes carry no lineage, but the `MessagesEvent` logged as each was sent reports its
+//! lineage, and the channel, workers, and sequence number that identify it once received.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::channels::lineage::Lineage;
+//! use timely::dataflow::operators::{ToStream, Exchange, Map, Inspect};
+//!
+//! let mut config = timely::Config::process(2);
+//! config.worker = config.worker.trace_lineage(true);
+//! timely::execute(config, |worker| {
+//!     let index = worker.index();
+//!     worker.dataflow::<u64,_,_>(|scope| {
+//!         (0 .. 10u64).to_stream(scope)
+//!                     .map(|x| x + 1)
+//!                     .exchange(|x| *x)
+//!                     .inspect_batch(move |_time, data| {
+//!                         // Each batch started at the worker that produced its records.
+//!                         let lineage = Lineage::current().expect("lineage is traced");
+//!                         assert!(data.iter().all(|x| *x % 2 == index as u64));
+//!                         assert!(lineage.worker < 2);
+//!                     });
+//!     });
+//! }).unwrap();
+//! ```
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::dataflow::channels::Bundle;
+
+/// The origin of a batch of records: the operator and worker at which they entered the dataflow.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, PartialEq, Eq, Ord, PartialOrd)]
+pub struct Lineage {
+    /// The worker-unique identifier of the operator that started the batch, as in `OperatesEvent`.
+    pub operator: usize,
+    /// The index of the worker on which the batch started.
+    pub worker: usize,
+    /// The sequence number of the batch, among those started by the worker.
+    pub seq: usize,
+}
+
+// Plain data, which abomonation copies as bytes.
+impl ::abomonation::Abomonation for Lineage { }
+
+impl Lineage {
+    /// The lineage of the batch most recently received by the operator running on this thread.
+    ///
+    /// This is `None` unless the worker running the operator traces lineage, and for operators
+    /// that have received no batch in their current scheduling.
+    pub fn current() -> Option<Lineage> {
+        CURRENT.with(|current| current.get())
+    }
+}
+
+thread_local! {
+    // The lineage reported by `Lineage::current`, set only while a tracing worker schedules an operator.
+    static CURRENT: Cell<Option<Lineage>> = const { Cell::new(None) };
+}
+
+/// The lineage annotated on `bundle`, if any.
+#[inline]
+pub(crate) fn of<T, D>(bundle: &Bundle<T, D>) -> Option<Lineage> {
+    bundle.annotation().and_then(|annotation| annotation.downcast_ref::<Lineage>()).copied()
+}
+
+/// Annotates `bundle` with `lineage`, if any.
+#[inline]
+pub(crate) fn annotate<T, D>(bundle: &mut Bundle<T, D>, lineage: Option<Lineage>) {
+    if let Some(lineage) = lineage {
+        bundle.set_annotation(Some(Arc::new(lineage) as Arc<dyn Any+Send+Sync>));
+    }
+}
+
+/// The lineage state of a worker that traces lineage, shared by its operators and channels.
+#[derive(Clone)]
+pub(crate) struct Tracer {
+    state: Rc<RefCell<State>>,
+}
+
+struct State {
+    worker: usize,
+    next: usize,
+    // The operator being scheduled, and the lineage of the batch it most recently received.
+    operator: Option<usize>,
+    current: Option<Lineage>,
+}
+
+/// The operator and lineage in effect before an operator was scheduled, restored after.
+pub(crate) struct Scheduled {
+    tracer: Tracer,
+    operator: Option<usize>,
+    current: Option<Lineage>,
+}
+
+impl Scheduled {
+    /// Notes that the operator noted by `Tracer::enter` has returned.
+    pub(crate) fn exit(self) {
+        let mut state = self.tracer.state.borrow_mut();
+        state.operator = self.operator;
+        state.current = self.current;
+        CURRENT.with(|current| current.set(self.current));
+    }
+}
+
+impl Tracer {
+    /// Allocates the lineage state of the worker `worker`.
+    pub(crate) fn new(worker: usize) -> Self {
+        Tracer { state: Rc::new(RefCell::new(State { worker, next: 0, operator: None, current: None })) }
+    }
+
+    /// Notes that `operator` is about to be scheduled, and has yet to receive a batch.
+    pub(crate) fn enter(&self, operator: usize) -> Scheduled {
+        let mut state = self.state.borrow_mut();
+        CURRENT.with(|current| current.set(None));
+        Scheduled { tracer: self.clone(), operator: state.operator.replace(operator), current: state.current.take() }
+    }
+
+    /// Notes that the running operator has received a batch with lineage `lineage`.
+    pub(crate) fn receive(&self, lineage: Option<Lineage>) {
+        self.state.borrow_mut().current = lineage;
+        CURRENT.with(|current| current.set(lineage));
+    }
+
+    /// The lineage of a batch sent by the running operator.
+    ///
+    /// This is the lineage of the batch the operator most recently received, or a new lineage if it
+    /// has received none.
+    pub(crate) fn stamp(&self) -> Option<Lineage> {
+        let mut state = self.state.borrow_mut();
+        match (state.current, state.operator) {
+            (Some(current), _) => Some(current),
+            (None, Some(operator)) => Some(state.start(operator)),
+            (None, None) => None,
+        }
+    }
+
+    /// A new lineage for a batch sent by `operator`, outside of its scheduling.
+    pub(crate) fn originate(&self, operator: usize) -> Lineage {
+        self.state.borrow_mut().start(operator)
+    }
+
+    /// True iff records buffered with `lineage` must be sent before records of the running operator.
+    ///
+    /// This is so when the operator has since received a batch with a different lineage.
+    pub(crate) fn changed(&self, lineage: Option<Lineage>) -> bool {
+        let current = self.state.borrow().current;
+        current.is_some() && current != lineage
+    }
+}
+
+impl State {
+    fn start(&mut self, operator: usize) -> Lineage {
+        let seq = self.next;
+        self.next += 1;
+        Lineage { operator, worker: self.worker, seq }
+    }
+}
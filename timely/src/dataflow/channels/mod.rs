@@ -1,6 +1,8 @@
 //! Structured communication between timely dataflow operators.
 
 use crate::communication::Push;
+use self::lineage::Lineage;
+use self::pool::BufferPool;
 
 /// A collection of types that may be pushed at.
 pub mod pushers;
@@ -8,6 +10,11 @@ pub mod pushers;
 pub mod pullers;
 /// Parallelization contracts, describing how data must be exchanged between operators.
 pub mod pact;
+pub mod pool;
+pub mod stats;
+pub mod credits;
+pub mod steal;
+pub mod lineage;
 
 /// The input to and output from timely dataflow communication channels.
 pub type Bundle<T, D> = crate::communication::Message<Message<T, D>>;
@@ -27,6 +34,9 @@ pub struct Message<T, D> {
 
 impl<T, D> Message<T, D> {
     /// Default buffer size.
+    ///
+    /// Workers may be configured with another size, with
+    /// [`Config::batch_size`](crate::worker::Config::batch_size).
     pub fn default_length() -> usize {
         1024
     }
@@ -39,10 +49,18 @@ impl<T, D> Message<T, D> {
     /// Forms a message, and pushes contents at `pusher`.
     #[inline]
     pub fn push_at<P: Push<Bundle<T, D>>>(buffer: &mut Vec<D>, time: T, pusher: &mut P) {
+        Self::push_at_traced(buffer, time, None, pusher, Self::default_length())
+    }
+
+    /// Forms a message with lineage `lineage`, and pushes contents at `pusher`, leaving `buffer`
+    /// with capacity `length`.
+    #[inline]
+    pub(crate) fn push_at_traced<P: Push<Bundle<T, D>>>(buffer: &mut Vec<D>, time: T, lineage: Option<Lineage>, pusher: &mut P, length: usize) {
 
         let data = ::std::mem::replace(buffer, Vec::new());
-        let message = Message::new(time, data, 0, 0);
-        let mut bundle = Some(Bundle::from_typed(message));
+        let mut message = Bundle::from_typed(Message::new(time, data, 0, 0));
+        lineage::annotate(&mut message, lineage);
+        let mut bundle = Some(message);
 
         pusher.push(&mut bundle);
 
@@ -54,7 +72,40 @@ impl<T, D> Message<T, D> {
         }
 
         // TODO: Unclear we always want this here.
-        if buffer.capacity() != Self::default_length() {
-            *buffer = Vec::with_capacity(Self::default_length());
+        if buffer.capacity() != length {
+            *buffer = Vec::with_capacity(length);
         }
-    }}
+    }
+
+    /// Forms a message, and pushes contents at `pusher`, replenishing `buffer` from `pool`.
+    ///
+    /// If the pusher does not hand back a typed message, a buffer is drawn from `pool`
+    /// rather than freshly allocated.
+    #[inline]
+    pub fn push_at_pooled<P: Push<Bundle<T, D>>>(buffer: &mut Vec<D>, time: T, pusher: &mut P, pool: &BufferPool<D>) {
+        Self::push_at_pooled_traced(buffer, time, None, pusher, pool)
+    }
+
+    /// Forms a message with lineage `lineage`, and pushes contents at `pusher`, replenishing `buffer` from `pool`.
+    #[inline]
+    pub(crate) fn push_at_pooled_traced<P: Push<Bundle<T, D>>>(buffer: &mut Vec<D>, time: T, lineage: Option<Lineage>, pusher: &mut P, pool: &BufferPool<D>) {
+
+        let data = ::std::mem::take(buffer);
+        let mut message = Bundle::from_typed(Message::new(time, data, 0, 0));
+        lineage::annotate(&mut message, lineage);
+        let mut bundle = Some(message);
+
+        pusher.push(&mut bundle);
+
+        if let Some(message) = bundle {
+            if let Some(message) = message.if_typed() {
+                *buffer = message.data;
+                buffer.clear();
+            }
+        }
+
+        if buffer.capacity() != pool.length() {
+            *buffer = pool.take();
+        }
+    }
+}
@@ -9,12 +9,20 @@
 
 use std::marker::PhantomData;
 
-use crate::communication::{Push, Pull, Data};
+use crate::communication::{Push, Pull};
+use crate::communication::codec::{Codec, Native, Bincode};
 use crate::communication::allocator::thread::{ThreadPusher, ThreadPuller};
 
-use crate::worker::AsWorker;
+use crate::worker::{AsWorker, ExchangeCodec};
 use crate::dataflow::channels::pushers::Exchange as ExchangePusher;
+use crate::dataflow::channels::pushers::Targeted;
+use crate::dataflow::channels::pullers::{Prioritize, Recycler};
+use crate::dataflow::channels::pool::{BufferPool, DEFAULT_POOL_CAPACITY};
+use crate::dataflow::channels::stats::ChannelCounter;
+use crate::dataflow::channels::credits::{Credits, CreditPuller};
+use crate::dataflow::channels::steal::{StealPusher, StealPuller};
 use super::{Bundle, Message};
+use super::lineage::{self, Tracer};
 
 use crate::logging::TimelyLogger as Logger;
 
@@ -26,47 +34,411 @@ pub trait ParallelizationContract<T: 'static, D: 'static> {
     type Puller: Pull<Bundle<T, D>>+'static;
     /// Allocates a matched pair of push and pull endpoints implementing the pact.
     fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller);
+    /// A short name for the kind of contract, used to describe the operators it feeds.
+    fn kind(&self) -> &'static str { std::any::type_name::<Self>() }
 }
 
 /// A direct connection
 pub struct Pipeline;
+impl Pipeline {
+    /// A direct connection delivering received batches smallest timestamp first.
+    ///
+    /// See [`Prioritized`] for the operators this suits.
+    pub fn prioritized(self) -> Prioritized<Self> {
+        Prioritized::new(self)
+    }
+}
 impl<T: 'static, D: 'static> ParallelizationContract<T, D> for Pipeline {
     type Pusher = LogPusher<T, D, ThreadPusher<Bundle<T, D>>>;
     type Puller = LogPuller<T, D, ThreadPuller<Bundle<T, D>>>;
+    fn kind(&self) -> &'static str { "Pipeline" }
     fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
         let (pusher, puller) = allocator.pipeline::<Message<T, D>>(identifier, address);
+        let stats = allocator.channel_stats().counter(identifier);
         // // ignore `&mut A` and use thread allocator
         // let (pusher, puller) = Thread::new::<Bundle<T, D>>();
-        (LogPusher::new(pusher, allocator.index(), allocator.index(), identifier, logging.clone()),
-         LogPuller::new(puller, allocator.index(), identifier, logging.clone()))
+        (LogPusher::new(pusher, allocator.index(), allocator.index(), identifier, logging.clone()).with_stats(stats.clone()),
+         LogPuller::new(puller, allocator.index(), identifier, logging.clone()).with_stats(stats).with_lineage(allocator.extensions().lineage()))
+    }
+}
+
+/// A direct connection, whose puller grants `credits` for the records it pulls.
+///
+/// See the [`credits`](crate::dataflow::channels::credits) module for credit-based flow control.
+pub struct Credited {
+    credits: Credits,
+}
+impl Credited {
+    /// Allocates a direct connection granting credits to `credits`.
+    pub fn new(credits: &Credits) -> Self {
+        Credited { credits: credits.clone() }
+    }
+}
+impl<T: 'static, D: 'static> ParallelizationContract<T, D> for Credited {
+    type Pusher = <Pipeline as ParallelizationContract<T, D>>::Pusher;
+    type Puller = CreditPuller<<Pipeline as ParallelizationContract<T, D>>::Puller>;
+    fn kind(&self) -> &'static str { "Credited" }
+    fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
+        let (pusher, puller) = Pipeline.connect(allocator, identifier, address, logging);
+        (pusher, CreditPuller::new(puller, self.credits))
+    }
+}
+
+/// A connection whose batches are pulled by whichever worker of the process asks for them first.
+///
+/// See the [`steal`](crate::dataflow::channels::steal) module for the operators it suits.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{ToStream, Inspect};
+/// use timely::dataflow::operators::generic::Operator;
+/// use timely::dataflow::channels::pact::Steal;
+///
+/// timely::example(|scope| {
+///     (0..10u64).to_stream(scope)
+///               .unary(Steal, "Expensive", |_cap, _info| |input, output| {
+///                   input.for_each(|time, data| {
+///                       let mut data = data.replace(Vec::new());
+///                       for x in data.iter_mut() { *x = (0 .. *x).sum(); }
+///                       output.session(&time).give_vec(&mut data);
+///                   });
+///               })
+///               .inspect(|x| println!("seen: {:?}", x));
+/// });
+/// ```
+pub struct Steal;
+impl<T: Clone+Send+'static, D: Clone+Send+'static> ParallelizationContract<T, D> for Steal {
+    type Pusher = LogPusher<T, D, StealPusher<T, D>>;
+    type Puller = LogPuller<T, D, StealPuller<T, D>>;
+    fn kind(&self) -> &'static str { "Steal" }
+    fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
+        let queue = allocator.config().steal_queues.queue::<T, D>(identifier);
+        queue.register(allocator.sync_activator_for(address));
+        let stats = allocator.channel_stats().counter(identifier);
+        let puller = StealPuller::new(queue.clone(), allocator.activator_for(address));
+        (LogPusher::new(StealPusher::new(queue), allocator.index(), allocator.index(), identifier, logging.clone()).with_stats(stats.clone()),
+         LogPuller::new(puller, allocator.index(), identifier, logging).with_stats(stats).with_lineage(allocator.extensions().lineage()))
     }
 }
 
 /// An exchange between multiple observers by data
-pub struct Exchange<D, F: FnMut(&D)->u64+'static> { hash_func: F, phantom: PhantomData<D>, }
+///
+/// Messages sent between processes are serialized with the codec `C`, which by default
+/// is the serialization implied by `communication::Data`.
+pub struct Exchange<D, F: FnMut(&D)->u64+'static, C = Native> { hash_func: F, batch: Option<usize>, phantom: PhantomData<(D, C)>, }
 impl<D, F: FnMut(&D)->u64> Exchange<D, F> {
     /// Allocates a new `Exchange` pact from a distribution function.
     pub fn new(func: F) -> Exchange<D, F> {
         Exchange {
             hash_func:  func,
+            batch:      None,
+            phantom:    PhantomData,
+        }
+    }
+    /// Allocates a new `Exchange` pact from a distribution function, whose batches hold up to
+    /// `batch` records rather than the worker's
+    /// [`Config::batch_length`](crate::worker::Config::batch_length).
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::generic::Operator;
+    /// use timely::dataflow::channels::pact::Exchange;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..100_000u64).to_stream(scope)
+    ///                    .unary(Exchange::new_with_batch(|x: &u64| *x, 16384), "Large", |_cap, _info| |input, output| {
+    ///                        input.for_each(|time, data| {
+    ///                            output.session(&time).give_vec(&mut data.replace(Vec::new()));
+    ///                        });
+    ///                    })
+    ///                    .inspect(|x| assert!(*x < 100_000));
+    /// });
+    /// ```
+    pub fn new_with_batch(func: F, batch: usize) -> Exchange<D, F> {
+        assert!(batch > 0, "exchange batches must hold at least one record");
+        Exchange {
+            hash_func:  func,
+            batch:      Some(batch),
             phantom:    PhantomData,
         }
     }
 }
 
+impl<D, F: FnMut(&D)->u64, C> Exchange<D, F, C> {
+    /// Serializes messages sent between processes with the codec `C2` instead.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::HashMap;
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::generic::Operator;
+    /// use timely::dataflow::channels::pact::Exchange;
+    /// use timely::communication::codec::Bincode;
+    ///
+    /// timely::example(|scope| {
+    ///     // `HashMap` does not implement `Abomonation`, but does implement `serde`'s traits.
+    ///     (0..10u64).map(|x| { let mut map = HashMap::new(); map.insert(x, x); map })
+    ///               .to_stream(scope)
+    ///               .unary(Exchange::new(|map: &HashMap<u64, u64>| map.len() as u64).with_codec::<Bincode>(), "Bincode", |_cap, _info| |input, output| {
+    ///                   input.for_each(|time, data| {
+    ///                       output.session(&time).give_vec(&mut data.replace(Vec::new()));
+    ///                   });
+    ///               })
+    ///               .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    pub fn with_codec<C2>(self) -> Exchange<D, F, C2> {
+        Exchange {
+            hash_func:  self.hash_func,
+            batch:      self.batch,
+            phantom:    PhantomData,
+        }
+    }
+}
+
+impl<D, F: FnMut(&D)->u64, C> Exchange<D, F, C> {
+    /// An exchange delivering received batches smallest timestamp first.
+    ///
+    /// See [`Prioritized`] for the operators this suits.
+    pub fn prioritized(self) -> Prioritized<Self> {
+        Prioritized::new(self)
+    }
+}
+
 // Exchange uses a `Box<Pushable>` because it cannot know what type of pushable will return from the allocator.
-impl<T: Eq+Data+Clone, D: Data+Clone, F: FnMut(&D)->u64+'static> ParallelizationContract<T, D> for Exchange<D, F> {
+impl<T, D, F, C> ParallelizationContract<T, D> for Exchange<D, F, C>
+where
+    T: Eq+Clone+Send+Sync+'static,
+    D: Clone+Send+Sync+'static,
+    F: FnMut(&D)->u64+'static,
+    C: Codec<Message<T, D>>,
+{
     // TODO: The closure in the type prevents us from naming it.
     //       Could specialize `ExchangePusher` to a time-free version.
     type Pusher = Box<dyn Push<Bundle<T, D>>>;
     type Puller = Box<dyn Pull<Bundle<T, D>>>;
-    fn connect<A: AsWorker>(mut self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
-        let (senders, receiver) = allocator.allocate::<Message<T, D>>(identifier, address);
-        let senders = senders.into_iter().enumerate().map(|(i,x)| LogPusher::new(x, allocator.index(), i, identifier, logging.clone())).collect::<Vec<_>>();
-        (Box::new(ExchangePusher::new(senders, move |_, d| (self.hash_func)(d))), Box::new(LogPuller::new(receiver, allocator.index(), identifier, logging.clone())))
+    fn kind(&self) -> &'static str { "Exchange" }
+    fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
+        self.connect_with::<T, A, C>(allocator, identifier, address, logging)
     }
 }
 
+/// The boxed endpoints of an exchange.
+type Endpoints<T, D> = (Box<dyn Push<Bundle<T, D>>>, Box<dyn Pull<Bundle<T, D>>>);
+
+/// A codec for [`Exchange`] that defers to the worker's configuration.
+///
+/// Exchanges using this codec serialize their messages with the codec selected by
+/// [`Config::exchange_codec`](crate::worker::Config::exchange_codec), and so require of their
+/// records and timestamps the bounds of each codec that may be selected.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{ToStream, Inspect};
+/// use timely::dataflow::operators::generic::Operator;
+/// use timely::dataflow::channels::pact::{Exchange, Configured};
+/// use timely::worker::ExchangeCodec;
+///
+/// let mut config = timely::Config::process(2);
+/// config.worker = config.worker.exchange_codec(ExchangeCodec::Bincode);
+/// timely::execute(config, |worker| {
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         (0..10u64).to_stream(scope)
+///                   .unary(Exchange::new(|x: &u64| *x).with_codec::<Configured>(), "Configured", |_cap, _info| |input, output| {
+///                       input.for_each(|time, data| {
+///                           output.session(&time).give_vec(&mut data.replace(Vec::new()));
+///                       });
+///                   })
+///                   .inspect(|x| println!("seen: {:?}", x));
+///     });
+/// }).unwrap();
+/// ```
+pub struct Configured;
+
+impl<T, D, F> ParallelizationContract<T, D> for Exchange<D, F, Configured>
+where
+    T: Eq+Clone+Send+Sync+'static,
+    D: Clone+Send+Sync+'static,
+    F: FnMut(&D)->u64+'static,
+    Native: Codec<Message<T, D>>,
+    Bincode: Codec<Message<T, D>>,
+{
+    type Pusher = Box<dyn Push<Bundle<T, D>>>;
+    type Puller = Box<dyn Pull<Bundle<T, D>>>;
+    fn kind(&self) -> &'static str { "Exchange" }
+    fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
+        match allocator.config().codec() {
+            ExchangeCodec::Native => self.connect_with::<T, A, Native>(allocator, identifier, address, logging),
+            ExchangeCodec::Bincode => self.connect_with::<T, A, Bincode>(allocator, identifier, address, logging),
+        }
+    }
+}
+
+impl<D: Clone+Send+Sync+'static, F: FnMut(&D)->u64+'static, C: 'static> Exchange<D, F, C> {
+    /// Allocates the endpoints of the exchange, serializing messages with `C2`.
+    fn connect_with<T, A, C2>(mut self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> Endpoints<T, D>
+    where
+        T: Eq+Clone+Send+Sync+'static,
+        A: AsWorker,
+        C2: Codec<Message<T, D>>,
+    {
+        let (senders, receiver) = allocator.allocate_with::<Message<T, D>, C2>(identifier, address);
+        let stats = allocator.channel_stats().counter(identifier);
+        let sizer = edge_sizer(allocator, C2::length_in_bytes);
+        let senders = senders.into_iter().enumerate().map(|(i,x)| LogPusher::new(x, allocator.index(), i, identifier, logging.clone()).with_stats(stats.clone()).with_sizer(sizer)).collect::<Vec<_>>();
+        // Buffers drained by the receiving operator are recycled for outgoing messages.
+        let batch = self.batch.unwrap_or_else(|| allocator.config().batch_length());
+        let pool = BufferPool::with_length(DEFAULT_POOL_CAPACITY, batch);
+        let receiver = LogPuller::new(receiver, allocator.index(), identifier, logging.clone()).with_stats(stats).with_lineage(allocator.extensions().lineage());
+        (Box::new(ExchangePusher::with_pool(senders, move |_, d| (self.hash_func)(d), pool.clone())), Box::new(Recycler::new(receiver, pool, identifier, logging)))
+    }
+}
+
+/// A connection through another pact, whose puller delivers received batches smallest timestamp first.
+///
+/// Pullers otherwise deliver batches in the order they arrive, and an operator that works
+/// through its input a few batches at a time, yielding between them, may then spend its effort
+/// on newer times while older times wait to complete. With this pact each pull delivers, of the
+/// batches received so far, one with the least timestamp, so that the operator finishes with the
+/// oldest times first, and the frontier of its output advances sooner. Batches at the same time
+/// are delivered in the order they arrived.
+///
+/// The pact only reorders batches pending at the operator, and brings no benefit to operators
+/// that drain their input whenever they are scheduled. The `prioritized` example measures the
+/// latency with which epochs complete, with and without it.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{ToStream, Inspect};
+/// use timely::dataflow::operators::generic::Operator;
+/// use timely::dataflow::channels::pact::Exchange;
+///
+/// timely::example(|scope| {
+///     (0..10u64).to_stream(scope)
+///               .unary(Exchange::new(|x: &u64| *x).prioritized(), "OldestFirst", |_cap, _info| |input, output| {
+///                   input.for_each(|time, data| {
+///                       output.session(&time).give_vec(&mut data.replace(Vec::new()));
+///                   });
+///               })
+///               .inspect(|x| println!("seen: {:?}", x));
+/// });
+/// ```
+pub struct Prioritized<P> {
+    pact: P,
+}
+impl<P> Prioritized<P> {
+    /// Allocates a connection through `pact`, delivering received batches smallest timestamp first.
+    pub fn new(pact: P) -> Self {
+        Prioritized { pact }
+    }
+}
+impl<T: Ord+'static, D: 'static, P: ParallelizationContract<T, D>> ParallelizationContract<T, D> for Prioritized<P> {
+    type Pusher = P::Pusher;
+    type Puller = Prioritize<T, D, P::Puller>;
+    fn kind(&self) -> &'static str { "Prioritized" }
+    fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
+        let (pusher, puller) = self.pact.connect(allocator, identifier, address, logging);
+        (pusher, Prioritize::new(puller).with_lineage(allocator.extensions().lineage()))
+    }
+}
+
+/// A connection from each worker to one worker, determined by the index of the sending worker.
+///
+/// Records pushed on each worker are received by a single worker, in the order they were pushed.
+/// This suits pipeline-parallel topologies, in which each worker hosts a stage of a pipeline and
+/// passes its results to the worker hosting the next stage.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{ToStream, Inspect};
+/// use timely::dataflow::operators::generic::Operator;
+/// use timely::dataflow::channels::pact::OneToOne;
+///
+/// timely::execute(timely::Config::process(3), |worker| {
+///     let index = worker.index();
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         (0..10u64).filter(move |x| x % 3 == index as u64)
+///                   .to_stream(scope)
+///                   .unary(OneToOne::offset(1), "Shift", |_cap, _info| |input, output| {
+///                       input.for_each(|time, data| {
+///                           output.session(&time).give_vec(&mut data.replace(Vec::new()));
+///                       });
+///                   })
+///                   .inspect(move |x| assert_eq!((*x as usize + 1) % 3, index));
+///     });
+/// }).unwrap();
+/// ```
+pub struct OneToOne {
+    route: Route,
+}
+
+// The worker to which each worker sends its records.
+enum Route {
+    Offset(usize),
+    Permutation(Vec<usize>),
+}
+
+impl OneToOne {
+    /// Allocates a connection from each worker `i` to worker `(i + offset) % peers`.
+    pub fn offset(offset: usize) -> Self {
+        OneToOne { route: Route::Offset(offset) }
+    }
+    /// Allocates a connection from each worker `i` to worker `permutation[i]`.
+    ///
+    /// The permutation must hold each worker index once.
+    pub fn permutation(permutation: Vec<usize>) -> Self {
+        OneToOne { route: Route::Permutation(permutation) }
+    }
+    /// The worker receiving the records of worker `index`, among `peers` workers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the connection was allocated from a permutation of other than `peers` workers.
+    pub fn target(&self, index: usize, peers: usize) -> usize {
+        match &self.route {
+            Route::Offset(offset) => (index + offset % peers) % peers,
+            Route::Permutation(permutation) => {
+                let mut sorted = permutation.clone();
+                sorted.sort();
+                assert!(
+                    sorted.into_iter().eq(0 .. peers),
+                    "{:?} is not a permutation of {} workers", permutation, peers
+                );
+                permutation[index]
+            },
+        }
+    }
+}
+
+impl<T, D> ParallelizationContract<T, D> for OneToOne
+where
+    T: Eq+Clone+Send+Sync+'static,
+    D: Clone+Send+Sync+'static,
+    Native: Codec<Message<T, D>>,
+{
+    type Pusher = Targeted<LogPusher<T, D, Box<dyn Push<Bundle<T, D>>>>>;
+    type Puller = LogPuller<T, D, Box<dyn Pull<Bundle<T, D>>>>;
+    fn kind(&self) -> &'static str { "OneToOne" }
+    fn connect<A: AsWorker>(self, allocator: &mut A, identifier: usize, address: &[usize], logging: Option<Logger>) -> (Self::Pusher, Self::Puller) {
+        let target = self.target(allocator.index(), allocator.peers());
+        let (senders, receiver) = allocator.allocate_with::<Message<T, D>, Native>(identifier, address);
+        let stats = allocator.channel_stats().counter(identifier);
+        let sizer = edge_sizer(allocator, Native::length_in_bytes);
+        let senders = senders.into_iter().enumerate().map(|(i,x)| LogPusher::new(x, allocator.index(), i, identifier, logging.clone()).with_stats(stats.clone()).with_sizer(sizer)).collect::<Vec<_>>();
+        let receiver = LogPuller::new(receiver, allocator.index(), identifier, logging).with_stats(stats).with_lineage(allocator.extensions().lineage());
+        (Targeted::new(senders, target), receiver)
+    }
+}
+
+/// Measures the serialized bytes of a batch.
+type Sizer<B> = fn(&B) -> usize;
+
+/// Returns `sizer` if edge statistics are logged, as measuring batches is otherwise wasted work.
+fn edge_sizer<A: AsWorker, B>(allocator: &A, sizer: Sizer<B>) -> Option<Sizer<B>> {
+    allocator.log_register().get::<crate::logging::EdgeStatsEvent>("timely/communication").map(|_| sizer)
+}
+
 /// Wraps a `Message<T,D>` pusher to provide a `Push<(T, Content<D>)>`.
 pub struct LogPusher<T, D, P: Push<Bundle<T, D>>> {
     pusher: P,
@@ -76,6 +448,8 @@ pub struct LogPusher<T, D, P: Push<Bundle<T, D>>> {
     target: usize,
     phantom: ::std::marker::PhantomData<(T, D)>,
     logging: Option<Logger>,
+    stats: Option<ChannelCounter>,
+    sizer: Option<Sizer<Bundle<T, D>>>,
 }
 impl<T, D, P: Push<Bundle<T, D>>> LogPusher<T, D, P> {
     /// Allocates a new pusher.
@@ -88,8 +462,20 @@ impl<T, D, P: Push<Bundle<T, D>>> LogPusher<T, D, P> {
             target,
             phantom: ::std::marker::PhantomData,
             logging,
+            stats: None,
+            sizer: None,
         }
     }
+    /// Counts pushed batches with `stats`.
+    pub(crate) fn with_stats(mut self, stats: ChannelCounter) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+    /// Counts the bytes of batches pushed to other workers as measured by `sizer`, if any.
+    pub(crate) fn with_sizer(mut self, sizer: Option<Sizer<Bundle<T, D>>>) -> Self {
+        self.sizer = sizer;
+        self
+    }
 }
 
 impl<T, D, P: Push<Bundle<T, D>>> Push<Bundle<T, D>> for LogPusher<T, D, P> {
@@ -111,7 +497,16 @@ impl<T, D, P: Push<Bundle<T, D>>> Push<Bundle<T, D>> for LogPusher<T, D, P> {
                 target: self.target,
                 seq_no: self.counter-1,
                 length: bundle.data.len(),
+                lineage: lineage::of(bundle),
             }));
+            if let Some(stats) = self.stats.as_ref() {
+                stats.pushed::<D>(bundle.data.len());
+                let bytes = match self.sizer {
+                    Some(sizer) if self.source != self.target => sizer(bundle),
+                    _ => 0,
+                };
+                stats.pushed_edge(self.source, self.target, bundle.data.len(), bytes);
+            }
         }
         self.pusher.push(pair);
     }
@@ -124,6 +519,8 @@ pub struct LogPuller<T, D, P: Pull<Bundle<T, D>>> {
     index: usize,
     phantom: ::std::marker::PhantomData<(T, D)>,
     logging: Option<Logger>,
+    stats: Option<ChannelCounter>,
+    tracer: Option<Tracer>,
 }
 impl<T, D, P: Pull<Bundle<T, D>>> LogPuller<T, D, P> {
     /// Allocates a new `Puller`.
@@ -134,8 +531,20 @@ impl<T, D, P: Pull<Bundle<T, D>>> LogPuller<T, D, P> {
             index,
             phantom: ::std::marker::PhantomData,
             logging,
+            stats: None,
+            tracer: None,
         }
     }
+    /// Counts pulled batches with `stats`.
+    pub(crate) fn with_stats(mut self, stats: ChannelCounter) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+    /// Notes the lineage of pulled batches with `tracer`, if any.
+    pub(crate) fn with_lineage(mut self, tracer: Option<Tracer>) -> Self {
+        self.tracer = tracer;
+        self
+    }
 }
 
 impl<T, D, P: Pull<Bundle<T, D>>> Pull<Bundle<T, D>> for LogPuller<T, D, P> {
@@ -152,7 +561,14 @@ impl<T, D, P: Pull<Bundle<T, D>>> Pull<Bundle<T, D>> for LogPuller<T, D, P> {
                 target,
                 seq_no: bundle.seq,
                 length: bundle.data.len(),
+                lineage: lineage::of(bundle),
             }));
+            if let Some(stats) = self.stats.as_ref() {
+                stats.pulled::<D>(bundle.data.len());
+            }
+            if let Some(tracer) = self.tracer.as_ref() {
+                tracer.receive(lineage::of(bundle));
+            }
         }
         result
     }
@@ -0,0 +1,131 @@
+//! A pool of empty buffers, recycled from a channel's puller to its pushers.
+//!
+//! Exchange channels allocate a fresh `Vec` each time a batch is sent. Once the receiving
+//! operator has drained a batch, its backing allocation can be returned to the pool and
+//! handed out to the next outgoing batch, rather than returned to the allocator.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::dataflow::channels::Message;
+
+/// The number of buffers a pool retains by default.
+pub const DEFAULT_POOL_CAPACITY: usize = 16;
+
+/// Counts describing the effectiveness of a `BufferPool`.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub struct PoolStats {
+    /// Requests for a buffer served from the pool.
+    pub hits: usize,
+    /// Requests for a buffer that required a fresh allocation.
+    pub misses: usize,
+    /// Buffers returned to the pool.
+    pub recycled: usize,
+    /// Buffers offered to the pool but dropped, because the pool was full or the buffer unsuitable.
+    pub discarded: usize,
+}
+
+impl PoolStats {
+    /// The fraction of buffer requests served from the pool, or `None` if there were no requests.
+    pub fn hit_rate(&self) -> Option<f64> {
+        let requests = self.hits + self.misses;
+        if requests > 0 { Some(self.hits as f64 / requests as f64) } else { None }
+    }
+}
+
+struct PoolInner<D> {
+    buffers: Vec<Vec<D>>,
+    capacity: usize,
+    length: usize,
+    stats: PoolStats,
+}
+
+/// A shared pool of empty buffers of `Message::default_length()` capacity.
+///
+/// Cloning a `BufferPool` produces a handle to the same pool.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::channels::pool::BufferPool;
+///
+/// let pool = BufferPool::<u64>::new(4);
+/// let buffer = pool.take();
+/// pool.give(buffer);
+/// let _buffer = pool.take();
+///
+/// let stats = pool.stats();
+/// assert_eq!((stats.hits, stats.misses, stats.recycled), (1, 1, 1));
+/// assert_eq!(stats.hit_rate(), Some(0.5));
+/// ```
+pub struct BufferPool<D> {
+    inner: Rc<RefCell<PoolInner<D>>>,
+}
+
+impl<D> Clone for BufferPool<D> {
+    fn clone(&self) -> Self {
+        BufferPool { inner: self.inner.clone() }
+    }
+}
+
+impl<D> Default for BufferPool<D> {
+    fn default() -> Self {
+        BufferPool::new(DEFAULT_POOL_CAPACITY)
+    }
+}
+
+impl<D> BufferPool<D> {
+    /// Allocates a new pool retaining at most `capacity` buffers.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_length(capacity, Message::<(), D>::default_length())
+    }
+
+    /// Allocates a new pool retaining at most `capacity` buffers, each of capacity `length`.
+    pub fn with_length(capacity: usize, length: usize) -> Self {
+        BufferPool {
+            inner: Rc::new(RefCell::new(PoolInner {
+                buffers: Vec::new(),
+                capacity,
+                length,
+                stats: PoolStats::default(),
+            })),
+        }
+    }
+
+    /// The capacity of the buffers the pool hands out.
+    pub fn length(&self) -> usize {
+        self.inner.borrow().length
+    }
+
+    /// Returns an empty buffer, from the pool if one is available.
+    pub fn take(&self) -> Vec<D> {
+        let mut inner = self.inner.borrow_mut();
+        if let Some(buffer) = inner.buffers.pop() {
+            inner.stats.hits += 1;
+            buffer
+        }
+        else {
+            inner.stats.misses += 1;
+            Vec::with_capacity(inner.length)
+        }
+    }
+
+    /// Offers a drained buffer back to the pool.
+    ///
+    /// The buffer is cleared, and retained only if it has the default capacity and the pool has room.
+    pub fn give(&self, mut buffer: Vec<D>) {
+        let mut inner = self.inner.borrow_mut();
+        if buffer.capacity() == inner.length && inner.buffers.len() < inner.capacity {
+            buffer.clear();
+            inner.buffers.push(buffer);
+            inner.stats.recycled += 1;
+        }
+        else {
+            inner.stats.discarded += 1;
+        }
+    }
+
+    /// Reports the pool's accumulated statistics.
+    pub fn stats(&self) -> PoolStats {
+        self.inner.borrow().stats
+    }
+}
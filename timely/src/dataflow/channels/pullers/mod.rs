@@ -1,5 +1,9 @@
 pub use self::counter::Counter;
+pub use self::recycle::Recycler;
+pub use self::priority::Prioritize;
 pub mod counter;
+pub mod recycle;
+pub mod priority;
 
 
 // pub trait Pullable<T, D> {
@@ -0,0 +1,87 @@
+//! A wrapper which delivers received messages in order of their timestamps.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::dataflow::channels::Bundle;
+use crate::dataflow::channels::lineage::{self, Tracer};
+use crate::communication::Pull;
+
+/// A wrapper which delivers the messages it has received smallest timestamp first.
+///
+/// Each call to `pull` drains the messages available from the wrapped puller, and delivers the
+/// pending message with the smallest timestamp. Messages with equal timestamps are delivered in
+/// the order they were received. An operator that pulls a few messages at a time, and yields
+/// with messages pending, thus works on the oldest times first, even as messages at newer
+/// times arrive.
+pub struct Prioritize<T, D, P: Pull<Bundle<T, D>>> {
+    puller: P,
+    pending: BinaryHeap<Pending<T, D>>,
+    received: usize,
+    current: Option<Bundle<T, D>>,
+    tracer: Option<Tracer>,
+}
+
+impl<T, D, P: Pull<Bundle<T, D>>> Prioritize<T, D, P> {
+    /// Allocates a new `Prioritize` delivering the messages of `puller`.
+    pub fn new(puller: P) -> Self {
+        Prioritize {
+            puller,
+            pending: BinaryHeap::new(),
+            received: 0,
+            current: None,
+            tracer: None,
+        }
+    }
+    /// Notes the lineage of delivered messages with `tracer`, if any.
+    pub(crate) fn with_lineage(mut self, tracer: Option<Tracer>) -> Self {
+        self.tracer = tracer;
+        self
+    }
+    /// The number of messages received and not yet delivered.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+impl<T: Ord, D, P: Pull<Bundle<T, D>>> Pull<Bundle<T, D>> for Prioritize<T, D, P> {
+    #[inline]
+    fn pull(&mut self) -> &mut Option<Bundle<T, D>> {
+        while let Some(bundle) = self.puller.pull().take() {
+            self.pending.push(Pending { bundle, received: self.received });
+            self.received += 1;
+        }
+        self.current = self.pending.pop().map(|pending| pending.bundle);
+        if let (Some(tracer), Some(bundle)) = (self.tracer.as_ref(), self.current.as_ref()) {
+            // The wrapped puller noted the lineage of the last message received, not delivered.
+            tracer.receive(lineage::of(bundle));
+        }
+        &mut self.current
+    }
+}
+
+// A received message, ordered so that the greatest is the earliest received at the least time.
+struct Pending<T, D> {
+    bundle: Bundle<T, D>,
+    received: usize,
+}
+
+impl<T: Ord, D> Ord for Pending<T, D> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.bundle.time.cmp(&self.bundle.time).then(other.received.cmp(&self.received))
+    }
+}
+
+impl<T: Ord, D> PartialOrd for Pending<T, D> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord, D> PartialEq for Pending<T, D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord, D> Eq for Pending<T, D> { }
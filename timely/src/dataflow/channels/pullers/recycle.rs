@@ -0,0 +1,61 @@
+//! A wrapper which returns drained message buffers to a shared pool.
+
+use crate::dataflow::channels::Bundle;
+use crate::dataflow::channels::pool::BufferPool;
+use crate::communication::Pull;
+use crate::logging::TimelyLogger as Logger;
+
+/// A wrapper which returns the buffers of consumed messages to a `BufferPool`.
+///
+/// A pulled message is retained until the next call to `pull`, at which point the
+/// consumer is done with it and its buffer, if typed, is offered to the pool.
+pub struct Recycler<T, D, P: Pull<Bundle<T, D>>> {
+    puller: P,
+    current: Option<Bundle<T, D>>,
+    pool: BufferPool<D>,
+    channel: usize,
+    logging: Option<Logger>,
+}
+
+impl<T, D, P: Pull<Bundle<T, D>>> Recycler<T, D, P> {
+    /// Allocates a new `Recycler` returning buffers to `pool`.
+    pub fn new(puller: P, pool: BufferPool<D>, channel: usize, logging: Option<Logger>) -> Self {
+        Recycler {
+            puller,
+            current: None,
+            pool,
+            channel,
+            logging,
+        }
+    }
+    /// The pool to which buffers are returned.
+    pub fn pool(&self) -> &BufferPool<D> {
+        &self.pool
+    }
+}
+
+impl<T, D, P: Pull<Bundle<T, D>>> Pull<Bundle<T, D>> for Recycler<T, D, P> {
+    #[inline]
+    fn pull(&mut self) -> &mut Option<Bundle<T, D>> {
+        if let Some(message) = self.current.take().and_then(|bundle| bundle.if_typed()) {
+            self.pool.give(message.data);
+        }
+        self.current = self.puller.pull().take();
+        &mut self.current
+    }
+}
+
+impl<T, D, P: Pull<Bundle<T, D>>> Drop for Recycler<T, D, P> {
+    fn drop(&mut self) {
+        if let Some(logger) = self.logging.as_ref() {
+            let stats = self.pool.stats();
+            logger.log(crate::logging::BufferPoolEvent {
+                channel: self.channel,
+                hits: stats.hits,
+                misses: stats.misses,
+                recycled: stats.recycled,
+                discarded: stats.discarded,
+            });
+        }
+    }
+}
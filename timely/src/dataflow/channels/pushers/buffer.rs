@@ -2,9 +2,11 @@
 //! with the performance of batched sends.
 
 use crate::dataflow::channels::{Bundle, Message};
+use crate::dataflow::channels::lineage::{Lineage, Tracer};
 use crate::progress::Timestamp;
 use crate::dataflow::operators::Capability;
 use crate::communication::Push;
+use crate::dataflow::channels::credits::{Credits, YieldNow};
 
 /// Buffers data sent at the same time, for efficient communication.
 ///
@@ -13,6 +15,9 @@ use crate::communication::Push;
 pub struct Buffer<T, D, P: Push<Bundle<T, D>>> {
     time: Option<T>,  // the currently open time, if it is open
     buffer: Vec<D>,   // a buffer for records, to send at self.time
+    length: usize,    // the number of records to buffer before sending them
+    lineage: Option<Lineage>,   // the lineage of the buffered records, if traced
+    tracer: Option<Tracer>,     // the lineage state of the worker, if it traces lineage
     pusher: P,
 }
 
@@ -23,19 +28,49 @@ impl<T, D, P: Push<Bundle<T, D>>> Buffer<T, D, P> where T: Eq+Clone {
         Buffer {
             time: None,
             buffer: Vec::with_capacity(Message::<T, D>::default_length()),
+            length: Message::<T, D>::default_length(),
+            lineage: None,
+            tracer: None,
             pusher,
         }
     }
 
+    /// Sends records in messages of `length` records, rather than `Message::default_length()`.
+    ///
+    /// Operators usually buffer as many records as the worker's
+    /// [`Config::batch_length`](crate::worker::Config::batch_length).
+    pub fn with_length(mut self, length: usize) -> Self {
+        assert!(length > 0, "messages must hold at least one record");
+        self.buffer = Vec::with_capacity(length);
+        self.length = length;
+        self
+    }
+
+    /// Sends records with the lineage traced by `tracer`, if any.
+    pub(crate) fn with_lineage(mut self, tracer: Option<Tracer>) -> Self {
+        self.tracer = tracer;
+        self
+    }
+
     /// Returns a `Session`, which accepts data to send at the associated time
     pub fn session(&mut self, time: &T) -> Session<T, D, P> {
         if let Some(true) = self.time.as_ref().map(|x| x != time) { self.flush(); }
+        self.trace();
         self.time = Some(time.clone());
         Session { buffer: self }
     }
+    /// Returns a `CreditedSession`, which accepts data to send at the associated time while
+    /// `credits` remain.
+    pub fn credited_session<'a>(&'a mut self, time: &T, credits: &'a Credits) -> CreditedSession<'a, T, D, P> {
+        if let Some(true) = self.time.as_ref().map(|x| x != time) { self.flush(); }
+        self.trace();
+        self.time = Some(time.clone());
+        CreditedSession { buffer: self, credits }
+    }
     /// Allocates a new `AutoflushSession` which flushes itself on drop.
     pub fn autoflush_session(&mut self, cap: Capability<T>) -> AutoflushSession<T, D, P> where T: Timestamp {
         if let Some(true) = self.time.as_ref().map(|x| x != cap.time()) { self.flush(); }
+        self.trace();
         self.time = Some(cap.time().clone());
         AutoflushSession {
             buffer: self,
@@ -54,11 +89,19 @@ impl<T, D, P: Push<Bundle<T, D>>> Buffer<T, D, P> where T: Eq+Clone {
         self.pusher.push(&mut None);
     }
 
+    // Flushes records buffered with a lineage other than that of the records to come, and notes theirs.
+    fn trace(&mut self) {
+        if self.tracer.as_ref().is_some_and(|tracer| tracer.changed(self.lineage)) { self.flush(); }
+        if let (Some(tracer), true) = (self.tracer.as_ref(), self.buffer.is_empty()) {
+            self.lineage = tracer.stamp();
+        }
+    }
+
     /// moves the contents of
     fn flush(&mut self) {
         if !self.buffer.is_empty() {
             let time = self.time.as_ref().unwrap().clone();
-            Message::push_at(&mut self.buffer, time, &mut self.pusher);
+            Message::push_at_traced(&mut self.buffer, time, self.lineage, &mut self.pusher, self.length);
         }
     }
 
@@ -79,7 +122,7 @@ impl<T, D, P: Push<Bundle<T, D>>> Buffer<T, D, P> where T: Eq+Clone {
         }
 
         let time = self.time.as_ref().expect("Buffer::give_vec(): time is None.").clone();
-        Message::push_at(vector, time, &mut self.pusher);
+        Message::push_at_traced(vector, time, self.lineage, &mut self.pusher, self.length);
     }
 }
 
@@ -119,6 +162,44 @@ impl<'a, T, D, P: Push<Bundle<T, D>>+'a> Session<'a, T, D, P>  where T: Eq+Clone
     }
 }
 
+/// An output session for sending records at a specified time, while credits remain.
+///
+/// Each record takes a credit from the session's [`Credits`]. Once they run out, the session
+/// sends the records it has buffered, and refuses further records until credits are granted.
+pub struct CreditedSession<'a, T, D, P: Push<Bundle<T, D>>+'a> where T: Eq+Clone+'a, D: 'a {
+    buffer: &'a mut Buffer<T, D, P>,
+    credits: &'a Credits,
+}
+
+impl<'a, T, D, P: Push<Bundle<T, D>>+'a> CreditedSession<'a, T, D, P>  where T: Eq+Clone+'a, D: 'a {
+    /// Provides one record at the time specified by the session, or returns it if no credits
+    /// are available.
+    #[inline]
+    pub fn give(&mut self, data: D) -> Result<(), YieldNow<D>> {
+        if self.credits.try_take() {
+            self.buffer.give(data);
+            Ok(())
+        }
+        else {
+            self.buffer.flush();
+            Err(YieldNow(data))
+        }
+    }
+    /// Provides records from an iterator at the time specified by the session, until credits
+    /// run out, in which case the first record refused is returned and the rest remain in `iter`.
+    #[inline]
+    pub fn give_iterator<I: Iterator<Item=D>>(&mut self, iter: &mut I) -> Result<(), YieldNow<D>> {
+        for item in iter {
+            self.give(item)?;
+        }
+        Ok(())
+    }
+    /// Reports the credits available.
+    pub fn available(&self) -> usize {
+        self.credits.available()
+    }
+}
+
 /// A session which will flush itself when dropped.
 pub struct AutoflushSession<'a, T: Timestamp, D, P: Push<Bundle<T, D>>+'a> where
     T: Eq+Clone+'a, D: 'a {
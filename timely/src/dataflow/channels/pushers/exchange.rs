@@ -3,38 +3,85 @@
 use crate::Data;
 use crate::communication::Push;
 use crate::dataflow::channels::{Bundle, Message};
+use crate::dataflow::channels::pool::BufferPool;
+use crate::dataflow::channels::lineage::{self, Lineage};
+
+/// The batch size at which each target's buffer starts.
+pub const INITIAL_BATCH: usize = 16;
 
 // TODO : Software write combining
 /// Distributes records among target pushees according to a distribution function.
+///
+/// Each target's batch size adapts to the observed rate of records: it starts at
+/// `INITIAL_BATCH`, doubles (up to the length of the pool's buffers, by default
+/// `Message::default_length()`) each time a buffer fills, and halves when a flush
+/// finds the buffer less than half full. Pushing `None`
+/// flushes all buffers regardless of their fill, so that records are not held back
+/// from the recipients while the operator's frontier advances.
 pub struct Exchange<T, D, P: Push<Bundle<T, D>>, H: FnMut(&T, &D) -> u64> {
     pushers: Vec<P>,
     buffers: Vec<Vec<D>>,
+    limits: Vec<usize>,
+    max: usize,
     current: Option<T>,
+    lineage: Option<Lineage>,
     hash_func: H,
+    pool: BufferPool<D>,
 }
 
 impl<T: Clone, D, P: Push<Bundle<T, D>>, H: FnMut(&T, &D)->u64>  Exchange<T, D, P, H> {
     /// Allocates a new `Exchange` from a supplied set of pushers and a distribution function.
     pub fn new(pushers: Vec<P>, key: H) -> Exchange<T, D, P, H> {
+        Self::with_pool(pushers, key, BufferPool::default())
+    }
+    /// Allocates a new `Exchange` which draws its outgoing buffers from `pool`.
+    ///
+    /// The pool is usually shared with the channel's puller, which returns buffers to it
+    /// once they have been drained by the receiving operator. Batches grow at most to the
+    /// length of the pool's buffers.
+    pub fn with_pool(pushers: Vec<P>, key: H, pool: BufferPool<D>) -> Exchange<T, D, P, H> {
         let mut buffers = vec![];
         for _ in 0..pushers.len() {
-            buffers.push(Vec::with_capacity(Message::<T, D>::default_length()));
+            buffers.push(pool.take());
         }
+        let max = pool.length();
+        let limits = vec![std::cmp::min(INITIAL_BATCH, max); pushers.len()];
         Exchange {
             pushers,
             hash_func: key,
             buffers,
+            limits,
+            max,
             current: None,
+            lineage: None,
+            pool,
         }
     }
+    /// The current batch size for the target `index`.
+    pub fn batch_size(&self, index: usize) -> usize {
+        self.limits[index]
+    }
     #[inline]
     fn flush(&mut self, index: usize) {
         if !self.buffers[index].is_empty() {
+            // A buffer flushed before it fills indicates a trickle of records; send smaller batches.
+            if self.buffers[index].len() < self.limits[index] / 2 {
+                self.limits[index] = std::cmp::max(self.limits[index] / 2, std::cmp::min(INITIAL_BATCH, self.max));
+            }
             if let Some(ref time) = self.current {
-                Message::push_at(&mut self.buffers[index], time.clone(), &mut self.pushers[index]);
+                Message::push_at_pooled_traced(&mut self.buffers[index], time.clone(), self.lineage, &mut self.pushers[index], &self.pool);
             }
         }
     }
+    #[inline]
+    fn push_record(&mut self, index: usize, datum: D) {
+        self.buffers[index].push(datum);
+        if self.buffers[index].len() >= self.limits[index] {
+            // A full buffer indicates a burst of records; send larger batches.
+            self.limits[index] = std::cmp::min(self.limits[index] * 2, self.max);
+            self.flush(index);
+        }
+    }
 }
 
 impl<T: Eq+Data, D: Data, P: Push<Bundle<T, D>>, H: FnMut(&T, &D)->u64> Push<Bundle<T, D>> for Exchange<T, D, P, H> {
@@ -46,28 +93,26 @@ impl<T: Eq+Data, D: Data, P: Push<Bundle<T, D>>, H: FnMut(&T, &D)->u64> Push<Bun
         }
         else if let Some(message) = message {
 
+            let traced = lineage::of(message);
             let message = message.as_mut();
             let time = &message.time;
             let data = &mut message.data;
 
-            // if the time isn't right, flush everything.
-            if self.current.as_ref().map_or(false, |x| x != time) {
+            // if the time or lineage isn't right, flush everything.
+            if self.current.as_ref().is_some_and(|x| x != time) || self.lineage != traced {
                 for index in 0..self.pushers.len() {
                     self.flush(index);
                 }
             }
             self.current = Some(time.clone());
+            self.lineage = traced;
 
             // if the number of pushers is a power of two, use a mask
             if (self.pushers.len() & (self.pushers.len() - 1)) == 0 {
                 let mask = (self.pushers.len() - 1) as u64;
                 for datum in data.drain(..) {
                     let index = (((self.hash_func)(time, &datum)) & mask) as usize;
-
-                    self.buffers[index].push(datum);
-                    if self.buffers[index].len() == self.buffers[index].capacity() {
-                        self.flush(index);
-                    }
+                    self.push_record(index, datum);
 
                     // unsafe {
                     //     self.buffers.get_unchecked_mut(index).push(datum);
@@ -82,10 +127,7 @@ impl<T: Eq+Data, D: Data, P: Push<Bundle<T, D>>, H: FnMut(&T, &D)->u64> Push<Bun
             else {
                 for datum in data.drain(..) {
                     let index = (((self.hash_func)(time, &datum)) % self.pushers.len() as u64) as usize;
-                    self.buffers[index].push(datum);
-                    if self.buffers[index].len() == self.buffers[index].capacity() {
-                        self.flush(index);
-                    }
+                    self.push_record(index, datum);
                 }
             }
 
@@ -99,3 +141,66 @@ impl<T: Eq+Data, D: Data, P: Push<Bundle<T, D>>, H: FnMut(&T, &D)->u64> Push<Bun
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use std::rc::Rc;
+    use std::cell::RefCell;
+
+    use crate::communication::Push;
+    use crate::dataflow::channels::{Bundle, Message};
+    use crate::dataflow::channels::pool::BufferPool;
+    use super::{Exchange, INITIAL_BATCH};
+
+    // Records the lengths of the messages pushed at it.
+    struct Lengths(Rc<RefCell<Vec<usize>>>);
+    impl Push<Bundle<u64, u64>> for Lengths {
+        fn push(&mut self, element: &mut Option<Bundle<u64, u64>>) {
+            if let Some(bundle) = element.take() {
+                self.0.borrow_mut().push(bundle.data.len());
+            }
+        }
+    }
+
+    #[test]
+    fn adaptive_batches() {
+
+        let lengths = [Rc::new(RefCell::new(Vec::new())), Rc::new(RefCell::new(Vec::new()))];
+        let pushers = lengths.iter().map(|l| Lengths(l.clone())).collect();
+        let mut exchange = Exchange::new(pushers, |_, x: &u64| *x);
+        assert_eq!(exchange.batch_size(0), INITIAL_BATCH);
+
+        // a burst of records grows the batch size toward the maximum.
+        let mut data = (0 .. 100_000).map(|x| 2 * x).collect::<Vec<u64>>();
+        Message::push_at(&mut data, 0, &mut exchange);
+        assert_eq!(exchange.batch_size(0), Message::<u64, u64>::default_length());
+        assert_eq!(exchange.batch_size(1), INITIAL_BATCH);
+
+        // a flush sends half-full buffers immediately.
+        exchange.push(&mut None);
+        assert_eq!(lengths[0].borrow().iter().sum::<usize>(), 100_000);
+
+        // a trickle of records shrinks the batch size.
+        for round in 1 .. 20 {
+            Message::push_at(&mut vec![0], round, &mut exchange);
+            exchange.push(&mut None);
+        }
+        assert_eq!(exchange.batch_size(0), INITIAL_BATCH);
+    }
+
+    #[test]
+    fn pool_length_bounds_batches() {
+
+        let lengths = [Rc::new(RefCell::new(Vec::new())), Rc::new(RefCell::new(Vec::new()))];
+        let pushers = lengths.iter().map(|l| Lengths(l.clone())).collect();
+        let mut exchange = Exchange::with_pool(pushers, |_, x: &u64| *x, BufferPool::with_length(4, 8));
+        assert_eq!(exchange.batch_size(0), 8);
+
+        let mut data = (0 .. 100).map(|x| 2 * x).collect::<Vec<u64>>();
+        Message::push_at(&mut data, 0, &mut exchange);
+        exchange.push(&mut None);
+        assert!(lengths[0].borrow().iter().all(|length| *length <= 8));
+        assert_eq!(lengths[0].borrow().iter().sum::<usize>(), 100);
+    }
+}
@@ -0,0 +1,32 @@
+//! A pusher that sends all data to one of several target pushees.
+
+use crate::communication::Push;
+use crate::dataflow::channels::Bundle;
+
+/// Sends all pushed data to one target pushee, and flushes of the pusher to all of them.
+pub struct Targeted<P> {
+    pushers: Vec<P>,
+    target: usize,
+}
+
+impl<P> Targeted<P> {
+    /// Allocates a new `Targeted` sending data to `pushers[target]`.
+    pub fn new(pushers: Vec<P>, target: usize) -> Self {
+        assert!(target < pushers.len(), "target {} out of range for {} pushers", target, pushers.len());
+        Targeted { pushers, target }
+    }
+}
+
+impl<T, D, P: Push<Bundle<T, D>>> Push<Bundle<T, D>> for Targeted<P> {
+    #[inline]
+    fn push(&mut self, message: &mut Option<Bundle<T, D>>) {
+        if message.is_some() {
+            self.pushers[self.target].push(message);
+        }
+        else {
+            for pusher in self.pushers.iter_mut() {
+                pusher.push(&mut None);
+            }
+        }
+    }
+}
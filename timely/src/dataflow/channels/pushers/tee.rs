@@ -5,6 +5,7 @@ use std::cell::RefCell;
 
 use crate::Data;
 use crate::dataflow::channels::{Bundle, Message};
+use crate::dataflow::channels::lineage;
 
 use crate::communication::Push;
 
@@ -21,7 +22,7 @@ impl<T: Data, D: Data> Push<Bundle<T, D>> for Tee<T, D> {
         if let Some(message) = message {
             for index in 1..pushers.len() {
                 self.buffer.extend_from_slice(&message.data);
-                Message::push_at(&mut self.buffer, message.time.clone(), &mut pushers[index-1]);
+                Message::push_at_traced(&mut self.buffer, message.time.clone(), lineage::of(message), &mut pushers[index-1], Message::<T, D>::default_length());
             }
         }
         else {
@@ -0,0 +1,219 @@
+//! Counts of the batches and records moved by channels.
+//!
+//! Each worker counts, for each channel of its installed dataflows, the batches it pushed into
+//! and pulled from the channel, with their records and an approximate number of bytes. The counts
+//! are reported by [`Worker::channel_stats`](crate::worker::Worker::channel_stats), and whenever
+//! they change, to the "timely/channels" log stream as a
+//! [`ChannelStatsEvent`](crate::logging::ChannelStatsEvent).
+//!
+//! Bytes are estimated as the in-memory size of each record, excluding any memory the record
+//! owns elsewhere, and so underestimate records that own allocations.
+//!
+//! A worker pushes into a channel on behalf of its instance of the source operator, and pulls
+//! from it on behalf of its instance of the target operator. For a pipeline channel the difference
+//! is what the worker has queued in the channel; for an exchange channel, records may move
+//! between workers and the queued amounts are the sums of differences across all workers.
+//!
+//! The pushed counts are also kept for each edge of a channel, from the worker to each target
+//! worker, as [`EdgeStats`]. These report the serialized size of the records a worker sends to
+//! other workers along exchange channels, under the channel's codec, and are reported whenever
+//! they change to the "timely/communication" log stream as an
+//! [`EdgeStatsEvent`](crate::logging::EdgeStatsEvent). Comparing the edges of a channel across
+//! workers quantifies the skew of its exchange.
+//!
+//! The worker finds its loggers for both log streams when it builds a dataflow, and so they
+//! should be installed before the dataflows whose channels they report are built.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Map, Probe};
+//!
+//! timely::execute(timely::Config::thread(), |worker| {
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow(|scope| {
+//!         scope.input_from(&mut input)
+//!              .map(|x: u64| x + 1)
+//!              .probe()
+//!     });
+//!     input.send_batch(&mut vec![0, 1, 2, 3]);
+//!     input.advance_to(1);
+//!     worker.step_while(|| probe.less_than(input.time()));
+//!
+//!     let stats = worker.channel_stats();
+//!     assert!(stats.iter().any(|s| s.pushed_records == 4 && s.pushed_bytes == 32));
+//!     assert!(stats.iter().all(|s| s.queued_batches() == 0));
+//!     assert!(stats.iter().all(|s| s.edges.iter().map(|e| e.records).sum::<u64>() == s.pushed_records));
+//! }).unwrap();
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+/// Counts of the batches and records a worker moved through one channel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelStats {
+    /// Worker-unique identifier for the channel.
+    pub id: usize,
+    /// The number of batches pushed into the channel.
+    pub pushed_batches: u64,
+    /// The number of records pushed into the channel.
+    pub pushed_records: u64,
+    /// The approximate number of bytes pushed into the channel.
+    pub pushed_bytes: u64,
+    /// The number of batches pulled from the channel.
+    pub pulled_batches: u64,
+    /// The number of records pulled from the channel.
+    pub pulled_records: u64,
+    /// The approximate number of bytes pulled from the channel.
+    pub pulled_bytes: u64,
+    /// The counts pushed along each edge of the channel leaving this worker, in target order.
+    pub edges: Vec<EdgeStats>,
+}
+
+/// Counts of the batches and records a worker pushed along one edge of a channel.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EdgeStats {
+    /// Worker-unique identifier for the channel.
+    pub channel: usize,
+    /// The index of the sending worker.
+    pub source: usize,
+    /// The index of the receiving worker.
+    pub target: usize,
+    /// The number of batches pushed along the edge.
+    pub batches: u64,
+    /// The number of records pushed along the edge.
+    pub records: u64,
+    /// The number of bytes of the pushed records when serialized.
+    ///
+    /// This is zero for edges from a worker to itself, and for pipeline channels, whose records
+    /// are never serialized. Measuring batches costs time, and so bytes are only counted on
+    /// channels built while `EdgeStatsEvent`s are logged to `"timely/communication"`.
+    pub bytes: u64,
+}
+
+impl ChannelStats {
+    /// The number of batches pushed but not pulled.
+    pub fn queued_batches(&self) -> i64 {
+        self.pushed_batches as i64 - self.pulled_batches as i64
+    }
+    /// The number of records pushed but not pulled.
+    pub fn queued_records(&self) -> i64 {
+        self.pushed_records as i64 - self.pulled_records as i64
+    }
+    /// The approximate number of bytes pushed but not pulled.
+    pub fn queued_bytes(&self) -> i64 {
+        self.pushed_bytes as i64 - self.pulled_bytes as i64
+    }
+}
+
+// The counts of a channel, and whether they changed since last reported.
+#[derive(Default)]
+struct Entry {
+    stats: ChannelStats,
+    changed: bool,
+    /// Counts by target worker, and whether they changed since last reported.
+    edges: BTreeMap<usize, (EdgeStats, bool)>,
+}
+
+impl Entry {
+    fn stats(&self) -> ChannelStats {
+        let mut stats = self.stats.clone();
+        stats.edges = self.edges.values().map(|(edge, _)| edge.clone()).collect();
+        stats
+    }
+}
+
+/// Updates the counts of one channel, from its pushers and pullers.
+#[derive(Clone)]
+pub(crate) struct ChannelCounter {
+    entry: Rc<RefCell<Entry>>,
+}
+
+impl ChannelCounter {
+    /// Counts a batch of `records` records of type `D` pushed into the channel.
+    pub(crate) fn pushed<D>(&self, records: usize) {
+        let mut entry = self.entry.borrow_mut();
+        entry.stats.pushed_batches += 1;
+        entry.stats.pushed_records += records as u64;
+        entry.stats.pushed_bytes += (records * std::mem::size_of::<D>()) as u64;
+        entry.changed = true;
+    }
+    /// Counts a batch of `records` records, of `bytes` serialized bytes, pushed from worker
+    /// `source` to worker `target`.
+    pub(crate) fn pushed_edge(&self, source: usize, target: usize, records: usize, bytes: usize) {
+        let mut entry = self.entry.borrow_mut();
+        let channel = entry.stats.id;
+        let (edge, changed) = entry.edges.entry(target).or_insert_with(|| (EdgeStats { channel, source, target, ..Default::default() }, false));
+        edge.batches += 1;
+        edge.records += records as u64;
+        edge.bytes += bytes as u64;
+        *changed = true;
+    }
+    /// Counts a batch of `records` records of type `D` pulled from the channel.
+    pub(crate) fn pulled<D>(&self, records: usize) {
+        let mut entry = self.entry.borrow_mut();
+        entry.stats.pulled_batches += 1;
+        entry.stats.pulled_records += records as u64;
+        entry.stats.pulled_bytes += (records * std::mem::size_of::<D>()) as u64;
+        entry.changed = true;
+    }
+    /// The number of records pulled from the channel.
+    pub(crate) fn pulled_records(&self) -> u64 {
+        self.entry.borrow().stats.pulled_records
+    }
+}
+
+/// A shared handle to the counts of a worker's channels, by channel identifier.
+#[derive(Clone, Default)]
+pub struct Stats {
+    channels: Rc<RefCell<BTreeMap<usize, ChannelCounter>>>,
+}
+
+impl Stats {
+    /// The counter for channel `id`, which starts counting the channel if it is new.
+    pub(crate) fn counter(&self, id: usize) -> ChannelCounter {
+        self.channels
+            .borrow_mut()
+            .entry(id)
+            .or_insert_with(|| ChannelCounter { entry: Rc::new(RefCell::new(Entry { stats: ChannelStats { id, ..Default::default() }, ..Default::default() })) })
+            .clone()
+    }
+    /// Forgets the counts of channel `id`.
+    pub(crate) fn forget(&self, id: usize) {
+        self.channels.borrow_mut().remove(&id);
+    }
+    /// The counts of channels that changed since this method was last called, in identifier order.
+    pub(crate) fn changed(&self) -> Vec<ChannelStats> {
+        let channels = self.channels.borrow();
+        let mut changed = Vec::new();
+        for counter in channels.values() {
+            let mut entry = counter.entry.borrow_mut();
+            if entry.changed {
+                entry.changed = false;
+                changed.push(entry.stats());
+            }
+        }
+        changed
+    }
+    /// The counts of edges that changed since this method was last called, in channel and target order.
+    pub(crate) fn changed_edges(&self) -> Vec<EdgeStats> {
+        let channels = self.channels.borrow();
+        let mut changed = Vec::new();
+        for counter in channels.values() {
+            let mut entry = counter.entry.borrow_mut();
+            for (edge, edge_changed) in entry.edges.values_mut() {
+                if *edge_changed {
+                    *edge_changed = false;
+                    changed.push(edge.clone());
+                }
+            }
+        }
+        changed
+    }
+    /// The counts of all channels, in identifier order.
+    pub fn snapshot(&self) -> Vec<ChannelStats> {
+        self.channels.borrow().values().map(|counter| counter.entry.borrow().stats()).collect()
+    }
+}
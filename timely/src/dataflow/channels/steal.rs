@@ -0,0 +1,143 @@
+//! Channels whose batches are pulled by whichever worker of a process asks for them first.
+//!
+//! A channel allocated by the [`Steal`](crate::dataflow::channels::pact::Steal) pact routes no
+//! records by content. Each worker pushes its batches onto a queue shared by the workers of its
+//! process, and each worker's operator pulls at most one batch from the queue each time it is
+//! scheduled. Workers with little else to do are scheduled more often, and so pull more of the
+//! batches, which smooths the skew between workers in expensive stages without tuning a routing
+//! function. Pushing a batch onto an empty queue activates the operator on every worker of the
+//! process, and an operator that pulls a batch activates itself again while batches remain.
+//!
+//! Which worker processes a record, and in what order, is not determined, so the pact suits only
+//! operators that keep no state between batches and whose results do not depend on the order of
+//! their input. Batches do not move between processes: each process balances its own records.
+//!
+//! The queues of a process are shared through the [`Config`](crate::worker::Config) from which
+//! its workers were built.
+
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, Weak};
+
+use crate::communication::{Push, Pull};
+use crate::scheduling::{Activator, SyncActivator};
+use super::{Bundle, Message};
+use super::lineage::{self, Lineage};
+
+/// The queues of the stealing channels of a process, by channel identifier.
+#[derive(Clone, Default)]
+pub struct Queues {
+    queues: Arc<Mutex<HashMap<usize, Box<dyn Any+Send>>>>,
+}
+
+impl Queues {
+    /// The queue of the channel `identifier`, allocated if no worker holds it.
+    ///
+    /// A queue no worker holds belongs to a dataflow since dropped, or to an earlier computation
+    /// built from the same configuration, and is replaced.
+    pub(crate) fn queue<T: Send+'static, D: Send+'static>(&self, identifier: usize) -> Arc<Queue<T, D>> {
+        let mut queues = self.queues.lock().expect("steal queues poisoned");
+        let existing = queues
+            .get(&identifier)
+            .and_then(|queue| queue.downcast_ref::<Weak<Queue<T, D>>>())
+            .and_then(|queue| queue.upgrade());
+        existing.unwrap_or_else(|| {
+            let queue = Arc::new(Queue { batches: Mutex::new(VecDeque::new()), activators: Mutex::new(Vec::new()) });
+            queues.insert(identifier, Box::new(Arc::downgrade(&queue)));
+            queue
+        })
+    }
+}
+
+impl fmt::Debug for Queues {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Queues").finish_non_exhaustive()
+    }
+}
+
+// A batch not yet pulled, with its lineage if traced.
+type Batch<T, D> = (Message<T, D>, Option<Lineage>);
+
+/// The batches of a stealing channel not yet pulled, and the operators that may pull them.
+pub struct Queue<T, D> {
+    batches: Mutex<VecDeque<Batch<T, D>>>,
+    activators: Mutex<Vec<SyncActivator>>,
+}
+
+impl<T, D> Queue<T, D> {
+    /// Registers the activator of an operator pulling from the queue.
+    pub(crate) fn register(&self, activator: SyncActivator) {
+        self.activators.lock().expect("steal queue poisoned").push(activator);
+    }
+}
+
+/// Pushes batches onto the queue of a stealing channel.
+pub struct StealPusher<T, D> {
+    queue: Arc<Queue<T, D>>,
+}
+
+impl<T, D> StealPusher<T, D> {
+    /// Allocates a pusher onto `queue`.
+    pub(crate) fn new(queue: Arc<Queue<T, D>>) -> Self {
+        StealPusher { queue }
+    }
+}
+
+impl<T: Clone, D: Clone> Push<Bundle<T, D>> for StealPusher<T, D> {
+    fn push(&mut self, element: &mut Option<Bundle<T, D>>) {
+        if let Some(bundle) = element.take() {
+            let traced = lineage::of(&bundle);
+            let was_empty = {
+                let mut batches = self.queue.batches.lock().expect("steal queue poisoned");
+                batches.push_back((bundle.into_typed(), traced));
+                batches.len() == 1
+            };
+            // Operators pulling from a non-empty queue activate themselves until it is empty.
+            if was_empty {
+                for activator in self.queue.activators.lock().expect("steal queue poisoned").iter() {
+                    // An operator no longer running has no need of the batch.
+                    let _ = activator.activate();
+                }
+            }
+        }
+    }
+}
+
+/// Pulls batches from the queue of a stealing channel, at most one each time it is scheduled.
+pub struct StealPuller<T, D> {
+    queue: Arc<Queue<T, D>>,
+    activator: Activator,
+    current: Option<Bundle<T, D>>,
+    pulled: bool,
+}
+
+impl<T, D> StealPuller<T, D> {
+    /// Allocates a puller from `queue`, for the operator activated by `activator`.
+    pub(crate) fn new(queue: Arc<Queue<T, D>>, activator: Activator) -> Self {
+        StealPuller { queue, activator, current: None, pulled: false }
+    }
+}
+
+impl<T, D> Pull<Bundle<T, D>> for StealPuller<T, D> {
+    fn pull(&mut self) -> &mut Option<Bundle<T, D>> {
+        let mut batches = self.queue.batches.lock().expect("steal queue poisoned");
+        if self.pulled {
+            // Yield to other workers, and return for another batch if any remain.
+            self.pulled = false;
+            self.current = None;
+            if !batches.is_empty() {
+                self.activator.activate();
+            }
+        }
+        else {
+            self.current = batches.pop_front().map(|(message, traced)| {
+                let mut bundle = Bundle::from_typed(message);
+                lineage::annotate(&mut bundle, traced);
+                bundle
+            });
+            self.pulled = self.current.is_some();
+        }
+        &mut self.current
+    }
+}
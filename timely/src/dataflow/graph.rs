@@ -0,0 +1,226 @@
+//! The operators and channels of constructed dataflows.
+//!
+//! Each worker retains the names of its operators and scopes, and the channels connecting them,
+//! for as long as their dataflow is installed. [`Worker::dataflow_graph`](crate::worker::Worker::dataflow_graph)
+//! reports them for one dataflow as a [`DataflowGraph`], which can be rendered in the DOT language
+//! for visualization, or as JSON for other tools. The same description can be assembled from the
+//! "timely" log stream, with [`DataflowGraph::from_events`].
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::operators::{ToStream, Map, Exchange, Inspect};
+//!
+//! timely::execute(timely::Config::thread(), |worker| {
+//!     worker.dataflow::<u64,_,_>(|scope| {
+//!         (0 .. 10).to_stream(scope)
+//!                  .map(|x| x + 1)
+//!                  .exchange(|x| *x)
+//!                  .inspect(|x| println!("seen: {:?}", x));
+//!     });
+//!
+//!     let graph = worker.dataflow_graph(0).unwrap();
+//!     assert_eq!(graph.operators.len(), 5);
+//!     assert!(graph.to_dot().contains("label=\"Exchange\""));
+//!     assert!(graph.to_json().starts_with("{\"operators\":[{\"addr\":[0],\"name\":\"Dataflow\"}"));
+//! }).unwrap();
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::dataflow::operators::generic::OperatorInfo;
+use crate::logging::TimelyEvent;
+
+/// The names of a worker's operators and scopes, and the channels between them.
+#[derive(Default)]
+pub struct Topology {
+    names: HashMap<Vec<usize>, String>,
+    channels: HashMap<usize, Channel>,
+    pacts: HashMap<usize, String>,
+    infos: HashMap<Vec<usize>, OperatorInfo>,
+}
+
+impl Topology {
+    /// Records the name of the operator or scope at `address`.
+    pub(crate) fn name_operator(&mut self, address: Vec<usize>, name: String) {
+        self.names.insert(address, name);
+    }
+    /// Records the description of an operator, unless one has already been recorded for its address.
+    pub(crate) fn describe_operator(&mut self, info: OperatorInfo) {
+        self.infos.entry(info.address.clone()).or_insert(info);
+    }
+    /// Records a channel within the scope at `scope_addr`.
+    pub(crate) fn add_channel(&mut self, id: usize, scope_addr: Vec<usize>, source: (usize, usize), target: (usize, usize)) {
+        self.channels.insert(id, Channel { id, scope_addr, source, target, pact: None });
+    }
+    /// Records how the records of a channel are moved between workers.
+    pub(crate) fn set_pact(&mut self, id: usize, pact: &str) {
+        self.pacts.insert(id, pact.to_owned());
+    }
+    /// Forgets the operators and channels of a dataflow.
+    pub(crate) fn forget(&mut self, dataflow: usize) {
+        self.names.retain(|addr, _| addr[0] != dataflow);
+        self.infos.retain(|addr, _| addr[0] != dataflow);
+        let channels = &mut self.channels;
+        let pacts = &mut self.pacts;
+        channels.retain(|id, channel| {
+            let retain = channel.scope_addr[0] != dataflow;
+            if !retain { pacts.remove(id); }
+            retain
+        });
+    }
+
+    /// The identifiers of the channels within the scope at `scope_addr` into `target`.
+    pub(crate) fn channels_to(&self, scope_addr: &[usize], target: (usize, usize)) -> Vec<usize> {
+        self.channels
+            .values()
+            .filter(|channel| channel.scope_addr == scope_addr && channel.target == target)
+            .map(|channel| channel.id)
+            .collect()
+    }
+
+    /// The name of the operator or scope at `address`, if known.
+    pub fn name(&self, address: &[usize]) -> Option<&str> {
+        self.names.get(address).map(|name| &name[..])
+    }
+
+    /// The description of the operator or scope at `address`, if known.
+    pub fn describe(&self, address: &[usize]) -> Option<&OperatorInfo> {
+        self.infos.get(address)
+    }
+
+    /// The operators and channels of the identified dataflow, if it is installed.
+    pub fn graph(&self, dataflow: usize) -> Option<DataflowGraph> {
+        let mut operators = self.names
+            .iter()
+            .filter(|(addr, _)| addr[0] == dataflow)
+            .map(|(addr, name)| Operator { addr: addr.clone(), name: name.clone() })
+            .collect::<Vec<_>>();
+        if operators.is_empty() {
+            return None;
+        }
+        operators.sort();
+        let mut channels = self.channels
+            .values()
+            .filter(|channel| channel.scope_addr[0] == dataflow)
+            .map(|channel| Channel { pact: self.pacts.get(&channel.id).cloned(), ..channel.clone() })
+            .collect::<Vec<_>>();
+        channels.sort();
+        Some(DataflowGraph { operators, channels })
+    }
+}
+
+/// An operator or scope, identified by its address.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Operator {
+    /// Sequence of nested scope identifiers indicating the path from the root to this instance.
+    pub addr: Vec<usize>,
+    /// A helpful name.
+    pub name: String,
+}
+
+/// A channel between operators of the same scope.
+///
+/// Operator index zero is the scope itself, whose inputs are sources and outputs are targets
+/// of the channels within it.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Channel {
+    /// Worker-unique identifier for the channel.
+    pub id: usize,
+    /// Address of the scope containing the channel.
+    pub scope_addr: Vec<usize>,
+    /// Source descriptor, indicating operator index and output port.
+    pub source: (usize, usize),
+    /// Target descriptor, indicating operator index and input port.
+    pub target: (usize, usize),
+    /// How records move between workers, "Pipeline" or "Exchange", if known.
+    pub pact: Option<String>,
+}
+
+impl Channel {
+    // Addresses of the operators the channel connects.
+    fn endpoints(&self) -> (Vec<usize>, Vec<usize>) {
+        let address = |node: usize| {
+            let mut addr = self.scope_addr.clone();
+            if node > 0 { addr.push(node); }
+            addr
+        };
+        (address(self.source.0), address(self.target.0))
+    }
+}
+
+/// The operators and channels of a dataflow.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct DataflowGraph {
+    /// The dataflow's operators and scopes, including the dataflow itself, in address order.
+    pub operators: Vec<Operator>,
+    /// The channels between the dataflow's operators, in order of their identifiers.
+    pub channels: Vec<Channel>,
+}
+
+impl DataflowGraph {
+    /// Assembles the graph of the identified dataflow from events of the "timely" log stream.
+    ///
+    /// The log does not record parallelization contracts, and so channels have no `pact`.
+    pub fn from_events<'a, I: IntoIterator<Item=&'a TimelyEvent>>(dataflow: usize, events: I) -> Self {
+        let mut operators = Vec::new();
+        let mut channels = Vec::new();
+        for event in events {
+            match event {
+                TimelyEvent::Operates(event) if event.addr.first() == Some(&dataflow) => {
+                    operators.push(Operator { addr: event.addr.clone(), name: event.name.clone() });
+                },
+                TimelyEvent::Channels(event) if event.scope_addr.first() == Some(&dataflow) => {
+                    channels.push(Channel {
+                        id: event.id,
+                        scope_addr: event.scope_addr.clone(),
+                        source: event.source,
+                        target: event.target,
+                        pact: None,
+                    });
+                },
+                _ => { },
+            }
+        }
+        operators.sort();
+        operators.dedup();
+        channels.sort();
+        DataflowGraph { operators, channels }
+    }
+
+    /// Renders the graph in the DOT language, with operators labeled by name and channels by pact.
+    pub fn to_dot(&self) -> String {
+        let id = |addr: &[usize]| addr.iter().map(|i| i.to_string()).collect::<Vec<_>>().join("_");
+        let mut dot = String::from("digraph dataflow {\n");
+        for operator in self.operators.iter() {
+            writeln!(dot, "  \"{}\" [label=\"{}\"];", id(&operator.addr), escape(&operator.name)).unwrap();
+        }
+        for channel in self.channels.iter() {
+            let (source, target) = channel.endpoints();
+            let label = channel.pact.as_ref().map(|p| &p[..]).unwrap_or("");
+            writeln!(dot, "  \"{}\" -> \"{}\" [label=\"{}\"];", id(&source), id(&target), escape(label)).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the graph as a JSON object with fields `operators` and `channels`.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("failed to serialize dataflow graph")
+    }
+}
+
+/// Escapes a string for inclusion between double quotes in DOT.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            c if (c as u32) < 0x20 => { write!(escaped, "\\u{:04x}", c as u32).unwrap(); },
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -17,9 +17,13 @@ pub use self::stream::Stream;
 pub use self::scopes::{Scope, ScopeParent};
 
 pub use self::operators::input::Handle as InputHandle;
+pub use self::operators::input::InputSession;
 pub use self::operators::probe::Handle as ProbeHandle;
+pub use self::operators::compaction::CompactionFrontier;
 
 pub mod operators;
 pub mod channels;
 pub mod scopes;
 pub mod stream;
+pub mod graph;
+pub mod template;
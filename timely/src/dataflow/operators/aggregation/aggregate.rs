@@ -1,11 +1,15 @@
 //! General purpose intra-timestamp aggregation
 use std::hash::Hash;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::collections::hash_map;
 
 use crate::{Data, ExchangeData};
 use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::{Capability, Delay};
+use crate::dataflow::operators::windows::WindowTime;
+use crate::dataflow::operators::epoch_buffer::EpochBuffer;
 use crate::dataflow::operators::generic::operator::Operator;
-use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::channels::pact::{Exchange, ParallelizationContract, Pipeline};
 
 /// Generic intra-timestamp aggregation
 ///
@@ -20,7 +24,14 @@ pub trait Aggregate<S: Scope, K: ExchangeData+Hash, V: ExchangeData> {
     /// to route `K` keys, respectively.
     ///
     /// Aggregation happens within each time, and results are produced once the
-    /// time is complete.
+    /// time is complete. The operator yields once it has spent the fuel set by
+    /// [`Config::operator_fuel`](crate::worker::Config::operator_fuel) folding or
+    /// emitting records, and resumes when next scheduled.
+    ///
+    /// If the worker spills to disk, as configured by
+    /// [`Config::spill_to_disk`](crate::worker::Config::spill_to_disk), the records of incomplete
+    /// times are instead held in an [`EpochBuffer`](crate::dataflow::operators::epoch_buffer::EpochBuffer)
+    /// and folded once their time completes.
     ///
     /// # Examples
     /// ```
@@ -65,6 +76,118 @@ pub trait Aggregate<S: Scope, K: ExchangeData+Hash, V: ExchangeData> {
         fold: F,
         emit: E,
         hash: H) -> Stream<S, R> where S::Timestamp: Eq;
+
+    /// Aggregates data of the form `(key, val)` at their event times, separating records that
+    /// arrive more than `allowance` after their event times.
+    ///
+    /// Each record is moved to its event time, as determined by `event_time`, and aggregated
+    /// as by `aggregate` with the other records of that time, if its time is at most `allowance`
+    /// after its event time. The aggregates of each event time are produced once the input frontier
+    /// has passed it by `allowance`. The second stream holds the late records, at the times they
+    /// arrived, as described by [`Delay::delay_with_late`](crate::dataflow::operators::Delay::delay_with_late).
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Capture};
+    /// use timely::dataflow::operators::aggregation::Aggregate;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let (sums, late) = timely::example(|scope| {
+    ///     // Records at time 10 carry event times 3, 8, and 9.
+    ///     let (sums, late) = vec![('a', 3u64), ('a', 8), ('b', 9)]
+    ///         .to_stream(scope)
+    ///         .delay(|_x, _| 10)
+    ///         .aggregate_with_late(
+    ///             5,
+    ///             |(_key, val)| *val / 2 * 2,
+    ///             |_key, val, sum: &mut u64| { *sum += val; },
+    ///             |key, sum| (key, sum),
+    ///             |key| *key as u64
+    ///         );
+    ///     (sums.capture(), late.capture())
+    /// });
+    ///
+    /// assert_eq!(sums.extract(), vec![(8, vec![('a', 8), ('b', 9)])]);
+    /// assert_eq!(late.extract(), vec![(10, vec![('a', 3)])]);
+    /// ```
+    fn aggregate_with_late<R: Data, D: Default+'static, T: Fn(&(K, V))->S::Timestamp+'static, F: Fn(&K, V, &mut D)+'static, E: Fn(K, D)->R+'static, H: Fn(&K)->u64+'static>(
+        &self,
+        allowance: S::Timestamp,
+        event_time: T,
+        fold: F,
+        emit: E,
+        hash: H) -> (Stream<S, R>, Stream<S, (K, V)>) where S::Timestamp: WindowTime;
+
+    /// Folds the values of each key at each time into partial states on each worker, before any
+    /// exchange, and produces each key with its partial state.
+    ///
+    /// The partial states of a time are produced once the time is complete, or sooner if the
+    /// worker holds more than `max_keys` partial states, in which case all of them are produced
+    /// and later values start new partial states. A key may then appear with several partial
+    /// states at a time, which `aggregate` should combine. Aggregations whose keys repeat within
+    /// each worker then exchange one record per key, rather than one per value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_keys` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Capture};
+    /// use timely::dataflow::operators::aggregation::Aggregate;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0..1000u64).to_stream(scope)
+    ///         .map(|x| (x % 2, x))
+    ///         .pre_aggregate(1024, |_key, val, sum: &mut u64| *sum += val)
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(0, 249500), (1, 250000)])]);
+    /// ```
+    fn pre_aggregate<P: ExchangeData+Default, C: Fn(&K, V, &mut P)+'static>(
+        &self,
+        max_keys: usize,
+        combine: C) -> Stream<S, (K, P)>;
+
+    /// Aggregates data of the form `(key, val)` in two phases: folding values into partial
+    /// states on each worker with `combine`, exchanging the partial states by `hash`, and folding
+    /// them into each key's state with `merge`, from which `emit` produces the results.
+    ///
+    /// This is `pre_aggregate` followed by `aggregate`, and produces the results `aggregate`
+    /// would for a fold that applies `combine` and then `merge`. The partial states held by each
+    /// worker are bounded by `max_keys`, as described at `pre_aggregate`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_keys` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    /// use timely::dataflow::operators::aggregation::Aggregate;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///         .map(|x| (x % 2, x))
+    ///         .aggregate_combined(
+    ///             1024,
+    ///             |_key, _val, count: &mut usize| *count += 1,
+    ///             |_key, count, total: &mut usize| *total += count,
+    ///             |key, total| (key, total),
+    ///             |key| *key as u64
+    ///         )
+    ///         .inspect(|x| assert!(*x == (0, 5) || *x == (1, 5)));
+    /// });
+    /// ```
+    fn aggregate_combined<R: Data, P: ExchangeData+Default, D: Default+'static, C: Fn(&K, V, &mut P)+'static, F: Fn(&K, P, &mut D)+'static, E: Fn(K, D)->R+'static, H: Fn(&K)->u64+'static>(
+        &self,
+        max_keys: usize,
+        combine: C,
+        merge: F,
+        emit: E,
+        hash: H) -> Stream<S, R> where S::Timestamp: Eq;
 }
 
 impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> Aggregate<S, K, V> for Stream<S, (K, V)> {
@@ -74,32 +197,156 @@ impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> Aggregate<S, K, V> for
         fold: F,
         emit: E,
         hash: H) -> Stream<S, R> where S::Timestamp: Eq {
+        aggregate_core(self, Exchange::new(move |(k, _)| hash(k)), "Aggregate", fold, emit)
+    }
+
+    fn aggregate_with_late<R: Data, D: Default+'static, T: Fn(&(K, V))->S::Timestamp+'static, F: Fn(&K, V, &mut D)+'static, E: Fn(K, D)->R+'static, H: Fn(&K)->u64+'static>(
+        &self,
+        allowance: S::Timestamp,
+        event_time: T,
+        fold: F,
+        emit: E,
+        hash: H) -> (Stream<S, R>, Stream<S, (K, V)>) where S::Timestamp: WindowTime {
+        let (on_time, late) = self.delay_with_late(allowance, move |x, _time| event_time(x));
+        (on_time.aggregate(fold, emit, hash), late)
+    }
+
+    fn pre_aggregate<P: ExchangeData+Default, C: Fn(&K, V, &mut P)+'static>(
+        &self,
+        max_keys: usize,
+        combine: C) -> Stream<S, (K, P)> {
+        assert!(max_keys > 0, "pre-aggregation must hold at least one key");
+        self.unary_frontier(Pipeline, "PreAggregate", move |_, _| {
+            // partial states of incomplete times, and their number.
+            let mut partials = HashMap::new();
+            let mut held = 0;
+            let mut vector = Vec::new();
+            move |input, output| {
+                input.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    let (_, partial) = partials.entry(time.time().clone()).or_insert_with(|| (time.retain(), HashMap::new()));
+                    for (key, val) in vector.drain(..) {
+                        if let Some(state) = partial.get_mut(&key) {
+                            combine(&key, val, state);
+                        }
+                        else {
+                            let mut state = P::default();
+                            combine(&key, val, &mut state);
+                            partial.insert(key, state);
+                            held += 1;
+                        }
+                    }
+                });
+
+                // produce partial states of complete times, or of all times if too many are held.
+                let frontier = input.frontier();
+                let flush = partials.keys().filter(|time| held > max_keys || !frontier.less_equal(time)).cloned().collect::<Vec<_>>();
+                for time in flush {
+                    let (capability, partial) = partials.remove(&time).unwrap();
+                    held -= partial.len();
+                    output.session(&capability).give_iterator(partial.into_iter());
+                }
+            }
+        })
+    }
+
+    fn aggregate_combined<R: Data, P: ExchangeData+Default, D: Default+'static, C: Fn(&K, V, &mut P)+'static, F: Fn(&K, P, &mut D)+'static, E: Fn(K, D)->R+'static, H: Fn(&K)->u64+'static>(
+        &self,
+        max_keys: usize,
+        combine: C,
+        merge: F,
+        emit: E,
+        hash: H) -> Stream<S, R> where S::Timestamp: Eq {
+        self.pre_aggregate(max_keys, combine)
+            .aggregate(merge, emit, hash)
+    }
+}
+
+/// Aggregates the `(key, val)` records of `stream` within each time, delivered by `pact`.
+///
+/// The pact must deliver all records with the same key to the same worker, as for example an
+/// exchange by the key does, or a pipeline does for records already partitioned by their keys.
+pub(crate) fn aggregate_core<S, K, V, R, D, F, E, P>(stream: &Stream<S, (K, V)>, pact: P, name: &str, fold: F, emit: E) -> Stream<S, R>
+where
+    S: Scope,
+    K: ExchangeData+Hash+Eq,
+    V: ExchangeData,
+    R: Data,
+    D: Default+'static,
+    F: Fn(&K, V, &mut D)+'static,
+    E: Fn(K, D)->R+'static,
+    P: ParallelizationContract<S::Timestamp, (K, V)>,
+{
+    let scope = stream.scope();
+    let mut fuel = scope.config().fuel();
+    let spill = scope.config().spill();
+    stream.unary_frontier(pact, name, move |_, info| {
 
+        let activator = scope.activator_for(&info.address[..]);
+        // aggregates of incomplete times, and completed aggregates yet to be emitted.
         let mut aggregates = HashMap::new();
+        let mut emitting = VecDeque::<(Capability<S::Timestamp>, hash_map::IntoIter<K, D>)>::new();
         let mut vector = Vec::new();
-        self.unary_notify(Exchange::new(move |&(ref k, _)| hash(k)), "Aggregate", vec![], move |input, output, notificator| {
+        // records of incomplete times, if spilling, to be folded once complete.
+        let mut spilling = spill.map(EpochBuffer::spilling);
+
+        move |input, output| {
+
+            fuel.refill();
 
             // read each input, fold into aggregates
-            input.for_each(|time, data| {
+            while !fuel.exhausted() {
+                let (time, data) = match input.next() {
+                    Some(message) => message,
+                    None => break,
+                };
                 data.swap(&mut vector);
-                let agg_time = aggregates.entry(time.time().clone()).or_insert_with(HashMap::new);
+                fuel.consume(vector.len());
+                if let Some(buffer) = spilling.as_mut() {
+                    buffer.push(time.time().clone(), &mut vector).expect("failed to spill records");
+                }
+                let (_, agg_time) = aggregates.entry(time.time().clone()).or_insert_with(|| (time.retain(), HashMap::new()));
                 for (key, val) in vector.drain(..) {
                     let agg = agg_time.entry(key.clone()).or_insert_with(Default::default);
                     fold(&key, val, agg);
                 }
-                notificator.notify_at(time.retain());
-            });
-
-            // pop completed aggregates, send along whatever
-            notificator.for_each(|time,_,_| {
-                if let Some(aggs) = aggregates.remove(time.time()) {
-                    let mut session = output.session(&time);
-                    for (key, agg) in aggs {
-                        session.give(emit(key, agg));
+            }
+
+            // queue completed aggregates, in order of their times
+            let mut complete = aggregates.keys().filter(|time| !input.frontier().less_equal(time)).cloned().collect::<Vec<_>>();
+            complete.sort();
+            for time in complete {
+                let (capability, mut aggs) = aggregates.remove(&time).unwrap();
+                if let Some(buffer) = spilling.as_mut() {
+                    for (key, val) in buffer.take(&time).expect("failed to read spilled records") {
+                        let agg = aggs.entry(key.clone()).or_insert_with(Default::default);
+                        fold(&key, val, agg);
                     }
                 }
-            });
-        })
+                emitting.push_back((capability, aggs.into_iter()));
+            }
 
-    }
+            // send along completed aggregates, while fuel remains
+            while let Some((capability, mut aggs)) = emitting.pop_front() {
+                {
+                    let mut session = output.session(&capability);
+                    while !fuel.exhausted() {
+                        match aggs.next() {
+                            Some((key, agg)) => { session.give(emit(key, agg)); fuel.consume(1); },
+                            None => break,
+                        }
+                    }
+                }
+                if aggs.len() > 0 {
+                    emitting.push_front((capability, aggs));
+                    break;
+                }
+            }
+
+            // resume once other operators have had their turn
+            if fuel.exhausted() {
+                activator.activate();
+            }
+        }
+    })
 }
@@ -0,0 +1,107 @@
+//! Keyed aggregation within timestamps, routed by a supplied hasher.
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::aggregation::Aggregate;
+
+/// Keyed aggregation of `(key, val)` records within each timestamp.
+///
+/// Records are exchanged so that all records with the same key are aggregated by the same worker,
+/// which is determined by hashing the key with hashers from `hasher`. The builder must produce
+/// identical hashes on all workers, as `BuildHasherDefault` does and `RandomState` does not.
+pub trait AggregateByKey<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> {
+    /// Folds the values of each key at each time into a state, and emits a result from the state
+    /// once the time is complete.
+    ///
+    /// This is `Aggregate::aggregate` with records routed by `hasher`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    /// use timely::dataflow::operators::aggregation::AggregateByKey;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///         .map(|x| (x % 2, x))
+    ///         .aggregate_by_key(
+    ///             |_key, val, agg: &mut Vec<i32>| agg.push(val),
+    ///             |key, agg| (key, agg.len()),
+    ///             BuildHasherDefault::<DefaultHasher>::default(),
+    ///         )
+    ///         .inspect(|x| assert!(*x == (0, 5) || *x == (1, 5)));
+    /// });
+    /// ```
+    fn aggregate_by_key<R, D, F, E, B>(&self, fold: F, emit: E, hasher: B) -> Stream<S, R>
+    where
+        R: Data,
+        D: Default+'static,
+        F: Fn(&K, V, &mut D)+'static,
+        E: Fn(K, D)->R+'static,
+        B: BuildHasher+'static,
+        S::Timestamp: Eq;
+
+    /// Combines the values of each key at each time with `reduce`, and emits the key and its
+    /// combined value once the time is complete.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use std::hash::BuildHasherDefault;
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    /// use timely::dataflow::operators::aggregation::AggregateByKey;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///         .map(|x| (x % 2, x))
+    ///         .reduce_by_key(|x, y| x + y, BuildHasherDefault::<DefaultHasher>::default())
+    ///         .inspect(|x| assert!(*x == (0, 20) || *x == (1, 25)));
+    /// });
+    /// ```
+    fn reduce_by_key<F, B>(&self, reduce: F, hasher: B) -> Stream<S, (K, V)>
+    where
+        F: Fn(V, V)->V+'static,
+        B: BuildHasher+'static,
+        S::Timestamp: Eq;
+}
+
+impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> AggregateByKey<S, K, V> for Stream<S, (K, V)> {
+    fn aggregate_by_key<R, D, F, E, B>(&self, fold: F, emit: E, hasher: B) -> Stream<S, R>
+    where
+        R: Data,
+        D: Default+'static,
+        F: Fn(&K, V, &mut D)+'static,
+        E: Fn(K, D)->R+'static,
+        B: BuildHasher+'static,
+        S::Timestamp: Eq
+    {
+        // `BuildHasher::hash_one` would need Rust 1.71.
+        #[allow(clippy::manual_hash_one)]
+        let hash = move |key: &K| {
+            let mut state = hasher.build_hasher();
+            key.hash(&mut state);
+            state.finish()
+        };
+        self.aggregate(fold, emit, hash)
+    }
+
+    fn reduce_by_key<F, B>(&self, reduce: F, hasher: B) -> Stream<S, (K, V)>
+    where
+        F: Fn(V, V)->V+'static,
+        B: BuildHasher+'static,
+        S::Timestamp: Eq
+    {
+        self.aggregate_by_key(
+            move |_key, val, agg: &mut Option<V>| {
+                *agg = Some(match agg.take() {
+                    Some(prev) => reduce(prev, val),
+                    None => val,
+                });
+            },
+            |key, agg| (key, agg.expect("aggregate without values")),
+            hasher,
+        )
+    }
+}
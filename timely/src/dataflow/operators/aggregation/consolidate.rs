@@ -0,0 +1,129 @@
+//! Consolidation of `(key, diff)` records within timestamps.
+//!
+//! Change streams describe collections by records that add or retract copies of keys, and often
+//! carry many records that cancel. Consolidation sums the diffs of each key at each time, and
+//! drops the keys whose diffs sum to zero, so that only net changes flow downstream.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::ExchangeData;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Exchange;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// A type whose values can be accumulated, with a distinguished zero.
+///
+/// Implementations should make `plus_equals` associative, and the zero its identity.
+pub trait Semigroup: Clone + 'static {
+    /// Adds `other` to `self`.
+    fn plus_equals(&mut self, other: &Self);
+    /// Indicates whether `self` is the zero, which consolidation discards.
+    fn is_zero(&self) -> bool;
+}
+
+macro_rules! implement_semigroup {
+    ($($index_type:ty,)*) => (
+        $(
+            impl Semigroup for $index_type {
+                #[inline] fn plus_equals(&mut self, other: &Self) { *self += other; }
+                #[inline] fn is_zero(&self) -> bool { *self == 0 }
+            }
+        )*
+    )
+}
+
+implement_semigroup!(i8, i16, i32, i64, i128, isize,);
+
+/// Sorts `vec` by key, accumulates the diffs of equal keys, and removes keys whose diffs are zero.
+///
+/// The consolidation is in place, and does not allocate.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::aggregation::consolidate::consolidate;
+///
+/// let mut vec = vec![("b", 1), ("a", 2), ("b", -1), ("c", 1), ("a", 1)];
+/// consolidate(&mut vec);
+/// assert_eq!(vec, vec![("a", 3), ("c", 1)]);
+/// ```
+pub fn consolidate<K: Ord, R: Semigroup>(vec: &mut Vec<(K, R)>) {
+    vec.sort_unstable_by(|x, y| x.0.cmp(&y.0));
+    // The records before `offset` are consolidated, and `offset` holds the key being accumulated.
+    let mut offset = 0;
+    for index in 1 .. vec.len() {
+        if vec[offset].0 == vec[index].0 {
+            let (consolidated, rest) = vec.split_at_mut(index);
+            consolidated[offset].1.plus_equals(&rest[0].1);
+        }
+        else {
+            if !vec[offset].1.is_zero() {
+                offset += 1;
+            }
+            vec.swap(offset, index);
+        }
+    }
+    if offset < vec.len() && !vec[offset].1.is_zero() {
+        offset += 1;
+    }
+    vec.truncate(offset);
+}
+
+/// Extension trait for consolidating a stream of `(key, diff)` records.
+pub trait ConsolidateByKey<S: Scope, K: ExchangeData+Hash+Ord, R: ExchangeData+Semigroup> {
+    /// Sums the diffs of each key at each time, and produces the keys whose sums are not zero
+    /// once the time is complete.
+    ///
+    /// Records are exchanged by key, using the worker's exchange hasher, so that each key is
+    /// consolidated by one worker. Records are consolidated as they accumulate, which bounds the
+    /// memory held for each time by about twice its consolidated size, and the buffers of
+    /// completed times are reused for later times.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::aggregation::ConsolidateByKey;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![('a', 1isize), ('b', 1), ('a', -1), ('c', 2), ('b', 1)]
+    ///         .to_stream(scope)
+    ///         .consolidate_by_key()
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![('b', 2), ('c', 2)])]);
+    /// ```
+    fn consolidate_by_key(&self) -> Stream<S, (K, R)>;
+}
+
+impl<S: Scope, K: ExchangeData+Hash+Ord, R: ExchangeData+Semigroup> ConsolidateByKey<S, K, R> for Stream<S, (K, R)> {
+    fn consolidate_by_key(&self) -> Stream<S, (K, R)> {
+        let hasher = self.scope().config().hasher().clone();
+        // Records of each incomplete time, and their number when last consolidated.
+        let mut pending = HashMap::<S::Timestamp, (Vec<(K, R)>, usize)>::new();
+        // Buffers of completed times, retained for later times.
+        let mut spare = Vec::new();
+        let mut vector = Vec::new();
+        self.unary_notify(Exchange::new(move |(key, _)| hasher.hash(key)), "ConsolidateByKey", vec![], move |input, output, notificator| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                let (buffer, consolidated) = pending.entry(time.time().clone())
+                                                    .or_insert_with(|| (spare.pop().unwrap_or_default(), 0));
+                buffer.append(&mut vector);
+                if buffer.len() > 2 * std::cmp::max(*consolidated, 1024) {
+                    consolidate(buffer);
+                    *consolidated = buffer.len();
+                }
+                notificator.notify_at(time.retain());
+            });
+            notificator.for_each(|time, _, _| {
+                if let Some((mut buffer, _)) = pending.remove(time.time()) {
+                    consolidate(&mut buffer);
+                    output.session(&time).give_iterator(buffer.drain(..));
+                    spare.push(buffer);
+                }
+            });
+        })
+    }
+}
@@ -0,0 +1,299 @@
+//! State transition operator whose keys may move between workers.
+use std::hash::Hash;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::{Data, ExchangeData};
+use crate::order::{PartialOrder, TotalOrder};
+use crate::progress::Antichain;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{Exchange, Pipeline};
+use crate::dataflow::operators::{Broadcast, ConnectLoop, Feedback};
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+
+/// Provides the `migrating_state_machine` method.
+///
+/// Keys are grouped into `bins` bins by their hash, and each bin is owned by one worker;
+/// initially bin `b` is owned by worker `b % peers`. A control stream of `(bin, worker)`
+/// records reassigns bins at the timestamps of the records. Records at times before the
+/// reassignment are applied at the old owner, after which the states of the bin's keys are
+/// exported to the new owner and installed there before it applies records at the time of the
+/// reassignment or later. No records or states are lost or applied twice.
+///
+/// Timestamps must be totally ordered, as the operator relies on applying records in time order
+/// relative to reassignments.
+pub trait MigratingStateMachine<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> {
+    /// Tracks a state for each presented key, moving states when `control` reassigns their bins.
+    ///
+    /// The transition logic `fold` behaves as in `StateMachine::state_machine`, except that the
+    /// state must be exchangeable so that it may be sent to another worker.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Inspect, Probe};
+    /// use timely::dataflow::operators::aggregation::MigratingStateMachine;
+    ///
+    /// timely::execute(timely::Config::process(2), |worker| {
+    ///
+    ///     let mut input = InputHandle::new();
+    ///     let mut control = InputHandle::new();
+    ///
+    ///     let probe = worker.dataflow(|scope| {
+    ///         let control = scope.input_from(&mut control);
+    ///         scope.input_from(&mut input)
+    ///              .migrating_state_machine(&control, 4, |key: &u64, val: u64, agg: &mut u64| {
+    ///                  *agg += val;
+    ///                  (false, Some((*key, *agg)))
+    ///              }, |key| *key)
+    ///              .inspect(|x| println!("sum: {:?}", x))
+    ///              .probe()
+    ///     });
+    ///
+    ///     for round in 0 .. 4u64 {
+    ///         if worker.index() == 0 {
+    ///             input.send((round % 4, round));
+    ///             // move every bin to worker 1 at round 2.
+    ///             if round == 2 {
+    ///                 for bin in 0 .. 4 { control.send((bin, 1)); }
+    ///             }
+    ///         }
+    ///         input.advance_to(round + 1);
+    ///         control.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    /// }).unwrap();
+    /// ```
+    fn migrating_state_machine<
+        R: Data,                                    // output type
+        D: ExchangeData+Default,                    // per-key state (data)
+        I: IntoIterator<Item=R>,                    // type of output iterator
+        F: Fn(&K, V, &mut D)->(bool, I)+'static,    // state update logic
+        H: Fn(&K)->u64+'static,                     // "hash" function for keys
+    >(&self, control: &Stream<S, (usize, usize)>, bins: usize, fold: F, hash: H) -> Stream<S, R> where S::Timestamp: Hash+TotalOrder;
+}
+
+/// The assignment of bins to workers, as it changes through time.
+struct Routing<T> {
+    /// Owners reflecting all changes before any time still of interest.
+    base: Vec<usize>,
+    /// Changes `(time, bin, worker)` in order of arrival.
+    changes: Vec<(T, usize, usize)>,
+}
+
+impl<T: Ord+PartialOrder+Clone> Routing<T> {
+    fn new(bins: usize, peers: usize) -> Self {
+        Routing { base: (0 .. bins).map(|bin| bin % peers).collect(), changes: Vec::new() }
+    }
+    /// The owner of `bin` for records at `time`.
+    fn owner_at(&self, bin: usize, time: &T) -> usize {
+        self.owner(bin, |t| t.less_equal(time))
+    }
+    /// The owner of `bin` for records at times immediately before `time`.
+    fn owner_before(&self, bin: usize, time: &T) -> usize {
+        self.owner(bin, |t| t.less_than(time))
+    }
+    fn owner(&self, bin: usize, include: impl Fn(&T)->bool) -> usize {
+        let mut owner = self.base[bin];
+        let mut latest: Option<&T> = None;
+        for (time, changed, worker) in self.changes.iter() {
+            if *changed == bin && include(time) && latest.map(|l| l.less_equal(time)).unwrap_or(true) {
+                owner = *worker;
+                latest = Some(time);
+            }
+        }
+        owner
+    }
+    /// Folds into `base` those changes that precede all times in `lower`.
+    fn compact(&mut self, lower: &[T]) {
+        let mut changes = ::std::mem::take(&mut self.changes);
+        changes.sort_by(|x, y| x.0.cmp(&y.0));
+        for (time, bin, worker) in changes {
+            if lower.iter().all(|l| time.less_than(l)) {
+                self.base[bin] = worker;
+            }
+            else {
+                self.changes.push((time, bin, worker));
+            }
+        }
+    }
+}
+
+impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> MigratingStateMachine<S, K, V> for Stream<S, (K, V)> {
+    fn migrating_state_machine<
+            R: Data,                                    // output type
+            D: ExchangeData+Default,                    // per-key state (data)
+            I: IntoIterator<Item=R>,                    // type of output iterator
+            F: Fn(&K, V, &mut D)->(bool, I)+'static,    // state update logic
+            H: Fn(&K)->u64+'static,                     // "hash" function for keys
+        >(&self, control: &Stream<S, (usize, usize)>, bins: usize, fold: F, hash: H) -> Stream<S, R> where S::Timestamp: Hash+TotalOrder {
+
+        let mut scope = self.scope();
+        let peers = scope.peers();
+        let index = scope.index();
+        let bin_of = Rc::new(move |key: &K| (hash(key) % bins as u64) as usize);
+        let route_bin_of = bin_of.clone();
+        let control = control.broadcast();
+
+        // Route each record to the owner of its bin as of the record's time, which is known
+        // once the control input is complete through that time.
+        let routed = self.binary_frontier(&control, Pipeline, Pipeline, "MigrateRoute", move |_, _| {
+            let mut routing = Routing::new(bins, peers);
+            let mut stash = Vec::new();
+            let mut vector = Vec::new();
+            move |input, control, output| {
+                control.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    for (bin, worker) in vector.drain(..) {
+                        routing.changes.push((time.time().clone(), bin, worker));
+                    }
+                });
+                input.for_each(|time, data| {
+                    let mut records = Vec::new();
+                    data.swap(&mut records);
+                    stash.push((time.retain(), records));
+                });
+
+                let frontier = control.frontier();
+                for (time, records) in stash.iter_mut().filter(|(time, _)| !frontier.less_equal(time.time())) {
+                    let mut session = output.session(time);
+                    for (key, val) in records.drain(..) {
+                        let owner = routing.owner_at(route_bin_of(&key), time.time());
+                        session.give((owner, key, val));
+                    }
+                }
+                stash.retain(|(_, records)| !records.is_empty());
+
+                let mut lower = input.frontier().frontier().to_vec();
+                lower.extend(stash.iter().map(|(time, _)| time.time().clone()));
+                routing.compact(&lower[..]);
+            }
+        });
+
+        // States move between workers through a loop, at the time of the reassignment. The loop
+        // does not advance timestamps, and so the exports must not depend on the arriving states;
+        // they are instead justified by a capability the operator holds until all are exported.
+        let (handle, migrated) = scope.feedback(Default::default());
+
+        let mut builder = OperatorBuilder::new("MigratingStateMachine".to_owned(), scope.clone());
+        let mut data_in = builder.new_input(&routed, Exchange::new(|x: &(usize, K, V)| x.0 as u64));
+        let mut control_in = builder.new_input(&control, Pipeline);
+        let mut state_in = builder.new_input(&migrated, Exchange::new(|x: &(usize, K, D)| x.0 as u64));
+        let (mut output, result) = builder.new_output();
+        let (mut migrate, migrations) = builder.new_output_connection(vec![
+            Antichain::from_elem(Default::default()),
+            Antichain::from_elem(Default::default()),
+            Antichain::new(),
+        ]);
+        migrations.connect_loop(handle);
+
+        builder.build(move |mut capabilities| {
+
+            // We retain a capability to export states until all reassignments are exported.
+            let mut state_cap = capabilities.pop();
+            ::std::mem::drop(capabilities);
+
+            let mut routing = Routing::new(bins, peers);
+            let mut exports: Vec<(S::Timestamp, usize)> = Vec::new();
+            let mut pending = HashMap::new();       // times -> (capability, (key, val) pairs)
+            let mut arrived = HashMap::new();       // times -> (key, state) pairs
+            let mut states = HashMap::new();        // keys -> state
+
+            let mut changes = Vec::new();
+            let mut records = Vec::new();
+            let mut installs = Vec::new();
+
+            move |frontiers| {
+
+                let mut output = output.activate();
+                let mut migrate = migrate.activate();
+
+                control_in.for_each(|time, data| {
+                    data.swap(&mut changes);
+                    for (bin, worker) in changes.drain(..) {
+                        routing.changes.push((time.time().clone(), bin, worker));
+                        exports.push((time.time().clone(), bin));
+                    }
+                });
+                data_in.for_each(|time, data| {
+                    data.swap(&mut records);
+                    pending
+                        .entry(time.time().clone())
+                        .or_insert_with(|| (time.delayed_for_output(time.time(), 0), Vec::new()))
+                        .1
+                        .extend(records.drain(..).map(|(_, key, val)| (key, val)));
+                });
+                state_in.for_each(|time, data| {
+                    data.swap(&mut installs);
+                    arrived
+                        .entry(time.time().clone())
+                        .or_insert_with(Vec::new)
+                        .extend(installs.drain(..).map(|(_, key, state)| (key, state)));
+                });
+
+                exports.sort_by(|x, y| x.0.cmp(&y.0));
+                exports.reverse();
+
+                // Alternate between exporting and applying, in time order.
+                loop {
+                    let next_export = exports.last().map(|(time, _)| time.clone());
+                    let next_apply = pending.keys().chain(arrived.keys()).min().cloned();
+
+                    match (next_export, next_apply) {
+                        (Some(time), apply) if apply.as_ref().map(|a| time.less_equal(a)).unwrap_or(true) => {
+                            // Export once no records or states before `time` may still arrive.
+                            if frontiers[0].frontier().iter().any(|t| t.less_than(&time)) ||
+                               frontiers[2].frontier().iter().any(|t| t.less_than(&time)) {
+                                break;
+                            }
+                            let (time, bin) = exports.pop().unwrap();
+                            let owner = routing.owner_at(bin, &time);
+                            if routing.owner_before(bin, &time) == index && owner != index {
+                                let keys = states.keys().filter(|key| bin_of(key) == bin).cloned().collect::<Vec<_>>();
+                                let cap = state_cap.as_ref().expect("state capability dropped with exports pending").delayed(&time);
+                                let mut session = migrate.session(&cap);
+                                for key in keys {
+                                    let state = states.remove(&key).unwrap();
+                                    session.give((owner, key, state));
+                                }
+                            }
+                        },
+                        (_, Some(time)) => {
+                            // Apply once all inputs, and so all exports, are complete through `time`.
+                            if frontiers.iter().any(|f| f.less_equal(&time)) {
+                                break;
+                            }
+                            for (key, state) in arrived.remove(&time).into_iter().flatten() {
+                                states.insert(key, state);
+                            }
+                            if let Some((cap, records)) = pending.remove(&time) {
+                                let mut session = output.session(&cap);
+                                for (key, val) in records {
+                                    let (remove, output) = {
+                                        let state = states.entry(key.clone()).or_insert_with(Default::default);
+                                        fold(&key, val, state)
+                                    };
+                                    if remove { states.remove(&key); }
+                                    session.give_iterator(output.into_iter());
+                                }
+                            }
+                        },
+                        _ => break,
+                    }
+                }
+
+                // Reassignments may still arrive at the control frontier.
+                let mut lower = frontiers[1].frontier().to_vec();
+                lower.extend(exports.iter().map(|(time, _)| time.clone()));
+                match lower.iter().min() {
+                    Some(time) => { if let Some(cap) = state_cap.as_mut() { cap.downgrade(time); } },
+                    None => { state_cap = None; },
+                }
+                routing.compact(&lower[..]);
+            }
+        });
+
+        result
+    }
+}
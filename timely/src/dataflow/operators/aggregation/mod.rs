@@ -3,6 +3,8 @@
 //! Two traits, `Aggregate` and `StateMachine`, which support the accumulation of streamed information.
 //!
 //! `Aggregate` accumulates records within times, and releases the accumulations once the time is complete.
+//! Its `aggregate_combined` method first accumulates partial states on each worker, so that only one record
+//! per key and worker is exchanged.
 //!
 //! `StateMachine` responds to a sequence of keyed events, maintaining and updating a state for each key.
 //! The user logic may produce output records for each transition, and optionally de-register the state to
@@ -10,9 +12,24 @@
 //!
 //! The two methods are often combined, using first `Aggregate` to reduce the volume of information, and then
 //! `StateMachine` to track an accumulation across timestamps.
+//!
+//! `MigratingStateMachine` is a variant of `StateMachine` whose keys may be reassigned between workers
+//! by a control stream, moving the state of each key to its new owner.
+//!
+//! `AggregateByKey` provides `Aggregate` with records routed by a supplied hasher, and the common case
+//! of combining each key's values with a binary function.
+//!
+//! `ConsolidateByKey` sums the diffs of `(key, diff)` records within times, for any diff type that
+//! implements `Semigroup`, and drops the keys whose diffs cancel.
 
 pub use self::aggregate::Aggregate;
 pub use self::state_machine::StateMachine;
+pub use self::migrate::MigratingStateMachine;
+pub use self::by_key::AggregateByKey;
+pub use self::consolidate::{ConsolidateByKey, Semigroup};
 
 pub mod state_machine;
+pub mod migrate;
 pub mod aggregate;
+pub mod by_key;
+pub mod consolidate;
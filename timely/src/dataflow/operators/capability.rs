@@ -31,6 +31,9 @@ use crate::progress::Timestamp;
 use crate::progress::ChangeBatch;
 use crate::scheduling::Activations;
 
+/// The shared counts of the capabilities held at each output of an operator.
+pub(crate) type OutputCounts<T> = Rc<RefCell<Vec<Rc<RefCell<ChangeBatch<T>>>>>>;
+
 /// An internal trait expressing the capability to send messages with a given timestamp.
 pub trait CapabilityTrait<T: Timestamp> {
     /// The timestamp associated with the capability.
@@ -60,6 +63,8 @@ impl<'a, T: Timestamp, C: CapabilityTrait<T>> CapabilityTrait<T> for &'a mut C {
 pub struct Capability<T: Timestamp> {
     time: T,
     internal: Rc<RefCell<ChangeBatch<T>>>,
+    // The worker's tracker and the capability's identifier with it, if tracking is enabled.
+    tracked: Option<(tracking::Tracker, u64)>,
 }
 
 impl<T: Timestamp> CapabilityTrait<T> for Capability<T> {
@@ -85,7 +90,7 @@ impl<T: Timestamp> Capability<T> {
         if !self.time.less_equal(new_time) {
             panic!("Attempted to delay {:?} to {:?}, which is not `less_equal` the capability's time.", self, new_time);
         }
-        mint(new_time.clone(), self.internal.clone())
+        mint(new_time.clone(), self.internal.clone(), self.tracker())
     }
 
     /// Downgrades the capability to one corresponding to `new_time`.
@@ -96,17 +101,23 @@ impl<T: Timestamp> Capability<T> {
         let new_cap = self.delayed(new_time);
         *self = new_cap;
     }
+
+    fn tracker(&self) -> Option<&tracking::Tracker> {
+        self.tracked.as_ref().map(|(tracker, _)| tracker)
+    }
 }
 
 /// Creates a new capability at `t` while incrementing (and keeping a reference to) the provided
-/// `ChangeBatch`.
+/// `ChangeBatch`, and records it with `tracker`, if any.
 /// Declared separately so that it can be kept private when `Capability` is re-exported.
 #[inline]
-pub fn mint<T: Timestamp>(time: T, internal: Rc<RefCell<ChangeBatch<T>>>) -> Capability<T> {
+pub fn mint<T: Timestamp>(time: T, internal: Rc<RefCell<ChangeBatch<T>>>, tracker: Option<&tracking::Tracker>) -> Capability<T> {
     internal.borrow_mut().update(time.clone(), 1);
+    let tracked = tracker.map(|tracker| (tracker.clone(), tracker.track(&time, &internal)));
     Capability {
         time,
         internal,
+        tracked,
     }
 }
 
@@ -117,13 +128,16 @@ impl<T: Timestamp> Drop for Capability<T> {
     #[inline]
     fn drop(&mut self) {
         self.internal.borrow_mut().update(self.time.clone(), -1);
+        if let Some((tracker, id)) = self.tracked.as_ref() {
+            tracker.untrack(*id);
+        }
     }
 }
 
 impl<T: Timestamp> Clone for Capability<T> {
     #[inline]
     fn clone(&self) -> Capability<T> {
-        mint(self.time.clone(), self.internal.clone())
+        mint(self.time.clone(), self.internal.clone(), self.tracker())
     }
 }
 
@@ -169,7 +183,8 @@ impl<T: Timestamp> ::std::hash::Hash for Capability<T> {
 /// and turns it into an owned capability
 pub struct CapabilityRef<'cap, T: Timestamp+'cap> {
     time: &'cap T,
-    internal: Rc<RefCell<Vec<Rc<RefCell<ChangeBatch<T>>>>>>,
+    internal: OutputCounts<T>,
+    tracker: Option<tracking::Tracker>,
 }
 
 impl<'cap, T: Timestamp+'cap> CapabilityTrait<T> for CapabilityRef<'cap, T> {
@@ -203,7 +218,7 @@ impl<'cap, T: Timestamp+'cap> CapabilityRef<'cap, T> {
             panic!("Attempted to delay {:?} to {:?}, which is not `less_equal` the capability's time.", self, new_time);
         }
         if output_port < self.internal.borrow().len() {
-            mint(new_time.clone(), self.internal.borrow()[output_port].clone())
+            mint(new_time.clone(), self.internal.borrow()[output_port].clone(), self.tracker.as_ref())
         }
         else {
             panic!("Attempted to acquire a capability for a non-existent output port.");
@@ -224,7 +239,7 @@ impl<'cap, T: Timestamp+'cap> CapabilityRef<'cap, T> {
     /// Transforms to an owned capability for a specific output port.
     pub fn retain_for_output(self, output_port: usize) -> Capability<T> {
         if output_port < self.internal.borrow().len() {
-            mint(self.time.clone(), self.internal.borrow()[output_port].clone())
+            mint(self.time.clone(), self.internal.borrow()[output_port].clone(), self.tracker.as_ref())
         }
         else {
             panic!("Attempted to acquire a capability for a non-existent output port.");
@@ -251,10 +266,11 @@ impl<'cap, T: Timestamp> Debug for CapabilityRef<'cap, T> {
 /// `ChangeBatch`.
 /// Declared separately so that it can be kept private when `Capability` is re-exported.
 #[inline]
-pub fn mint_ref<'cap, T: Timestamp>(time: &'cap T, internal: Rc<RefCell<Vec<Rc<RefCell<ChangeBatch<T>>>>>>) -> CapabilityRef<'cap, T> {
+pub fn mint_ref<'cap, T: Timestamp>(time: &'cap T, internal: OutputCounts<T>, tracker: Option<tracking::Tracker>) -> CapabilityRef<'cap, T> {
     CapabilityRef {
         time,
         internal,
+        tracker,
     }
 }
 
@@ -308,16 +324,23 @@ impl<T: Timestamp> Drop for ActivateCapability<T> {
 }
 
 /// A set of capabilities, for possibly incomparable times.
+///
+/// The set retains only capabilities that are minimal among those for the same output, and
+/// so describes the antichain of times at which each output may still send data. Outputs are
+/// numbered in the order their first capabilities were added to the set, which matches the
+/// operator's output ports when the set is built from the capabilities an operator builder
+/// supplies.
 #[derive(Clone, Debug)]
 pub struct CapabilitySet<T: Timestamp> {
     elements: Vec<Capability<T>>,
+    outputs: Vec<Rc<RefCell<ChangeBatch<T>>>>,
 }
 
 impl<T: Timestamp> CapabilitySet<T> {
 
     /// Allocates an empty capability set.
     pub fn new() -> Self {
-        CapabilitySet { elements: Vec::new() }
+        CapabilitySet { elements: Vec::new(), outputs: Vec::new() }
     }
 
     /// Allocates a capability set containing a single capability.
@@ -350,11 +373,16 @@ impl<T: Timestamp> CapabilitySet<T> {
     /// });
     /// ```
     pub fn from_elem(cap: Capability<T>) -> Self {
-        CapabilitySet { elements: vec![cap] }
+        let mut set = CapabilitySet::new();
+        set.insert(cap);
+        set
     }
 
     /// Inserts `capability` into the set, discarding redundant capabilities.
     pub fn insert(&mut self, capability: Capability<T>) {
+        if !self.outputs.iter().any(|output| Rc::ptr_eq(output, &capability.internal)) {
+            self.outputs.push(capability.internal.clone());
+        }
         if !self.elements.iter().any(|c| c.less_equal(&capability)) {
             self.elements.retain(|c| !capability.less_equal(c));
             self.elements.push(capability);
@@ -368,8 +396,58 @@ impl<T: Timestamp> CapabilitySet<T> {
         self.elements.iter().find(|c| c.time().less_equal(time)).unwrap().delayed(time)
     }
 
+    /// Creates a new capability to send data at `time` on output `output_port`.
+    ///
+    /// This method panics if the set holds no capability for `output_port` less or equal to `time`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect, CapabilitySet};
+    /// use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::example(|scope| {
+    ///     let stream = (0 .. 10u64).to_stream(scope);
+    ///     let mut builder = OperatorBuilder::new("Split".to_owned(), scope.clone());
+    ///     let mut input = builder.new_input(&stream, Pipeline);
+    ///     let (mut evens, evens_stream) = builder.new_output();
+    ///     let (mut odds, odds_stream) = builder.new_output();
+    ///     builder.build(move |capabilities| {
+    ///         let mut capabilities = CapabilitySet::from(capabilities);
+    ///         let mut vector = Vec::new();
+    ///         move |frontiers| {
+    ///             let mut evens = evens.activate();
+    ///             let mut odds = odds.activate();
+    ///             input.for_each(|time, data| {
+    ///                 data.swap(&mut vector);
+    ///                 let even = capabilities.delayed_for_output(time.time(), 0);
+    ///                 let odd = capabilities.delayed_for_output(time.time(), 1);
+    ///                 for x in vector.drain(..) {
+    ///                     if x % 2 == 0 { evens.session(&even).give(x); }
+    ///                     else { odds.session(&odd).give(x); }
+    ///                 }
+    ///             });
+    ///             capabilities.downgrade(&frontiers[0].frontier());
+    ///         }
+    ///     });
+    ///     evens_stream.inspect(|x| assert_eq!(x % 2, 0));
+    ///     odds_stream.inspect(|x| assert_eq!(x % 2, 1));
+    /// });
+    /// ```
+    pub fn delayed_for_output(&self, time: &T, output_port: usize) -> Capability<T> {
+        let output = self.outputs.get(output_port).expect("Attempted to acquire a capability for an output not in the set.");
+        self.elements
+            .iter()
+            .find(|c| Rc::ptr_eq(&c.internal, output) && c.time().less_equal(time))
+            .unwrap_or_else(|| panic!("Attempted to delay to {:?} on output {}, which holds no capability less or equal.", time, output_port))
+            .delayed(time)
+    }
+
     /// Downgrades the set of capabilities to correspond with the times in `frontier`.
     ///
+    /// Each output retains capabilities for those times in `frontier` that are greater or equal
+    /// to one of its current capabilities.
+    ///
     /// This method panics if any element of `frontier` is not greater or equal to some element of `self.elements`.
     pub fn downgrade<B, F>(&mut self, frontier: F)
     where
@@ -378,10 +456,31 @@ impl<T: Timestamp> CapabilitySet<T> {
     {
         let count = self.elements.len();
         for time in frontier.into_iter() {
-            let capability = self.delayed(time.borrow());
-            self.elements.push(capability);
+            let time = time.borrow();
+            let mut found = false;
+            for output in self.outputs.iter() {
+                if let Some(capability) = self.elements[.. count].iter().find(|c| Rc::ptr_eq(&c.internal, output) && c.time().less_equal(time)) {
+                    let capability = capability.delayed(time);
+                    self.elements.push(capability);
+                    found = true;
+                }
+            }
+            if !found {
+                panic!("Attempted to downgrade to {:?}, which is not greater or equal to a held capability.", time);
+            }
+        }
+        self.elements.drain(.. count);
+    }
+}
+
+impl<T: Timestamp> From<Vec<Capability<T>>> for CapabilitySet<T> {
+    /// Collects the capabilities supplied to an operator's constructor, one for each output.
+    fn from(capabilities: Vec<Capability<T>>) -> Self {
+        let mut set = CapabilitySet::new();
+        for capability in capabilities {
+            set.insert(capability);
         }
-        self.elements.drain(..count);
+        set
     }
 }
 
@@ -392,3 +491,123 @@ impl<T: Timestamp> Deref for CapabilitySet<T> {
         &self.elements[..]
     }
 }
+
+/// Diagnostic tracking of the capabilities held by a worker's operators.
+///
+/// A worker whose configuration requests it keeps a `Tracker` among its extensions, which
+/// records the operator, output, time, and creation backtrace of each live `Capability`, and
+/// the times held by operators that account for their capabilities without `Capability`s.
+pub(crate) mod tracking {
+
+    use std::backtrace::Backtrace;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+    use std::fmt::Debug;
+    use std::rc::Rc;
+
+    use crate::progress::Timestamp;
+    use crate::progress::ChangeBatch;
+    use crate::logging::CapabilityHeldEvent;
+
+    #[derive(Default)]
+    struct Inner {
+        next: u64,
+        // Operator address and output port, by the address of each output's `ChangeBatch`.
+        outputs: HashMap<usize, (Vec<usize>, usize)>,
+        // Operator address, output port, time, and creation backtrace of each live capability.
+        live: HashMap<u64, (Vec<usize>, usize, String, Backtrace)>,
+        // Counts of the times held by operators without `Capability`s, by address and port.
+        held: HashMap<(Vec<usize>, usize), HashMap<String, i64>>,
+    }
+
+    /// The capabilities held by the operators of one worker.
+    #[derive(Clone, Default)]
+    pub(crate) struct Tracker {
+        inner: Rc<RefCell<Inner>>,
+    }
+
+    fn key<T: Timestamp>(internal: &Rc<RefCell<ChangeBatch<T>>>) -> usize {
+        Rc::as_ptr(internal) as *const () as usize
+    }
+
+    impl Tracker {
+        /// Associates the capabilities of an output with its operator and port, for as long as
+        /// the returned registration lives.
+        pub(crate) fn register_output<T: Timestamp>(&self, internal: &Rc<RefCell<ChangeBatch<T>>>, address: &[usize], port: usize) -> Output {
+            let key = key(internal);
+            self.inner.borrow_mut().outputs.insert(key, (address.to_vec(), port));
+            Output { tracker: self.clone(), address: address.to_vec(), port, key: Some(key) }
+        }
+
+        /// Registers an output whose capabilities are counted with `Output::update`, rather than
+        /// held as `Capability`s.
+        pub(crate) fn counted_output(&self, address: &[usize], port: usize) -> Output {
+            Output { tracker: self.clone(), address: address.to_vec(), port, key: None }
+        }
+
+        /// Records a new capability, returning its identifier.
+        pub(crate) fn track<T: Timestamp>(&self, time: &T, internal: &Rc<RefCell<ChangeBatch<T>>>) -> u64 {
+            let mut inner = self.inner.borrow_mut();
+            let id = inner.next;
+            inner.next += 1;
+            let (address, port) = inner.outputs.get(&key(internal)).cloned().unwrap_or_default();
+            inner.live.insert(id, (address, port, format!("{:?}", time), Backtrace::force_capture()));
+            id
+        }
+
+        /// Records that a capability has been dropped.
+        pub(crate) fn untrack(&self, id: u64) {
+            self.inner.borrow_mut().live.remove(&id);
+        }
+
+        /// Describes each live capability and each counted time, ordered by operator and time.
+        pub(crate) fn holders(&self) -> Vec<CapabilityHeldEvent> {
+            let inner = self.inner.borrow();
+            let live = inner.live.values().map(|(addr, port, time, backtrace)| {
+                CapabilityHeldEvent { addr: addr.clone(), name: String::new(), port: *port, time: time.clone(), backtrace: backtrace.to_string() }
+            });
+            let held = inner.held.iter().flat_map(|((addr, port), times)| {
+                times.keys().map(move |time| {
+                    CapabilityHeldEvent { addr: addr.clone(), name: String::new(), port: *port, time: time.clone(), backtrace: String::new() }
+                })
+            });
+            let mut holders = live.chain(held).collect::<Vec<_>>();
+            holders.sort();
+            holders
+        }
+    }
+
+    /// The registration of an operator output with a worker's `Tracker`.
+    ///
+    /// Dropping the registration, as happens when its operator is dropped, forgets the output.
+    pub(crate) struct Output {
+        tracker: Tracker,
+        address: Vec<usize>,
+        port: usize,
+        key: Option<usize>,
+    }
+
+    impl Output {
+        /// Counts `diff` capabilities for `time` held by this worker at the output.
+        pub(crate) fn update<T: Debug>(&self, time: &T, diff: i64) {
+            let mut inner = self.tracker.inner.borrow_mut();
+            let times = inner.held.entry((self.address.clone(), self.port)).or_default();
+            let time = format!("{:?}", time);
+            let count = times.entry(time.clone()).or_insert(0);
+            *count += diff;
+            if *count == 0 {
+                times.remove(&time);
+            }
+        }
+    }
+
+    impl Drop for Output {
+        fn drop(&mut self) {
+            let mut inner = self.tracker.inner.borrow_mut();
+            if let Some(key) = self.key {
+                inner.outputs.remove(&key);
+            }
+            inner.held.remove(&(self.address.clone(), self.port));
+        }
+    }
+}
@@ -112,15 +112,155 @@ pub mod link {
 }
 
 /// A binary event pusher and iterator.
+///
+/// The binary format begins with a [`CaptureHeader`](binary::CaptureHeader), which describes
+/// the format, the crate version, and the timestamp and data types of the events that follow.
+/// An `EventReader` checks the header before decoding any event, and panics if the events were
+/// written with different types, unless a migration from those types has been registered.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::capture::{Event, EventPusher, EventWriter, EventReader};
+/// use timely::dataflow::operators::capture::event::EventIterator;
+///
+/// let mut bytes = Vec::new();
+/// EventWriter::<u32, u32, _>::new(&mut bytes).push(Event::Messages(0, vec![1, 2, 3]));
+///
+/// // Events written as `u32` are read as `u64`, by a registered migration.
+/// let mut reader = EventReader::<u64, u64, _>::new(&bytes[..])
+///     .with_migration(|event: Event<u32, u32>| match event {
+///         Event::Progress(updates) => Event::Progress(updates.into_iter().map(|(t, d)| (t as u64, d)).collect()),
+///         Event::Messages(time, data) => Event::Messages(time as u64, data.into_iter().map(|x| x as u64).collect()),
+///     });
+///
+/// let mut events = Vec::new();
+/// for _ in 0 .. 4 {
+///     if let Some(event) = reader.next() { events.push(event.clone()); }
+/// }
+/// assert_eq!(events, vec![Event::Messages(0, vec![1, 2, 3])]);
+/// assert_eq!(reader.header().unwrap().timestamp, "u32");
+/// ```
 pub mod binary {
 
+    use std::collections::HashMap;
     use std::io::Write;
     use abomonation::Abomonation;
     use super::{Event, EventPusher, EventIterator};
 
+    /// Bytes identifying a binary event stream.
+    const MAGIC: &[u8; 8] = b"TDEVENTS";
+    /// The version of the binary format, which changes with the layout of `Event`.
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// Describes the events of a binary event stream.
+    ///
+    /// The schema is a hash of the format version, and of the names and sizes of the timestamp
+    /// and data types. Type names are those reported by `std::any::type_name`, which are not
+    /// guaranteed to be stable across compiler versions; a changed name is reported as a mismatch.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct CaptureHeader {
+        /// The version of the binary format.
+        pub format: u32,
+        /// The version of the crate that wrote the stream.
+        pub crate_version: String,
+        /// The name of the timestamp type.
+        pub timestamp: String,
+        /// The name of the data type.
+        pub data: String,
+        /// A hash of the format version and the timestamp and data types.
+        pub schema: u64,
+    }
+
+    impl CaptureHeader {
+        /// The header of events with timestamp type `T` and data type `D`.
+        pub fn of<T, D>() -> Self {
+            CaptureHeader {
+                format: FORMAT_VERSION,
+                crate_version: env!("CARGO_PKG_VERSION").to_owned(),
+                timestamp: ::std::any::type_name::<T>().to_owned(),
+                data: ::std::any::type_name::<D>().to_owned(),
+                schema: schema::<T, D>(),
+            }
+        }
+
+        /// Writes the header to `writer`.
+        pub fn encode<W: Write>(&self, writer: &mut W) -> ::std::io::Result<()> {
+            writer.write_all(MAGIC)?;
+            writer.write_all(&self.format.to_le_bytes())?;
+            for text in [&self.crate_version, &self.timestamp, &self.data].iter() {
+                writer.write_all(&(text.len() as u32).to_le_bytes())?;
+                writer.write_all(text.as_bytes())?;
+            }
+            writer.write_all(&self.schema.to_le_bytes())?;
+            // Pad the header to a multiple of eight bytes, to align the events that follow.
+            let length = MAGIC.len() + 4 + 12 + self.crate_version.len() + self.timestamp.len() + self.data.len() + 8;
+            writer.write_all(&[0u8; 8][.. (8 - length % 8) % 8])
+        }
+
+        /// Reads a header from the start of `bytes`, returning it and the number of bytes it occupies.
+        ///
+        /// Returns `Ok(None)` if `bytes` holds only a prefix of a header, and an error if `bytes`
+        /// does not start with a header.
+        pub fn decode(bytes: &[u8]) -> Result<Option<(Self, usize)>, String> {
+            let prefix = ::std::cmp::min(bytes.len(), MAGIC.len());
+            if bytes[.. prefix] != MAGIC[.. prefix] {
+                return Err("event stream does not start with a capture header".to_owned());
+            }
+            let mut offset = MAGIC.len();
+            let format = match take(bytes, &mut offset, 4) { Some(b) => u32::from_le_bytes([b[0], b[1], b[2], b[3]]), None => return Ok(None) };
+            let mut texts = Vec::new();
+            for _ in 0 .. 3 {
+                let length = match take(bytes, &mut offset, 4) { Some(b) => u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize, None => return Ok(None) };
+                match take(bytes, &mut offset, length) {
+                    Some(text) => texts.push(String::from_utf8(text.to_vec()).map_err(|_| "capture header is not valid UTF-8".to_owned())?),
+                    None => return Ok(None),
+                }
+            }
+            let schema = match take(bytes, &mut offset, 8) {
+                Some(b) => u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]),
+                None => return Ok(None),
+            };
+            let padding = (8 - offset % 8) % 8;
+            if take(bytes, &mut offset, padding).is_none() {
+                return Ok(None);
+            }
+            let data = texts.pop().unwrap();
+            let timestamp = texts.pop().unwrap();
+            let crate_version = texts.pop().unwrap();
+            Ok(Some((CaptureHeader { format, crate_version, timestamp, data, schema }, offset)))
+        }
+    }
+
+    // The `length` bytes at `offset`, if present, advancing `offset` past them.
+    fn take<'a>(bytes: &'a [u8], offset: &mut usize, length: usize) -> Option<&'a [u8]> {
+        let taken = bytes.get(*offset .. *offset + length);
+        *offset += length;
+        taken
+    }
+
+    // The FNV-1a hash of the format version and the names and sizes of `T` and `D`.
+    fn schema<T, D>() -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let mut write = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        write(&FORMAT_VERSION.to_le_bytes());
+        write(::std::any::type_name::<T>().as_bytes());
+        write(&(::std::mem::size_of::<T>() as u64).to_le_bytes());
+        write(::std::any::type_name::<D>().as_bytes());
+        write(&(::std::mem::size_of::<D>() as u64).to_le_bytes());
+        hash
+    }
+
     /// A wrapper for `W: Write` implementing `EventPusher<T, D>`.
+    ///
+    /// The stream's header is written before the first event.
     pub struct EventWriter<T, D, W: ::std::io::Write> {
         stream: W,
+        header: bool,
         phant: ::std::marker::PhantomData<(T,D)>,
     }
 
@@ -129,6 +269,7 @@ pub mod binary {
         pub fn new(w: W) -> EventWriter<T, D, W> {
             EventWriter {
                 stream: w,
+                header: false,
                 phant: ::std::marker::PhantomData,
             }
         }
@@ -137,10 +278,18 @@ pub mod binary {
     impl<T: Abomonation, D: Abomonation, W: ::std::io::Write> EventPusher<T, D> for EventWriter<T, D, W> {
         fn push(&mut self, event: Event<T, D>) {
             // TODO: `push` has no mechanism to report errors, so we `unwrap`.
+            if !self.header {
+                CaptureHeader::of::<T, D>().encode(&mut self.stream).expect("Event header write failed");
+                self.header = true;
+            }
             unsafe { ::abomonation::encode(&event, &mut self.stream).expect("Event abomonation/write failed"); }
         }
     }
 
+    // Decodes an event written with other types from the start of the bytes, returning it
+    // converted and the number of bytes remaining.
+    type Migration<T, D> = Box<dyn FnMut(&mut [u8]) -> Option<(Event<T, D>, usize)>>;
+
     /// A Wrapper for `R: Read` implementing `EventIterator<T, D>`.
     pub struct EventReader<T, D, R: ::std::io::Read> {
         reader: R,
@@ -149,6 +298,13 @@ pub mod binary {
         buff2: Vec<u8>,
         consumed: usize,
         valid: usize,
+        // Whether the stream starts with a header, and the header once read.
+        expect_header: bool,
+        header: Option<CaptureHeader>,
+        // Migrations from other schemas, and the one in use, with its most recent event.
+        migrations: HashMap<u64, Migration<T, D>>,
+        migration: Option<Migration<T, D>>,
+        migrated: Option<Event<T, D>>,
         phant: ::std::marker::PhantomData<(T,D)>,
     }
 
@@ -162,19 +318,88 @@ pub mod binary {
                 buff2: vec![],
                 consumed: 0,
                 valid: 0,
+                expect_header: true,
+                header: None,
+                migrations: HashMap::new(),
+                migration: None,
+                migrated: None,
                 phant: ::std::marker::PhantomData,
             }
         }
+
+        /// Allocates a new `EventReader` for a stream written without a header, by versions
+        /// before headers were introduced.
+        ///
+        /// The stream's types are not checked, and must be `T` and `D`.
+        pub fn without_header(r: R) -> EventReader<T, D, R> {
+            EventReader { expect_header: false, ..Self::new(r) }
+        }
+
+        /// Registers a migration from events written with timestamp type `T0` and data type `D0`.
+        ///
+        /// If the header of the stream describes `T0` and `D0`, each event is decoded with those
+        /// types and converted by `func`.
+        pub fn with_migration<T0, D0, F>(mut self, mut func: F) -> Self
+        where
+            T0: Abomonation+Clone,
+            D0: Abomonation+Clone,
+            F: FnMut(Event<T0, D0>)->Event<T, D>+'static,
+        {
+            let migration: Migration<T, D> = Box::new(move |bytes| {
+                unsafe { ::abomonation::decode::<Event<T0, D0>>(bytes) }
+                    .map(|(event, rest)| (func(event.clone()), rest.len()))
+            });
+            self.migrations.insert(schema::<T0, D0>(), migration);
+            self
+        }
+
+        /// The header of the stream, once it has been read.
+        pub fn header(&self) -> Option<&CaptureHeader> {
+            self.header.as_ref()
+        }
+
+        // Reads the header, if it is available, and checks it against `T`, `D`, and the migrations.
+        fn read_header(&mut self) -> bool {
+            let (header, length) = match CaptureHeader::decode(&self.buff1[self.consumed .. self.valid]) {
+                Ok(Some(decoded)) => decoded,
+                Ok(None) => return false,
+                Err(error) => panic!("EventReader: {}", error),
+            };
+            let expected = CaptureHeader::of::<T, D>();
+            if header.schema != expected.schema {
+                match self.migrations.remove(&header.schema) {
+                    Some(migration) => self.migration = Some(migration),
+                    None => panic!(
+                        "EventReader: events were written as Event<{}, {}> by version {} (format {}), but are read as Event<{}, {}>",
+                        header.timestamp, header.data, header.crate_version, header.format, expected.timestamp, expected.data,
+                    ),
+                }
+            }
+            self.consumed += length;
+            self.header = Some(header);
+            true
+        }
     }
 
     impl<T: Abomonation, D: Abomonation, R: ::std::io::Read> EventIterator<T, D> for EventReader<T, D, R> {
         fn next(&mut self) -> Option<&Event<T, D>> {
 
-            // if we can decode something, we should just return it! :D
-            if unsafe { ::abomonation::decode::<Event<T,D>>(&mut self.buff1[self.consumed..]) }.is_some() {
-                let (item, rest) = unsafe { ::abomonation::decode::<Event<T,D>>(&mut self.buff1[self.consumed..]) }.unwrap();
-                self.consumed = self.valid - rest.len();
-                return Some(item);
+            let ready = !self.expect_header || self.header.is_some() || self.read_header();
+
+            if ready {
+                if let Some(migration) = self.migration.as_mut() {
+                    if let Some((event, rest)) = migration(&mut self.buff1[self.consumed .. self.valid]) {
+                        self.consumed = self.valid - rest;
+                        self.migrated = Some(event);
+                        return self.migrated.as_ref();
+                    }
+                }
+                // if we can decode something, we should just return it! :D
+                else if unsafe { ::abomonation::decode::<Event<T,D>>(&mut self.buff1[self.consumed..]) }.is_some() {
+                    let (item, rest) = unsafe { ::abomonation::decode::<Event<T,D>>(&mut self.buff1[self.consumed..]) }.unwrap();
+                    self.consumed = self.valid - rest.len();
+                    return Some(item);
+                }
             }
             // if we exhaust data we should shift back (if any shifting to do)
             if self.consumed > 0 {
@@ -0,0 +1,272 @@
+//! Captured event streams with an index, from which replays may start at a requested time.
+//!
+//! An [`IndexedEventWriter`] writes events in the binary format of an
+//! [`EventWriter`](super::EventWriter), and alongside them an index of checkpoints. Each
+//! checkpoint records a byte offset in the events, the capabilities the stream held at that
+//! offset, and the greatest times of the messages before it. [`replay_from`] reads the index,
+//! seeks the events to the last checkpoint before which no message is at or after the requested
+//! time, and replays from there, starting from the capabilities of the checkpoint and omitting
+//! messages at times not at or after the requested time. A partial replay of a large capture
+//! then reads from about where its first message lies, rather than from the start.
+//!
+//! # Examples
+//! ```
+//! use std::fs::File;
+//! use timely::dataflow::operators::{ToStream, Delay, Capture, Inspect};
+//! use timely::dataflow::operators::capture::Replay;
+//! use timely::dataflow::operators::capture::indexed::{IndexedEventWriter, replay_from};
+//!
+//! let directory = std::env::temp_dir();
+//! let events = directory.join(format!("timely-indexed-{}.events", std::process::id()));
+//! let index = directory.join(format!("timely-indexed-{}.index", std::process::id()));
+//!
+//! let writer = IndexedEventWriter::new(File::create(&events).unwrap(), File::create(&index).unwrap()).period(1024);
+//! timely::example(move |scope| {
+//!     (0 .. 1000u64).to_stream(scope)
+//!                   .delay(|x, _time| *x / 10)
+//!                   .capture_into(writer);
+//! });
+//!
+//! timely::example(move |scope| {
+//!     let reader = replay_from::<u64, u64, _, _>(File::open(&events).unwrap(), File::open(&index).unwrap(), 90).unwrap();
+//!     // The replay skips the events of the earlier times.
+//!     assert!(reader.offset() > 0);
+//!     Some(reader).replay_into(scope)
+//!                 .inspect_time(|time, x| assert!(*time >= 90 && *x / 10 == *time));
+//! });
+//! ```
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use abomonation::Abomonation;
+
+use crate::progress::{ChangeBatch, Timestamp};
+
+use super::{CaptureHeader, Event, EventPusher, EventReader};
+use super::event::EventIterator;
+
+/// The default number of event bytes between checkpoints.
+pub const DEFAULT_PERIOD: u64 = 1 << 20;
+
+/// A position in the events of an indexed capture, from which a replay may start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint<T> {
+    /// The byte offset of the first event after the checkpoint.
+    pub offset: u64,
+    /// The capabilities held by the stream before that event, as counts of times.
+    pub counts: Vec<(T, i64)>,
+    /// The greatest times of the messages before that event.
+    pub maxima: Vec<T>,
+}
+
+impl<T: Abomonation> Abomonation for Checkpoint<T> {
+    #[inline] unsafe fn entomb<W: Write>(&self, write: &mut W) -> io::Result<()> {
+        self.offset.entomb(write)?;
+        self.counts.entomb(write)?;
+        self.maxima.entomb(write)
+    }
+    #[inline] unsafe fn exhume<'b>(&mut self, bytes: &'b mut [u8]) -> Option<&'b mut [u8]> {
+        let bytes = self.offset.exhume(bytes)?;
+        let bytes = self.counts.exhume(bytes)?;
+        self.maxima.exhume(bytes)
+    }
+    #[inline] fn extent(&self) -> usize {
+        self.offset.extent() + self.counts.extent() + self.maxima.extent()
+    }
+}
+
+/// An event pusher writing events and an index of checkpoints into them.
+///
+/// Each writer captures one stream, and so one worker's shard of a captured stream.
+pub struct IndexedEventWriter<T: Timestamp, D, W: Write, I: Write> {
+    events: W,
+    index: I,
+    period: u64,
+    // The bytes written to `events`, and their number when the last checkpoint was written.
+    offset: u64,
+    checkpointed: Option<u64>,
+    counts: ChangeBatch<T>,
+    maxima: Vec<T>,
+    buffer: Vec<u8>,
+    phant: ::std::marker::PhantomData<D>,
+}
+
+impl<T: Timestamp, D, W: Write, I: Write> IndexedEventWriter<T, D, W, I> {
+    /// Allocates a writer of events into `events` and of checkpoints into `index`.
+    pub fn new(events: W, index: I) -> Self {
+        IndexedEventWriter {
+            events,
+            index,
+            period: DEFAULT_PERIOD,
+            offset: 0,
+            checkpointed: None,
+            counts: ChangeBatch::new_from(T::minimum(), 1),
+            maxima: Vec::new(),
+            buffer: Vec::new(),
+            phant: ::std::marker::PhantomData,
+        }
+    }
+
+    /// Sets the number of event bytes between checkpoints, by default [`DEFAULT_PERIOD`].
+    pub fn period(mut self, bytes: u64) -> Self {
+        self.period = bytes;
+        self
+    }
+}
+
+impl<T: Timestamp+Abomonation, D, W: Write, I: Write> IndexedEventWriter<T, D, W, I> {
+    // Writes the headers of both files, and the first checkpoint.
+    fn write_headers(&mut self) -> io::Result<()> {
+        CaptureHeader::of::<T, D>().encode(&mut self.buffer)?;
+        self.events.write_all(&self.buffer[..])?;
+        self.offset = self.buffer.len() as u64;
+        self.buffer.clear();
+        CaptureHeader::of::<T, D>().encode(&mut self.index)?;
+        self.write_checkpoint()
+    }
+
+    fn write_checkpoint(&mut self) -> io::Result<()> {
+        let checkpoint = Checkpoint {
+            offset: self.offset,
+            counts: self.counts.iter().cloned().collect(),
+            maxima: self.maxima.clone(),
+        };
+        unsafe { ::abomonation::encode(&checkpoint, &mut self.index)?; }
+        self.checkpointed = Some(self.offset);
+        Ok(())
+    }
+}
+
+impl<T: Timestamp+Abomonation, D: Abomonation, W: Write, I: Write> EventPusher<T, D> for IndexedEventWriter<T, D, W, I> {
+    fn push(&mut self, event: Event<T, D>) {
+        // TODO: `push` has no mechanism to report errors, so we `expect`.
+        match self.checkpointed {
+            None => self.write_headers().expect("Indexed event header write failed"),
+            Some(checkpointed) if self.offset - checkpointed >= self.period => {
+                self.write_checkpoint().expect("Indexed event checkpoint write failed");
+            },
+            Some(_) => { },
+        }
+        match &event {
+            Event::Progress(updates) => self.counts.extend(updates.iter().cloned()),
+            Event::Messages(time, _) => {
+                if !self.maxima.iter().any(|maximum| time.less_equal(maximum)) {
+                    self.maxima.retain(|maximum| !maximum.less_equal(time));
+                    self.maxima.push(time.clone());
+                }
+            },
+        }
+        unsafe { ::abomonation::encode(&event, &mut self.buffer).expect("Event abomonation failed"); }
+        self.events.write_all(&self.buffer[..]).expect("Indexed event write failed");
+        self.offset += self.buffer.len() as u64;
+        self.buffer.clear();
+    }
+}
+
+/// Reads the checkpoints of an index written by an [`IndexedEventWriter`].
+///
+/// Returns an error if the index was written with other types than `T` and `D`, or is corrupt.
+pub fn read_index<T, D, I>(mut index: I) -> io::Result<Vec<Checkpoint<T>>>
+where
+    T: Abomonation+Clone,
+    I: Read,
+{
+    let mut bytes = Vec::new();
+    index.read_to_end(&mut bytes)?;
+    let mut offset = check_header::<T, D>(&bytes[..])?;
+    let mut checkpoints = Vec::new();
+    let length = bytes.len();
+    while offset < length {
+        let (checkpoint, rest) = unsafe { ::abomonation::decode::<Checkpoint<T>>(&mut bytes[offset ..]) }
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "corrupt capture index"))?;
+        checkpoints.push(checkpoint.clone());
+        offset = length - rest.len();
+    }
+    Ok(checkpoints)
+}
+
+// Checks the capture header at the start of `bytes`, returning its length.
+fn check_header<T, D>(bytes: &[u8]) -> io::Result<usize> {
+    let (header, length) = match CaptureHeader::decode(bytes) {
+        Ok(Some(decoded)) => decoded,
+        Ok(None) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated capture header")),
+        Err(error) => return Err(io::Error::new(io::ErrorKind::InvalidData, error)),
+    };
+    let expected = CaptureHeader::of::<T, D>();
+    if header.schema != expected.schema {
+        let error = format!("events were written as Event<{}, {}>, but are read as Event<{}, {}>", header.timestamp, header.data, expected.timestamp, expected.data);
+        return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+    }
+    Ok(length)
+}
+
+/// Opens a replay of the events of an indexed capture, starting at `time`.
+///
+/// The events are read from the last checkpoint of `index` before which no message is at a time
+/// at or after `time`. The replay starts with the capabilities the stream held at the checkpoint,
+/// and omits messages at times not at or after `time`.
+///
+/// Returns an error if the events or index were written with other types than `T` and `D`.
+pub fn replay_from<T, D, R, I>(mut events: R, index: I, time: T) -> io::Result<IndexedEventReader<T, D, R>>
+where
+    T: Timestamp+Abomonation,
+    D: Abomonation+Clone,
+    R: Read+Seek,
+    I: Read,
+{
+    let checkpoints = read_index::<T, D, I>(index)?;
+
+    let mut header = Vec::new();
+    (&mut events).take(4096).read_to_end(&mut header)?;
+    check_header::<T, D>(&header[..])?;
+
+    let checkpoint = checkpoints.iter()
+                                .take_while(|checkpoint| !checkpoint.maxima.iter().any(|maximum| time.less_equal(maximum)))
+                                .last()
+                                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "capture index has no checkpoints"))?;
+    events.seek(SeekFrom::Start(checkpoint.offset))?;
+
+    // The replay starts from one capability for the minimum time, exchanged for those of the checkpoint.
+    let mut counts = ChangeBatch::new_from(T::minimum(), -1);
+    counts.extend(checkpoint.counts.iter().cloned());
+
+    Ok(IndexedEventReader {
+        reader: EventReader::without_header(events),
+        offset: checkpoint.offset,
+        time,
+        event: Some(Event::Progress(counts.into_inner())),
+        started: false,
+    })
+}
+
+/// An event iterator replaying an indexed capture from a time, created by [`replay_from`].
+pub struct IndexedEventReader<T, D, R: Read> {
+    reader: EventReader<T, D, R>,
+    offset: u64,
+    time: T,
+    event: Option<Event<T, D>>,
+    started: bool,
+}
+
+impl<T, D, R: Read> IndexedEventReader<T, D, R> {
+    /// The byte offset of the events at which the replay started.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl<T: Timestamp+Abomonation, D: Abomonation+Clone, R: Read> EventIterator<T, D> for IndexedEventReader<T, D, R> {
+    fn next(&mut self) -> Option<&Event<T, D>> {
+        if !self.started {
+            self.started = true;
+            return self.event.as_ref();
+        }
+        loop {
+            match self.reader.next() {
+                Some(Event::Messages(time, _)) if !self.time.less_equal(time) => { },
+                Some(event) => { self.event = Some(event.clone()); return self.event.as_ref(); },
+                None => return None,
+            }
+        }
+    }
+}
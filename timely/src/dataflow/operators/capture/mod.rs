@@ -13,6 +13,13 @@
 //! There is a protocol the captured stream uses, and implementors of new event streams should
 //! make sure to understand this (and complain if it is not clear).
 //!
+//! The [`tcp`] module uses these operators to connect independently deployed computations,
+//! sending each producing worker's shard to a consuming worker and replaying the shards with
+//! their progress statements, so that the consuming computation observes the producer's frontier.
+//!
+//! The [`indexed`] module writes captures with an index of checkpoints, so that a replay may seek
+//! to the events of a requested time rather than read the capture from its start.
+//!
 //! # Examples
 //!
 //! The type `Rc<EventLink<T,D>>` implements a typed linked list,
@@ -74,14 +81,18 @@
 //! ```
 
 pub use self::capture::Capture;
-pub use self::replay::Replay;
+pub use self::replay::{Replay, ReplayRebalanced};
 pub use self::extract::Extract;
 pub use self::event::{Event, EventPusher};
 pub use self::event::link::EventLink;
 pub use self::event::binary::EventReader;
 pub use self::event::binary::EventWriter;
+pub use self::event::binary::CaptureHeader;
+pub use self::indexed::{IndexedEventWriter, replay_from};
 
 pub mod capture;
 pub mod replay;
 pub mod extract;
 pub mod event;
+pub mod tcp;
+pub mod indexed;
@@ -38,11 +38,12 @@
 //! allowing the replay to occur in a timely dataflow computation with more or fewer workers
 //! than that in which the stream was captured.
 
-use crate::Data;
+use crate::{Data, ExchangeData};
 use crate::dataflow::{Scope, Stream};
 use crate::dataflow::channels::pushers::Counter as PushCounter;
 use crate::dataflow::channels::pushers::buffer::Buffer as PushBuffer;
 use crate::dataflow::operators::generic::builder_raw::OperatorBuilder;
+use crate::dataflow::operators::Exchange;
 use crate::progress::Timestamp;
 
 use super::Event;
@@ -74,7 +75,7 @@ where I : IntoIterator,
 
         let (targets, stream) = builder.new_output();
 
-        let mut output = PushBuffer::new(PushCounter::new(targets));
+        let mut output = PushBuffer::new(PushCounter::new(targets)).with_length(scope.config().batch_length()).with_lineage(scope.extensions().lineage());
         let mut event_streams = self.into_iter().collect::<Vec<_>>();
         let mut started = false;
 
@@ -117,3 +118,56 @@ where I : IntoIterator,
         stream
     }
 }
+
+/// Replay captured shards into a scope with a different number of workers.
+pub trait ReplayRebalanced : Scope {
+    /// Replays `shards` capture shards, distributing their records among workers by `route`.
+    ///
+    /// Each worker opens the shards `shard` for which `shard % peers` is its index, by calling
+    /// `open(shard)`, and replays them. Records are then routed to the worker indicated by
+    /// `route`, and so a capture of one number of workers can be replayed into any other. The
+    /// progress of the shards is merged as described for [`Replay`]; workers assigned no shards
+    /// hold no capabilities.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture, Inspect};
+    /// use timely::dataflow::operators::capture::{EventLink, ReplayRebalanced};
+    ///
+    /// // Capture eight shards, and replay them into one worker.
+    /// timely::example(|scope| {
+    ///     let shards = (0 .. 8u64).map(|shard| {
+    ///         let link = std::rc::Rc::new(EventLink::new());
+    ///         (shard * 10 .. shard * 10 + 10).to_stream(scope).capture_into(link.clone());
+    ///         link
+    ///     }).collect::<Vec<_>>();
+    ///     scope.replay_rebalanced(8, move |shard| shards[shard].clone(), |x: &u64| *x)
+    ///          .inspect(|x| println!("replayed: {:?}", x));
+    /// });
+    /// ```
+    fn replay_rebalanced<D, E, O, R>(&mut self, shards: usize, open: O, route: R) -> Stream<Self, D>
+    where
+        D: ExchangeData,
+        E: EventIterator<Self::Timestamp, D>+'static,
+        O: FnMut(usize)->E,
+        R: Fn(&D)->u64+'static;
+}
+
+impl<G: Scope> ReplayRebalanced for G {
+    fn replay_rebalanced<D, E, O, R>(&mut self, shards: usize, open: O, route: R) -> Stream<G, D>
+    where
+        D: ExchangeData,
+        E: EventIterator<G::Timestamp, D>+'static,
+        O: FnMut(usize)->E,
+        R: Fn(&D)->u64+'static,
+    {
+        let index = self.index();
+        let peers = self.peers();
+        (0 .. shards)
+            .filter(|shard| shard % peers == index)
+            .map(open)
+            .collect::<Vec<_>>()
+            .replay_into(self)
+            .exchange(route)
+    }
+}
@@ -0,0 +1,228 @@
+//! Capture and replay of streams between timely computations over TCP.
+//!
+//! Each worker of the producing computation captures its part of a stream as one *shard*,
+//! and sends it to one worker of the consuming computation, which replays all shards sent
+//! to it. The shards carry the producing workers' progress statements, and the consuming
+//! workers hold capabilities on behalf of every shard they expect, including shards that have
+//! not yet connected. The replayed stream's frontier therefore advances only as the frontier
+//! of the captured stream advances, however the two computations are deployed and started.
+//!
+//! Shard `i` of `n` is sent to consuming worker `i % m`, where `m` is the number of consuming
+//! workers. Producing workers connect to the address of their consuming worker, retrying until
+//! it is reachable, and consuming workers accept connections without blocking. Consuming workers
+//! poll for connections and events less often while none arrive, and stop once every shard they
+//! expect has completed.
+//!
+//! # Examples
+//! ```
+//! use std::cell::Cell;
+//! use std::net::TcpListener;
+//! use std::rc::Rc;
+//! use std::sync::{Arc, Mutex};
+//! use timely::dataflow::operators::{ToStream, Inspect, Probe};
+//! use timely::dataflow::operators::capture::tcp::{CaptureTcp, ReplayTcp};
+//!
+//! // Two consuming workers, each listening on its own address.
+//! let listeners = (0 .. 2).map(|_| TcpListener::bind("127.0.0.1:0").unwrap()).collect::<Vec<_>>();
+//! let addresses = listeners.iter().map(|l| l.local_addr().unwrap().to_string()).collect::<Vec<_>>();
+//! let listeners = Arc::new(Mutex::new(listeners.into_iter().map(Some).collect::<Vec<_>>()));
+//!
+//! // Three producing workers, each capturing one shard.
+//! let producer = std::thread::spawn(move || {
+//!     timely::execute(timely::Config::process(3), move |worker| {
+//!         let index = worker.index() as u64;
+//!         worker.dataflow::<u64,_,_>(|scope| {
+//!             (0 .. 10u64).map(move |x| 10 * index + x).to_stream(scope).capture_tcp(&addresses[..]);
+//!         });
+//!     }).unwrap();
+//! });
+//!
+//! let received = timely::execute(timely::Config::process(2), move |worker| {
+//!     let listener = listeners.lock().unwrap()[worker.index()].take().unwrap();
+//!     let received = Rc::new(Cell::new(0));
+//!     let counter = received.clone();
+//!     let probe = worker.dataflow::<u64,_,_>(|scope| {
+//!         scope.replay_tcp::<u64>(listener, 3)
+//!              .inspect(move |_| counter.set(counter.get() + 1))
+//!              .probe()
+//!     });
+//!     worker.step_while(|| !probe.done());
+//!     received.get()
+//! }).unwrap();
+//!
+//! let total: usize = received.join().into_iter().map(|r| r.unwrap()).sum();
+//! assert_eq!(total, 30);
+//! producer.join().unwrap();
+//! ```
+
+use std::io::{Read, Write, ErrorKind};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use abomonation::Abomonation;
+
+use crate::Data;
+use crate::communication::networking::Backoff;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pushers::Counter as PushCounter;
+use crate::dataflow::channels::pushers::buffer::Buffer as PushBuffer;
+use crate::dataflow::operators::generic::builder_raw::OperatorBuilder;
+use crate::progress::Timestamp;
+
+use super::{Capture, Event, EventReader, EventWriter};
+use super::event::EventIterator;
+
+// Sent first on each shard's connection, to reject connections from other services.
+const SHARD_MAGIC: u64 = 0x5a1c_e7d3_08b4_91e6;
+
+// How often a replaying worker polls its connections for further events, backing off from the
+// initial period while the connections are idle.
+const POLL_BACKOFF: Backoff = Backoff {
+    initial: Duration::from_millis(1),
+    maximum: Duration::from_millis(100),
+    attempts: None,
+};
+
+/// Captures a stream and sends it to a consuming timely computation.
+pub trait CaptureTcp<T: Timestamp, D: Data> {
+    /// Sends this worker's shard of the stream to the consuming worker responsible for it.
+    ///
+    /// The consuming workers listen at `addresses`, in order of their worker index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `addresses` is empty, or if the connection cannot be established.
+    fn capture_tcp(&self, addresses: &[String]);
+}
+
+impl<S: Scope, D: Data+Abomonation> CaptureTcp<S::Timestamp, D> for Stream<S, D> where S::Timestamp: Abomonation {
+    fn capture_tcp(&self, addresses: &[String]) {
+        assert!(!addresses.is_empty(), "no addresses supplied to capture_tcp");
+        let shard = self.scope().index();
+        let shards = self.scope().peers();
+        let address = &addresses[shard % addresses.len()];
+
+        let mut delays = Backoff::default().delays();
+        let mut stream = loop {
+            match TcpStream::connect(address) {
+                Ok(stream) => break stream,
+                Err(error) => match delays.next() {
+                    Some(delay) => std::thread::sleep(delay),
+                    None => panic!("failed to connect to {}: {}", address, error),
+                },
+            }
+        };
+        stream.set_nodelay(true).expect("set_nodelay call failed");
+        for word in [SHARD_MAGIC, shard as u64, shards as u64] {
+            stream.write_all(&word.to_le_bytes()).expect("failed to send shard header");
+        }
+
+        self.capture_into(EventWriter::new(stream));
+    }
+}
+
+/// Replays streams sent by the workers of another timely computation.
+pub trait ReplayTcp : Scope {
+    /// Replays the shards this worker is responsible for, of a stream captured in `shards` shards.
+    ///
+    /// The listener should be bound to this worker's entry in the addresses supplied to
+    /// `capture_tcp`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a connection announces a shard this worker is not responsible for, or a
+    /// number of shards other than `shards`.
+    fn replay_tcp<D: Data+Abomonation>(&mut self, listener: TcpListener, shards: usize) -> Stream<Self, D> where Self::Timestamp: Abomonation;
+}
+
+impl<G: Scope> ReplayTcp for G {
+    fn replay_tcp<D: Data+Abomonation>(&mut self, listener: TcpListener, shards: usize) -> Stream<G, D> where G::Timestamp: Abomonation {
+
+        let index = self.index();
+        let peers = self.peers();
+        let expected = (0 .. shards).filter(|shard| shard % peers == index).count();
+        listener.set_nonblocking(true).expect("failed to set listener to non-blocking");
+
+        let mut builder = OperatorBuilder::new("ReplayTcp".to_owned(), self.clone());
+        let address = builder.operator_info().address;
+        let activator = self.activator_for(&address[..]);
+
+        let (targets, stream) = builder.new_output();
+        let mut output = PushBuffer::new(PushCounter::new(targets)).with_length(self.config().batch_length()).with_lineage(self.extensions().lineage());
+        let mut readers: Vec<EventReader<G::Timestamp, D, TcpStream>> = Vec::new();
+        let mut started = false;
+        // Capabilities held on behalf of the shards, and the delays before polling them again.
+        let mut held = expected as i64;
+        let mut delays = POLL_BACKOFF.delays();
+
+        builder.build(move |progress| {
+
+            if !started {
+                // Hold a capability for each expected shard, whether or not it has connected.
+                progress.internals[0].update(G::Timestamp::minimum(), (expected as i64) - 1);
+                started = true;
+            }
+
+            let mut idle = true;
+
+            while readers.len() < expected {
+                match listener.accept() {
+                    Ok((mut stream, _)) => {
+                        idle = false;
+                        // Some platforms propagate the listener's non-blocking mode.
+                        stream.set_nonblocking(false).expect("failed to set stream to blocking");
+                        let mut header = [0u8; 24];
+                        stream.read_exact(&mut header).expect("failed to read shard header");
+                        let word = |i: usize| {
+                            let mut bytes = [0u8; 8];
+                            bytes.copy_from_slice(&header[8 * i .. 8 * (i + 1)]);
+                            u64::from_le_bytes(bytes)
+                        };
+                        if word(0) != SHARD_MAGIC {
+                            continue;
+                        }
+                        let (shard, total) = (word(1) as usize, word(2) as usize);
+                        if total != shards || shard % peers != index {
+                            panic!("worker {} received shard {} of {}, expecting {} shards", index, shard, total, shards);
+                        }
+                        stream.set_nonblocking(true).expect("failed to set stream to non-blocking");
+                        readers.push(EventReader::new(stream));
+                    },
+                    Err(error) if error.kind() == ErrorKind::WouldBlock => break,
+                    Err(error) => panic!("failed to accept shard connection: {}", error),
+                }
+            }
+
+            for reader in readers.iter_mut() {
+                while let Some(event) = reader.next() {
+                    idle = false;
+                    match *event {
+                        Event::Progress(ref vec) => {
+                            held += vec.iter().map(|(_, diff)| diff).sum::<i64>();
+                            progress.internals[0].extend(vec.iter().cloned());
+                        },
+                        Event::Messages(ref time, ref data) => {
+                            output.session(time).give_iterator(data.iter().cloned());
+                        }
+                    }
+                }
+            }
+
+            // Once every shard has connected and released its capabilities, nothing remains to
+            // replay. Until then, poll again, soon after activity and less often while idle.
+            if readers.len() < expected || held > 0 {
+                if !idle {
+                    delays = POLL_BACKOFF.delays();
+                }
+                activator.activate_after(delays.next().expect("polling backs off indefinitely"));
+            }
+
+            output.cease();
+            output.inner().produced().borrow_mut().drain_into(&mut progress.produceds[0]);
+
+            false
+        });
+
+        stream
+    }
+}
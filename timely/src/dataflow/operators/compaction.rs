@@ -0,0 +1,226 @@
+//! Frontiers below which state held outside a dataflow may be compacted.
+//!
+//! Applications often keep state alongside a dataflow, such as caches of its results by time,
+//! and may discard or consolidate the state of times no longer needed. A [`CompactionFrontier`]
+//! reports the least times that may still be needed: times not greater than or equal to some
+//! element of its frontier will never again be needed downstream, and state for them may be
+//! compacted. The frontier is driven by probes on the terminal outputs of the computation, and
+//! advances as their frontiers do, but never regresses. Readers of historical state may take a
+//! [`CompactionHold`], which holds the frontier back until it is advanced or dropped.
+//!
+//! Callbacks registered with [`CompactionFrontier::on_advance`] are invoked with the frontier as
+//! it advances, so that state can be compacted in step with the computation. The handle is shared
+//! by cloning, and so may be held by operators and application code alike.
+//!
+//! # Examples
+//! ```
+//! use std::cell::RefCell;
+//! use std::collections::BTreeMap;
+//! use std::rc::Rc;
+//! use timely::dataflow::{CompactionFrontier, InputHandle};
+//! use timely::dataflow::operators::{Input, Inspect, Probe};
+//!
+//! timely::execute(timely::Config::thread(), |worker| {
+//!     // Counts of records by time, held outside the dataflow.
+//!     let counts = Rc::new(RefCell::new(BTreeMap::new()));
+//!     let mut input = InputHandle::<u64, u64>::new();
+//!     let probe = worker.dataflow(|scope| {
+//!         let counts = counts.clone();
+//!         scope.input_from(&mut input)
+//!              .inspect_time(move |time, _x| *counts.borrow_mut().entry(*time).or_insert(0) += 1)
+//!              .probe()
+//!     });
+//!
+//!     // Discard the counts of times no longer needed, except those a reader holds.
+//!     let mut compaction = CompactionFrontier::new();
+//!     compaction.track(&probe);
+//!     let compacted = counts.clone();
+//!     compaction.on_advance(move |frontier| compacted.borrow_mut().retain(|time, _| frontier.less_equal(time)));
+//!     let hold = compaction.hold();
+//!
+//!     for round in 0 .. 10 {
+//!         input.send(round);
+//!         input.advance_to(round + 1);
+//!         worker.step_while(|| probe.less_than(input.time()));
+//!     }
+//!     assert_eq!(counts.borrow().len(), 10);
+//!
+//!     drop(hold);
+//!     assert_eq!(compaction.frontier().elements(), &[10]);
+//!     assert!(counts.borrow().is_empty());
+//! }).unwrap();
+//! ```
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::Probe;
+use crate::dataflow::operators::probe::Handle;
+use crate::order::PartialOrder;
+use crate::progress::Timestamp;
+use crate::progress::frontier::{Antichain, AntichainRef, MutableAntichain};
+
+/// A shared handle to the frontier below which state held outside a dataflow may be compacted.
+pub struct CompactionFrontier<T: Timestamp> {
+    state: Rc<RefCell<State<T>>>,
+}
+
+struct State<T: Timestamp> {
+    // Probes of the terminal outputs, whose frontiers drive the compaction frontier.
+    handles: Vec<Handle<T>>,
+    // The frontiers held by outstanding `CompactionHold`s.
+    holds: MutableAntichain<T>,
+    frontier: Antichain<T>,
+    callbacks: Callbacks<T>,
+}
+
+// Functions to invoke with the compaction frontier, when it advances.
+type Callbacks<T> = Vec<Box<dyn FnMut(AntichainRef<T>)>>;
+
+impl<T: Timestamp> CompactionFrontier<T> {
+    /// Allocates a compaction frontier at the minimum time, driven by no probes.
+    ///
+    /// The frontier does not advance until some stream or probe is tracked.
+    pub fn new() -> Self {
+        CompactionFrontier {
+            state: Rc::new(RefCell::new(State {
+                handles: Vec::new(),
+                holds: MutableAntichain::new(),
+                frontier: Antichain::from_elem(T::minimum()),
+                callbacks: Vec::new(),
+            }))
+        }
+    }
+
+    /// Probes `stream`, a terminal output of the computation, whose frontier then drives the
+    /// compaction frontier.
+    pub fn probe<G: Scope<Timestamp=T>, D: Data>(&mut self, stream: &Stream<G, D>) -> Stream<G, D> {
+        let mut handle = Handle::new();
+        let result = stream.probe_with(&mut handle);
+        self.track(&handle);
+        result
+    }
+
+    /// Tracks `handle`, the probe of a terminal output, whose frontier then drives the compaction
+    /// frontier.
+    ///
+    /// The compaction frontier never regresses, so that the probed stream should not produce
+    /// records at times the compaction frontier has already passed.
+    pub fn track(&mut self, handle: &Handle<T>) {
+        // A weak reference breaks the cycle through the handle's own callbacks.
+        let state = Rc::downgrade(&self.state);
+        handle.on_frontier_change(move |_| refresh(&state));
+        self.state.borrow_mut().handles.push(handle.clone());
+        refresh(&Rc::downgrade(&self.state));
+    }
+
+    /// The frontier below which state may be compacted.
+    pub fn frontier(&self) -> Antichain<T> {
+        self.state.borrow().frontier.clone()
+    }
+
+    /// Returns true iff the frontier is less than or equal to `time`, which may then be needed.
+    pub fn less_equal(&self, time: &T) -> bool {
+        self.state.borrow().frontier.less_equal(time)
+    }
+
+    /// Registers `callback` to be invoked with the frontier each time it advances.
+    ///
+    /// Callbacks are invoked as probed streams advance while the worker steps, and as holds are
+    /// advanced or dropped, and so should not block.
+    pub fn on_advance<F: FnMut(AntichainRef<T>)+'static>(&self, callback: F) {
+        self.state.borrow_mut().callbacks.push(Box::new(callback));
+    }
+
+    /// Holds the frontier at its current value, until the hold is advanced or dropped.
+    pub fn hold(&self) -> CompactionHold<T> {
+        let held = self.frontier();
+        self.state.borrow_mut().holds.update_iter(held.iter().map(|time| (time.clone(), 1)));
+        CompactionHold { state: Rc::downgrade(&self.state), held }
+    }
+}
+
+impl<T: Timestamp> Clone for CompactionFrontier<T> {
+    fn clone(&self) -> Self {
+        CompactionFrontier { state: self.state.clone() }
+    }
+}
+
+impl<T: Timestamp> Default for CompactionFrontier<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Holds a [`CompactionFrontier`] at or before a frontier, so that state at the times it permits
+/// is retained.
+pub struct CompactionHold<T: Timestamp> {
+    state: Weak<RefCell<State<T>>>,
+    held: Antichain<T>,
+}
+
+impl<T: Timestamp> CompactionHold<T> {
+    /// The frontier held.
+    pub fn frontier(&self) -> AntichainRef<'_, T> {
+        self.held.borrow()
+    }
+
+    /// Advances the held frontier to `frontier`, allowing the compaction frontier to advance.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frontier` is not greater than or equal to the held frontier.
+    pub fn advance_to(&mut self, frontier: AntichainRef<T>) {
+        assert!(PartialOrder::less_equal(&self.held.borrow(), &frontier), "compaction hold may not regress");
+        let advanced = frontier.to_owned();
+        if let Some(state) = self.state.upgrade() {
+            let changes = advanced.iter().map(|time| (time.clone(), 1)).chain(self.held.iter().map(|time| (time.clone(), -1)));
+            state.borrow_mut().holds.update_iter(changes);
+        }
+        self.held = advanced;
+        refresh(&self.state);
+    }
+}
+
+impl<T: Timestamp> Drop for CompactionHold<T> {
+    fn drop(&mut self) {
+        self.advance_to(Antichain::new().borrow());
+    }
+}
+
+// Advances the compaction frontier to the least of the probed and held frontiers, if that does
+// not regress it, and invokes callbacks if it advanced.
+fn refresh<T: Timestamp>(state: &Weak<RefCell<State<T>>>) {
+    let state = match state.upgrade() {
+        Some(state) => state,
+        None => return,
+    };
+    let mut callbacks = {
+        let mut borrow = state.borrow_mut();
+        if borrow.handles.is_empty() {
+            return;
+        }
+        let mut frontier = Antichain::new();
+        for handle in borrow.handles.iter() {
+            handle.with_frontier(|f| for time in f.iter() { frontier.insert(time.clone()); });
+        }
+        for time in borrow.holds.frontier().iter() {
+            frontier.insert(time.clone());
+        }
+        if frontier == borrow.frontier || !PartialOrder::less_equal(&borrow.frontier, &frontier) {
+            return;
+        }
+        borrow.frontier = frontier;
+        // callbacks may register further callbacks, which are retained after these.
+        ::std::mem::take(&mut borrow.callbacks)
+    };
+    let frontier = state.borrow().frontier.clone();
+    for callback in callbacks.iter_mut() {
+        callback(frontier.borrow());
+    }
+    let mut borrow = state.borrow_mut();
+    callbacks.append(&mut borrow.callbacks);
+    borrow.callbacks = callbacks;
+}
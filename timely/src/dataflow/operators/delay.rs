@@ -1,12 +1,17 @@
 //! Operators acting on timestamps to logically delay records
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::Data;
+use crate::{Data, ExchangeData};
 use crate::order::{PartialOrder, TotalOrder};
+use crate::progress::Timestamp;
+use crate::progress::frontier::AntichainRef;
 use crate::dataflow::channels::pact::Pipeline;
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::dataflow::operators::epoch_buffer::EpochBuffer;
+use crate::dataflow::operators::windows::WindowTime;
 
 /// Methods to advance the timestamps of records or batches of records.
 pub trait Delay<G: Scope, D: Data> {
@@ -38,6 +43,30 @@ pub trait Delay<G: Scope, D: Data> {
     /// ```
     fn delay<L: FnMut(&D, &G::Timestamp)->G::Timestamp+'static>(&self, func: L) -> Self;
 
+    /// Advances the timestamp of records using a supplied function, spilling the records held
+    /// to disk as configured by [`Config::spill_to_disk`](crate::worker::Config::spill_to_disk).
+    ///
+    /// Without a configuration this behaves as `delay`. See the
+    /// [`epoch_buffer`](crate::dataflow::operators::epoch_buffer) module for how records spill.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Inspect};
+    /// use timely::dataflow::operators::epoch_buffer::SpillConfig;
+    ///
+    /// let mut config = timely::Config::thread();
+    /// config.worker = config.worker.spill_to_disk(SpillConfig::new(4096, std::env::temp_dir()));
+    /// timely::execute(config, |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10_000u64).to_stream(scope)
+    ///                       .delay_spilling(|data, _time| *data % 10)
+    ///                       .inspect_batch(|time, data| assert!(data.iter().all(|x| x % 10 == *time)));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    fn delay_spilling<L: FnMut(&D, &G::Timestamp)->G::Timestamp+'static>(&self, func: L) -> Self
+    where D: ExchangeData;
+
     /// Advances the timestamp of records using a supplied function.
     ///
     /// This method is a specialization of `delay` for when the timestamp is totally
@@ -92,6 +121,98 @@ pub trait Delay<G: Scope, D: Data> {
     /// });
     /// ```
     fn delay_batch<L: FnMut(&G::Timestamp)->G::Timestamp+'static>(&self, func: L) -> Self;
+
+    /// Advances the timestamp of records using a supplied function, preserving their order.
+    ///
+    /// Records delayed to the same time are emitted in order of their original times, and
+    /// records with the same original time in the order they were received. The relative order
+    /// of records from different workers is not determined by this method.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use timely::dataflow::operators::{ToStream, Delay, Inspect};
+    ///
+    /// let seen = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = seen.clone();
+    ///
+    /// timely::example(move |scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .delay(|data, _time| 9 - *data)
+    ///            .delay_ordered(|_data, _time| 10)
+    ///            .inspect_time(move |time, data| sink.lock().unwrap().push((*time, *data)));
+    /// });
+    ///
+    /// let expected = (0..10).rev().map(|x| (10, x)).collect::<Vec<_>>();
+    /// assert_eq!(*seen.lock().unwrap(), expected);
+    /// ```
+    fn delay_ordered<L: FnMut(&D, &G::Timestamp)->G::Timestamp+'static>(&self, func: L) -> Self;
+
+    /// Advances the timestamp of batches of records using a supplied function, which may observe
+    /// the input frontier.
+    ///
+    /// Each batch is released once the input frontier passes its new time. The function *must*
+    /// advance the timestamp, as with `delay_batch`.
+    ///
+    /// # Examples
+    ///
+    /// The following example delays each batch to the time of the current input frontier, if it
+    /// is later than the batch's time.
+    ///
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0..4).to_stream(scope)
+    ///           .delay(|data, _time| *data)
+    ///           .delay_batch_with_frontier(|time, frontier| {
+    ///               frontier.iter().cloned().max().map(|f| f.max(*time)).unwrap_or(*time)
+    ///           })
+    ///           .capture()
+    /// });
+    ///
+    /// let records = captured.extract().into_iter().flat_map(|(_, data)| data).collect::<Vec<_>>();
+    /// assert_eq!(records, vec![0, 1, 2, 3]);
+    /// ```
+    fn delay_batch_with_frontier<L>(&self, func: L) -> Self
+    where
+        L: FnMut(&G::Timestamp, AntichainRef<G::Timestamp>)->G::Timestamp+'static;
+
+    /// Moves the timestamp of records to times supplied by a function, which may precede their
+    /// times by up to `allowance`, and separates records whose times precede theirs by more.
+    ///
+    /// Records are usually moved to their event times, which may lag the times at which they
+    /// arrive. The first stream holds each record at its new time, and lags the input frontier by
+    /// `allowance`. The second stream holds the late records, whose new times precede their times
+    /// by more than `allowance`, unchanged and at their original times, so that they may be
+    /// audited or reprocessed rather than dropped.
+    ///
+    /// # Examples
+    ///
+    /// The following example moves records to the times they carry, allowing them to arrive up
+    /// to two times late.
+    ///
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let (on_time, late) = timely::example(|scope| {
+    ///     let (on_time, late) = vec![(0, 'a'), (5, 'b'), (3, 'c'), (1, 'd')]
+    ///         .to_stream(scope)
+    ///         .delay(|x, _time| [0, 5, 5, 5][x.0 as usize % 4])
+    ///         .delay_with_late(2, |x, _time| x.0);
+    ///     (on_time.capture(), late.capture())
+    /// });
+    ///
+    /// assert_eq!(on_time.extract(), vec![(0, vec![(0, 'a')]), (3, vec![(3, 'c')]), (5, vec![(5, 'b')])]);
+    /// assert_eq!(late.extract(), vec![(5, vec![(1, 'd')])]);
+    /// ```
+    fn delay_with_late<L>(&self, allowance: G::Timestamp, func: L) -> (Stream<G, D>, Stream<G, D>)
+    where
+        G::Timestamp: WindowTime,
+        L: FnMut(&D, &G::Timestamp)->G::Timestamp+'static;
 }
 
 impl<G: Scope, D: Data> Delay<G, D> for Stream<G, D> {
@@ -119,6 +240,41 @@ impl<G: Scope, D: Data> Delay<G, D> for Stream<G, D> {
         })
     }
 
+    fn delay_spilling<L: FnMut(&D, &G::Timestamp)->G::Timestamp+'static>(&self, mut func: L) -> Self
+    where D: ExchangeData
+    {
+        let mut buffer = EpochBuffer::with_config(self.scope().config().spill());
+        let mut pending = HashSet::new();
+        let mut staged = HashMap::new();
+        let mut vector = Vec::new();
+        self.unary_notify(Pipeline, "DelaySpilling", vec![], move |input, output, notificator| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                // Records are grouped by their new times, and each group is buffered at once.
+                for datum in vector.drain(..) {
+                    let new_time = func(&datum, &time);
+                    assert!(time.time().less_equal(&new_time));
+                    staged.entry(new_time).or_insert_with(Vec::new).push(datum);
+                }
+                for (new_time, mut delayed) in staged.drain() {
+                    if pending.insert(new_time.clone()) {
+                        notificator.notify_at(time.delayed(&new_time));
+                    }
+                    buffer.push(new_time, &mut delayed).expect("failed to spill delayed records");
+                }
+            });
+
+            // for each available notification, send corresponding set
+            notificator.for_each(|time,_,_| {
+                pending.remove(time.time());
+                let mut data = buffer.take(time.time()).expect("failed to read spilled records");
+                if !data.is_empty() {
+                    output.session(&time).give_vec(&mut data);
+                }
+            });
+        })
+    }
+
     fn delay_total<L: FnMut(&D, &G::Timestamp)->G::Timestamp+'static>(&self, func: L) -> Self
     where G::Timestamp: TotalOrder
     {
@@ -146,4 +302,109 @@ impl<G: Scope, D: Data> Delay<G, D> for Stream<G, D> {
             });
         })
     }
+
+    fn delay_ordered<L: FnMut(&D, &G::Timestamp)->G::Timestamp+'static>(&self, mut func: L) -> Self {
+        let mut elements = HashMap::new();
+        let mut vector = Vec::new();
+        self.unary_notify(Pipeline, "DelayOrdered", vec![], move |input, output, notificator| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                for datum in vector.drain(..) {
+                    let new_time = func(&datum, &time);
+                    assert!(time.time().less_equal(&new_time));
+                    elements.entry(new_time.clone())
+                            .or_insert_with(|| { notificator.notify_at(time.delayed(&new_time)); Vec::new() })
+                            .push((time.time().clone(), datum));
+                }
+            });
+
+            notificator.for_each(|time,_,_| {
+                if let Some(mut data) = elements.remove(&time) {
+                    // a stable sort retains the order of records with the same original time.
+                    data.sort_by(|x, y| x.0.cmp(&y.0));
+                    output.session(&time).give_iterator(data.drain(..).map(|(_, datum)| datum));
+                }
+            });
+        })
+    }
+
+    fn delay_batch_with_frontier<L>(&self, mut func: L) -> Self
+    where
+        L: FnMut(&G::Timestamp, AntichainRef<G::Timestamp>)->G::Timestamp+'static
+    {
+        let mut elements = HashMap::new();
+        self.unary_frontier(Pipeline, "DelayBatchWithFrontier", move |_,_| move |input, output| {
+            let frontier = input.frontier();
+            input.for_each(|time, data| {
+                let new_time = func(&time, frontier.frontier());
+                assert!(time.time().less_equal(&new_time));
+                elements.entry(new_time.clone())
+                        .or_insert_with(|| (time.delayed(&new_time), Vec::new()))
+                        .1
+                        .push(data.replace(Vec::new()));
+            });
+
+            let ready = elements.keys().filter(|t| !frontier.less_equal(t)).cloned().collect::<Vec<_>>();
+            for time in ready {
+                let (cap, mut datas) = elements.remove(&time).unwrap();
+                let mut session = output.session(&cap);
+                for mut data in datas.drain(..) {
+                    session.give_vec(&mut data);
+                }
+            }
+        })
+    }
+
+    fn delay_with_late<L>(&self, allowance: G::Timestamp, mut func: L) -> (Stream<G, D>, Stream<G, D>)
+    where
+        G::Timestamp: WindowTime,
+        L: FnMut(&D, &G::Timestamp)->G::Timestamp+'static
+    {
+        let mut builder = OperatorBuilder::new("DelayWithLate".to_owned(), self.scope());
+        let mut input = builder.new_input(self, Pipeline);
+        let (mut on_time, on_time_stream) = builder.new_output();
+        let (mut late, late_stream) = builder.new_output();
+
+        builder.build(move |mut capabilities| {
+            // A capability for the input frontier less `allowance`, the least time of on-time records.
+            capabilities.truncate(1);
+            let mut watermark = capabilities.pop();
+            let mut vector = Vec::new();
+            move |frontiers| {
+                let mut on_time_handle = on_time.activate();
+                let mut late_handle = late.activate();
+                input.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    for datum in vector.drain(..) {
+                        let new_time = func(&datum, &time);
+                        if time.time().less_equal(&new_time) {
+                            on_time_handle.session(&time.delayed(&new_time)).give(datum);
+                        }
+                        else if time.time().checked_sub(allowance).map(|least| least <= new_time).unwrap_or(true) {
+                            let watermark = watermark.as_ref().expect("watermark released before input frontier");
+                            on_time_handle.session(&watermark.delayed(&new_time)).give(datum);
+                        }
+                        else {
+                            late_handle.session(&time).give(datum);
+                        }
+                    }
+                });
+
+                match frontiers[0].frontier().first() {
+                    Some(frontier) => {
+                        let least = frontier.checked_sub(allowance).unwrap_or_else(G::Timestamp::minimum);
+                        if let Some(watermark) = watermark.as_mut() {
+                            if watermark.time() < &least {
+                                watermark.downgrade(&least);
+                            }
+                        }
+                    },
+                    None => { watermark = None; },
+                }
+            }
+        });
+
+        (on_time_stream, late_stream)
+    }
 }
+
@@ -0,0 +1,142 @@
+//! Counting, deduplicating, and ranking records within each timestamp.
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{Exchange, ParallelizationContract, Pipeline};
+use crate::dataflow::operators::Map;
+use crate::dataflow::operators::aggregation::Aggregate;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Counts records by key within each timestamp.
+pub trait CountBy<G: Scope, D: Data> {
+    /// Counts the records at each time by the key `key` extracts, producing `(key, count)` once
+    /// the time is complete.
+    ///
+    /// Keys are exchanged by the worker's exchange hasher, so that each key is counted by one worker.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::distinct::CountBy;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .count_by(|x| x % 3)
+    ///            .capture()
+    /// });
+    ///
+    /// let mut counts = captured.extract()[0].1.clone();
+    /// counts.sort();
+    /// assert_eq!(counts, vec![(0, 4), (1, 3), (2, 3)]);
+    /// ```
+    fn count_by<K: ExchangeData+Hash+Eq, F: Fn(&D)->K+'static>(&self, key: F) -> Stream<G, (K, usize)>;
+}
+
+impl<G: Scope, D: Data> CountBy<G, D> for Stream<G, D> {
+    fn count_by<K: ExchangeData+Hash+Eq, F: Fn(&D)->K+'static>(&self, key: F) -> Stream<G, (K, usize)> {
+        let hasher = self.scope().config().hasher().clone();
+        self.map(move |x| (key(&x), 1usize))
+            .aggregate(|_key, val, count| *count += val, |key, count| (key, count), move |key| hasher.hash(key))
+    }
+}
+
+/// Removes duplicate records within each timestamp.
+pub trait Distinct<G: Scope, D: ExchangeData+Hash+Eq> {
+    /// Produces each distinct record at each time once, when the time is complete.
+    ///
+    /// Records are exchanged by the worker's exchange hasher, so that equal records meet at one worker.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::distinct::Distinct;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![1, 2, 1, 3, 2].to_stream(scope)
+    ///                        .distinct()
+    ///                        .capture()
+    /// });
+    ///
+    /// let mut records = captured.extract()[0].1.clone();
+    /// records.sort();
+    /// assert_eq!(records, vec![1, 2, 3]);
+    /// ```
+    fn distinct(&self) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: ExchangeData+Hash+Eq> Distinct<G, D> for Stream<G, D> {
+    fn distinct(&self) -> Stream<G, D> {
+        let hasher = self.scope().config().hasher().clone();
+        self.map(|x| (x, ()))
+            .aggregate(|_key, _val, _agg: &mut ()| { }, |key, _agg| key, move |key| hasher.hash(key))
+    }
+}
+
+/// Selects the first records of each timestamp in some order.
+pub trait TopK<G: Scope, D: ExchangeData> {
+    /// Produces the `k` least records at each time under `cmp`, once the time is complete.
+    ///
+    /// Each worker first selects its `k` least records, which are then sent to worker zero to
+    /// select the `k` least overall. To select the greatest records, reverse the comparison.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::distinct::TopK;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![5, 3, 9, 1, 7].to_stream(scope)
+    ///                        .topk(3, |x, y| y.cmp(x))
+    ///                        .capture()
+    /// });
+    ///
+    /// // `extract` sorts the records of each time.
+    /// assert_eq!(captured.extract(), vec![(0, vec![5, 7, 9])]);
+    /// ```
+    fn topk<F: Fn(&D, &D)->Ordering+Clone+'static>(&self, k: usize, cmp: F) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: ExchangeData> TopK<G, D> for Stream<G, D> {
+    fn topk<F: Fn(&D, &D)->Ordering+Clone+'static>(&self, k: usize, cmp: F) -> Stream<G, D> {
+        let local = select_least(self, Pipeline, "TopKLocal", k, cmp.clone());
+        select_least(&local, Exchange::new(|_| 0), "TopK", k, cmp)
+    }
+}
+
+// Retains the `k` least records of each time under `cmp`, and produces them when the time is complete.
+fn select_least<G, D, P, F>(stream: &Stream<G, D>, pact: P, name: &str, k: usize, cmp: F) -> Stream<G, D>
+where
+    G: Scope,
+    D: Data,
+    P: ParallelizationContract<G::Timestamp, D>,
+    F: Fn(&D, &D)->Ordering+'static,
+{
+    let mut stash = HashMap::new();
+    let mut vector = Vec::new();
+    stream.unary_notify(pact, name, vec![], move |input, output, notificator| {
+        input.for_each(|time, data| {
+            data.swap(&mut vector);
+            let least = stash.entry(time.time().clone()).or_insert_with(Vec::new);
+            least.append(&mut vector);
+            // Compact once the candidates are twice the size of the result.
+            if least.len() > 2 * k {
+                least.sort_by(|x, y| cmp(x, y));
+                least.truncate(k);
+            }
+            notificator.notify_at(time.retain());
+        });
+        notificator.for_each(|time, _, _| {
+            if let Some(mut least) = stash.remove(time.time()) {
+                least.sort_by(|x, y| cmp(x, y));
+                least.truncate(k);
+                output.session(&time).give_vec(&mut least);
+            }
+        });
+    })
+}
@@ -28,7 +28,7 @@ use crate::order::Product;
 use crate::Data;
 use crate::communication::Push;
 use crate::dataflow::channels::pushers::{Counter, Tee};
-use crate::dataflow::channels::{Bundle, Message};
+use crate::dataflow::channels::{Bundle, Message, lineage};
 
 use crate::worker::AsWorker;
 use crate::dataflow::{Stream, Scope};
@@ -143,9 +143,12 @@ struct IngressNub<TOuter: Timestamp, TInner: Timestamp+Refines<TOuter>, TData: D
 impl<TOuter: Timestamp, TInner: Timestamp+Refines<TOuter>, TData: Data> Push<Bundle<TOuter, TData>> for IngressNub<TOuter, TInner, TData> {
     fn push(&mut self, message: &mut Option<Bundle<TOuter, TData>>) {
         if let Some(message) = message {
+            let traced = lineage::of(message);
             let outer_message = message.as_mut();
             let data = ::std::mem::replace(&mut outer_message.data, Vec::new());
-            let mut inner_message = Some(Bundle::from_typed(Message::new(TInner::to_inner(outer_message.time.clone()), data, 0, 0)));
+            let mut inner_message = Bundle::from_typed(Message::new(TInner::to_inner(outer_message.time.clone()), data, 0, 0));
+            lineage::annotate(&mut inner_message, traced);
+            let mut inner_message = Some(inner_message);
             self.targets.push(&mut inner_message);
             if let Some(inner_message) = inner_message {
                 if let Some(inner_message) = inner_message.if_typed() {
@@ -167,9 +170,12 @@ impl<TOuter, TInner, TData> Push<Bundle<TInner, TData>> for EgressNub<TOuter, TI
 where TOuter: Timestamp, TInner: Timestamp+Refines<TOuter>, TData: Data {
     fn push(&mut self, message: &mut Option<Bundle<TInner, TData>>) {
         if let Some(message) = message {
+            let traced = lineage::of(message);
             let inner_message = message.as_mut();
             let data = ::std::mem::replace(&mut inner_message.data, Vec::new());
-            let mut outer_message = Some(Bundle::from_typed(Message::new(inner_message.time.clone().to_outer(), data, 0, 0)));
+            let mut outer_message = Bundle::from_typed(Message::new(inner_message.time.clone().to_outer(), data, 0, 0));
+            lineage::annotate(&mut outer_message, traced);
+            let mut outer_message = Some(outer_message);
             self.targets.push(&mut outer_message);
             if let Some(outer_message) = outer_message {
                 if let Some(outer_message) = outer_message.if_typed() {
@@ -0,0 +1,210 @@
+//! Buffering of records by time, spilling to disk past a memory threshold.
+//!
+//! Operators that hold the records of each time until their input frontier passes the time may
+//! hold a great many records. An [`EpochBuffer`] holds them in memory until their approximate
+//! size exceeds a threshold, and then writes the records of the latest times, which will be
+//! released last, to temporary files. Taking the records of a time reads back any it spilled.
+//!
+//! Spilling is configured for a worker's stock operators with
+//! [`Config::spill_to_disk`](crate::worker::Config::spill_to_disk), and applies to `delay_spilling`
+//! and `aggregate`, whose records can be serialized. Sizes are estimated as the in-memory size of
+//! each record, excluding any memory the record owns elsewhere.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::operators::epoch_buffer::{EpochBuffer, SpillConfig};
+//!
+//! let directory = std::env::temp_dir();
+//! let mut buffer = EpochBuffer::spilling(SpillConfig::new(1024, directory));
+//! for time in 0 .. 10u64 {
+//!     buffer.push(time, &mut (0 .. 100u64).collect()).expect("failed to spill");
+//! }
+//! assert!(buffer.spilled() > 0);
+//! assert_eq!(buffer.times(), (0 .. 10).collect::<Vec<_>>());
+//! assert_eq!(buffer.take(&9).expect("failed to read back"), (0 .. 100).collect::<Vec<_>>());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Result, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::ExchangeData;
+use crate::bytes::arc::Bytes;
+use crate::communication::Message;
+use crate::communication::codec::{Codec, Native};
+
+/// Where, and past what size, an `EpochBuffer` spills records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpillConfig {
+    /// The approximate number of bytes of records held in memory before spilling.
+    pub threshold: usize,
+    /// The directory in which spilled records are written.
+    pub directory: PathBuf,
+}
+
+impl SpillConfig {
+    /// Spills records past `threshold` bytes to files in `directory`.
+    pub fn new<P: Into<PathBuf>>(threshold: usize, directory: P) -> Self {
+        SpillConfig { threshold, directory: directory.into() }
+    }
+}
+
+// Distinguishes the files of buffers in the same process.
+static NEXT_BUFFER: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes records to, and reads them back from, files.
+struct Spiller<D> {
+    config: SpillConfig,
+    prefix: String,
+    encode: fn(Vec<D>, &mut Vec<u8>),
+    decode: fn(Bytes) -> Vec<D>,
+}
+
+fn encode<D: ExchangeData>(data: Vec<D>, bytes: &mut Vec<u8>) {
+    let message = Message::from_typed(data);
+    bytes.reserve(<Native as Codec<Vec<D>>>::length_in_bytes(&message));
+    <Native as Codec<Vec<D>>>::into_bytes(&message, bytes);
+}
+
+fn decode<D: ExchangeData>(bytes: Bytes) -> Vec<D> {
+    let message: Message<Vec<D>> = <Native as Codec<Vec<D>>>::from_bytes(bytes);
+    message.into_typed()
+}
+
+/// Records held by time, some of which may be spilled to disk.
+pub struct EpochBuffer<T: Ord, D> {
+    memory: BTreeMap<T, Vec<D>>,
+    bytes: usize,
+    // The file, and number of records, spilled for each time.
+    files: BTreeMap<T, (PathBuf, usize)>,
+    next_file: usize,
+    spiller: Option<Spiller<D>>,
+}
+
+impl<T: Ord+Clone, D> EpochBuffer<T, D> {
+    /// Allocates a buffer that holds all records in memory.
+    pub fn new() -> Self {
+        EpochBuffer {
+            memory: BTreeMap::new(),
+            bytes: 0,
+            files: BTreeMap::new(),
+            next_file: 0,
+            spiller: None,
+        }
+    }
+
+    /// Allocates a buffer that spills records as described by `config`.
+    pub fn spilling(config: SpillConfig) -> Self where D: ExchangeData {
+        let prefix = format!("timely-epochs-{}-{}", std::process::id(), NEXT_BUFFER.fetch_add(1, Ordering::Relaxed));
+        let mut buffer = Self::new();
+        buffer.spiller = Some(Spiller { config, prefix, encode: encode::<D>, decode: decode::<D> });
+        buffer
+    }
+
+    /// Allocates a buffer that spills records as described by `config`, if any.
+    pub fn with_config(config: Option<SpillConfig>) -> Self where D: ExchangeData {
+        match config {
+            Some(config) => Self::spilling(config),
+            None => Self::new(),
+        }
+    }
+
+    /// Adds the records of `data` at `time`, leaving `data` empty.
+    ///
+    /// This spills records of the latest times if the records held in memory exceed the threshold.
+    pub fn push(&mut self, time: T, data: &mut Vec<D>) -> Result<()> {
+        self.bytes += data.len() * std::mem::size_of::<D>();
+        self.memory.entry(time).or_default().append(data);
+        let threshold = self.spiller.as_ref().map(|s| s.config.threshold);
+        if let Some(threshold) = threshold {
+            while self.bytes > threshold {
+                let time = self.memory.keys().next_back().cloned().expect("bytes held without records");
+                self.spill(time)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the records of `time` held in memory to its file.
+    fn spill(&mut self, time: T) -> Result<()> {
+        let data = self.memory.remove(&time).unwrap_or_default();
+        self.bytes -= data.len() * std::mem::size_of::<D>();
+        let spiller = self.spiller.as_ref().expect("spilling without a configuration");
+        let count = data.len();
+        let mut bytes = Vec::new();
+        (spiller.encode)(data, &mut bytes);
+        if !self.files.contains_key(&time) {
+            let path = spiller.config.directory.join(format!("{}-{}", spiller.prefix, self.next_file));
+            self.next_file += 1;
+            self.files.insert(time.clone(), (path, 0));
+        }
+        let (path, records) = self.files.get_mut(&time).unwrap();
+        let mut file = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+        file.write_all(&(bytes.len() as u64).to_le_bytes())?;
+        file.write_all(&bytes)?;
+        file.flush()?;
+        *records += count;
+        Ok(())
+    }
+
+    /// Removes and returns the records at `time`, reading back any that were spilled.
+    pub fn take(&mut self, time: &T) -> Result<Vec<D>> {
+        let mut result = Vec::new();
+        if let Some((path, records)) = self.files.remove(time) {
+            let spiller = self.spiller.as_ref().expect("spilled without a configuration");
+            result.reserve(records);
+            let mut file = BufReader::new(File::open(&path)?);
+            let mut length = [0u8; 8];
+            loop {
+                match file.read_exact(&mut length) {
+                    Ok(()) => { },
+                    Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                    Err(error) => return Err(error),
+                }
+                let mut bytes = vec![0u8; u64::from_le_bytes(length) as usize];
+                file.read_exact(&mut bytes)?;
+                result.extend((spiller.decode)(Bytes::from(bytes)));
+            }
+            fs::remove_file(&path)?;
+        }
+        if let Some(mut data) = self.memory.remove(time) {
+            self.bytes -= data.len() * std::mem::size_of::<D>();
+            result.append(&mut data);
+        }
+        Ok(result)
+    }
+
+    /// The times with records, in order.
+    pub fn times(&self) -> Vec<T> {
+        let mut times = self.memory.keys().chain(self.files.keys()).cloned().collect::<Vec<_>>();
+        times.sort();
+        times.dedup();
+        times
+    }
+
+    /// Returns `true` if the buffer holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.memory.is_empty() && self.files.is_empty()
+    }
+
+    /// The number of records spilled to disk.
+    pub fn spilled(&self) -> usize {
+        self.files.values().map(|(_, records)| records).sum()
+    }
+}
+
+impl<T: Ord+Clone, D> Default for EpochBuffer<T, D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord, D> Drop for EpochBuffer<T, D> {
+    fn drop(&mut self) {
+        for (path, _) in self.files.values() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
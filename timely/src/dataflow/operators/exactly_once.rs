@@ -0,0 +1,113 @@
+//! Delivery of a stream to an external system, committing each timestamp exactly once.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::Data;
+use crate::progress::{Antichain, frontier::AntichainRef};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+
+/// Extension trait for committing a stream to an external system.
+pub trait SinkExactlyOnce<G: Scope, D: Data> {
+    /// Commits the records of each timestamp once the input frontier has passed it.
+    ///
+    /// Records are buffered by timestamp until the input frontier passes their time, at which
+    /// point `commit` is called with the complete times and their records, in time order, and
+    /// with the frontier through which the external system will then be complete. The frontier
+    /// should be recorded in the same transaction as the records. If `commit` returns an error,
+    /// nothing is considered committed and the same times are offered again, with any that have
+    /// since completed, after a short delay.
+    ///
+    /// The `committed` argument should be the frontier most recently recorded by `commit` for
+    /// this worker, or the minimal frontier if nothing has been committed. Records at times this
+    /// frontier has passed are discarded, so that a computation replaying its input after a
+    /// failure does not commit them a second time.
+    ///
+    /// Each worker commits the records it receives, and the external system should track the
+    /// committed frontier of each worker separately.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use timely::dataflow::operators::{ToStream, Delay};
+    /// use timely::dataflow::operators::exactly_once::SinkExactlyOnce;
+    /// use timely::progress::Antichain;
+    ///
+    /// let committed = Arc::new(Mutex::new(Vec::new()));
+    /// let external = committed.clone();
+    ///
+    /// timely::example(move |scope| {
+    ///     // Times before 3 were committed by a previous execution.
+    ///     (0 .. 6u64)
+    ///         .to_stream(scope)
+    ///         .delay(|x, _| *x)
+    ///         .sink_exactly_once(Antichain::from_elem(3), move |batch, frontier| {
+    ///             let mut external = external.lock().unwrap();
+    ///             for (time, records) in batch.iter() {
+    ///                 external.push((*time, records.clone(), frontier.to_vec()));
+    ///             }
+    ///             Ok::<(), ()>(())
+    ///         });
+    /// });
+    ///
+    /// let committed = committed.lock().unwrap();
+    /// assert_eq!(committed.iter().map(|x| x.0).collect::<Vec<_>>(), vec![3, 4, 5]);
+    /// assert_eq!(committed[2].2, Vec::<u64>::new());
+    /// ```
+    fn sink_exactly_once<E, F>(&self, committed: Antichain<G::Timestamp>, commit: F)
+    where
+        F: FnMut(&[(G::Timestamp, Vec<D>)], AntichainRef<G::Timestamp>)->Result<(), E>+'static;
+}
+
+impl<G: Scope, D: Data> SinkExactlyOnce<G, D> for Stream<G, D> {
+    fn sink_exactly_once<E, F>(&self, committed: Antichain<G::Timestamp>, mut commit: F)
+    where
+        F: FnMut(&[(G::Timestamp, Vec<D>)], AntichainRef<G::Timestamp>)->Result<(), E>+'static
+    {
+        let mut builder = OperatorBuilder::new("SinkExactlyOnce".to_owned(), self.scope());
+        let mut input = builder.new_input(self, Pipeline);
+        let activator = self.scope().activator_for(&builder.operator_info().address[..]);
+
+        builder.build_reschedule(move |_capabilities| {
+
+            let mut committed = committed;
+            let mut buffered = HashMap::new();
+            let mut ready = Vec::new();
+
+            move |frontiers| {
+
+                input.for_each(|time, data| {
+                    // Records at committed times are replayed, and must not be committed again.
+                    if committed.less_equal(time.time()) {
+                        buffered
+                            .entry(time.time().clone())
+                            .or_insert_with(Vec::new)
+                            .extend(data.replace(Vec::new()));
+                    }
+                });
+
+                let frontier = &frontiers[0];
+                if committed.borrow() != frontier.frontier() && frontier.frontier().iter().all(|t| committed.less_equal(t)) {
+                    let complete = buffered.keys().filter(|t| !frontier.less_equal(t)).cloned().collect::<Vec<_>>();
+                    for time in complete {
+                        let records = buffered.remove(&time).unwrap();
+                        ready.push((time, records));
+                    }
+                    ready.sort_by(|x: &(G::Timestamp, Vec<D>), y| x.0.cmp(&y.0));
+
+                    if commit(&ready[..], frontier.frontier()).is_ok() {
+                        ready.clear();
+                        committed = frontier.frontier().to_owned();
+                    }
+                    else {
+                        activator.activate_after(Duration::from_millis(100));
+                    }
+                }
+
+                !ready.is_empty()
+            }
+        });
+    }
+}
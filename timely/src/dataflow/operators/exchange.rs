@@ -1,12 +1,15 @@
 //! Exchange records between workers.
 
-use crate::ExchangeData;
-use crate::dataflow::channels::pact::Exchange as ExchangePact;
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use crate::{Data, ExchangeData, SerdeData};
+use crate::communication::codec::Bincode;
+use crate::dataflow::channels::pact::{Exchange as ExchangePact, OneToOne, Steal as StealPact};
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::operators::generic::operator::Operator;
 
 /// Exchange records between workers.
-pub trait Exchange<T, D: ExchangeData> {
+pub trait Exchange<T, D: Data> {
     /// Exchange records between workers.
     ///
     /// The closure supplied should map a reference to a record to a `u64`,
@@ -22,12 +25,136 @@ pub trait Exchange<T, D: ExchangeData> {
     ///            .inspect(|x| println!("seen: {:?}", x));
     /// });
     /// ```
-    fn exchange(&self, route: impl Fn(&D)->u64+'static) -> Self;
+    fn exchange(&self, route: impl Fn(&D)->u64+'static) -> Self where D: ExchangeData;
+
+    /// Exchange records between workers, serializing them with `serde` rather than `Abomonation`.
+    ///
+    /// This allows records that implement `serde`'s traits, but not `Abomonation`, to move
+    /// between processes. The closure supplied routes records as for `exchange`.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // `BTreeMap` does not implement `Abomonation`, but does implement `serde`'s traits.
+    ///     (0..10u64).map(|x| { let mut map = BTreeMap::new(); map.insert(x, x); map })
+    ///               .to_stream(scope)
+    ///               .exchange_serde(|map| map.len() as u64)
+    ///               .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn exchange_serde(&self, route: impl Fn(&D)->u64+'static) -> Self where D: SerdeData, T: SerdeData;
+
+    /// Exchange records between workers by their `Hash` implementation.
+    ///
+    /// Records are hashed with the worker's configured hasher, which can be set with
+    /// [`WorkerConfig::exchange_hasher`](crate::WorkerConfig::exchange_hasher).
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .exchange_hashed()
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn exchange_hashed(&self) -> Self where D: ExchangeData+Hash;
+
+    /// Exchange records between workers by their `Hash` implementation, using hashers from `builder`.
+    ///
+    /// The builder must produce identical hashes on all workers, which rules out randomly seeded
+    /// builders unless their seed is shared across the computation.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::hash::BuildHasherDefault;
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .exchange_with_hasher(BuildHasherDefault::<DefaultHasher>::default())
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn exchange_with_hasher<B: BuildHasher+'static>(&self, builder: B) -> Self where D: ExchangeData+Hash;
+
+    /// Moves batches of records to whichever worker of the process is free to take them.
+    ///
+    /// Each worker's operator takes at most one batch each time it is scheduled, so that the
+    /// operators that follow it on the same worker process the batch before it takes another.
+    /// Expensive stages following `steal` are then shared among the workers of a process by how
+    /// busy they are, rather than by a routing function. The worker and order in which a record
+    /// is processed are not determined, which suits stateless and order-insensitive operators.
+    /// See the [`steal`](crate::dataflow::channels::steal) module for details.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Exchange, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10u64).to_stream(scope)
+    ///               .steal()
+    ///               .map(|x| (0 .. x).sum::<u64>())
+    ///               .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn steal(&self) -> Self;
+
+    /// Moves all records of each worker `i` to worker `(i + offset) % peers`, in order.
+    ///
+    /// Each worker receives the records of one other worker, or its own if `offset` is a multiple
+    /// of the number of workers, in the order that worker sent them. This suits pipeline-parallel
+    /// topologies, in which each worker hosts one stage of a pipeline.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Exchange, Map, Inspect};
+    ///
+    /// timely::execute(timely::Config::process(3), |worker| {
+    ///     let index = worker.index();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         // Records start at the worker given by their value, and pass through each worker.
+    ///         (0..3u64).filter(move |x| *x == index as u64)
+    ///                  .to_stream(scope)
+    ///                  .map(move |x| vec![x])
+    ///                  .pipeline_across_workers(1)
+    ///                  .map(move |mut path| { path.push(index as u64); path })
+    ///                  .pipeline_across_workers(1)
+    ///                  .inspect(move |path| assert_eq!(path[0], (index as u64 + 1) % 3));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    fn pipeline_across_workers(&self, offset: usize) -> Self where D: ExchangeData;
+
+    /// Moves all records of each worker `i` to worker `permutation[i]`, in order.
+    ///
+    /// The permutation must hold each worker index once.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+    ///
+    /// timely::execute(timely::Config::process(3), |worker| {
+    ///     let index = worker.index();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..3u64).filter(move |x| *x == index as u64)
+    ///                  .to_stream(scope)
+    ///                  .permute_workers(vec![2, 0, 1])
+    ///                  .inspect(move |x| assert_eq!([2, 0, 1][*x as usize], index));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    fn permute_workers(&self, permutation: Vec<usize>) -> Self where D: ExchangeData;
 }
 
 // impl<T: Timestamp, G: Scope<Timestamp=T>, D: ExchangeData> Exchange<T, D> for Stream<G, D> {
-impl<G: Scope, D: ExchangeData> Exchange<G::Timestamp, D> for Stream<G, D> {
-    fn exchange(&self, route: impl Fn(&D)->u64+'static) -> Stream<G, D> {
+impl<G: Scope, D: Data+Send+Sync> Exchange<G::Timestamp, D> for Stream<G, D> {
+    fn exchange(&self, route: impl Fn(&D)->u64+'static) -> Stream<G, D> where D: ExchangeData {
         let mut vector = Vec::new();
         self.unary(ExchangePact::new(route), "Exchange", move |_,_| move |input, output| {
             input.for_each(|time, data| {
@@ -36,4 +163,60 @@ impl<G: Scope, D: ExchangeData> Exchange<G::Timestamp, D> for Stream<G, D> {
             });
         })
     }
+
+    fn exchange_serde(&self, route: impl Fn(&D)->u64+'static) -> Stream<G, D> where D: SerdeData, G::Timestamp: SerdeData {
+        let mut vector = Vec::new();
+        self.unary(ExchangePact::new(route).with_codec::<Bincode>(), "Exchange", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                output.session(&time).give_vec(&mut vector);
+            });
+        })
+    }
+
+    fn exchange_hashed(&self) -> Stream<G, D> where D: ExchangeData+Hash {
+        let hasher = self.scope().config().hasher().clone();
+        self.exchange(move |x| hasher.hash(x))
+    }
+
+    fn exchange_with_hasher<B: BuildHasher+'static>(&self, builder: B) -> Stream<G, D> where D: ExchangeData+Hash {
+        // `BuildHasher::hash_one` would need Rust 1.71.
+        #[allow(clippy::manual_hash_one)]
+        let route = move |x: &D| {
+            let mut hasher = builder.build_hasher();
+            x.hash(&mut hasher);
+            hasher.finish()
+        };
+        self.exchange(route)
+    }
+
+    fn steal(&self) -> Stream<G, D> {
+        let mut vector = Vec::new();
+        self.unary(StealPact, "Steal", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                output.session(&time).give_vec(&mut vector);
+            });
+        })
+    }
+
+    fn pipeline_across_workers(&self, offset: usize) -> Stream<G, D> where D: ExchangeData {
+        let mut vector = Vec::new();
+        self.unary(OneToOne::offset(offset), "PipelineAcrossWorkers", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                output.session(&time).give_vec(&mut vector);
+            });
+        })
+    }
+
+    fn permute_workers(&self, permutation: Vec<usize>) -> Stream<G, D> where D: ExchangeData {
+        let mut vector = Vec::new();
+        self.unary(OneToOne::permutation(permutation), "PermuteWorkers", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                output.session(&time).give_vec(&mut vector);
+            });
+        })
+    }
 }
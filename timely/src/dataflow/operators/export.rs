@@ -0,0 +1,355 @@
+//! Streams shared between the dataflows of a worker.
+//!
+//! A stream exported under a name from one dataflow may be imported by a dataflow the same
+//! worker constructs later, without leaving the worker. The importing dataflow observes the
+//! exported stream's records and the progress of its frontier, exactly as the exporting
+//! dataflow produced them, in the manner of `capture` and `replay`.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Inspect, Probe};
+//! use timely::dataflow::operators::export::{Export, Import};
+//!
+//! timely::execute(timely::Config::thread(), |worker| {
+//!
+//!     let mut input = InputHandle::new();
+//!     worker.dataflow(|scope| {
+//!         scope.input_from(&mut input).export("numbers");
+//!     });
+//!
+//!     let probe = worker.dataflow(|scope| {
+//!         scope.import::<u64>("numbers")
+//!              .inspect(|x| println!("imported: {:?}", x))
+//!              .probe()
+//!     });
+//!
+//!     for round in 0 .. 10u64 {
+//!         input.send(round);
+//!         input.advance_to(round + 1);
+//!         worker.step_while(|| probe.less_than(input.time()));
+//!     }
+//! }).unwrap();
+//! ```
+//!
+//! A stream may instead be published, after which any number of dataflows may subscribe to it
+//! while it runs. Each subscriber observes the published stream from the moment it subscribes:
+//! its frontier at that moment, and the records and progress that follow. A subscriber stops
+//! once its `Subscription` is dropped, without affecting the published stream.
+//!
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Inspect, Probe};
+//! use timely::dataflow::operators::export::{Publish, Subscribe};
+//!
+//! timely::execute(timely::Config::thread(), |worker| {
+//!
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow(|scope| {
+//!         let stream = scope.input_from(&mut input);
+//!         stream.publish("numbers");
+//!         stream.probe()
+//!     });
+//!
+//!     // attach a temporary observer for rounds five through seven.
+//!     let mut observer = None;
+//!     for round in 0 .. 10u64 {
+//!         if round == 5 {
+//!             observer = Some(worker.dataflow::<u64,_,_>(|scope| {
+//!                 let (subscription, stream) = scope.subscribe::<u64>("numbers");
+//!                 let probe = stream.inspect(|x| assert!((5 .. 8).contains(x))).probe();
+//!                 (subscription, probe)
+//!             }));
+//!         }
+//!         if round == 8 {
+//!             let (subscription, observed) = observer.take().unwrap();
+//!             drop(subscription);
+//!             worker.step_while(|| !observed.done());
+//!         }
+//!         input.send(round);
+//!         input.advance_to(round + 1);
+//!         worker.step_while(|| probe.less_than(input.time()));
+//!     }
+//! }).unwrap();
+//! ```
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::rc::{Rc, Weak};
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pushers::Counter as PushCounter;
+use crate::dataflow::channels::pushers::buffer::Buffer as PushBuffer;
+use crate::dataflow::operators::capture::{Capture, Event, EventPusher};
+use crate::dataflow::operators::capture::event::EventIterator;
+use crate::dataflow::operators::capture::event::link::EventLink;
+use crate::dataflow::operators::generic::builder_raw::OperatorBuilder;
+use crate::progress::{ChangeBatch, Timestamp};
+use crate::scheduling::Activator;
+
+/// Exported streams of a worker that have not yet been imported, and published streams.
+#[derive(Default)]
+pub struct Exports {
+    // Maps each name to a `Shared<T, D>` for the exported stream's types.
+    streams: HashMap<String, Box<dyn Any>>,
+    // Maps each name to a `Rc<RefCell<Publication<T, D>>>` for the published stream's types.
+    publications: HashMap<String, Box<dyn Any>>,
+}
+
+/// The events of an exported stream, and the importers to activate when they arrive.
+struct Shared<T, D> {
+    events: Rc<EventLink<T, D>>,
+    importers: Rc<RefCell<Vec<Activator>>>,
+}
+
+/// Appends events to the shared list, and activates the importers.
+struct ExportPusher<T, D> {
+    events: Rc<EventLink<T, D>>,
+    importers: Rc<RefCell<Vec<Activator>>>,
+}
+
+impl<T, D> EventPusher<T, D> for ExportPusher<T, D> {
+    fn push(&mut self, event: Event<T, D>) {
+        self.events.push(event);
+        for activator in self.importers.borrow().iter() {
+            activator.activate();
+        }
+    }
+}
+
+/// Exports a stream for use by other dataflows of the same worker.
+pub trait Export<G: Scope, D: Data> {
+    /// Exports the stream under `name`, for a later call to `import`.
+    ///
+    /// The stream's events are retained until it is imported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a stream exported under `name` has not yet been imported.
+    fn export(&self, name: &str);
+}
+
+impl<G: Scope, D: Data> Export<G, D> for Stream<G, D> {
+    fn export(&self, name: &str) {
+        let events = Rc::new(EventLink::new());
+        let importers = Rc::new(RefCell::new(Vec::new()));
+        let shared = Shared { events: events.clone(), importers: importers.clone() };
+        let previous = self.scope().exports().streams.insert(name.to_owned(), Box::new(shared));
+        if previous.is_some() {
+            panic!("stream {:?} exported twice without being imported", name);
+        }
+        self.capture_into(ExportPusher { events, importers });
+    }
+}
+
+/// Imports a stream exported by another dataflow of the same worker.
+pub trait Import : Scope {
+    /// Imports the stream exported under `name`, which must have this scope's timestamp.
+    ///
+    /// Each export may be imported once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no stream has been exported under `name`, or if it has different types.
+    fn import<D: Data>(&mut self, name: &str) -> Stream<Self, D>;
+}
+
+impl<G: Scope> Import for G {
+    fn import<D: Data>(&mut self, name: &str) -> Stream<G, D> {
+
+        let shared = self.exports().streams.remove(name).unwrap_or_else(|| panic!("no stream exported as {:?}", name));
+        let shared = shared.downcast::<Shared<G::Timestamp, D>>().unwrap_or_else(|_| panic!("stream {:?} exported with other types", name));
+        let mut events = shared.events;
+
+        let mut builder = OperatorBuilder::new("Import".to_owned(), self.clone());
+        let address = builder.operator_info().address;
+        shared.importers.borrow_mut().push(self.activator_for(&address[..]));
+
+        let (targets, stream) = builder.new_output();
+        let mut output = PushBuffer::new(PushCounter::new(targets)).with_length(self.config().batch_length()).with_lineage(self.extensions().lineage());
+
+        // As with `replay`, the operator starts with the capability the exported stream's
+        // progress statements assume, and applies them as they arrive.
+        builder.build(move |progress| {
+            while let Some(event) = events.next() {
+                match *event {
+                    Event::Progress(ref vec) => {
+                        progress.internals[0].extend(vec.iter().cloned());
+                    },
+                    Event::Messages(ref time, ref data) => {
+                        output.session(time).give_iterator(data.iter().cloned());
+                    }
+                }
+            }
+            output.cease();
+            output.inner().produced().borrow_mut().drain_into(&mut progress.produceds[0]);
+            false
+        });
+
+        stream
+    }
+}
+
+/// The progress of a published stream, and the subscribers to forward its events to.
+struct Publication<T: Timestamp, D> {
+    /// Accumulated progress of the stream, from an initial count of one for the minimum time.
+    counts: ChangeBatch<T>,
+    subscribers: Vec<Weak<Subscriber<T, D>>>,
+}
+
+impl<T: Timestamp, D: Clone> Publication<T, D> {
+    /// Sends `event` to each subscriber, and forgets those that have gone.
+    fn forward(&mut self, event: Event<T, D>) {
+        self.subscribers.retain(|subscriber| subscriber.upgrade().map(|s| !s.cancelled.get()).unwrap_or(false));
+        for subscriber in self.subscribers.iter().filter_map(|subscriber| subscriber.upgrade()) {
+            subscriber.events.borrow_mut().push_back(event.clone());
+            subscriber.activator.activate();
+        }
+    }
+}
+
+/// The events a subscriber has yet to replay.
+struct Subscriber<T, D> {
+    events: RefCell<VecDeque<Event<T, D>>>,
+    activator: Activator,
+    cancelled: Cell<bool>,
+}
+
+/// Forwards the events of a published stream to its subscribers.
+struct PublishPusher<T: Timestamp, D> {
+    publication: Rc<RefCell<Publication<T, D>>>,
+}
+
+impl<T: Timestamp, D: Clone> EventPusher<T, D> for PublishPusher<T, D> {
+    fn push(&mut self, event: Event<T, D>) {
+        let mut publication = self.publication.borrow_mut();
+        if let Event::Progress(ref vec) = event {
+            publication.counts.extend(vec.iter().cloned());
+        }
+        publication.forward(event);
+    }
+}
+
+// If the published stream's dataflow is dropped before it completes, subscribers are released.
+impl<T: Timestamp, D> Drop for PublishPusher<T, D> {
+    fn drop(&mut self) {
+        let mut publication = self.publication.borrow_mut();
+        let release = publication.counts.drain().map(|(time, diff)| (time, -diff)).collect::<Vec<_>>();
+        if !release.is_empty() {
+            for subscriber in publication.subscribers.iter().filter_map(|subscriber| subscriber.upgrade()) {
+                subscriber.events.borrow_mut().push_back(Event::Progress(release.clone()));
+                subscriber.activator.activate();
+            }
+        }
+    }
+}
+
+/// Publishes a stream, for subscription by other dataflows of the same worker.
+pub trait Publish<G: Scope, D: Data> {
+    /// Publishes the stream under `name`, for any number of calls to `subscribe`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a stream has already been published under `name`.
+    fn publish(&self, name: &str);
+}
+
+impl<G: Scope, D: Data> Publish<G, D> for Stream<G, D> {
+    fn publish(&self, name: &str) {
+        let publication = Rc::new(RefCell::new(Publication {
+            counts: ChangeBatch::new_from(G::Timestamp::minimum(), 1),
+            subscribers: Vec::new(),
+        }));
+        let previous = self.scope().exports().publications.insert(name.to_owned(), Box::new(publication.clone()));
+        if previous.is_some() {
+            panic!("stream {:?} published twice", name);
+        }
+        self.capture_into(PublishPusher { publication });
+    }
+}
+
+/// A subscription to a published stream, which ends when dropped.
+///
+/// Once the subscription ends, the subscribing stream releases its capabilities and produces no
+/// further records, and the published stream no longer retains records for it.
+pub struct Subscription<T, D> {
+    subscriber: Rc<Subscriber<T, D>>,
+}
+
+impl<T, D> Drop for Subscription<T, D> {
+    fn drop(&mut self) {
+        self.subscriber.cancelled.set(true);
+        self.subscriber.activator.activate();
+    }
+}
+
+/// Subscribes to a stream published by a dataflow of the same worker.
+pub trait Subscribe : Scope {
+    /// Subscribes to the stream published under `name`, which must have this scope's timestamp.
+    ///
+    /// The returned stream's frontier starts at the published stream's frontier, and it carries
+    /// the records the published stream carries from that point, until the subscription is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no stream has been published under `name`, or if it has different types.
+    fn subscribe<D: Data>(&mut self, name: &str) -> (Subscription<Self::Timestamp, D>, Stream<Self, D>);
+}
+
+impl<G: Scope> Subscribe for G {
+    fn subscribe<D: Data>(&mut self, name: &str) -> (Subscription<G::Timestamp, D>, Stream<G, D>) {
+
+        let publication = self.exports()
+            .publications
+            .get(name)
+            .unwrap_or_else(|| panic!("no stream published as {:?}", name))
+            .downcast_ref::<Rc<RefCell<Publication<G::Timestamp, D>>>>()
+            .unwrap_or_else(|| panic!("stream {:?} published with other types", name))
+            .clone();
+
+        let mut builder = OperatorBuilder::new("Subscribe".to_owned(), self.clone());
+        let address = builder.operator_info().address;
+        let subscriber = Rc::new(Subscriber {
+            events: RefCell::new(VecDeque::new()),
+            activator: self.activator_for(&address[..]),
+            cancelled: Cell::new(false),
+        });
+
+        // The operator starts with a capability for the minimum time, and moves it to the
+        // published stream's current frontier.
+        let mut initial = publication.borrow().counts.clone();
+        initial.update(G::Timestamp::minimum(), -1);
+        subscriber.events.borrow_mut().push_back(Event::Progress(initial.into_inner()));
+        publication.borrow_mut().subscribers.push(Rc::downgrade(&subscriber));
+
+        let (targets, stream) = builder.new_output();
+        let mut output = PushBuffer::new(PushCounter::new(targets)).with_length(self.config().batch_length()).with_lineage(self.extensions().lineage());
+
+        let events = subscriber.clone();
+        let mut held = ChangeBatch::new_from(G::Timestamp::minimum(), 1);
+        builder.build(move |progress| {
+            if events.cancelled.get() {
+                events.events.borrow_mut().clear();
+                progress.internals[0].extend(held.drain().map(|(time, diff)| (time, -diff)));
+            }
+            while let Some(event) = events.events.borrow_mut().pop_front() {
+                match event {
+                    Event::Progress(vec) => {
+                        held.extend(vec.iter().cloned());
+                        progress.internals[0].extend(vec.into_iter());
+                    },
+                    Event::Messages(time, data) => {
+                        output.session(&time).give_iterator(data.into_iter());
+                    }
+                }
+            }
+            output.cease();
+            output.inner().produced().borrow_mut().drain_into(&mut progress.produceds[0]);
+            false
+        });
+
+        (Subscription { subscriber }, stream)
+    }
+}
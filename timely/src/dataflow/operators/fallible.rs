@@ -0,0 +1,136 @@
+//! Record-by-record transformations whose errors are routed to a second stream.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope, ScopeParent};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// An error produced by a fallible transformation, with the context in which it occurred.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FallibleError<T, E> {
+    /// The name of the operator whose logic failed.
+    pub operator: String,
+    /// The timestamp of the record the logic failed on.
+    pub time: T,
+    /// The error returned by the logic.
+    pub error: E,
+}
+
+// The streams of successfully transformed records and of errors.
+type Outputs<S, D, E> = (Stream<S, D>, Stream<S, FallibleError<<S as ScopeParent>::Timestamp, E>>);
+
+/// Extension trait for `Stream`.
+pub trait Fallible<S: Scope, D: Data> {
+    /// Consumes each element of the stream and yields a new element, or an error.
+    ///
+    /// Errors are sent to the second returned stream, at the time of the failed record, along
+    /// with the operator's `name` and that time.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::fallible::Fallible;
+    ///
+    /// timely::example(|scope| {
+    ///     let (numbers, errors) = vec!["1", "two", "3"]
+    ///         .to_stream(scope)
+    ///         .map_fallible("Parse", |x| x.parse::<u64>().map_err(|e| e.to_string()));
+    ///
+    ///     numbers.inspect(|x| println!("parsed: {:?}", x));
+    ///     errors.inspect(|e| println!("{} failed at {:?}: {}", e.operator, e.time, e.error));
+    /// });
+    /// ```
+    fn map_fallible<D2, E, L>(&self, name: &str, logic: L) -> Outputs<S, D2, E>
+    where
+        D2: Data,
+        E: Data,
+        L: FnMut(D)->Result<D2, E>+'static;
+    /// Consumes each element of the stream and yields at most one new element, or an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::fallible::Fallible;
+    ///
+    /// timely::example(|scope| {
+    ///     let (numbers, errors) = vec!["1", "", "two"]
+    ///         .to_stream(scope)
+    ///         .filter_map_fallible("Parse", |x| {
+    ///             if x.is_empty() { Ok(None) }
+    ///             else { x.parse::<u64>().map(Some).map_err(|e| e.to_string()) }
+    ///         });
+    ///
+    ///     numbers.inspect(|x| assert_eq!(*x, 1));
+    ///     errors.inspect(|e| println!("{} failed at {:?}: {}", e.operator, e.time, e.error));
+    /// });
+    /// ```
+    fn filter_map_fallible<D2, E, L>(&self, name: &str, logic: L) -> Outputs<S, D2, E>
+    where
+        D2: Data,
+        E: Data,
+        L: FnMut(D)->Result<Option<D2>, E>+'static;
+    /// Consumes each element of the stream and yields some number of new elements, or an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::fallible::Fallible;
+    ///
+    /// timely::example(|scope| {
+    ///     let (numbers, errors) = (0..10u64)
+    ///         .to_stream(scope)
+    ///         .flat_map_fallible("Countdown", |x| if x < 8 { Ok(0 .. x) } else { Err(x) });
+    ///
+    ///     numbers.inspect(|x| assert!(*x < 7));
+    ///     errors.inspect(|e| assert!(e.error >= 8));
+    /// });
+    /// ```
+    fn flat_map_fallible<I, E, L>(&self, name: &str, logic: L) -> Outputs<S, I::Item, E>
+    where
+        I: IntoIterator,
+        I::Item: Data,
+        E: Data,
+        L: FnMut(D)->Result<I, E>+'static;
+}
+
+impl<S: Scope, D: Data> Fallible<S, D> for Stream<S, D> {
+    fn map_fallible<D2, E, L>(&self, name: &str, mut logic: L) -> Outputs<S, D2, E>
+    where
+        D2: Data,
+        E: Data,
+        L: FnMut(D)->Result<D2, E>+'static
+    {
+        self.flat_map_fallible(name, move |x| logic(x).map(Some))
+    }
+    fn filter_map_fallible<D2, E, L>(&self, name: &str, logic: L) -> Outputs<S, D2, E>
+    where
+        D2: Data,
+        E: Data,
+        L: FnMut(D)->Result<Option<D2>, E>+'static
+    {
+        self.flat_map_fallible(name, logic)
+    }
+    fn flat_map_fallible<I, E, L>(&self, name: &str, mut logic: L) -> Outputs<S, I::Item, E>
+    where
+        I: IntoIterator,
+        I::Item: Data,
+        E: Data,
+        L: FnMut(D)->Result<I, E>+'static
+    {
+        let operator = name.to_owned();
+        let mut vector = Vec::new();
+        self.unary_with_two_outputs(Pipeline, name, move |_, _, _| move |input, ok, err| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                let mut ok = ok.session(&time);
+                let mut err = err.session(&time);
+                for datum in vector.drain(..) {
+                    match logic(datum) {
+                        Ok(data) => ok.give_iterator(data.into_iter()),
+                        Err(error) => err.give(FallibleError { operator: operator.clone(), time: time.time().clone(), error }),
+                    }
+                }
+            });
+        })
+    }
+}
@@ -10,6 +10,7 @@ use crate::dataflow::channels::pushers::Tee;
 use crate::dataflow::channels::pact::Pipeline;
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::scopes::child::Iterative;
+use crate::dataflow::operators::{Branch, Concat, Enter, Leave};
 use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
 use crate::dataflow::operators::generic::OutputWrapper;
 
@@ -103,6 +104,25 @@ pub trait ConnectLoop<G: Scope, D: Data> {
     /// });
     /// ```
     fn connect_loop(&self, _: Handle<G, D>);
+
+    /// Connect a `Stream` to be the input of a loop variable, circulating only those records
+    /// for which `predicate` holds at their new time.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::Scope;
+    /// use timely::dataflow::operators::{Feedback, ConnectLoop, ToStream, Concat, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     // double each number until it exceeds 1000, for at most 8 rounds.
+    ///     let (handle, cycle) = scope.feedback(1);
+    ///     (1..10).to_stream(scope)
+    ///            .concat(&cycle.map(|x| 2 * x))
+    ///            .inspect(|x| println!("seen: {:?}", x))
+    ///            .connect_loop_while(handle, |time, x| *time < 8 && *x <= 1000);
+    /// });
+    /// ```
+    fn connect_loop_while<P: FnMut(&G::Timestamp, &D)->bool+'static>(&self, _: Handle<G, D>, predicate: P);
 }
 
 impl<G: Scope, D: Data> ConnectLoop<G, D> for Stream<G, D> {
@@ -128,6 +148,83 @@ impl<G: Scope, D: Data> ConnectLoop<G, D> for Stream<G, D> {
             });
         });
     }
+
+    fn connect_loop_while<P: FnMut(&G::Timestamp, &D)->bool+'static>(&self, helper: Handle<G, D>, mut predicate: P) {
+
+        let mut builder = helper.builder;
+        let summary = helper.summary;
+        let mut output = helper.output;
+
+        let mut input = builder.new_input_connection(self, Pipeline, vec![Antichain::from_elem(summary.clone())]);
+
+        let mut vector = Vec::new();
+        builder.build(move |_capability| move |_frontier| {
+            let mut output = output.activate();
+            input.for_each(|cap, data| {
+                data.swap(&mut vector);
+                if let Some(new_time) = summary.results_in(cap.time()) {
+                    vector.retain(|x| predicate(&new_time, x));
+                    if !vector.is_empty() {
+                        let new_cap = cap.delayed(&new_time);
+                        output
+                            .session(&new_cap)
+                            .give_vec(&mut vector);
+                    }
+                }
+            });
+        });
+    }
+}
+
+/// Iterates a computation on a stream while a condition holds.
+pub trait Iterate : Scope {
+    /// Applies `body` to each record of `initial` repeatedly while `condition` holds for its
+    /// result, for at most `max_rounds` applications, and returns the final results.
+    ///
+    /// Each round's results for which `condition` holds, if fewer than `max_rounds` rounds have
+    /// been applied, are the next round's input; the others leave the loop. Once no records
+    /// remain in the loop, the iterative scope's capabilities are released and the returned
+    /// stream's frontier advances.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Capture};
+    /// use timely::dataflow::operators::feedback::Iterate;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let (bounded, capped) = timely::example(|scope| {
+    ///     let initial = (1..5u64).to_stream(scope);
+    ///     let bounded = scope.iterate_while(&initial, 10, |stream| stream.map(|x| 3 * x), |x| *x < 100);
+    ///     let capped = scope.iterate_while(&initial, 2, |stream| stream.map(|x| 3 * x), |x| *x < 100);
+    ///     (bounded.capture(), capped.capture())
+    /// });
+    ///
+    /// assert_eq!(bounded.extract(), vec![(0, vec![108, 162, 243, 243])]);
+    /// assert_eq!(capped.extract(), vec![(0, vec![9, 18, 27, 36])]);
+    /// ```
+    fn iterate_while<D, B, C>(&mut self, initial: &Stream<Self, D>, max_rounds: u64, body: B, condition: C) -> Stream<Self, D>
+    where
+        D: Data,
+        B: for<'a> FnOnce(&Stream<Iterative<'a, Self, u64>, D>)->Stream<Iterative<'a, Self, u64>, D>,
+        C: Fn(&D)->bool+'static;
+}
+
+impl<G: Scope> Iterate for G {
+    fn iterate_while<D, B, C>(&mut self, initial: &Stream<G, D>, max_rounds: u64, body: B, condition: C) -> Stream<G, D>
+    where
+        D: Data,
+        B: for<'a> FnOnce(&Stream<Iterative<'a, G, u64>, D>)->Stream<Iterative<'a, G, u64>, D>,
+        C: Fn(&D)->bool+'static,
+    {
+        self.iterative::<u64, _, _>(|subgraph| {
+            let (handle, cycle) = subgraph.loop_variable(1);
+            let results = body(&initial.enter(subgraph).concat(&cycle));
+            // records leave the loop once the condition fails, or after the final round.
+            let (circulating, complete) = results.branch(move |time, x| time.inner + 1 >= max_rounds || !condition(x));
+            circulating.connect_loop(handle);
+            complete.leave()
+        })
+    }
 }
 
 /// A handle used to bind the source of a loop variable.
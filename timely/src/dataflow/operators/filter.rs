@@ -9,6 +9,9 @@ use crate::dataflow::operators::generic::operator::Operator;
 pub trait Filter<D: Data> {
     /// Returns a new instance of `self` containing only records satisfying `predicate`.
     ///
+    /// Records are retained within the allocation of each input batch, which is sent on without
+    /// copying.
+    ///
     /// # Examples
     /// ```
     /// use timely::dataflow::operators::{ToStream, Filter, Inspect};
@@ -0,0 +1,49 @@
+//! Release of each timestamp's records once the timestamp is complete.
+use std::collections::HashMap;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for releasing records once their timestamps are complete.
+pub trait EmitAtFrontier<G: Scope, D: Data> {
+    /// Buffers the records of each time, and produces them in a single batch once the input
+    /// frontier has passed the time.
+    ///
+    /// Downstream operators then observe each time's records only once the time is complete, and
+    /// all together.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Inspect};
+    /// use timely::dataflow::operators::flush::EmitAtFrontier;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10u64).to_stream(scope)
+    ///               .delay(|x, _| x % 2)
+    ///               .emit_at_frontier()
+    ///               .inspect_batch(|time, batch| assert_eq!(batch.len(), 5, "at time {}", time));
+    /// });
+    /// ```
+    fn emit_at_frontier(&self) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> EmitAtFrontier<G, D> for Stream<G, D> {
+    fn emit_at_frontier(&self) -> Stream<G, D> {
+        let mut stash = HashMap::new();
+        self.unary_notify(Pipeline, "EmitAtFrontier", vec![], move |input, output, notificator| {
+            input.for_each(|time, data| {
+                stash.entry(time.time().clone())
+                     .or_insert_with(Vec::new)
+                     .append(&mut data.replace(Vec::new()));
+                notificator.notify_at(time.retain());
+            });
+            notificator.for_each(|time, _, _| {
+                if let Some(mut batch) = stash.remove(time.time()) {
+                    output.session(&time).give_vec(&mut batch);
+                }
+            });
+        })
+    }
+}
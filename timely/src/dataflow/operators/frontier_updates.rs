@@ -0,0 +1,70 @@
+//! The frontier of a stream, as a stream of updates.
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::CapabilitySet;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::progress::{Antichain, Timestamp};
+
+/// Extension trait for observing the frontier of a stream as data.
+pub trait FrontierUpdates<G: Scope, D: Data> {
+    /// Produces updates `(frontier, diff)` whose accumulation is the frontier of this worker's
+    /// part of the stream.
+    ///
+    /// The initial frontier is produced at the minimal time. Each time the frontier advances, the
+    /// old frontier is retracted and the new frontier is produced, at a time of the old frontier.
+    /// For totally ordered timestamps, this is the time the frontier has just passed, so that
+    /// downstream operators observe the completion of each time as a record at that time. The
+    /// records of the stream are discarded.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Inspect, Probe};
+    /// use timely::dataflow::operators::frontier_updates::FrontierUpdates;
+    /// use timely::progress::Antichain;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let mut input = InputHandle::<u64, u64>::new();
+    ///     let probe = worker.dataflow(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .frontier_updates()
+    ///              .inspect(|x| println!("frontier update: {:?}", x))
+    ///              .probe()
+    ///     });
+    ///
+    ///     // The completion of each time is observed at that time.
+    ///     for round in 1 .. 4 {
+    ///         input.advance_to(round);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    /// }).unwrap();
+    /// ```
+    fn frontier_updates(&self) -> Stream<G, (Antichain<G::Timestamp>, i64)>;
+}
+
+impl<G: Scope, D: Data> FrontierUpdates<G, D> for Stream<G, D> {
+    fn frontier_updates(&self) -> Stream<G, (Antichain<G::Timestamp>, i64)> {
+        self.unary_frontier(Pipeline, "FrontierUpdates", |capability, _info| {
+            let mut capabilities = CapabilitySet::from_elem(capability);
+            let mut current: Option<Antichain<G::Timestamp>> = None;
+            move |input, output| {
+                input.for_each(|_time, _data| { });
+                if current.is_none() {
+                    let initial = Antichain::from_elem(G::Timestamp::minimum());
+                    output.session(&capabilities[0]).give((initial.clone(), 1));
+                    current = Some(initial);
+                }
+                let frontier = input.frontier().frontier();
+                let previous = current.as_mut().unwrap();
+                if previous.borrow() != frontier {
+                    let next = frontier.to_owned();
+                    let mut session = output.session(&capabilities[0]);
+                    session.give((::std::mem::replace(previous, next.clone()), -1));
+                    session.give((next, 1));
+                    capabilities.downgrade(previous.elements());
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,101 @@
+//! Chains of record-by-record transformations applied by a single operator.
+//!
+//! Each of `map`, `filter`, and `flat_map` on a `Stream` constructs an operator, and each record
+//! passes through a channel and each time through the progress tracker for each of them. A chain
+//! of such transformations may instead be described by a `Fused` stream, which applies the whole
+//! chain to each record in turn within one operator.
+
+use std::marker::PhantomData;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+// Receives the records yielded by a chain of transformations.
+type Sink<'a, D> = &'a mut dyn FnMut(D);
+// The empty chain of transformations.
+type Identity<D> = for<'a> fn(D, Sink<'a, D>);
+
+/// Extension trait for `Stream`.
+pub trait Fuse<S: Scope, D: Data> {
+    /// Starts a chain of transformations, to be applied by a single operator.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Fuse, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .fused()
+    ///            .map(|x| x + 1)
+    ///            .filter(|x| x % 2 == 0)
+    ///            .flat_map(|x| vec![x; 2])
+    ///            .into_stream()
+    ///            .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![2, 2, 4, 4, 6, 6, 8, 8, 10, 10])]);
+    /// ```
+    fn fused(&self) -> Fused<S, D, D, Identity<D>>;
+}
+
+impl<S: Scope, D: Data> Fuse<S, D> for Stream<S, D> {
+    fn fused(&self) -> Fused<S, D, D, Identity<D>> {
+        fn identity<D>(datum: D, sink: Sink<'_, D>) { sink(datum) }
+        Fused::new(self.clone(), identity)
+    }
+}
+
+/// A stream of `D0` records, with transformations yielding `D` records still to be applied.
+///
+/// The transformations are applied once the chain is converted back to a stream, by `into_stream`.
+pub struct Fused<S: Scope, D0: Data, D, L> {
+    stream: Stream<S, D0>,
+    logic: L,
+    phantom: PhantomData<D>,
+}
+
+impl<S: Scope, D0: Data, D, L: FnMut(D0, Sink<'_, D>)+'static> Fused<S, D0, D, L> {
+
+    /// Appends a transformation consuming each element and yielding a new element.
+    pub fn map<D2, F: FnMut(D)->D2+'static>(self, mut logic: F) -> Fused<S, D0, D2, impl FnMut(D0, Sink<'_, D2>)+'static> {
+        let (stream, mut prior) = (self.stream, self.logic);
+        Fused::new(stream, move |datum, sink: Sink<'_, D2>| prior(datum, &mut |x| sink(logic(x))))
+    }
+
+    /// Appends a transformation retaining only the elements satisfying `predicate`.
+    pub fn filter<P: FnMut(&D)->bool+'static>(self, mut predicate: P) -> Fused<S, D0, D, impl FnMut(D0, Sink<'_, D>)+'static> {
+        let (stream, mut prior) = (self.stream, self.logic);
+        Fused::new(stream, move |datum, sink: Sink<'_, D>| prior(datum, &mut |x| if predicate(&x) { sink(x) }))
+    }
+
+    /// Appends a transformation consuming each element and yielding some number of new elements.
+    #[allow(clippy::type_complexity)]
+    pub fn flat_map<I: IntoIterator, F: FnMut(D)->I+'static>(self, mut logic: F) -> Fused<S, D0, I::Item, impl FnMut(D0, Sink<'_, I::Item>)+'static> {
+        let (stream, mut prior) = (self.stream, self.logic);
+        Fused::new(stream, move |datum, sink: Sink<'_, I::Item>| prior(datum, &mut |x| for y in logic(x) { sink(y) }))
+    }
+
+    /// Constructs the operator applying the chain of transformations.
+    pub fn into_stream(self) -> Stream<S, D> where D: Data {
+        let mut logic = self.logic;
+        let mut vector = Vec::new();
+        self.stream.unary(Pipeline, "Fused", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                let mut session = output.session(&time);
+                for datum in vector.drain(..) {
+                    logic(datum, &mut |x| session.give(x));
+                }
+            });
+        })
+    }
+}
+
+impl<S: Scope, D0: Data, D, L> Fused<S, D0, D, L> {
+    fn new(stream: Stream<S, D0>, logic: L) -> Self {
+        Fused { stream, logic, phantom: PhantomData }
+    }
+}
@@ -0,0 +1,97 @@
+//! Holding back records until another stream is complete through their times.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::Capability;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for gating a stream on the frontier of another.
+pub trait Gate<S: Scope, D: Data> {
+    /// Holds back records at each time until the frontier of `control` has passed that time.
+    ///
+    /// Records are produced at their own times, once `control` can no longer produce records at
+    /// or before them, and records at times `control` has already passed are produced as they
+    /// arrive. The records of `control` are discarded. This suits records at a time that must
+    /// wait for, say, the configuration or model of that time, which arrives on `control`.
+    ///
+    /// Each worker observes the frontier of its own `control` input, which reflects the records
+    /// of all workers, and so no worker releases records at a time before every worker's
+    /// `control` records at that time have been produced.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Capture, Probe};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::gate::Gate;
+    ///
+    /// let captured = timely::execute_directly(|worker| {
+    ///     let mut data = InputHandle::<u64, char>::new();
+    ///     let mut models = InputHandle::<u64, String>::new();
+    ///     let (probe, captured) = worker.dataflow(|scope| {
+    ///         let models = scope.input_from(&mut models);
+    ///         let gated = scope.input_from(&mut data).gate(&models);
+    ///         (gated.probe(), gated.capture())
+    ///     });
+    ///
+    ///     data.send('a');
+    ///     data.advance_to(1);
+    ///     for _ in 0 .. 10 {
+    ///         worker.step();
+    ///     }
+    ///     // The model for time 0 may yet arrive, and so 'a' is held back.
+    ///     assert!(probe.less_equal(&0));
+    ///
+    ///     models.send("model".to_owned());
+    ///     models.advance_to(1);
+    ///     worker.step_while(|| probe.less_than(&1));
+    ///     captured
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec!['a'])]);
+    /// ```
+    fn gate<D2: Data>(&self, control: &Stream<S, D2>) -> Stream<S, D>;
+}
+
+impl<S: Scope, D: Data> Gate<S, D> for Stream<S, D> {
+    fn gate<D2: Data>(&self, control: &Stream<S, D2>) -> Stream<S, D> {
+        self.binary_frontier(control, Pipeline, Pipeline, "Gate", |_, _| {
+            // Records held back, by the capability for their time.
+            let mut stash: Vec<(Capability<S::Timestamp>, Vec<D>)> = Vec::new();
+            let mut vector = Vec::new();
+            let mut discard = Vec::new();
+            move |input, control, output| {
+                control.for_each(|_time, data| data.swap(&mut discard));
+                discard.clear();
+
+                input.for_each(|time, data| {
+                    if control.frontier().less_equal(time.time()) {
+                        data.swap(&mut vector);
+                        match stash.iter_mut().find(|(cap, _)| cap.time() == time.time()) {
+                            Some((_, held)) => held.append(&mut vector),
+                            None => stash.push((time.retain(), std::mem::take(&mut vector))),
+                        }
+                    }
+                    else {
+                        data.swap(&mut vector);
+                        output.session(&time).give_vec(&mut vector);
+                    }
+                });
+
+                // Release the records at times the frontier of `control` has passed.
+                let frontier = control.frontier();
+                let mut index = 0;
+                while index < stash.len() {
+                    if frontier.less_equal(stash[index].0.time()) {
+                        index += 1;
+                    }
+                    else {
+                        let (cap, mut held) = stash.swap_remove(index);
+                        output.session(&cap).give_vec(&mut held);
+                    }
+                }
+            }
+        })
+    }
+}
@@ -18,7 +18,8 @@ use crate::progress::{Timestamp, Operate, operate::SharedProgress, Antichain};
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::channels::pushers::Tee;
 use crate::dataflow::channels::pact::ParallelizationContract;
-use crate::dataflow::operators::generic::operator_info::OperatorInfo;
+use crate::dataflow::operators::capability::tracking::{self, Tracker};
+use crate::dataflow::operators::generic::operator_info::{OperatorInfo, PortInfo};
 
 /// Contains type-free information about the operator properties.
 pub struct OperatorShape {
@@ -60,6 +61,9 @@ pub struct OperatorBuilder<G: Scope> {
     address: Vec<usize>,    // path to the operator (ending with index).
     shape: OperatorShape,
     summary: Vec<Vec<Antichain<<G::Timestamp as Timestamp>::Summary>>>,
+    ports: (Vec<PortInfo>, Vec<PortInfo>),  // descriptions of the input and output ports.
+    // The worker's capability tracker, if the capabilities of the operator are to be counted.
+    pub(crate) tracker: Option<Tracker>,
 }
 
 impl<G: Scope> OperatorBuilder<G> {
@@ -72,6 +76,7 @@ impl<G: Scope> OperatorBuilder<G> {
         let mut address = scope.addr();
         address.push(index);
         let peers = scope.peers();
+        let tracker = scope.extensions().capabilities();
 
         OperatorBuilder {
             scope,
@@ -80,6 +85,8 @@ impl<G: Scope> OperatorBuilder<G> {
             address,
             shape: OperatorShape::new(name, peers),
             summary: vec![],
+            ports: (Vec::new(), Vec::new()),
+            tracker,
         }
     }
 
@@ -116,6 +123,7 @@ impl<G: Scope> OperatorBuilder<G> {
     where
         P: ParallelizationContract<G::Timestamp, D> {
 
+        self.ports.0.push(PortInfo { data: Some(std::any::type_name::<D>()), pact: Some(pact.kind()) });
         let channel_id = self.scope.new_identifier();
         let logging = self.scope.logging();
         let (sender, receiver) = pact.connect(&mut self.scope, channel_id, &self.address[..], logging);
@@ -139,6 +147,7 @@ impl<G: Scope> OperatorBuilder<G> {
     /// Adds a new output to a generic operator builder, returning the `Push` implementor to use.
     pub fn new_output_connection<D: Data>(&mut self, connection: Vec<Antichain<<G::Timestamp as Timestamp>::Summary>>) -> (Tee<G::Timestamp, D>, Stream<G, D>) {
 
+        self.ports.1.push(PortInfo { data: Some(std::any::type_name::<D>()), pact: None });
         let (targets, registrar) = Tee::<G::Timestamp,D>::new();
         let source = Source::new(self.index, self.shape.outputs);
         let stream = Stream::new(source, registrar, self.scope.clone());
@@ -160,6 +169,11 @@ impl<G: Scope> OperatorBuilder<G> {
         let inputs = self.shape.inputs;
         let outputs = self.shape.outputs;
 
+        let info = self.operator_info();
+        self.scope.topology().describe_operator(info);
+
+        let tracked = self.tracker.as_ref().map(|tracker| (0 .. outputs).map(|port| tracker.counted_output(&self.address[..], port)).collect());
+
         let operator = OperatorCore {
             shape: self.shape,
             address: self.address,
@@ -167,6 +181,7 @@ impl<G: Scope> OperatorBuilder<G> {
             logic,
             shared_progress: Rc::new(RefCell::new(SharedProgress::new(inputs, outputs))),
             summary: self.summary,
+            tracked,
         };
 
         self.scope.add_operator_with_indices(Box::new(operator), self.index, self.global);
@@ -174,7 +189,12 @@ impl<G: Scope> OperatorBuilder<G> {
 
     /// Information describing the operator.
     pub fn operator_info(&self) -> OperatorInfo {
-        OperatorInfo::new(self.index, self.global, &self.address[..])
+        OperatorInfo {
+            name: self.shape.name.clone(),
+            inputs: self.ports.0.clone(),
+            outputs: self.ports.1.clone(),
+            ..OperatorInfo::new(self.index, self.global, &self.address[..])
+        }
     }
 }
 
@@ -189,6 +209,8 @@ where
     shared_progress: Rc<RefCell<SharedProgress<T>>>,
     activations: Rc<RefCell<Activations>>,
     summary: Vec<Vec<Antichain<T::Summary>>>,
+    // The registration of each output with the worker's capability tracker, if tracking.
+    tracked: Option<Vec<tracking::Output>>,
 }
 
 impl<T, L> Schedule for OperatorCore<T, L>
@@ -200,7 +222,19 @@ where
     fn path(&self) -> &[usize] { &self.address[..] }
     fn schedule(&mut self) -> bool {
         let shared_progress = &mut *self.shared_progress.borrow_mut();
-        (self.logic)(shared_progress)
+        match self.tracked.as_ref() {
+            Some(tracked) => {
+                // Count the capability changes of this call, as earlier changes may be undrained.
+                let mut before = shared_progress.internals.clone();
+                let result = (self.logic)(shared_progress);
+                for ((output, before), after) in tracked.iter().zip(before.iter_mut()).zip(shared_progress.internals.iter_mut()) {
+                    for (time, diff) in after.iter() { output.update(time, *diff); }
+                    for (time, diff) in before.iter() { output.update(time, -*diff); }
+                }
+                result
+            },
+            None => (self.logic)(shared_progress),
+        }
     }
 }
 
@@ -225,6 +259,11 @@ where
             .iter_mut()
             .for_each(|output| output.update(T::minimum(), self.shape.peers as i64));
 
+        // Of the capabilities reserved for all workers, this worker holds one on each output.
+        for output in self.tracked.iter().flatten() {
+            output.update(&T::minimum(), 1);
+        }
+
         (self.summary.clone(), self.shared_progress.clone())
     }
 
@@ -10,14 +10,16 @@ use crate::progress::{ChangeBatch, Timestamp};
 use crate::progress::operate::SharedProgress;
 use crate::progress::frontier::{Antichain, MutableAntichain};
 
-use crate::dataflow::{Stream, Scope};
+use crate::dataflow::{Stream, Scope, ScopeParent};
 use crate::dataflow::channels::pushers::Tee;
+use crate::dataflow::channels::lineage::Tracer;
 use crate::dataflow::channels::pushers::Counter as PushCounter;
 use crate::dataflow::channels::pushers::buffer::Buffer as PushBuffer;
 use crate::dataflow::channels::pact::ParallelizationContract;
 use crate::dataflow::channels::pullers::Counter as PullCounter;
 use crate::dataflow::operators::capability::Capability;
 use crate::dataflow::operators::capability::mint as mint_capability;
+use crate::dataflow::operators::capability::tracking;
 use crate::dataflow::operators::generic::handles::{InputHandle, new_input_handle, OutputWrapper};
 use crate::dataflow::operators::generic::operator_info::OperatorInfo;
 
@@ -25,6 +27,9 @@ use crate::logging::TimelyLogger as Logger;
 
 use super::builder_raw::OperatorBuilder as OperatorBuilderRaw;
 
+// An output's `Push` implementor and stream, as returned by `OperatorBuilder::new_output`.
+type NewOutput<G, D> = (OutputWrapper<<G as ScopeParent>::Timestamp, D, Tee<<G as ScopeParent>::Timestamp, D>>, Stream<G, D>);
+
 /// Builds operators with generic shape.
 pub struct OperatorBuilder<G: Scope> {
     builder: OperatorBuilderRaw<G>,
@@ -33,6 +38,11 @@ pub struct OperatorBuilder<G: Scope> {
     internal: Rc<RefCell<Vec<Rc<RefCell<ChangeBatch<G::Timestamp>>>>>>,
     produced: Vec<Rc<RefCell<ChangeBatch<G::Timestamp>>>>,
     logging: Option<Logger>,
+    lineage: Option<Tracer>,
+    length: usize,
+    tracker: Option<tracking::Tracker>,
+    // The registration of each output with the worker's capability tracker, if tracking.
+    tracked: Vec<tracking::Output>,
 }
 
 impl<G: Scope> OperatorBuilder<G> {
@@ -40,13 +50,22 @@ impl<G: Scope> OperatorBuilder<G> {
     /// Allocates a new generic operator builder from its containing scope.
     pub fn new(name: String, scope: G) -> Self {
         let logging = scope.logging();
+        let lineage = scope.extensions().lineage();
+        let length = scope.config().batch_length();
+        let mut builder = OperatorBuilderRaw::new(name, scope);
+        // Capabilities are tracked as they are minted, rather than counted by the raw operator.
+        let tracker = builder.tracker.take();
         OperatorBuilder {
-            builder: OperatorBuilderRaw::new(name, scope),
+            builder,
             frontier: Vec::new(),
             consumed: Vec::new(),
             internal: Rc::new(RefCell::new(Vec::new())),
             produced: Vec::new(),
             logging,
+            lineage,
+            length,
+            tracker,
+            tracked: Vec::new(),
         }
     }
 
@@ -82,7 +101,7 @@ impl<G: Scope> OperatorBuilder<G> {
         self.frontier.push(MutableAntichain::new());
         self.consumed.push(input.consumed().clone());
 
-        new_input_handle(input, self.internal.clone(), self.logging.clone())
+        new_input_handle(input, self.internal.clone(), self.logging.clone(), self.tracker.clone())
     }
 
     /// Adds a new output to a generic operator builder, returning the `Push` implementor to use.
@@ -104,14 +123,87 @@ impl<G: Scope> OperatorBuilder<G> {
         let (tee, stream) = self.builder.new_output_connection(connection);
 
         let internal = Rc::new(RefCell::new(ChangeBatch::new()));
+        if let Some(tracker) = self.tracker.as_ref() {
+            self.tracked.push(tracker.register_output(&internal, &self.builder.operator_info().address[..], self.internal.borrow().len()));
+        }
         self.internal.borrow_mut().push(internal.clone());
 
-        let mut buffer = PushBuffer::new(PushCounter::new(tee));
+        let mut buffer = PushBuffer::new(PushCounter::new(tee)).with_length(self.length).with_lineage(self.lineage.clone());
         self.produced.push(buffer.inner().produced().clone());
 
         (OutputWrapper::new(buffer, internal), stream)
     }
 
+    /// Adds two new outputs to a generic operator builder, returning the `Push` implementor and stream of each.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::example(|scope| {
+    ///     let stream = (0u64..10).to_stream(scope);
+    ///     let mut builder = OperatorBuilder::new("Split".to_owned(), scope.clone());
+    ///     let mut input = builder.new_input(&stream, Pipeline);
+    ///     let ((mut evens, even_stream), (mut odds, odd_stream)) = builder.new_output_pair::<u64, String>();
+    ///     builder.build(move |_capabilities| {
+    ///         move |_frontiers| {
+    ///             let mut evens = evens.activate();
+    ///             let mut odds = odds.activate();
+    ///             input.for_each(|time, data| {
+    ///                 let even_time = time.delayed_for_output(time.time(), 0);
+    ///                 let odd_time = time.delayed_for_output(time.time(), 1);
+    ///                 for x in data.iter() {
+    ///                     if x % 2 == 0 { evens.session(&even_time).give(*x); }
+    ///                     else { odds.session(&odd_time).give(format!("{}", x)); }
+    ///                 }
+    ///             });
+    ///         }
+    ///     });
+    ///     even_stream.inspect(|x| assert_eq!(x % 2, 0));
+    ///     odd_stream.inspect(|x| println!("odd: {}", x));
+    /// });
+    /// ```
+    pub fn new_output_pair<D1: Data, D2: Data>(&mut self) -> (NewOutput<G, D1>, NewOutput<G, D2>) {
+        let connection = vec![Antichain::from_elem(Default::default()); self.builder.shape().inputs()];
+        self.new_output_pair_connection(connection.clone(), connection)
+    }
+
+    /// Adds two new outputs with connection information to a generic operator builder.
+    ///
+    /// Each output has its own `connection`, with the meaning described in `new_output_connection`.
+    pub fn new_output_pair_connection<D1: Data, D2: Data>(
+        &mut self,
+        connection1: Vec<Antichain<<G::Timestamp as Timestamp>::Summary>>,
+        connection2: Vec<Antichain<<G::Timestamp as Timestamp>::Summary>>,
+    ) -> (NewOutput<G, D1>, NewOutput<G, D2>) {
+        let output1 = self.new_output_connection(connection1);
+        let output2 = self.new_output_connection(connection2);
+        (output1, output2)
+    }
+
+    /// Adds three new outputs to a generic operator builder, returning the `Push` implementor and stream of each.
+    pub fn new_output_triple<D1: Data, D2: Data, D3: Data>(&mut self) -> (NewOutput<G, D1>, NewOutput<G, D2>, NewOutput<G, D3>) {
+        let connection = vec![Antichain::from_elem(Default::default()); self.builder.shape().inputs()];
+        self.new_output_triple_connection(connection.clone(), connection.clone(), connection)
+    }
+
+    /// Adds three new outputs with connection information to a generic operator builder.
+    ///
+    /// Each output has its own `connection`, with the meaning described in `new_output_connection`.
+    pub fn new_output_triple_connection<D1: Data, D2: Data, D3: Data>(
+        &mut self,
+        connection1: Vec<Antichain<<G::Timestamp as Timestamp>::Summary>>,
+        connection2: Vec<Antichain<<G::Timestamp as Timestamp>::Summary>>,
+        connection3: Vec<Antichain<<G::Timestamp as Timestamp>::Summary>>,
+    ) -> (NewOutput<G, D1>, NewOutput<G, D2>, NewOutput<G, D3>) {
+        let output1 = self.new_output_connection(connection1);
+        let output2 = self.new_output_connection(connection2);
+        let output3 = self.new_output_connection(connection3);
+        (output1, output2, output3)
+    }
+
     /// Creates an operator implementation from supplied logic constructor.
     pub fn build<B, L>(self, constructor: B)
     where
@@ -139,7 +231,7 @@ impl<G: Scope> OperatorBuilder<G> {
         let mut capabilities = Vec::with_capacity(self.internal.borrow().len());
         for output_index in 0  .. self.internal.borrow().len() {
             let borrow = &self.internal.borrow()[output_index];
-            capabilities.push(mint_capability(G::Timestamp::minimum(), borrow.clone()));
+            capabilities.push(mint_capability(G::Timestamp::minimum(), borrow.clone(), self.tracker.as_ref()));
             // Discard evidence of creation, as we are assumed to start with one.
             borrow.borrow_mut().clear();
         }
@@ -150,10 +242,14 @@ impl<G: Scope> OperatorBuilder<G> {
         let self_consumed = self.consumed;
         let self_internal = self.internal;
         let self_produced = self.produced;
+        let self_tracked = self.tracked;
 
         let raw_logic =
         move |progress: &mut SharedProgress<G::Timestamp>| {
 
+            // Outputs remain registered with the capability tracker as long as the operator lives.
+            let _ = &self_tracked;
+
             // drain frontier changes
             for index in 0 .. progress.frontiers.len() {
                 self_frontier[index].update_iter(progress.frontiers[index].drain());
@@ -12,7 +12,8 @@ use crate::progress::ChangeBatch;
 use crate::progress::frontier::MutableAntichain;
 use crate::dataflow::channels::pullers::Counter as PullCounter;
 use crate::dataflow::channels::pushers::Counter as PushCounter;
-use crate::dataflow::channels::pushers::buffer::{Buffer, Session};
+use crate::dataflow::channels::pushers::buffer::{Buffer, CreditedSession, Session};
+use crate::dataflow::channels::credits::Credits;
 use crate::dataflow::channels::Bundle;
 use crate::communication::{Push, Pull, message::RefOrMut};
 use crate::logging::TimelyLogger as Logger;
@@ -20,12 +21,15 @@ use crate::logging::TimelyLogger as Logger;
 use crate::dataflow::operators::CapabilityRef;
 use crate::dataflow::operators::capability::mint_ref as mint_capability_ref;
 use crate::dataflow::operators::capability::CapabilityTrait;
+use crate::dataflow::operators::capability::tracking::Tracker;
+use crate::dataflow::operators::capability::OutputCounts;
 
 /// Handle to an operator's input stream.
 pub struct InputHandle<T: Timestamp, D, P: Pull<Bundle<T, D>>> {
     pull_counter: PullCounter<T, D, P>,
-    internal: Rc<RefCell<Vec<Rc<RefCell<ChangeBatch<T>>>>>>,
+    internal: OutputCounts<T>,
     logging: Option<Logger>,
+    tracker: Option<Tracker>,
 }
 
 /// Handle to an operator's input stream and frontier.
@@ -43,14 +47,14 @@ impl<'a, T: Timestamp, D: Data, P: Pull<Bundle<T, D>>> InputHandle<T, D, P> {
     /// Returns `None` when there's no more data available.
     #[inline]
     pub fn next(&mut self) -> Option<(CapabilityRef<T>, RefOrMut<Vec<D>>)> {
-        let internal = &self.internal;
+        let (internal, tracker) = (&self.internal, &self.tracker);
         self.pull_counter.next().map(|bundle| {
             match bundle.as_ref_or_mut() {
                 RefOrMut::Ref(bundle) => {
-                    (mint_capability_ref(&bundle.time, internal.clone()), RefOrMut::Ref(&bundle.data))
+                    (mint_capability_ref(&bundle.time, internal.clone(), tracker.clone()), RefOrMut::Ref(&bundle.data))
                 },
                 RefOrMut::Mut(bundle) => {
-                    (mint_capability_ref(&bundle.time, internal.clone()), RefOrMut::Mut(&mut bundle.data))
+                    (mint_capability_ref(&bundle.time, internal.clone(), tracker.clone()), RefOrMut::Mut(&mut bundle.data))
                 },
             }
         })
@@ -77,14 +81,14 @@ impl<'a, T: Timestamp, D: Data, P: Pull<Bundle<T, D>>> InputHandle<T, D, P> {
     #[inline]
     pub fn for_each<F: FnMut(CapabilityRef<T>, RefOrMut<Vec<D>>)>(&mut self, mut logic: F) {
         // We inline `next()` so that we can use `self.logging` without cloning (and dropping) the logger.
-        let internal = &self.internal;
+        let (internal, tracker) = (&self.internal, &self.tracker);
         while let Some((cap, data)) = self.pull_counter.next().map(|bundle| {
             match bundle.as_ref_or_mut() {
                 RefOrMut::Ref(bundle) => {
-                    (mint_capability_ref(&bundle.time, internal.clone()), RefOrMut::Ref(&bundle.data))
+                    (mint_capability_ref(&bundle.time, internal.clone(), tracker.clone()), RefOrMut::Ref(&bundle.data))
                 },
                 RefOrMut::Mut(bundle) => {
-                    (mint_capability_ref(&bundle.time, internal.clone()), RefOrMut::Mut(&mut bundle.data))
+                    (mint_capability_ref(&bundle.time, internal.clone(), tracker.clone()), RefOrMut::Mut(&mut bundle.data))
                 },
             }
         }) {
@@ -149,11 +153,12 @@ pub fn _access_pull_counter<T: Timestamp, D, P: Pull<Bundle<T, D>>>(input: &mut
 
 /// Constructs an input handle.
 /// Declared separately so that it can be kept private when `InputHandle` is re-exported.
-pub fn new_input_handle<T: Timestamp, D, P: Pull<Bundle<T, D>>>(pull_counter: PullCounter<T, D, P>, internal: Rc<RefCell<Vec<Rc<RefCell<ChangeBatch<T>>>>>>, logging: Option<Logger>) -> InputHandle<T, D, P> {
+pub fn new_input_handle<T: Timestamp, D, P: Pull<Bundle<T, D>>>(pull_counter: PullCounter<T, D, P>, internal: OutputCounts<T>, logging: Option<Logger>, tracker: Option<Tracker>) -> InputHandle<T, D, P> {
     InputHandle {
         pull_counter,
         internal,
         logging,
+        tracker,
     }
 }
 
@@ -221,6 +226,16 @@ impl<'a, T: Timestamp, D, P: Push<Bundle<T, D>>> OutputHandle<'a, T, D, P> {
         assert!(cap.valid_for_output(&self.internal_buffer), "Attempted to open output session with invalid capability");
         self.push_buffer.session(cap.time())
     }
+
+    /// Obtains a session that can send data at the timestamp associated with capability `cap`,
+    /// taking a credit from `credits` for each record.
+    ///
+    /// The session refuses records once credits run out, and the operator should then yield.
+    /// See the [`credits`](crate::dataflow::channels::credits) module for an example.
+    pub fn credited_session<'b, C: CapabilityTrait<T>>(&'b mut self, cap: &'b C, credits: &'b Credits) -> CreditedSession<'b, T, D, PushCounter<T, D, P>> where 'a: 'b {
+        assert!(cap.valid_for_output(self.internal_buffer), "Attempted to open output session with invalid capability");
+        self.push_buffer.credited_session(cap.time(), credits)
+    }
 }
 
 impl<'a, T: Timestamp, D, P: Push<Bundle<T, D>>> Drop for OutputHandle<'a, T, D, P> {
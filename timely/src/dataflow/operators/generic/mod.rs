@@ -7,9 +7,11 @@ pub mod builder_raw;
 mod handles;
 mod notificator;
 mod operator_info;
+mod stateful;
 
 pub use self::handles::{InputHandle, FrontieredInputHandle, OutputHandle, OutputWrapper};
 pub use self::notificator::{Notificator, FrontierNotificator};
+pub use self::stateful::TimedState;
 
 pub use self::operator::{Operator, source};
-pub use self::operator_info::OperatorInfo;
+pub use self::operator_info::{OperatorInfo, PortInfo};
@@ -113,7 +113,7 @@ fn notificator_delivers_notifications_in_topo_order() {
 
     let mut frontier = MutableAntichain::new_bottom(Product::new(0, 0));
 
-    let root_capability = mint_capability(Product::new(0,0), Rc::new(RefCell::new(ChangeBatch::new())));
+    let root_capability = mint_capability(Product::new(0,0), Rc::new(RefCell::new(ChangeBatch::new())), None);
 
     let logging = None;//::logging::new_inactive_logger();
 
@@ -233,6 +233,8 @@ fn notificator_delivers_notifications_in_topo_order() {
 pub struct FrontierNotificator<T: Timestamp> {
     pending: Vec<(Capability<T>, u64)>,
     available: ::std::collections::BinaryHeap<OrderReversed<T>>,
+    /// Times of interest, for which no capability is held.
+    times: Vec<T>,
 }
 
 impl<T: Timestamp> FrontierNotificator<T> {
@@ -241,6 +243,7 @@ impl<T: Timestamp> FrontierNotificator<T> {
         FrontierNotificator {
             pending: Vec::new(),
             available: ::std::collections::BinaryHeap::new(),
+            times: Vec::new(),
         }
     }
 
@@ -249,6 +252,7 @@ impl<T: Timestamp> FrontierNotificator<T> {
         FrontierNotificator {
             pending: iter.into_iter().map(|x| (x,1)).collect(),
             available: ::std::collections::BinaryHeap::new(),
+            times: Vec::new(),
         }
     }
 
@@ -302,6 +306,59 @@ impl<T: Timestamp> FrontierNotificator<T> {
         }
     }
 
+    /// Requests a notification once `frontiers` have passed `time`, without holding a capability.
+    ///
+    /// Unlike `notify_at`, the request does not prevent the operator's outputs from advancing
+    /// past `time`, and the notification provides only the time. Operators that must produce
+    /// output in response should mint the capabilities they need from those they otherwise hold,
+    /// for example from an input's capability for an earlier time.
+    ///
+    /// Notifications requested this way are delivered by `for_each_time`, and not by `next` or
+    /// `for_each`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, FrontierNotificator};
+    /// use timely::dataflow::operators::generic::operator::Operator;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .unary_frontier::<u64,_,_,_>(Pipeline, "example", |_, _| {
+    ///                let mut notificator = FrontierNotificator::new();
+    ///                move |input, _output| {
+    ///                    input.for_each(|cap, _data| {
+    ///                        notificator.notify_at_time(cap.time() + 5);
+    ///                    });
+    ///                    notificator.for_each_time(&[input.frontier()], |time, _| {
+    ///                        println!("input complete through: {:?}", time);
+    ///                    });
+    ///                }
+    ///            });
+    /// });
+    /// ```
+    #[inline]
+    pub fn notify_at_time(&mut self, time: T) {
+        self.times.push(time);
+    }
+
+    /// Repeatedly calls `logic` with the times requested by `notify_at_time` that `frontiers`
+    /// have passed, in non-decreasing order and each time once.
+    #[inline]
+    pub fn for_each_time<'a, F: FnMut(T, &mut FrontierNotificator<T>)>(&mut self, frontiers: &'a [&'a MutableAntichain<T>], mut logic: F) {
+        let mut ready = Vec::new();
+        self.times.retain(|time| {
+            let passed = frontiers.iter().all(|f| !f.less_equal(time));
+            if passed { ready.push(time.clone()); }
+            !passed
+        });
+        ready.sort();
+        ready.dedup();
+        for time in ready {
+            logic(time, self);
+        }
+    }
+
     /// Enables pending notifications not in advance of any element of `frontiers`.
     pub fn make_available<'a>(&mut self, frontiers: &'a [&'a MutableAntichain<T>]) {
 
@@ -1,19 +1,23 @@
 
 //! Methods to construct generic streaming and blocking unary operators.
 
+use std::collections::BTreeMap;
+
 use crate::dataflow::channels::pushers::Tee;
-use crate::dataflow::channels::pact::ParallelizationContract;
+use crate::dataflow::channels::pact::{ParallelizationContract, Pipeline};
 
 use crate::dataflow::operators::generic::handles::{InputHandle, FrontieredInputHandle, OutputHandle};
 use crate::dataflow::operators::capability::Capability;
 
-use crate::Data;
+use crate::{Data, ExchangeData};
 
 use crate::dataflow::{Stream, Scope};
 
 use super::builder_rc::OperatorBuilder;
+use crate::dataflow::operators::Broadcast;
 use crate::dataflow::operators::generic::OperatorInfo;
 use crate::dataflow::operators::generic::notificator::{Notificator, FrontierNotificator};
+use crate::dataflow::operators::generic::stateful::TimedState;
 
 /// Methods to construct generic streaming and blocking operators.
 pub trait Operator<G: Scope, D1: Data> {
@@ -100,6 +104,49 @@ pub trait Operator<G: Scope, D1: Data> {
              P: ParallelizationContract<G::Timestamp, D1>>
              (&self, pact: P, name: &str, init: impl IntoIterator<Item=G::Timestamp>, logic: L) -> Stream<G, D2>;
 
+    /// Creates a new dataflow operator that partitions its input stream by a parallelization
+    /// strategy `pact`, and repeatedly invokes `logic`, which can read from the input stream, write
+    /// to the output stream, and register state for the times of its input.
+    ///
+    /// Registering state for a time holds a capability for the time, once however often the time
+    /// is registered. Once the input frontier passes the time, the operator calls `complete` with
+    /// the time, its state, and a buffer of records to send at the time, and then drops the
+    /// capability. Times are completed in order.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::generic::Operator;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// let sums = Arc::new(Mutex::new(Vec::new()));
+    /// let seen = sums.clone();
+    /// timely::example(move |scope| {
+    ///     (0u64..10)
+    ///         .to_stream(scope)
+    ///         .unary_stateful(Pipeline, "Sum",
+    ///             |input, _output, states| {
+    ///                 input.for_each(|time, data| {
+    ///                     *states.state(&time) += data.iter().sum::<u64>();
+    ///                 });
+    ///             },
+    ///             |_time, sum: u64, output| output.push(sum),
+    ///         )
+    ///         .inspect(move |sum| seen.lock().unwrap().push(*sum));
+    /// });
+    /// assert_eq!(*sums.lock().unwrap(), vec![45]);
+    /// ```
+    fn unary_stateful<D2, S, L, C, P>(&self, pact: P, name: &str, logic: L, complete: C) -> Stream<G, D2>
+    where
+        D2: Data,
+        S: 'static,
+        L: FnMut(&mut InputHandle<G::Timestamp, D1, P::Puller>,
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>,
+                 &mut TimedState<G::Timestamp, S>)+'static,
+        C: FnMut(&G::Timestamp, S, &mut Vec<D2>)+'static,
+        P: ParallelizationContract<G::Timestamp, D1>;
+
     /// Creates a new dataflow operator that partitions its input stream by a parallelization
     /// strategy `pact`, and repeatedly invokes `logic`, the function returned by the function passed as `constructor`.
     /// `logic` can read from the input stream, and write to the output stream.
@@ -297,6 +344,185 @@ pub trait Operator<G: Scope, D1: Data> {
         P1: ParallelizationContract<G::Timestamp, D1>,
         P2: ParallelizationContract<G::Timestamp, D2>;
 
+    /// Creates a new dataflow operator with two outputs, that partitions its input stream by a
+    /// parallelization strategy `pact`, and repeatedly invokes `logic`, the function returned by the
+    /// function passed as `constructor`.
+    /// `constructor` receives a capability for each output, and `logic` can read from the input stream
+    /// and write to both output streams.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::generic::operator::Operator;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::example(|scope| {
+    ///     let (parsed, errors) = vec!["1", "two", "3"]
+    ///         .to_stream(scope)
+    ///         .unary_with_two_outputs(Pipeline, "Parse", |_cap1, _cap2, _info| {
+    ///             move |input, parsed, errors| {
+    ///                 input.for_each(|time, data| {
+    ///                     let parsed_time = time.delayed_for_output(time.time(), 0);
+    ///                     let errors_time = time.delayed_for_output(time.time(), 1);
+    ///                     for text in data.iter() {
+    ///                         match text.parse::<u64>() {
+    ///                             Ok(number) => parsed.session(&parsed_time).give(number),
+    ///                             Err(error) => errors.session(&errors_time).give(error.to_string()),
+    ///                         }
+    ///                     }
+    ///                 });
+    ///             }
+    ///         });
+    ///     parsed.inspect(|x| println!("parsed: {}", x));
+    ///     errors.inspect(|x| println!("error: {}", x));
+    /// });
+    /// ```
+    fn unary_with_two_outputs<D2, D3, B, L, P>(&self, pact: P, name: &str, constructor: B) -> (Stream<G, D2>, Stream<G, D3>)
+    where
+        D2: Data,
+        D3: Data,
+        B: FnOnce(Capability<G::Timestamp>, Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut(&mut InputHandle<G::Timestamp, D1, P::Puller>,
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>,
+                 &mut OutputHandle<G::Timestamp, D3, Tee<G::Timestamp, D3>>)+'static,
+        P: ParallelizationContract<G::Timestamp, D1>;
+
+    /// Creates a new dataflow operator that partitions its three input streams by parallelization
+    /// strategies `pact1`, `pact2`, and `pact3`, and repeatedly invokes `logic`, the function returned
+    /// by the function passed as `constructor`.
+    /// `logic` can read from the input streams, write to the output stream, and inspect the frontier at the inputs.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::collections::HashMap;
+    /// use timely::dataflow::operators::{ToStream, Inspect, FrontierNotificator};
+    /// use timely::dataflow::operators::generic::operator::Operator;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::example(|scope| {
+    ///     let names = vec![(0u64, "zero"), (1, "one")].to_stream(scope);
+    ///     let colors = vec![(0u64, "red"), (1, "blue")].to_stream(scope);
+    ///     (0u64..2).to_stream(scope)
+    ///         .ternary_frontier(&names, &colors, Pipeline, Pipeline, Pipeline, "example", |_cap, _info| {
+    ///             let mut notificator = FrontierNotificator::new();
+    ///             let mut stash = HashMap::new();
+    ///             move |keys, names, colors, output| {
+    ///                 keys.for_each(|time, data| {
+    ///                     stash.entry(time.time().clone()).or_insert_with(|| (Vec::new(), Vec::new(), Vec::new())).0.extend(data.iter().cloned());
+    ///                     notificator.notify_at(time.retain());
+    ///                 });
+    ///                 names.for_each(|time, data| {
+    ///                     stash.entry(time.time().clone()).or_insert_with(|| (Vec::new(), Vec::new(), Vec::new())).1.extend(data.iter().cloned());
+    ///                 });
+    ///                 colors.for_each(|time, data| {
+    ///                     stash.entry(time.time().clone()).or_insert_with(|| (Vec::new(), Vec::new(), Vec::new())).2.extend(data.iter().cloned());
+    ///                 });
+    ///                 notificator.for_each(&[keys.frontier(), names.frontier(), colors.frontier()], |time, _not| {
+    ///                     if let Some((keys, names, colors)) = stash.remove(time.time()) {
+    ///                         let mut session = output.session(&time);
+    ///                         for key in keys {
+    ///                             let name = names.iter().find(|x| x.0 == key).map(|x| x.1);
+    ///                             let color = colors.iter().find(|x| x.0 == key).map(|x| x.1);
+    ///                             session.give((key, name, color));
+    ///                         }
+    ///                     }
+    ///                 });
+    ///             }
+    ///         })
+    ///         .inspect(|x| println!("{:?}", x));
+    /// });
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    fn ternary_frontier<D2, D3, D4, B, L, P1, P2, P3>(&self, other1: &Stream<G, D2>, other2: &Stream<G, D3>, pact1: P1, pact2: P2, pact3: P3, name: &str, constructor: B) -> Stream<G, D4>
+    where
+        D2: Data,
+        D3: Data,
+        D4: Data,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut(&mut FrontieredInputHandle<G::Timestamp, D1, P1::Puller>,
+                 &mut FrontieredInputHandle<G::Timestamp, D2, P2::Puller>,
+                 &mut FrontieredInputHandle<G::Timestamp, D3, P3::Puller>,
+                 &mut OutputHandle<G::Timestamp, D4, Tee<G::Timestamp, D4>>)+'static,
+        P1: ParallelizationContract<G::Timestamp, D1>,
+        P2: ParallelizationContract<G::Timestamp, D2>,
+        P3: ParallelizationContract<G::Timestamp, D3>;
+
+    /// Creates a new dataflow operator that partitions its three input streams by parallelization
+    /// strategies `pact1`, `pact2`, and `pact3`, and repeatedly invokes `logic`, the function returned
+    /// by the function passed as `constructor`.
+    /// `logic` can read from the input streams, and write to the output stream.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::generic::operator::Operator;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::example(|scope| {
+    ///     let stream2 = (10u64..20).to_stream(scope);
+    ///     let stream3 = (20u64..30).to_stream(scope);
+    ///     (0u64..10).to_stream(scope)
+    ///         .ternary(&stream2, &stream3, Pipeline, Pipeline, Pipeline, "example", |_cap, _info| {
+    ///             move |input1, input2, input3, output| {
+    ///                 input1.for_each(|time, data| output.session(&time).give_vec(&mut data.replace(Vec::new())));
+    ///                 input2.for_each(|time, data| output.session(&time).give_vec(&mut data.replace(Vec::new())));
+    ///                 input3.for_each(|time, data| output.session(&time).give_vec(&mut data.replace(Vec::new())));
+    ///             }
+    ///         })
+    ///         .inspect(|x| println!("{:?}", x));
+    /// });
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    fn ternary<D2, D3, D4, B, L, P1, P2, P3>(&self, other1: &Stream<G, D2>, other2: &Stream<G, D3>, pact1: P1, pact2: P2, pact3: P3, name: &str, constructor: B) -> Stream<G, D4>
+    where
+        D2: Data,
+        D3: Data,
+        D4: Data,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut(&mut InputHandle<G::Timestamp, D1, P1::Puller>,
+                 &mut InputHandle<G::Timestamp, D2, P2::Puller>,
+                 &mut InputHandle<G::Timestamp, D3, P3::Puller>,
+                 &mut OutputHandle<G::Timestamp, D4, Tee<G::Timestamp, D4>>)+'static,
+        P1: ParallelizationContract<G::Timestamp, D1>,
+        P2: ParallelizationContract<G::Timestamp, D2>,
+        P3: ParallelizationContract<G::Timestamp, D3>;
+
+    /// Creates a new dataflow operator with any number of inputs of the same type, and repeatedly
+    /// invokes `logic`, the function returned by the function passed as `constructor`.
+    ///
+    /// The operator's inputs are this stream, partitioned by `pact`, followed by each of `others`,
+    /// partitioned by its accompanying strategy. `logic` receives the handles of the inputs in this
+    /// order, and can read from them, write to the output stream, and inspect their frontiers.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::generic::operator::Operator;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// timely::example(|scope| {
+    ///     let others = (1 .. 5u64).map(|i| ((10 * i) .. (10 * i + 10)).to_stream(scope)).collect::<Vec<_>>();
+    ///     (0u64..10).to_stream(scope)
+    ///         .nary_frontier(Pipeline, others.into_iter().map(|s| (s, Pipeline)).collect(), "example", |_cap, _info| {
+    ///             move |inputs, output| {
+    ///                 for (index, input) in inputs.iter_mut().enumerate() {
+    ///                     input.for_each(|time, data| {
+    ///                         output.session(&time).give_iterator(data.iter().map(|x| (index, *x)));
+    ///                     });
+    ///                 }
+    ///             }
+    ///         })
+    ///         .inspect(|x| println!("{:?}", x));
+    /// });
+    /// ```
+    fn nary_frontier<D2, B, L, P>(&self, pact: P, others: Vec<(Stream<G, D1>, P)>, name: &str, constructor: B) -> Stream<G, D2>
+    where
+        D2: Data,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut(&mut [FrontieredInputHandle<G::Timestamp, D1, P::Puller>],
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>)+'static,
+        P: ParallelizationContract<G::Timestamp, D1>;
+
     /// Creates a new dataflow operator that partitions its input stream by a parallelization
     /// strategy `pact`, and repeatedly invokes the function `logic` which can read from the input stream
     /// and inspect the frontier at the input.
@@ -320,10 +546,99 @@ pub trait Operator<G: Scope, D1: Data> {
     ///         });
     /// });
     /// ```
+    ///
+    /// The input's frontier indicates when the records of a time are complete, for example to
+    /// write them to an external system only once all have arrived.
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use std::sync::{Arc, Mutex};
+    /// use timely::dataflow::operators::{ToStream, Delay};
+    /// use timely::dataflow::operators::generic::operator::Operator;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    ///
+    /// let written = Arc::new(Mutex::new(Vec::new()));
+    /// let external = written.clone();
+    ///
+    /// timely::example(move |scope| {
+    ///     let mut pending = BTreeMap::new();
+    ///     (0u64..10)
+    ///         .to_stream(scope)
+    ///         .delay(|x, _| x / 5)
+    ///         .sink(Pipeline, "flush", move |input| {
+    ///             while let Some((time, data)) = input.next() {
+    ///                 pending.entry(*time.time()).or_insert_with(Vec::new).extend(data.replace(Vec::new()));
+    ///             }
+    ///             // write out each time the frontier has passed.
+    ///             while let Some(time) = pending.keys().next().cloned().filter(|t| !input.frontier().less_equal(t)) {
+    ///                 external.lock().unwrap().push((time, pending.remove(&time).unwrap()));
+    ///             }
+    ///         });
+    /// });
+    ///
+    /// assert_eq!(*written.lock().unwrap(), vec![(0, vec![0, 1, 2, 3, 4]), (1, vec![5, 6, 7, 8, 9])]);
+    /// ```
     fn sink<L, P>(&self, pact: P, name: &str, logic: L)
     where
         L: FnMut(&mut FrontieredInputHandle<G::Timestamp, D1, P::Puller>)+'static,
         P: ParallelizationContract<G::Timestamp, D1>;
+
+    /// Creates a new dataflow operator with a data input partitioned by `pact`, and a `control`
+    /// input broadcast to all workers, and repeatedly invokes `logic`, the function returned by the
+    /// function passed as `constructor`.
+    ///
+    /// Records are buffered until the frontier of the control input has passed their time. Then
+    /// `logic` is invoked for each such time, in order, with a capability for the time, the control
+    /// records and then the data records at the time. Each control record is delivered to every
+    /// worker, before any data at the same time, and so may reconfigure the operator for that
+    /// and subsequent times.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    /// use timely::dataflow::operators::{Input, Inspect, Probe};
+    /// use timely::dataflow::operators::generic::Operator;
+    ///
+    /// timely::execute(timely::Config::process(2), |worker| {
+    ///     let mut data = InputHandle::new();
+    ///     let mut control = InputHandle::new();
+    ///     let probe = worker.dataflow(|scope| {
+    ///         let control = scope.input_from(&mut control);
+    ///         scope.input_from(&mut data)
+    ///              .unary_control(&control, Pipeline, "DynamicFilter", |_capability, _info| {
+    ///                  let mut threshold = 0;
+    ///                  move |time, control, data, output| {
+    ///                      threshold = control.drain(..).fold(threshold, u64::max);
+    ///                      output.session(time).give_iterator(data.drain(..).filter(|x| *x >= threshold));
+    ///                  }
+    ///              })
+    ///              .inspect_time(|time, x| assert!(*x >= 5 * (time + 1)))
+    ///              .probe()
+    ///     });
+    ///
+    ///     for round in 0 .. 3 {
+    ///         // one worker reconfigures the filter for all workers.
+    ///         if worker.index() == 0 {
+    ///             control.send(5 * (round + 1));
+    ///         }
+    ///         data.send_batch(&mut (0 .. 20).collect());
+    ///         data.advance_to(round + 1);
+    ///         control.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(data.time()));
+    ///     }
+    /// }).unwrap();
+    /// ```
+    fn unary_control<C, D2, B, L, P>(&self, control: &Stream<G, C>, pact: P, name: &str, constructor: B) -> Stream<G, D2>
+    where
+        C: ExchangeData,
+        D2: Data,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut(&Capability<G::Timestamp>,
+                 &mut Vec<C>,
+                 &mut Vec<D1>,
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>)+'static,
+        P: ParallelizationContract<G::Timestamp, D1>;
 }
 
 impl<G: Scope, D1: Data> Operator<G, D1> for Stream<G, D1> {
@@ -378,6 +693,31 @@ impl<G: Scope, D1: Data> Operator<G, D1> for Stream<G, D1> {
         })
     }
 
+    fn unary_stateful<D2, S, L, C, P>(&self, pact: P, name: &str, mut logic: L, mut complete: C) -> Stream<G, D2>
+    where
+        D2: Data,
+        S: 'static,
+        L: FnMut(&mut InputHandle<G::Timestamp, D1, P::Puller>,
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>,
+                 &mut TimedState<G::Timestamp, S>)+'static,
+        C: FnMut(&G::Timestamp, S, &mut Vec<D2>)+'static,
+        P: ParallelizationContract<G::Timestamp, D1> {
+
+        self.unary_frontier(pact, name, move |_capability, _info| {
+            let mut states = TimedState::new();
+            let mut buffer = Vec::new();
+            move |input, output| {
+                logic(input.handle, output, &mut states);
+                for (capability, state) in states.retire(input.frontier()) {
+                    complete(capability.time(), state, &mut buffer);
+                    if !buffer.is_empty() {
+                        output.session(&capability).give_vec(&mut buffer);
+                    }
+                }
+            }
+        })
+    }
+
     fn unary<D2, B, L, P>(&self, pact: P, name: &str, constructor: B) -> Stream<G, D2>
     where
         D2: Data,
@@ -498,6 +838,143 @@ impl<G: Scope, D1: Data> Operator<G, D1> for Stream<G, D1> {
         stream
     }
 
+    fn unary_with_two_outputs<D2, D3, B, L, P>(&self, pact: P, name: &str, constructor: B) -> (Stream<G, D2>, Stream<G, D3>)
+    where
+        D2: Data,
+        D3: Data,
+        B: FnOnce(Capability<G::Timestamp>, Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut(&mut InputHandle<G::Timestamp, D1, P::Puller>,
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>,
+                 &mut OutputHandle<G::Timestamp, D3, Tee<G::Timestamp, D3>>)+'static,
+        P: ParallelizationContract<G::Timestamp, D1> {
+
+        let mut builder = OperatorBuilder::new(name.to_owned(), self.scope());
+        let operator_info = builder.operator_info();
+
+        let mut input = builder.new_input(self, pact);
+        let ((mut output1, stream1), (mut output2, stream2)) = builder.new_output_pair();
+        builder.set_notify(false);
+
+        builder.build(move |mut capabilities| {
+            // `capabilities` should be a two-element vector.
+            let capability2 = capabilities.pop().unwrap();
+            let capability1 = capabilities.pop().unwrap();
+            let mut logic = constructor(capability1, capability2, operator_info);
+            move |_frontiers| {
+                let mut output1_handle = output1.activate();
+                let mut output2_handle = output2.activate();
+                logic(&mut input, &mut output1_handle, &mut output2_handle);
+            }
+        });
+
+        (stream1, stream2)
+    }
+
+    fn ternary_frontier<D2, D3, D4, B, L, P1, P2, P3>(&self, other1: &Stream<G, D2>, other2: &Stream<G, D3>, pact1: P1, pact2: P2, pact3: P3, name: &str, constructor: B) -> Stream<G, D4>
+    where
+        D2: Data,
+        D3: Data,
+        D4: Data,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut(&mut FrontieredInputHandle<G::Timestamp, D1, P1::Puller>,
+                 &mut FrontieredInputHandle<G::Timestamp, D2, P2::Puller>,
+                 &mut FrontieredInputHandle<G::Timestamp, D3, P3::Puller>,
+                 &mut OutputHandle<G::Timestamp, D4, Tee<G::Timestamp, D4>>)+'static,
+        P1: ParallelizationContract<G::Timestamp, D1>,
+        P2: ParallelizationContract<G::Timestamp, D2>,
+        P3: ParallelizationContract<G::Timestamp, D3> {
+
+        let mut builder = OperatorBuilder::new(name.to_owned(), self.scope());
+        let operator_info = builder.operator_info();
+
+        let mut input1 = builder.new_input(self, pact1);
+        let mut input2 = builder.new_input(other1, pact2);
+        let mut input3 = builder.new_input(other2, pact3);
+        let (mut output, stream) = builder.new_output();
+
+        builder.build(move |mut capabilities| {
+            // `capabilities` should be a single-element vector.
+            let capability = capabilities.pop().unwrap();
+            let mut logic = constructor(capability, operator_info);
+            move |frontiers| {
+                let mut input1_handle = FrontieredInputHandle::new(&mut input1, &frontiers[0]);
+                let mut input2_handle = FrontieredInputHandle::new(&mut input2, &frontiers[1]);
+                let mut input3_handle = FrontieredInputHandle::new(&mut input3, &frontiers[2]);
+                let mut output_handle = output.activate();
+                logic(&mut input1_handle, &mut input2_handle, &mut input3_handle, &mut output_handle);
+            }
+        });
+
+        stream
+    }
+
+    fn ternary<D2, D3, D4, B, L, P1, P2, P3>(&self, other1: &Stream<G, D2>, other2: &Stream<G, D3>, pact1: P1, pact2: P2, pact3: P3, name: &str, constructor: B) -> Stream<G, D4>
+    where
+        D2: Data,
+        D3: Data,
+        D4: Data,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut(&mut InputHandle<G::Timestamp, D1, P1::Puller>,
+                 &mut InputHandle<G::Timestamp, D2, P2::Puller>,
+                 &mut InputHandle<G::Timestamp, D3, P3::Puller>,
+                 &mut OutputHandle<G::Timestamp, D4, Tee<G::Timestamp, D4>>)+'static,
+        P1: ParallelizationContract<G::Timestamp, D1>,
+        P2: ParallelizationContract<G::Timestamp, D2>,
+        P3: ParallelizationContract<G::Timestamp, D3> {
+
+        let mut builder = OperatorBuilder::new(name.to_owned(), self.scope());
+        let operator_info = builder.operator_info();
+
+        let mut input1 = builder.new_input(self, pact1);
+        let mut input2 = builder.new_input(other1, pact2);
+        let mut input3 = builder.new_input(other2, pact3);
+        let (mut output, stream) = builder.new_output();
+        builder.set_notify(false);
+
+        builder.build(move |mut capabilities| {
+            // `capabilities` should be a single-element vector.
+            let capability = capabilities.pop().unwrap();
+            let mut logic = constructor(capability, operator_info);
+            move |_frontiers| {
+                let mut output_handle = output.activate();
+                logic(&mut input1, &mut input2, &mut input3, &mut output_handle);
+            }
+        });
+
+        stream
+    }
+
+    fn nary_frontier<D2, B, L, P>(&self, pact: P, others: Vec<(Stream<G, D1>, P)>, name: &str, constructor: B) -> Stream<G, D2>
+    where
+        D2: Data,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut(&mut [FrontieredInputHandle<G::Timestamp, D1, P::Puller>],
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>)+'static,
+        P: ParallelizationContract<G::Timestamp, D1> {
+
+        let mut builder = OperatorBuilder::new(name.to_owned(), self.scope());
+        let operator_info = builder.operator_info();
+
+        let mut inputs = vec![builder.new_input(self, pact)];
+        for (stream, pact) in others {
+            inputs.push(builder.new_input(&stream, pact));
+        }
+        let (mut output, stream) = builder.new_output();
+
+        builder.build(move |mut capabilities| {
+            // `capabilities` should be a single-element vector.
+            let capability = capabilities.pop().unwrap();
+            let mut logic = constructor(capability, operator_info);
+            move |frontiers| {
+                let mut handles = inputs.iter_mut().zip(frontiers.iter()).map(|(input, frontier)| FrontieredInputHandle::new(input, frontier)).collect::<Vec<_>>();
+                let mut output_handle = output.activate();
+                logic(&mut handles[..], &mut output_handle);
+            }
+        });
+
+        stream
+    }
+
     fn sink<L, P>(&self, pact: P, name: &str, mut logic: L)
     where
         L: FnMut(&mut FrontieredInputHandle<G::Timestamp, D1, P::Puller>)+'static,
@@ -513,6 +990,55 @@ impl<G: Scope, D1: Data> Operator<G, D1> for Stream<G, D1> {
             }
         });
     }
+
+    fn unary_control<C, D2, B, L, P>(&self, control: &Stream<G, C>, pact: P, name: &str, constructor: B) -> Stream<G, D2>
+    where
+        C: ExchangeData,
+        D2: Data,
+        B: FnOnce(Capability<G::Timestamp>, OperatorInfo) -> L,
+        L: FnMut(&Capability<G::Timestamp>,
+                 &mut Vec<C>,
+                 &mut Vec<D1>,
+                 &mut OutputHandle<G::Timestamp, D2, Tee<G::Timestamp, D2>>)+'static,
+        P: ParallelizationContract<G::Timestamp, D1> {
+
+        let mut builder = OperatorBuilder::new(name.to_owned(), self.scope());
+        let operator_info = builder.operator_info();
+
+        let mut control_input = builder.new_input(&control.broadcast(), Pipeline);
+        let mut data_input = builder.new_input(self, pact);
+        let (mut output, stream) = builder.new_output();
+
+        builder.build(move |mut capabilities| {
+            // `capabilities` should be a single-element vector.
+            let capability = capabilities.pop().unwrap();
+            let mut logic = constructor(capability, operator_info);
+
+            // control and data records, and a capability, for each time not yet passed by control.
+            let mut pending = BTreeMap::<G::Timestamp, (Capability<G::Timestamp>, Vec<C>, Vec<D1>)>::new();
+            move |frontiers| {
+                control_input.for_each(|time, data| {
+                    pending.entry(time.time().clone())
+                           .or_insert_with(|| (time.retain(), Vec::new(), Vec::new()))
+                           .1.extend(data.replace(Vec::new()));
+                });
+                data_input.for_each(|time, data| {
+                    pending.entry(time.time().clone())
+                           .or_insert_with(|| (time.retain(), Vec::new(), Vec::new()))
+                           .2.extend(data.replace(Vec::new()));
+                });
+
+                let ready = pending.keys().filter(|time| !frontiers[0].less_equal(time)).cloned().collect::<Vec<_>>();
+                let mut output_handle = output.activate();
+                for time in ready {
+                    let (capability, mut control, mut data) = pending.remove(&time).unwrap();
+                    logic(&capability, &mut control, &mut data, &mut output_handle);
+                }
+            }
+        });
+
+        stream
+    }
 }
 
 /// Creates a new data stream source for a scope.
@@ -1,6 +1,6 @@
 
 /// Information about the operator being constructed
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct OperatorInfo {
     /// Scope-local index assigned to the operator being constructed.
     pub local_id: usize,
@@ -8,6 +8,12 @@ pub struct OperatorInfo {
     pub global_id: usize,
     /// Operator address.
     pub address: Vec<usize>,
+    /// Operator name.
+    pub name: String,
+    /// The input ports of the operator, in order.
+    pub inputs: Vec<PortInfo>,
+    /// The output ports of the operator, in order.
+    pub outputs: Vec<PortInfo>,
 }
 
 impl OperatorInfo {
@@ -17,6 +23,25 @@ impl OperatorInfo {
             local_id,
             global_id,
             address: address.to_vec(),
+            name: String::new(),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
         }
     }
 }
+
+/// Information about an input or output port of an operator.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PortInfo {
+    /// The name of the type of the port's records, if known.
+    pub data: Option<&'static str>,
+    /// For inputs, the kind of parallelization contract, "Pipeline" or "Exchange", if known.
+    pub pact: Option<&'static str>,
+}
+
+impl PortInfo {
+    /// A port whose record type and parallelization contract are not known.
+    pub fn unknown() -> Self {
+        PortInfo { data: None, pact: None }
+    }
+}
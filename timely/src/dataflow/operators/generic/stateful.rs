@@ -0,0 +1,69 @@
+//! Per-time operator state, holding one capability for each time with state.
+
+use std::collections::BTreeMap;
+
+use crate::progress::Timestamp;
+use crate::progress::frontier::MutableAntichain;
+use crate::dataflow::operators::{Capability, CapabilityRef};
+
+/// State an operator holds for each of its pending times, with a capability for each time.
+///
+/// Registering state for a time retains a capability for the time, once, however often the time
+/// is registered. The state and its capability are retired together once the input frontier
+/// passes the time, so that the operator can neither drop the capability while it holds state
+/// for the time, nor hold it once the state is retired.
+///
+/// `TimedState` is used by [`Operator::unary_stateful`](super::Operator::unary_stateful), which
+/// retires states as its input frontier advances.
+pub struct TimedState<T: Timestamp, S> {
+    states: BTreeMap<T, (Capability<T>, S)>,
+}
+
+impl<T: Timestamp, S> TimedState<T, S> {
+    /// Allocates an empty `TimedState`.
+    pub fn new() -> Self {
+        TimedState { states: BTreeMap::new() }
+    }
+
+    /// The state for the time of `time`, created by `init` and holding a capability for the time
+    /// if the time has no state.
+    pub fn state_with<F: FnOnce()->S>(&mut self, time: &CapabilityRef<T>, init: F) -> &mut S {
+        &mut self.states
+            .entry(time.time().clone())
+            .or_insert_with(|| (time.delayed(time.time()), init()))
+            .1
+    }
+
+    /// The state for the time of `time`, created as the default state if the time has no state.
+    pub fn state(&mut self, time: &CapabilityRef<T>) -> &mut S where S: Default {
+        self.state_with(time, Default::default)
+    }
+
+    /// The state for `time`, if any.
+    pub fn get(&self, time: &T) -> Option<&S> {
+        self.states.get(time).map(|(_, state)| state)
+    }
+
+    /// The number of times with state.
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Indicates that no time has state.
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    /// Removes and returns, in order of their times, the states of times `frontier` has passed,
+    /// each with the capability held for its time.
+    pub fn retire(&mut self, frontier: &MutableAntichain<T>) -> Vec<(Capability<T>, S)> {
+        let retired = self.states.keys().filter(|time| !frontier.less_equal(time)).cloned().collect::<Vec<_>>();
+        retired.iter().map(|time| self.states.remove(time).expect("time has state")).collect()
+    }
+}
+
+impl<T: Timestamp, S> Default for TimedState<T, S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,7 +1,7 @@
 //! Create new `Streams` connected to external inputs.
 
 use std::rc::Rc;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 
 use crate::scheduling::{Schedule, Activator};
 
@@ -12,7 +12,8 @@ use crate::progress::Source;
 use crate::Data;
 use crate::communication::Push;
 use crate::dataflow::{Stream, ScopeParent, Scope};
-use crate::dataflow::channels::{Message, pushers::{Tee, Counter}};
+use crate::dataflow::channels::{Message, lineage::{Lineage, Tracer}, pushers::{Tee, Counter}};
+use crate::dataflow::operators::capability::tracking;
 
 // TODO : This is an exogenous input, but it would be nice to wrap a Subgraph in something
 // TODO : more like a harness, with direct access to its inputs.
@@ -91,7 +92,7 @@ pub trait Input : Scope {
     fn input_from<D: Data>(&mut self, handle: &mut Handle<<Self as ScopeParent>::Timestamp, D>) -> Stream<Self, D>;
 }
 
-use crate::order::TotalOrder;
+use crate::order::{PartialOrder, TotalOrder};
 impl<G: Scope> Input for G where <G as ScopeParent>::Timestamp: TotalOrder {
     fn new_input<D: Data>(&mut self) -> (Handle<<G as ScopeParent>::Timestamp, D>, Stream<G, D>) {
         let mut handle = Handle::new();
@@ -106,25 +107,60 @@ impl<G: Scope> Input for G where <G as ScopeParent>::Timestamp: TotalOrder {
         let produced = counter.produced().clone();
 
         let index = self.allocate_operator_index();
+        // Batches originate at the input operator, whose identifier is needed only to trace them.
+        let tracer = self.extensions().lineage();
+        let global = tracer.as_ref().map(|_| self.new_identifier());
         let mut address = self.addr();
         address.push(index);
 
-        handle.activate.push(self.activator_for(&address[..]));
-
         let progress = Rc::new(RefCell::new(ChangeBatch::new()));
 
-        handle.register(counter, progress.clone());
+        // Resume from the frontier of the checkpoint the dataflow is restored from, if any. The
+        // frontier is that of a totally ordered timestamp, and so has at most one element; an
+        // empty frontier reflects all of the input in the restored state, and closes the input.
+        let restoring = self.checkpoints().restoring::<<G as ScopeParent>::Timestamp>(address[0]);
+        let resume = restoring.map(|(_, frontier)| {
+            frontier.elements().iter().fold(None, |least: Option<&G::Timestamp>, time| {
+                match least {
+                    Some(least) if least.less_equal(time) => Some(least),
+                    _ => Some(time),
+                }
+            }).cloned()
+        });
+
+        let scheduled = if let Some(None) = resume {
+            progress.borrow_mut().update(<G as ScopeParent>::Timestamp::minimum(), -1);
+            self.activator_for(&address[..]).activate();
+            Rc::new(Cell::new(false))
+        }
+        else {
+            handle.activate.push(self.activator_for(&address[..]));
+            handle.register(counter, progress.clone(), tracer.zip(global), self.config().batch_length());
+            if let Some(Some(time)) = resume {
+                if handle.now_at.less_than(&time) {
+                    handle.advance_to(time);
+                }
+            }
+            handle.scheduled.clone()
+        };
 
         let copies = self.peers();
+        let tracked = self.extensions().capabilities().map(|tracker| tracker.counted_output(&address[..], 0));
 
-        self.add_operator_with_index(Box::new(Operator {
+        let operator = Box::new(Operator {
             name: "Input".to_owned(),
             address,
             shared_progress: Rc::new(RefCell::new(SharedProgress::new(0, 1))),
             progress,
             messages: produced,
             copies,
-        }), index);
+            scheduled,
+            tracked,
+        });
+        match global {
+            Some(global) => self.add_operator_with_indices(operator, index, global),
+            None => self.add_operator_with_index(operator, index),
+        }
 
         Stream::new(Source::new(index, 0), registrar, self.clone())
     }
@@ -137,6 +173,8 @@ struct Operator<T:Timestamp> {
     progress:   Rc<RefCell<ChangeBatch<T>>>,           // times closed since last asked
     messages:   Rc<RefCell<ChangeBatch<T>>>,           // messages sent since last asked
     copies:     usize,
+    scheduled:  Rc<Cell<bool>>,                        // set when scheduled, for coalesced advances
+    tracked:    Option<tracking::Output>,              // registration with the capability tracker
 }
 
 impl<T:Timestamp> Schedule for Operator<T> {
@@ -147,8 +185,12 @@ impl<T:Timestamp> Schedule for Operator<T> {
 
     fn schedule(&mut self) -> bool {
         let shared_progress = &mut *self.shared_progress.borrow_mut();
+        if let Some(tracked) = self.tracked.as_ref() {
+            for (time, diff) in self.progress.borrow_mut().iter() { tracked.update(time, *diff); }
+        }
         self.progress.borrow_mut().drain_into(&mut shared_progress.internals[0]);
         self.messages.borrow_mut().drain_into(&mut shared_progress.produceds[0]);
+        self.scheduled.set(true);
         false
     }
 }
@@ -160,6 +202,10 @@ impl<T:Timestamp> Operate<T> for Operator<T> {
 
     fn get_internal_summary(&mut self) -> (Vec<Vec<Antichain<<T as Timestamp>::Summary>>>, Rc<RefCell<SharedProgress<T>>>) {
         self.shared_progress.borrow_mut().internals[0].update(T::minimum(), self.copies as i64);
+        // Of the copies held for all workers, this worker's handle holds one.
+        if let Some(tracked) = self.tracked.as_ref() {
+            tracked.update(&T::minimum(), 1);
+        }
         (Vec::new(), self.shared_progress.clone())
     }
 
@@ -173,9 +219,16 @@ pub struct Handle<T: Timestamp, D: Data> {
     activate: Vec<Activator>,
     progress: Vec<Rc<RefCell<ChangeBatch<T>>>>,
     pushers: Vec<Counter<T, D, Tee<T, D>>>,
+    // The lineage state of the worker and the identifier of each input operator, if traced.
+    origins: Vec<Option<(Tracer, usize)>>,
     buffer1: Vec<D>,
     buffer2: Vec<D>,
+    length: usize,
     now_at: T,
+    // Whether an input operator has been scheduled since the epoch last changed.
+    scheduled: Rc<Cell<bool>>,
+    // The epoch requested by `advance_to_coalesced`, if not yet applied.
+    coalesced: Option<T>,
 }
 
 impl<T:Timestamp, D: Data> Handle<T, D> {
@@ -211,9 +264,13 @@ impl<T:Timestamp, D: Data> Handle<T, D> {
             activate: Vec::new(),
             progress: Vec::new(),
             pushers: Vec::new(),
+            origins: Vec::new(),
             buffer1: Vec::with_capacity(Message::<T, D>::default_length()),
             buffer2: Vec::with_capacity(Message::<T, D>::default_length()),
+            length: Message::<T, D>::default_length(),
             now_at: T::minimum(),
+            scheduled: Rc::new(Cell::new(false)),
+            coalesced: None,
         }
     }
 
@@ -254,11 +311,20 @@ impl<T:Timestamp, D: Data> Handle<T, D> {
     fn register(
         &mut self,
         pusher: Counter<T, D, Tee<T, D>>,
-        progress: Rc<RefCell<ChangeBatch<T>>>
+        progress: Rc<RefCell<ChangeBatch<T>>>,
+        origin: Option<(Tracer, usize)>,
+        length: usize,
     ) {
         // flush current contents, so new registrant does not see existing data.
         if !self.buffer1.is_empty() { self.flush(); }
 
+        // batch records as the worker is configured to.
+        if self.length != length {
+            self.length = length;
+            self.buffer1 = Vec::with_capacity(length);
+            self.buffer2 = Vec::with_capacity(length);
+        }
+
         // we need to produce an appropriate update to the capabilities for `progress`, in case a
         // user has decided to drive the handle around a bit before registering it.
         progress.borrow_mut().update(T::minimum(), -1);
@@ -266,6 +332,12 @@ impl<T:Timestamp, D: Data> Handle<T, D> {
 
         self.progress.push(progress);
         self.pushers.push(pusher);
+        self.origins.push(origin);
+    }
+
+    // A new lineage for a batch sent to the input operator `index`, if traced.
+    fn originate(&self, index: usize) -> Option<Lineage> {
+        self.origins[index].as_ref().map(|(tracer, operator)| tracer.originate(*operator))
     }
 
     // flushes our buffer at each of the destinations. there can be more than one; clone if needed.
@@ -274,11 +346,13 @@ impl<T:Timestamp, D: Data> Handle<T, D> {
         for index in 0 .. self.pushers.len() {
             if index < self.pushers.len() - 1 {
                 self.buffer2.extend_from_slice(&self.buffer1[..]);
-                Message::push_at(&mut self.buffer2, self.now_at.clone(), &mut self.pushers[index]);
+                let lineage = self.originate(index);
+                Message::push_at_traced(&mut self.buffer2, self.now_at.clone(), lineage, &mut self.pushers[index], self.length);
                 debug_assert!(self.buffer2.is_empty());
             }
             else {
-                Message::push_at(&mut self.buffer1, self.now_at.clone(), &mut self.pushers[index]);
+                let lineage = self.originate(index);
+                Message::push_at_traced(&mut self.buffer1, self.now_at.clone(), lineage, &mut self.pushers[index], self.length);
                 debug_assert!(self.buffer1.is_empty());
             }
         }
@@ -303,6 +377,7 @@ impl<T:Timestamp, D: Data> Handle<T, D> {
     #[inline]
     /// Sends one record into the corresponding timely dataflow `Stream`, at the current epoch.
     pub fn send(&mut self, data: D) {
+        if self.coalesced.is_some() && self.scheduled.get() { self.apply_coalesced(); }
         // assert!(self.buffer1.capacity() == Message::<T, D>::default_length());
         self.buffer1.push(data);
         if self.buffer1.len() == self.buffer1.capacity() {
@@ -313,8 +388,31 @@ impl<T:Timestamp, D: Data> Handle<T, D> {
     /// Sends a batch of records into the corresponding timely dataflow `Stream`, at the current epoch.
     ///
     /// This method flushes single elements previously sent with `send`, to keep the insertion order.
+    /// The contents of `buffer` are moved into the dataflow without copying, except when the input
+    /// has multiple streams, and `buffer` is left empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Inspect};
+    /// use timely::dataflow::operators::input::Handle;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let mut input = Handle::new();
+    ///     worker.dataflow(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .inspect(|x| println!("hello {:?}", x));
+    ///     });
+    ///
+    ///     let mut batch = (0 .. 1000).collect::<Vec<_>>();
+    ///     input.send_batch(&mut batch);
+    ///     assert!(batch.is_empty());
+    ///     input.advance_to(1);
+    /// }).unwrap();
+    /// ```
     pub fn send_batch(&mut self, buffer: &mut Vec<D>) {
 
+        if self.coalesced.is_some() && self.scheduled.get() { self.apply_coalesced(); }
+
         if !buffer.is_empty() {
             // flush buffered elements to ensure local fifo.
             if !self.buffer1.is_empty() { self.flush(); }
@@ -323,11 +421,13 @@ impl<T:Timestamp, D: Data> Handle<T, D> {
             for index in 0 .. self.pushers.len() {
                 if index < self.pushers.len() - 1 {
                     self.buffer2.extend_from_slice(&buffer[..]);
-                    Message::push_at(&mut self.buffer2, self.now_at.clone(), &mut self.pushers[index]);
+                    let lineage = self.originate(index);
+                    Message::push_at_traced(&mut self.buffer2, self.now_at.clone(), lineage, &mut self.pushers[index], self.length);
                     assert!(self.buffer2.is_empty());
                 }
                 else {
-                    Message::push_at(buffer, self.now_at.clone(), &mut self.pushers[index]);
+                    let lineage = self.originate(index);
+                    Message::push_at_traced(buffer, self.now_at.clone(), lineage, &mut self.pushers[index], self.length);
                     assert!(buffer.is_empty());
                 }
             }
@@ -335,23 +435,107 @@ impl<T:Timestamp, D: Data> Handle<T, D> {
         }
     }
 
+    /// Sends the records of an iterator into the corresponding timely dataflow `Stream`, at the current epoch.
+    ///
+    /// Records are collected into full-sized batches, rather than sent one by one.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Inspect};
+    /// use timely::dataflow::operators::input::Handle;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let mut input = Handle::new();
+    ///     worker.dataflow(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .inspect(|x| println!("hello {:?}", x));
+    ///     });
+    ///
+    ///     input.send_iter(0 .. 1000);
+    ///     input.advance_to(1);
+    /// }).unwrap();
+    /// ```
+    pub fn send_iter<I: IntoIterator<Item=D>>(&mut self, iter: I) {
+        for data in iter {
+            self.send(data);
+        }
+    }
+
     /// Advances the current epoch to `next`.
     ///
     /// This method allows timely dataflow to issue progress notifications as it can now determine
     /// that this input can no longer produce data at earlier timestamps.
     pub fn advance_to(&mut self, next: T) {
-        // Assert that we do not rewind time.
+        // Assert that we do not rewind time, including requested but coalesced time.
         assert!(self.now_at.less_equal(&next));
+        if let Some(coalesced) = self.coalesced.take() {
+            assert!(coalesced.less_equal(&next));
+        }
         // Flush buffers if time has actually changed.
         if !self.now_at.eq(&next) {
             self.close_epoch();
             self.now_at = next;
+            self.scheduled.set(false);
             for progress in self.progress.iter() {
                 progress.borrow_mut().update(self.now_at.clone(), 1);
             }
         }
     }
 
+    /// Advances the epoch to `next`, coalescing with other advances before the worker next steps.
+    ///
+    /// The first advance after the input's operators have been scheduled takes effect at once,
+    /// as `advance_to` would. Later advances before they are next scheduled are only recorded,
+    /// and records sent meanwhile are sent at the current epoch, reported by `time`, rather
+    /// than at the requested epoch. The requested epoch takes effect when the handle next
+    /// advances or sends records after the input's operators have been scheduled, and so a
+    /// driver loop that advances the epoch many times between worker steps introduces one epoch
+    /// per step, rather than one for each advance, with less progress traffic as a result.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Probe};
+    /// use timely::dataflow::operators::input::Handle;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let mut input = Handle::new();
+    ///     let probe = worker.dataflow(|scope| scope.input_from(&mut input).probe());
+    ///
+    ///     input.advance_to_coalesced(1);
+    ///     for tick in 0 .. 100u64 {
+    ///         input.send(tick);
+    ///         input.advance_to_coalesced(tick + 2);
+    ///     }
+    ///     // The advance to 1 has not yet taken effect, and all records are at 0.
+    ///     assert_eq!(input.time(), &0);
+    ///
+    ///     worker.step();
+    ///     input.advance_to_coalesced(102);
+    ///     assert_eq!(input.time(), &102);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    /// }).unwrap();
+    /// ```
+    pub fn advance_to_coalesced(&mut self, next: T) {
+        assert!(self.coalesced.as_ref().unwrap_or(&self.now_at).less_equal(&next));
+        let deferred = self.coalesced.replace(next).is_some();
+        if self.scheduled.get() {
+            self.apply_coalesced();
+        }
+        else if !deferred {
+            // Have the input's operators scheduled, after which the advance can take effect.
+            for activate in self.activate.iter() {
+                activate.activate();
+            }
+        }
+    }
+
+    // Advances to the epoch requested by `advance_to_coalesced`.
+    fn apply_coalesced(&mut self) {
+        if let Some(next) = self.coalesced.clone() {
+            self.advance_to(next);
+        }
+    }
+
     /// Closes the input.
     ///
     /// This method allows timely dataflow to issue all progress notifications blocked by this input
@@ -367,6 +551,236 @@ impl<T:Timestamp, D: Data> Handle<T, D> {
     pub fn time(&self) -> &T {
         &self.now_at
     }
+
+    /// Bounds the input at `time`, after which it closes itself.
+    ///
+    /// The returned input accepts records only at times not greater or equal to `time`, and
+    /// closes once advanced to such a time, so that the computation may complete even if the
+    /// input is never explicitly closed.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Input, Probe};
+    /// use timely::dataflow::operators::input::Handle;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///
+    ///     let mut input = Handle::new();
+    ///     let probe = worker.dataflow(|scope| scope.input_from(&mut input).probe());
+    ///     let mut input = input.close_at(3);
+    ///
+    ///     for round in 0 .. 10u64 {
+    ///         let sent = input.send(round);
+    ///         assert_eq!(sent.is_ok(), round < 3);
+    ///         input.advance_to(round + 1);
+    ///     }
+    ///     assert!(input.is_closed());
+    ///
+    ///     // completes, although `input` is still in scope.
+    ///     worker.step_while(|| !probe.done());
+    /// }).unwrap();
+    /// ```
+    pub fn close_at(self, time: T) -> BoundedInput<T, D> {
+        BoundedInput::new(self, time)
+    }
+}
+
+/// An input handle that closes itself once advanced to a final timestamp.
+///
+/// Constructed by `Handle::close_at`.
+pub struct BoundedInput<T: Timestamp, D: Data> {
+    handle: Option<Handle<T, D>>,
+    final_time: T,
+}
+
+impl<T: Timestamp, D: Data> BoundedInput<T, D> {
+
+    fn new(handle: Handle<T, D>, final_time: T) -> Self {
+        let mut bounded = BoundedInput { handle: Some(handle), final_time };
+        let now_at = bounded.time().clone();
+        bounded.advance_to(now_at);
+        bounded
+    }
+
+    /// Sends one record at the current epoch, or returns it if the input has closed.
+    pub fn send(&mut self, data: D) -> Result<(), D> {
+        match self.handle.as_mut() {
+            Some(handle) => { handle.send(data); Ok(()) },
+            None => Err(data),
+        }
+    }
+
+    /// Sends a batch of records at the current epoch, or returns them if the input has closed.
+    pub fn send_batch(&mut self, buffer: &mut Vec<D>) -> Result<(), Vec<D>> {
+        match self.handle.as_mut() {
+            Some(handle) => { handle.send_batch(buffer); Ok(()) },
+            None => Err(::std::mem::take(buffer)),
+        }
+    }
+
+    /// Advances the current epoch to `next`, and closes the input if `next` reaches the final timestamp.
+    ///
+    /// Once the input has closed, this method has no effect.
+    pub fn advance_to(&mut self, next: T) {
+        if self.final_time.less_equal(&next) {
+            self.handle = None;
+        }
+        else if let Some(handle) = self.handle.as_mut() {
+            handle.advance_to(next);
+        }
+    }
+
+    /// Closes the input, before it reaches the final timestamp.
+    pub fn close(self) { }
+
+    /// Reports whether the input has closed.
+    pub fn is_closed(&self) -> bool {
+        self.handle.is_none()
+    }
+
+    /// Reports the final timestamp.
+    pub fn final_time(&self) -> &T {
+        &self.final_time
+    }
+
+    /// Reports the current timestamp, which is the final timestamp once the input has closed.
+    pub fn time(&self) -> &T {
+        self.handle.as_ref().map(|handle| handle.time()).unwrap_or(&self.final_time)
+    }
+}
+
+/// An input handle paired with probes of the streams it feeds, which drives a worker until
+/// those streams have caught up with the input.
+///
+/// A driver loop that sends records and advances the input without waiting for the dataflow
+/// buffers records for epochs the dataflow has yet to process, without bound. The session
+/// tracks the epochs it has advanced past, and its `flush_until_caught_up` and
+/// `flush_until_within` methods step the worker, parking it while it awaits messages, until at
+/// most a given number of those epochs remain incomplete at any probe.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::InputSession;
+/// use timely::dataflow::operators::{Exchange, Input, Probe};
+///
+/// timely::execute(timely::Config::process(2), |worker| {
+///     let mut session = InputSession::new();
+///     let probe = worker.dataflow(|scope| {
+///         scope.input_from(session.handle())
+///              .exchange(|x: &u64| *x)
+///              .probe()
+///     });
+///     session.add_probe(probe);
+///
+///     for round in 0 .. 100u64 {
+///         session.send(round);
+///         session.advance_to(round + 1);
+///         // Allow the dataflow to lag the input by at most three epochs.
+///         session.flush_until_within(worker, 3);
+///         assert!(session.outstanding() <= 3);
+///     }
+///     session.flush_until_caught_up(worker);
+///     assert!(session.caught_up());
+/// }).unwrap();
+/// ```
+pub struct InputSession<T: Timestamp, D: Data> {
+    handle: Handle<T, D>,
+    probes: Vec<crate::dataflow::ProbeHandle<T>>,
+    // Epochs advanced past, in order, which may yet be incomplete at some probe.
+    outstanding: std::collections::VecDeque<T>,
+}
+
+impl<T: Timestamp, D: Data> InputSession<T, D> {
+
+    /// Allocates a new input session, with a new input handle and no probes.
+    pub fn new() -> Self {
+        Self::from_handle(Handle::new())
+    }
+
+    /// Pairs an existing input handle with probes to be added.
+    pub fn from_handle(handle: Handle<T, D>) -> Self {
+        InputSession {
+            handle,
+            probes: Vec::new(),
+            outstanding: Default::default(),
+        }
+    }
+
+    /// The input handle, from which to create streams.
+    pub fn handle(&mut self) -> &mut Handle<T, D> {
+        &mut self.handle
+    }
+
+    /// Adds a probe the session waits for, typically of a stream fed by the input.
+    pub fn add_probe(&mut self, probe: crate::dataflow::ProbeHandle<T>) {
+        self.probes.push(probe);
+    }
+
+    /// Sends one record at the current epoch.
+    pub fn send(&mut self, data: D) {
+        self.handle.send(data);
+    }
+
+    /// Sends a batch of records at the current epoch, leaving `buffer` empty.
+    pub fn send_batch(&mut self, buffer: &mut Vec<D>) {
+        self.handle.send_batch(buffer);
+    }
+
+    /// Advances the current epoch to `next`.
+    pub fn advance_to(&mut self, next: T) {
+        if self.handle.time() != &next {
+            self.outstanding.push_back(self.handle.time().clone());
+        }
+        self.handle.advance_to(next);
+    }
+
+    /// Reports the current epoch.
+    pub fn time(&self) -> &T {
+        self.handle.time()
+    }
+
+    /// Reports the number of epochs advanced past that are incomplete at some probe.
+    ///
+    /// A probe whose dataflow has yet to be scheduled reports no incomplete epochs.
+    pub fn outstanding(&mut self) -> usize {
+        let probes = &self.probes;
+        self.outstanding.retain(|epoch| probes.iter().any(|probe| probe.less_equal(epoch)));
+        self.outstanding.len()
+    }
+
+    /// Reports whether each probe has completed all epochs before the current epoch.
+    pub fn caught_up(&mut self) -> bool {
+        self.outstanding() == 0
+    }
+
+    /// Steps `worker` until each probe has completed all epochs before the current epoch.
+    ///
+    /// Returns early if the worker has no dataflows left to step.
+    pub fn flush_until_caught_up<A: crate::communication::Allocate>(&mut self, worker: &mut crate::worker::Worker<A>) {
+        self.flush_until_within(worker, 0);
+    }
+
+    /// Steps `worker` until at most `epochs` epochs advanced past are incomplete at some probe.
+    ///
+    /// The worker steps at least once, as probes report the frontiers of their streams only once
+    /// their dataflows have been scheduled. Returns early if the worker has no dataflows left to
+    /// step.
+    pub fn flush_until_within<A: crate::communication::Allocate>(&mut self, worker: &mut crate::worker::Worker<A>, epochs: usize) {
+        if worker.step() {
+            while self.outstanding() > epochs && worker.step_or_park(None) { }
+        }
+    }
+
+    /// Closes the input, returning the probes.
+    pub fn close(self) -> Vec<crate::dataflow::ProbeHandle<T>> {
+        self.probes
+    }
+}
+
+impl<T: Timestamp, D: Data> Default for InputSession<T, D> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T:Timestamp, D: Data> Drop for Handle<T, D> {
@@ -1,6 +1,7 @@
 //! Extension trait and implementation for observing and action on streamed data.
 
 use crate::Data;
+use crate::progress::{Antichain, frontier::AntichainRef};
 use crate::dataflow::channels::pact::Pipeline;
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::operators::generic::Operator;
@@ -55,6 +56,55 @@ pub trait Inspect<G: Scope, D: Data> {
     /// });
     /// ```
     fn inspect_batch(&self, func: impl FnMut(&G::Timestamp, &[D])+'static) -> Stream<G, D>;
+
+    /// Runs a supplied closure on the input frontier each time it changes.
+    ///
+    /// The closure is first called with the initial frontier, and last with the empty frontier.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use timely::dataflow::operators::{ToStream, Delay, Inspect};
+    ///
+    /// let frontiers = Arc::new(Mutex::new(Vec::new()));
+    /// let seen = frontiers.clone();
+    ///
+    /// timely::example(move |scope| {
+    ///     (0..3u64).to_stream(scope)
+    ///              .delay(|x, _| *x)
+    ///              .inspect_frontier(move |frontier| seen.lock().unwrap().push(frontier.to_vec()));
+    /// });
+    ///
+    /// assert_eq!(frontiers.lock().unwrap().first(), Some(&vec![0]));
+    /// assert_eq!(frontiers.lock().unwrap().last(), Some(&vec![]));
+    /// ```
+    fn inspect_frontier(&self, func: impl FnMut(AntichainRef<G::Timestamp>)+'static) -> Stream<G, D>;
+
+    /// Runs a supplied closure on each observed data batch, as `Ok((time, data))`, and on the
+    /// input frontier each time it changes, as `Err(frontier)`.
+    ///
+    /// The closure observes batches and frontiers in the order the operator does: each time it
+    /// runs, the batches it received and then the frontier, if it changed. A batch is therefore
+    /// reported before the frontier that passes its time. The closure is last called with the
+    /// empty frontier.
+    ///
+    /// If the worker traces lineage, [`Lineage::current`](crate::dataflow::channels::lineage::Lineage::current)
+    /// reports within the closure where the records of each batch entered the dataflow.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..3u64).to_stream(scope)
+    ///              .delay(|x, _| *x)
+    ///              .inspect_core(|event| match event {
+    ///                  Ok((time, data)) => println!("seen at: {:?}\t{:?}", time, data),
+    ///                  Err(frontier) => println!("frontier: {:?}", frontier),
+    ///              });
+    /// });
+    /// ```
+    fn inspect_core(&self, func: impl FnMut(Result<(&G::Timestamp, &[D]), &[G::Timestamp]>)+'static) -> Stream<G, D>;
 }
 
 impl<G: Scope, D: Data> Inspect<G, D> for Stream<G, D> {
@@ -69,4 +119,39 @@ impl<G: Scope, D: Data> Inspect<G, D> for Stream<G, D> {
             });
         })
     }
+
+    fn inspect_frontier(&self, mut func: impl FnMut(AntichainRef<G::Timestamp>)+'static) -> Stream<G, D> {
+        let mut vector = Vec::new();
+        // `None` until the initial frontier has been reported.
+        let mut reported: Option<Antichain<G::Timestamp>> = None;
+        self.unary_frontier(Pipeline, "InspectFrontier", move |_,_| move |input, output| {
+            let frontier = input.frontier().frontier();
+            if reported.as_ref().map(|r| r.elements() != &frontier[..]).unwrap_or(true) {
+                func(frontier);
+                reported = Some(frontier.to_owned());
+            }
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                output.session(&time).give_vec(&mut vector);
+            });
+        })
+    }
+
+    fn inspect_core(&self, mut func: impl FnMut(Result<(&G::Timestamp, &[D]), &[G::Timestamp]>)+'static) -> Stream<G, D> {
+        let mut vector = Vec::new();
+        // `None` until the initial frontier has been reported.
+        let mut reported: Option<Antichain<G::Timestamp>> = None;
+        self.unary_frontier(Pipeline, "InspectCore", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                func(Ok((&time, &vector[..])));
+                output.session(&time).give_vec(&mut vector);
+            });
+            let frontier = input.frontier().frontier();
+            if reported.as_ref().map(|r| r.elements() != &frontier[..]).unwrap_or(true) {
+                func(Err(&frontier[..]));
+                reported = Some(frontier.to_owned());
+            }
+        })
+    }
 }
@@ -0,0 +1,330 @@
+//! Sources reading records from files, and sinks writing them.
+//!
+//! Each worker reads the whole file, and introduces those records whose position in the file is
+//! congruent to the worker's index modulo the number of workers, so that together the workers
+//! introduce each record once. Records are read in batches, between which the reading operator
+//! yields to the worker and reschedules itself, so that reading a large file does not prevent the
+//! worker from performing other work.
+//!
+//! The operators hold a capability for the minimal timestamp until the file is read, so that the
+//! timestamps assigned to records may have any order.
+//!
+//! Each worker writes the records it receives to files of its own, one for each timestamp, and
+//! moves each file into place only once its timestamp is complete.
+
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::Data;
+use crate::order::PartialOrder;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::{Operator, TimedState};
+use crate::dataflow::operators::generic::operator::source;
+
+/// Reads records from files.
+pub trait ReadFile : Scope {
+    /// Reads the lines of the file at `path`, at times assigned by `time`.
+    ///
+    /// Line terminators are removed, and at most `batch` lines are read each time the operator
+    /// is scheduled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch` is zero, or if an error occurs once the file is being read.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use timely::dataflow::operators::Capture;
+    /// use timely::dataflow::operators::io::ReadFile;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let path = std::env::temp_dir().join("timely_read_lines_example.txt");
+    /// let mut file = std::fs::File::create(&path).unwrap();
+    /// writeln!(file, "0 hello").unwrap();
+    /// writeln!(file, "1 world").unwrap();
+    ///
+    /// let path2 = path.clone();
+    /// let captured = timely::example(move |scope| {
+    ///     scope.read_lines(&path2, 1024, |line| line[.. 1].parse::<u64>().unwrap())
+    ///          .unwrap()
+    ///          .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![
+    ///     (0, vec!["0 hello".to_string()]),
+    ///     (1, vec!["1 world".to_string()]),
+    /// ]);
+    /// std::fs::remove_file(&path).unwrap();
+    /// ```
+    fn read_lines<P, F>(&mut self, path: P, batch: usize, time: F) -> std::io::Result<Stream<Self, String>>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&str)->Self::Timestamp+'static;
+
+    /// Reads the comma-separated records of the file at `path`, at times assigned by `time`.
+    ///
+    /// Each line is a record, whose fields may be enclosed in double quotes to contain commas,
+    /// with double quotes within them written twice. Quoted fields may not contain line breaks.
+    /// At most `batch` records are read each time the operator is scheduled.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `batch` is zero, or if an error occurs once the file is being read.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::io::Write;
+    /// use timely::dataflow::operators::Capture;
+    /// use timely::dataflow::operators::io::ReadFile;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let path = std::env::temp_dir().join("timely_read_csv_example.csv");
+    /// let mut file = std::fs::File::create(&path).unwrap();
+    /// writeln!(file, "3,apple").unwrap();
+    /// writeln!(file, "5,\"pear, \"\"ripe\"\"\"").unwrap();
+    ///
+    /// let path2 = path.clone();
+    /// let captured = timely::example(move |scope| {
+    ///     scope.read_csv(&path2, 1024, |fields| fields[0].parse::<u64>().unwrap())
+    ///          .unwrap()
+    ///          .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![
+    ///     (3, vec![vec!["3".to_string(), "apple".to_string()]]),
+    ///     (5, vec![vec!["5".to_string(), "pear, \"ripe\"".to_string()]]),
+    /// ]);
+    /// std::fs::remove_file(&path).unwrap();
+    /// ```
+    fn read_csv<P, F>(&mut self, path: P, batch: usize, time: F) -> std::io::Result<Stream<Self, Vec<String>>>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&[String])->Self::Timestamp+'static;
+}
+
+impl<G: Scope> ReadFile for G {
+    fn read_lines<P, F>(&mut self, path: P, batch: usize, mut time: F) -> std::io::Result<Stream<G, String>>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&str)->G::Timestamp+'static,
+    {
+        read_records(self, "ReadLines", path.as_ref(), batch, move |line| {
+            let time = time(&line);
+            (time, line)
+        })
+    }
+
+    fn read_csv<P, F>(&mut self, path: P, batch: usize, mut time: F) -> std::io::Result<Stream<G, Vec<String>>>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&[String])->G::Timestamp+'static,
+    {
+        read_records(self, "ReadCsv", path.as_ref(), batch, move |line| {
+            let fields = parse_csv(&line);
+            (time(&fields[..]), fields)
+        })
+    }
+}
+
+/// Writes records to files.
+pub trait WriteFiles<G: Scope, D: Data> {
+    /// Writes the records of each timestamp to a file of this worker, and reports each file once
+    /// it is complete.
+    ///
+    /// Each record is written as a line. The file of a timestamp is named by `path_template`,
+    /// with `{worker}` replaced by the worker's index and `{epoch}` by the timestamp, formatted
+    /// with `Debug`. Records are written to a hidden file in the same directory, which is moved
+    /// to the named path once the input frontier passes the timestamp, so that a named file is
+    /// only ever seen complete. The returned stream reports the path of each completed file, at
+    /// its timestamp. Timestamps the input frontier passes through without records have an empty
+    /// file, so that each completed epoch is marked by a file of each worker.
+    ///
+    /// A file that cannot be created, written, or moved is reported as an error message in place
+    /// of its path, and its remaining records are discarded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path_template` does not contain both `{worker}` and `{epoch}`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{Capture, ToStream, Delay};
+    /// use timely::dataflow::operators::io::WriteFiles;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let directory = std::env::temp_dir().join("timely_write_files_example");
+    /// std::fs::create_dir_all(&directory).unwrap();
+    /// let template = directory.join("part-{worker}-{epoch}.txt").display().to_string();
+    ///
+    /// let manifest = timely::example(move |scope| {
+    ///     (0 .. 6u64).to_stream(scope)
+    ///                .delay(|x, _| *x / 3)
+    ///                .write_to_files(&template)
+    ///                .capture()
+    /// });
+    ///
+    /// let manifest = manifest.extract();
+    /// assert_eq!(manifest.len(), 2);
+    /// let path = manifest[1].1[0].as_ref().unwrap();
+    /// assert_eq!(std::fs::read_to_string(path).unwrap(), "3\n4\n5\n");
+    /// std::fs::remove_dir_all(&directory).unwrap();
+    /// ```
+    fn write_to_files(&self, path_template: &str) -> Stream<G, Result<PathBuf, String>>;
+}
+
+impl<G: Scope, D: Data+Display> WriteFiles<G, D> for Stream<G, D> {
+    fn write_to_files(&self, path_template: &str) -> Stream<G, Result<PathBuf, String>> {
+        assert!(
+            path_template.contains("{worker}") && path_template.contains("{epoch}"),
+            "file sinks require `{{worker}}` and `{{epoch}}` in the path template: {}", path_template
+        );
+
+        let template = path_template.replace("{worker}", &self.scope().index().to_string());
+        let path_for = move |time: &G::Timestamp| PathBuf::from(template.replace("{epoch}", &format!("{:?}", time)));
+
+        self.unary_frontier(Pipeline, "WriteFiles", move |capability, _info| {
+
+            let mut files = TimedState::new();
+            // Capabilities for the times of the input frontier, with which to mark empty epochs.
+            let mut visited = vec![capability];
+
+            move |input, output| {
+                input.for_each(|time, data| {
+                    let file = files.state_with(&time, || EpochFile::create(path_for(time.time())));
+                    if let Ok(epoch_file) = file {
+                        for record in data.iter() {
+                            if let Err(error) = writeln!(epoch_file.writer, "{}", record) {
+                                *file = Err(format!("failed to write {}: {}", epoch_file.partial.display(), error));
+                                break;
+                            }
+                        }
+                    }
+                });
+
+                let frontier = input.frontier();
+                let mut committed = Vec::new();
+                for (capability, file) in files.retire(frontier) {
+                    output.session(&capability).give(file.and_then(EpochFile::commit));
+                    committed.push(capability.time().clone());
+                }
+
+                let mut advanced = Vec::new();
+                for time in frontier.frontier().iter() {
+                    if let Some(capability) = visited.iter().find(|capability| capability.time().less_equal(time)) {
+                        advanced.push(capability.delayed(time));
+                    }
+                }
+                for capability in ::std::mem::replace(&mut visited, advanced) {
+                    if !frontier.less_equal(capability.time()) && !committed.contains(capability.time()) {
+                        let file = EpochFile::create(path_for(capability.time()));
+                        output.session(&capability).give(file.and_then(EpochFile::commit));
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// A file being written, under a hidden name until committed to its path.
+struct EpochFile {
+    path: PathBuf,
+    partial: PathBuf,
+    writer: BufWriter<File>,
+}
+
+impl EpochFile {
+    /// Creates the hidden file to be committed to `path`.
+    fn create(path: PathBuf) -> Result<Self, String> {
+        let name = path.file_name().ok_or_else(|| format!("file sink path has no file name: {}", path.display()))?;
+        let partial = path.with_file_name(format!(".{}.partial", name.to_string_lossy()));
+        let file = File::create(&partial).map_err(|error| format!("failed to create {}: {}", partial.display(), error))?;
+        Ok(EpochFile { path, partial, writer: BufWriter::new(file) })
+    }
+
+    /// Flushes the file to disk and moves it to its path, which it returns.
+    fn commit(self) -> Result<PathBuf, String> {
+        let EpochFile { path, partial, writer } = self;
+        writer.into_inner()
+              .map_err(|error| error.into_error())
+              .and_then(|file| file.sync_all())
+              .and_then(|()| fs::rename(&partial, &path))
+              .map_err(|error| format!("failed to commit {}: {}", path.display(), error))?;
+        Ok(path)
+    }
+}
+
+/// Reads this worker's lines of the file at `path`, and converts each to a timed record.
+fn read_records<G, D, L>(scope: &G, name: &str, path: &Path, batch: usize, mut logic: L) -> std::io::Result<Stream<G, D>>
+where
+    G: Scope,
+    D: crate::Data,
+    L: FnMut(String)->(G::Timestamp, D)+'static,
+{
+    assert!(batch > 0, "file sources require a positive batch size");
+
+    let mut lines = BufReader::new(File::open(path)?).lines();
+    let display = path.display().to_string();
+    let index = scope.index();
+    let peers = scope.peers();
+
+    Ok(source(scope, name, move |capability, info| {
+
+        let activator = scope.activator_for(&info.address[..]);
+        let mut capability = Some(capability);
+        let mut position = 0;
+
+        move |output| {
+            if let Some(cap) = capability.as_ref() {
+                let mut read = 0;
+                while read < batch {
+                    match lines.next() {
+                        Some(line) => {
+                            let line = line.unwrap_or_else(|error| panic!("failed to read {}: {}", display, error));
+                            if position % peers == index {
+                                let (time, record) = logic(line);
+                                output.session(&cap.delayed(&time)).give(record);
+                                read += 1;
+                            }
+                            position += 1;
+                        },
+                        None => {
+                            capability = None;
+                            return;
+                        },
+                    }
+                }
+                activator.activate();
+            }
+        }
+    }))
+}
+
+/// Splits a line into comma-separated fields, removing quotes.
+fn parse_csv(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => { field.push('"'); chars.next(); },
+            '"' => { quoted = !quoted; },
+            ',' if !quoted => { fields.push(::std::mem::take(&mut field)); },
+            _ => { field.push(c); },
+        }
+    }
+    fields.push(field);
+    fields
+}
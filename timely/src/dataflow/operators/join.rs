@@ -0,0 +1,160 @@
+//! Joins of two keyed streams, matching records whose times are close.
+//!
+//! Each input's records are retained only as long as records of the other input could arrive
+//! to match them, and are discarded once the other input's frontier has passed their expiry.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{Exchange, ParallelizationContract};
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::dataflow::operators::windows::WindowTime;
+use crate::progress::frontier::MutableAntichain;
+
+/// Extension trait for joining two keyed streams.
+pub trait JoinByKey<G: Scope, K: ExchangeData+Hash+Eq, V1: ExchangeData> where G::Timestamp: WindowTime {
+    /// Produces `(key, val1, val2)` for each pair of records with equal keys whose times differ by
+    /// at most `window`, at the later of the two times.
+    ///
+    /// Both inputs are exchanged by the worker's exchange hasher, so that records with equal keys
+    /// meet at one worker. A record is discarded once the other input's frontier has passed its
+    /// time plus `window`. The operator yields once it has spent the fuel set by
+    /// [`Config::operator_fuel`](crate::worker::Config::operator_fuel) on input records and
+    /// matches, and resumes when next scheduled.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::join::JoinByKey;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let clicks = vec![(1u64, 0u64), (2, 5), (1, 9)].to_stream(scope).delay(|x, _| x.1);
+    ///     let views = vec![(1u64, 'a'), (2, 'b')].to_stream(scope).delay(|x, _| if x.0 == 1 { 2 } else { 10 });
+    ///     clicks.join_by_key(&views, 3).capture()
+    /// });
+    ///
+    /// // The click at time 9 is too late to match the view at time 2.
+    /// assert_eq!(captured.extract(), vec![(2, vec![(1, 0, 'a')])]);
+    /// ```
+    fn join_by_key<V2: ExchangeData>(&self, other: &Stream<G, (K, V2)>, window: G::Timestamp) -> Stream<G, (K, V1, V2)>;
+}
+
+/// Records of one input, indexed by time and then by key.
+struct Index<T, K, V> {
+    records: BTreeMap<T, HashMap<K, Vec<V>>>,
+}
+
+impl<T: WindowTime, K: Hash+Eq, V> Index<T, K, V> {
+    fn new() -> Self {
+        Index { records: BTreeMap::new() }
+    }
+    fn insert(&mut self, time: T, key: K, val: V) {
+        self.records.entry(time).or_default().entry(key).or_default().push(val);
+    }
+    /// Calls `logic` with each time and value of `key` within `window` of `time`.
+    fn matches(&self, time: T, key: &K, window: T, mut logic: impl FnMut(&T, &V)) {
+        let lower = time.checked_sub(window).unwrap_or_else(T::minimum);
+        for (time, keys) in self.records.range(lower ..= time.saturating_add(window)) {
+            for val in keys.get(key).into_iter().flatten() {
+                logic(time, val);
+            }
+        }
+    }
+    /// Discards records that no record at a time in advance of `frontier` can match.
+    fn expire(&mut self, frontier: &MutableAntichain<T>, window: T) {
+        while let Some(time) = self.records.keys().next().cloned() {
+            if frontier.less_equal(&time.saturating_add(window)) {
+                break;
+            }
+            self.records.remove(&time);
+        }
+    }
+}
+
+impl<G: Scope, K: ExchangeData+Hash+Eq, V1: ExchangeData> JoinByKey<G, K, V1> for Stream<G, (K, V1)> where G::Timestamp: WindowTime {
+    fn join_by_key<V2: ExchangeData>(&self, other: &Stream<G, (K, V2)>, window: G::Timestamp) -> Stream<G, (K, V1, V2)> {
+        let hasher1 = self.scope().config().hasher().clone();
+        let hasher2 = hasher1.clone();
+        let exchange1 = Exchange::new(move |x: &(K, V1)| hasher1.hash(&x.0));
+        let exchange2 = Exchange::new(move |x: &(K, V2)| hasher2.hash(&x.0));
+        join_core(self, other, exchange1, exchange2, window, |key, val1, val2| (key.clone(), val1.clone(), val2.clone()))
+    }
+}
+
+/// Joins the `(key, val)` records of two streams, delivered by `pact1` and `pact2`, producing
+/// `result(key, val1, val2)` for each match.
+///
+/// The pacts must deliver all records with the same key to the same worker.
+pub(crate) fn join_core<G, K, V1, V2, R, P1, P2, F>(stream1: &Stream<G, (K, V1)>, stream2: &Stream<G, (K, V2)>, pact1: P1, pact2: P2, window: G::Timestamp, result: F) -> Stream<G, R>
+where
+    G: Scope,
+    G::Timestamp: WindowTime,
+    K: Data+Hash+Eq,
+    V1: Data,
+    V2: Data,
+    R: Data,
+    P1: ParallelizationContract<G::Timestamp, (K, V1)>,
+    P2: ParallelizationContract<G::Timestamp, (K, V2)>,
+    F: Fn(&K, &V1, &V2)->R+'static,
+{
+    let scope = stream1.scope();
+    let mut fuel = scope.config().fuel();
+
+    stream1.binary_frontier(stream2, pact1, pact2, "JoinByKey", move |_, info| {
+        let activator = scope.activator_for(&info.address[..]);
+        let mut index1 = Index::new();
+        let mut index2 = Index::new();
+        let mut vector1 = Vec::new();
+        let mut vector2 = Vec::new();
+        move |input1, input2, output| {
+            // Each record probes the other input's retained records before it is retained,
+            // so that each matching pair is produced once, by whichever record arrives last.
+            // The inputs take turns, so that neither is starved once fuel runs short.
+            fuel.refill();
+            loop {
+                if fuel.exhausted() {
+                    activator.activate();
+                    break;
+                }
+                let mut read = false;
+                if let Some((time, data)) = input1.next() {
+                    data.swap(&mut vector1);
+                    let now = *time.time();
+                    fuel.consume(vector1.len());
+                    for (key, val1) in vector1.drain(..) {
+                        index2.matches(now, &key, window, |other, val2: &V2| {
+                            let later = if *other > now { *other } else { now };
+                            output.session(&time.delayed(&later)).give(result(&key, &val1, val2));
+                            fuel.consume(1);
+                        });
+                        index1.insert(now, key, val1);
+                    }
+                    read = true;
+                }
+                if let Some((time, data)) = input2.next() {
+                    data.swap(&mut vector2);
+                    let now = *time.time();
+                    fuel.consume(vector2.len());
+                    for (key, val2) in vector2.drain(..) {
+                        index1.matches(now, &key, window, |other, val1: &V1| {
+                            let later = if *other > now { *other } else { now };
+                            output.session(&time.delayed(&later)).give(result(&key, val1, &val2));
+                            fuel.consume(1);
+                        });
+                        index2.insert(now, key, val2);
+                    }
+                    read = true;
+                }
+                if !read {
+                    break;
+                }
+            }
+
+            index1.expire(input2.frontier(), window);
+            index2.expire(input1.frontier(), window);
+        }
+    })
+}
@@ -0,0 +1,117 @@
+//! Streams partitioned by key, whose operators rely on the partitioning rather than exchange.
+//!
+//! A [`KeyedStream`] holds `(key, val)` records that have been exchanged by the worker's
+//! exchange hasher, so that all records with the same key are at the same worker. Its operators
+//! preserve the keys of records, and so the partitioning, and exchange nothing. A keyed stream
+//! offers no operator that changes keys; to re-key or otherwise move records, leave the keyed
+//! stream with [`KeyedStream::into_stream`].
+
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::{Exchange, Map};
+use crate::dataflow::operators::aggregation::aggregate::aggregate_core;
+use crate::dataflow::operators::join::join_core;
+use crate::dataflow::operators::windows::WindowTime;
+
+/// A stream of `(key, val)` records, partitioned among workers by key.
+///
+/// Records with equal keys are at the same worker, which is the worker the exchange hasher
+/// assigns the key, as for [`exchange_hashed`](crate::dataflow::operators::Exchange::exchange_hashed)
+/// and [`join_by_key`](crate::dataflow::operators::JoinByKey::join_by_key).
+pub struct KeyedStream<S: Scope, K, V> {
+    stream: Stream<S, (K, V)>,
+}
+
+impl<S: Scope, K: Data, V: Data> Clone for KeyedStream<S, K, V> {
+    fn clone(&self) -> Self {
+        KeyedStream { stream: self.stream.clone() }
+    }
+}
+
+/// Extension trait for partitioning a stream by key.
+pub trait KeyBy<S: Scope, V: ExchangeData> {
+    /// Pairs each record with its key, as determined by `key`, and exchanges the pairs by key.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    /// use timely::dataflow::operators::keyed::KeyBy;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10u64).to_stream(scope)
+    ///               .key_by(|x| x % 3)
+    ///               .map_values(|x| x * 10)
+    ///               .fold_per_key(|_key, val, sum: &mut u64| *sum += val)
+    ///               .into_stream()
+    ///               .inspect(|x| println!("key and sum: {:?}", x));
+    /// });
+    /// ```
+    fn key_by<K: ExchangeData+Hash, F: Fn(&V)->K+'static>(&self, key: F) -> KeyedStream<S, K, V>;
+}
+
+impl<S: Scope, V: ExchangeData> KeyBy<S, V> for Stream<S, V> {
+    fn key_by<K: ExchangeData+Hash, F: Fn(&V)->K+'static>(&self, key: F) -> KeyedStream<S, K, V> {
+        let hasher = self.scope().config().hasher().clone();
+        let stream = self.map(move |val| (key(&val), val))
+                         .exchange(move |(key, _)| hasher.hash(key));
+        KeyedStream { stream }
+    }
+}
+
+impl<S: Scope, K: Data, V: Data> KeyedStream<S, K, V> {
+    /// The underlying stream of `(key, val)` records.
+    pub fn stream(&self) -> &Stream<S, (K, V)> {
+        &self.stream
+    }
+
+    /// Leaves the keyed stream, for operators that may change or ignore the partitioning.
+    pub fn into_stream(self) -> Stream<S, (K, V)> {
+        self.stream
+    }
+
+    /// Transforms the value of each record, retaining its key and so its partitioning.
+    pub fn map_values<V2: Data, F: Fn(V)->V2+'static>(&self, logic: F) -> KeyedStream<S, K, V2> {
+        let stream = self.stream.map(move |(key, val)| (key, logic(val)));
+        KeyedStream { stream }
+    }
+}
+
+impl<S: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> KeyedStream<S, K, V> {
+    /// Folds the values of each key at each time into an accumulator, and produces the key and
+    /// its accumulator once the time is complete.
+    ///
+    /// This is [`Aggregate::aggregate`](crate::dataflow::operators::aggregation::Aggregate::aggregate)
+    /// without the exchange, as records with the same key are already at the same worker.
+    pub fn fold_per_key<A: Data+Default, F: Fn(&K, V, &mut A)+'static>(&self, fold: F) -> KeyedStream<S, K, A> {
+        let stream = aggregate_core(&self.stream, Pipeline, "FoldPerKey", fold, |key, acc| (key, acc));
+        KeyedStream { stream }
+    }
+
+    /// Produces `(key, (val1, val2))` for each pair of records with equal keys whose times differ
+    /// by at most `window`, at the later of the two times.
+    ///
+    /// This is [`JoinByKey::join_by_key`](crate::dataflow::operators::JoinByKey::join_by_key)
+    /// without the exchanges, as both inputs are partitioned by the same hasher.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::keyed::KeyBy;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let names = vec![(1u64, 'a'), (2, 'b')].to_stream(scope).key_by(|x| x.0).map_values(|x| x.1);
+    ///     let counts = vec![(1u64, 10u64), (1, 11), (3, 30)].to_stream(scope).key_by(|x| x.0).map_values(|x| x.1);
+    ///     names.join(&counts, 0).into_stream().capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![(1, ('a', 10)), (1, ('a', 11))])]);
+    /// ```
+    pub fn join<V2: ExchangeData>(&self, other: &KeyedStream<S, K, V2>, window: S::Timestamp) -> KeyedStream<S, K, (V, V2)> where S::Timestamp: WindowTime {
+        let stream = join_core(&self.stream, &other.stream, Pipeline, Pipeline, window, |key, val1, val2| (key.clone(), (val1.clone(), val2.clone())));
+        KeyedStream { stream }
+    }
+}
@@ -0,0 +1,232 @@
+//! Measurement of the latency of records, relative to their event times.
+//!
+//! The `measure_latency` operator compares the time at which it observes each record, as measured
+//! by the worker's timer, with an event time extracted from the record. The latencies are recorded
+//! into a histogram for each name, which the worker can report with `Worker::latency`, and each
+//! batch of latencies observed is also logged to the "timely/latency" log stream.
+//!
+//! Histograms have buckets of size at most one eighth of their lower bound, in the manner of
+//! HDR histograms, and so report quantiles with a relative error of at most 12.5%.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use abomonation::Abomonation;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+use crate::logging::LatencyEvent;
+use crate::logging_core::clock::{Clock, Instant};
+
+// Values below this number of nanoseconds have their own bucket.
+const EXACT: u64 = 16;
+// Each power of two above `EXACT` is divided into this many buckets.
+const SUB_BUCKETS: u64 = 8;
+
+/// Counts of latencies, in logarithmically sized buckets.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct LatencyHistogram {
+    /// The number of latencies in each bucket, indexed by bucket.
+    counts: Vec<u64>,
+}
+
+impl LatencyHistogram {
+    /// Creates an empty histogram.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a latency.
+    pub fn record(&mut self, latency: Duration) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        let bucket = Self::bucket(nanos);
+        if self.counts.len() <= bucket {
+            self.counts.resize(bucket + 1, 0);
+        }
+        self.counts[bucket] += 1;
+    }
+
+    /// Adds the counts of `other` to this histogram.
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other;
+        }
+    }
+
+    /// The number of latencies recorded.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The lower bound of the bucket containing the latency at quantile `q`, between zero and one.
+    ///
+    /// Returns `None` if no latencies have been recorded.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::latency::LatencyHistogram;
+    ///
+    /// let mut histogram = LatencyHistogram::new();
+    /// for millis in 1 ..= 100 {
+    ///     histogram.record(Duration::from_millis(millis));
+    /// }
+    /// let median = histogram.quantile(0.5).unwrap();
+    /// assert!(Duration::from_millis(44) <= median && median <= Duration::from_millis(50));
+    /// ```
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        let total = self.count();
+        if total == 0 {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return Some(Duration::from_nanos(Self::lower_bound(bucket)));
+            }
+        }
+        unreachable!("rank exceeds the number of latencies")
+    }
+
+    /// Iterates over the lower bounds and counts of non-empty buckets, in increasing order.
+    pub fn buckets(&self) -> impl Iterator<Item=(Duration, u64)>+'_ {
+        self.counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(bucket, count)| (Duration::from_nanos(Self::lower_bound(bucket)), *count))
+    }
+
+    fn bucket(nanos: u64) -> usize {
+        if nanos < EXACT {
+            nanos as usize
+        }
+        else {
+            let exponent = 63 - u64::from(nanos.leading_zeros());
+            let sub = (nanos >> (exponent - 3)) & (SUB_BUCKETS - 1);
+            (EXACT + (exponent - 4) * SUB_BUCKETS + sub) as usize
+        }
+    }
+
+    fn lower_bound(bucket: usize) -> u64 {
+        let bucket = bucket as u64;
+        if bucket < EXACT {
+            bucket
+        }
+        else {
+            let exponent = (bucket - EXACT) / SUB_BUCKETS + 4;
+            let sub = (bucket - EXACT) % SUB_BUCKETS;
+            (SUB_BUCKETS + sub) << (exponent - 3)
+        }
+    }
+}
+
+impl Abomonation for LatencyHistogram {
+    #[inline] unsafe fn entomb<W: ::std::io::Write>(&self, write: &mut W) -> ::std::io::Result<()> {
+        self.counts.entomb(write)
+    }
+    #[inline] unsafe fn exhume<'b>(&mut self, bytes: &'b mut [u8]) -> Option<&'b mut [u8]> {
+        self.counts.exhume(bytes)
+    }
+    #[inline] fn extent(&self) -> usize {
+        self.counts.extent()
+    }
+}
+
+/// A shared handle to the latency histograms of a worker, by name.
+#[derive(Clone)]
+pub struct Latencies {
+    timer: Instant,
+    clock: Arc<dyn Clock>,
+    histograms: Rc<RefCell<HashMap<String, LatencyHistogram>>>,
+}
+
+impl Latencies {
+    /// Creates an empty set of histograms, measuring time by `clock` from `timer`.
+    pub(crate) fn new(timer: Instant, clock: Arc<dyn Clock>) -> Self {
+        Latencies { timer, clock, histograms: Rc::new(RefCell::new(HashMap::new())) }
+    }
+    /// The histogram recorded under `name`, if any.
+    pub fn get(&self, name: &str) -> Option<LatencyHistogram> {
+        self.histograms.borrow().get(name).cloned()
+    }
+    fn record(&self, name: &str, observed: &LatencyHistogram) {
+        self.histograms.borrow_mut().entry(name.to_owned()).or_default().merge(observed);
+    }
+}
+
+/// Extension trait for measuring the latency of records.
+pub trait MeasureLatency<G: Scope, D: Data> {
+    /// Records the latency of each record under `name`, and passes the records on unchanged.
+    ///
+    /// The latency of a record is the time elapsed since the worker's timer started, less the
+    /// record's event time as extracted by `event_time`, or zero if the event time is later.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Probe};
+    /// use timely::dataflow::operators::latency::MeasureLatency;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let mut input = InputHandle::new();
+    ///     let probe = worker.dataflow(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .measure_latency("ingest", |event_time: &Duration| *event_time)
+    ///              .probe()
+    ///     });
+    ///
+    ///     for round in 0 .. 10u64 {
+    ///         input.send(worker.timer().elapsed());
+    ///         input.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    ///
+    ///     let histogram = worker.latency("ingest").unwrap();
+    ///     assert_eq!(histogram.count(), 10);
+    ///     println!("median latency: {:?}", histogram.quantile(0.5));
+    /// }).unwrap();
+    /// ```
+    fn measure_latency<F: Fn(&D)->Duration+'static>(&self, name: &str, event_time: F) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> MeasureLatency<G, D> for Stream<G, D> {
+    fn measure_latency<F: Fn(&D)->Duration+'static>(&self, name: &str, event_time: F) -> Stream<G, D> {
+        let scope = self.scope();
+        let name = name.to_owned();
+        let latencies = scope.latencies();
+        let timer = latencies.timer;
+        let clock = latencies.clock.clone();
+        let logger = scope.log_register().get::<LatencyEvent>("timely/latency");
+        let mut vector = Vec::new();
+        self.unary(Pipeline, "MeasureLatency", move |_, _| move |input, output| {
+            let mut observed = LatencyHistogram::new();
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                let now = clock.elapsed(timer);
+                for datum in vector.iter() {
+                    observed.record(now.saturating_sub(event_time(datum)));
+                }
+                output.session(&time).give_vec(&mut vector);
+            });
+            if observed.count() > 0 {
+                latencies.record(&name, &observed);
+                if let Some(logger) = logger.as_ref() {
+                    logger.log(LatencyEvent { name: name.clone(), histogram: observed });
+                }
+            }
+        })
+    }
+}
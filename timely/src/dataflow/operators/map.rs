@@ -9,6 +9,10 @@ use crate::dataflow::operators::generic::operator::Operator;
 pub trait Map<S: Scope, D: Data> {
     /// Consumes each element of the stream and yields a new element.
     ///
+    /// Each batch of new elements is collected into the allocation of the input batch when the two
+    /// element types have the same size and alignment, and sent on without copying. Otherwise a
+    /// new allocation is made for the batch.
+    ///
     /// # Examples
     /// ```
     /// use timely::dataflow::operators::{ToStream, Map, Inspect};
@@ -33,6 +37,20 @@ pub trait Map<S: Scope, D: Data> {
     /// });
     /// ```
     fn map_in_place<L: FnMut(&mut D)+'static>(&self, logic: L) -> Stream<S, D>;
+    /// Updates each element of the stream and yields those for which `logic` returns true,
+    /// re-using memory where possible.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .filter_map_in_place(|x| { *x *= 3; *x % 2 == 0 })
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn filter_map_in_place<L: FnMut(&mut D)->bool+'static>(&self, logic: L) -> Stream<S, D>;
     /// Consumes each element of the stream and yields some number of new elements.
     ///
     /// # Examples
@@ -46,6 +64,41 @@ pub trait Map<S: Scope, D: Data> {
     /// });
     /// ```
     fn flat_map<I: IntoIterator, L: FnMut(D)->I+'static>(&self, logic: L) -> Stream<S, I::Item> where I::Item: Data;
+    /// Consumes each batch of the stream, with its time, and yields a batch of new elements.
+    ///
+    /// The closure owns the batch, and may transform it in bulk or reuse its allocation for the
+    /// batch it returns, which is sent at the same time. The returned batch may have any length.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .map_batch(|_time, mut batch| {
+    ///                for x in batch.iter_mut() { *x *= 2; }
+    ///                batch
+    ///            })
+    ///            .inspect(|x| assert!(x % 2 == 0));
+    /// });
+    /// ```
+    fn map_batch<D2: Data, L: FnMut(&S::Timestamp, Vec<D>)->Vec<D2>+'static>(&self, logic: L) -> Stream<S, D2>;
+    /// Consumes each batch of the stream, with its time, and yields some number of new elements.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10).to_stream(scope)
+    ///            .flat_map_batch(|time, batch| {
+    ///                let time = *time;
+    ///                batch.into_iter().map(move |x| (time, x))
+    ///            })
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn flat_map_batch<I: IntoIterator, L: FnMut(&S::Timestamp, Vec<D>)->I+'static>(&self, logic: L) -> Stream<S, I::Item> where I::Item: Data;
 }
 
 impl<S: Scope, D: Data> Map<S, D> for Stream<S, D> {
@@ -54,7 +107,8 @@ impl<S: Scope, D: Data> Map<S, D> for Stream<S, D> {
         self.unary(Pipeline, "Map", move |_,_| move |input, output| {
             input.for_each(|time, data| {
                 data.swap(&mut vector);
-                output.session(&time).give_iterator(vector.drain(..).map(|x| logic(x)));
+                let mut batch = ::std::mem::take(&mut vector).into_iter().map(&mut logic).collect::<Vec<_>>();
+                output.session(&time).give_vec(&mut batch);
             });
         })
     }
@@ -68,6 +122,16 @@ impl<S: Scope, D: Data> Map<S, D> for Stream<S, D> {
             })
         })
     }
+    fn filter_map_in_place<L: FnMut(&mut D)->bool+'static>(&self, mut logic: L) -> Stream<S, D> {
+        let mut vector = Vec::new();
+        self.unary(Pipeline, "FilterMapInPlace", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                vector.retain_mut(|datum| logic(datum));
+                output.session(&time).give_vec(&mut vector);
+            })
+        })
+    }
     // TODO : This would be more robust if it captured an iterator and then pulled an appropriate
     // TODO : number of elements from the iterator. This would allow iterators that produce many
     // TODO : records without taking arbitrarily long and arbitrarily much memory.
@@ -80,4 +144,23 @@ impl<S: Scope, D: Data> Map<S, D> for Stream<S, D> {
             });
         })
     }
+    fn map_batch<D2: Data, L: FnMut(&S::Timestamp, Vec<D>)->Vec<D2>+'static>(&self, mut logic: L) -> Stream<S, D2> {
+        let mut vector = Vec::new();
+        self.unary(Pipeline, "MapBatch", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                let mut batch = logic(time.time(), ::std::mem::take(&mut vector));
+                output.session(&time).give_vec(&mut batch);
+            });
+        })
+    }
+    fn flat_map_batch<I: IntoIterator, L: FnMut(&S::Timestamp, Vec<D>)->I+'static>(&self, mut logic: L) -> Stream<S, I::Item> where I::Item: Data {
+        let mut vector = Vec::new();
+        self.unary(Pipeline, "FlatMapBatch", move |_,_| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                output.session(&time).give_iterator(logic(time.time(), ::std::mem::take(&mut vector)).into_iter());
+            });
+        })
+    }
 }
@@ -12,10 +12,11 @@ pub use self::enterleave::{Enter, EnterAt, Leave};
 // pub use self::queue::*;
 pub use self::input::Input;
 pub use self::unordered_input::UnorderedInput;
-pub use self::feedback::{Feedback, LoopVariable, ConnectLoop};
+pub use self::feedback::{Feedback, LoopVariable, ConnectLoop, Iterate};
 pub use self::concat::{Concat, Concatenate};
 pub use self::partition::Partition;
 pub use self::map::Map;
+pub use self::fused::Fuse;
 pub use self::inspect::Inspect;
 pub use self::filter::Filter;
 pub use self::delay::Delay;
@@ -27,12 +28,35 @@ pub use self::capture::Capture;
 pub use self::branch::{Branch, BranchWhen};
 pub use self::ok_err::OkErr;
 pub use self::result::ResultStream;
+pub use self::option::OptionStream;
+pub use self::fallible::Fallible;
 
 pub use self::generic::Operator;
 pub use self::generic::{Notificator, FrontierNotificator};
 
-pub use self::reclock::Reclock;
+pub use self::reclock::{Reclock, AlignTo};
 pub use self::count::Accumulate;
+pub use self::distinct::{CountBy, Distinct, TopK};
+pub use self::windows::{Windows, SessionWindows};
+pub use self::join::JoinByKey;
+pub use self::zip::ZipByTime;
+pub use self::throttle::Throttle;
+pub use self::sample::Sample;
+pub use self::flush::EmitAtFrontier;
+pub use self::latency::MeasureLatency;
+pub use self::exactly_once::SinkExactlyOnce;
+pub use self::export::{Export, Import, Publish, Subscribe};
+pub use self::io::{ReadFile, WriteFiles};
+pub use self::socket::{SocketSource, SocketSink};
+pub use self::watch::Watch;
+pub use self::tap::Tap;
+pub use self::frontier_updates::FrontierUpdates;
+pub use self::shed::Shed;
+pub use self::sort::SortWithinEpoch;
+pub use self::keyed::{KeyBy, KeyedStream};
+pub use self::gate::Gate;
+pub use self::sketch::Sketch;
+pub use self::scan::Scan;
 
 pub mod enterleave;
 pub mod input;
@@ -42,24 +66,51 @@ pub mod feedback;
 pub mod concat;
 pub mod partition;
 pub mod map;
+pub mod fused;
 pub mod inspect;
 pub mod filter;
 pub mod delay;
 pub mod exchange;
 pub mod broadcast;
 pub mod probe;
+pub mod compaction;
 pub mod to_stream;
 pub mod capture;
 pub mod branch;
 pub mod ok_err;
 pub mod result;
+pub mod option;
+pub mod fallible;
 
 pub mod aggregation;
 pub mod generic;
 
 pub mod reclock;
 pub mod count;
+pub mod distinct;
+pub mod windows;
+pub mod join;
+pub mod zip;
+pub mod throttle;
+pub mod sample;
+pub mod flush;
+pub mod latency;
+pub mod exactly_once;
+pub mod export;
+pub mod io;
+pub mod socket;
+pub mod watch;
+pub mod tap;
+pub mod frontier_updates;
+pub mod epoch_buffer;
+pub mod shed;
+pub mod sort;
+pub mod keyed;
+pub mod gate;
+pub mod sketch;
+pub mod scan;
 
 // keep "mint" module-private
 mod capability;
 pub use self::capability::{ActivateCapability, Capability, CapabilityRef, CapabilitySet};
+pub(crate) use self::capability::tracking as capability_tracking;
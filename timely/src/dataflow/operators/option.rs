@@ -0,0 +1,128 @@
+//! Extension methods for `Stream` containing `Option`s.
+
+use crate::Data;
+use crate::dataflow::operators::Map;
+use crate::dataflow::{Scope, Stream};
+
+/// Extension trait for `Stream`.
+pub trait OptionStream<S: Scope, T: Data> {
+    /// Returns a new instance of `self` containing the values of the `Some` records.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect, OptionStream};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![Some(0), None].to_stream(scope)
+    ///            .some()
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn some(&self) -> Stream<S, T>;
+
+    /// Returns a new instance of `self` applying `logic` on all `Some` records.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect, OptionStream};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![Some(0), None].to_stream(scope)
+    ///            .map_some(|x| x + 1)
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn map_some<T2: Data, L: FnMut(T) -> T2 + 'static>(&self, logic: L) -> Stream<S, Option<T2>>;
+
+    /// Returns a new instance of `self` applying `logic` on all `Some` records, passing through
+    /// `None` records.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect, OptionStream};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![Some(0), None].to_stream(scope)
+    ///            .and_then_some(|x| if x > 0 { Some(x) } else { None })
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn and_then_some<T2: Data, L: FnMut(T) -> Option<T2> + 'static>(&self, logic: L) -> Stream<S, Option<T2>>;
+
+    /// Returns a new instance of `self` replacing each `None` record with the result of `logic`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect, OptionStream};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![Some(1), None].to_stream(scope)
+    ///            .unwrap_or_else(|| 0)
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn unwrap_or_else<L: FnMut() -> T + 'static>(&self, logic: L) -> Stream<S, T>;
+}
+
+impl<S: Scope, T: Data> OptionStream<S, T> for Stream<S, Option<T>> {
+    fn some(&self) -> Stream<S, T> {
+        self.flat_map(|x| x)
+    }
+
+    fn map_some<T2: Data, L: FnMut(T) -> T2 + 'static>(&self, mut logic: L) -> Stream<S, Option<T2>> {
+        self.map(move |x| x.map(&mut logic))
+    }
+
+    fn and_then_some<T2: Data, L: FnMut(T) -> Option<T2> + 'static>(&self, mut logic: L) -> Stream<S, Option<T2>> {
+        self.map(move |x| x.and_then(&mut logic))
+    }
+
+    fn unwrap_or_else<L: FnMut() -> T + 'static>(&self, mut logic: L) -> Stream<S, T> {
+        self.map(move |x| x.unwrap_or_else(&mut logic))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dataflow::operators::{ToStream, OptionStream, Capture, capture::Extract};
+
+    #[test]
+    fn test_some() {
+        let output = crate::example(|scope| {
+            vec![Some(0), None].to_stream(scope)
+                .some()
+                .capture()
+        });
+        assert_eq!(output.extract()[0].1, vec![0]);
+    }
+
+    #[test]
+    fn test_map_some() {
+        let output = crate::example(|scope| {
+            vec![Some(0), None].to_stream(scope)
+                .map_some(|_| 10)
+                .capture()
+        });
+        assert_eq!(output.extract()[0].1, vec![None, Some(10)]);
+    }
+
+    #[test]
+    fn test_and_then_some() {
+        let output = crate::example(|scope| {
+            vec![Some(0), Some(1), None].to_stream(scope)
+                .and_then_some(|x| if x > 0 { Some(x * 10) } else { None })
+                .capture()
+        });
+        assert_eq!(output.extract()[0].1, vec![None, None, Some(10)]);
+    }
+
+    #[test]
+    fn test_unwrap_or_else() {
+        let output = crate::example(|scope| {
+            vec![Some(0), None].to_stream(scope)
+                .unwrap_or_else(|| 10)
+                .capture()
+        });
+        assert_eq!(output.extract()[0].1, vec![0, 10]);
+    }
+}
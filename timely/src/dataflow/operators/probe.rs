@@ -1,10 +1,13 @@
 //! Monitor progress at a `Stream`.
 
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
 
-use crate::progress::Timestamp;
-use crate::progress::frontier::{AntichainRef, MutableAntichain};
+use crate::progress::{ChangeBatch, Timestamp};
+use crate::progress::frontier::{Antichain, AntichainRef, MutableAntichain};
 use crate::dataflow::channels::pushers::Counter as PushCounter;
 use crate::dataflow::channels::pushers::buffer::Buffer as PushBuffer;
 use crate::dataflow::channels::pact::Pipeline;
@@ -77,6 +80,35 @@ pub trait Probe<G: Scope, D: Data> {
     /// }).unwrap();
     /// ```
     fn probe_with(&self, handle: &mut Handle<G::Timestamp>) -> Stream<G, D>;
+
+    /// Constructs a progress probe which also supplies `logic` with each new frontier.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Probe};
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let watermarks = Rc::new(RefCell::new(Vec::new()));
+    ///     let seen = watermarks.clone();
+    ///     let mut input = InputHandle::<u64, u64>::new();
+    ///     let probe = worker.dataflow(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .probe_with_frontier(move |frontier| seen.borrow_mut().push(frontier))
+    ///     });
+    ///
+    ///     input.advance_to(4);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    ///     assert_eq!(watermarks.borrow().last().map(|f| f.elements().to_vec()), Some(vec![4]));
+    /// }).unwrap();
+    /// ```
+    fn probe_with_frontier(&self, mut logic: impl FnMut(Antichain<G::Timestamp>)+'static) -> Handle<G::Timestamp> {
+        let handle = self.probe();
+        handle.on_frontier_change(move |frontier| logic(frontier.to_owned()));
+        handle
+    }
 }
 
 impl<G: Scope, D: Data> Probe<G, D> for Stream<G, D> {
@@ -92,9 +124,9 @@ impl<G: Scope, D: Data> Probe<G, D> for Stream<G, D> {
         let mut builder = OperatorBuilder::new("Probe".to_owned(), self.scope());
         let mut input = PullCounter::new(builder.new_input(self, Pipeline));
         let (tee, stream) = builder.new_output();
-        let mut output = PushBuffer::new(PushCounter::new(tee));
+        let mut output = PushBuffer::new(PushCounter::new(tee)).with_length(self.scope().config().batch_length()).with_lineage(self.scope().extensions().lineage());
 
-        let shared_frontier = handle.frontier.clone();
+        let mut observed = Observed { frontier: handle.frontier.clone(), callbacks: handle.callbacks.clone(), contributed: ChangeBatch::new() };
         let mut started = false;
 
         let mut vector = Vec::new();
@@ -103,8 +135,7 @@ impl<G: Scope, D: Data> Probe<G, D> for Stream<G, D> {
             move |progress| {
 
                 // surface all frontier changes to the shared frontier.
-                let mut borrow = shared_frontier.borrow_mut();
-                borrow.update_iter(progress.frontiers[0].drain());
+                observed.update(progress.frontiers[0].drain());
 
                 if !started {
                     // discard initial capability.
@@ -136,9 +167,50 @@ impl<G: Scope, D: Data> Probe<G, D> for Stream<G, D> {
     }
 }
 
+// The contribution of a probe operator to the frontier of its handle, which it retracts when dropped.
+//
+// The operator is dropped with its dataflow, which may happen before the dataflow completes, for
+// example when the dataflow is poisoned by a panic. Retracting the frontier lets loops waiting on
+// the handle complete, and leaves the streams of other dataflows probed with the handle in charge.
+struct Observed<T: Timestamp> {
+    frontier: Rc<RefCell<MutableAntichain<T>>>,
+    callbacks: Callbacks<T>,
+    contributed: ChangeBatch<T>,
+}
+
+impl<T: Timestamp> Observed<T> {
+    // Applies `changes` to the shared frontier, and invokes callbacks if it changed.
+    fn update<I: IntoIterator<Item=(T, i64)>>(&mut self, changes: I) {
+        let contributed = &mut self.contributed;
+        let changes = changes.into_iter().inspect(|(time, diff)| contributed.update(time.clone(), *diff));
+        let changed = self.frontier.borrow_mut().update_iter(changes).next().is_some();
+        if changed {
+            // callbacks may register further callbacks, which are retained after these.
+            let mut current = ::std::mem::take(&mut *self.callbacks.borrow_mut());
+            for callback in current.iter_mut() {
+                callback(self.frontier.borrow().frontier());
+            }
+            let mut borrow = self.callbacks.borrow_mut();
+            current.append(&mut borrow);
+            *borrow = current;
+        }
+    }
+}
+
+impl<T: Timestamp> Drop for Observed<T> {
+    fn drop(&mut self) {
+        let retractions = self.contributed.drain().map(|(time, diff)| (time, -diff)).collect::<Vec<_>>();
+        self.update(retractions);
+    }
+}
+
+// Functions to invoke with the probed frontier, when it changes.
+type Callbacks<T> = Rc<RefCell<Vec<Box<dyn FnMut(AntichainRef<T>)>>>>;
+
 /// Reports information about progress at the probe.
 pub struct Handle<T:Timestamp> {
-    frontier: Rc<RefCell<MutableAntichain<T>>>
+    frontier: Rc<RefCell<MutableAntichain<T>>>,
+    callbacks: Callbacks<T>,
 }
 
 impl<T: Timestamp> Handle<T> {
@@ -149,7 +221,37 @@ impl<T: Timestamp> Handle<T> {
     /// returns true iff the frontier is empty.
     #[inline] pub fn done(&self) -> bool { self.frontier.borrow().is_empty() }
     /// Allocates a new handle.
-    #[inline] pub fn new() -> Self { Handle { frontier: Rc::new(RefCell::new(MutableAntichain::new())) } }
+    #[inline] pub fn new() -> Self { Handle { frontier: Rc::new(RefCell::new(MutableAntichain::new())), callbacks: Default::default() } }
+
+    /// Registers `callback` to be invoked with the frontier each time it changes.
+    ///
+    /// Callbacks are invoked by the probe operator as the worker steps, and so should not block.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Probe};
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let mut input = InputHandle::<u64, u64>::new();
+    ///     let probe = worker.dataflow(|scope| scope.input_from(&mut input).probe());
+    ///
+    ///     let frontiers = Rc::new(RefCell::new(Vec::new()));
+    ///     let seen = frontiers.clone();
+    ///     probe.on_frontier_change(move |frontier| seen.borrow_mut().push(frontier.to_vec()));
+    ///
+    ///     for round in 0 .. 3 {
+    ///         input.advance_to(round + 1);
+    ///         worker.step_while(|| probe.less_than(input.time()));
+    ///     }
+    ///     assert_eq!(frontiers.borrow().last(), Some(&vec![3]));
+    /// }).unwrap();
+    /// ```
+    pub fn on_frontier_change<F: FnMut(AntichainRef<T>)+'static>(&self, callback: F) {
+        self.callbacks.borrow_mut().push(Box::new(callback));
+    }
 
     /// Invokes a method on the frontier, returning its result.
     ///
@@ -168,16 +270,191 @@ impl<T: Timestamp> Handle<T> {
     pub fn with_frontier<R, F: FnMut(AntichainRef<T>)->R>(&self, mut function: F) -> R {
         function(self.frontier.borrow().frontier())
     }
+
+    /// A future that resolves once the frontier is empty.
+    ///
+    /// The frontier advances only as the worker steps, and so the future resolves only if the
+    /// worker is stepped while it is pending; it suits hosts that step the worker themselves, as
+    /// with [`execute_cooperatively`](crate::execute_cooperatively), and await completion from
+    /// tasks of their own executor. To step the worker until the frontier is empty, use
+    /// [`Worker::await_done`](crate::worker::Worker::await_done) instead.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::future::Future;
+    /// use std::pin::Pin;
+    /// use std::task::{Context, Poll, Waker};
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Probe};
+    ///
+    /// let (mut coop, (mut input, probe)) = timely::execute_cooperatively(|worker| {
+    ///     let mut input = InputHandle::<u64, u64>::new();
+    ///     let probe = worker.dataflow(|scope| scope.input_from(&mut input).probe());
+    ///     (input, probe)
+    /// });
+    ///
+    /// let mut done = Box::pin(probe.when_done());
+    /// let mut context = Context::from_waker(Waker::noop());
+    /// assert!(done.as_mut().poll(&mut context).is_pending());
+    ///
+    /// input.send(0);
+    /// input.close();
+    /// while done.as_mut().poll(&mut context).is_pending() {
+    ///     coop.worker().step();
+    /// }
+    /// assert!(probe.done());
+    /// ```
+    pub fn when_done(&self) -> Done<T> {
+        Done { handle: self.clone(), waker: None }
+    }
+}
+
+/// A future that resolves once the frontier of a probe is empty.
+///
+/// Returned by [`Handle::when_done`].
+pub struct Done<T: Timestamp> {
+    handle: Handle<T>,
+    // The waker of the most recent poll, shared with a callback on the handle once registered.
+    waker: Option<Rc<RefCell<Waker>>>,
+}
+
+impl<T: Timestamp> Future for Done<T> {
+    type Output = ();
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.handle.done() {
+            return Poll::Ready(());
+        }
+        match &self.waker {
+            Some(waker) => waker.borrow_mut().clone_from(cx.waker()),
+            None => {
+                let waker = Rc::new(RefCell::new(cx.waker().clone()));
+                // The callback outlives the future, and holds only a weak reference to its waker.
+                let weak: Weak<RefCell<Waker>> = Rc::downgrade(&waker);
+                self.handle.on_frontier_change(move |frontier| {
+                    if frontier.is_empty() {
+                        if let Some(waker) = weak.upgrade() {
+                            waker.borrow().wake_by_ref();
+                        }
+                    }
+                });
+                self.waker = Some(waker);
+            }
+        }
+        Poll::Pending
+    }
 }
 
 impl<T: Timestamp> Clone for Handle<T> {
     fn clone(&self) -> Self {
         Handle {
-            frontier: self.frontier.clone()
+            frontier: self.frontier.clone(),
+            callbacks: self.callbacks.clone(),
         }
     }
 }
 
+impl<T: Timestamp> Default for Handle<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reports information about progress at several streams, each with its own probe.
+///
+/// A `Handle` used to probe several streams reports only their combined frontier. A `MultiProbe`
+/// reports the frontier of each stream, as well as their combined frontier.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::InputHandle;
+/// use timely::dataflow::operators::Input;
+/// use timely::dataflow::operators::probe::MultiProbe;
+///
+/// timely::execute(timely::Config::thread(), |worker| {
+///     let mut input1 = InputHandle::<u64, u64>::new();
+///     let mut input2 = InputHandle::<u64, u64>::new();
+///     let mut probes = MultiProbe::new();
+///     worker.dataflow(|scope| {
+///         probes.probe(&scope.input_from(&mut input1));
+///         probes.probe(&scope.input_from(&mut input2));
+///     });
+///
+///     input1.advance_to(5);
+///     input2.advance_to(3);
+///     worker.step_while(|| probes.less_than(&3));
+///     assert_eq!(probes.frontier().elements(), &[3]);
+///     assert_eq!(probes.handle(0).with_frontier(|f| f.to_vec()), vec![5]);
+/// }).unwrap();
+/// ```
+pub struct MultiProbe<T: Timestamp> {
+    handles: Rc<RefCell<Vec<Handle<T>>>>,
+    callbacks: Callbacks<T>,
+}
+
+impl<T: Timestamp> MultiProbe<T> {
+    /// Allocates a new multi-probe, probing no streams.
+    pub fn new() -> Self {
+        MultiProbe { handles: Default::default(), callbacks: Default::default() }
+    }
+
+    /// Probes `stream`, whose index among the probed streams is the number previously probed.
+    pub fn probe<G: Scope<Timestamp=T>, D: Data>(&mut self, stream: &Stream<G, D>) -> Stream<G, D> {
+        let mut handle = Handle::new();
+        let result = stream.probe_with(&mut handle);
+        // Weak references break the cycle through the handle's own callbacks.
+        let handles = Rc::downgrade(&self.handles);
+        let callbacks = Rc::downgrade(&self.callbacks);
+        handle.on_frontier_change(move |_| {
+            if let (Some(handles), Some(callbacks)) = (handles.upgrade(), callbacks.upgrade()) {
+                let frontier = combine(&handles.borrow());
+                for callback in callbacks.borrow_mut().iter_mut() {
+                    callback(frontier.borrow());
+                }
+            }
+        });
+        self.handles.borrow_mut().push(handle);
+        result
+    }
+
+    /// The probe of the stream with index `index`.
+    pub fn handle(&self, index: usize) -> Handle<T> {
+        self.handles.borrow()[index].clone()
+    }
+
+    /// The combined frontier of the probed streams.
+    pub fn frontier(&self) -> Antichain<T> {
+        combine(&self.handles.borrow())
+    }
+
+    /// returns true iff the frontier of some probed stream is strictly less than `time`.
+    pub fn less_than(&self, time: &T) -> bool { self.handles.borrow().iter().any(|h| h.less_than(time)) }
+    /// returns true iff the frontier of some probed stream is less than or equal to `time`.
+    pub fn less_equal(&self, time: &T) -> bool { self.handles.borrow().iter().any(|h| h.less_equal(time)) }
+    /// returns true iff the frontiers of all probed streams are empty.
+    pub fn done(&self) -> bool { self.handles.borrow().iter().all(|h| h.done()) }
+
+    /// Registers `callback` to be invoked with the combined frontier each time the frontier of a
+    /// probed stream changes.
+    pub fn on_frontier_change<F: FnMut(AntichainRef<T>)+'static>(&self, callback: F) {
+        self.callbacks.borrow_mut().push(Box::new(callback));
+    }
+}
+
+impl<T: Timestamp> Default for MultiProbe<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The least elements of the frontiers of `handles`.
+fn combine<T: Timestamp>(handles: &[Handle<T>]) -> Antichain<T> {
+    let mut frontier = Antichain::new();
+    for handle in handles.iter() {
+        handle.with_frontier(|f| for time in f.iter() { frontier.insert(time.clone()); });
+    }
+    frontier
+}
+
 #[cfg(test)]
 mod tests {
 
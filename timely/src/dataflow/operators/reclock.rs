@@ -1,7 +1,9 @@
 //! Extension methods for `Stream` based on record-by-record transformation.
 
+use std::collections::BTreeMap;
+
 use crate::Data;
-use crate::order::PartialOrder;
+use crate::order::{PartialOrder, TotalOrder};
 use crate::dataflow::{Stream, Scope};
 use crate::dataflow::channels::pact::Pipeline;
 use crate::dataflow::operators::generic::operator::Operator;
@@ -78,3 +80,130 @@ impl<S: Scope, D: Data> Reclock<S, D> for Stream<S, D> {
         })
     }
 }
+
+/// How `align_to` assigns records to the ticks of a clock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AlignPolicy {
+    /// Delays each record to the first tick at or after its time, once the records and ticks
+    /// through that tick are complete, as with `reclock`. Records after the final tick are
+    /// discarded.
+    Delay,
+    /// Delays each record to the first tick at or after its time, releasing each tick as soon as
+    /// the clock is complete through it. Records arriving at or before a released tick are late,
+    /// and are discarded.
+    DropLate,
+    /// Emits each record at its own time, once the clock is complete through that time, paired
+    /// with the last tick at or before its time. Records before the first tick are discarded.
+    Stamp,
+}
+
+/// Extension trait for aligning a stream to a clock.
+pub trait AlignTo<S: Scope, D: Data> where S::Timestamp: TotalOrder {
+    /// Pairs each record with a tick of `clock`, and emits it according to `policy`.
+    ///
+    /// The times of the records on `clock` are its ticks. As with `reclock`, workers observe only
+    /// their own clock records, and `broadcast` can ensure that all observe the same ticks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Map, Capture};
+    /// use timely::dataflow::operators::reclock::{AlignTo, AlignPolicy};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let (delayed, stamped) = timely::example(|scope| {
+    ///
+    ///     let data = (0..10).to_stream(scope).delay(|x,_| *x);
+    ///     let clock = vec![3, 5, 8].into_iter().to_stream(scope).delay(|x,_| *x).map(|_| ());
+    ///
+    ///     let delayed = data.align_to(&clock, AlignPolicy::Delay).capture();
+    ///     let stamped = data.align_to(&clock, AlignPolicy::Stamp).capture();
+    ///     (delayed, stamped)
+    /// });
+    ///
+    /// let delayed = delayed.extract();
+    /// assert_eq!(delayed[0], (3, vec![(3,0), (3,1), (3,2), (3,3)]));
+    /// assert_eq!(delayed[2], (8, vec![(8,6), (8,7), (8,8)]));
+    /// assert_eq!(delayed.len(), 3);
+    ///
+    /// let stamped = stamped.extract();
+    /// assert_eq!(stamped[0], (3, vec![(3,3)]));
+    /// assert_eq!(stamped[6], (9, vec![(8,9)]));
+    /// assert_eq!(stamped.len(), 7);
+    /// ```
+    fn align_to(&self, clock: &Stream<S, ()>, policy: AlignPolicy) -> Stream<S, (S::Timestamp, D)>;
+}
+
+impl<S: Scope, D: Data> AlignTo<S, D> for Stream<S, D> where S::Timestamp: TotalOrder {
+    fn align_to(&self, clock: &Stream<S, ()>, policy: AlignPolicy) -> Stream<S, (S::Timestamp, D)> {
+
+        self.binary_frontier(clock, Pipeline, Pipeline, "AlignTo", move |_,_| {
+
+            // ticks not yet released, with capabilities unless stamping.
+            let mut ticks = BTreeMap::new();
+            // the greatest released tick.
+            let mut released = None;
+            // records not yet emitted, with capabilities if stamping.
+            let mut stash = Vec::new();
+
+            move |input1, input2, output| {
+
+                input2.for_each(|time, _data| {
+                    let tick = time.time().clone();
+                    let cap = if policy == AlignPolicy::Stamp { None } else { Some(time.retain()) };
+                    ticks.entry(tick).or_insert(cap);
+                });
+
+                input1.for_each(|time, data| {
+                    let late = policy == AlignPolicy::DropLate && released.as_ref().map(|r| time.time().less_equal(r)).unwrap_or(false);
+                    if !late {
+                        let records = (time.time().clone(), data.replace(Vec::new()));
+                        let cap = if policy == AlignPolicy::Stamp { Some(time.retain()) } else { None };
+                        stash.push((records.0, cap, records.1));
+                    }
+                });
+
+                let data_frontier = input1.frontier();
+                let clock_frontier = input2.frontier();
+
+                if policy == AlignPolicy::Stamp {
+                    for (time, cap, data) in stash.iter_mut().filter(|(time, _, _)| !clock_frontier.less_equal(time)) {
+                        if let Some((tick, _)) = ticks.range(..= time.clone()).next_back() {
+                            output.session(cap.as_ref().unwrap()).give_iterator(data.drain(..).map(|d| (tick.clone(), d)));
+                        }
+                        data.clear();
+                    }
+                    stash.retain(|(_, _, data)| !data.is_empty());
+                    // retain the last tick before any time still to be stamped, and those after.
+                    let lower = data_frontier.frontier().iter().chain(stash.iter().map(|(t, _, _)| t)).min().cloned();
+                    match lower {
+                        Some(lower) => {
+                            if let Some(keep) = ticks.range(..= lower).next_back().map(|(t, _)| t.clone()) {
+                                ticks = ticks.split_off(&keep);
+                            }
+                        },
+                        None => { ticks.clear(); },
+                    }
+                }
+                else {
+                    while let Some(tick) = ticks.keys().next().cloned() {
+                        let complete = !clock_frontier.less_equal(&tick) &&
+                            (policy == AlignPolicy::DropLate || !data_frontier.less_equal(&tick));
+                        if !complete { break; }
+                        let cap = ticks.remove(&tick).unwrap().unwrap();
+                        let mut session = output.session(&cap);
+                        for (_, _, data) in stash.iter_mut().filter(|(time, _, _)| time.less_equal(&tick)) {
+                            session.give_iterator(data.drain(..).map(|d| (tick.clone(), d)));
+                        }
+                        stash.retain(|(time, _, _)| !time.less_equal(&tick));
+                        released = Some(tick);
+                    }
+                    // without further ticks, remaining records have none to be assigned to.
+                    if ticks.is_empty() && clock_frontier.is_empty() {
+                        stash.clear();
+                    }
+                }
+            }
+        })
+    }
+}
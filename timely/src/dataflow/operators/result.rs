@@ -1,8 +1,11 @@
 //! Extension methods for `Stream` containing `Result`s.
 
+use std::fmt::Debug;
+
 use crate::Data;
-use crate::dataflow::operators::Map;
+use crate::dataflow::operators::{Map, OkErr};
 use crate::dataflow::{Scope, Stream};
+use crate::logging::TimelyEvent;
 
 /// Extension trait for `Stream`.
 pub trait ResultStream<S: Scope, T: Data, E: Data> {
@@ -93,6 +96,57 @@ pub trait ResultStream<S: Scope, T: Data, E: Data> {
     /// });
     /// ```
     fn unwrap_or_else<L: FnMut(E) -> T + 'static>(&self, logic: L) -> Stream<S, T>;
+
+    /// Splits `self` into a stream of the `Ok` values and a stream of the `Err` values.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect, ResultStream};
+    ///
+    /// timely::example(|scope| {
+    ///     let (parsed, failed) = vec!["1", "x", "3"].into_iter()
+    ///            .map(|text| text.parse::<u64>().map_err(|_| text.to_owned()))
+    ///            .to_stream(scope)
+    ///            .split_results();
+    ///     parsed.inspect(|x| println!("parsed: {:?}", x));
+    ///     failed.inspect(|x| println!("failed: {:?}", x));
+    /// });
+    /// ```
+    fn split_results(&self) -> (Stream<S, T>, Stream<S, E>);
+
+    /// Returns a new instance of `self` replacing each `Ok` record with an `Ok` record for each
+    /// element produced by `logic`, and passing through `Err` records.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect, ResultStream};
+    ///
+    /// timely::example(|scope| {
+    ///     vec![Ok(3), Err(())].to_stream(scope)
+    ///            .flat_map_ok(|x| 0 .. x)
+    ///            .inspect(|x| println!("seen: {:?}", x));
+    /// });
+    /// ```
+    fn flat_map_ok<I: IntoIterator, L: FnMut(T) -> I + 'static>(&self, logic: L) -> Stream<S, Result<I::Item, E>> where I::Item: Data;
+
+    /// Returns a new instance of `self` containing only `Ok` records, logging each `Err` record,
+    /// prefixed by `context`, to the "timely" log stream as a `TimelyEvent::Text` event.
+    ///
+    /// Errors are dropped without a trace if no "timely" logger is registered.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Inspect, ResultStream};
+    ///
+    /// timely::example(|scope| {
+    ///     vec!["1", "x", "3"].into_iter()
+    ///            .map(|text| text.parse::<u64>())
+    ///            .to_stream(scope)
+    ///            .unwrap_or_log("parsing input")
+    ///            .inspect(|x| println!("parsed: {:?}", x));
+    /// });
+    /// ```
+    fn unwrap_or_log(&self, context: &str) -> Stream<S, T> where E: Debug;
 }
 
 impl<S: Scope, T: Data, E: Data> ResultStream<S, T, E> for Stream<S, Result<T, E>> {
@@ -119,6 +173,34 @@ impl<S: Scope, T: Data, E: Data> ResultStream<S, T, E> for Stream<S, Result<T, E
     fn unwrap_or_else<L: FnMut(E) -> T + 'static>(&self, mut logic: L) -> Stream<S, T> {
         self.map(move |r| r.unwrap_or_else(|err| logic(err)))
     }
+
+    fn split_results(&self) -> (Stream<S, T>, Stream<S, E>) {
+        self.ok_err(|r| r)
+    }
+
+    fn flat_map_ok<I: IntoIterator, L: FnMut(T) -> I + 'static>(&self, mut logic: L) -> Stream<S, Result<I::Item, E>> where I::Item: Data {
+        self.flat_map(move |r| {
+            let (oks, err) = match r {
+                Ok(x) => (Some(logic(x).into_iter().map(Ok)), None),
+                Err(e) => (None, Some(Err(e))),
+            };
+            oks.into_iter().flatten().chain(err)
+        })
+    }
+
+    fn unwrap_or_log(&self, context: &str) -> Stream<S, T> where E: Debug {
+        let logger = self.scope().logging();
+        let context = context.to_owned();
+        self.flat_map(move |r| match r {
+            Ok(x) => Some(x),
+            Err(e) => {
+                if let Some(logger) = logger.as_ref() {
+                    logger.log(TimelyEvent::Text(format!("{}: {:?}", context, e)));
+                }
+                None
+            }
+        })
+    }
 }
 
 #[cfg(test)]
@@ -184,4 +266,50 @@ mod tests {
         });
         assert_eq!(output.extract()[0].1, vec![0, 10]);
     }
+
+    #[test]
+    fn test_split_results() {
+        let (oks, errs) = crate::example(|scope| {
+            let (oks, errs) = vec![Ok(0), Err(()), Ok(1)].to_stream(scope)
+                .split_results();
+            (oks.capture(), errs.capture())
+        });
+        assert_eq!(oks.extract()[0].1, vec![0, 1]);
+        assert_eq!(errs.extract()[0].1, vec![()]);
+    }
+
+    #[test]
+    fn test_flat_map_ok() {
+        let output = crate::example(|scope| {
+            vec![Ok(2), Err(()), Ok(0)].to_stream(scope)
+                .flat_map_ok(|x| 0 .. x)
+                .capture()
+        });
+        assert_eq!(output.extract()[0].1, vec![Ok(0), Ok(1), Err(())]);
+    }
+
+    #[test]
+    fn test_unwrap_or_log() {
+        use std::sync::{Arc, Mutex};
+        use crate::logging::TimelyEvent;
+
+        let logged = Arc::new(Mutex::new(Vec::new()));
+        let sink = logged.clone();
+        let output = crate::execute_directly(move |worker| {
+            worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+                for (_, _, event) in data.drain(..) {
+                    if let TimelyEvent::Text(text) = event {
+                        sink.lock().unwrap().push(text);
+                    }
+                }
+            });
+            worker.dataflow::<u64,_,_>(|scope| {
+                vec![Ok(0), Err("bad"), Ok(1)].to_stream(scope)
+                    .unwrap_or_log("parsing")
+                    .capture()
+            })
+        });
+        assert_eq!(output.extract()[0].1, vec![0, 1]);
+        assert_eq!(*logged.lock().unwrap(), vec!["parsing: \"bad\"".to_owned()]);
+    }
 }
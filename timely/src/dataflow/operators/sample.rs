@@ -0,0 +1,55 @@
+//! Random sampling of the records of a stream.
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for sampling a stream.
+pub trait Sample<G: Scope, D: Data> {
+    /// Retains each record independently with probability `probability`.
+    ///
+    /// The choices are pseudo-random, determined by `seed` and the worker index, so that a
+    /// computation presented with the same records in the same order samples the same records.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::sample::Sample;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0..1000).to_stream(scope)
+    ///              .sample(0.1, 7)
+    ///              .capture()
+    /// });
+    ///
+    /// let sampled = captured.extract()[0].1.len();
+    /// assert!(50 < sampled && sampled < 150);
+    /// ```
+    fn sample(&self, probability: f64, seed: u64) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> Sample<G, D> for Stream<G, D> {
+    fn sample(&self, probability: f64, seed: u64) -> Stream<G, D> {
+        let mut state = seed ^ (self.scope().index() as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        // Records are retained when the next random value is below this threshold.
+        let threshold = (probability.clamp(0.0, 1.0) * (u64::MAX as f64)) as u64;
+        let mut vector = Vec::new();
+        self.unary(Pipeline, "Sample", move |_, _| move |input, output| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                vector.retain(|_| splitmix64(&mut state) < threshold || threshold == u64::MAX);
+                output.session(&time).give_vec(&mut vector);
+            });
+        })
+    }
+}
+
+// The splitmix64 generator, which advances `state` and returns the next value.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
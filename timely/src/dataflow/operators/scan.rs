@@ -0,0 +1,99 @@
+//! Folds the records of each time into state carried across times.
+
+use crate::Data;
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Folds the records of each time into state carried from earlier times.
+pub trait Scan<G: Scope, D: Data> {
+    /// Folds the records of each time into a state starting at `initial`, and produces the state
+    /// after each time.
+    ///
+    /// Records are held until the input frontier passes their time, and the times are then folded
+    /// in order, so that each fold sees the state left by all earlier times however the records
+    /// arrived. The records of a time are presented in the order they arrived. Times without
+    /// records are not folded, and produce no state. Each worker folds the records it receives
+    /// into a state of its own.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Scan, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 10u64).to_stream(scope)
+    ///                 .delay(|x, _| 9 - x)
+    ///                 .scan(0, |sum, _time, records| *sum += records.iter().sum::<u64>())
+    ///                 .capture()
+    /// });
+    ///
+    /// let extracted = captured.extract();
+    /// assert_eq!(extracted[0], (0, vec![9]));
+    /// assert_eq!(extracted[9], (9, vec![45]));
+    /// ```
+    fn scan<S, F>(&self, initial: S, mut fold: F) -> Stream<G, S>
+    where
+        S: Data,
+        F: FnMut(&mut S, &G::Timestamp, Vec<D>)+'static,
+    {
+        self.scan_with(initial, move |state, time, records, output| {
+            fold(state, time, records);
+            output.push(state.clone());
+        })
+    }
+
+    /// Folds the records of each time into a state starting at `initial`, with `fold` producing
+    /// records at the time.
+    ///
+    /// Times are folded as for [`scan`](Scan::scan), and `fold` is offered a buffer of records
+    /// to produce at the time it folds.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Scan, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// // Reports each record that is a new maximum.
+    /// let captured = timely::example(|scope| {
+    ///     vec![3, 1, 4, 1, 5, 9, 2, 6u64].into_iter()
+    ///         .enumerate()
+    ///         .map(|(time, x)| (time as u64, x))
+    ///         .to_stream(scope)
+    ///         .delay(|(time, _), _| *time)
+    ///         .scan_with(0, |max, _time, records, output| {
+    ///             for (_, x) in records {
+    ///                 if x > *max { *max = x; output.push(x); }
+    ///             }
+    ///         })
+    ///         .capture()
+    /// });
+    ///
+    /// let maxima = captured.extract().into_iter().flat_map(|(_, x)| x).collect::<Vec<_>>();
+    /// assert_eq!(maxima, vec![3, 4, 5, 9]);
+    /// ```
+    fn scan_with<S, R, F>(&self, initial: S, fold: F) -> Stream<G, R>
+    where
+        S: 'static,
+        R: Data,
+        F: FnMut(&mut S, &G::Timestamp, Vec<D>, &mut Vec<R>)+'static;
+}
+
+impl<G: Scope, D: Data> Scan<G, D> for Stream<G, D> {
+    fn scan_with<S, R, F>(&self, initial: S, mut fold: F) -> Stream<G, R>
+    where
+        S: 'static,
+        R: Data,
+        F: FnMut(&mut S, &G::Timestamp, Vec<D>, &mut Vec<R>)+'static,
+    {
+        let mut state = initial;
+        self.unary_stateful(Pipeline, "Scan",
+            |input, _output, pending| {
+                input.for_each(|time, data| {
+                    pending.state_with(&time, Vec::new).append(&mut data.replace(Vec::new()));
+                });
+            },
+            move |time, records, output| fold(&mut state, time, records, output),
+        )
+    }
+}
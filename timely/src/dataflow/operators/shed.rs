@@ -0,0 +1,88 @@
+//! Load shedding of records that lag behind a reference stream.
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::builder_rc::OperatorBuilder;
+use crate::order::PartialOrder;
+use crate::progress::{Antichain, PathSummary, Timestamp};
+
+/// Extension trait for shedding records that lag too far behind.
+pub trait Shed<G: Scope, D: Data> {
+    /// Drops records whose timestamps lag more than `threshold` behind the frontier of `reference`.
+    ///
+    /// A record at time `t` is shed if `t` advanced by `threshold` is strictly less than some
+    /// element of the frontier of `reference`, as observed when the record arrives, and is
+    /// otherwise passed to the first output. For each batch of records shed, the number shed is
+    /// produced at the batch's time on the second output. The records of `reference` are
+    /// discarded, and its frontier does not hold back either output.
+    ///
+    /// This allows latency-sensitive pipelines to discard work that is already late, for example
+    /// by using the frontier of an input as the reference, rather than fall further behind.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Inspect, Probe};
+    /// use timely::dataflow::operators::shed::Shed;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let mut events = InputHandle::<u64, u64>::new();
+    ///     let mut clock = InputHandle::<u64, ()>::new();
+    ///     let probe = worker.dataflow(|scope| {
+    ///         let clock = scope.input_from(&mut clock);
+    ///         let (kept, shed) = scope.input_from(&mut events).shed_if_behind(&clock, 5);
+    ///         shed.inspect(|count| println!("shed {} records", count));
+    ///         kept.probe()
+    ///     });
+    ///
+    ///     // The clock has reached 10, and so records at times before 5 are shed.
+    ///     clock.advance_to(10);
+    ///     worker.step();
+    ///     events.send(0);
+    ///     events.advance_to(11);
+    ///     worker.step_while(|| probe.less_than(events.time()));
+    /// }).unwrap();
+    /// ```
+    fn shed_if_behind<D2: Data>(&self, reference: &Stream<G, D2>, threshold: <G::Timestamp as Timestamp>::Summary) -> (Stream<G, D>, Stream<G, usize>);
+}
+
+impl<G: Scope, D: Data> Shed<G, D> for Stream<G, D> {
+    fn shed_if_behind<D2: Data>(&self, reference: &Stream<G, D2>, threshold: <G::Timestamp as Timestamp>::Summary) -> (Stream<G, D>, Stream<G, usize>) {
+        let mut builder = OperatorBuilder::new("ShedIfBehind".to_owned(), self.scope());
+
+        let mut input = builder.new_input(self, Pipeline);
+        let mut clock = builder.new_input_connection(reference, Pipeline, Vec::new());
+        // Both outputs depend on the data input, and neither on the reference.
+        let connection = vec![Antichain::from_elem(Default::default()), Antichain::new()];
+        let (mut kept, kept_stream) = builder.new_output_connection(connection.clone());
+        let (mut shed, shed_stream) = builder.new_output_connection(connection);
+
+        builder.build(move |_| {
+            let mut vector = Vec::new();
+            let mut discard = Vec::new();
+            move |frontiers| {
+                clock.for_each(|_time, data| data.swap(&mut discard));
+                discard.clear();
+                let reference = frontiers[1].frontier();
+                let mut kept = kept.activate();
+                let mut shed = shed.activate();
+                input.for_each(|time, data| {
+                    let behind = threshold
+                        .results_in(time.time())
+                        .map(|advanced| reference.iter().any(|t| advanced.less_than(t)))
+                        .unwrap_or(false);
+                    if behind {
+                        shed.session(&time).give(data.len());
+                    }
+                    else {
+                        data.swap(&mut vector);
+                        kept.session(&time).give_vec(&mut vector);
+                    }
+                });
+            }
+        });
+
+        (kept_stream, shed_stream)
+    }
+}
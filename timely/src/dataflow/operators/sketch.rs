@@ -0,0 +1,493 @@
+//! Approximate summaries of the records of each timestamp.
+//!
+//! Sketches summarize many records in little space, and two sketches of the same kind merge into
+//! a sketch of the records of both. The operators of [`Sketch`] have each worker sketch the
+//! records it receives at a timestamp, and once the input frontier passes the timestamp send its
+//! sketch to the first worker, which merges the sketches of all workers and produces the merged
+//! sketch at the timestamp. Timestamps without records produce no sketch.
+//!
+//! Records are hashed with `DefaultHasher` and its fixed keys, so that all workers hash equal
+//! records equally.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use abomonation::Abomonation;
+
+use crate::{Data, ExchangeData};
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::{Exchange, Pipeline};
+use crate::dataflow::operators::Map;
+use crate::dataflow::operators::generic::Operator;
+
+/// A summary that merges with summaries of the same kind.
+pub trait Merge {
+    /// Merges `other` into this summary, which then summarizes the records of both.
+    fn merge(&mut self, other: &Self);
+}
+
+/// Hashes `item`, after `seed`.
+fn hash<D: Hash+?Sized>(seed: u64, item: &D) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Estimates the number of distinct records.
+///
+/// A HyperLogLog sketch with precision `p` holds `2^p` registers of one byte, and estimates the
+/// number of distinct records with a relative standard error of about `1.04 / 2^(p/2)`.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::sketch::HyperLogLog;
+///
+/// let mut sketch = HyperLogLog::new(12);
+/// for record in 0 .. 100_000u64 {
+///     sketch.insert(&(record % 10_000));
+/// }
+/// let estimate = sketch.estimate() as f64;
+/// assert!((estimate - 10_000.0).abs() < 500.0);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct HyperLogLog {
+    precision: u32,
+    /// For each register, the greatest rank of the hashes it has seen.
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// Creates an empty sketch with `2^precision` registers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `precision` is not between 4 and 16.
+    pub fn new(precision: u32) -> Self {
+        assert!((4 ..= 16).contains(&precision), "HyperLogLog precision must be between 4 and 16, not {}", precision);
+        HyperLogLog { precision, registers: vec![0; 1 << precision] }
+    }
+
+    /// Records `item`.
+    pub fn insert<D: Hash+?Sized>(&mut self, item: &D) {
+        let hash = hash(0, item);
+        let register = (hash >> (64 - self.precision)) as usize;
+        // The rank is the position of the first set bit after the register bits, which are
+        // replaced by a guard bit so that the rank is at most `65 - precision`.
+        let rank = ((hash << self.precision) | (1 << (self.precision - 1))).leading_zeros() + 1;
+        self.registers[register] = self.registers[register].max(rank as u8);
+    }
+
+    /// The estimated number of distinct records recorded.
+    pub fn estimate(&self) -> u64 {
+        let registers = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / registers),
+        };
+        let sum = self.registers.iter().map(|rank| 2f64.powi(-(*rank as i32))).sum::<f64>();
+        let estimate = alpha * registers * registers / sum;
+        let empty = self.registers.iter().filter(|rank| **rank == 0).count();
+        // Small cardinalities are better estimated by the number of empty registers.
+        if estimate <= 2.5 * registers && empty > 0 {
+            (registers * (registers / empty as f64).ln()).round() as u64
+        }
+        else {
+            estimate.round() as u64
+        }
+    }
+}
+
+impl Abomonation for HyperLogLog {
+    #[inline] unsafe fn entomb<W: ::std::io::Write>(&self, write: &mut W) -> ::std::io::Result<()> {
+        self.precision.entomb(write)?;
+        self.registers.entomb(write)
+    }
+    #[inline] unsafe fn exhume<'b>(&mut self, bytes: &'b mut [u8]) -> Option<&'b mut [u8]> {
+        let bytes = self.precision.exhume(bytes)?;
+        self.registers.exhume(bytes)
+    }
+    #[inline] fn extent(&self) -> usize {
+        self.precision.extent() + self.registers.extent()
+    }
+}
+
+impl Merge for HyperLogLog {
+    /// # Panics
+    ///
+    /// Panics if the sketches have different precisions.
+    fn merge(&mut self, other: &Self) {
+        assert_eq!(self.precision, other.precision, "merged HyperLogLog sketches must have equal precisions");
+        for (rank, other) in self.registers.iter_mut().zip(other.registers.iter()) {
+            *rank = (*rank).max(*other);
+        }
+    }
+}
+
+/// Estimates the number of times each record occurs.
+///
+/// A count-min sketch holds `depth` rows of `width` counts. Each record increments one count in
+/// each row, and its estimated number of occurrences is the least of its counts. Estimates are
+/// never less than the true number, and with probability `1 - 1/e^depth` exceed it by at most
+/// `e/width` of the number of records.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::sketch::CountMin;
+///
+/// let mut sketch = CountMin::new(1024, 4);
+/// for record in 0 .. 1000u64 {
+///     sketch.insert(&(record % 10));
+/// }
+/// assert!(sketch.estimate(&3u64) >= 100);
+/// assert!(sketch.estimate(&3u64) < 110);
+/// assert_eq!(sketch.total(), 1000);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct CountMin {
+    width: usize,
+    depth: usize,
+    /// The counts of each row, one row after another.
+    counts: Vec<u64>,
+    total: u64,
+}
+
+impl CountMin {
+    /// Creates an empty sketch of `depth` rows of `width` counts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `depth` is zero.
+    pub fn new(width: usize, depth: usize) -> Self {
+        assert!(width > 0 && depth > 0, "count-min sketches require a positive width and depth");
+        CountMin { width, depth, counts: vec![0; width * depth], total: 0 }
+    }
+
+    /// Records `count` occurrences of `item`.
+    pub fn add<D: Hash+?Sized>(&mut self, item: &D, count: u64) {
+        for row in 0 .. self.depth {
+            let column = (hash(row as u64, item) % self.width as u64) as usize;
+            self.counts[row * self.width + column] += count;
+        }
+        self.total += count;
+    }
+
+    /// Records one occurrence of `item`.
+    pub fn insert<D: Hash+?Sized>(&mut self, item: &D) {
+        self.add(item, 1);
+    }
+
+    /// The estimated number of occurrences of `item`.
+    pub fn estimate<D: Hash+?Sized>(&self, item: &D) -> u64 {
+        (0 .. self.depth)
+            .map(|row| self.counts[row * self.width + (hash(row as u64, item) % self.width as u64) as usize])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// The number of occurrences recorded, of all records.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+}
+
+impl Abomonation for CountMin {
+    #[inline] unsafe fn entomb<W: ::std::io::Write>(&self, write: &mut W) -> ::std::io::Result<()> {
+        self.width.entomb(write)?;
+        self.depth.entomb(write)?;
+        self.counts.entomb(write)?;
+        self.total.entomb(write)
+    }
+    #[inline] unsafe fn exhume<'b>(&mut self, bytes: &'b mut [u8]) -> Option<&'b mut [u8]> {
+        let bytes = self.width.exhume(bytes)?;
+        let bytes = self.depth.exhume(bytes)?;
+        let bytes = self.counts.exhume(bytes)?;
+        self.total.exhume(bytes)
+    }
+    #[inline] fn extent(&self) -> usize {
+        self.width.extent() + self.depth.extent() + self.counts.extent() + self.total.extent()
+    }
+}
+
+impl Merge for CountMin {
+    /// # Panics
+    ///
+    /// Panics if the sketches have different widths or depths.
+    fn merge(&mut self, other: &Self) {
+        assert!(self.width == other.width && self.depth == other.depth, "merged count-min sketches must have equal dimensions");
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += *other;
+        }
+        self.total += other.total;
+    }
+}
+
+/// Estimates the quantiles of records.
+///
+/// The sketch holds records in levels of at most `capacity` records, each record at level `i`
+/// standing for `2^i` records. A full level is compacted by sorting it and promoting every
+/// other record to the next level. With `n` records the sketch holds about
+/// `capacity * log2(n / capacity)` records, and the rank of an estimated quantile is typically
+/// within a few times `n / capacity` of the requested rank.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::sketch::Quantiles;
+///
+/// let mut sketch = Quantiles::new(128);
+/// for record in 0 .. 10_000u64 {
+///     sketch.insert(record);
+/// }
+/// let median = sketch.quantile(0.5).unwrap();
+/// assert!(4_500 <= median && median <= 5_500);
+/// assert!(sketch.quantile(0.0).unwrap() < 100);
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Quantiles<T> {
+    capacity: usize,
+    /// The records of each level, each at level `i` standing for `2^i` records.
+    levels: Vec<Vec<T>>,
+    count: u64,
+    /// Alternates which of each pair of records a compaction promotes, to balance its error.
+    odd: bool,
+}
+
+impl<T: Ord+Clone> Quantiles<T> {
+    /// Creates an empty sketch holding at most `capacity` records at each level.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is less than two.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity >= 2, "quantile sketches require a capacity of at least two");
+        Quantiles { capacity, levels: vec![Vec::new()], count: 0, odd: false }
+    }
+
+    /// Records `item`.
+    pub fn insert(&mut self, item: T) {
+        self.levels[0].push(item);
+        self.count += 1;
+        self.compact();
+    }
+
+    /// The number of records recorded.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// The estimated record at quantile `q`, between zero and one.
+    ///
+    /// Returns `None` if no records have been recorded.
+    pub fn quantile(&self, q: f64) -> Option<T> {
+        let mut weighted = self.levels.iter().enumerate()
+            .flat_map(|(level, items)| items.iter().map(move |item| (item, 1u64 << level)))
+            .collect::<Vec<_>>();
+        weighted.sort_by(|x, y| x.0.cmp(y.0));
+        let total = weighted.iter().map(|(_, weight)| weight).sum::<u64>();
+        let rank = (q.clamp(0.0, 1.0) * total as f64).floor() as u64;
+        let mut seen = 0;
+        for (item, weight) in weighted.iter() {
+            seen += weight;
+            if seen > rank {
+                return Some((*item).clone());
+            }
+        }
+        weighted.last().map(|(item, _)| (*item).clone())
+    }
+
+    /// Promotes half of the records of each full level to the next level.
+    fn compact(&mut self) {
+        let mut level = 0;
+        while level < self.levels.len() {
+            if self.levels[level].len() > self.capacity {
+                let mut items = ::std::mem::take(&mut self.levels[level]);
+                items.sort();
+                // An odd record out stays at its level.
+                if items.len() % 2 == 1 {
+                    self.levels[level].push(items.pop().unwrap());
+                }
+                let offset = if self.odd { 1 } else { 0 };
+                self.odd = !self.odd;
+                let promoted = items.into_iter().skip(offset).step_by(2);
+                if level + 1 == self.levels.len() {
+                    self.levels.push(Vec::new());
+                }
+                self.levels[level + 1].extend(promoted);
+            }
+            level += 1;
+        }
+    }
+}
+
+impl<T: Abomonation> Abomonation for Quantiles<T> {
+    #[inline] unsafe fn entomb<W: ::std::io::Write>(&self, write: &mut W) -> ::std::io::Result<()> {
+        self.capacity.entomb(write)?;
+        self.levels.entomb(write)?;
+        self.count.entomb(write)?;
+        self.odd.entomb(write)
+    }
+    #[inline] unsafe fn exhume<'b>(&mut self, bytes: &'b mut [u8]) -> Option<&'b mut [u8]> {
+        let bytes = self.capacity.exhume(bytes)?;
+        let bytes = self.levels.exhume(bytes)?;
+        let bytes = self.count.exhume(bytes)?;
+        self.odd.exhume(bytes)
+    }
+    #[inline] fn extent(&self) -> usize {
+        self.capacity.extent() + self.levels.extent() + self.count.extent() + self.odd.extent()
+    }
+}
+
+impl<T: Ord+Clone> Merge for Quantiles<T> {
+    /// # Panics
+    ///
+    /// Panics if the sketches have different capacities.
+    fn merge(&mut self, other: &Self) {
+        assert_eq!(self.capacity, other.capacity, "merged quantile sketches must have equal capacities");
+        for (level, items) in other.levels.iter().enumerate() {
+            if level == self.levels.len() {
+                self.levels.push(Vec::new());
+            }
+            self.levels[level].extend(items.iter().cloned());
+        }
+        self.count += other.count;
+        self.compact();
+    }
+}
+
+/// Extension trait for sketching the records of each timestamp.
+pub trait Sketch<G: Scope, D: Data> {
+    /// Sketches the distinct records of each timestamp, with `2^precision` registers.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::sketch::Sketch;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 1000u64).map(|x| x % 100)
+    ///                   .to_stream(scope)
+    ///                   .distinct_sketch(10)
+    ///                   .capture()
+    /// });
+    ///
+    /// let sketches = captured.extract();
+    /// let estimate = sketches[0].1[0].estimate();
+    /// assert!(90 <= estimate && estimate <= 110);
+    /// ```
+    fn distinct_sketch(&self, precision: u32) -> Stream<G, HyperLogLog> where D: Hash;
+
+    /// Estimates the number of distinct records of each timestamp, with `2^precision` registers.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::sketch::Sketch;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 10u64).to_stream(scope)
+    ///                 .approximate_distinct(8)
+    ///                 .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(0, vec![10])]);
+    /// ```
+    fn approximate_distinct(&self, precision: u32) -> Stream<G, u64> where D: Hash {
+        self.distinct_sketch(precision).map(|sketch| sketch.estimate())
+    }
+
+    /// Sketches the number of occurrences of the records of each timestamp, in `depth` rows of
+    /// `width` counts.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::sketch::Sketch;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 100u64).map(|x| x % 4)
+    ///                  .to_stream(scope)
+    ///                  .count_min_sketch(64, 3)
+    ///                  .capture()
+    /// });
+    ///
+    /// let sketches = captured.extract();
+    /// assert!(sketches[0].1[0].estimate(&2u64) >= 25);
+    /// ```
+    fn count_min_sketch(&self, width: usize, depth: usize) -> Stream<G, CountMin> where D: Hash;
+
+    /// Sketches the quantiles of the records of each timestamp, with levels of at most
+    /// `capacity` records.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::sketch::Sketch;
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 1000u64).to_stream(scope)
+    ///                   .quantile_sketch(64)
+    ///                   .capture()
+    /// });
+    ///
+    /// let sketches = captured.extract();
+    /// let median = sketches[0].1[0].quantile(0.5).unwrap();
+    /// assert!(400 <= median && median <= 600);
+    /// ```
+    fn quantile_sketch(&self, capacity: usize) -> Stream<G, Quantiles<D>> where D: ExchangeData+Ord;
+}
+
+impl<G: Scope, D: Data> Sketch<G, D> for Stream<G, D> {
+    fn distinct_sketch(&self, precision: u32) -> Stream<G, HyperLogLog> where D: Hash {
+        sketch(self, "DistinctSketch", move || HyperLogLog::new(precision), |sketch, record| sketch.insert(record))
+    }
+
+    fn count_min_sketch(&self, width: usize, depth: usize) -> Stream<G, CountMin> where D: Hash {
+        sketch(self, "CountMinSketch", move || CountMin::new(width, depth), |sketch, record| sketch.insert(record))
+    }
+
+    fn quantile_sketch(&self, capacity: usize) -> Stream<G, Quantiles<D>> where D: ExchangeData+Ord {
+        sketch(self, "QuantileSketch", move || Quantiles::new(capacity), |sketch, record: &D| sketch.insert(record.clone()))
+    }
+}
+
+/// Sketches the records of each timestamp on each worker, and merges the sketches of all workers
+/// on the first worker.
+fn sketch<G, D, S, N, I>(stream: &Stream<G, D>, name: &str, new: N, insert: I) -> Stream<G, S>
+where
+    G: Scope,
+    D: Data,
+    S: ExchangeData+Merge,
+    N: Fn()->S+Clone+'static,
+    I: Fn(&mut S, &D)+'static,
+{
+    let merged = new.clone();
+    stream
+        .unary_stateful(Pipeline, name,
+            move |input, _output, sketches| {
+                input.for_each(|time, data| {
+                    let sketch = sketches.state_with(&time, &new);
+                    for record in data.iter() {
+                        insert(sketch, record);
+                    }
+                });
+            },
+            |_time, sketch, output| output.push(sketch),
+        )
+        .unary_stateful(Exchange::new(|_| 0), &format!("{}Merge", name),
+            move |input, _output, sketches| {
+                input.for_each(|time, data| {
+                    let sketch = sketches.state_with(&time, &merged);
+                    for other in data.iter() {
+                        sketch.merge(other);
+                    }
+                });
+            },
+            |_time, sketch, output| output.push(sketch),
+        )
+}
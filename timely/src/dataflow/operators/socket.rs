@@ -0,0 +1,396 @@
+//! Sources and sinks exchanging framed records with sockets.
+//!
+//! A [`Codec`] divides the bytes of a connection into frames, each a record, and writes records
+//! as frames. [`LengthPrefixed`] frames bytes behind their length, [`Lines`] frames text by line
+//! breaks, and other framings implement the trait.
+//!
+//! Each operator reads or writes its connection on a thread of its own, so that a connection
+//! that is slow or idle does not block the worker. The reading thread activates the source with
+//! a [`SyncActivator`](crate::scheduling::SyncActivator) when it has decoded records, so that the
+//! source is scheduled only when it has work to do, and a worker parked in
+//! [`step_or_park`](crate::worker::Worker::step_or_park) wakes to do it.
+//!
+//! The operators accept any connected stream implementing `Read` or `Write`, for example a
+//! `std::net::TcpStream` or a `std::os::unix::net::UnixStream`, and each worker supplies its own
+//! connection. A connection may be shared by a source and a sink through `try_clone`.
+//!
+//! # Examples
+//! ```
+//! use std::io::{BufRead, BufReader, Write};
+//! use std::net::{TcpListener, TcpStream};
+//! use timely::dataflow::operators::Map;
+//! use timely::dataflow::operators::socket::{Lines, SocketSink, SocketSource};
+//!
+//! let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+//! let address = listener.local_addr().unwrap();
+//!
+//! // A client sends three lines, and reads back the lines echoed by the dataflow.
+//! let client = std::thread::spawn(move || {
+//!     let mut stream = TcpStream::connect(address).unwrap();
+//!     stream.write_all(b"one\ntwo\nthree\n").unwrap();
+//!     stream.shutdown(std::net::Shutdown::Write).unwrap();
+//!     BufReader::new(stream).lines().map(|line| line.unwrap()).collect::<Vec<_>>()
+//! });
+//!
+//! let (stream, _) = listener.accept().unwrap();
+//! let writer = stream.try_clone().unwrap();
+//! timely::execute_directly(move |worker| {
+//!     let sink = worker.dataflow::<u64,_,_>(|scope| {
+//!         scope.socket_source("Echo", stream, Lines, |_line| 0)
+//!              .map(|line| line.to_uppercase())
+//!              .socket_sink("Reply", writer, Lines)
+//!     });
+//!     // The worker parks until the reading thread activates the source.
+//!     while worker.step_or_park(None) { }
+//!     sink.join().unwrap().unwrap();
+//! });
+//!
+//! assert_eq!(client.join().unwrap(), vec!["ONE", "TWO", "THREE"]);
+//! ```
+
+use std::io::{ErrorKind, Read, Write};
+use std::sync::mpsc::{sync_channel, TryRecvError};
+use std::thread::JoinHandle;
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::{source, Operator};
+use crate::order::PartialOrder;
+
+// The number of decoded batches a reading thread may run ahead of its source.
+const BATCHES_IN_FLIGHT: usize = 16;
+
+// The size of each read from a connection.
+const READ_SIZE: usize = 1 << 16;
+
+/// Divides the bytes of a connection into records, and writes records as bytes.
+///
+/// A codec is moved to the thread reading or writing its connection.
+pub trait Codec : Send+'static {
+    /// The type of records.
+    type Item: Data+Send;
+
+    /// Decodes the first record of `bytes`, returning it and the number of bytes it occupies,
+    /// or `None` if `bytes` does not yet contain a complete frame.
+    fn decode(&mut self, bytes: &[u8]) -> std::io::Result<Option<(Self::Item, usize)>>;
+
+    /// Decodes the first record of `bytes`, the remaining bytes once the connection has closed.
+    ///
+    /// By default, the remaining bytes must not contain an incomplete frame.
+    fn decode_eof(&mut self, bytes: &[u8]) -> std::io::Result<Option<(Self::Item, usize)>> {
+        match self.decode(bytes)? {
+            None if !bytes.is_empty() => Err(std::io::Error::new(ErrorKind::UnexpectedEof, "connection closed within a frame")),
+            decoded => Ok(decoded),
+        }
+    }
+
+    /// Appends the frame of `item` to `bytes`.
+    fn encode(&mut self, item: &Self::Item, bytes: &mut Vec<u8>) -> std::io::Result<()>;
+}
+
+/// Frames of bytes, each preceded by its length as a big-endian `u32`.
+#[derive(Debug, Clone, Copy)]
+pub struct LengthPrefixed {
+    max_length: usize,
+}
+
+impl LengthPrefixed {
+    /// Frames of any length that fits in a `u32`.
+    pub fn new() -> Self {
+        Self::with_max_length(u32::MAX as usize)
+    }
+
+    /// Frames of at most `max_length` bytes, longer frames being errors.
+    pub fn with_max_length(max_length: usize) -> Self {
+        LengthPrefixed { max_length: max_length.min(u32::MAX as usize) }
+    }
+}
+
+impl Default for LengthPrefixed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Codec for LengthPrefixed {
+    type Item = Vec<u8>;
+
+    fn decode(&mut self, bytes: &[u8]) -> std::io::Result<Option<(Vec<u8>, usize)>> {
+        if bytes.len() < 4 {
+            return Ok(None);
+        }
+        let length = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+        if length > self.max_length {
+            let message = format!("frame of {} bytes exceeds the limit of {} bytes", length, self.max_length);
+            return Err(std::io::Error::new(ErrorKind::InvalidData, message));
+        }
+        if bytes.len() < 4 + length {
+            return Ok(None);
+        }
+        Ok(Some((bytes[4 .. 4 + length].to_vec(), 4 + length)))
+    }
+
+    fn encode(&mut self, item: &Vec<u8>, bytes: &mut Vec<u8>) -> std::io::Result<()> {
+        if item.len() > self.max_length {
+            let message = format!("frame of {} bytes exceeds the limit of {} bytes", item.len(), self.max_length);
+            return Err(std::io::Error::new(ErrorKind::InvalidInput, message));
+        }
+        bytes.extend_from_slice(&(item.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(item);
+        Ok(())
+    }
+}
+
+/// Lines of UTF-8 text, each ended by `\n` or `\r\n`, which is removed.
+///
+/// A final line without a line break is decoded once the connection closes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lines;
+
+impl Lines {
+    fn line(bytes: &[u8]) -> std::io::Result<String> {
+        let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+        String::from_utf8(bytes.to_vec()).map_err(|error| std::io::Error::new(ErrorKind::InvalidData, error))
+    }
+}
+
+impl Codec for Lines {
+    type Item = String;
+
+    fn decode(&mut self, bytes: &[u8]) -> std::io::Result<Option<(String, usize)>> {
+        match bytes.iter().position(|byte| *byte == b'\n') {
+            Some(end) => Ok(Some((Self::line(&bytes[.. end])?, end + 1))),
+            None => Ok(None),
+        }
+    }
+
+    fn decode_eof(&mut self, bytes: &[u8]) -> std::io::Result<Option<(String, usize)>> {
+        match self.decode(bytes)? {
+            None if !bytes.is_empty() => Ok(Some((Self::line(bytes)?, bytes.len()))),
+            decoded => Ok(decoded),
+        }
+    }
+
+    fn encode(&mut self, item: &String, bytes: &mut Vec<u8>) -> std::io::Result<()> {
+        bytes.extend_from_slice(item.as_bytes());
+        bytes.push(b'\n');
+        Ok(())
+    }
+}
+
+/// Reads records from a socket.
+pub trait SocketSource : Scope {
+    /// Reads the records framed by `codec` from `socket`, at times assigned by `time`.
+    ///
+    /// The source holds a capability while the connection is open, at the time of the last
+    /// record read, so that the frontier of the stream follows the times of its records and is
+    /// empty once the connection closes. The times of records must therefore not decrease. A
+    /// connection reset by its peer closes the connection as the end of its bytes does.
+    ///
+    /// The thread reading `socket` ends once the connection closes. If the dataflow is dropped
+    /// before then, the thread ends once it next reads from the connection.
+    ///
+    /// # Panics
+    ///
+    /// The source panics if reading or decoding fails, or if a record is assigned a time earlier
+    /// than that of the record before it.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(unix)] {
+    /// use std::io::Write;
+    /// use std::os::unix::net::UnixStream;
+    /// use timely::dataflow::operators::Capture;
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::socket::{LengthPrefixed, SocketSource};
+    ///
+    /// let (mut client, server) = UnixStream::pair().unwrap();
+    /// for record in [&b""[..], &b"a"[..], &b"b"[..], &b"cd"[..]] {
+    ///     client.write_all(&(record.len() as u32).to_be_bytes()).unwrap();
+    ///     client.write_all(record).unwrap();
+    /// }
+    /// drop(client);
+    ///
+    /// let captured = timely::example(move |scope| {
+    ///     scope.socket_source("Frames", server, LengthPrefixed::new(), |bytes| bytes.len() as u64)
+    ///          .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![
+    ///     (0, vec![b"".to_vec()]),
+    ///     (1, vec![b"a".to_vec(), b"b".to_vec()]),
+    ///     (2, vec![b"cd".to_vec()]),
+    /// ]);
+    /// # }
+    /// ```
+    fn socket_source<R, C, F>(&mut self, name: &str, socket: R, codec: C, time: F) -> Stream<Self, C::Item>
+    where
+        R: Read+Send+'static,
+        C: Codec,
+        F: FnMut(&C::Item)->Self::Timestamp+'static;
+}
+
+impl<G: Scope> SocketSource for G {
+    fn socket_source<R, C, F>(&mut self, name: &str, socket: R, codec: C, mut time: F) -> Stream<G, C::Item>
+    where
+        R: Read+Send+'static,
+        C: Codec,
+        F: FnMut(&C::Item)->G::Timestamp+'static,
+    {
+        let scope = self.clone();
+        source(self, name, move |capability, info| {
+
+            let activator = scope.sync_activator_for(&info.address[..]);
+            let (send, recv) = sync_channel(BATCHES_IN_FLIGHT);
+            std::thread::spawn(move || {
+                let result = read_frames(socket, codec, |batch| {
+                    send.send(Ok(batch)).is_ok() && activator.activate().is_ok()
+                });
+                if let Err(error) = result {
+                    let _ = send.send(Err(error));
+                }
+                drop(send);
+                let _ = activator.activate();
+            });
+
+            let mut capability = Some(capability);
+            move |output| {
+                let mut closed = false;
+                if let Some(cap) = capability.as_mut() {
+                    loop {
+                        match recv.try_recv() {
+                            Ok(Ok(batch)) => {
+                                for record in batch {
+                                    let time = time(&record);
+                                    assert!(cap.time().less_equal(&time), "socket source assigned a decreasing time");
+                                    cap.downgrade(&time);
+                                    output.session(cap).give(record);
+                                }
+                            },
+                            Ok(Err(error)) => panic!("failed to read from socket: {}", error),
+                            Err(TryRecvError::Empty) => break,
+                            Err(TryRecvError::Disconnected) => { closed = true; break; },
+                        }
+                    }
+                }
+                if closed {
+                    capability = None;
+                }
+            }
+        })
+    }
+}
+
+/// Reads `socket` until it closes, decoding its frames into batches passed to `deliver`, which
+/// returns false if no further batches are wanted.
+fn read_frames<R: Read, C: Codec>(mut socket: R, mut codec: C, mut deliver: impl FnMut(Vec<C::Item>)->bool) -> std::io::Result<()> {
+    let mut bytes = Vec::new();
+    let mut chunk = vec![0u8; READ_SIZE];
+    loop {
+        let read = match socket.read(&mut chunk[..]) {
+            Ok(read) => read,
+            Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+            Err(error) if error.kind() == ErrorKind::ConnectionReset || error.kind() == ErrorKind::ConnectionAborted => 0,
+            Err(error) => return Err(error),
+        };
+        bytes.extend_from_slice(&chunk[.. read]);
+
+        let mut batch = Vec::new();
+        let mut offset = 0;
+        loop {
+            let decoded = if read == 0 { codec.decode_eof(&bytes[offset ..])? } else { codec.decode(&bytes[offset ..])? };
+            match decoded {
+                Some((item, length)) => {
+                    batch.push(item);
+                    offset += length;
+                },
+                None => break,
+            }
+        }
+        bytes.drain(.. offset);
+
+        if !batch.is_empty() && !deliver(batch) {
+            return Ok(());
+        }
+        if read == 0 {
+            return Ok(());
+        }
+    }
+}
+
+/// Writes records to a socket.
+pub trait SocketSink<D: Data> {
+    /// Writes the records of the stream to `socket`, framed by `codec`.
+    ///
+    /// Records are written in the order they arrive, which need not be the order of their times,
+    /// and the connection is flushed after each batch. The sink blocks the worker if the writing
+    /// thread falls behind by several batches. Once the frontier of the stream is empty,
+    /// the remaining records are written and the thread writing them ends, returning the first
+    /// error encountered, if any. Records arriving after an error are discarded.
+    ///
+    /// # Examples
+    /// ```
+    /// # #[cfg(unix)] {
+    /// use std::io::Read;
+    /// use std::os::unix::net::UnixStream;
+    /// use timely::dataflow::operators::ToStream;
+    /// use timely::dataflow::operators::socket::{Lines, SocketSink};
+    ///
+    /// let (server, mut client) = UnixStream::pair().unwrap();
+    /// let sink = timely::example(move |scope| {
+    ///     (0 .. 3).map(|x| x.to_string()).to_stream(scope).socket_sink("Lines", server, Lines)
+    /// });
+    /// sink.join().unwrap().unwrap();
+    ///
+    /// let mut text = String::new();
+    /// client.read_to_string(&mut text).unwrap();
+    /// assert_eq!(text, "0\n1\n2\n");
+    /// # }
+    /// ```
+    fn socket_sink<W, C>(&self, name: &str, socket: W, codec: C) -> JoinHandle<std::io::Result<()>>
+    where
+        W: Write+Send+'static,
+        C: Codec<Item=D>;
+}
+
+impl<G: Scope, D: Data+Send> SocketSink<D> for Stream<G, D> {
+    fn socket_sink<W, C>(&self, name: &str, mut socket: W, mut codec: C) -> JoinHandle<std::io::Result<()>>
+    where
+        W: Write+Send+'static,
+        C: Codec<Item=D>,
+    {
+        let (send, recv) = sync_channel::<Vec<D>>(BATCHES_IN_FLIGHT);
+        let writer = std::thread::spawn(move || {
+            let mut bytes = Vec::new();
+            for batch in recv {
+                for record in batch.iter() {
+                    codec.encode(record, &mut bytes)?;
+                }
+                socket.write_all(&bytes[..])?;
+                socket.flush()?;
+                bytes.clear();
+            }
+            Ok(())
+        });
+
+        let mut send = Some(send);
+        let mut vector = Vec::new();
+        self.sink(Pipeline, name, move |input| {
+            while let Some((_time, data)) = input.next() {
+                data.swap(&mut vector);
+                // a closed channel means the writer has failed, and the records are discarded.
+                if let Some(sender) = send.as_ref() {
+                    if sender.send(std::mem::take(&mut vector)).is_err() {
+                        send = None;
+                    }
+                }
+            }
+            if input.frontier().is_empty() {
+                send = None;
+            }
+        });
+
+        writer
+    }
+}
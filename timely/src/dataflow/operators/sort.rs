@@ -0,0 +1,171 @@
+//! Sorting of each timestamp's records, once the timestamp is complete.
+use std::collections::HashMap;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Sorts records by key, by merging sorted runs, and reuses the allocations of its runs.
+///
+/// Records are added in batches, each of which is sorted into a run. Runs of similar lengths are
+/// merged as they are added, so that the sorter holds a logarithmic number of runs, and the
+/// buffers released by each merge are retained for later runs and merges. The sort is stable:
+/// records with equal keys are produced in the order they were added.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::sort::MergeSorter;
+///
+/// let mut sorter = MergeSorter::new();
+/// sorter.push(&mut vec![5, 3, 1], &|x: &u64| *x);
+/// sorter.push(&mut vec![4, 2, 0], &|x: &u64| *x);
+/// let mut sorted = Vec::new();
+/// sorter.finish_into(&mut sorted, &|x: &u64| *x);
+/// assert_eq!(sorted, vec![0, 1, 2, 3, 4, 5]);
+/// ```
+pub struct MergeSorter<D> {
+    // Sorted runs, in the order their records were added, with non-increasing lengths.
+    runs: Vec<Vec<D>>,
+    // Empty buffers, retained for reuse.
+    stash: Vec<Vec<D>>,
+}
+
+impl<D> MergeSorter<D> {
+    /// Allocates a new empty sorter.
+    pub fn new() -> Self {
+        MergeSorter { runs: Vec::new(), stash: Vec::new() }
+    }
+
+    /// Returns `true` if the sorter holds no records.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Adds the records of `batch`, leaving `batch` empty.
+    pub fn push<K: Ord, F: Fn(&D)->K>(&mut self, batch: &mut Vec<D>, key: &F) {
+        if batch.is_empty() {
+            return;
+        }
+        let mut run = self.stash.pop().unwrap_or_default();
+        ::std::mem::swap(&mut run, batch);
+        run.sort_by_key(key);
+        self.runs.push(run);
+        while self.runs.len() > 1 && self.runs[self.runs.len() - 1].len() * 2 > self.runs[self.runs.len() - 2].len() {
+            let later = self.runs.pop().unwrap();
+            let earlier = self.runs.pop().unwrap();
+            let merged = self.merge(earlier, later, key);
+            self.runs.push(merged);
+        }
+    }
+
+    /// Produces all records in sorted order into `output`, leaving the sorter empty.
+    ///
+    /// If `output` is empty, its allocation is retained by the sorter for reuse.
+    pub fn finish_into<K: Ord, F: Fn(&D)->K>(&mut self, output: &mut Vec<D>, key: &F) {
+        while self.runs.len() > 1 {
+            let later = self.runs.pop().unwrap();
+            let earlier = self.runs.pop().unwrap();
+            let merged = self.merge(earlier, later, key);
+            self.runs.push(merged);
+        }
+        if let Some(mut run) = self.runs.pop() {
+            if output.is_empty() {
+                ::std::mem::swap(output, &mut run);
+                if run.capacity() > 0 {
+                    self.stash.push(run);
+                }
+            }
+            else {
+                output.append(&mut run);
+                self.stash.push(run);
+            }
+        }
+    }
+
+    /// Retains the allocation of `buffer` for later runs and merges.
+    pub fn recycle(&mut self, mut buffer: Vec<D>) {
+        buffer.clear();
+        if buffer.capacity() > 0 {
+            self.stash.push(buffer);
+        }
+    }
+
+    // Merges two sorted runs, preferring `earlier` among equal keys.
+    fn merge<K: Ord, F: Fn(&D)->K>(&mut self, mut earlier: Vec<D>, mut later: Vec<D>, key: &F) -> Vec<D> {
+        let mut merged = self.stash.pop().unwrap_or_default();
+        merged.reserve(earlier.len() + later.len());
+        {
+            let mut earlier = earlier.drain(..).peekable();
+            let mut later = later.drain(..).peekable();
+            loop {
+                let take_later = match (earlier.peek(), later.peek()) {
+                    (Some(x), Some(y)) => key(y) < key(x),
+                    (Some(_), None) => false,
+                    (None, Some(_)) => true,
+                    (None, None) => break,
+                };
+                merged.push(if take_later { later.next().unwrap() } else { earlier.next().unwrap() });
+            }
+        }
+        self.stash.push(earlier);
+        self.stash.push(later);
+        merged
+    }
+}
+
+impl<D> Default for MergeSorter<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Extension trait for sorting the records of each time.
+pub trait SortWithinEpoch<G: Scope, D: Data> {
+    /// Buffers the records of each time, and produces them sorted by `key` once the input
+    /// frontier has passed the time.
+    ///
+    /// Records with equal keys are produced in the order they arrived. The buffers used to sort
+    /// the records of each time are reused for later times.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Inspect};
+    /// use timely::dataflow::operators::sort::SortWithinEpoch;
+    ///
+    /// timely::example(|scope| {
+    ///     (0..10u64).rev()
+    ///               .to_stream(scope)
+    ///               .delay(|x, _| x % 2)
+    ///               .sort_within_epoch(|x| *x)
+    ///               .inspect_batch(|_time, batch| assert!(batch.windows(2).all(|w| w[0] <= w[1])));
+    /// });
+    /// ```
+    fn sort_within_epoch<K: Ord, F: Fn(&D)->K+'static>(&self, key: F) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> SortWithinEpoch<G, D> for Stream<G, D> {
+    fn sort_within_epoch<K: Ord, F: Fn(&D)->K+'static>(&self, key: F) -> Stream<G, D> {
+        let mut sorters = HashMap::<G::Timestamp, MergeSorter<D>>::new();
+        // Sorters of completed times, retained with their buffers for later times.
+        let mut spare = Vec::new();
+        let mut vector = Vec::new();
+        self.unary_notify(Pipeline, "SortWithinEpoch", vec![], move |input, output, notificator| {
+            input.for_each(|time, data| {
+                data.swap(&mut vector);
+                sorters.entry(time.time().clone())
+                       .or_insert_with(|| spare.pop().unwrap_or_default())
+                       .push(&mut vector, &key);
+                notificator.notify_at(time.retain());
+            });
+            notificator.for_each(|time, _, _| {
+                if let Some(mut sorter) = sorters.remove(time.time()) {
+                    let mut sorted = Vec::new();
+                    sorter.finish_into(&mut sorted, &key);
+                    output.session(&time).give_vec(&mut sorted);
+                    spare.push(sorter);
+                }
+            });
+        })
+    }
+}
@@ -0,0 +1,200 @@
+//! Attaching and detaching listeners to a stream while its dataflow runs.
+//!
+//! Operators like `inspect` and `capture_into` fix their consumers when the dataflow is built. A
+//! tap instead presents a [`TapHandle`], through which the worker may attach listeners to the
+//! stream between steps, and detach them again, for example to toggle a debugging tap. A tap
+//! without listeners discards the records it receives.
+
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use crate::Data;
+use crate::dataflow::{Scope, Stream};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::channels::pullers::Counter as PullCounter;
+use crate::dataflow::operators::generic::builder_raw::OperatorBuilder;
+use crate::dataflow::operators::capture::{Event, EventPusher};
+
+use crate::progress::ChangeBatch;
+use crate::progress::Timestamp;
+use crate::progress::frontier::MutableAntichain;
+
+/// Extension trait for tapping a stream.
+pub trait Tap<T: Timestamp, D: Data> {
+    /// Returns a handle to which listeners of this worker's part of the stream may be attached.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::sync::mpsc;
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Probe};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::tap::Tap;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let mut input = InputHandle::new();
+    ///     let (tap, probe) = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let stream = scope.input_from(&mut input);
+    ///         (stream.tee_handle(), stream.probe())
+    ///     });
+    ///
+    ///     // Records sent while no listener is attached are not observed.
+    ///     input.send(0);
+    ///     input.advance_to(1);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    ///
+    ///     let (send, recv) = mpsc::channel();
+    ///     let listener = tap.attach(send);
+    ///     input.send(1);
+    ///     input.advance_to(2);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    ///     assert!(tap.detach(listener));
+    ///
+    ///     input.send(2);
+    ///     input.close();
+    ///     worker.step_while(|| !probe.done());
+    ///     assert_eq!(recv.extract(), vec![(1, vec![1])]);
+    /// }).unwrap();
+    /// ```
+    fn tee_handle(&self) -> TapHandle<T, D>;
+}
+
+impl<S: Scope, D: Data> Tap<S::Timestamp, D> for Stream<S, D> {
+    fn tee_handle(&self) -> TapHandle<S::Timestamp, D> {
+
+        let handle = TapHandle { shared: Rc::new(RefCell::new(Listeners::new())) };
+        let shared = handle.shared.clone();
+
+        let mut builder = OperatorBuilder::new("Tap".to_owned(), self.scope());
+        let mut input = PullCounter::new(builder.new_input(self, Pipeline));
+        let mut started = false;
+
+        builder.build(
+            move |progress| {
+
+                if !started {
+                    // discard initial capability.
+                    progress.frontiers[0].update(S::Timestamp::minimum(), -1);
+                    started = true;
+                }
+
+                let mut shared = shared.borrow_mut();
+                if !progress.frontiers[0].is_empty() {
+                    let changes = ::std::mem::replace(&mut progress.frontiers[0], ChangeBatch::new()).into_inner();
+                    shared.frontier.update_iter(changes.iter().cloned());
+                    if !shared.listeners.is_empty() {
+                        shared.push(Event::Progress(changes));
+                    }
+                }
+
+                while let Some(message) = input.next() {
+                    if !shared.listeners.is_empty() {
+                        let time = message.time.clone();
+                        let data = ::std::mem::take(&mut message.as_mut().data);
+                        shared.push(Event::Messages(time, data));
+                    }
+                }
+                input.consumed().borrow_mut().drain_into(&mut progress.consumeds[0]);
+                false
+            }
+        );
+
+        handle
+    }
+}
+
+/// Identifies a listener attached to a `TapHandle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerId(usize);
+
+/// A handle to the listeners of a tapped stream.
+///
+/// Listeners receive the records the tap observes after they are attached, and the frontier
+/// of the stream in the form of `Event::Progress` changes, as if the stream was captured into
+/// them. The handle is local to the worker, which should attach and detach listeners between
+/// steps of the dataflow.
+pub struct TapHandle<T: Timestamp, D> {
+    shared: Rc<RefCell<Listeners<T, D>>>,
+}
+
+impl<T: Timestamp, D: Data> TapHandle<T, D> {
+    /// Attaches a listener, which first receives the current frontier of the stream.
+    pub fn attach<P: EventPusher<T, D>+'static>(&self, mut pusher: P) -> ListenerId {
+        let mut shared = self.shared.borrow_mut();
+        // Listeners assume the stream starts at the minimal frontier.
+        let mut changes = ChangeBatch::new();
+        changes.update(T::minimum(), -1);
+        changes.extend(shared.frontier.frontier().iter().map(|time| (time.clone(), 1)));
+        if !changes.is_empty() {
+            pusher.push(Event::Progress(changes.into_inner()));
+        }
+        let id = ListenerId(shared.next_id);
+        shared.next_id += 1;
+        shared.listeners.push((id, Box::new(pusher)));
+        id
+    }
+
+    /// Attaches a callback, which is called with the records the tap observes at each time.
+    pub fn attach_inspect<F: FnMut(&T, &[D])+'static>(&self, func: F) -> ListenerId {
+        self.attach(InspectPusher { func })
+    }
+
+    /// Detaches a listener, returning `true` if it was attached.
+    pub fn detach(&self, id: ListenerId) -> bool {
+        let mut shared = self.shared.borrow_mut();
+        let count = shared.listeners.len();
+        shared.listeners.retain(|(other, _)| *other != id);
+        shared.listeners.len() < count
+    }
+
+    /// The number of attached listeners.
+    pub fn listeners(&self) -> usize {
+        self.shared.borrow().listeners.len()
+    }
+}
+
+impl<T: Timestamp, D> Clone for TapHandle<T, D> {
+    fn clone(&self) -> Self {
+        TapHandle { shared: self.shared.clone() }
+    }
+}
+
+/// The listeners of a tap, and the frontier it has observed.
+struct Listeners<T: Timestamp, D> {
+    frontier: MutableAntichain<T>,
+    listeners: Vec<(ListenerId, Box<dyn EventPusher<T, D>>)>,
+    next_id: usize,
+}
+
+impl<T: Timestamp, D: Data> Listeners<T, D> {
+    fn new() -> Self {
+        Listeners {
+            frontier: MutableAntichain::new_bottom(T::minimum()),
+            listeners: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Presents `event` to each listener, cloning it for all but the last.
+    fn push(&mut self, event: Event<T, D>) {
+        if let Some(((_, last), rest)) = self.listeners.split_last_mut() {
+            for (_, listener) in rest.iter_mut() {
+                listener.push(event.clone());
+            }
+            last.push(event);
+        }
+    }
+}
+
+/// Presents the records of events to a callback.
+struct InspectPusher<F> {
+    func: F,
+}
+
+impl<T, D, F: FnMut(&T, &[D])> EventPusher<T, D> for InspectPusher<F> {
+    fn push(&mut self, event: Event<T, D>) {
+        if let Event::Messages(time, data) = event {
+            (self.func)(&time, &data[..]);
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! Limits the rate at which records pass through a stream.
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for limiting the rate of a stream.
+pub trait Throttle<G: Scope, D: Data> {
+    /// Produces at most `rate` records each second, as measured by the worker's clock.
+    ///
+    /// Records beyond the budget are not dropped, but retained with their timestamps and produced
+    /// once the budget admits them, in the order they arrived. The budget accrues only while
+    /// records are retained, so that records arriving after an idle period are not released in a
+    /// burst. The operator schedules its own activation for when the next retained record is due,
+    /// and holds capabilities for the times of retained records, so that the output frontier does
+    /// not pass them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Instant;
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::throttle::Throttle;
+    ///
+    /// let start = Instant::now();
+    /// let data = timely::example(|scope| {
+    ///     (0..100).to_stream(scope)
+    ///             .throttle(1000)
+    ///             .capture()
+    /// });
+    ///
+    /// assert!(start.elapsed().as_millis() >= 90);
+    /// assert_eq!(data.extract(), vec![(0, (0..100).collect::<Vec<_>>())]);
+    /// ```
+    fn throttle(&self, rate: usize) -> Stream<G, D>;
+}
+
+impl<G: Scope, D: Data> Throttle<G, D> for Stream<G, D> {
+    fn throttle(&self, rate: usize) -> Stream<G, D> {
+        assert!(rate > 0, "throttle requires a positive rate");
+        let scope = self.scope();
+        self.unary(Pipeline, "Throttle", move |_, info| {
+            let activator = scope.activator_for(&info.address[..]);
+            let clock = scope.clock();
+            // Retained records, with capabilities for their times, in order of arrival.
+            let mut pending = VecDeque::new();
+            let start = clock.now();
+            let mut sent = 0u128;
+            move |input, output| {
+                // Records permitted by the elapsed time, beyond those already sent.
+                let allowed = clock.elapsed(start).as_nanos() * (rate as u128) / 1_000_000_000 + 1;
+                if pending.is_empty() {
+                    // Forfeit the budget accrued while idle.
+                    sent = sent.max(allowed - 1);
+                }
+                input.for_each(|time, data| {
+                    pending.push_back((time.retain(), data.replace(Vec::new())));
+                });
+                while sent < allowed && !pending.is_empty() {
+                    let budget = (allowed - sent).min(pending[0].1.len() as u128) as usize;
+                    if pending[0].1.len() == budget {
+                        let (cap, mut batch) = pending.pop_front().unwrap();
+                        output.session(&cap).give_vec(&mut batch);
+                    }
+                    else {
+                        let (cap, batch) = &mut pending[0];
+                        output.session(cap).give_iterator(batch.drain(.. budget));
+                    }
+                    sent += budget as u128;
+                }
+                if !pending.is_empty() {
+                    let due = start + Duration::from_nanos((sent * 1_000_000_000 / (rate as u128)) as u64);
+                    activator.activate_after(due.saturating_duration_since(clock.now()));
+                }
+            }
+        })
+    }
+}
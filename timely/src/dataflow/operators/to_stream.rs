@@ -3,6 +3,7 @@
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 use crate::dataflow::channels::Message;
 use crate::dataflow::operators::generic::operator::source;
@@ -30,6 +31,31 @@ pub trait ToStream<T: Timestamp, D: Data> {
     /// assert_eq!(data1.extract(), data2.extract());
     /// ```
     fn to_stream<S: Scope<Timestamp=T>>(self, scope: &mut S) -> Stream<S, D>;
+
+    /// Converts to a timely `Stream`, introducing at most `rate` records each second.
+    ///
+    /// The operator yields between batches of records, rather than blocking the worker.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rate` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Instant;
+    /// use timely::dataflow::operators::{ToStream, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let start = Instant::now();
+    /// let data = timely::example(|scope| {
+    ///     (0..100).to_stream_paced(scope, 1000).capture()
+    /// });
+    ///
+    /// assert!(start.elapsed().as_millis() >= 90);
+    /// assert_eq!(data.extract(), vec![(0, (0..100).collect::<Vec<_>>())]);
+    /// ```
+    fn to_stream_paced<S: Scope<Timestamp=T>>(self, scope: &mut S, rate: usize) -> Stream<S, D>;
 }
 
 impl<T: Timestamp, I: IntoIterator+'static> ToStream<T, I::Item> for I where I::Item: Data {
@@ -59,6 +85,46 @@ impl<T: Timestamp, I: IntoIterator+'static> ToStream<T, I::Item> for I where I::
             }
         })
     }
+
+    fn to_stream_paced<S: Scope<Timestamp=T>>(self, scope: &mut S, rate: usize) -> Stream<S, I::Item> {
+
+        assert!(rate > 0, "to_stream_paced requires a positive rate");
+
+        source(scope, "ToStreamPaced", |capability, info| {
+
+            let activator = scope.activator_for(&info.address[..]);
+            let clock = scope.clock();
+
+            let mut iterator = self.into_iter().peekable();
+            let mut capability = Some(capability);
+            let mut start = None;
+            let mut sent = 0u128;
+
+            move |output| {
+
+                let start = *start.get_or_insert_with(|| clock.now());
+                if iterator.peek().is_some() {
+                    // Records permitted by the elapsed time, beyond those already sent.
+                    let allowed = clock.elapsed(start).as_nanos() * (rate as u128) / 1_000_000_000 + 1;
+                    let mut session = output.session(capability.as_ref().unwrap());
+                    while sent < allowed {
+                        match iterator.next() {
+                            Some(element) => session.give(element),
+                            None => break,
+                        }
+                        sent += 1;
+                    }
+                }
+                if iterator.peek().is_some() {
+                    let due = start + Duration::from_nanos((sent * 1_000_000_000 / (rate as u128)) as u64);
+                    activator.activate_after(due.saturating_duration_since(clock.now()));
+                }
+                else {
+                    capability = None;
+                }
+            }
+        })
+    }
 }
 
 /// Data and progress events of the native stream.
@@ -16,8 +16,17 @@ use crate::dataflow::channels::pushers::buffer::{Buffer as PushBuffer, Autoflush
 
 use crate::dataflow::operators::ActivateCapability;
 use crate::dataflow::operators::capability::mint as mint_capability;
+use crate::dataflow::operators::capability::tracking;
+use crate::dataflow::operators::windows::WindowTime;
 
-use crate::dataflow::{Stream, Scope};
+use crate::dataflow::{Stream, Scope, ScopeParent};
+
+/// The handle and capability of a new unordered input, and its stream.
+type UnorderedParts<G, D> = ((UnorderedHandle<<G as ScopeParent>::Timestamp, D>, ActivateCapability<<G as ScopeParent>::Timestamp>), Stream<G, D>);
+/// The handle of a new input with lateness, and its streams of on time and late records.
+type LatenessParts<G, D> = (LatenessInput<<G as ScopeParent>::Timestamp, D>, Stream<G, D>, Stream<G, D>);
+/// A session of an unordered input, which activates the input when dropped.
+type UnorderedSession<'b, T, D> = ActivateOnDrop<AutoflushSession<'b, T, D, PushCounter<T, D, Tee<T, D>>>>;
 
 /// Create a new `Stream` and `Handle` through which to supply input.
 pub trait UnorderedInput<G: Scope> {
@@ -74,17 +83,48 @@ pub trait UnorderedInput<G: Scope> {
     ///     assert_eq!(extract[i], (i, vec![i]));
     /// }
     /// ```
-    fn new_unordered_input<D:Data>(&mut self) -> ((UnorderedHandle<G::Timestamp, D>, ActivateCapability<G::Timestamp>), Stream<G, D>);
+    fn new_unordered_input<D:Data>(&mut self) -> UnorderedParts<G, D>;
+
+    /// Creates an input whose records carry their own event times, and whose watermark trails
+    /// the greatest event time observed by `lateness`, as described at [`BoundedLateness`].
+    ///
+    /// The method returns a handle and two streams. Each record given to the handle is produced
+    /// on the first stream at its event time, as determined by `event_time`, unless the time is
+    /// before the watermark; such late records are produced on the second stream at the
+    /// watermark instead. Both streams advance with the watermark, and complete once the handle
+    /// is dropped.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{UnorderedInput, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    ///
+    /// let (on_time, late) = timely::execute_directly(|worker| {
+    ///     let (mut input, on_time, late) = worker.dataflow(|scope| {
+    ///         let (input, on_time, late) = scope.new_input_with_lateness(2, |x: &u64| *x);
+    ///         (input, on_time.capture(), late.capture())
+    ///     });
+    ///     for time in vec![3, 1, 6, 4, 2, 7] {
+    ///         input.give(time);
+    ///     }
+    ///     (on_time, late)
+    /// });
+    ///
+    /// // Once 6 is observed the watermark is 4, and 2 arrives too late.
+    /// assert_eq!(on_time.extract(), vec![(1, vec![1]), (3, vec![3]), (4, vec![4]), (6, vec![6]), (7, vec![7])]);
+    /// assert_eq!(late.extract(), vec![(4, vec![2])]);
+    /// ```
+    fn new_input_with_lateness<D: Data, F: Fn(&D)->G::Timestamp+'static>(&mut self, lateness: G::Timestamp, event_time: F) -> LatenessParts<G, D>
+    where G::Timestamp: WindowTime;
 }
 
 
 impl<G: Scope> UnorderedInput<G> for G {
-    fn new_unordered_input<D:Data>(&mut self) -> ((UnorderedHandle<G::Timestamp, D>, ActivateCapability<G::Timestamp>), Stream<G, D>) {
+    fn new_unordered_input<D:Data>(&mut self) -> UnorderedParts<G, D> {
 
         let (output, registrar) = Tee::<G::Timestamp, D>::new();
         let internal = Rc::new(RefCell::new(ChangeBatch::new()));
         // let produced = Rc::new(RefCell::new(ChangeBatch::new()));
-        let cap = mint_capability(G::Timestamp::minimum(), internal.clone());
         let counter = PushCounter::new(output);
         let produced = counter.produced().clone();
         let peers = self.peers();
@@ -93,9 +133,13 @@ impl<G: Scope> UnorderedInput<G> for G {
         let mut address = self.addr();
         address.push(index);
 
+        let tracker = self.extensions().capabilities();
+        let tracked = tracker.as_ref().map(|tracker| tracker.register_output(&internal, &address[..], 0));
+        let cap = mint_capability(G::Timestamp::minimum(), internal.clone(), tracker.as_ref());
+
         let cap = ActivateCapability::new(cap, &address[..], self.activations().clone());
 
-        let helper = UnorderedHandle::new(counter);
+        let helper = UnorderedHandle::new(counter, self.config().batch_length());
 
         self.add_operator_with_index(Box::new(UnorderedOperator {
             name: "UnorderedInput".to_owned(),
@@ -104,10 +148,25 @@ impl<G: Scope> UnorderedInput<G> for G {
             internal,
             produced,
             peers,
+            _tracked: tracked,
         }), index);
 
         ((helper, cap), Stream::new(Source::new(index, 0), registrar, self.clone()))
     }
+
+    fn new_input_with_lateness<D: Data, F: Fn(&D)->G::Timestamp+'static>(&mut self, lateness: G::Timestamp, event_time: F) -> LatenessParts<G, D>
+    where G::Timestamp: WindowTime {
+        let ((on_time, on_time_cap), on_time_stream) = self.new_unordered_input();
+        let ((late, late_cap), late_stream) = self.new_unordered_input();
+        let input = LatenessInput {
+            policy: BoundedLateness::new(lateness),
+            event_time: Box::new(event_time),
+            on_time,
+            late,
+            capabilities: Some((on_time_cap, late_cap)),
+        };
+        (input, on_time_stream, late_stream)
+    }
 }
 
 struct UnorderedOperator<T:Timestamp> {
@@ -117,6 +176,8 @@ struct UnorderedOperator<T:Timestamp> {
     internal:   Rc<RefCell<ChangeBatch<T>>>,
     produced:   Rc<RefCell<ChangeBatch<T>>>,
     peers:     usize,
+    // The output's registration with the worker's capability tracker, if tracking.
+    _tracked:   Option<tracking::Output>,
 }
 
 impl<T:Timestamp> Schedule for UnorderedOperator<T> {
@@ -151,14 +212,246 @@ pub struct UnorderedHandle<T: Timestamp, D: Data> {
 }
 
 impl<T: Timestamp, D: Data> UnorderedHandle<T, D> {
-    fn new(pusher: PushCounter<T, D, Tee<T, D>>) -> UnorderedHandle<T, D> {
+    fn new(pusher: PushCounter<T, D, Tee<T, D>>, length: usize) -> UnorderedHandle<T, D> {
         UnorderedHandle {
-            buffer: PushBuffer::new(pusher),
+            buffer: PushBuffer::new(pusher).with_length(length),
         }
     }
 
     /// Allocates a new automatically flushing session based on the supplied capability.
-    pub fn session<'b>(&'b mut self, cap: ActivateCapability<T>) -> ActivateOnDrop<AutoflushSession<'b, T, D, PushCounter<T, D, Tee<T, D>>>> {
+    pub fn session<'b>(&'b mut self, cap: ActivateCapability<T>) -> UnorderedSession<'b, T, D> {
         ActivateOnDrop::new(self.buffer.autoflush_session(cap.capability.clone()), cap.address.clone(), cap.activations.clone())
     }
 }
+
+/// Capabilities of an unordered input, held separately for each of several logical sources.
+///
+/// Each source advances independently, by downgrading or closing its own capability, and the
+/// input may send data at the times of the combined frontier of the sources still open.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::{Inspect, Probe, UnorderedInput};
+/// use timely::dataflow::operators::unordered_input::UnorderedSources;
+///
+/// timely::execute(timely::Config::thread(), |worker| {
+///
+///     let ((mut input, cap), probe) = worker.dataflow::<u64,_,_>(|scope| {
+///         let (input, stream) = scope.new_unordered_input();
+///         (input, stream.inspect(|x: &u64| println!("seen: {:?}", x)).probe())
+///     });
+///
+///     // two sources, for example two partitions of an external log.
+///     let mut sources = UnorderedSources::new(cap, 2);
+///     input.session(sources.capability(0).unwrap().clone()).give(0);
+///     sources.downgrade(0, &5);
+///     sources.downgrade(1, &3);
+///     assert_eq!(sources.frontier().elements(), &[3]);
+///
+///     worker.step_while(|| probe.less_than(&3));
+///     assert!(!probe.less_than(&3));
+///
+///     // a new source, splitting off from the first.
+///     let third = sources.split(0);
+///     sources.close(0);
+///     sources.close(1);
+///     assert_eq!(sources.frontier().elements(), &[5]);
+///     sources.close(third);
+///     assert!(sources.is_done());
+///
+///     worker.step_while(|| !probe.done());
+/// }).unwrap();
+/// ```
+pub struct UnorderedSources<T: Timestamp> {
+    capabilities: Vec<Option<ActivateCapability<T>>>,
+}
+
+impl<T: Timestamp> UnorderedSources<T> {
+    /// Splits `capability` among `sources` logical sources, numbered from zero.
+    pub fn new(capability: ActivateCapability<T>, sources: usize) -> Self {
+        UnorderedSources { capabilities: (0 .. sources).map(|_| Some(capability.clone())).collect() }
+    }
+
+    /// The capability held for `source`, unless it has closed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` does not exist.
+    pub fn capability(&self, source: usize) -> Option<&ActivateCapability<T>> {
+        self.capabilities[source].as_ref()
+    }
+
+    /// Adds a source starting with a copy of the capability held for `source`, and returns its number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` has closed.
+    pub fn split(&mut self, source: usize) -> usize {
+        let capability = self.capabilities[source].clone().expect("split of closed source");
+        self.capabilities.push(Some(capability));
+        self.capabilities.len() - 1
+    }
+
+    /// Downgrades the capability held for `source` to `time`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `source` has closed, or if `time` is not greater or equal to its capability's time.
+    pub fn downgrade(&mut self, source: usize, time: &T) {
+        self.capabilities[source].as_mut().expect("downgrade of closed source").downgrade(time);
+    }
+
+    /// Drops the capability held for `source`, which may send no further data.
+    pub fn close(&mut self, source: usize) {
+        self.capabilities[source] = None;
+    }
+
+    /// The number of sources, closed or not.
+    pub fn sources(&self) -> usize {
+        self.capabilities.len()
+    }
+
+    /// Reports whether every source has closed.
+    pub fn is_done(&self) -> bool {
+        self.capabilities.iter().all(|c| c.is_none())
+    }
+
+    /// The least times of the capabilities held by the open sources.
+    pub fn frontier(&self) -> Antichain<T> {
+        let mut frontier = Antichain::new();
+        for capability in self.capabilities.iter().flatten() {
+            frontier.insert(capability.time().clone());
+        }
+        frontier
+    }
+}
+
+/// A watermark policy for sources whose records arrive out of order by event time.
+///
+/// The policy tracks the greatest event time observed, and holds a watermark that trails it by a
+/// fixed allowed lateness: the watermark is the greatest event time less `lateness`, or the least
+/// time if that would be negative. A source may downgrade its capability to the watermark, and
+/// records with event times before the watermark are late, as they can no longer be produced at
+/// their event times.
+///
+/// # Examples
+/// ```
+/// use timely::dataflow::operators::unordered_input::BoundedLateness;
+///
+/// let mut policy = BoundedLateness::new(5u64);
+/// assert!(policy.observe(12));
+/// assert_eq!(policy.watermark(), 7);
+/// assert!(policy.observe(8));
+/// assert!(!policy.observe(6));
+/// assert_eq!(policy.max_event_time(), Some(12));
+/// ```
+#[derive(Debug, Clone)]
+pub struct BoundedLateness<T: WindowTime> {
+    lateness: T,
+    max_event_time: Option<T>,
+    watermark: T,
+}
+
+impl<T: WindowTime> BoundedLateness<T> {
+    /// A policy allowing records to arrive up to `lateness` after the greatest event time observed.
+    pub fn new(lateness: T) -> Self {
+        BoundedLateness { lateness, max_event_time: None, watermark: T::minimum() }
+    }
+
+    /// Observes a record with event time `time`, and reports whether it is on time.
+    ///
+    /// A late record does not advance the greatest event time observed.
+    pub fn observe(&mut self, time: T) -> bool {
+        if time < self.watermark {
+            return false;
+        }
+        if self.max_event_time.map(|max| max < time).unwrap_or(true) {
+            self.max_event_time = Some(time);
+            let watermark = time.checked_sub(self.lateness).unwrap_or_else(T::minimum);
+            if self.watermark < watermark {
+                self.watermark = watermark;
+            }
+        }
+        true
+    }
+
+    /// Advances the watermark to `time`, if it is later, for example once a source is known to be idle.
+    pub fn advance_to(&mut self, time: T) {
+        if self.watermark < time {
+            self.watermark = time;
+        }
+    }
+
+    /// The time before which records are late.
+    pub fn watermark(&self) -> T {
+        self.watermark
+    }
+
+    /// The greatest event time observed among records on time, if any.
+    pub fn max_event_time(&self) -> Option<T> {
+        self.max_event_time
+    }
+}
+
+/// A handle to an input whose capabilities follow a [`BoundedLateness`] watermark.
+///
+/// Created by [`UnorderedInput::new_input_with_lateness`].
+pub struct LatenessInput<T: WindowTime, D: Data> {
+    policy: BoundedLateness<T>,
+    event_time: Box<dyn Fn(&D)->T>,
+    on_time: UnorderedHandle<T, D>,
+    late: UnorderedHandle<T, D>,
+    // Capabilities of the on-time and late streams, at the watermark, until the input closes.
+    capabilities: Option<(ActivateCapability<T>, ActivateCapability<T>)>,
+}
+
+impl<T: WindowTime, D: Data> LatenessInput<T, D> {
+    /// Introduces `record`, at its event time if it is on time and on the late stream otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input has closed.
+    pub fn give(&mut self, record: D) {
+        let time = (self.event_time)(&record);
+        let (on_time, late) = self.capabilities.as_ref().expect("record given to closed input");
+        if self.policy.observe(time) {
+            self.on_time.session(on_time.delayed(&time)).give(record);
+        }
+        else {
+            self.late.session(late.clone()).give(record);
+        }
+        self.downgrade();
+    }
+
+    /// Advances the watermark to `time`, if it is later, without a record.
+    pub fn advance_to(&mut self, time: T) {
+        self.policy.advance_to(time);
+        self.downgrade();
+    }
+
+    /// The time before which records are late, and to which the streams have advanced.
+    pub fn watermark(&self) -> T {
+        self.policy.watermark()
+    }
+
+    /// The policy tracking the watermark.
+    pub fn policy(&self) -> &BoundedLateness<T> {
+        &self.policy
+    }
+
+    /// Closes the input, after which both streams complete.
+    pub fn close(&mut self) {
+        self.capabilities = None;
+    }
+
+    // Downgrades both capabilities to the watermark.
+    fn downgrade(&mut self) {
+        let watermark = self.policy.watermark();
+        if let Some((on_time, late)) = self.capabilities.as_mut() {
+            if *on_time.time() < watermark {
+                on_time.downgrade(&watermark);
+                late.downgrade(&watermark);
+            }
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Observing the records and frontier of a stream from outside the dataflow.
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::operators::capture::{Capture, Event, EventPusher};
+use crate::progress::{Antichain, Timestamp};
+use crate::progress::frontier::MutableAntichain;
+
+/// An observation of a watched stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Update<T, D> {
+    /// Records the stream carried at a time.
+    Data(T, Vec<D>),
+    /// The frontier of the stream advanced to the antichain.
+    Frontier(Antichain<T>),
+}
+
+/// Extension trait for watching a stream.
+pub trait Watch<T: Timestamp, D: Data> {
+    /// Returns a `Watcher` of the records and frontier of this worker's part of the stream.
+    ///
+    /// Each worker watches the records it receives, and the frontier it observes. The watcher may
+    /// be used by the worker between steps, or sent to another thread.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Map};
+    /// use timely::dataflow::operators::watch::{Update, Watch};
+    /// use timely::progress::Antichain;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let mut input = InputHandle::new();
+    ///     let mut watcher = worker.dataflow::<u64,_,_>(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .map(|x: u64| x * 10)
+    ///              .watch()
+    ///     });
+    ///
+    ///     input.send(1);
+    ///     input.advance_to(1);
+    ///     worker.step_while(|| watcher.frontier().less_than(&1));
+    ///
+    ///     let updates = std::iter::from_fn(|| watcher.try_next()).collect::<Vec<_>>();
+    ///     assert_eq!(updates, vec![Update::Data(0, vec![10]), Update::Frontier(Antichain::from_elem(1))]);
+    /// }).unwrap();
+    /// ```
+    fn watch(&self) -> Watcher<T, D>;
+}
+
+impl<G: Scope, D: Data> Watch<G::Timestamp, D> for Stream<G, D> {
+    fn watch(&self) -> Watcher<G::Timestamp, D> {
+        let (send, recv) = crossbeam_channel::unbounded();
+        self.capture_into(WatchPusher { send });
+        Watcher {
+            recv,
+            frontier: MutableAntichain::new_bottom(G::Timestamp::minimum()),
+            updates: VecDeque::new(),
+        }
+    }
+}
+
+/// Sends captured events to a watcher.
+struct WatchPusher<T, D> {
+    send: Sender<Event<T, D>>,
+}
+
+impl<T, D> EventPusher<T, D> for WatchPusher<T, D> {
+    fn push(&mut self, event: Event<T, D>) {
+        // A dropped watcher no longer wants events.
+        let _ = self.send.send(event);
+    }
+}
+
+/// Receives the records and frontier changes of a watched stream.
+///
+/// As an iterator, the watcher blocks until the next update is available, and ends once the
+/// stream completes. The `try_next` method does not block, and suits the worker that drives the
+/// dataflow, which must step it to produce updates.
+pub struct Watcher<T: Timestamp, D> {
+    recv: Receiver<Event<T, D>>,
+    frontier: MutableAntichain<T>,
+    // Updates received but not yet returned.
+    updates: VecDeque<Update<T, D>>,
+}
+
+impl<T: Timestamp, D> Watcher<T, D> {
+    /// The frontier of the stream, as of the updates received so far.
+    ///
+    /// This reflects updates the watcher has received, which may not yet have been returned.
+    pub fn frontier(&mut self) -> &MutableAntichain<T> {
+        self.receive();
+        &self.frontier
+    }
+
+    /// The next update, if one is available.
+    pub fn try_next(&mut self) -> Option<Update<T, D>> {
+        self.receive();
+        self.updates.pop_front()
+    }
+
+    /// The next update, waiting at most `timeout` for one to become available.
+    ///
+    /// Returns `None` if no update arrives in time, or if the stream has completed.
+    pub fn next_timeout(&mut self, timeout: Duration) -> Option<Update<T, D>> {
+        if self.updates.is_empty() {
+            match self.recv.recv_timeout(timeout) {
+                Ok(event) => self.absorb(event),
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => { },
+            }
+        }
+        self.try_next()
+    }
+
+    /// Absorbs the events available without blocking.
+    fn receive(&mut self) {
+        while let Ok(event) = self.recv.try_recv() {
+            self.absorb(event);
+        }
+    }
+
+    /// Converts an event to an update, if it changes anything.
+    fn absorb(&mut self, event: Event<T, D>) {
+        let update = match event {
+            Event::Messages(time, data) => Update::Data(time, data),
+            Event::Progress(changes) => {
+                if self.frontier.update_iter(changes).next().is_none() { return; }
+                Update::Frontier(self.frontier.frontier().to_owned())
+            }
+        };
+        self.updates.push_back(update);
+    }
+}
+
+impl<T: Timestamp, D> Iterator for Watcher<T, D> {
+    type Item = Update<T, D>;
+    fn next(&mut self) -> Option<Update<T, D>> {
+        while self.updates.is_empty() {
+            match self.recv.recv() {
+                Ok(event) => self.absorb(event),
+                Err(_) => return None,
+            }
+        }
+        self.try_next()
+    }
+}
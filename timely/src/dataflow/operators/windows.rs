@@ -0,0 +1,370 @@
+//! Aggregation of records into windows of timestamps.
+//!
+//! Windows are ranges of timestamps of a fixed size. A record is folded into the state of each
+//! window containing its timestamp, and each window's state is produced once the input frontier
+//! has passed all of its times, at the last time of the window. Windows that receive no records
+//! produce nothing.
+//!
+//! Windows require numeric timestamps, described by the `WindowTime` trait. Each worker
+//! aggregates the records it receives; exchange the stream first to aggregate by key.
+//!
+//! Session windows are instead formed for each key from the times of its records, and close
+//! once a key has no records for some gap. Their records are exchanged by key.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::{Data, ExchangeData};
+use crate::order::TotalOrder;
+use crate::progress::Timestamp;
+use crate::dataflow::{Stream, Scope, ScopeParent};
+use crate::dataflow::channels::pact::{Exchange, Pipeline};
+use crate::dataflow::operators::{Capability, Delay};
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Timestamps that can be divided into windows.
+pub trait WindowTime: Timestamp+TotalOrder+Copy {
+    /// The unit timestamp difference.
+    fn one() -> Self;
+    /// The greatest multiple of `step` less than or equal to `self`.
+    fn round_down(self, step: Self) -> Self;
+    /// Subtracts `other`, returning `None` if the result would be negative.
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    /// Adds `other`, saturating at the greatest timestamp.
+    fn saturating_add(self, other: Self) -> Self;
+}
+
+macro_rules! implement_window_time {
+    ($($index_type:ty,)*) => (
+        $(
+            impl WindowTime for $index_type {
+                #[inline] fn one() -> Self { 1 }
+                #[inline] fn round_down(self, step: Self) -> Self { self - self % step }
+                #[inline] fn checked_sub(self, other: Self) -> Option<Self> { <$index_type>::checked_sub(self, other) }
+                #[inline] fn saturating_add(self, other: Self) -> Self { <$index_type>::saturating_add(self, other) }
+            }
+        )*
+    )
+}
+
+implement_window_time!(usize, u128, u64, u32, u16, u8,);
+
+// Each window's start and state, and the late records.
+type WindowsWithLate<G, A, D> = (Stream<G, (<G as ScopeParent>::Timestamp, A)>, Stream<G, D>);
+
+/// Extension trait for aggregating a stream into windows.
+pub trait Windows<G: Scope, D: Data> where G::Timestamp: WindowTime {
+    /// Folds records into consecutive, non-overlapping windows of `size` timestamps.
+    ///
+    /// The window containing time `t` starts at the greatest multiple of `size` not greater than
+    /// `t`. Each window produces its start and state, at the window's last time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::windows::Windows;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 10u64).to_stream(scope)
+    ///                 .delay(|x, _| *x)
+    ///                 .tumbling_window(5, |sum: &mut u64, x| *sum += x)
+    ///                 .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![(4, vec![(0, 10)]), (9, vec![(5, 35)])]);
+    /// ```
+    fn tumbling_window<A, F>(&self, size: G::Timestamp, fold: F) -> Stream<G, (G::Timestamp, A)>
+    where
+        A: Data+Default,
+        F: FnMut(&mut A, D)+'static;
+
+    /// Folds records into windows of `size` timestamps, starting every `slide` timestamps.
+    ///
+    /// Windows start at multiples of `slide`, and each record is folded into every window that
+    /// contains its time. Each window produces its start and state, at the window's last time.
+    /// If `slide` exceeds `size` the windows leave gaps between them, and records at times in no
+    /// window are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` or `slide` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::windows::Windows;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     (0 .. 6u64).to_stream(scope)
+    ///                .delay(|x, _| *x)
+    ///                .sliding_window(4, 2, |seen: &mut Vec<u64>, x| seen.push(x))
+    ///                .capture()
+    /// });
+    ///
+    /// let mut windows = captured.extract().into_iter().flat_map(|(_, w)| w).collect::<Vec<_>>();
+    /// for (_, seen) in windows.iter_mut() { seen.sort(); }
+    /// assert_eq!(windows, vec![(0, vec![0, 1, 2, 3]), (2, vec![2, 3, 4, 5]), (4, vec![4, 5])]);
+    /// ```
+    fn sliding_window<A, F>(&self, size: G::Timestamp, slide: G::Timestamp, fold: F) -> Stream<G, (G::Timestamp, A)>
+    where
+        A: Data+Default,
+        D: Clone,
+        F: FnMut(&mut A, D)+'static;
+
+    /// Folds records into consecutive, non-overlapping windows of `size` event times, and
+    /// separates records that arrive more than `allowance` after their event times.
+    ///
+    /// Each record is folded into the window containing its event time, as determined by
+    /// `event_time`, if its time is at most `allowance` after its event time. Windows are
+    /// produced as by `tumbling_window`, once the input frontier has passed their last time by
+    /// `allowance`. The second stream holds the late records, at the times they arrived, as
+    /// described by [`Delay::delay_with_late`](crate::dataflow::operators::Delay::delay_with_late).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::windows::Windows;
+    ///
+    /// let (windows, late) = timely::example(|scope| {
+    ///     // Records at time 6 carry event times 1, 4, and 5.
+    ///     let (windows, late) = vec![1u64, 4, 5].to_stream(scope)
+    ///                                             .delay(|_x, _| 6)
+    ///                                             .tumbling_window_with_late(5, 2, |x| *x, |sum: &mut u64, x| *sum += x);
+    ///     (windows.capture(), late.capture())
+    /// });
+    ///
+    /// assert_eq!(windows.extract(), vec![(4, vec![(0, 4)]), (9, vec![(5, 5)])]);
+    /// assert_eq!(late.extract(), vec![(6, vec![1])]);
+    /// ```
+    fn tumbling_window_with_late<A, E, F>(&self, size: G::Timestamp, allowance: G::Timestamp, event_time: E, fold: F) -> WindowsWithLate<G, A, D>
+    where
+        A: Data+Default,
+        E: Fn(&D)->G::Timestamp+'static,
+        F: FnMut(&mut A, D)+'static;
+
+    /// Folds records into windows of `size` event times, starting every `slide` event times, and
+    /// separates records that arrive more than `allowance` after their event times.
+    ///
+    /// Records are assigned to windows as by `sliding_window`, and otherwise as described at
+    /// `tumbling_window_with_late`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` or `slide` is zero.
+    fn sliding_window_with_late<A, E, F>(&self, size: G::Timestamp, slide: G::Timestamp, allowance: G::Timestamp, event_time: E, fold: F) -> WindowsWithLate<G, A, D>
+    where
+        A: Data+Default,
+        D: Clone,
+        E: Fn(&D)->G::Timestamp+'static,
+        F: FnMut(&mut A, D)+'static;
+}
+
+impl<G: Scope, D: Data> Windows<G, D> for Stream<G, D> where G::Timestamp: WindowTime {
+    fn tumbling_window<A, F>(&self, size: G::Timestamp, fold: F) -> Stream<G, (G::Timestamp, A)>
+    where
+        A: Data+Default,
+        F: FnMut(&mut A, D)+'static
+    {
+        self.sliding_window(size, size, fold)
+    }
+
+    fn sliding_window<A, F>(&self, size: G::Timestamp, slide: G::Timestamp, mut fold: F) -> Stream<G, (G::Timestamp, A)>
+    where
+        A: Data+Default,
+        D: Clone,
+        F: FnMut(&mut A, D)+'static
+    {
+        let zero = G::Timestamp::minimum();
+        assert!(size != zero && slide != zero, "windows must have a positive size and slide");
+        // The offset of a window's last time from its start.
+        let span = size.checked_sub(G::Timestamp::one()).unwrap();
+
+        let name = if size == slide { "TumblingWindow" } else { "SlidingWindow" };
+        self.unary_frontier(Pipeline, name, move |_, _| {
+            // Each open window's capability for its last time, and its state.
+            let mut windows = HashMap::new();
+            let mut vector = Vec::new();
+            let mut starts = Vec::new();
+            move |input, output| {
+                input.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    // The windows containing `time`, latest first.
+                    let mut start = Some(time.time().round_down(slide));
+                    while let Some(s) = start.filter(|s| time.time() <= &s.saturating_add(span)) {
+                        starts.push(s);
+                        start = s.checked_sub(slide);
+                    }
+                    // Records in the gaps between windows are dropped.
+                    if starts.is_empty() {
+                        vector.clear();
+                    }
+                    for datum in vector.drain(..) {
+                        let (last, rest) = starts.split_last().unwrap();
+                        for start in rest.iter() {
+                            let state = windows.entry(*start).or_insert_with(|| (time.delayed(&start.saturating_add(span)), A::default()));
+                            fold(&mut state.1, datum.clone());
+                        }
+                        let state = windows.entry(*last).or_insert_with(|| (time.delayed(&last.saturating_add(span)), A::default()));
+                        fold(&mut state.1, datum);
+                    }
+                    starts.clear();
+                });
+
+                let frontier = input.frontier();
+                let mut complete = windows.keys().filter(|start| !frontier.less_equal(&start.saturating_add(span))).cloned().collect::<Vec<_>>();
+                complete.sort();
+                for start in complete {
+                    let (cap, state) = windows.remove(&start).unwrap();
+                    output.session(&cap).give((start, state));
+                }
+            }
+        })
+    }
+
+    fn tumbling_window_with_late<A, E, F>(&self, size: G::Timestamp, allowance: G::Timestamp, event_time: E, fold: F) -> WindowsWithLate<G, A, D>
+    where
+        A: Data+Default,
+        E: Fn(&D)->G::Timestamp+'static,
+        F: FnMut(&mut A, D)+'static
+    {
+        let (on_time, late) = self.delay_with_late(allowance, move |x, _time| event_time(x));
+        (on_time.tumbling_window(size, fold), late)
+    }
+
+    fn sliding_window_with_late<A, E, F>(&self, size: G::Timestamp, slide: G::Timestamp, allowance: G::Timestamp, event_time: E, fold: F) -> WindowsWithLate<G, A, D>
+    where
+        A: Data+Default,
+        D: Clone,
+        E: Fn(&D)->G::Timestamp+'static,
+        F: FnMut(&mut A, D)+'static
+    {
+        let (on_time, late) = self.delay_with_late(allowance, move |x, _time| event_time(x));
+        (on_time.sliding_window(size, slide, fold), late)
+    }
+}
+
+// Each session's key, the times of its first and latest records, and its state.
+type Sessions<G, K, A> = Stream<G, (K, (<G as ScopeParent>::Timestamp, <G as ScopeParent>::Timestamp), A)>;
+
+/// Extension trait for aggregating a keyed stream into session windows.
+pub trait SessionWindows<G: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> where G::Timestamp: WindowTime {
+    /// Folds the records of each key into sessions, which end once `gap` timestamps pass without
+    /// a record for the key.
+    ///
+    /// A record at time `t` joins its key's open session if the session's latest record is at
+    /// time `t - gap` or later, and otherwise starts a new session. Each session produces its key,
+    /// the times of its first and latest records, and its state, at the time `gap` after its
+    /// latest record, once the input frontier has passed that time.
+    ///
+    /// Records are exchanged by the worker's exchange hasher, so that each key's sessions are
+    /// formed by one worker.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::windows::SessionWindows;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     vec![('a', 0u64), ('a', 2), ('b', 3), ('a', 9), ('a', 10)]
+    ///         .to_stream(scope)
+    ///         .delay(|x, _| x.1)
+    ///         .session_windows(3, |_key, count: &mut usize, _val| *count += 1)
+    ///         .capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![
+    ///     (5, vec![('a', (0, 2), 2)]),
+    ///     (6, vec![('b', (3, 3), 1)]),
+    ///     (13, vec![('a', (9, 10), 2)]),
+    /// ]);
+    /// ```
+    fn session_windows<A, F>(&self, gap: G::Timestamp, fold: F) -> Sessions<G, K, A>
+    where
+        A: Data+Default,
+        F: FnMut(&K, &mut A, V)+'static;
+}
+
+/// An open session of a key.
+struct Session<T: Timestamp, A> {
+    first: T,
+    /// A capability for the time of the session's latest record.
+    latest: Capability<T>,
+    state: A,
+}
+
+impl<T: WindowTime, A> Session<T, A> {
+    /// The time at which the session closes, absent further records.
+    fn end(&self, gap: T) -> T {
+        self.latest.time().saturating_add(gap)
+    }
+}
+
+impl<G: Scope, K: ExchangeData+Hash+Eq, V: ExchangeData> SessionWindows<G, K, V> for Stream<G, (K, V)> where G::Timestamp: WindowTime {
+    fn session_windows<A, F>(&self, gap: G::Timestamp, mut fold: F) -> Sessions<G, K, A>
+    where
+        A: Data+Default,
+        F: FnMut(&K, &mut A, V)+'static
+    {
+        let hasher = self.scope().config().hasher().clone();
+        let exchange = Exchange::new(move |x: &(K, V)| hasher.hash(&x.0));
+
+        self.unary_frontier(exchange, "SessionWindows", move |_, _| {
+            // Records by time, with a capability for the time, until the time is complete.
+            let mut stash = HashMap::new();
+            let mut sessions: HashMap<K, Session<G::Timestamp, A>> = HashMap::new();
+            let mut vector = Vec::new();
+            move |input, output| {
+                input.for_each(|time, data| {
+                    data.swap(&mut vector);
+                    stash.entry(*time.time())
+                         .or_insert_with(|| (time.retain(), Vec::new()))
+                         .1
+                         .append(&mut vector);
+                });
+
+                // Apply complete times in order, as each may extend or close sessions.
+                let frontier = input.frontier();
+                let mut complete = stash.keys().filter(|time| !frontier.less_equal(time)).cloned().collect::<Vec<_>>();
+                complete.sort();
+                for time in complete {
+                    let (cap, records) = stash.remove(&time).unwrap();
+                    for (key, val) in records {
+                        if let Some(session) = sessions.get_mut(&key) {
+                            if time <= session.end(gap) {
+                                session.latest = cap.clone();
+                                fold(&key, &mut session.state, val);
+                                continue;
+                            }
+                            let session = sessions.remove(&key).unwrap();
+                            let end = session.end(gap);
+                            output.session(&session.latest.delayed(&end)).give((key.clone(), (session.first, *session.latest.time()), session.state));
+                        }
+                        let mut state = A::default();
+                        fold(&key, &mut state, val);
+                        sessions.insert(key, Session { first: time, latest: cap.clone(), state });
+                    }
+                }
+
+                let mut closed = sessions.iter().filter(|(_, session)| !frontier.less_equal(&session.end(gap))).map(|(key, _)| key.clone()).collect::<Vec<_>>();
+                closed.sort_by_key(|key| *sessions[key].latest.time());
+                for key in closed {
+                    let session = sessions.remove(&key).unwrap();
+                    let end = session.end(gap);
+                    output.session(&session.latest.delayed(&end)).give((key, (session.first, *session.latest.time()), session.state));
+                }
+            }
+        })
+    }
+}
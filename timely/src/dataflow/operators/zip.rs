@@ -0,0 +1,63 @@
+//! Alignment of two streams by timestamp.
+use std::collections::HashMap;
+
+use crate::Data;
+use crate::dataflow::{Stream, Scope};
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::generic::operator::Operator;
+
+/// Extension trait for aligning two streams by timestamp.
+pub trait ZipByTime<G: Scope, D1: Data> {
+    /// Produces, for each time at which either stream has records, the records of both streams
+    /// at that time.
+    ///
+    /// Records are buffered until both input frontiers have passed their time, and each time's
+    /// pair of record sets is then produced as one record at that time.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Capture};
+    /// use timely::dataflow::operators::capture::Extract;
+    /// use timely::dataflow::operators::zip::ZipByTime;
+    ///
+    /// let captured = timely::example(|scope| {
+    ///     let data = (0 .. 6u64).to_stream(scope).delay(|x, _| x / 2);
+    ///     let models = vec!["a", "b"].to_stream(scope).delay(|x, _| if *x == "a" { 0 } else { 2 });
+    ///     data.zip_by_time(&models).capture()
+    /// });
+    ///
+    /// assert_eq!(captured.extract(), vec![
+    ///     (0, vec![(vec![0, 1], vec!["a"])]),
+    ///     (1, vec![(vec![2, 3], vec![])]),
+    ///     (2, vec![(vec![4, 5], vec!["b"])]),
+    /// ]);
+    /// ```
+    fn zip_by_time<D2: Data>(&self, other: &Stream<G, D2>) -> Stream<G, (Vec<D1>, Vec<D2>)>;
+}
+
+impl<G: Scope, D1: Data> ZipByTime<G, D1> for Stream<G, D1> {
+    fn zip_by_time<D2: Data>(&self, other: &Stream<G, D2>) -> Stream<G, (Vec<D1>, Vec<D2>)> {
+        let mut stash = HashMap::new();
+        self.binary_notify(other, Pipeline, Pipeline, "ZipByTime", vec![], move |input1, input2, output, notificator| {
+            input1.for_each(|time, data| {
+                stash.entry(time.time().clone())
+                     .or_insert_with(|| (Vec::new(), Vec::new()))
+                     .0
+                     .append(&mut data.replace(Vec::new()));
+                notificator.notify_at(time.retain());
+            });
+            input2.for_each(|time, data| {
+                stash.entry(time.time().clone())
+                     .or_insert_with(|| (Vec::new(), Vec::new()))
+                     .1
+                     .append(&mut data.replace(Vec::new()));
+                notificator.notify_at(time.retain());
+            });
+            notificator.for_each(|time, _, _| {
+                if let Some(pair) = stash.remove(time.time()) {
+                    output.session(&time).give(pair);
+                }
+            });
+        })
+    }
+}
@@ -3,8 +3,11 @@
 use std::rc::Rc;
 use std::cell::RefCell;
 
-use crate::communication::{Data, Push, Pull};
+use std::any::Any;
+
+use crate::communication::codec::Codec;
 use crate::communication::allocator::thread::{ThreadPusher, ThreadPuller};
+use crate::communication::allocator::{BoxedPush, BoxedPull};
 use crate::scheduling::Scheduler;
 use crate::scheduling::activate::Activations;
 use crate::progress::{Timestamp, Operate, SubgraphBuilder};
@@ -58,8 +61,8 @@ where
     fn config(&self) -> &Config { self.parent.config() }
     fn index(&self) -> usize { self.parent.index() }
     fn peers(&self) -> usize { self.parent.peers() }
-    fn allocate<D: Data>(&mut self, identifier: usize, address: &[usize]) -> (Vec<Box<dyn Push<Message<D>>>>, Box<dyn Pull<Message<D>>>) {
-        self.parent.allocate(identifier, address)
+    fn allocate_with<D: Any+Send+Sync, C: Codec<D>>(&mut self, identifier: usize, address: &[usize]) -> (Vec<BoxedPush<D>>, BoxedPull<D>) {
+        self.parent.allocate_with::<D, C>(identifier, address)
     }
     fn pipeline<D: 'static>(&mut self, identifier: usize, address: &[usize]) -> (ThreadPusher<Message<D>>, ThreadPuller<Message<D>>) {
         self.parent.pipeline(identifier, address)
@@ -70,6 +73,9 @@ where
     fn log_register(&self) -> ::std::cell::RefMut<crate::logging_core::Registry<crate::logging::WorkerIdentifier>> {
         self.parent.log_register()
     }
+    fn extensions(&self) -> &crate::worker::Extensions {
+        self.parent.extensions()
+    }
 }
 
 impl<'a, G, T> Scheduler for Child<'a, G, T>
@@ -128,7 +134,12 @@ where
             };
             func(&mut builder)
         };
-        let subscope = subscope.into_inner().build(self);
+        let subscope = subscope.into_inner();
+        // mistakes are reported when the dataflow is built, which then discards it.
+        if let Err(error) = subscope.validate() {
+            self.subgraph.borrow_mut().record_error(error);
+        }
+        let subscope = subscope.build(self);
 
         self.add_operator_with_index(Box::new(subscope), index);
 
@@ -1,7 +1,7 @@
 //! Hierarchical organization of timely dataflow graphs.
 
 use crate::progress::{Timestamp, Operate, Source, Target};
-use crate::order::Product;
+use crate::order::{Bounded, Product};
 use crate::progress::timestamp::Refines;
 use crate::communication::Allocate;
 use crate::worker::AsWorker;
@@ -126,6 +126,39 @@ pub trait Scope: ScopeParent {
         self.scoped::<Product<<Self as ScopeParent>::Timestamp, T>,R,F>("Iterative", func)
     }
 
+    /// Creates an iterative dataflow subgraph, whose iterations may not exceed `MAX`.
+    ///
+    /// This method is a specialization of `scoped` which uses the `Product` timestamp combinator
+    /// with a [`Bounded`](crate::order::Bounded) iteration counter. Feedback past iteration `MAX`
+    /// is discarded, and the progress tracker knows as much, so that the loop terminates and its
+    /// frontiers advance once iteration `MAX` completes, even if records would circulate further.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::Scope;
+    /// use timely::dataflow::operators::{ToStream, Concat, Enter, Leave, Inspect, Map};
+    /// use timely::dataflow::operators::feedback::{LoopVariable, ConnectLoop};
+    /// use timely::order::Bounded;
+    ///
+    /// timely::example(|scope| {
+    ///     let stream = (0 .. 5u64).to_stream(scope);
+    ///     scope.bounded_iterative::<10,_,_>(|inner| {
+    ///         // Records double in each iteration, without end but for the bound.
+    ///         let (handle, cycle) = inner.loop_variable(Bounded::new(1));
+    ///         let doubled = stream.enter(inner).concat(&cycle).map(|x| x * 2);
+    ///         doubled.connect_loop(handle);
+    ///         doubled.leave()
+    ///     })
+    ///     .inspect(|x| assert_eq!(x % 2, 0));
+    /// });
+    /// ```
+    fn bounded_iterative<const MAX: u32, R, F>(&mut self, func: F) -> R
+    where
+        F: FnOnce(&mut Child<Self, Product<<Self as ScopeParent>::Timestamp, Bounded<MAX>>>) -> R,
+    {
+        self.scoped::<Product<<Self as ScopeParent>::Timestamp, Bounded<MAX>>,R,F>("BoundedIterative", func)
+    }
+
     /// Creates a dataflow region with the same timestamp.
     ///
     /// This method is a specialization of `scoped` which uses the same timestamp as the
@@ -43,6 +43,7 @@ impl<S: Scope, D> Stream<S, D> {
             target: (target.node, target.port),
         }));
 
+        self.scope.topology().add_channel(identifier, self.scope.addr(), (self.name.node, self.name.port), (target.node, target.port));
         self.scope.add_edge(self.name, target);
         self.ports.add_pusher(pusher);
     }
@@ -0,0 +1,101 @@
+//! Dataflows described once and instantiated many times.
+//!
+//! A [`Template`] holds a function that builds a dataflow from a parameter bundle, and
+//! [`Worker::instantiate`](crate::worker::Worker::instantiate) installs an instance of it for
+//! each bundle, with the template's name. Instances are independent dataflows, each with its own
+//! operators, inputs, and probes, and may be dropped individually; the template's function runs
+//! once for each instance, and operator construction is not shared. Instances of one topology
+//! share the compilation of their progress tracking: the path summaries between the locations of
+//! the first instance's graph are reused by later instances whose graphs are identical, rather
+//! than compiled again for each. Parameters that change the topology are allowed, at the cost of
+//! compiling the summaries of each new graph.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Filter, Probe};
+//! use timely::dataflow::template::Template;
+//!
+//! timely::execute_directly(|worker| {
+//!     // Each instance keeps the records equal to its key modulo ten.
+//!     let template = Template::new("KeyFilter", |key: u64, scope| {
+//!         let mut input = InputHandle::<u64, u64>::new();
+//!         let probe = scope.input_from(&mut input)
+//!                          .filter(move |x| x % 10 == key)
+//!                          .probe();
+//!         (input, probe)
+//!     });
+//!
+//!     let mut instances = (0 .. 10).map(|key| worker.instantiate(&template, key)).collect::<Vec<_>>();
+//!     assert_eq!(template.reused(), 9);
+//!
+//!     for (input, _probe) in instances.iter_mut() {
+//!         input.send(7);
+//!         input.advance_to(1);
+//!     }
+//!     worker.step_while(|| instances.iter().any(|(_input, probe)| probe.less_than(&1)));
+//! });
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::communication::Allocate;
+use crate::dataflow::scopes::Child;
+use crate::progress::timestamp::Refines;
+use crate::progress::reachability::SummaryCache;
+use crate::worker::Worker;
+
+// A function building an instance in a dataflow scope.
+type Build<A, T, P, R> = Rc<dyn for<'a> Fn(P, &mut Child<'a, Worker<A>, T>) -> R>;
+
+/// A function building a dataflow from parameters, from which instances may be installed.
+pub struct Template<A: Allocate, T: Refines<()>, P, R> {
+    name: String,
+    build: Build<A, T, P, R>,
+    summaries: Rc<RefCell<SummaryCache<T>>>,
+}
+
+impl<A: Allocate, T: Refines<()>, P, R> Template<A, T, P, R> {
+    /// Describes a dataflow, named `name`, that `build` constructs for each parameter bundle.
+    pub fn new<F>(name: &str, build: F) -> Self
+    where
+        F: for<'a> Fn(P, &mut Child<'a, Worker<A>, T>) -> R + 'static,
+    {
+        Template {
+            name: name.to_owned(),
+            build: Rc::new(build),
+            summaries: Rc::new(RefCell::new(SummaryCache::new())),
+        }
+    }
+
+    /// The name of the template's instances.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The number of instances that reused the path summaries of an earlier instance.
+    pub fn reused(&self) -> usize {
+        self.summaries.borrow().hits()
+    }
+
+    /// Builds an instance for `params` in `scope`.
+    pub(crate) fn build(&self, params: P, scope: &mut Child<Worker<A>, T>) -> R {
+        (self.build)(params, scope)
+    }
+
+    /// The path summaries shared by the template's instances.
+    pub(crate) fn summaries(&self) -> &Rc<RefCell<SummaryCache<T>>> {
+        &self.summaries
+    }
+}
+
+impl<A: Allocate, T: Refines<()>, P, R> Clone for Template<A, T, P, R> {
+    fn clone(&self) -> Self {
+        Template {
+            name: self.name.clone(),
+            build: self.build.clone(),
+            summaries: self.summaries.clone(),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+//! Errors in starting computations and building their dataflows.
+//!
+//! The `try_` variants of functions that start computations, such as
+//! [`try_execute`](crate::try_execute()), return an [`Error`] when the workers cannot be started,
+//! for example because a process cannot bind its address or disagrees with another process on the
+//! number of workers. The functions they wrap, such as [`execute`](crate::execute()), describe the
+//! error as a string. Fallible methods of workers, such as
+//! [`Worker::try_dataflow`](crate::worker::Worker::try_dataflow), report mistakes in the
+//! construction of dataflows as errors that convert into an [`Error`], so that embedders may
+//! handle all of them with `?`. The methods they wrap, such as `Worker::dataflow`, panic instead.
+
+use std::fmt;
+
+use crate::communication::Error as CommunicationError;
+use crate::progress::validation::GraphError;
+
+/// A failure to start a computation, or to build one of its dataflows.
+#[derive(Debug)]
+pub enum Error {
+    /// The configuration could not be parsed.
+    Config(String),
+    /// Communication between workers could not be established, as for a failure to bind an
+    /// address, or a disagreement between processes on the number of workers.
+    Communication(CommunicationError),
+    /// A dataflow was constructed incorrectly.
+    Graph(GraphError),
+    /// A channel was allocated for an operator with an invalid address.
+    Address {
+        /// The identifier of the channel.
+        identifier: usize,
+        /// The address of the operator.
+        address: Vec<usize>,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Config(reason) => write!(f, "invalid configuration: {}", reason),
+            Error::Communication(error) => error.fmt(f),
+            Error::Graph(error) => error.fmt(f),
+            Error::Address { identifier, address } => write!(f, "unacceptable address {:?} for channel {}", address, identifier),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Communication(error) => Some(error),
+            Error::Graph(error) => Some(error),
+            Error::Config(_) | Error::Address { .. } => None,
+        }
+    }
+}
+
+impl From<CommunicationError> for Error {
+    fn from(error: CommunicationError) -> Error {
+        Error::Communication(error)
+    }
+}
+
+impl From<GraphError> for Error {
+    fn from(error: GraphError) -> Error {
+        Error::Graph(error)
+    }
+}
+
+/// Describes the error, for callers that report errors as strings.
+impl From<Error> for String {
+    fn from(error: Error) -> String {
+        error.to_string()
+    }
+}
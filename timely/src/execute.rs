@@ -1,9 +1,17 @@
 //! Starts a timely dataflow execution from configuration information and per-worker logic.
 
-use crate::communication::{initialize_from, Allocator, allocator::AllocateBuilder, WorkerGuards};
+use crate::communication::{try_initialize_from, initialize_with_spawner, try_build_in_process, try_build_mpi, Allocator, allocator::AllocateBuilder, Error as CommunicationError, WorkerGuards, WorkerTask};
+use crate::communication::allocator::GenericBuilder;
+use crate::communication::allocator::zero_copy::mpi::Communicator;
+use crate::communication::compression::Compression;
+use crate::communication::allocator::simulation::{Simulated, Simulation, SimulationConfig};
 use crate::dataflow::scopes::Child;
+use crate::logging_core::clock::MockClock;
 use crate::worker::Worker;
-use crate::{CommunicationConfig, WorkerConfig};
+use crate::{CommunicationConfig, Error, WorkerConfig};
+
+// Records queued for the collectors configured, or named by `TIMELY_WORKER_LOG_ADDR` and `TIMELY_COMM_LOG_ADDR`.
+const LOG_SINK_CAPACITY: usize = 1 << 16;
 
 /// Configures the execution of a timely dataflow computation.
 pub struct Config {
@@ -56,6 +64,81 @@ impl Config {
         Config::from_matches(&matches)
     }
 
+    /// Constructs a configuration from TOML text.
+    ///
+    /// All settings are optional, and default as they do for [`execute_from_args`]. The
+    /// top-level settings describe the communication infrastructure:
+    ///
+    /// * `threads`, `processes`, `process`: as the `-w`, `-n`, and `-p` arguments.
+    /// * `hosts`: the addresses of the processes, in order; or `hostfile`, a file naming them.
+    ///   Each address may be followed by whitespace and a separate bind address, as described
+    ///   at [`PeerAddress`](crate::communication::networking::PeerAddress).
+    /// * `bind`: the address on which this process accepts connections, if not its address.
+    /// * `transport`: `"tcp"` or `"shm"`; `compression`: `"none"` or `"lz4"`; `report`: a bool.
+    /// * `max_record_bytes`: the largest serialized record a worker may send to another process;
+    ///   workers drop larger records.
+    ///
+    /// The `[worker]` table holds `progress_mode` (`"eager"` or `"demand"`), `batch_size`,
+    /// `track_capabilities`, `trace_lineage`, `validate_progress`, `isolate_panics`,
+    /// `watchdog_ms`, and `log_file_capacity` with `log_file_period_ms`, each as the
+    /// [`WorkerConfig`] method of the same name. Its `[worker.params]` table holds parameters installed with
+    /// [`WorkerConfig::set`], as `String`, `i64`, `f64`, or `bool` values. The `[logging]`
+    /// table holds the addresses of the `worker` and `communication` log collectors.
+    ///
+    /// Unknown settings, values of the wrong type, and invalid combinations are errors, each of
+    /// which names the offending setting.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use timely::worker::AsWorker;
+    ///
+    /// let config = timely::Config::from_toml(r#"
+    ///     threads = 2
+    ///
+    ///     [worker]
+    ///     batch_size = 256
+    ///
+    ///     [worker.params]
+    ///     greeting = "hello"
+    /// "#).unwrap();
+    ///
+    /// timely::execute(config, |worker| {
+    ///     assert_eq!(worker.peers(), 2);
+    ///     assert_eq!(worker.config().get::<String>("greeting").map(|s| s.as_str()), Some("hello"));
+    /// }).unwrap();
+    ///
+    /// let error = timely::Config::from_toml("threads = 0").err().unwrap();
+    /// assert!(error.contains("`threads`"));
+    /// ```
+    pub fn from_toml(text: &str) -> Result<Config, String> {
+        crate::settings::Settings::from_toml(text)?.into_config()
+    }
+
+    /// Constructs a configuration from the TOML file at `path`, overridden by the environment.
+    ///
+    /// The file is read as by [`Config::from_toml`], and then environment variables override its
+    /// settings as described at [`Config::from_env`].
+    pub fn from_file<P: AsRef<::std::path::Path>>(path: P) -> Result<Config, String> {
+        let path = path.as_ref();
+        let text = ::std::fs::read_to_string(path).map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let mut settings = crate::settings::Settings::from_toml(&text)?;
+        settings.apply_vars(::std::env::vars())?;
+        settings.into_config()
+    }
+
+    /// Constructs a configuration from environment variables.
+    ///
+    /// The variables `TIMELY_THREADS`, `TIMELY_PROCESSES`, `TIMELY_PROCESS`, `TIMELY_HOSTS` (a
+    /// comma-separated list), `TIMELY_HOSTFILE`, `TIMELY_BIND`, `TIMELY_TRANSPORT`,
+    /// `TIMELY_COMPRESSION`, `TIMELY_REPORT`, `TIMELY_MAX_RECORD_BYTES`, `TIMELY_PROGRESS_MODE`,
+    /// `TIMELY_BATCH_SIZE`, `TIMELY_WORKER_LOG_ADDR`, and `TIMELY_COMM_LOG_ADDR` supply the settings described at
+    /// [`Config::from_toml`].
+    pub fn from_env() -> Result<Config, String> {
+        let mut settings = crate::settings::Settings::default();
+        settings.apply_vars(::std::env::vars())?;
+        settings.into_config()
+    }
+
     /// Constructs a `Config` that uses one worker thread and the
     /// defaults for all other parameters.
     pub fn thread() -> Config {
@@ -159,6 +242,101 @@ where
     result
 }
 
+/// A single worker, stepped by its host rather than run to completion.
+///
+/// Returned by [`execute_cooperatively`].
+pub struct Cooperative {
+    worker: Worker<crate::communication::allocator::thread::Thread>,
+}
+
+impl Cooperative {
+    /// Steps the worker once, without waiting for events, and indicates if dataflows remain.
+    pub fn tick(&mut self) -> bool {
+        self.worker.step()
+    }
+    /// The worker, for example to supply input or build dataflows between ticks.
+    pub fn worker(&mut self) -> &mut Worker<crate::communication::allocator::thread::Thread> {
+        &mut self.worker
+    }
+}
+
+/// Constructs a single-threaded timely dataflow computation, to be stepped by the caller.
+///
+/// The `execute_cooperatively` method constructs a `Worker`, invokes the supplied closure to build
+/// dataflows, and returns the result of the closure along with the worker. Unlike
+/// `execute_directly`, it does not run the worker; the caller instead invokes
+/// [`Cooperative::tick`] as it sees fit, for example from a timer or animation frame callback
+/// of a host that must not be blocked. The worker neither spawns threads nor waits for events,
+/// which makes this method suitable for targets such as `wasm32-unknown-unknown`.
+///
+/// Targets without a system clock should instead use [`execute_cooperatively_from`], with a
+/// worker configuration whose [`clock`](WorkerConfig::clock) the host advances before each tick.
+///
+/// # Examples
+/// ```rust
+/// use timely::dataflow::InputHandle;
+/// use timely::dataflow::operators::{Input, Inspect, Probe};
+///
+/// let (mut ticker, (mut input, probe)) = timely::execute_cooperatively(|worker| {
+///     let mut input = InputHandle::new();
+///     let probe = worker.dataflow(|scope| {
+///         scope.input_from(&mut input)
+///              .inspect(|x: &u64| println!("seen: {:?}", x))
+///              .probe()
+///     });
+///     (input, probe)
+/// });
+///
+/// // A host would invoke `tick` from its own event loop.
+/// for round in 0 .. 10 {
+///     input.send(round);
+///     input.advance_to(round + 1);
+///     while probe.less_than(input.time()) {
+///         ticker.tick();
+///     }
+/// }
+/// drop(input);
+/// while ticker.tick() { }
+/// ```
+pub fn execute_cooperatively<T, F>(func: F) -> (Cooperative, T)
+where
+    F: FnOnce(&mut Worker<crate::communication::allocator::thread::Thread>)->T,
+{
+    execute_cooperatively_from(WorkerConfig::default(), func)
+}
+
+/// Constructs a single-threaded timely dataflow computation from a worker configuration, to be
+/// stepped by the caller.
+///
+/// As [`execute_cooperatively`], but with the worker configured by `worker_config`, for example
+/// to read time from a clock the host advances.
+///
+/// # Examples
+/// ```rust
+/// use std::time::Duration;
+/// use timely::WorkerConfig;
+/// use timely::logging_core::clock::MockClock;
+///
+/// let clock = MockClock::new();
+/// let config = WorkerConfig::default().clock(clock.clone());
+/// let (mut ticker, ()) = timely::execute_cooperatively_from(config, |_worker| ());
+///
+/// // A host would advance the clock, and then tick, from its own event loop.
+/// clock.advance(Duration::from_millis(16));
+/// ticker.tick();
+/// ```
+pub fn execute_cooperatively_from<T, F>(mut worker_config: WorkerConfig, func: F) -> (Cooperative, T)
+where
+    F: FnOnce(&mut Worker<crate::communication::allocator::thread::Thread>)->T,
+{
+    // Clones of a configuration share steal queues, which must not outlive one computation.
+    worker_config.steal_queues = Default::default();
+    let alloc = crate::communication::allocator::thread::Thread::new();
+    let mut worker = crate::worker::Worker::new(worker_config, alloc);
+    let result = func(&mut worker);
+    (Cooperative { worker }, result)
+}
+
 /// Executes a timely dataflow from a configuration and per-communicator logic.
 ///
 /// The `execute` method takes a `Configuration` and spins up some number of
@@ -217,75 +395,311 @@ where
 /// assert_eq!(recv.extract()[0].1, (0..30).map(|x| x / 3).collect::<Vec<_>>());
 /// ```
 pub fn execute<T, F>(
-    mut config: Config,
+    config: Config,
     func: F
 ) -> Result<WorkerGuards<T>,String>
 where
     T:Send+'static,
     F: Fn(&mut Worker<Allocator>)->T+Send+Sync+'static {
+    Ok(try_execute(config, func)?)
+}
+
+/// Executes a timely dataflow from a configuration and per-communicator logic, as
+/// [`execute`](execute()) does, reporting why it could not start as an [`Error`].
+///
+/// # Examples
+/// ```rust
+/// use timely::{CommunicationConfig, Config, Error};
+/// use timely::communication::networking::PeerAddress;
+///
+/// // process 1 of a single process has no address.
+/// let peers = vec![PeerAddress::new("127.0.0.1:1")];
+/// let config = Config { communication: CommunicationConfig::cluster(1, 1, peers), worker: Default::default() };
+/// match timely::try_execute(config, |_worker| ()) {
+///     Err(Error::Communication(error)) => println!("could not start: {}", error),
+///     _ => panic!("execution should fail"),
+/// }
+/// ```
+pub fn try_execute<T, F>(
+    config: Config,
+    func: F
+) -> Result<WorkerGuards<T>,Error>
+where
+    T:Send+'static,
+    F: Fn(&mut Worker<Allocator>)->T+Send+Sync+'static {
+    let (allocators, other) = build_communication(config.communication, config.worker.log_addresses.1.clone())?;
+    Ok(try_initialize_from(allocators, other, worker_logic(config.worker, func))?)
+}
+
+/// Executes a timely dataflow from a configuration, on worker threads provided by `spawner`.
+///
+/// This method behaves as [`execute`](execute()), except that rather than spawning a thread for
+/// each worker, it calls `spawner` with the index of each worker and a task that runs the worker
+/// to completion. This allows timely to run inside runtimes that manage their own threads, such
+/// as thread pools, test frameworks, or hosts that call in from other languages. The tasks of all
+/// workers must run at the same time, each on its own thread; see
+/// [`initialize_with_spawner`](crate::communication::initialize_with_spawner) for the
+/// requirements on `spawner`. Threads that connect processes of a cluster are still spawned by
+/// timely.
+///
+/// # Examples
+/// ```rust
+/// use timely::dataflow::operators::{ToStream, Inspect};
+///
+/// // runs each worker on a thread with a larger stack, named by the caller.
+/// let guards = timely::execute_with_spawner(timely::Config::process(2), |index, task| {
+///     std::thread::Builder::new()
+///         .name(format!("embedder:{}", index))
+///         .stack_size(16 << 20)
+///         .spawn(task)
+///         .map(|_| ())
+///         .map_err(|e| e.to_string())
+/// }, |worker| {
+///     worker.dataflow::<(),_,_>(|scope| {
+///         (0..10).to_stream(scope)
+///                .inspect(|x| println!("seen: {:?}", x));
+///     });
+///     worker.index()
+/// }).unwrap();
+///
+/// assert_eq!(guards.join(), vec![Ok(0), Ok(1)]);
+/// ```
+pub fn execute_with_spawner<T, F, S>(
+    config: Config,
+    spawner: S,
+    func: F
+) -> Result<WorkerGuards<T>,String>
+where
+    T:Send+'static,
+    F: Fn(&mut Worker<Allocator>)->T+Send+Sync+'static,
+    S: FnMut(usize, WorkerTask)->Result<(), String> {
+    let (allocators, other) = build_communication(config.communication, config.worker.log_addresses.1.clone())?;
+    initialize_with_spawner(allocators, other, spawner, worker_logic(config.worker, func))
+}
+
+// Builds the allocators of `communication`, logging communication events to the collector at
+// `log_address`, or to the one named by `TIMELY_COMM_LOG_ADDR`.
+fn build_communication(
+    mut communication: CommunicationConfig,
+    log_address: Option<String>,
+) -> Result<(Vec<GenericBuilder>, Box<dyn ::std::any::Any+Send>), CommunicationError> {
 
-    if let CommunicationConfig::Cluster { ref mut log_fn, .. } = config.communication {
+    if let CommunicationConfig::Cluster { ref mut log_fn, .. } = communication {
 
-        *log_fn = Box::new(|events_setup| {
+        *log_fn = Box::new(move |events_setup| {
 
             let mut result = None;
-            if let Ok(addr) = ::std::env::var("TIMELY_COMM_LOG_ADDR") {
+            if let Some(addr) = log_address.clone().or_else(|| ::std::env::var("TIMELY_COMM_LOG_ADDR").ok()) {
 
-                use ::std::net::TcpStream;
-                use crate::logging::BatchLogger;
-                use crate::dataflow::operators::capture::EventWriter;
+                use crate::logging::{BatchLogger, TcpSink};
 
                 eprintln!("enabled COMM logging to {}", addr);
 
-                if let Ok(stream) = TcpStream::connect(&addr) {
-                    let writer = EventWriter::new(stream);
-                    let mut logger = BatchLogger::new(writer);
-                    result = Some(crate::logging_core::Logger::new(
-                        ::std::time::Instant::now(),
-                        ::std::time::Duration::default(),
-                        events_setup,
-                        move |time, data| logger.publish_batch(time, data)
-                    ));
-                }
-                else {
-                    panic!("Could not connect to communication log address: {:?}", addr);
-                }
+                let mut logger = BatchLogger::new(TcpSink::connect(addr, LOG_SINK_CAPACITY));
+                result = Some(crate::logging_core::Logger::new(
+                    crate::logging_core::clock::system().now(),
+                    ::std::time::Duration::default(),
+                    events_setup,
+                    move |time, data| logger.publish_batch(time, data)
+                ));
             }
             result
         });
     }
 
-    let (allocators, other) = config.communication.try_build()?;
+    communication.try_assemble()
+}
 
-    let worker_config = config.worker;
-    initialize_from(allocators, other, move |allocator| {
+// The logic of each worker, which installs the configured logging, runs `func`, and then steps
+// the worker until its dataflows complete.
+fn worker_logic<T, F>(mut worker_config: WorkerConfig, func: F) -> impl Fn(Allocator)->T+Send+Sync+'static
+where
+    T:Send+'static,
+    F: Fn(&mut Worker<Allocator>)->T+Send+Sync+'static {
+    // Clones of a configuration share steal queues, which must not outlive one computation.
+    worker_config.steal_queues = Default::default();
+    move |allocator| {
 
         let mut worker = Worker::new(worker_config.clone(), allocator);
 
-        // If an environment variable is set, use it as the default timely logging.
-        if let Ok(addr) = ::std::env::var("TIMELY_WORKER_LOG_ADDR") {
+        // If a collector is configured, or named by an environment variable, use it as the default timely logging.
+        if let Some(addr) = worker_config.log_addresses.0.clone().or_else(|| ::std::env::var("TIMELY_WORKER_LOG_ADDR").ok()) {
 
-            use ::std::net::TcpStream;
-            use crate::logging::{BatchLogger, TimelyEvent};
-            use crate::dataflow::operators::capture::EventWriter;
+            use crate::logging::{BatchLogger, TcpSink, TimelyEvent};
 
-            if let Ok(stream) = TcpStream::connect(&addr) {
-                let writer = EventWriter::new(stream);
-                let mut logger = BatchLogger::new(writer);
-                worker.log_register()
-                    .insert::<TimelyEvent,_>("timely", move |time, data|
-                        logger.publish_batch(time, data)
-                    );
-            }
-            else {
-                panic!("Could not connect logging stream to: {:?}", addr);
-            }
+            let mut logger = BatchLogger::new(TcpSink::connect(addr, LOG_SINK_CAPACITY));
+            worker.log_register()
+                .insert::<TimelyEvent,_>("timely", move |time, data|
+                    logger.publish_batch(time, data)
+                );
         }
 
         let result = func(&mut worker);
         while worker.step_or_park(None) { }
         result
-    })
+    }
+}
+
+/// Workers of a simulated network, stepped by the caller in an order chosen by the simulation.
+///
+/// Returned by [`execute_simulated`].
+pub struct SimulatedCluster {
+    simulation: Simulation,
+    workers: Vec<Worker<Simulated>>,
+    clock: MockClock,
+}
+
+/// The time by which the clock of a simulated cluster advances at each tick.
+pub const SIMULATED_TICK: ::std::time::Duration = ::std::time::Duration::from_millis(1);
+
+impl SimulatedCluster {
+    /// Steps each worker once, in an order chosen by the simulation, and then delivers the
+    /// messages due and advances the simulation clock, and the workers' clock by [`SIMULATED_TICK`].
+    ///
+    /// Returns `true` while any worker has dataflows or any message is in flight.
+    pub fn tick(&mut self) -> bool {
+        let mut active = false;
+        for index in self.simulation.order() {
+            active |= self.workers[index].step();
+        }
+        self.simulation.advance();
+        self.clock.advance(SIMULATED_TICK);
+        active || self.simulation.in_flight() > 0
+    }
+    /// The worker with index `index`, for example to supply input between ticks.
+    pub fn worker(&mut self, index: usize) -> &mut Worker<Simulated> {
+        &mut self.workers[index]
+    }
+    /// The simulated network, for example to read its clock or count dropped messages.
+    pub fn simulation(&self) -> &Simulation {
+        &self.simulation
+    }
+    /// The clock the workers read, for example to advance time further between ticks.
+    pub fn clock(&self) -> &MockClock {
+        &self.clock
+    }
+}
+
+/// Constructs `peers` workers connected by a simulated network, to be stepped by the caller.
+///
+/// All workers run in the current thread, and exchange messages through a
+/// [`Simulation`](crate::communication::allocator::simulation::Simulation) that delays, reorders,
+/// and drops them as directed by `config`, with all choices drawn from its seed. The supplied
+/// closure is invoked on each worker in turn to build dataflows, and the results are returned
+/// along with the workers, which the caller runs by invoking [`SimulatedCluster::tick`]. The same
+/// seed and the same calls produce the same execution, which allows properties of a computation
+/// to be tested against many adversarial schedules, and failures replayed from their seed.
+///
+/// The workers read the time from a [`MockClock`] that advances by [`SIMULATED_TICK`] at each
+/// tick, and so delayed activations and time-based operators are also deterministic.
+///
+/// # Examples
+/// ```rust
+/// use timely::dataflow::InputHandle;
+/// use timely::dataflow::operators::{Input, Exchange, Probe};
+/// use timely::communication::allocator::simulation::SimulationConfig;
+///
+/// for seed in 0 .. 10 {
+///     let config = SimulationConfig::new(seed).max_delay(5);
+///     let (mut cluster, mut handles) = timely::execute_simulated(3, config, |worker| {
+///         let mut input = InputHandle::new();
+///         let probe = worker.dataflow(|scope| {
+///             scope.input_from(&mut input)
+///                  .exchange(|x: &u64| *x)
+///                  .probe()
+///         });
+///         (input, probe)
+///     });
+///
+///     for (input, _) in handles.iter_mut() {
+///         input.send(1);
+///         input.advance_to(1);
+///     }
+///     while handles.iter().any(|(_, probe)| probe.less_than(&1)) {
+///         cluster.tick();
+///     }
+///     handles.clear();
+///     while cluster.tick() { }
+/// }
+/// ```
+pub fn execute_simulated<T, F>(peers: usize, config: SimulationConfig, mut func: F) -> (SimulatedCluster, Vec<T>)
+where
+    F: FnMut(&mut Worker<Simulated>)->T,
+{
+    let (simulation, allocators) = Simulation::new(peers, config);
+    let clock = MockClock::new();
+    let worker_config = WorkerConfig::default().clock(clock.clone());
+    let mut workers = allocators.into_iter().map(|allocator| Worker::new(worker_config.clone(), allocator)).collect::<Vec<_>>();
+    let results = workers.iter_mut().map(&mut func).collect();
+    (SimulatedCluster { simulation, workers, clock }, results)
+}
+
+/// Executes a timely dataflow on `processes` processes of `threads` workers each, all within
+/// this process.
+///
+/// The processes are connected by loopback sockets, and their workers exchange data and progress
+/// information exactly as workers in different processes would, through serialization and the
+/// network send and receive threads. This exercises the cluster communication paths from a single
+/// test binary, without spawning processes or choosing ports. All connections are established
+/// before any worker starts, and each worker has the index it would have in a cluster.
+///
+/// The result contains the guards of each process, in order. Dropping them blocks until the
+/// workers of each process complete, and then shuts down its communication threads.
+///
+/// # Examples
+/// ```rust
+/// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+/// use timely::WorkerConfig;
+///
+/// let guards = timely::execute_multiprocess_in_process(2, 2, WorkerConfig::default(), |worker| {
+///     let index = worker.index();
+///     worker.dataflow::<u64,_,_>(|scope| {
+///         (0..10u64).to_stream(scope)
+///                   .exchange(|x| *x)
+///                   .inspect(move |x| assert_eq!(*x as usize % 4, index));
+///     });
+///     index
+/// }).unwrap();
+///
+/// let indices = guards.into_iter().flat_map(|guards| guards.join()).map(|result| result.unwrap());
+/// assert_eq!(indices.collect::<Vec<_>>(), vec![0, 1, 2, 3]);
+/// ```
+pub fn execute_multiprocess_in_process<T, F>(
+    processes: usize,
+    threads: usize,
+    worker_config: WorkerConfig,
+    func: F,
+) -> Result<Vec<WorkerGuards<T>>, String>
+where
+    T: Send+'static,
+    F: Fn(&mut Worker<Allocator>)->T+Send+Sync+'static {
+    let func = ::std::sync::Arc::new(func);
+    try_build_in_process(processes, threads)?
+        .into_iter()
+        .map(|(builders, others)| {
+            let func = func.clone();
+            execute_from(builders, others, worker_config.clone(), move |worker| func(worker))
+        })
+        .collect()
+}
+
+/// Executes a timely dataflow in a process of a cluster connected by MPI.
+///
+/// Each process of the cluster calls this method, with a communicator whose rank is the index of
+/// the process, and the same number of `threads`. Data between processes are carried by MPI
+/// point-to-point messages; see the [`mpi`](crate::communication::allocator::zero_copy::mpi)
+/// module for how to supply the communicator. The method otherwise behaves as [`execute`](execute()).
+pub fn execute_mpi<T, F>(
+    communicator: ::std::sync::Arc<dyn Communicator>,
+    threads: usize,
+    worker_config: WorkerConfig,
+    func: F,
+) -> Result<WorkerGuards<T>, String>
+where
+    T: Send+'static,
+    F: Fn(&mut Worker<Allocator>)->T+Send+Sync+'static {
+    let (builders, others) = try_build_mpi(communicator, threads, Compression::None)?;
+    execute_from(builders, others, worker_config, func)
 }
 
 /// Executes a timely dataflow from supplied arguments and per-communicator logic.
@@ -312,7 +726,21 @@ where
 ///
 /// `-h, --hostfile`: a text file whose lines are "hostname:port" in order of process identity.
 /// If not specified, `localhost` will be used, with port numbers increasing from 2101 (chosen
-/// arbitrarily).
+/// arbitrarily). A line may follow the address with whitespace and an address on which the
+/// process accepts connections, if it differs, for example "host0:2101 0.0.0.0:2101".
+///
+/// `--bind`: the address on which this process accepts connections, if not its address in the
+/// hostfile; for processes behind network address translation or in containers.
+///
+/// `--compression`: compression for traffic between processes, either `none` (the default) or
+/// `lz4`. Connections compress only if both processes request the same compression.
+///
+/// `--transport`: how bytes move between processes, either `tcp` (the default) or `shm`, which
+/// uses shared memory between processes on the same host (Linux only).
+///
+/// `--rendezvous`, `--listen`: rather than reading a hostfile, register the address given by
+/// `--listen` with the coordinator at the address given by `--rendezvous`, which assigns the
+/// process identity unless `-p` is also given.
 ///
 /// This method is only available if the `getopts` feature is enabled, which
 /// it is by default.
@@ -348,8 +776,20 @@ pub fn execute_from_args<I, T, F>(iter: I, func: F) -> Result<WorkerGuards<T>,St
     where I: Iterator<Item=String>,
           T:Send+'static,
           F: Fn(&mut Worker<Allocator>)->T+Send+Sync+'static, {
-    let config = Config::from_args(iter)?;
-    execute(config, func)
+    Ok(try_execute_from_args(iter, func)?)
+}
+
+/// Executes a timely dataflow from supplied arguments and per-communicator logic, as
+/// [`execute_from_args`] does, reporting why it could not start as an [`Error`].
+///
+/// Arguments that cannot be parsed produce an [`Error::Config`].
+#[cfg(feature = "getopts")]
+pub fn try_execute_from_args<I, T, F>(iter: I, func: F) -> Result<WorkerGuards<T>,Error>
+    where I: Iterator<Item=String>,
+          T:Send+'static,
+          F: Fn(&mut Worker<Allocator>)->T+Send+Sync+'static, {
+    let config = Config::from_args(iter).map_err(Error::Config)?;
+    try_execute(config, func)
 }
 
 /// Executes a timely dataflow from supplied allocators and logging.
@@ -379,10 +819,27 @@ where
     A: AllocateBuilder+'static,
     T: Send+'static,
     F: Fn(&mut Worker<<A as AllocateBuilder>::Allocator>)->T+Send+Sync+'static {
-    initialize_from(builders, others, move |allocator| {
+    Ok(try_execute_from(builders, others, worker_config, func)?)
+}
+
+/// Executes a timely dataflow from supplied allocators and logging, as
+/// [`execute_from`](execute_from()) does, reporting why it could not start as an [`Error`].
+pub fn try_execute_from<A, T, F>(
+    builders: Vec<A>,
+    others: Box<dyn ::std::any::Any+Send>,
+    mut worker_config: WorkerConfig,
+    func: F,
+) -> Result<WorkerGuards<T>, Error>
+where
+    A: AllocateBuilder+'static,
+    T: Send+'static,
+    F: Fn(&mut Worker<<A as AllocateBuilder>::Allocator>)->T+Send+Sync+'static {
+    // Clones of a configuration share steal queues, which must not outlive one computation.
+    worker_config.steal_queues = Default::default();
+    Ok(try_initialize_from(builders, others, move |allocator| {
         let mut worker = Worker::new(worker_config.clone(), allocator);
         let result = func(&mut worker);
         while worker.step_or_park(None) { }
         result
-    })
+    })?)
 }
@@ -0,0 +1,90 @@
+//! A minimal HTTP server, answering `GET` requests from shared state.
+//!
+//! The server answers one connection at a time on its own thread, and is intended for the small
+//! volumes of requests that diagnostic endpoints receive.
+
+use std::io::{Read, Write, ErrorKind};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Weak;
+use std::time::Duration;
+
+// How often the server checks for connections, and whether it is still needed.
+const POLL_PERIOD: Duration = Duration::from_millis(10);
+
+// How long the server waits on a connection for its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A response: its status line, content type, and body.
+pub(crate) type Response = (&'static str, &'static str, String);
+
+/// Answers `GET` requests to `listener` with `handler`, until `state` is no longer referenced.
+///
+/// The handler is supplied the state and the requested path.
+pub(crate) fn serve<S, H>(name: &str, listener: TcpListener, state: Weak<S>, handler: H) -> std::io::Result<()>
+where
+    S: Send + Sync + 'static,
+    H: Fn(&S, &str)->Response + Send + 'static,
+{
+    listener.set_nonblocking(true)?;
+    std::thread::Builder::new()
+        .name(name.to_owned())
+        .spawn(move || {
+            loop {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let state = match state.upgrade() {
+                            Some(state) => state,
+                            None => return,
+                        };
+                        // Failures are the client's to observe, and do not stop the server.
+                        let _ = respond(stream, |path| handler(&state, path));
+                    },
+                    Err(error) if error.kind() == ErrorKind::WouldBlock => {
+                        if state.strong_count() == 0 {
+                            return;
+                        }
+                        std::thread::sleep(POLL_PERIOD);
+                    },
+                    Err(_) => std::thread::sleep(POLL_PERIOD),
+                }
+            }
+        })?;
+    Ok(())
+}
+
+/// Reads one request from `stream`, and writes the response.
+fn respond<H: FnOnce(&str)->Response>(mut stream: TcpStream, handler: H) -> std::io::Result<()> {
+    // Some platforms propagate the listener's non-blocking mode.
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 { break; }
+        request.extend_from_slice(&buffer[.. read]);
+    }
+    let request = String::from_utf8_lossy(&request);
+    let mut words = request.lines().next().unwrap_or("").split_whitespace();
+    let method = words.next().unwrap_or("");
+    let path = words.next().unwrap_or("");
+    let version = match words.next() {
+        Some("HTTP/1.0") => "HTTP/1.0",
+        _ => "HTTP/1.1",
+    };
+
+    let (status, content_type, body) = if method == "GET" {
+        handler(path)
+    }
+    else {
+        ("405 Method Not Allowed", "text/plain", "method not allowed\n".to_owned())
+    };
+
+    write!(
+        stream,
+        "{} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        version, status, content_type, body.len(), body,
+    )?;
+    stream.flush()
+}
@@ -0,0 +1,383 @@
+//! An HTTP endpoint reporting the state of a process's workers, as JSON.
+//!
+//! An [`Introspection`] server listens at an address and answers `GET` requests for the following
+//! paths, describing those workers that have been [attached](Introspection::attach) to it:
+//!
+//! * `/dataflows`: the installed dataflows, with their names and numbers of operators.
+//! * `/operators`: each worker's operators, with the frontiers at their inputs, the number of
+//!   times they have been scheduled, and the number of times they were scheduled in the most
+//!   recent complete second of the worker's logging time.
+//! * `/channels`: the channels between operators, with the numbers of records sent and received
+//!   by the process's workers, and the difference of the two, records still enqueued.
+//! * `/`: all of the above, as the fields `dataflows`, `operators`, and `channels` of one object.
+//!
+//! The state is assembled from the "timely" and "timely/reachability" log streams, to which
+//! attaching a worker binds loggers. Changes are reported once the worker flushes its logs,
+//! which it does each time it steps. Channels are identified by their worker-local identifiers,
+//! which agree across workers that construct the same dataflows in the same order.
+//!
+//! This module is only available with the `introspection` feature.
+//!
+//! # Examples
+//! ```
+//! use std::io::{Read, Write};
+//! use std::net::TcpStream;
+//! use timely::introspection::Introspection;
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Map, Probe};
+//!
+//! let server = Introspection::serve("127.0.0.1:0").unwrap();
+//! let address = server.local_addr();
+//!
+//! timely::execute(timely::Config::thread(), move |worker| {
+//!     server.attach(worker);
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow(|scope| {
+//!         scope.input_from(&mut input)
+//!              .map(|x: u64| x + 1)
+//!              .probe()
+//!     });
+//!     input.send(0);
+//!     input.advance_to(5);
+//!     worker.step_while(|| probe.less_than(input.time()));
+//!
+//!     let mut stream = TcpStream::connect(address).unwrap();
+//!     stream.write_all(b"GET /operators HTTP/1.0\r\n\r\n").unwrap();
+//!     let mut response = String::new();
+//!     stream.read_to_string(&mut response).unwrap();
+//!     assert!(response.starts_with("HTTP/1.0 200 OK"));
+//!     assert!(response.contains("\"name\":\"Map\""));
+//!     assert!(response.contains("\"frontiers\":[[\"5\"]]"));
+//! }).unwrap();
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::communication::Allocate;
+use crate::http::{self, Response};
+use crate::logging::{TimelyEvent, StartStop};
+use crate::progress::reachability::logging::TrackerEvent;
+use crate::worker::Worker;
+
+/// A handle to an HTTP server reporting the state of attached workers.
+///
+/// The server stops once all handles are dropped, and all attached workers have dropped their
+/// loggers.
+#[derive(Clone)]
+pub struct Introspection {
+    state: Arc<Mutex<State>>,
+    address: SocketAddr,
+}
+
+impl Introspection {
+    /// Starts a server listening at `address`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub fn serve<A: ToSocketAddrs>(address: A) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+        http::serve("timely:introspection", listener, Arc::downgrade(&state), respond)?;
+        Ok(Introspection { state, address })
+    }
+
+    /// The address at which the server listens.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Reports the state of `worker`, from its subsequently constructed dataflows.
+    ///
+    /// This binds loggers for the "timely" and "timely/reachability" log streams, replacing any
+    /// loggers already bound to them.
+    pub fn attach<A: Allocate>(&self, worker: &mut Worker<A>) {
+        let mut register = worker.log_register();
+        let state = self.state.clone();
+        register.insert::<TimelyEvent,_>("timely", move |_time, data| {
+            let mut state = state.lock().expect("introspection state poisoned");
+            for (time, worker, event) in data.drain(..) {
+                state.workers.entry(worker).or_default().timely(time, event);
+            }
+        });
+        let state = self.state.clone();
+        register.insert::<TrackerEvent,_>("timely/reachability", move |_time, data| {
+            let mut state = state.lock().expect("introspection state poisoned");
+            for (_time, worker, event) in data.drain(..) {
+                state.workers.entry(worker).or_default().tracker(event);
+            }
+        });
+    }
+}
+
+/// Answers a request for `path`.
+fn respond(state: &Mutex<State>, path: &str) -> Response {
+    let state = state.lock().expect("introspection state poisoned");
+    let body = match path {
+        "/" => serde_json::to_string(&Summary {
+            dataflows: state.dataflows(),
+            operators: state.operators(),
+            channels: state.channels(),
+        }),
+        "/dataflows" => serde_json::to_string(&state.dataflows()),
+        "/operators" => serde_json::to_string(&state.operators()),
+        "/channels" => serde_json::to_string(&state.channels()),
+        _ => return ("404 Not Found", "application/json", "{\"error\":\"not found\"}".to_owned()),
+    };
+    ("200 OK", "application/json", body.expect("failed to serialize introspection state"))
+}
+
+/// The state of all attached workers, indexed by worker.
+#[derive(Default)]
+struct State {
+    workers: BTreeMap<usize, WorkerState>,
+}
+
+impl State {
+
+    fn dataflows(&self) -> Vec<DataflowSummary<'_>> {
+        // Dataflow identifier to name, workers, and operators.
+        let mut dataflows = BTreeMap::<usize, (&str, usize, usize)>::new();
+        for worker in self.workers.values() {
+            for operator in worker.operators.values() {
+                let entry = dataflows.entry(operator.addr[0]).or_insert(("", 0, 0));
+                if operator.addr.len() == 1 {
+                    entry.0 = &operator.name;
+                    entry.1 += 1;
+                }
+                else {
+                    entry.2 += 1;
+                }
+            }
+        }
+        dataflows
+            .into_iter()
+            .filter(|(_, (_, workers, _))| *workers > 0)
+            .map(|(id, (name, workers, operators))| DataflowSummary { id, name, workers, operators })
+            .collect()
+    }
+
+    fn operators(&self) -> Vec<OperatorSummary<'_>> {
+        let mut operators = Vec::new();
+        for (index, worker) in self.workers.iter() {
+            let mut ids = worker.operators.keys().collect::<Vec<_>>();
+            ids.sort_by_key(|id| &worker.operators[id].addr);
+            for id in ids {
+                let operator = &worker.operators[id];
+                let ports = worker.frontiers.get(&operator.addr);
+                let inputs = ports.and_then(|ports| ports.keys().max().map(|port| port + 1)).unwrap_or(0);
+                let frontiers = (0 .. inputs)
+                    .map(|port| {
+                        let mut frontier = ports
+                            .and_then(|ports| ports.get(&port))
+                            .map(|times| times.iter().filter(|(_, count)| **count > 0).map(|(time, _)| time.clone()).collect::<Vec<_>>())
+                            .unwrap_or_default();
+                        frontier.sort();
+                        frontier
+                    })
+                    .collect::<Vec<_>>();
+                operators.push(OperatorSummary {
+                    worker: *index,
+                    id: *id,
+                    addr: &operator.addr,
+                    name: &operator.name,
+                    frontiers,
+                    activations: operator.activations,
+                    recent_activations: operator.recent(worker.latest),
+                });
+            }
+        }
+        operators
+    }
+
+    fn channels(&self) -> Vec<ChannelSummary<'_>> {
+        let mut channels = BTreeMap::<usize, (&ChannelState, u64, u64)>::new();
+        for worker in self.workers.values() {
+            for (id, channel) in worker.channels.iter() {
+                let entry = channels.entry(*id).or_insert((channel, 0, 0));
+                entry.1 += channel.sent;
+                entry.2 += channel.received;
+            }
+        }
+        channels
+            .into_iter()
+            .map(|(id, (channel, sent, received))| ChannelSummary {
+                id,
+                scope_addr: &channel.scope_addr,
+                source: channel.source,
+                target: channel.target,
+                sent,
+                received,
+                queued: sent.saturating_sub(received),
+            })
+            .collect()
+    }
+}
+
+/// The state of one worker.
+#[derive(Default)]
+struct WorkerState {
+    /// The time of the most recent logged event.
+    latest: Duration,
+    /// Operators by identifier.
+    operators: HashMap<usize, OperatorState>,
+    /// Channels by identifier.
+    channels: HashMap<usize, ChannelState>,
+    /// Frontiers at the inputs of operators, by operator address and input port.
+    frontiers: HashMap<Vec<usize>, HashMap<usize, HashMap<String, i64>>>,
+}
+
+impl WorkerState {
+
+    fn timely(&mut self, time: Duration, event: TimelyEvent) {
+        self.latest = std::cmp::max(self.latest, time);
+        match event {
+            TimelyEvent::Operates(event) => {
+                self.operators.insert(event.id, OperatorState::new(event.addr, event.name));
+            },
+            TimelyEvent::Channels(event) => {
+                self.channels.insert(event.id, ChannelState {
+                    scope_addr: event.scope_addr,
+                    source: event.source,
+                    target: event.target,
+                    sent: 0,
+                    received: 0,
+                });
+            },
+            TimelyEvent::Messages(event) => {
+                if let Some(channel) = self.channels.get_mut(&event.channel) {
+                    if event.is_send { channel.sent += event.length as u64; }
+                    else { channel.received += event.length as u64; }
+                }
+            },
+            TimelyEvent::Schedule(event) if event.start_stop == StartStop::Start => {
+                if let Some(operator) = self.operators.get_mut(&event.id) {
+                    operator.scheduled(time);
+                }
+            },
+            TimelyEvent::Shutdown(event) => {
+                if let Some(operator) = self.operators.remove(&event.id) {
+                    self.frontiers.remove(&operator.addr);
+                    if operator.addr.len() == 1 {
+                        let dataflow = operator.addr[0];
+                        self.operators.retain(|_, operator| operator.addr[0] != dataflow);
+                        self.channels.retain(|_, channel| channel.scope_addr[0] != dataflow);
+                        self.frontiers.retain(|addr, _| addr[0] != dataflow);
+                    }
+                }
+            },
+            _ => { },
+        }
+    }
+
+    fn tracker(&mut self, event: TrackerEvent) {
+        if let TrackerEvent::FrontierUpdate(event) = event {
+            for (node, port, time, diff) in event.updates.iter() {
+                // Node zero is the scope itself, whose frontiers its parent reports.
+                if *node > 0 {
+                    let mut addr = event.tracker_id.clone();
+                    addr.push(*node);
+                    let times = self.frontiers.entry(addr).or_default().entry(*port).or_default();
+                    let time = format!("{:?}", time);
+                    *times.entry(time.clone()).or_insert(0) += diff;
+                    if times[&time] == 0 {
+                        times.remove(&time);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The state of one operator.
+struct OperatorState {
+    addr: Vec<usize>,
+    name: String,
+    /// The number of times the operator was scheduled.
+    activations: u64,
+    /// The second of logging time of the most recent scheduling.
+    second: u64,
+    /// The number of schedulings within `second`.
+    current: u64,
+    /// The number of schedulings within the second before `second`.
+    previous: u64,
+}
+
+impl OperatorState {
+    fn new(addr: Vec<usize>, name: String) -> Self {
+        OperatorState { addr, name, activations: 0, second: 0, current: 0, previous: 0 }
+    }
+
+    fn scheduled(&mut self, time: Duration) {
+        let second = time.as_secs();
+        if second != self.second {
+            self.previous = if second == self.second + 1 { self.current } else { 0 };
+            self.current = 0;
+            self.second = second;
+        }
+        self.current += 1;
+        self.activations += 1;
+    }
+
+    /// The number of schedulings in the complete second before `latest`.
+    fn recent(&self, latest: Duration) -> u64 {
+        let second = latest.as_secs();
+        if second == self.second { self.previous }
+        else if second == self.second + 1 { self.current }
+        else { 0 }
+    }
+}
+
+/// The description and traffic of one channel.
+struct ChannelState {
+    scope_addr: Vec<usize>,
+    source: (usize, usize),
+    target: (usize, usize),
+    sent: u64,
+    received: u64,
+}
+
+/// The report for `/`.
+#[derive(Serialize)]
+struct Summary<'a> {
+    dataflows: Vec<DataflowSummary<'a>>,
+    operators: Vec<OperatorSummary<'a>>,
+    channels: Vec<ChannelSummary<'a>>,
+}
+
+/// The report of one dataflow, for `/dataflows`.
+#[derive(Serialize)]
+struct DataflowSummary<'a> {
+    id: usize,
+    name: &'a str,
+    workers: usize,
+    operators: usize,
+}
+
+/// The report of one operator of one worker, for `/operators`.
+#[derive(Serialize)]
+struct OperatorSummary<'a> {
+    worker: usize,
+    id: usize,
+    addr: &'a [usize],
+    name: &'a str,
+    frontiers: Vec<Vec<String>>,
+    activations: u64,
+    recent_activations: u64,
+}
+
+/// The report of one channel, for `/channels`.
+#[derive(Serialize)]
+struct ChannelSummary<'a> {
+    id: usize,
+    scope_addr: &'a [usize],
+    source: (usize, usize),
+    target: (usize, usize),
+    sent: u64,
+    received: u64,
+    queued: u64,
+}
@@ -66,11 +66,14 @@ extern crate serde_derive;
 extern crate timely_communication;
 extern crate timely_bytes;
 extern crate timely_logging;
+#[cfg(target_os = "linux")]
+extern crate libc;
 
-pub use execute::{execute, execute_directly, example};
+pub use execute::{execute, try_execute, execute_with_spawner, execute_directly, execute_cooperatively, execute_cooperatively_from, execute_multiprocess_in_process, execute_mpi, execute_simulated, example};
 #[cfg(feature = "getopts")]
-pub use execute::execute_from_args;
+pub use execute::{execute_from_args, try_execute_from_args};
 pub use order::PartialOrder;
+pub use error::Error;
 
 pub use timely_communication::Config as CommunicationConfig;
 pub use worker::Config as WorkerConfig;
@@ -96,12 +99,25 @@ pub mod progress;
 pub mod dataflow;
 pub mod synchronization;
 pub mod execute;
+pub mod error;
 pub mod order;
+pub mod checkpoint;
+pub mod state;
+mod settings;
 
 pub mod logging;
 // pub mod log_events;
 
 pub mod scheduling;
+pub mod trace;
+pub mod test_harness;
+
+#[cfg(feature = "introspection")]
+pub mod introspection;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+#[cfg(any(feature = "introspection", feature = "prometheus"))]
+mod http;
 
 /// A composite trait for types usable as data in timely dataflow.
 ///
@@ -113,5 +129,16 @@ impl<T: Clone+'static> Data for T { }
 ///
 /// The `ExchangeData` trait extends `Data` with any requirements imposed by the `timely_communication`
 /// `Data` trait, which describes requirements for communication along channels.
+/// These are the requirements of the zero-copy serialization that exchange channels use by default;
+/// types that only implement `serde`'s traits may instead be exchanged as [`SerdeData`].
 pub trait ExchangeData: Data + communication::Data { }
 impl<T: Data + communication::Data> ExchangeData for T { }
+
+/// A composite trait for types usable on exchange channels that serialize with `serde`.
+///
+/// The `SerdeData` trait extends `Data` with `serde`'s traits, which suffice for channels whose
+/// pact uses the [`Bincode`](communication::codec::Bincode) codec, for example those of
+/// [`exchange_serde`](dataflow::operators::Exchange::exchange_serde). Such types need not
+/// implement `Abomonation`, and each channel of a dataflow may use either serialization.
+pub trait SerdeData: Data + Send + Sync + serde::Serialize + serde::de::DeserializeOwned { }
+impl<T: Data + Send + Sync + serde::Serialize + serde::de::DeserializeOwned> SerdeData for T { }
@@ -8,6 +8,20 @@ pub type Logger<Event> = crate::logging_core::Logger<Event, WorkerIdentifier>;
 pub type TimelyLogger = Logger<TimelyEvent>;
 /// Logger for timely dataflow progress events (the "timely/progress" log stream).
 pub type TimelyProgressLogger = Logger<TimelyProgressEvent>;
+/// Logger for latencies measured by `measure_latency` (the "timely/latency" log stream).
+pub type LatencyLogger = Logger<LatencyEvent>;
+/// Logger for the measurements of operator schedulings (the "timely/metrics" log stream).
+pub type MetricsLogger = Logger<ScheduleMetricsEvent>;
+/// Logger for the operators that spent longest scheduled (the "timely/profile" log stream).
+pub type ProfileLogger = Logger<ProfileEvent>;
+/// Logger for the counts of batches moved by channels (the "timely/channels" log stream).
+pub type ChannelStatsLogger = Logger<ChannelStatsEvent>;
+/// Logger for the counts of batches moved along channel edges (the "timely/communication" log stream).
+pub type EdgeStatsLogger = Logger<EdgeStatsEvent>;
+/// Logger for scopes whose progress has stalled (the "timely/stalls" log stream).
+pub type StallLogger = Logger<StallEvent>;
+/// Logger for summaries of the phases of worker steps (the "timely/schedule" log stream).
+pub type ScheduleSummaryLogger = Logger<ScheduleSummaryEvent>;
 
 use std::time::Duration;
 use crate::dataflow::operators::capture::{Event, EventPusher};
@@ -48,6 +62,354 @@ impl<T, E, P> Drop for BatchLogger<T, E, P> where P: EventPusher<Duration, (Dura
     }
 }
 
+/// The encoding of events written by [`Worker::log_to_file`](crate::worker::Worker::log_to_file).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One JSON object per line, written by a [`JsonWriter`].
+    Json,
+    /// Framed binary events with progress statements, in the format of
+    /// [`EventWriter`](crate::dataflow::operators::capture::EventWriter), and so replayable
+    /// with [`Replay`](crate::dataflow::operators::capture::Replay).
+    Binary,
+}
+
+/// Logs events as newline-delimited JSON.
+///
+/// Each event is written as an object with fields `time`, the nanoseconds elapsed when it was
+/// logged, `worker`, and `event`.
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+/// use timely::logging::{JsonWriter, TimelyEvent, ShutdownEvent};
+///
+/// let mut bytes = Vec::new();
+/// let mut writer = JsonWriter::new(&mut bytes);
+/// let event = TimelyEvent::Shutdown(ShutdownEvent { id: 3 });
+/// writer.publish_batch(&mut vec![(Duration::from_nanos(12), 0, event)]);
+/// drop(writer);
+///
+/// assert_eq!(String::from_utf8(bytes).unwrap(), "{\"time\":12,\"worker\":0,\"event\":{\"Shutdown\":{\"id\":3}}}\n");
+/// ```
+pub struct JsonWriter<W: std::io::Write> {
+    stream: W,
+}
+
+impl<W: std::io::Write> JsonWriter<W> {
+    /// Creates a new JSON writer.
+    pub fn new(stream: W) -> Self {
+        JsonWriter { stream }
+    }
+    /// Writes a batch of logged events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the events cannot be written.
+    pub fn publish_batch<E: serde::Serialize>(&mut self, data: &mut Vec<(Duration, WorkerIdentifier, E)>) {
+        for (time, worker, event) in data.drain(..) {
+            write!(self.stream, "{{\"time\":{},\"worker\":{},\"event\":", time.as_nanos(), worker).expect("failed to write log event");
+            serde_json::to_writer(&mut self.stream, &event).expect("failed to write log event");
+            self.stream.write_all(b"}\n").expect("failed to write log event");
+        }
+    }
+}
+
+/// A buffered writer that flushes once a period has elapsed since it last flushed.
+///
+/// The period is checked as data are written, and so buffered data may be held for longer than
+/// the period if no further data are written. Buffered data are flushed when the writer is dropped.
+pub struct PeriodicFlush<W: std::io::Write> {
+    stream: std::io::BufWriter<W>,
+    period: Duration,
+    flushed: std::time::Instant,
+}
+
+impl<W: std::io::Write> PeriodicFlush<W> {
+    /// Buffers up to `capacity` bytes for `stream`, flushing at least every `period`.
+    pub fn new(stream: W, capacity: usize, period: Duration) -> Self {
+        PeriodicFlush {
+            stream: std::io::BufWriter::with_capacity(capacity, stream),
+            period,
+            flushed: std::time::Instant::now(),
+        }
+    }
+}
+
+impl<W: std::io::Write> std::io::Write for PeriodicFlush<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.stream.write(buf)?;
+        if self.flushed.elapsed() >= self.period {
+            self.flush()?;
+        }
+        Ok(written)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flushed = std::time::Instant::now();
+        self.stream.flush()
+    }
+}
+
+/// Sends events to a remote collector over TCP, without blocking the logging worker.
+///
+/// A background thread connects to the collector, reconnecting with backoff whenever the
+/// connection fails, and writes events in the format of
+/// [`EventWriter`](crate::dataflow::operators::capture::EventWriter). Events wait in a queue of at
+/// most `capacity` records while the collector is unreachable or slow; once full, the oldest
+/// batches of records are dropped, and counted by [`dropped`](Self::dropped). Progress statements
+/// are never dropped, but consolidated while they wait. Each new connection starts with a
+/// progress statement of all progress sent so far, so that a collector replaying the connection
+/// observes the frontier of the logged stream.
+///
+/// Logging a stream to a [`BatchLogger`] wrapping a `TcpSink` is the behavior of the
+/// `TIMELY_WORKER_LOG_ADDR` and `TIMELY_COMM_LOG_ADDR` environment variables.
+///
+/// # Examples
+/// ```
+/// use std::net::TcpListener;
+/// use std::sync::atomic::Ordering;
+/// use std::time::Duration;
+/// use timely::dataflow::operators::capture::{Event, EventPusher};
+/// use timely::logging::TcpSink;
+///
+/// // An address at which no collector listens.
+/// let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+///
+/// let mut sink = TcpSink::<Duration, u64>::connect(address, 4);
+/// let dropped = sink.dropped();
+/// sink.push(Event::Messages(Duration::default(), vec![0, 1, 2]));
+/// sink.push(Event::Messages(Duration::default(), vec![3, 4, 5]));
+/// assert_eq!(dropped.load(Ordering::SeqCst), 3);
+/// ```
+pub struct TcpSink<T, D> {
+    shared: std::sync::Arc<TcpSinkShared<T, D>>,
+}
+
+// State shared by a `TcpSink` and its background thread.
+struct TcpSinkShared<T, D> {
+    queue: std::sync::Mutex<TcpSinkQueue<T, D>>,
+    changed: std::sync::Condvar,
+    dropped: std::sync::Arc<std::sync::atomic::AtomicU64>,
+}
+
+struct TcpSinkQueue<T, D> {
+    events: std::collections::VecDeque<Event<T, D>>,
+    // Records in `events`.
+    records: usize,
+    capacity: usize,
+    // Set when the sink is dropped, and unset by the background thread as it stops.
+    running: bool,
+}
+
+// How long a dropped `TcpSink` waits for its queued events to be sent.
+const TCP_SINK_LINGER: Duration = Duration::from_secs(1);
+
+impl<T, D> TcpSink<T, D>
+where
+    T: abomonation::Abomonation + Ord + Clone + Send + 'static,
+    D: abomonation::Abomonation + Send + 'static,
+{
+    /// Starts sending events to the collector at `address`, queueing at most `capacity` records.
+    pub fn connect<A: std::net::ToSocketAddrs + Send + 'static>(address: A, capacity: usize) -> Self {
+        let shared = std::sync::Arc::new(TcpSinkShared {
+            queue: std::sync::Mutex::new(TcpSinkQueue {
+                events: std::collections::VecDeque::new(),
+                records: 0,
+                capacity,
+                running: true,
+            }),
+            changed: std::sync::Condvar::new(),
+            dropped: Default::default(),
+        });
+        let state = shared.clone();
+        std::thread::Builder::new()
+            .name("timely:log-sink".to_owned())
+            .spawn(move || state.send_to(address))
+            .expect("failed to spawn log sink thread");
+        TcpSink { shared }
+    }
+}
+
+impl<T, D> TcpSink<T, D> {
+    /// A counter of the records dropped, either from a full queue or with a failed connection.
+    pub fn dropped(&self) -> std::sync::Arc<std::sync::atomic::AtomicU64> {
+        self.shared.dropped.clone()
+    }
+}
+
+impl<T: Ord, D> EventPusher<T, D> for TcpSink<T, D> {
+    fn push(&mut self, event: Event<T, D>) {
+        let mut queue = self.shared.queue.lock().expect("log sink poisoned");
+        match event {
+            Event::Progress(updates) => {
+                if let Some(Event::Progress(queued)) = queue.events.back_mut() {
+                    let mut batch = crate::progress::ChangeBatch::new();
+                    batch.extend(queued.drain(..).chain(updates));
+                    *queued = batch.into_inner();
+                }
+                else {
+                    queue.events.push_back(Event::Progress(updates));
+                }
+            },
+            Event::Messages(time, data) => {
+                queue.records += data.len();
+                queue.events.push_back(Event::Messages(time, data));
+                while queue.records > queue.capacity {
+                    let oldest = queue.events.iter().position(|event| matches!(event, Event::Messages(..)));
+                    if let Some(Event::Messages(_, data)) = oldest.and_then(|index| queue.events.remove(index)) {
+                        queue.records -= data.len();
+                        self.shared.dropped.fetch_add(data.len() as u64, std::sync::atomic::Ordering::SeqCst);
+                    }
+                }
+            },
+        }
+        self.shared.changed.notify_all();
+    }
+}
+
+impl<T, D> Drop for TcpSink<T, D> {
+    fn drop(&mut self) {
+        let mut queue = self.shared.queue.lock().expect("log sink poisoned");
+        queue.running = false;
+        self.shared.changed.notify_all();
+        // Events still queued after the linger period are abandoned to the background thread.
+        let _ = self.shared.changed.wait_timeout_while(queue, TCP_SINK_LINGER, |queue| !queue.events.is_empty());
+    }
+}
+
+impl<T, D> TcpSinkShared<T, D>
+where
+    T: abomonation::Abomonation + Ord + Clone,
+    D: abomonation::Abomonation,
+{
+    // Sends queued events to `address` until the sink is dropped and the queue is empty, or the
+    // sink is dropped while no connection is established.
+    fn send_to<A: std::net::ToSocketAddrs>(&self, address: A) {
+        let mut sent = crate::progress::ChangeBatch::<T>::new();
+        let mut delays = crate::communication::networking::Backoff::default().delays();
+        let mut connection = None;
+        loop {
+            let stream = match connection.as_mut() {
+                Some(stream) => stream,
+                None => {
+                    match std::net::TcpStream::connect(&address) {
+                        Ok(mut stream) => {
+                            delays = crate::communication::networking::Backoff::default().delays();
+                            let progress = Event::<T, D>::Progress(sent.clone().into_inner());
+                            if unsafe { abomonation::encode(&progress, &mut stream) }.is_ok() {
+                                connection = Some(stream);
+                            }
+                        },
+                        Err(_) => {
+                            let queue = self.queue.lock().expect("log sink poisoned");
+                            if !queue.running { break; }
+                            let delay = delays.next().unwrap_or(Duration::from_secs(5));
+                            let _ = self.changed.wait_timeout_while(queue, delay, |queue| queue.running);
+                        },
+                    }
+                    continue;
+                },
+            };
+
+            let event = {
+                let queue = self.queue.lock().expect("log sink poisoned");
+                let mut queue = self.changed.wait_while(queue, |queue| queue.running && queue.events.is_empty()).expect("log sink poisoned");
+                match queue.events.pop_front() {
+                    Some(event) => {
+                        if let Event::Messages(_, data) = &event { queue.records -= data.len(); }
+                        event
+                    },
+                    None => break,
+                }
+            };
+            if let Event::Progress(updates) = &event {
+                sent.extend(updates.iter().cloned());
+            }
+            if unsafe { abomonation::encode(&event, stream) }.is_err() {
+                if let Event::Messages(_, data) = &event {
+                    self.dropped.fetch_add(data.len() as u64, std::sync::atomic::Ordering::SeqCst);
+                }
+                connection = None;
+            }
+            self.changed.notify_all();
+        }
+        self.queue.lock().expect("log sink poisoned").events.clear();
+        self.changed.notify_all();
+    }
+}
+
+/// A change to a named log stream, applied by every worker through a [`LogControl`].
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum LogCommand {
+    /// Resumes the named log stream.
+    Enable(String),
+    /// Pauses the named log stream, whose events are discarded until it is resumed.
+    Disable(String),
+}
+
+/// Pauses and resumes named log streams on all workers of a computation at once.
+///
+/// Each worker creates a `LogControl`, which builds a dataflow and so must be created by all
+/// workers in the same order relative to their other dataflows. Any worker may then `enable` or
+/// `disable` a log stream, and every worker applies the commands of all workers, in the same
+/// order, as it calls `apply`. Commands naming streams a worker has not bound are ignored by it.
+/// See [`Registry::set_enabled`](crate::logging_core::Registry::set_enabled) for the effect of
+/// pausing a stream.
+///
+/// # Examples
+/// ```
+/// use timely::logging::{LogControl, TimelyEvent};
+///
+/// timely::execute(timely::Config::process(2), |worker| {
+///     worker.log_register().insert::<TimelyEvent,_>("timely", |_time, _data| { });
+///     let mut control = LogControl::new(worker);
+///     if worker.index() == 0 {
+///         control.disable("timely");
+///     }
+///     while control.apply(worker) == 0 {
+///         worker.step();
+///     }
+///     assert_eq!(worker.log_register().is_enabled("timely"), Some(false));
+/// }).unwrap();
+/// ```
+pub struct LogControl {
+    sequencer: crate::synchronization::Sequencer<LogCommand>,
+}
+
+impl LogControl {
+    /// Creates a control for the log streams of `worker`, and of its peers.
+    pub fn new<A: crate::communication::Allocate>(worker: &mut crate::worker::Worker<A>) -> Self {
+        let timer = worker.timer();
+        LogControl { sequencer: crate::synchronization::Sequencer::new(worker, timer) }
+    }
+
+    /// Resumes the log stream `name` on all workers.
+    pub fn enable(&mut self, name: &str) {
+        self.sequencer.push(LogCommand::Enable(name.to_owned()));
+    }
+
+    /// Pauses the log stream `name` on all workers.
+    pub fn disable(&mut self, name: &str) {
+        self.sequencer.push(LogCommand::Disable(name.to_owned()));
+    }
+
+    /// Applies to the log streams of `worker` the commands sequenced since last called, and
+    /// returns their number.
+    ///
+    /// Commands are sequenced as workers step, and so a command takes effect only once all
+    /// workers have stepped since it was issued.
+    pub fn apply<A: crate::communication::Allocate>(&mut self, worker: &crate::worker::Worker<A>) -> usize {
+        let mut applied = 0;
+        for command in self.sequencer.by_ref() {
+            match &command {
+                LogCommand::Enable(name) => worker.log_register().set_enabled(name, true),
+                LogCommand::Disable(name) => worker.log_register().set_enabled(name, false),
+            };
+            applied += 1;
+        }
+        applied
+    }
+}
+
 #[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 /// The creation of an `Operate` implementor.
 pub struct OperatesEvent {
@@ -115,6 +477,8 @@ impl<T: crate::Data + std::fmt::Debug + std::any::Any> ProgressEventTimestamp fo
 pub trait ProgressEventTimestampVec: std::fmt::Debug + std::any::Any {
     /// Iterate over the contents of the vector
     fn iter<'a>(&'a self) -> Box<dyn Iterator<Item=(&'a usize, &'a usize, &'a dyn ProgressEventTimestamp, &'a i64)>+'a>;
+    /// Upcasts this `ProgressEventTimestampVec` to `Any`, to recover the concrete vector.
+    fn as_any(&self) -> &dyn std::any::Any;
 }
 
 impl<T: ProgressEventTimestamp> ProgressEventTimestampVec for Vec<(usize, usize, T, i64)> {
@@ -124,6 +488,7 @@ impl<T: ProgressEventTimestamp> ProgressEventTimestampVec for Vec<(usize, usize,
             (n, p, t, d)
         }))
     }
+    fn as_any(&self) -> &dyn std::any::Any { self }
 }
 
 #[derive(Debug)]
@@ -145,6 +510,245 @@ pub struct TimelyProgressEvent {
     pub internal: Box<dyn ProgressEventTimestampVec>,
 }
 
+impl TimelyProgressEvent {
+    /// The updates to message counts, as `(operator, input port, time, delta)`, if times have type `T`.
+    ///
+    /// Operators are identified by their index within the scope at `addr`, where index zero
+    /// is the scope itself and its input ports are the scope's outputs.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Probe};
+    /// use timely::logging::TimelyProgressEvent;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     // Capability updates sent by the input operator, the dataflow's operator one.
+    ///     let updates = Rc::new(RefCell::new(Vec::new()));
+    ///     let sink = updates.clone();
+    ///     worker.log_register().insert::<TimelyProgressEvent,_>("timely/progress", move |_time, data| {
+    ///         for (_, _, event) in data.iter().filter(|(_, _, event)| event.is_send) {
+    ///             let internal = event.internal_as::<u64>().unwrap();
+    ///             sink.borrow_mut().extend(internal.iter().filter(|u| u.0 == 1).map(|u| (u.2, u.3)));
+    ///         }
+    ///     });
+    ///
+    ///     let mut input = InputHandle::<u64, u64>::new();
+    ///     let probe = worker.dataflow(|scope| scope.input_from(&mut input).probe());
+    ///     input.advance_to(3);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    ///
+    ///     assert!(updates.borrow().contains(&(0, -1)));
+    ///     assert!(updates.borrow().contains(&(3, 1)));
+    /// }).unwrap();
+    /// ```
+    pub fn messages_as<T: 'static>(&self) -> Option<&[(usize, usize, T, i64)]> {
+        self.messages.as_any().downcast_ref::<Vec<(usize, usize, T, i64)>>().map(|v| &v[..])
+    }
+    /// The updates to capabilities, as `(operator, output port, time, delta)`, if times have type `T`.
+    ///
+    /// Operators are identified as for [`messages_as`](Self::messages_as), and the output ports
+    /// of operator zero are the scope's inputs.
+    pub fn internal_as<T: 'static>(&self) -> Option<&[(usize, usize, T, i64)]> {
+        self.internal.as_any().downcast_ref::<Vec<(usize, usize, T, i64)>>().map(|v| &v[..])
+    }
+}
+
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// Latencies observed by a `measure_latency` operator in one activation.
+pub struct LatencyEvent {
+    /// The name under which the latencies are recorded.
+    pub name: String,
+    /// The latencies observed.
+    pub histogram: crate::dataflow::operators::latency::LatencyHistogram,
+}
+
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Copy, Default, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// The reasons a scope scheduled an operator; any number may apply.
+pub struct ActivationCause {
+    /// The operator was activated, by an `Activator` or on its own behalf.
+    pub activated: bool,
+    /// The frontier of some input changed.
+    pub frontier: bool,
+    /// Records were sent to some input.
+    pub messages: bool,
+}
+
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// The measurements of one scheduling of an operator.
+pub struct ScheduleMetricsEvent {
+    /// Worker-unique identifier for the operator, linkable to the identifiers in `OperatesEvent`.
+    pub id: usize,
+    /// The time the operator spent scheduled.
+    pub elapsed: Duration,
+    /// The number of records consumed at each input.
+    pub consumed: Vec<i64>,
+    /// The number of records produced at each output.
+    pub produced: Vec<i64>,
+    /// The reasons the operator was scheduled.
+    pub cause: ActivationCause,
+}
+
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// One of the operators that spent longest scheduled, reported periodically as configured by
+/// [`Config::profile`](crate::worker::Config::profile).
+pub struct ProfileEvent {
+    /// The position of the operator among those reported, from zero for the longest scheduled.
+    pub rank: usize,
+    /// Worker-unique identifier for the operator, linkable to the identifiers in `OperatesEvent`.
+    pub id: usize,
+    /// Sequence of nested scope identifiers indicating the path from the root to this instance.
+    pub addr: Vec<usize>,
+    /// A helpful name.
+    pub name: String,
+    /// The number of times the operator was scheduled.
+    pub schedules: u64,
+    /// The total time the operator spent scheduled.
+    pub elapsed: Duration,
+}
+
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// A summary of the worker steps since the previous summary, reported periodically as configured
+/// by [`Config::schedule_summary`](crate::worker::Config::schedule_summary).
+///
+/// See the [`steps`](crate::scheduling::steps) module for the phases of a step.
+pub struct ScheduleSummaryEvent {
+    /// The number of steps.
+    pub steps: u64,
+    /// The number of steps in which the worker parked.
+    pub parks: u64,
+    /// The time spent parked.
+    pub parked: Duration,
+    /// The time spent receiving messages from the communication layer.
+    pub receive: Duration,
+    /// The time spent organizing activations.
+    pub tidy: Duration,
+    /// The time spent scheduling active dataflows.
+    pub dataflows: Duration,
+    /// The time spent reporting to and flushing logs.
+    pub logging: Duration,
+    /// The time spent releasing sent messages to the communication layer.
+    pub release: Duration,
+    /// The longest time spent in the phases of any one step.
+    pub longest: Duration,
+}
+
+impl From<crate::scheduling::steps::StepStats> for ScheduleSummaryEvent {
+    fn from(stats: crate::scheduling::steps::StepStats) -> Self {
+        ScheduleSummaryEvent {
+            steps: stats.steps,
+            parks: stats.parks,
+            parked: stats.parked,
+            receive: stats.phases.receive,
+            tidy: stats.phases.tidy,
+            dataflows: stats.phases.dataflows,
+            logging: stats.phases.logging,
+            release: stats.phases.release,
+            longest: stats.longest,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// A scope whose frontiers have not changed for the timeout configured by
+/// [`Config::watchdog`](crate::worker::Config::watchdog).
+///
+/// See the [`watchdog`](crate::scheduling::watchdog) module for how stalls are detected.
+pub struct StallEvent {
+    /// Sequence of nested scope identifiers indicating the path from the root to the scope.
+    pub addr: Vec<usize>,
+    /// A helpful name for the scope.
+    pub name: String,
+    /// The time since some frontier of the scope last changed.
+    pub stalled_for: Duration,
+    /// The operators of the scope holding capabilities or with messages in flight to them.
+    pub operators: Vec<StalledOperator>,
+}
+
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// An operator of a stalled scope, with times in their `Debug` representations.
+pub struct StalledOperator {
+    /// Sequence of nested scope identifiers indicating the path from the root to this instance.
+    pub addr: Vec<usize>,
+    /// A helpful name.
+    pub name: String,
+    /// The frontier at each input.
+    pub frontiers: Vec<Vec<String>>,
+    /// The times of messages in flight to each input, from all workers.
+    pub messages: Vec<Vec<String>>,
+    /// The times of capabilities held at each output, by all workers.
+    pub capabilities: Vec<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// The counts of a channel, reported after a worker step in which they changed.
+///
+/// See the [`stats`](crate::dataflow::channels::stats) module for how the counts are determined.
+pub struct ChannelStatsEvent {
+    /// Worker-unique identifier for the channel, linkable to the identifiers in `ChannelsEvent`.
+    pub id: usize,
+    /// The number of batches pushed into the channel.
+    pub pushed_batches: u64,
+    /// The number of records pushed into the channel.
+    pub pushed_records: u64,
+    /// The approximate number of bytes pushed into the channel.
+    pub pushed_bytes: u64,
+    /// The number of batches pulled from the channel.
+    pub pulled_batches: u64,
+    /// The number of records pulled from the channel.
+    pub pulled_records: u64,
+    /// The approximate number of bytes pulled from the channel.
+    pub pulled_bytes: u64,
+}
+
+impl From<crate::dataflow::channels::stats::ChannelStats> for ChannelStatsEvent {
+    fn from(stats: crate::dataflow::channels::stats::ChannelStats) -> Self {
+        ChannelStatsEvent {
+            id: stats.id,
+            pushed_batches: stats.pushed_batches,
+            pushed_records: stats.pushed_records,
+            pushed_bytes: stats.pushed_bytes,
+            pulled_batches: stats.pulled_batches,
+            pulled_records: stats.pulled_records,
+            pulled_bytes: stats.pulled_bytes,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// The counts of a channel edge, reported after a worker step in which they changed.
+///
+/// See the [`stats`](crate::dataflow::channels::stats) module for how the counts are determined.
+pub struct EdgeStatsEvent {
+    /// Worker-unique identifier for the channel, linkable to the identifiers in `ChannelsEvent`.
+    pub channel: usize,
+    /// The index of the sending worker.
+    pub source: usize,
+    /// The index of the receiving worker.
+    pub target: usize,
+    /// The number of batches pushed along the edge.
+    pub batches: u64,
+    /// The number of records pushed along the edge.
+    pub records: u64,
+    /// The number of bytes of the pushed records when serialized.
+    pub bytes: u64,
+}
+
+impl From<crate::dataflow::channels::stats::EdgeStats> for EdgeStatsEvent {
+    fn from(stats: crate::dataflow::channels::stats::EdgeStats) -> Self {
+        EdgeStatsEvent {
+            channel: stats.channel,
+            source: stats.source,
+            target: stats.target,
+            batches: stats.batches,
+            records: stats.records,
+            bytes: stats.bytes,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
 /// External progress pushed onto an operator
 pub struct PushProgressEvent {
@@ -167,6 +771,8 @@ pub struct MessagesEvent {
     pub seq_no: usize,
     /// Number of typed records in the message.
     pub length: usize,
+    /// Where the records of the message entered the dataflow, if lineage is traced.
+    pub lineage: Option<crate::dataflow::channels::lineage::Lineage>,
 }
 
 /// Records the starting and stopping of an operator.
@@ -258,6 +864,36 @@ pub struct InputEvent {
     pub start_stop: StartStop,
 }
 
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// Buffer pool statistics for a channel, reported when the channel is torn down.
+pub struct BufferPoolEvent {
+    /// Channel identifier
+    pub channel: usize,
+    /// Buffers served from the pool.
+    pub hits: usize,
+    /// Buffers freshly allocated because the pool was empty.
+    pub misses: usize,
+    /// Buffers returned to the pool.
+    pub recycled: usize,
+    /// Buffers dropped rather than returned to the pool.
+    pub discarded: usize,
+}
+
+#[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+/// A live capability, which prevents the frontier of its operator's output from passing its time.
+pub struct CapabilityHeldEvent {
+    /// Address of the operator holding the capability, if known.
+    pub addr: Vec<usize>,
+    /// Names of the operator and its enclosing scopes, separated by `/`, if known.
+    pub name: String,
+    /// Output port of the operator.
+    pub port: usize,
+    /// The capability's time, in its `Debug` representation.
+    pub time: String,
+    /// Where the capability was created, or empty if the operator holds it without a `Capability`.
+    pub backtrace: String,
+}
+
 /// Records the starting and stopping of an operator.
 #[derive(Serialize, Deserialize, Abomonation, Debug, Clone, Hash, PartialEq, Eq, Ord, PartialOrd)]
 pub enum ParkEvent {
@@ -301,10 +937,75 @@ pub enum TimelyEvent {
     Input(InputEvent),
     /// Park event.
     Park(ParkEvent),
+    /// Buffer pool statistics.
+    BufferPool(BufferPoolEvent),
+    /// A capability held by an operator, as reported by `Worker::report_capability_holders`.
+    CapabilityHeld(CapabilityHeldEvent),
     /// Unstructured event.
     Text(String),
 }
 
+impl TimelyEvent {
+    /// The name of the event's variant, such as `"Operates"` or `"Messages"`.
+    ///
+    /// This supports filters on the kinds of events to log, as supplied to
+    /// [`Registry::insert_filtered`](crate::logging_core::Registry::insert_filtered).
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::logging::TimelyEvent;
+    /// use timely::logging_core::Sampler;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     // Log one in a hundred message events, and no other events.
+    ///     let mut sampler = Sampler::new(0.01, worker.index() as u64);
+    ///     worker.log_register().insert_filtered::<TimelyEvent,_,_>(
+    ///         "timely",
+    ///         move |event| event.kind() == "Messages" && sampler.sample(),
+    ///         |_time, data| {
+    ///             assert!(data.iter().all(|(_, _, event)| event.kind() == "Messages"));
+    ///         },
+    ///     );
+    /// }).unwrap();
+    /// ```
+    pub fn kind(&self) -> &'static str {
+        match self {
+            TimelyEvent::Operates(_) => "Operates",
+            TimelyEvent::Channels(_) => "Channels",
+            TimelyEvent::PushProgress(_) => "PushProgress",
+            TimelyEvent::Messages(_) => "Messages",
+            TimelyEvent::Schedule(_) => "Schedule",
+            TimelyEvent::Shutdown(_) => "Shutdown",
+            TimelyEvent::Application(_) => "Application",
+            TimelyEvent::GuardedMessage(_) => "GuardedMessage",
+            TimelyEvent::GuardedProgress(_) => "GuardedProgress",
+            TimelyEvent::CommChannels(_) => "CommChannels",
+            TimelyEvent::Input(_) => "Input",
+            TimelyEvent::Park(_) => "Park",
+            TimelyEvent::BufferPool(_) => "BufferPool",
+            TimelyEvent::CapabilityHeld(_) => "CapabilityHeld",
+            TimelyEvent::Text(_) => "Text",
+        }
+    }
+
+    /// The worker-unique identifier of the operator the event concerns, if any.
+    ///
+    /// Identifiers are those of `OperatesEvent`, which relates them to operator addresses.
+    pub fn operator(&self) -> Option<usize> {
+        match self {
+            TimelyEvent::Operates(event) => Some(event.id),
+            TimelyEvent::PushProgress(event) => Some(event.op_id),
+            TimelyEvent::Schedule(event) => Some(event.id),
+            TimelyEvent::Shutdown(event) => Some(event.id),
+            _ => None,
+        }
+    }
+}
+
+impl From<CapabilityHeldEvent> for TimelyEvent {
+    fn from(v: CapabilityHeldEvent) -> TimelyEvent { TimelyEvent::CapabilityHeld(v) }
+}
+
 impl From<OperatesEvent> for TimelyEvent {
     fn from(v: OperatesEvent) -> TimelyEvent { TimelyEvent::Operates(v) }
 }
@@ -352,3 +1053,7 @@ impl From<InputEvent> for TimelyEvent {
 impl From<ParkEvent> for TimelyEvent {
     fn from(v: ParkEvent) -> TimelyEvent { TimelyEvent::Park(v) }
 }
+
+impl From<BufferPoolEvent> for TimelyEvent {
+    fn from(v: BufferPoolEvent) -> TimelyEvent { TimelyEvent::BufferPool(v) }
+}
@@ -25,6 +25,24 @@ pub trait PartialOrder : Eq {
 /// and other sanity-maintaining operations.
 pub trait TotalOrder : PartialOrder { }
 
+/// A partially ordered type in which each pair of elements has a least upper bound and a
+/// greatest lower bound.
+///
+/// # Examples
+/// ```
+/// use timely::order::{Lattice, Product};
+///
+/// assert_eq!(3.join(&5), 5);
+/// assert_eq!(Product::new(1, 4).join(&Product::new(2, 3)), Product::new(2, 4));
+/// assert_eq!(Product::new(1, 4).meet(&Product::new(2, 3)), Product::new(1, 3));
+/// ```
+pub trait Lattice : PartialOrder {
+    /// The least element greater or equal to both `self` and `other`.
+    fn join(&self, other: &Self) -> Self;
+    /// The greatest element less or equal to both `self` and `other`.
+    fn meet(&self, other: &Self) -> Self;
+}
+
 macro_rules! implement_partial {
     ($($index_type:ty,)*) => (
         $(
@@ -44,12 +62,26 @@ macro_rules! implement_total {
     )
 }
 
-implement_partial!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, (), ::std::time::Duration,);
-implement_total!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, (), ::std::time::Duration,);
+macro_rules! implement_lattice {
+    ($($index_type:ty,)*) => (
+        $(
+            impl Lattice for $index_type {
+                #[inline] fn join(&self, other: &Self) -> Self { ::std::cmp::max(*self, *other) }
+                #[inline] fn meet(&self, other: &Self) -> Self { ::std::cmp::min(*self, *other) }
+            }
+        )*
+    )
+}
+
+implement_partial!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, (), ::std::time::Duration, crate::progress::timestamp::EpochMillis,);
+implement_total!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, (), ::std::time::Duration, crate::progress::timestamp::EpochMillis,);
+implement_lattice!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, (), ::std::time::Duration, crate::progress::timestamp::EpochMillis,);
 
 
 use std::fmt::{Formatter, Error, Debug};
 
+use abomonation::Abomonation;
+
 use crate::progress::Timestamp;
 use crate::progress::timestamp::Refines;
 
@@ -101,6 +133,17 @@ impl<TOuter: PartialOrder, TInner: PartialOrder> PartialOrder for Product<TOuter
     }
 }
 
+impl<TOuter: Lattice, TInner: Lattice> Lattice for Product<TOuter, TInner> {
+    #[inline]
+    fn join(&self, other: &Self) -> Self {
+        Product::new(self.outer.join(&other.outer), self.inner.join(&other.inner))
+    }
+    #[inline]
+    fn meet(&self, other: &Self) -> Self {
+        Product::new(self.outer.meet(&other.outer), self.inner.meet(&other.inner))
+    }
+}
+
 impl<TOuter: Timestamp, TInner: Timestamp> Timestamp for Product<TOuter, TInner> {
     type Summary = Product<TOuter::Summary, TInner::Summary>;
     fn minimum() -> Self { Product { outer: TOuter::minimum(), inner: TInner::minimum() }}
@@ -126,6 +169,357 @@ impl<TOuter: Timestamp, TInner: Timestamp> PathSummary<Product<TOuter, TInner>>
     }
 }
 
+/// A pair of independent timestamps, ordered when both coordinates are ordered.
+///
+/// Unlike `Product`, which nests an inner timestamp within an outer scope, a `Pair` refines `()`
+/// and so may be the timestamp of a dataflow, for example to track both event time and processing
+/// time. Frontiers of pairs may contain incomparable elements, for example `(1, 0)` and `(0, 1)`.
+///
+/// # Examples
+///
+/// ```
+/// use timely::PartialOrder;
+/// use timely::order::Pair;
+/// use timely::progress::Antichain;
+///
+/// assert!(Pair::new(1, 2).less_equal(&Pair::new(2, 2)));
+/// assert!(!Pair::new(2, 1).less_equal(&Pair::new(1, 2)));
+/// assert!(!Pair::new(1, 2).less_equal(&Pair::new(2, 1)));
+///
+/// let frontier = Antichain::from(vec![Pair::new(2, 1), Pair::new(1, 2)]);
+/// assert_eq!(frontier.elements().len(), 2);
+/// assert!(frontier.less_equal(&Pair::new(2, 2)));
+/// assert!(!frontier.less_equal(&Pair::new(1, 1)));
+/// ```
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Default, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct Pair<A, B> {
+    /// The first coordinate.
+    pub first: A,
+    /// The second coordinate.
+    pub second: B,
+}
+
+impl<A, B> Pair<A, B> {
+    /// Creates a new pair from its coordinates.
+    pub fn new(first: A, second: B) -> Pair<A, B> {
+        Pair {
+            first,
+            second,
+        }
+    }
+}
+
+impl<A: Abomonation, B: Abomonation> Abomonation for Pair<A, B> {
+    #[inline] unsafe fn entomb<W: ::std::io::Write>(&self, write: &mut W) -> ::std::io::Result<()> {
+        self.first.entomb(write)?;
+        self.second.entomb(write)
+    }
+    #[inline] unsafe fn exhume<'b>(&mut self, bytes: &'b mut [u8]) -> Option<&'b mut [u8]> {
+        let bytes = self.first.exhume(bytes)?;
+        self.second.exhume(bytes)
+    }
+    #[inline] fn extent(&self) -> usize {
+        self.first.extent() + self.second.extent()
+    }
+}
+
+/// Debug implementation to avoid seeing fully qualified path names.
+impl<A: Debug, B: Debug> Debug for Pair<A, B> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.write_str(&format!("({:?}, {:?})", self.first, self.second))
+    }
+}
+
+impl<A: PartialOrder, B: PartialOrder> PartialOrder for Pair<A, B> {
+    #[inline]
+    fn less_equal(&self, other: &Self) -> bool {
+        self.first.less_equal(&other.first) && self.second.less_equal(&other.second)
+    }
+}
+
+impl<A: Lattice, B: Lattice> Lattice for Pair<A, B> {
+    #[inline]
+    fn join(&self, other: &Self) -> Self {
+        Pair::new(self.first.join(&other.first), self.second.join(&other.second))
+    }
+    #[inline]
+    fn meet(&self, other: &Self) -> Self {
+        Pair::new(self.first.meet(&other.first), self.second.meet(&other.second))
+    }
+}
+
+impl<A: Timestamp, B: Timestamp> Timestamp for Pair<A, B> {
+    type Summary = Pair<A::Summary, B::Summary>;
+    fn minimum() -> Self { Pair { first: A::minimum(), second: B::minimum() }}
+}
+
+impl<A: Timestamp, B: Timestamp> PathSummary<Pair<A, B>> for Pair<A::Summary, B::Summary> {
+    #[inline]
+    fn results_in(&self, pair: &Pair<A, B>) -> Option<Pair<A, B>> {
+        self.first.results_in(&pair.first)
+            .and_then(|first|
+                self.second.results_in(&pair.second)
+                    .map(|second| Pair::new(first, second))
+            )
+    }
+    #[inline]
+    fn followed_by(&self, other: &Pair<A::Summary, B::Summary>) -> Option<Pair<A::Summary, B::Summary>> {
+        self.first.followed_by(&other.first)
+            .and_then(|first|
+                self.second.followed_by(&other.second)
+                    .map(|second| Pair::new(first, second))
+            )
+    }
+}
+
+impl<A: Timestamp, B: Timestamp> Refines<()> for Pair<A, B> {
+    fn to_inner(_: ()) -> Self { Self::minimum() }
+    fn to_outer(self) { }
+    fn summarize(_: <Self as Timestamp>::Summary) { }
+}
+
+/// Iteration counters for any number of nested loops, ordered when all counters are ordered.
+///
+/// The counter of each level defaults to zero, and trailing zeros are not stored, so that equal
+/// counters have equal representations. Paths are summarized by [`IterationsSummary`], which
+/// increments the counters of chosen levels.
+///
+/// # Examples
+///
+/// ```
+/// use timely::PartialOrder;
+/// use timely::order::{Iterations, IterationsSummary};
+/// use timely::progress::PathSummary;
+///
+/// let time = Iterations::new(vec![1, 4, 2]);
+/// assert_eq!(time, Iterations::new(vec![1, 4, 2, 0]));
+/// assert!(Iterations::new(vec![1, 2]).less_equal(&time));
+/// assert!(!Iterations::new(vec![0, 5]).less_equal(&time));
+///
+/// let summary = IterationsSummary::increment(1, 1);
+/// assert_eq!(summary.results_in(&time), Some(Iterations::new(vec![1, 5, 2])));
+/// ```
+#[derive(Clone, Hash, Eq, PartialEq, Default, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct Iterations {
+    counters: Vec<u32>,
+}
+
+impl Iterations {
+    /// Creates iteration counters, from the counters of the outermost level inwards.
+    pub fn new(mut counters: Vec<u32>) -> Iterations {
+        while counters.last() == Some(&0) {
+            counters.pop();
+        }
+        Iterations { counters }
+    }
+    /// The counter of `level`.
+    pub fn get(&self, level: usize) -> u32 {
+        self.counters.get(level).copied().unwrap_or(0)
+    }
+    /// The counters, omitting trailing zeros.
+    pub fn counters(&self) -> &[u32] {
+        &self.counters[..]
+    }
+}
+
+impl Abomonation for Iterations {
+    #[inline] unsafe fn entomb<W: ::std::io::Write>(&self, write: &mut W) -> ::std::io::Result<()> {
+        self.counters.entomb(write)
+    }
+    #[inline] unsafe fn exhume<'b>(&mut self, bytes: &'b mut [u8]) -> Option<&'b mut [u8]> {
+        self.counters.exhume(bytes)
+    }
+    #[inline] fn extent(&self) -> usize {
+        self.counters.extent()
+    }
+}
+
+/// Debug implementation to avoid seeing fully qualified path names.
+impl Debug for Iterations {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.write_str(&format!("{:?}", self.counters))
+    }
+}
+
+impl PartialOrder for Iterations {
+    fn less_equal(&self, other: &Self) -> bool {
+        (0 .. self.counters.len().max(other.counters.len())).all(|level| self.get(level) <= other.get(level))
+    }
+}
+
+impl Lattice for Iterations {
+    fn join(&self, other: &Self) -> Self {
+        let levels = self.counters.len().max(other.counters.len());
+        Iterations::new((0 .. levels).map(|level| self.get(level).max(other.get(level))).collect())
+    }
+    fn meet(&self, other: &Self) -> Self {
+        let levels = self.counters.len().min(other.counters.len());
+        Iterations::new((0 .. levels).map(|level| self.get(level).min(other.get(level))).collect())
+    }
+}
+
+impl Timestamp for Iterations {
+    type Summary = IterationsSummary;
+    fn minimum() -> Self { Iterations::default() }
+}
+
+impl Refines<()> for Iterations {
+    fn to_inner(_: ()) -> Self { Self::minimum() }
+    fn to_outer(self) { }
+    fn summarize(_: <Self as Timestamp>::Summary) { }
+}
+
+/// A summary of paths between `Iterations`, as the amounts by which it increments each counter.
+///
+/// Summaries do not reset the counters of deeper levels when they increment a level, as the
+/// result would not be greater or equal to the original time, which timely dataflow requires of
+/// the times at which records circulate. A loop whose deeper counters must restart from zero in
+/// each of its iterations should instead contain those deeper loops in a nested scope.
+///
+/// # Examples
+///
+/// ```
+/// use timely::PartialOrder;
+/// use timely::order::{Iterations, IterationsSummary};
+/// use timely::progress::PathSummary;
+///
+/// let inner = IterationsSummary::increment(1, 2);
+/// let outer = IterationsSummary::increment(0, 1);
+/// let time = Iterations::new(vec![0, 3, 7]);
+///
+/// let summary = inner.followed_by(&outer).unwrap();
+/// assert_eq!(summary.results_in(&time), Some(Iterations::new(vec![1, 5, 7])));
+/// assert!(outer.less_equal(&summary));
+/// assert!(!outer.less_equal(&inner));
+/// ```
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq)]
+pub struct IterationsSummary {
+    increments: Vec<u32>,
+}
+
+impl IterationsSummary {
+    /// Increments the counter of `level` by `by`.
+    pub fn increment(level: usize, by: u32) -> IterationsSummary {
+        let mut increments = vec![0; level + 1];
+        increments[level] = by;
+        IterationsSummary::new(increments)
+    }
+    /// Increments the counter of each level by the corresponding element of `increments`.
+    pub fn new(increments: Vec<u32>) -> IterationsSummary {
+        IterationsSummary { increments: Iterations::new(increments).counters }
+    }
+    /// The increment of `level`.
+    pub fn get(&self, level: usize) -> u32 {
+        self.increments.get(level).copied().unwrap_or(0)
+    }
+}
+
+impl PartialOrder for IterationsSummary {
+    fn less_equal(&self, other: &Self) -> bool {
+        (0 .. self.increments.len().max(other.increments.len())).all(|level| self.get(level) <= other.get(level))
+    }
+}
+
+impl PathSummary<Iterations> for IterationsSummary {
+    fn results_in(&self, src: &Iterations) -> Option<Iterations> {
+        let levels = self.increments.len().max(src.counters.len());
+        let counters = (0 .. levels).map(|level| src.get(level).checked_add(self.get(level))).collect::<Option<Vec<_>>>()?;
+        Some(Iterations::new(counters))
+    }
+    fn followed_by(&self, other: &Self) -> Option<Self> {
+        let levels = self.increments.len().max(other.increments.len());
+        let increments = (0 .. levels).map(|level| self.get(level).checked_add(other.get(level))).collect::<Option<Vec<_>>>()?;
+        Some(IterationsSummary::new(increments))
+    }
+}
+
+/// An iteration counter that may not exceed `MAX`.
+///
+/// A `Bounded` counter serves as its own path summary, incrementing counters by its value.
+/// Summaries yield no time when they would increment a counter past `MAX`, so that records
+/// circulating in a loop leave it at the latest by iteration `MAX`, and the progress tracker
+/// knows that no iteration past `MAX` can occur. Loops whose counters are bounded therefore
+/// terminate, and their frontiers advance, even if some records would circulate indefinitely.
+///
+/// # Examples
+///
+/// ```
+/// use timely::order::Bounded;
+/// use timely::progress::PathSummary;
+///
+/// let step = Bounded::<10>::new(1);
+/// assert_eq!(step.results_in(&Bounded::new(4)), Some(Bounded::new(5)));
+/// assert_eq!(step.results_in(&Bounded::new(10)), None);
+/// assert_eq!(Bounded::<10>::new(6).followed_by(&Bounded::new(5)), None);
+/// ```
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Default, Ord, PartialOrd, Serialize, Deserialize)]
+pub struct Bounded<const MAX: u32> {
+    counter: u32,
+}
+
+impl<const MAX: u32> Bounded<MAX> {
+    /// Creates a counter at `counter`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `counter` exceeds `MAX`.
+    pub fn new(counter: u32) -> Bounded<MAX> {
+        assert!(counter <= MAX, "counter {} exceeds its bound {}", counter, MAX);
+        Bounded { counter }
+    }
+    /// The value of the counter.
+    pub fn get(&self) -> u32 {
+        self.counter
+    }
+    /// The greatest value of the counter.
+    pub fn max() -> Bounded<MAX> {
+        Bounded { counter: MAX }
+    }
+}
+
+impl<const MAX: u32> ::abomonation::Abomonation for Bounded<MAX> { }
+
+/// Debug implementation to avoid seeing fully qualified path names.
+impl<const MAX: u32> Debug for Bounded<MAX> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        f.write_str(&format!("{:?}", self.counter))
+    }
+}
+
+impl<const MAX: u32> PartialOrder for Bounded<MAX> {
+    #[inline] fn less_than(&self, other: &Self) -> bool { self.counter < other.counter }
+    #[inline] fn less_equal(&self, other: &Self) -> bool { self.counter <= other.counter }
+}
+
+impl<const MAX: u32> TotalOrder for Bounded<MAX> { }
+
+impl<const MAX: u32> Lattice for Bounded<MAX> {
+    #[inline] fn join(&self, other: &Self) -> Self { ::std::cmp::max(*self, *other) }
+    #[inline] fn meet(&self, other: &Self) -> Self { ::std::cmp::min(*self, *other) }
+}
+
+impl<const MAX: u32> Timestamp for Bounded<MAX> {
+    type Summary = Bounded<MAX>;
+    fn minimum() -> Self { Bounded { counter: 0 } }
+}
+
+impl<const MAX: u32> PathSummary<Bounded<MAX>> for Bounded<MAX> {
+    #[inline]
+    fn results_in(&self, src: &Bounded<MAX>) -> Option<Bounded<MAX>> {
+        self.followed_by(src)
+    }
+    #[inline]
+    fn followed_by(&self, other: &Bounded<MAX>) -> Option<Bounded<MAX>> {
+        self.counter.checked_add(other.counter).filter(|counter| *counter <= MAX).map(|counter| Bounded { counter })
+    }
+}
+
+impl<const MAX: u32> Refines<()> for Bounded<MAX> {
+    fn to_inner(_: ()) -> Self { Self::minimum() }
+    fn to_outer(self) { }
+    fn summarize(_: <Self as Timestamp>::Summary) { }
+}
+
 /// A type that does not affect total orderedness.
 ///
 /// This trait is not useful, but must be made public and documented or else Rust
@@ -1,10 +1,23 @@
 //! Broadcasts progress information among workers.
+//!
+//! By default, each scheduling of a scope that produces progress updates sends them to all
+//! workers. A worker configured with [`Config::progress_batching`](crate::worker::Config::progress_batching)
+//! instead holds back updates until enough have accumulated, or the oldest has been held for long
+//! enough. Updates held back are always sent before the worker parks, as other workers, and the
+//! worker itself, may otherwise wait on them indefinitely.
 
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::logging_core::clock::{Clock, Instant};
 use crate::progress::{ChangeBatch, Timestamp};
 use crate::progress::{Location, Port};
 use crate::communication::{Message, Push, Pull};
 use crate::logging::TimelyLogger as Logger;
 use crate::logging::TimelyProgressLogger as ProgressLogger;
+use crate::scheduling::Activations;
 
 /// A list of progress updates corresponding to `((child_scope, [in/out]_port, timestamp), delta)`
 pub type ProgressVec<T> = Vec<((Location, T), i64)>;
@@ -27,6 +40,54 @@ pub struct Progcaster<T:Timestamp> {
     channel_identifier: usize,
 
     progress_logging: Option<ProgressLogger>,
+
+    /// The limits on held updates, and the worker's record of held updates, if batching.
+    batching: Option<(usize, Duration, Batches)>,
+    /// When updates were first held back, if any are.
+    held_since: Option<Instant>,
+    activations: Rc<RefCell<Activations>>,
+    /// The clock of the worker, by which updates are held and activations delayed.
+    clock: Arc<dyn Clock>,
+}
+
+/// A shared handle to the scopes of a worker holding back progress updates.
+#[derive(Clone, Default)]
+pub struct Batches {
+    held: Rc<RefCell<Vec<Vec<usize>>>>,
+    flushing: Rc<Cell<bool>>,
+}
+
+impl Batches {
+    /// Indicates whether some scope holds back progress updates.
+    pub(crate) fn is_holding(&self) -> bool {
+        !self.held.borrow().is_empty()
+    }
+
+    /// Activates each scope holding back updates, and asks it to send them when next scheduled.
+    ///
+    /// The request stands until `end_flush` is called.
+    pub(crate) fn flush(&self, activations: &RefCell<Activations>) {
+        self.flushing.set(true);
+        for path in self.held.borrow_mut().drain(..) {
+            activations.borrow_mut().activate(&path[..]);
+        }
+    }
+
+    /// Withdraws any request to send held updates.
+    pub(crate) fn end_flush(&self) {
+        self.flushing.set(false);
+    }
+
+    fn hold(&self, path: &[usize]) {
+        let mut held = self.held.borrow_mut();
+        if !held.iter().any(|held| &held[..] == path) {
+            held.push(path.to_vec());
+        }
+    }
+
+    fn release(&self, path: &[usize]) {
+        self.held.borrow_mut().retain(|held| &held[..] != path);
+    }
 }
 
 impl<T:Timestamp+Send> Progcaster<T> {
@@ -41,6 +102,9 @@ impl<T:Timestamp+Send> Progcaster<T> {
         }));
         let worker_index = worker.index();
         let addr = path.clone();
+        let batching = worker.config().progress_batching.map(|(updates, period)| (updates, period, worker.progress_batches()));
+        let activations = worker.activations();
+        let clock = worker.clock();
         Progcaster {
             to_push: None,
             pushers,
@@ -50,12 +114,52 @@ impl<T:Timestamp+Send> Progcaster<T> {
             addr,
             channel_identifier,
             progress_logging,
+            batching,
+            held_since: None,
+            activations,
+            clock,
         }
     }
 
+    /// Indicates whether changes offered have been held back, and not yet sent.
+    pub fn is_holding(&self) -> bool {
+        self.held_since.is_some()
+    }
+
+    /// Sends pointstamp changes to all workers, unless batching holds them back to send later.
+    ///
+    /// Changes held back remain in `changes`, and are sent once at least the configured number
+    /// of changes are held, the oldest has been held for the configured period, or the worker
+    /// asks for them before parking. The progcaster arranges to be activated when the period
+    /// expires.
+    pub fn offer(&mut self, changes: &mut ChangeBatch<(Location, T)>) {
+        if let Some((updates, period, batches)) = self.batching.as_ref() {
+            changes.compact();
+            if changes.is_empty() {
+                return;
+            }
+            let held_for = self.held_since.map(|since| self.clock.elapsed(since)).unwrap_or_default();
+            if changes.len() < *updates && held_for < *period && !batches.flushing.get() {
+                if self.held_since.is_none() {
+                    self.held_since = Some(self.clock.now());
+                    self.activations.borrow_mut().activate_after(&self.addr[..], *period);
+                }
+                batches.hold(&self.addr);
+                return;
+            }
+        }
+        self.send(changes);
+    }
+
     /// Sends pointstamp changes to all workers.
     pub fn send(&mut self, changes: &mut ChangeBatch<(Location, T)>) {
 
+        if self.held_since.take().is_some() {
+            if let Some((_, _, batches)) = self.batching.as_ref() {
+                batches.release(&self.addr);
+            }
+        }
+
         changes.compact();
         if !changes.is_empty() {
 
@@ -133,8 +237,8 @@ impl<T:Timestamp+Send> Progcaster<T> {
             // options for improving it if performance limits users who want other logging.
             self.progress_logging.as_ref().map(|l| {
 
-                let mut messages = Box::new(Vec::with_capacity(changes.len()));
-                let mut internal = Box::new(Vec::with_capacity(changes.len()));
+                let mut messages = Box::new(Vec::with_capacity(recv_changes.len()));
+                let mut internal = Box::new(Vec::with_capacity(recv_changes.len()));
 
                 for ((location, time), diff) in recv_changes.iter() {
 
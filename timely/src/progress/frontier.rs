@@ -1,7 +1,7 @@
 //! Tracks minimal sets of mutually incomparable elements of a partial order.
 
 use crate::progress::ChangeBatch;
-use crate::order::PartialOrder;
+use crate::order::{Lattice, PartialOrder};
 
 /// A set of mutually incomparable elements.
 ///
@@ -103,8 +103,128 @@ impl<T: PartialOrder> Antichain<T> {
         self.elements.iter().any(|x| x.less_equal(time))
     }
 
+    /// Inserts clones of the elements of `other`, and returns true iff any insertion does.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::order::Product;
+    ///
+    /// let mut frontier = Antichain::from_elem(Product::new(1, 2));
+    /// assert!(frontier.extend_from(&Antichain::from(vec![Product::new(2, 1), Product::new(3, 3)])));
+    /// assert_eq!(frontier, Antichain::from(vec![Product::new(1, 2), Product::new(2, 1)]));
+    ///```
+    pub fn extend_from(&mut self, other: &Antichain<T>) -> bool where T: Clone {
+        self.extend(other.elements().iter().cloned())
+    }
+
+    /// The greatest lower bound of `self` and `other`, the minimal elements of both.
+    ///
+    /// A time is greater or equal to an element of the result exactly when it is greater or equal
+    /// to an element of either `self` or `other`.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::order::Product;
+    ///
+    /// let frontier1 = Antichain::from(vec![Product::new(0, 2), Product::new(2, 0)]);
+    /// let frontier2 = Antichain::from(vec![Product::new(1, 1), Product::new(3, 0)]);
+    /// assert_eq!(frontier1.meet(&frontier2), Antichain::from(vec![Product::new(0, 2), Product::new(1, 1), Product::new(2, 0)]));
+    ///```
+    pub fn meet(&self, other: &Antichain<T>) -> Antichain<T> where T: Clone {
+        let mut result = self.clone();
+        result.extend_from(other);
+        result
+    }
+
+    /// The least upper bound of `self` and `other`, the minimal joins of their elements.
+    ///
+    /// A time is greater or equal to an element of the result exactly when it is greater or equal
+    /// to elements of both `self` and `other`. An empty antichain is greater than all others.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::order::Product;
+    ///
+    /// let frontier = Antichain::from_elem(3);
+    /// assert_eq!(frontier.join(&Antichain::from_elem(5)), Antichain::from_elem(5));
+    /// assert_eq!(frontier.join(&Antichain::new()), Antichain::new());
+    ///
+    /// let frontier1 = Antichain::from(vec![Product::new(0, 2), Product::new(2, 0)]);
+    /// let frontier2 = Antichain::from_elem(Product::new(1, 1));
+    /// assert_eq!(frontier1.join(&frontier2), Antichain::from(vec![Product::new(1, 2), Product::new(2, 1)]));
+    ///```
+    pub fn join(&self, other: &Antichain<T>) -> Antichain<T> where T: Lattice {
+        self.join_by(other, T::join)
+    }
+
+    /// The least upper bound of `self` and `other`, from the least upper bound of pairs of elements.
+    ///
+    /// The function `join` should return the least element greater or equal to both its arguments.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::order::Product;
+    ///
+    /// let frontier1 = Antichain::from(vec![Product::new(0, 2), Product::new(2, 0)]);
+    /// let frontier2 = Antichain::from_elem(Product::new(1, 1));
+    /// let join = |x: &Product<u64, u64>, y: &Product<u64, u64>| Product::new(x.outer.max(y.outer), x.inner.max(y.inner));
+    /// assert_eq!(frontier1.join_by(&frontier2, join), Antichain::from(vec![Product::new(1, 2), Product::new(2, 1)]));
+    ///```
+    pub fn join_by<F: Fn(&T, &T)->T>(&self, other: &Antichain<T>, join: F) -> Antichain<T> {
+        let mut result = Antichain::new();
+        for x in self.elements().iter() {
+            for y in other.elements().iter() {
+                result.insert(join(x, y));
+            }
+        }
+        result
+    }
+
+    /// Returns true if every element of `self` is also an element of `other`.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::order::Product;
+    ///
+    /// let frontier = Antichain::from(vec![Product::new(0, 2), Product::new(2, 0)]);
+    /// assert!(Antichain::from_elem(Product::new(2, 0)).is_subset_of(&frontier));
+    /// assert!(!Antichain::from_elem(Product::new(1, 1)).is_subset_of(&frontier));
+    ///```
+    pub fn is_subset_of(&self, other: &Antichain<T>) -> bool {
+        self.elements().len() <= other.elements().len() &&
+        (
+            self.elements().iter().zip(other.elements().iter()).all(|(t1,t2)| t1 == t2) ||
+            self.elements().iter().all(|t1| other.elements().iter().any(|t2| t1.eq(t2)))
+        )
+    }
+
     /// Returns true if every element of `other` is greater or equal to some element of `self`.
-    #[deprecated(since="0.12.0", note="please use `PartialOrder::less_equal` instead")]
+    ///
+    /// This is `PartialOrder::less_equal` on antichains, named for frontiers: `self` dominates
+    /// `other` when `other` has advanced at least as far. The empty antichain is dominated by
+    /// all others.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::order::Product;
+    ///
+    /// let frontier = Antichain::from(vec![Product::new(0, 2), Product::new(2, 0)]);
+    /// assert!(frontier.dominates(&Antichain::from_elem(Product::new(1, 2))));
+    /// assert!(!frontier.dominates(&Antichain::from_elem(Product::new(1, 0))));
+    /// assert!(frontier.dominates(&Antichain::new()));
+    ///```
     #[inline]
     pub fn dominates(&self, other: &Antichain<T>) -> bool {
         <Self as PartialOrder>::less_equal(self, other)
@@ -163,6 +283,19 @@ impl<T> Antichain<T> {
     ///```
     #[inline] pub fn elements(&self) -> &[T] { &self.elements[..] }
 
+    /// Iterates over the elements in the antichain.
+    ///
+    /// # Examples
+    ///
+    ///```
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::order::Product;
+    ///
+    /// let frontier = Antichain::from(vec![Product::new(0, 2), Product::new(2, 0)]);
+    /// assert_eq!(frontier.iter().map(|t| t.outer + t.inner).sum::<u64>(), 4);
+    ///```
+    #[inline] pub fn iter(&self) -> ::std::slice::Iter<'_, T> { self.elements.iter() }
+
     /// Reveals the elements in the antichain.
     ///
     /// # Examples
@@ -194,6 +327,30 @@ impl<T: PartialOrder> PartialOrder for Antichain<T> {
     }
 }
 
+impl<T: PartialOrder> ::std::iter::FromIterator<T> for Antichain<T> {
+    fn from_iter<I: IntoIterator<Item=T>>(iterator: I) -> Self {
+        let mut result = Antichain::new();
+        result.extend(iterator);
+        result
+    }
+}
+
+impl<T> ::std::iter::IntoIterator for Antichain<T> {
+    type Item = T;
+    type IntoIter = ::std::vec::IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+}
+
+impl<'a, T> ::std::iter::IntoIterator for &'a Antichain<T> {
+    type Item = &'a T;
+    type IntoIter = ::std::slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
 impl<T: PartialOrder> From<Vec<T>> for Antichain<T> {
     fn from(vec: Vec<T>) -> Self {
         // TODO: We could reuse `vec` with some care.
@@ -226,6 +383,8 @@ impl<T: PartialOrder> From<Vec<T>> for Antichain<T> {
 pub struct MutableAntichain<T: PartialOrder+Ord> {
     dirty: usize,
     updates: Vec<(T, i64)>,
+    // The length of `updates` when last consolidated.
+    clean: usize,
     frontier: Vec<T>,
     changes: ChangeBatch<T>,
 }
@@ -246,6 +405,7 @@ impl<T: PartialOrder+Ord+Clone> MutableAntichain<T> {
         MutableAntichain {
             dirty: 0,
             updates: Vec::new(),
+            clean: 0,
             frontier:  Vec::new(),
             changes: ChangeBatch::new(),
         }
@@ -266,6 +426,7 @@ impl<T: PartialOrder+Ord+Clone> MutableAntichain<T> {
     pub fn clear(&mut self) {
         self.dirty = 0;
         self.updates.clear();
+        self.clean = 0;
         self.frontier.clear();
         self.changes.clear();
     }
@@ -307,6 +468,7 @@ impl<T: PartialOrder+Ord+Clone> MutableAntichain<T> {
         MutableAntichain {
             dirty: 0,
             updates: vec![(bottom.clone(), 1)],
+            clean: 1,
             frontier: vec![bottom],
             changes: ChangeBatch::new(),
         }
@@ -378,6 +540,9 @@ impl<T: PartialOrder+Ord+Clone> MutableAntichain<T> {
 
     /// Applies updates to the antichain and enumerates any changes.
     ///
+    /// Updates that do not change the frontier are retained, and consolidated once they accumulate,
+    /// so that repeated updates to the same times use bounded memory.
+    ///
     /// # Examples
     ///
     ///```
@@ -423,6 +588,10 @@ impl<T: PartialOrder+Ord+Clone> MutableAntichain<T> {
         if rebuild_required {
             self.rebuild()
         }
+        // Updates that do not change the frontier accumulate, and are consolidated in bulk.
+        else if self.updates.len() > 2 * self.clean + 32 {
+            self.consolidate();
+        }
         self.changes.drain()
     }
 
@@ -433,17 +602,7 @@ impl<T: PartialOrder+Ord+Clone> MutableAntichain<T> {
     /// especially true when we want to apply very large numbers of updates.
     fn rebuild(&mut self) {
 
-        // sort and consolidate updates; retain non-zero accumulations.
-        if !self.updates.is_empty() {
-            self.updates.sort_by(|x,y| x.0.cmp(&y.0));
-            for i in 0 .. self.updates.len() - 1 {
-                if self.updates[i].0 == self.updates[i+1].0 {
-                    self.updates[i+1].1 += self.updates[i].1;
-                    self.updates[i].1 = 0;
-                }
-            }
-            self.updates.retain(|x| x.1 != 0);
-        }
+        self.consolidate();
 
         for time in self.frontier.drain(..) {
             self.changes.update(time, -1);
@@ -462,6 +621,21 @@ impl<T: PartialOrder+Ord+Clone> MutableAntichain<T> {
         }
     }
 
+    /// Sorts and consolidates `self.updates`, retaining non-zero accumulations.
+    fn consolidate(&mut self) {
+        if !self.updates.is_empty() {
+            self.updates.sort_by(|x,y| x.0.cmp(&y.0));
+            for i in 0 .. self.updates.len() - 1 {
+                if self.updates[i].0 == self.updates[i+1].0 {
+                    self.updates[i+1].1 += self.updates[i].1;
+                    self.updates[i].1 = 0;
+                }
+            }
+            self.updates.retain(|x| x.1 != 0);
+        }
+        self.clean = self.updates.len();
+    }
+
     /// Reports the count for a queried time.
     pub fn count_for(&self, query_time: &T) -> i64 {
         self.updates
@@ -13,6 +13,8 @@ pub mod operate;
 pub mod broadcast;
 pub mod reachability;
 pub mod subgraph;
+pub mod validation;
+pub mod snapshot;
 
 /// A timely dataflow location.
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Abomonation, Serialize, Deserialize)]
@@ -74,6 +74,7 @@
 
 use std::collections::{BinaryHeap, HashMap, VecDeque};
 use std::cmp::Reverse;
+use std::rc::Rc;
 
 use crate::progress::Timestamp;
 use crate::progress::{Source, Target};
@@ -83,6 +84,10 @@ use crate::progress::{Location, Port};
 use crate::progress::frontier::{Antichain, MutableAntichain};
 use crate::progress::timestamp::PathSummary;
 
+// A tracker, and the summaries from each of its scope inputs to each scope output.
+type Built<T> = (Tracker<T>, Vec<Vec<Antichain<<T as Timestamp>::Summary>>>);
+// Summaries from one location to each scope output, shared by locations with identical summaries.
+type SharedSummaries<T> = Rc<Vec<Antichain<<T as Timestamp>::Summary>>>;
 
 /// A topology builder, which can summarize reachability along paths.
 ///
@@ -195,14 +200,57 @@ impl<T: Timestamp> Builder<T> {
     /// default summaries (a serious liveness issue).
     ///
     /// The optional logger information is baked into the resulting tracker.
-    pub fn build(self, logger: Option<logging::TrackerLogger>) -> (Tracker<T>, Vec<Vec<Antichain<T::Summary>>>) {
+    pub fn build(self, logger: Option<logging::TrackerLogger>) -> Built<T> {
+        self.check_cycles();
+        Tracker::allocate_from(self, logger)
+    }
 
+    /// Compiles the current nodes and edges as `build` does, reusing the path summaries of
+    /// `cache` if it holds those of an identical graph.
+    ///
+    /// The summaries compiled for a graph not held by `cache` replace those it held. Builders of
+    /// many instances of one graph may share a cache, and compile its summaries only once.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use timely::progress::frontier::Antichain;
+    /// use timely::progress::{Source, Target};
+    /// use timely::progress::reachability::{Builder, SummaryCache};
+    ///
+    /// let mut cache = SummaryCache::new();
+    /// for _ in 0 .. 3 {
+    ///     let mut builder = Builder::<usize>::new();
+    ///     builder.add_node(0, 1, 1, vec![vec![Antichain::from_elem(0)]]);
+    ///     builder.add_node(1, 1, 1, vec![vec![Antichain::from_elem(1)]]);
+    ///     builder.add_edge(Source::new(0, 0), Target::new(1, 0));
+    ///     builder.add_edge(Source::new(1, 0), Target::new(0, 0));
+    ///     let (_tracker, _summary) = builder.build_cached(None, &mut cache);
+    /// }
+    /// assert_eq!(cache.hits(), 2);
+    /// ```
+    pub fn build_cached(self, logger: Option<logging::TrackerLogger>, cache: &mut SummaryCache<T>) -> Built<T> {
+        let compiled = match cache.compiled.take() {
+            Some(compiled) if compiled.nodes == self.nodes && compiled.edges == self.edges => {
+                cache.hits += 1;
+                compiled
+            },
+            _ => {
+                self.check_cycles();
+                Compiled::compile(&self)
+            },
+        };
+        let result = Tracker::assemble(self, &compiled, logger);
+        cache.compiled = Some(compiled);
+        result
+    }
+
+    // Reports cycles of default path summaries, which are likely to stall progress tracking.
+    fn check_cycles(&self) {
         if !self.is_acyclic() {
             println!("Cycle detected without timestamp increment");
             println!("{:?}", self);
         }
-
-        Tracker::allocate_from(self, logger)
     }
 
     /// Tests whether the graph a cycle of default path summaries.
@@ -343,6 +391,77 @@ impl<T: Timestamp> Builder<T> {
     }
 }
 
+/// Path summaries compiled for one graph, which builders of identical graphs may reuse.
+///
+/// See [`Builder::build_cached`].
+pub struct SummaryCache<T: Timestamp> {
+    compiled: Option<Compiled<T>>,
+    hits: usize,
+}
+
+impl<T: Timestamp> SummaryCache<T> {
+    /// Allocates an empty cache.
+    pub fn new() -> Self {
+        SummaryCache { compiled: None, hits: 0 }
+    }
+
+    /// The number of builds that reused the summaries of the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+}
+
+impl<T: Timestamp> Default for SummaryCache<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The graph of a builder, and the summaries compiled from it.
+struct Compiled<T: Timestamp> {
+    nodes: Vec<Vec<Vec<Antichain<T::Summary>>>>,
+    edges: Vec<Vec<Vec<Target>>>,
+    // Summaries from scope inputs to scope outputs.
+    scope_summary: Vec<Vec<Antichain<T::Summary>>>,
+    // Summaries from internal locations to scope outputs.
+    summaries: Vec<(Location, SharedSummaries<T>)>,
+}
+
+impl<T: Timestamp> Compiled<T> {
+    fn compile(builder: &Builder<T>) -> Self {
+
+        // Summary of scope inputs to scope outputs.
+        let mut scope_summary = vec![vec![]; builder.shape[0].1];
+        let mut summaries = Vec::new();
+
+        // Compile summaries from each location to each scope output.
+        let output_summaries = summarize_outputs::<T>(&builder.nodes, &builder.edges);
+        let mut interner = Interner::default();
+        for (location, output) in output_summaries.into_iter() {
+            // Summaries from scope inputs are useful in summarizing the scope.
+            if location.node == 0 {
+                if let Port::Source(port) = location.port {
+                    scope_summary[port] = output;
+                }
+                else {
+                    // Ignore (ideally trivial) output to output summaries.
+                }
+            }
+            // Summaries from internal nodes are important for projecting capabilities.
+            else {
+                summaries.push((location, interner.intern(output)));
+            }
+        }
+
+        Compiled {
+            nodes: builder.nodes.clone(),
+            edges: builder.edges.clone(),
+            scope_summary,
+            summaries,
+        }
+    }
+}
+
 /// An interactive tracker of propagated reachability information.
 ///
 /// A `Tracker` tracks, for a fixed graph topology, the implications of
@@ -426,8 +545,8 @@ pub struct PortInformation<T: Timestamp> {
     pub pointstamps: MutableAntichain<T>,
     /// Current implications of active pointstamps across the dataflow.
     pub implications: MutableAntichain<T>,
-    /// Path summaries to each of the scope outputs.
-    pub output_summaries: Vec<Antichain<T::Summary>>,
+    /// Path summaries to each of the scope outputs, shared by ports with identical summaries.
+    output_summaries: SharedSummaries<T>,
 }
 
 impl<T: Timestamp> PortInformation<T> {
@@ -436,9 +555,13 @@ impl<T: Timestamp> PortInformation<T> {
         PortInformation {
             pointstamps: MutableAntichain::new(),
             implications: MutableAntichain::new(),
-            output_summaries: Vec::new(),
+            output_summaries: Rc::new(Vec::new()),
         }
     }
+    /// Path summaries to each of the scope outputs.
+    pub fn output_summaries(&self) -> &[Antichain<T::Summary>] {
+        &self.output_summaries
+    }
     /// True if updates at this pointstamp uniquely block progress.
     ///
     /// This method returns true if the currently maintained pointstamp
@@ -490,7 +613,13 @@ impl<T:Timestamp> Tracker<T> {
     /// output port.
     ///
     /// If the optional logger is provided, it will be used to log various tracker events.
-    pub fn allocate_from(builder: Builder<T>, logger: Option<logging::TrackerLogger>) -> (Self, Vec<Vec<Antichain<T::Summary>>>) {
+    pub fn allocate_from(builder: Builder<T>, logger: Option<logging::TrackerLogger>) -> Built<T> {
+        let compiled = Compiled::compile(&builder);
+        Self::assemble(builder, &compiled, logger)
+    }
+
+    // Allocates a tracker for the graph of `builder`, from the summaries compiled for it.
+    fn assemble(builder: Builder<T>, compiled: &Compiled<T>, logger: Option<logging::TrackerLogger>) -> Built<T> {
 
         // Allocate buffer space for each input and input port.
         let mut per_operator =
@@ -500,31 +629,14 @@ impl<T:Timestamp> Tracker<T> {
             .map(|&(inputs, outputs)| PerOperator::new(inputs, outputs))
             .collect::<Vec<_>>();
 
-        // Summary of scope inputs to scope outputs.
-        let mut builder_summary = vec![vec![]; builder.shape[0].1];
-
-        // Compile summaries from each location to each scope output.
-        let output_summaries = summarize_outputs::<T>(&builder.nodes, &builder.edges);
-        for (location, summaries) in output_summaries.into_iter() {
-            // Summaries from scope inputs are useful in summarizing the scope.
-            if location.node == 0 {
-                if let Port::Source(port) = location.port {
-                    builder_summary[port] = summaries;
-                }
-                else {
-                    // Ignore (ideally trivial) output to output summaries.
-                }
-            }
-            // Summaries from internal nodes are important for projecting capabilities.
-            else {
-                match location.port {
-                    Port::Target(port) => {
-                        per_operator[location.node].targets[port].output_summaries = summaries;
-                    },
-                    Port::Source(port) => {
-                        per_operator[location.node].sources[port].output_summaries = summaries;
-                    },
-                }
+        for (location, summaries) in compiled.summaries.iter() {
+            match location.port {
+                Port::Target(port) => {
+                    per_operator[location.node].targets[port].output_summaries = summaries.clone();
+                },
+                Port::Source(port) => {
+                    per_operator[location.node].sources[port].output_summaries = summaries.clone();
+                },
             }
         }
 
@@ -545,7 +657,7 @@ impl<T:Timestamp> Tracker<T> {
             logger,
         };
 
-        (tracker, builder_summary)
+        (tracker, compiled.scope_summary.clone())
     }
 
     /// Propagates all pending updates.
@@ -622,6 +734,9 @@ impl<T:Timestamp> Tracker<T> {
             }
         }
 
+        // Changes to the frontiers at targets, recorded only if logging is enabled.
+        let mut frontier_changes = Vec::new();
+
         // Step 2: Circulate implications of changes to `self.pointstamps`.
         //
         // TODO: The argument that this always terminates is subtle, and should be made.
@@ -659,6 +774,9 @@ impl<T:Timestamp> Tracker<T> {
                                     }
                                 }
                             }
+                            if self.logger.is_some() {
+                                frontier_changes.push((location.node, port_index, time.clone(), diff));
+                            }
                             self.pushed_changes.update((location, time), diff);
                         }
                     }
@@ -686,6 +804,12 @@ impl<T:Timestamp> Tracker<T> {
                 };
             }
         }
+
+        if let Some(logger) = &mut self.logger {
+            if !frontier_changes.is_empty() {
+                logger.log_frontier_updates(Box::new(frontier_changes));
+            }
+        }
     }
 
     /// Implications of maintained capabilities projected to each output.
@@ -717,6 +841,38 @@ impl<T:Timestamp> Tracker<T> {
     }
 }
 
+// The most distinct summaries retained for sharing by an `Interner`.
+const INTERNED_LIMIT: usize = 64;
+
+/// Shares identical summaries to scope outputs among locations.
+///
+/// Most locations of large graphs have one of few distinct summaries, for example the default
+/// summary to each output along chains of operators, and sharing them saves an allocation per port.
+/// Summaries are compared by equality, and so only a bounded number of distinct summaries are
+/// retained for comparison.
+struct Interner<S> {
+    interned: Vec<Rc<Vec<Antichain<S>>>>,
+}
+
+impl<S> Default for Interner<S> {
+    fn default() -> Self {
+        Interner { interned: Vec::new() }
+    }
+}
+
+impl<S: PartialEq> Interner<S> {
+    fn intern(&mut self, summaries: Vec<Antichain<S>>) -> Rc<Vec<Antichain<S>>> {
+        if let Some(interned) = self.interned.iter().find(|interned| ***interned == summaries) {
+            return interned.clone();
+        }
+        let summaries = Rc::new(summaries);
+        if self.interned.len() < INTERNED_LIMIT {
+            self.interned.push(summaries.clone());
+        }
+        summaries
+    }
+}
+
 /// Determines summaries from locations to scope outputs.
 ///
 /// Specifically, for each location whose node identifier is non-zero, we compile
@@ -843,6 +999,15 @@ pub mod logging {
                 }
             })
         }
+        /// Log frontier update events with additional identifying information.
+        pub fn log_frontier_updates(&mut self, updates: Box<dyn ProgressEventTimestampVec>) {
+            self.logger.log({
+                FrontierUpdate {
+                    tracker_id: self.path.clone(),
+                    updates,
+                }
+            })
+        }
     }
 
     /// Events that the tracker may record.
@@ -851,6 +1016,8 @@ pub mod logging {
         SourceUpdate(SourceUpdate),
         /// Updates made at a target of data.
         TargetUpdate(TargetUpdate),
+        /// Changes to the frontier at a target of data.
+        FrontierUpdate(FrontierUpdate),
     }
 
     /// An update made at a source of data.
@@ -869,6 +1036,14 @@ pub mod logging {
         pub updates: Box<dyn ProgressEventTimestampVec>,
     }
 
+    /// Changes to the frontier at a target of data, resulting from propagated updates.
+    pub struct FrontierUpdate {
+        /// An identifier for the tracker.
+        pub tracker_id: Vec<usize>,
+        /// Frontier changes themselves, as `(node, port, time, diff)`.
+        pub updates: Box<dyn ProgressEventTimestampVec>,
+    }
+
     impl From<SourceUpdate> for TrackerEvent {
         fn from(v: SourceUpdate) -> TrackerEvent { TrackerEvent::SourceUpdate(v) }
     }
@@ -876,4 +1051,8 @@ pub mod logging {
     impl From<TargetUpdate> for TrackerEvent {
         fn from(v: TargetUpdate) -> TrackerEvent { TrackerEvent::TargetUpdate(v) }
     }
+
+    impl From<FrontierUpdate> for TrackerEvent {
+        fn from(v: FrontierUpdate) -> TrackerEvent { TrackerEvent::FrontierUpdate(v) }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,74 @@
+//! Snapshots of the progress of a dataflow's operators, for readers outside the dataflow.
+//!
+//! [`Worker::progress_snapshot`](crate::worker::Worker::progress_snapshot) describes where each
+//! operator of a dataflow stands: the frontier at each of its inputs and outputs, the times of
+//! messages in flight to its inputs, and the times of capabilities held at its outputs. Control
+//! planes such as autoscalers and schedulers may poll it between worker steps, rather than parse
+//! the progress logging streams.
+//!
+//! The snapshot reflects this worker's view of the dataflow's progress, which includes the
+//! progress updates of other workers it has received. Times appear in their `Debug`
+//! representations, so that snapshots of dataflows with different timestamp types have one type.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Map, Probe};
+//!
+//! timely::execute_directly(|worker| {
+//!     let mut input = InputHandle::new();
+//!     let dataflow = worker.next_dataflow_index();
+//!     let probe = worker.dataflow::<u64,_,_>(|scope| {
+//!         scope.input_from(&mut input).map(|x: u64| x + 1).probe()
+//!     });
+//!     input.advance_to(3);
+//!     worker.step_while(|| probe.less_than(&3));
+//!
+//!     let snapshot = worker.progress_snapshot(dataflow).unwrap();
+//!     let map = snapshot.operators.iter().find(|operator| operator.name == "Map").unwrap();
+//!     assert_eq!(map.inputs[0].frontier, vec!["3".to_string()]);
+//!     assert!(map.inputs[0].messages.is_empty());
+//! });
+//! ```
+
+/// The progress of the operators of a dataflow, as seen by one worker.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct ProgressSnapshot {
+    /// The identifier of the dataflow.
+    pub dataflow: usize,
+    /// The name of the dataflow.
+    pub name: String,
+    /// The operators of the dataflow, including those of nested scopes, each following its scope.
+    pub operators: Vec<OperatorProgress>,
+}
+
+/// The progress of one operator.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct OperatorProgress {
+    /// Sequence of nested scope identifiers indicating the path from the root to this operator.
+    pub addr: Vec<usize>,
+    /// A helpful name.
+    pub name: String,
+    /// The progress at each input.
+    pub inputs: Vec<InputProgress>,
+    /// The progress at each output.
+    pub outputs: Vec<OutputProgress>,
+}
+
+/// The progress at an operator input.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct InputProgress {
+    /// The frontier of times the input may yet receive.
+    pub frontier: Vec<String>,
+    /// The times of messages in flight to the input, from all workers.
+    pub messages: Vec<String>,
+}
+
+/// The progress at an operator output.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct OutputProgress {
+    /// The frontier of times the output may yet produce, accounting for upstream operators.
+    pub frontier: Vec<String>,
+    /// The times of capabilities held at the output, by all workers.
+    pub capabilities: Vec<String>,
+}
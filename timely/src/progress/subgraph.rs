@@ -9,12 +9,22 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use std::collections::BinaryHeap;
 use std::cmp::Reverse;
+use std::sync::Arc;
+use crate::logging_core::clock::Clock;
 
 use crate::logging::TimelyLogger as Logger;
 use crate::logging::TimelyProgressLogger as ProgressLogger;
+use crate::logging::{ActivationCause, MetricsLogger, ScheduleMetricsEvent, StallLogger, StallEvent, StalledOperator};
 
 use crate::scheduling::Schedule;
 use crate::scheduling::activate::Activations;
+use crate::scheduling::metrics::OperatorMetrics;
+use crate::scheduling::hooks::{Activity, Hooks};
+use crate::scheduling::watchdog::ScopeWatch;
+use crate::progress::snapshot::{OperatorProgress, InputProgress, OutputProgress};
+use crate::dataflow::channels::stats::ChannelCounter;
+use crate::dataflow::channels::lineage::Tracer;
+use crate::dataflow::operators::generic::{OperatorInfo, PortInfo};
 
 use crate::progress::frontier::{Antichain, MutableAntichain, MutableAntichainFilter};
 use crate::progress::{Timestamp, Operate, operate::SharedProgress};
@@ -23,6 +33,7 @@ use crate::progress::{Location, Port, Source, Target};
 use crate::progress::ChangeBatch;
 use crate::progress::broadcast::Progcaster;
 use crate::progress::reachability;
+use crate::progress::validation::{self, GraphError};
 use crate::progress::timestamp::Refines;
 
 use crate::worker::ProgressMode;
@@ -56,6 +67,9 @@ where
 
     edge_stash: Vec<(Source, Target)>,
 
+    // mistakes found in the graphs of nested scopes, as they were built.
+    errors: Vec<GraphError>,
+
     // shared state written to by the datapath, counting records entering this subgraph instance.
     input_messages: Vec<Rc<RefCell<ChangeBatch<TInner>>>>,
 
@@ -67,6 +81,9 @@ where
 
     /// Progress logging handle
     progress_logging: Option<ProgressLogger>,
+
+    // path summaries shared with builders of identical subgraphs.
+    summary_cache: Option<Rc<RefCell<reachability::SummaryCache<TInner>>>>,
 }
 
 impl<TOuter, TInner> SubgraphBuilder<TOuter, TInner>
@@ -116,13 +133,21 @@ where
             children,
             child_count: 1,
             edge_stash: Vec::new(),
+            errors: Vec::new(),
             input_messages: Vec::new(),
             output_capabilities: Vec::new(),
             logging,
             progress_logging,
+            summary_cache: None,
         }
     }
 
+    /// Compiles the path summaries of the subgraph with `cache`, reusing those of a previously
+    /// built subgraph with an identical graph.
+    pub fn share_summaries(&mut self, cache: Rc<RefCell<reachability::SummaryCache<TInner>>>) {
+        self.summary_cache = Some(cache);
+    }
+
     /// Allocates a new child identifier, for later use.
     pub fn allocate_child_id(&mut self) -> usize {
         self.child_count += 1;
@@ -143,7 +168,45 @@ where
         self.children.push(PerOperatorState::new(child, index, self.path.clone(), identifier, self.logging.clone()))
     }
 
+    /// Checks the graph of the subgraph, and reports the first mistake found in it or in the
+    /// graphs of its nested scopes.
+    ///
+    /// See the [`validation`](crate::progress::validation) module for the mistakes checked.
+    pub fn validate(&self) -> Result<(), GraphError> {
+        if let Some(error) = self.errors.first() {
+            return Err(error.clone());
+        }
+        let count = self.children.iter().map(|child| child.index + 1).max().unwrap_or(0).max(self.child_count);
+        let mut nodes = (0 .. count).map(|_| None).collect::<Vec<_>>();
+        // child zero represents the subgraph, whose inputs are sources and outputs are targets.
+        nodes[0] = Some(validation::Node {
+            name: self.name.clone(),
+            inputs: self.output_capabilities.len(),
+            outputs: self.input_messages.len(),
+            unadvanced: Vec::new(),
+        });
+        for child in self.children.iter().filter(|child| child.index > 0) {
+            let unadvanced = child.internal_summary.iter().map(|outputs| {
+                outputs.iter()
+                       .enumerate()
+                       .filter(|(_, summaries)| summaries.elements().iter().any(|summary| summary == &Default::default()))
+                       .map(|(output, _)| output)
+                       .collect()
+            }).collect();
+            nodes[child.index] = Some(validation::Node { name: child.name.clone(), inputs: child.inputs, outputs: child.outputs, unadvanced });
+        }
+        validation::check(&(self.name.clone(), self.path.clone()), &nodes, &self.edge_stash)
+    }
+
+    /// Records a mistake found in the graph of a nested scope, to be reported by `validate`.
+    pub fn record_error(&mut self, error: GraphError) {
+        self.errors.push(error);
+    }
+
     /// Now that initialization is complete, actually build a subgraph.
+    ///
+    /// Connections naming operators or ports that do not exist are reported by `validate`, and
+    /// are discarded here.
     pub fn build<A: crate::worker::AsWorker>(mut self, worker: &mut A) -> Subgraph<TOuter, TInner> {
         // at this point, the subgraph is frozen. we should initialize any internal state which
         // may have been determined after construction (e.g. the numbers of inputs and outputs).
@@ -152,8 +215,56 @@ where
 
         // perhaps first check that the children are sanely identified
         self.children.sort_by(|x,y| x.index.cmp(&y.index));
+        // operators allocated but never built, reported by `validate`, are left as empty placeholders.
+        for index in 0 .. self.child_count {
+            if self.children.get(index).map(|child| child.index != index).unwrap_or(true) {
+                let mut placeholder = PerOperatorState::empty(0, 0);
+                placeholder.index = index;
+                self.children.insert(index, placeholder);
+            }
+        }
         assert!(self.children.iter().enumerate().all(|(i,x)| i == x.index));
 
+        // retain names by address, for diagnostics, and measure the scheduling of children.
+        {
+            let mut topology = worker.topology();
+            let metrics = if worker.config().measures_operators() { Some(worker.operator_metrics()) } else { None };
+            let metrics_logging = worker.log_register().get::<ScheduleMetricsEvent>("timely/metrics");
+            topology.name_operator(self.path.clone(), self.name.clone());
+            // child zero represents the subgraph itself, and is named by its parent.
+            for child in self.children.iter_mut().skip(1) {
+                let mut child_path = self.path.clone();
+                child_path.push(child.index);
+                topology.name_operator(child_path.clone(), child.name.clone());
+                // operators built with `OperatorBuilder` describe themselves in more detail.
+                topology.describe_operator(OperatorInfo {
+                    name: child.name.clone(),
+                    inputs: vec![PortInfo::unknown(); child.inputs],
+                    outputs: vec![PortInfo::unknown(); child.outputs],
+                    ..OperatorInfo::new(child.index, child.id, &child_path)
+                });
+                child.addr = child_path.clone();
+                child.metrics = metrics.as_ref().map(|metrics| metrics.register(child.id, child_path, child.name.clone(), child.inputs, child.outputs));
+                child.metrics_logging = metrics_logging.clone();
+                child.clock = Some(worker.clock());
+                child.hooks = Some(worker.schedule_hooks());
+                child.lineage = worker.extensions().lineage();
+                // the inputs of scopes are received by their own children, and are checked there.
+                if worker.config().validate_progress && child.local {
+                    let stats = worker.channel_stats();
+                    let index = child.index;
+                    let path = &self.path;
+                    child.received = Some((0 .. child.inputs).map(|input| {
+                        topology
+                            .channels_to(path, (index, input))
+                            .into_iter()
+                            .map(|channel| stats.counter(channel))
+                            .collect()
+                    }).collect());
+                }
+            }
+        }
+
         let inputs = self.input_messages.len();
         let outputs = self.output_capabilities.len();
 
@@ -168,7 +279,13 @@ where
             builder.add_node(index, child.inputs, child.outputs, child.internal_summary.clone());
         }
 
-        for (source, target) in self.edge_stash {
+        let children = &self.children;
+        let valid = |(source, target): &(Source, Target)| {
+            children.get(source.node).map(|child| source.port < child.outputs).unwrap_or(false) &&
+            children.get(target.node).map(|child| target.port < child.inputs).unwrap_or(false)
+        };
+        let edges = self.edge_stash.into_iter().filter(valid).collect::<Vec<_>>();
+        for (source, target) in edges {
             self.children[source.node].edges[source.port].push(target);
             builder.add_edge(source, target);
         }
@@ -179,7 +296,10 @@ where
         worker.log_register()
             .get::<reachability::logging::TrackerEvent>("timely/reachability")
             .map(|logger| reachability::logging::TrackerLogger::new(path, logger));
-        let (tracker, scope_summary) = builder.build(reachability_logging);
+        let (tracker, scope_summary) = match &self.summary_cache {
+            Some(cache) => builder.build_cached(reachability_logging, &mut cache.borrow_mut()),
+            None => builder.build(reachability_logging),
+        };
 
         let progcaster = Progcaster::new(worker, &self.path, self.logging.clone(), self.progress_logging.clone());
 
@@ -191,6 +311,11 @@ where
 
         activations.borrow_mut().activate(&self.path[..]);
 
+        let watchdog = match (worker.config().watchdog, worker.log_register().get::<StallEvent>("timely/stalls")) {
+            (Some(_), Some(logger)) => Some((worker.watchdog().register(self.path.clone()), logger)),
+            _ => None,
+        };
+
         Subgraph {
             name: self.name,
             path: self.path,
@@ -200,6 +325,7 @@ where
             incomplete_count,
             activations,
             temp_active: BinaryHeap::new(),
+            causes: vec![ActivationCause::default(); self.children.len()],
             children: self.children,
             input_messages: self.input_messages,
             output_capabilities: self.output_capabilities,
@@ -213,6 +339,7 @@ where
             scope_summary,
 
             progress_mode: worker.config().progress_mode,
+            watchdog,
         }
     }
 }
@@ -242,6 +369,8 @@ where
     // shared activations (including children).
     activations: Rc<RefCell<Activations>>,
     temp_active: BinaryHeap<Reverse<usize>>,
+    // the reasons each child is to be scheduled.
+    causes: Vec<ActivationCause>,
 
     // shared state written to by the datapath, counting records entering this subgraph instance.
     input_messages: Vec<Rc<RefCell<ChangeBatch<TInner>>>>,
@@ -264,6 +393,9 @@ where
     scope_summary: Vec<Vec<Antichain<TInner::Summary>>>,
 
     progress_mode: ProgressMode,
+
+    // when the frontiers of the scope last changed, and where to report stalls.
+    watchdog: Option<(Rc<RefCell<ScopeWatch>>, StallLogger)>,
 }
 
 impl<TOuter, TInner> Schedule for Subgraph<TOuter, TInner>
@@ -282,20 +414,39 @@ where
         // into atomic actions that should be able to be safely executed in
         // isolation, by a potentially clueless user (yours truly).
 
-        self.accept_frontier();         // Accept supplied frontier changes.
-        self.harvest_inputs();          // Count records entering the scope.
-
         // Receive post-exchange progress updates.
         self.progcaster.recv(&mut self.final_pointstamp);
 
+        // Skip a quiescent subgraph, whose scheduling would change nothing.
+        if self.is_quiescent() {
+            return self.incomplete_count > 0 || self.pointstamp_tracker.tracking_anything();
+        }
+
+        self.accept_frontier();         // Accept supplied frontier changes.
+        self.harvest_inputs();          // Count records entering the scope.
+
         // Commit and propagate final pointstamps.
         self.propagate_pointstamps();
 
+        // Report a stall, if the watchdog asked.
+        if let Some((watch, logger)) = self.watchdog.as_ref() {
+            if let Some(stalled_for) = watch.borrow_mut().take_request() {
+                let operators = self.stalled_operators();
+                if !operators.is_empty() {
+                    logger.log(StallEvent { addr: self.path.clone(), name: self.name.clone(), stalled_for, operators });
+                }
+            }
+        }
+
         {   // Enqueue active children; scoped to let borrow drop.
             let temp_active = &mut self.temp_active;
+            let causes = &mut self.causes;
             self.activations
                 .borrow_mut()
-                .for_extensions(&self.path[..], |index| temp_active.push(Reverse(index)));
+                .for_extensions(&self.path[..], |index| {
+                    temp_active.push(Reverse(index));
+                    if let Some(cause) = causes.get_mut(index) { cause.activated = true; }
+                });
         }
 
         // Schedule child operators.
@@ -326,6 +477,24 @@ where
 
         incomplete || tracking
     }
+
+    fn progress_snapshot(&self, operators: &mut Vec<OperatorProgress>) {
+        let times = |antichain: &MutableAntichain<TInner>| antichain.frontier().iter().map(|time| format!("{:?}", time)).collect::<Vec<_>>();
+        for child in self.children.iter().skip(1) {
+            let state = self.pointstamp_tracker.node_state(child.index);
+            let mut addr = self.path.clone();
+            addr.push(child.index);
+            operators.push(OperatorProgress {
+                addr,
+                name: child.name.clone(),
+                inputs: state.targets.iter().map(|target| InputProgress { frontier: times(&target.implications), messages: times(&target.pointstamps) }).collect(),
+                outputs: state.sources.iter().map(|source| OutputProgress { frontier: times(&source.implications), capabilities: times(&source.pointstamps) }).collect(),
+            });
+            if let Some(operator) = child.operator.as_ref() {
+                operator.progress_snapshot(operators);
+            }
+        }
+    }
 }
 
 
@@ -334,6 +503,24 @@ where
     TOuter: Timestamp,
     TInner: Timestamp+Refines<TOuter>,
 {
+    /// Indicates that scheduling the subgraph would change nothing.
+    ///
+    /// A subgraph is quiescent if no child is active, no progress updates are pending, whether
+    /// from the parent scope, from peers, or from children, none are held back from peers, and no
+    /// records have entered it since it was last scheduled. Its children then have no work, and
+    /// its progress state is as it was.
+    fn is_quiescent(&mut self) -> bool {
+        let mut active = false;
+        self.activations.borrow().for_extensions(&self.path[..], |_| active = true);
+        let pending_progress =
+            !self.final_pointstamp.is_empty() ||
+            self.progcaster.is_holding() ||
+            self.shared_progress.borrow_mut().frontiers.iter_mut().any(|changes| !changes.is_empty());
+        let pending_records = self.input_messages.iter().any(|messages| !messages.borrow_mut().is_empty());
+        let stall_requested = self.watchdog.as_ref().map(|(watch, _)| watch.borrow().is_requested()).unwrap_or(false);
+        !active && !pending_progress && !pending_records && !stall_requested
+    }
+
     /// Schedules a child operator and collects progress statements.
     ///
     /// The return value indicates that the child task cannot yet shut down.
@@ -341,7 +528,14 @@ where
 
         let child = &mut self.children[child_index];
 
-        let incomplete = child.schedule();
+        // A declined child keeps its cause, and is activated again for the next step.
+        if child.operator.is_some() && !child.hooks.as_ref().map(|hooks| hooks.before(&child.addr)).unwrap_or(true) {
+            self.activations.borrow_mut().activate(&child.addr[..]);
+            return self.incomplete[child_index];
+        }
+
+        let cause = std::mem::take(&mut self.causes[child_index]);
+        let incomplete = child.schedule(cause);
 
         if incomplete != self.incomplete[child_index] {
             if incomplete { self.incomplete_count += 1; }
@@ -359,18 +553,19 @@ where
             }
         }
         else {
-            // In debug mode, check that the progress statements do not violate invariants.
-            #[cfg(debug_assertions)] {
-                child.validate_progress(self.pointstamp_tracker.node_state(child_index));
+            // In debug mode, or if configured, check that the progress statements do not violate invariants.
+            if cfg!(debug_assertions) || child.received.is_some() {
+                child.validate_progress(&self.path, self.pointstamp_tracker.node_state(child_index));
             }
         }
+        child.validate_consumed(&self.path);
 
         // Extract progress statements into either pre- or post-exchange buffers.
         if child.local {
-            child.extract_progress(&mut self.local_pointstamp, &mut self.temp_active);
+            child.extract_progress(&mut self.local_pointstamp, &mut self.temp_active, &mut self.causes);
         }
         else {
-            child.extract_progress(&mut self.final_pointstamp, &mut self.temp_active);
+            child.extract_progress(&mut self.final_pointstamp, &mut self.temp_active, &mut self.causes);
         }
 
         incomplete
@@ -459,12 +654,19 @@ where
         // Propagate implications of progress changes.
         self.pointstamp_tracker.propagate_all();
 
+        if let Some((watch, _)) = self.watchdog.as_ref() {
+            if !self.pointstamp_tracker.pushed().is_empty() || self.pointstamp_tracker.pushed_output().iter_mut().any(|changes| !changes.is_empty()) {
+                watch.borrow_mut().advanced();
+            }
+        }
+
         // Drain propagated information into shared progress structure.
         for ((location, time), diff) in self.pointstamp_tracker.pushed().drain() {
             // Targets are actionable, sources are not.
             if let crate::progress::Port::Target(port) = location.port {
                 if self.children[location.node].notify {
                     self.temp_active.push(Reverse(location.node));
+                    self.causes[location.node].frontier = true;
                 }
                 // TODO: This logic could also be guarded by `.notify`, but
                 // we want to be a bit careful to make sure all related logic
@@ -488,6 +690,29 @@ where
         }
     }
 
+    /// Describes the children holding capabilities or with messages in flight to them.
+    fn stalled_operators(&self) -> Vec<StalledOperator> {
+        let times = |antichain: &MutableAntichain<TInner>| antichain.frontier().iter().map(|time| format!("{:?}", time)).collect::<Vec<_>>();
+        let mut operators = Vec::new();
+        for child in self.children.iter().skip(1) {
+            let state = self.pointstamp_tracker.node_state(child.index);
+            let messages = state.targets.iter().map(|target| times(&target.pointstamps)).collect::<Vec<_>>();
+            let capabilities = state.sources.iter().map(|source| times(&source.pointstamps)).collect::<Vec<_>>();
+            if messages.iter().chain(capabilities.iter()).any(|times| !times.is_empty()) {
+                let mut addr = self.path.clone();
+                addr.push(child.index);
+                operators.push(StalledOperator {
+                    addr,
+                    name: child.name.clone(),
+                    frontiers: state.targets.iter().map(|target| times(&target.implications)).collect(),
+                    messages,
+                    capabilities,
+                });
+            }
+        }
+        operators
+    }
+
     /// Sends local progress updates to all workers.
     ///
     /// This method does not guarantee that all of `self.local_pointstamps` are
@@ -507,7 +732,7 @@ where
         };
 
         if must_send {
-            self.progcaster.send(&mut self.local_pointstamp);
+            self.progcaster.offer(&mut self.local_pointstamp);
         }
     }
 }
@@ -543,7 +768,7 @@ where
         // We introduce these into the progress tracker to determine the scope's initial
         // internal capabilities.
         for child in self.children.iter_mut() {
-            child.extract_progress(&mut self.final_pointstamp, &mut self.temp_active);
+            child.extract_progress(&mut self.final_pointstamp, &mut self.temp_active, &mut self.causes);
         }
 
         self.propagate_pointstamps();  // Propagate expressed capabilities to output frontiers.
@@ -555,10 +780,15 @@ where
     fn set_external_summary(&mut self) {
         self.accept_frontier();
         self.propagate_pointstamps();  // ensure propagation of input frontiers.
-        self.children
-            .iter_mut()
-            .flat_map(|child| child.operator.as_mut())
-            .for_each(|op| op.set_external_summary());
+        for child in self.children.iter_mut() {
+            if let Some(operator) = child.operator.as_mut() {
+                // Operators may be scheduled as they learn their summaries.
+                let id = child.id;
+                let scheduled = child.lineage.as_ref().map(|tracer| tracer.enter(id));
+                operator.set_external_summary();
+                if let Some(scheduled) = scheduled { scheduled.exit(); }
+            }
+        }
     }
 }
 
@@ -582,6 +812,16 @@ struct PerOperatorState<T: Timestamp> {
     internal_summary: Vec<Vec<Antichain<T::Summary>>>,   // cached result from get_internal_summary.
 
     logging: Option<Logger>,
+
+    metrics: Option<Rc<RefCell<OperatorMetrics>>>,  // accumulated measurements of scheduling.
+    metrics_logging: Option<MetricsLogger>,
+    clock: Option<Arc<dyn Clock>>,                  // the clock by which schedulings are measured.
+
+    addr: Vec<usize>,                               // the address of the operator.
+    hooks: Option<Hooks>,                           // called around each scheduling.
+
+    received: Option<Vec<Vec<ChannelCounter>>>,     // counts of the channels into each input, if validating.
+    lineage: Option<Tracer>,                        // the lineage state of the worker, if traced.
 }
 
 impl<T: Timestamp> PerOperatorState<T> {
@@ -601,6 +841,16 @@ impl<T: Timestamp> PerOperatorState<T> {
 
             logging: None,
 
+            metrics: None,
+            metrics_logging: None,
+            clock: None,
+
+            addr: Vec::new(),
+            hooks: None,
+
+            received: None,
+            lineage: None,
+
             shared_progress: Rc::new(RefCell::new(SharedProgress::new(inputs,outputs))),
             internal_summary: Vec::new(),
         }
@@ -637,12 +887,22 @@ impl<T: Timestamp> PerOperatorState<T> {
 
             logging,
 
+            metrics: None,
+            metrics_logging: None,
+            clock: None,
+
+            addr: Vec::new(),
+            hooks: None,
+
+            received: None,
+            lineage: None,
+
             shared_progress,
             internal_summary,
         }
     }
 
-    pub fn schedule(&mut self) -> bool {
+    pub fn schedule(&mut self, cause: ActivationCause) -> bool {
 
         if let Some(ref mut operator) = self.operator {
 
@@ -659,13 +919,39 @@ impl<T: Timestamp> PerOperatorState<T> {
                 l.log(crate::logging::ScheduleEvent::start(self.id));
             }
 
+            // Measure the scheduling only if some consumer wants the measurements.
+            let hooked = self.hooks.as_ref().map(|hooks| hooks.is_set()).unwrap_or(false);
+            let measured = self.metrics.is_some() || self.metrics_logging.is_some() || hooked;
+            let start = self.clock.as_ref().filter(|_| measured).map(|clock| clock.now());
+            let id = self.id;
+            let scheduled = self.lineage.as_ref().map(|tracer| tracer.enter(id));
             let incomplete = operator.schedule();
+            if let Some(scheduled) = scheduled { scheduled.exit(); }
+            let elapsed = start.and_then(|start| self.clock.as_ref().map(|clock| clock.elapsed(start)));
 
             // Perhaps log information about the stop of the schedule call.
             if let Some(l) = self.logging.as_mut() {
                 l.log(crate::logging::ScheduleEvent::stop(self.id));
             }
 
+            // Measure the records moved, before `extract_progress` drains their counts.
+            if let Some(elapsed) = elapsed {
+                let shared_progress = &mut *self.shared_progress.borrow_mut();
+                let count = |batch: &mut ChangeBatch<T>| batch.iter().map(|(_, diff)| *diff).sum::<i64>();
+                let consumed = shared_progress.consumeds.iter_mut().map(count).collect::<Vec<_>>();
+                let produced = shared_progress.produceds.iter_mut().map(count).collect::<Vec<_>>();
+                if let Some(metrics) = self.metrics.as_ref() {
+                    metrics.borrow_mut().record(elapsed, &consumed, &produced, cause);
+                }
+                if let (true, Some(hooks)) = (hooked, self.hooks.as_ref()) {
+                    let activity = Activity { id: self.id, elapsed, consumed: consumed.clone(), produced: produced.clone(), cause, incomplete };
+                    hooks.after(&self.addr, &activity);
+                }
+                if let Some(l) = self.metrics_logging.as_mut() {
+                    l.log(ScheduleMetricsEvent { id: self.id, elapsed, consumed, produced, cause });
+                }
+            }
+
             incomplete
         }
         else {
@@ -693,7 +979,7 @@ impl<T: Timestamp> PerOperatorState<T> {
     }
 
     /// Extracts shared progress information and converts to pointstamp changes.
-    fn extract_progress(&mut self, pointstamps: &mut ChangeBatch<(Location, T)>, temp_active: &mut BinaryHeap<Reverse<usize>>) {
+    fn extract_progress(&mut self, pointstamps: &mut ChangeBatch<(Location, T)>, temp_active: &mut BinaryHeap<Reverse<usize>>, causes: &mut [ActivationCause]) {
 
         let shared_progress = &mut *self.shared_progress.borrow_mut();
 
@@ -715,6 +1001,7 @@ impl<T: Timestamp> PerOperatorState<T> {
                 for target in &self.edges[output] {
                     pointstamps.update((Location::from(*target), time.clone()), delta);
                     temp_active.push(Reverse(target.node));
+                    causes[target.node].messages = true;
                 }
             }
         }
@@ -724,20 +1011,21 @@ impl<T: Timestamp> PerOperatorState<T> {
     ///
     /// The validity of shared progress information depends on both the external frontiers and the
     /// internal capabilities, as events can occur that cannot be explained locally otherwise.
-    #[allow(dead_code)]
-    fn validate_progress(&mut self, child_state: &reachability::PerOperator<T>) {
+    fn validate_progress(&mut self, path: &[usize], child_state: &reachability::PerOperator<T>) {
 
         let shared_progress = &mut *self.shared_progress.borrow_mut();
 
-        // Increments to internal capabilities require a consumed input message, a
+        // Increments to internal capabilities require a consumed input message, or a held capability.
         for (output, internal) in shared_progress.internals.iter_mut().enumerate() {
             for (time, diff) in internal.iter() {
                 if *diff > 0 {
                     let consumed = shared_progress.consumeds.iter_mut().any(|x| x.iter().any(|(t,d)| *d > 0 && t.less_equal(time)));
                     let internal = child_state.sources[output].implications.less_equal(time);
                     if !consumed && !internal {
-                        println!("Increment at {:?}, not supported by\n\tconsumed: {:?}\n\tinternal: {:?}", time, shared_progress.consumeds, child_state.sources[output].implications);
-                        panic!("Progress error; internal {:?}", self.name);
+                        panic!(
+                            "Progress error at operator {:?} {:?}: capability acquired at {:?} on output {}, not supported by\n\tconsumed: {:?}\n\tinternal: {:?}",
+                            self.name, (path, self.index), time, output, shared_progress.consumeds, child_state.sources[output].implications,
+                        );
                     }
                 }
             }
@@ -748,13 +1036,33 @@ impl<T: Timestamp> PerOperatorState<T> {
                     let consumed = shared_progress.consumeds.iter_mut().any(|x| x.iter().any(|(t,d)| *d > 0 && t.less_equal(time)));
                     let internal = child_state.sources[output].implications.less_equal(time);
                     if !consumed && !internal {
-                        println!("Increment at {:?}, not supported by\n\tconsumed: {:?}\n\tinternal: {:?}", time, shared_progress.consumeds, child_state.sources[output].implications);
-                        panic!("Progress error; produced {:?}", self.name);
+                        panic!(
+                            "Progress error at operator {:?} {:?}: records produced at {:?} on output {}, not supported by\n\tconsumed: {:?}\n\tinternal: {:?}",
+                            self.name, (path, self.index), time, output, shared_progress.consumeds, child_state.sources[output].implications,
+                        );
                     }
                 }
             }
         }
     }
+
+    /// Tests that the records reported consumed at each input are those received, if validating.
+    ///
+    /// Both are totals over all schedulings, and must agree once the operator returns.
+    fn validate_consumed(&self, path: &[usize]) {
+        if let (Some(received), Some(metrics)) = (self.received.as_ref(), self.metrics.as_ref()) {
+            let metrics = metrics.borrow();
+            for (input, channels) in received.iter().enumerate() {
+                let received = channels.iter().map(|channel| channel.pulled_records()).sum::<u64>() as i64;
+                if !channels.is_empty() && metrics.consumed[input] != received {
+                    panic!(
+                        "Progress error at operator {:?} {:?}: {} records reported consumed at input {}, but {} received",
+                        self.name, (path, self.index), metrics.consumed[input], input, received,
+                    );
+                }
+            }
+        }
+    }
 }
 
 // Explicitly shut down the operator to get logged information.
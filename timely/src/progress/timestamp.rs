@@ -98,6 +98,86 @@ impl PathSummary<::std::time::Duration> for ::std::time::Duration {
     fn followed_by(&self, other: &::std::time::Duration) -> Option<::std::time::Duration> { self.checked_add(*other) }
 }
 
+/// A wall-clock time, as milliseconds since the Unix epoch.
+///
+/// Paths are summarized by a `Duration`, of which any fraction of a millisecond is rounded up to
+/// a whole millisecond, so that every non-zero summary advances times.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use timely::dataflow::InputHandle;
+/// use timely::dataflow::operators::{Input, Inspect, Probe};
+/// use timely::progress::timestamp::EpochMillis;
+///
+/// timely::execute_directly(|worker| {
+///     let mut input = InputHandle::new();
+///     let probe = worker.dataflow(|scope| {
+///         scope.input_from(&mut input)
+///              .inspect_batch(|time: &EpochMillis, data: &[&str]| println!("{:?}: {:?}", time.to_system_time(), data))
+///              .probe()
+///     });
+///     input.advance_to(EpochMillis::now());
+///     input.send("hello");
+///     let later = input.time().checked_add(Duration::from_secs(1)).unwrap();
+///     input.advance_to(later);
+///     worker.step_while(|| probe.less_than(&later));
+///     assert_eq!(later.duration_since(EpochMillis(0)), Some(Duration::from_millis(later.0)));
+/// });
+/// ```
+#[derive(Copy, Clone, Hash, Eq, PartialEq, Default, Ord, PartialOrd, Serialize, Deserialize, Debug)]
+pub struct EpochMillis(pub u64);
+
+// Plain data, which abomonation copies as bytes.
+impl ::abomonation::Abomonation for EpochMillis { }
+
+impl EpochMillis {
+    /// The current wall-clock time, or the epoch if the system clock precedes it.
+    pub fn now() -> Self {
+        Self::from_system_time(::std::time::SystemTime::now()).unwrap_or_default()
+    }
+    /// The time `time`, or `None` if it precedes the epoch or is not representable.
+    pub fn from_system_time(time: ::std::time::SystemTime) -> Option<Self> {
+        let since = time.duration_since(::std::time::UNIX_EPOCH).ok()?;
+        ::std::convert::TryFrom::try_from(since.as_millis()).ok().map(EpochMillis)
+    }
+    /// The time as a `SystemTime`.
+    pub fn to_system_time(&self) -> ::std::time::SystemTime {
+        ::std::time::UNIX_EPOCH + ::std::time::Duration::from_millis(self.0)
+    }
+    /// The time `duration` later, discarding fractions of a millisecond, or `None` on overflow.
+    pub fn checked_add(&self, duration: ::std::time::Duration) -> Option<Self> {
+        let millis: u64 = ::std::convert::TryFrom::try_from(duration.as_millis()).ok()?;
+        self.0.checked_add(millis).map(EpochMillis)
+    }
+    /// The time elapsed since `earlier`, or `None` if `earlier` is later.
+    pub fn duration_since(&self, earlier: EpochMillis) -> Option<::std::time::Duration> {
+        self.0.checked_sub(earlier.0).map(::std::time::Duration::from_millis)
+    }
+}
+
+impl Timestamp for EpochMillis {
+    type Summary = ::std::time::Duration;
+    fn minimum() -> Self { EpochMillis(0) }
+}
+impl PathSummary<EpochMillis> for ::std::time::Duration {
+    #[inline]
+    fn results_in(&self, src: &EpochMillis) -> Option<EpochMillis> {
+        src.0.checked_add(ceil_millis(self)?).map(EpochMillis)
+    }
+    #[inline]
+    fn followed_by(&self, other: &::std::time::Duration) -> Option<::std::time::Duration> {
+        ceil_millis(self)?.checked_add(ceil_millis(other)?).map(::std::time::Duration::from_millis)
+    }
+}
+
+/// The number of milliseconds in `duration`, rounded up, or `None` if not representable.
+fn ceil_millis(duration: &::std::time::Duration) -> Option<u64> {
+    let millis: u64 = ::std::convert::TryFrom::try_from(duration.as_millis()).ok()?;
+    if ::std::time::Duration::from_millis(millis) == *duration { Some(millis) } else { millis.checked_add(1) }
+}
+
 pub use self::refines::Refines;
 mod refines {
 
@@ -147,5 +227,162 @@ mod refines {
         )
     }
 
-    implement_refines_empty!(usize, u128, u64, u32, u16, u8, isize, i128, i64, i32, i16, i8, ::std::time::Duration,);
+    implement_refines_empty!(usize, u128, u64, u32, u16, u8, isize, i128, i64, i32, i16, i8, ::std::time::Duration, super::EpochMillis,);
+}
+
+/// Defines a timestamp type ordered lexicographically by its fields, each themselves timestamps.
+///
+/// The macro accepts a struct with named fields, followed by the declaration of a unit struct
+/// naming its summary type. It implements `PartialOrder`, `Timestamp`, and `PathSummary` for the
+/// summary type, whose fields are the summaries of the corresponding fields of the timestamp. The
+/// type refines both `()`, and the type of its first field, for use in a scope nested within
+/// one with that timestamp type. The first field must not be of type `()`.
+///
+/// The struct should derive the traits required of timestamps, among them `Clone`, `Debug`,
+/// `Eq`, `Hash`, and `Abomonation` (or `Serialize` and `Deserialize` with the `bincode` feature),
+/// and should derive `Ord` to agree with the lexicographic order.
+///
+/// A summary applies each of its fields to the corresponding field of a timestamp. Summaries are
+/// ordered field by field, and the summaries of fields should strictly advance any advanced time,
+/// as do those of integers, `Duration`, and their products; otherwise summaries may reorder times.
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use]
+/// extern crate abomonation_derive;
+/// extern crate abomonation;
+/// extern crate timely;
+///
+/// use timely::PartialOrder;
+/// use timely::dataflow::Scope;
+/// use timely::dataflow::operators::{ToStream, Concat, Enter, Leave, Feedback, ConnectLoop, Filter, Map, Capture};
+/// use timely::dataflow::operators::capture::Extract;
+///
+/// timely::timestamp! {
+///     /// A revision of a document, and an edit within it.
+///     #[derive(Abomonation, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///     pub struct Version {
+///         /// The revision of the document.
+///         pub revision: u64,
+///         /// The edit within the revision.
+///         pub edit: u32,
+///     }
+///     /// Summarizes paths between versions.
+///     pub struct VersionSummary;
+/// }
+///
+/// fn main() {
+///     assert!(Version { revision: 0, edit: 5 }.less_than(&Version { revision: 1, edit: 0 }));
+///
+///     // Each record is edited three times within its revision.
+///     let captured = timely::example(|scope| {
+///         let stream = (0 .. 2u64).to_stream(scope).map(|x| (x, 0));
+///         scope.scoped::<Version,_,_>("Edits", |inner| {
+///             let (handle, cycle) = inner.feedback(VersionSummary { revision: 0, edit: 1 });
+///             let edited = stream.enter(inner)
+///                                .concat(&cycle)
+///                                .map(|(x, edits)| (x, edits + 1));
+///             edited.filter(|(_, edits)| *edits < 3).connect_loop(handle);
+///             edited.filter(|(_, edits)| *edits == 3).leave()
+///         })
+///         .capture()
+///     });
+///
+///     assert_eq!(captured.extract(), vec![(0, vec![(0, 3), (1, 3)])]);
+/// }
+/// ```
+#[macro_export]
+macro_rules! timestamp {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(#[$field_attr_first:meta])*
+            $field_vis_first:vis $field_first:ident : $type_first:ty
+            $(, $(#[$field_attr:meta])* $field_vis:vis $field:ident : $type:ty)* $(,)?
+        }
+        $(#[$summary_attr:meta])*
+        $summary_vis:vis struct $summary:ident;
+    ) => {
+        $(#[$attr])*
+        $vis struct $name {
+            $(#[$field_attr_first])*
+            $field_vis_first $field_first: $type_first,
+            $($(#[$field_attr])* $field_vis $field: $type,)*
+        }
+
+        $(#[$summary_attr])*
+        #[derive(Clone, Debug, Default, PartialEq, Eq)]
+        $summary_vis struct $summary {
+            #[doc = concat!("Summarizes paths for the `", stringify!($field_first), "` field.")]
+            $field_vis_first $field_first: <$type_first as $crate::progress::Timestamp>::Summary,
+            $(
+                #[doc = concat!("Summarizes paths for the `", stringify!($field), "` field.")]
+                $field_vis $field: <$type as $crate::progress::Timestamp>::Summary,
+            )*
+        }
+
+        impl $crate::order::PartialOrder for $name {
+            fn less_equal(&self, other: &Self) -> bool {
+                if self.$field_first != other.$field_first {
+                    return $crate::order::PartialOrder::less_equal(&self.$field_first, &other.$field_first);
+                }
+                $(
+                    if self.$field != other.$field {
+                        return $crate::order::PartialOrder::less_equal(&self.$field, &other.$field);
+                    }
+                )*
+                true
+            }
+        }
+
+        impl $crate::progress::Timestamp for $name {
+            type Summary = $summary;
+            fn minimum() -> Self {
+                $name {
+                    $field_first: <$type_first as $crate::progress::Timestamp>::minimum(),
+                    $($field: <$type as $crate::progress::Timestamp>::minimum(),)*
+                }
+            }
+        }
+
+        impl $crate::order::PartialOrder for $summary {
+            fn less_equal(&self, other: &Self) -> bool {
+                $crate::order::PartialOrder::less_equal(&self.$field_first, &other.$field_first)
+                $(&& $crate::order::PartialOrder::less_equal(&self.$field, &other.$field))*
+            }
+        }
+
+        impl $crate::progress::PathSummary<$name> for $summary {
+            fn results_in(&self, src: &$name) -> Option<$name> {
+                Some($name {
+                    $field_first: $crate::progress::PathSummary::results_in(&self.$field_first, &src.$field_first)?,
+                    $($field: $crate::progress::PathSummary::results_in(&self.$field, &src.$field)?,)*
+                })
+            }
+            fn followed_by(&self, other: &Self) -> Option<Self> {
+                Some($summary {
+                    $field_first: $crate::progress::PathSummary::followed_by(&self.$field_first, &other.$field_first)?,
+                    $($field: $crate::progress::PathSummary::followed_by(&self.$field, &other.$field)?,)*
+                })
+            }
+        }
+
+        impl $crate::progress::timestamp::Refines<()> for $name {
+            fn to_inner(_: ()) -> Self { <$name as $crate::progress::Timestamp>::minimum() }
+            fn to_outer(self) { }
+            fn summarize(_: $summary) { }
+        }
+
+        impl $crate::progress::timestamp::Refines<$type_first> for $name {
+            fn to_inner(other: $type_first) -> Self {
+                $name {
+                    $field_first: other,
+                    $($field: <$type as $crate::progress::Timestamp>::minimum(),)*
+                }
+            }
+            fn to_outer(self) -> $type_first { self.$field_first }
+            fn summarize(path: $summary) -> <$type_first as $crate::progress::Timestamp>::Summary { path.$field_first }
+        }
+    };
 }
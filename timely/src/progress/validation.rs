@@ -0,0 +1,196 @@
+//! Checks of the graphs of scopes, made as dataflows are built.
+//!
+//! Some mistakes in the construction of a dataflow otherwise surface only as it runs: a cycle
+//! along which timestamps need not advance stalls progress tracking, an input connected to no
+//! channel never receives records, and an operator that was never built, or a connection to an
+//! operator or port that does not exist, panics deep inside progress tracking. Each scope checks its graph as it is built, and reports
+//! the first mistake it finds as a [`GraphError`], which names the scope and operators involved.
+//!
+//! [`Worker::dataflow`](crate::worker::Worker::dataflow) panics with the description of the
+//! error, and [`Worker::try_dataflow`](crate::worker::Worker::try_dataflow) returns it.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::progress::{Location, Source, Target};
+
+/// A mistake in the construction of a dataflow, detected as it is built.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError {
+    /// A cycle along which timestamps need not advance, lacking a feedback edge that increments
+    /// them, which stalls progress tracking.
+    CycleWithoutIncrement {
+        /// The name and path of the scope containing the cycle.
+        scope: (String, Vec<usize>),
+        /// The names and paths of the operators on the cycle.
+        operators: Vec<(String, Vec<usize>)>,
+    },
+    /// An operator allocated in a scope but never built, as for a feedback stream whose handle is
+    /// never connected with [`connect_loop`](crate::dataflow::operators::ConnectLoop::connect_loop).
+    MissingOperator {
+        /// The name and path of the scope.
+        scope: (String, Vec<usize>),
+        /// The path the operator would have had.
+        operator: Vec<usize>,
+    },
+    /// An input of an operator connected to no channel.
+    UnconnectedInput {
+        /// The name and path of the scope containing the operator.
+        scope: (String, Vec<usize>),
+        /// The name and path of the operator.
+        operator: (String, Vec<usize>),
+        /// The index of the input.
+        input: usize,
+    },
+    /// A connection from or to an operator or port that does not exist.
+    InvalidEdge {
+        /// The name and path of the scope containing the connection.
+        scope: (String, Vec<usize>),
+        /// The source of the connection.
+        source: Source,
+        /// The target of the connection.
+        target: Target,
+    },
+    /// A channel identifier allocated more than once.
+    DuplicateChannel {
+        /// The name of the dataflow allocating the channel.
+        dataflow: String,
+        /// The identifier of the channel.
+        identifier: usize,
+    },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::CycleWithoutIncrement { scope, operators } => {
+                write!(f, "cycle without a timestamp increment in scope `{}` {:?}, through operators ", scope.0, scope.1)?;
+                for (index, (name, path)) in operators.iter().enumerate() {
+                    if index > 0 { f.write_str(", ")?; }
+                    write!(f, "`{}` {:?}", name, path)?;
+                }
+                f.write_str("; loop streams back through a feedback edge with a non-zero summary")
+            },
+            GraphError::MissingOperator { scope, operator } => {
+                write!(f, "operator {:?} in scope `{}` {:?} was never built; is a feedback handle never connected?", operator, scope.0, scope.1)
+            },
+            GraphError::UnconnectedInput { scope, operator, input } => {
+                write!(f, "input {} of operator `{}` {:?} in scope `{}` {:?} is connected to no stream", input, operator.0, operator.1, scope.0, scope.1)
+            },
+            GraphError::InvalidEdge { scope, source, target } => {
+                write!(f, "connection from {:?} to {:?} in scope `{}` {:?} names an operator or port that does not exist", source, target, scope.0, scope.1)
+            },
+            GraphError::DuplicateChannel { dataflow, identifier } => {
+                write!(f, "channel identifier {} allocated more than once, in dataflow `{}`", identifier, dataflow)
+            },
+        }
+    }
+}
+
+impl std::error::Error for GraphError { }
+
+/// The shape of an operator in a scope being checked.
+pub(crate) struct Node {
+    /// The name of the operator.
+    pub name: String,
+    /// The number of inputs of the operator.
+    pub inputs: usize,
+    /// The number of outputs of the operator.
+    pub outputs: usize,
+    /// For each input, the outputs it reaches with a default (non-incrementing) summary.
+    pub unadvanced: Vec<Vec<usize>>,
+}
+
+/// Checks the graph of the scope `scope`, whose operators are `nodes` by index, absent if never
+/// built, and whose channels are `edges`. Node zero represents the scope itself.
+pub(crate) fn check(scope: &(String, Vec<usize>), nodes: &[Option<Node>], edges: &[(Source, Target)]) -> Result<(), GraphError> {
+
+    let path = |index: usize| {
+        let mut path = scope.1.clone();
+        path.push(index);
+        path
+    };
+
+    if let Some(index) = nodes.iter().position(|node| node.is_none()) {
+        return Err(GraphError::MissingOperator { scope: scope.clone(), operator: path(index) });
+    }
+    let nodes = nodes.iter().flatten().collect::<Vec<_>>();
+    let operator = |index: usize| (nodes[index].name.clone(), path(index));
+
+    for &(source, target) in edges.iter() {
+        let valid_source = nodes.get(source.node).map(|node| source.port < node.outputs).unwrap_or(false);
+        let valid_target = nodes.get(target.node).map(|node| target.port < node.inputs).unwrap_or(false);
+        if !valid_source || !valid_target {
+            return Err(GraphError::InvalidEdge { scope: scope.clone(), source, target });
+        }
+    }
+
+    let connected = edges.iter().map(|(_, target)| (target.node, target.port)).collect::<HashSet<_>>();
+    for (index, node) in nodes.iter().enumerate().skip(1) {
+        if let Some(input) = (0 .. node.inputs).find(|input| !connected.contains(&(index, *input))) {
+            return Err(GraphError::UnconnectedInput { scope: scope.clone(), operator: operator(index), input });
+        }
+    }
+
+    let cycle = unadvanced_cycle(&nodes, edges);
+    if !cycle.is_empty() {
+        let operators = cycle.into_iter().map(operator).collect();
+        return Err(GraphError::CycleWithoutIncrement { scope: scope.clone(), operators });
+    }
+
+    Ok(())
+}
+
+// The indices of operators on cycles of channels and default summaries, in order.
+//
+// Locations that cannot be reached from a cycle are removed, and then those that cannot reach a
+// cycle, which leaves the locations on cycles, and any on paths between them.
+fn unadvanced_cycle(nodes: &[&Node], edges: &[(Source, Target)]) -> Vec<usize> {
+
+    let mut forward = HashMap::<Location, Vec<Location>>::new();
+    for &(source, target) in edges.iter() {
+        forward.entry(Location::from(source)).or_default().push(Location::from(target));
+    }
+    for (index, node) in nodes.iter().enumerate() {
+        for (input, outputs) in node.unadvanced.iter().enumerate() {
+            let targets = forward.entry(Location::new_target(index, input)).or_default();
+            targets.extend(outputs.iter().map(|output| Location::new_source(index, *output)));
+        }
+    }
+    let mut backward = HashMap::<Location, Vec<Location>>::new();
+    for (from, tos) in forward.iter() {
+        for to in tos.iter() {
+            backward.entry(*to).or_default().push(*from);
+        }
+    }
+
+    let mut remaining = forward.keys().chain(backward.keys()).cloned().collect::<HashSet<_>>();
+    prune(&mut remaining, &backward, &forward);
+    prune(&mut remaining, &forward, &backward);
+
+    let mut operators = remaining.into_iter().map(|location| location.node).collect::<Vec<_>>();
+    operators.sort();
+    operators.dedup();
+    operators
+}
+
+// Repeatedly removes from `remaining` the locations with no `incoming` edges from `remaining`,
+// updating the locations they reach by `outgoing` edges.
+fn prune(remaining: &mut HashSet<Location>, incoming: &HashMap<Location, Vec<Location>>, outgoing: &HashMap<Location, Vec<Location>>) {
+    let mut degree = remaining.iter().map(|location| {
+        let count = incoming.get(location).map(|from| from.iter().filter(|l| remaining.contains(l)).count()).unwrap_or(0);
+        (*location, count)
+    }).collect::<HashMap<_,_>>();
+    let mut worklist = degree.iter().filter(|(_, count)| **count == 0).map(|(location, _)| *location).collect::<Vec<_>>();
+    while let Some(location) = worklist.pop() {
+        remaining.remove(&location);
+        for next in outgoing.get(&location).into_iter().flatten() {
+            if let Some(count) = degree.get_mut(next) {
+                *count -= 1;
+                if *count == 0 {
+                    worklist.push(*next);
+                }
+            }
+        }
+    }
+}
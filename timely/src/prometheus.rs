@@ -0,0 +1,237 @@
+//! A Prometheus scrape endpoint for the measurements of a process's workers.
+//!
+//! An [`Exporter`] listens at an address and answers `GET /metrics` requests in the Prometheus
+//! text exposition format. Workers report to the exporter installed in their configuration by
+//! [`Config::prometheus`](crate::worker::Config::prometheus), and the following are exported,
+//! each labeled by the index of the reporting worker:
+//!
+//! * `timely_channel_records_sent_total` and `timely_channel_records_received_total`, counters
+//!   of the records each worker sent and received on each channel, labeled by channel identifier.
+//! * `timely_step_duration_seconds`, a histogram of the time each worker spent scheduling its
+//!   dataflows in each step, excluding time spent parked.
+//! * `timely_probe_frontier_lag_seconds`, a gauge of how far the frontier of each
+//!   [watched](Exporter::watch_probe) probe trails the wall clock, labeled by probe name.
+//!
+//! Channel counts are assembled from the "timely" log stream, to which each worker binds a logger
+//! as it is constructed. Binding another logger to that stream stops their reporting.
+//!
+//! This module is only available with the `prometheus` feature.
+//!
+//! # Examples
+//! ```
+//! use std::io::{Read, Write};
+//! use std::net::TcpStream;
+//! use std::time::Duration;
+//! use timely::prometheus::Exporter;
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Exchange, Probe};
+//!
+//! let exporter = Exporter::serve("127.0.0.1:0").unwrap();
+//! let address = exporter.local_addr();
+//!
+//! let mut config = timely::Config::thread();
+//! config.worker = config.worker.prometheus(exporter.clone());
+//! timely::execute(config, move |worker| {
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow(|scope| {
+//!         scope.input_from(&mut input)
+//!              .exchange(|x: &u64| *x)
+//!              .probe()
+//!     });
+//!     // Timestamps are seconds since the Unix epoch.
+//!     exporter.watch_probe(worker.index(), "output", &probe, |time| Duration::from_secs(*time));
+//!     input.send(0);
+//!     input.advance_to(1);
+//!     worker.step_while(|| probe.less_than(input.time()));
+//!
+//!     let mut stream = TcpStream::connect(address).unwrap();
+//!     stream.write_all(b"GET /metrics HTTP/1.0\r\n\r\n").unwrap();
+//!     let mut response = String::new();
+//!     stream.read_to_string(&mut response).unwrap();
+//!     assert!(response.starts_with("HTTP/1.0 200 OK"));
+//!     assert!(response.contains("timely_channel_records_sent_total{worker=\"0\""));
+//!     assert!(response.contains("timely_step_duration_seconds_count{worker=\"0\"}"));
+//!     assert!(response.contains("timely_probe_frontier_lag_seconds{worker=\"0\",probe=\"output\"}"));
+//! }).unwrap();
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::communication::Allocate;
+use crate::dataflow::operators::probe::Handle;
+use crate::http::{self, Response};
+use crate::logging::TimelyEvent;
+use crate::progress::Timestamp;
+use crate::worker::Worker;
+
+// Upper bounds of the step duration histogram's buckets, in seconds.
+const STEP_BUCKETS: [f64; 6] = [0.00001, 0.0001, 0.001, 0.01, 0.1, 1.0];
+
+/// A handle to a Prometheus scrape endpoint.
+///
+/// The endpoint stops once all handles are dropped, and all reporting workers have dropped their
+/// loggers.
+#[derive(Clone)]
+pub struct Exporter {
+    state: Arc<Mutex<State>>,
+    address: SocketAddr,
+}
+
+impl Exporter {
+    /// Starts an endpoint listening at `address`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub fn serve<A: ToSocketAddrs>(address: A) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+        http::serve("timely:prometheus", listener, Arc::downgrade(&state), respond)?;
+        Ok(Exporter { state, address })
+    }
+
+    /// The address at which the endpoint listens.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Exports the lag of the frontier of `probe` as observed by worker `worker`, under `name`.
+    ///
+    /// The lag is the time elapsed since the Unix epoch, less the least event time of elements
+    /// of the frontier, as determined by `event_time`, or zero if the frontier is empty.
+    pub fn watch_probe<T, F>(&self, worker: usize, name: &str, probe: &Handle<T>, event_time: F)
+    where
+        T: Timestamp,
+        F: Fn(&T)->Duration+'static,
+    {
+        let key = (worker, name.to_owned());
+        let least = move |frontier: &[T]| frontier.iter().map(&event_time).min();
+        let initial = probe.with_frontier(|frontier| least(&frontier[..]));
+        self.lock().probes.insert(key.clone(), initial);
+        let state = self.state.clone();
+        probe.on_frontier_change(move |frontier| {
+            let least = least(&frontier[..]);
+            state.lock().expect("prometheus state poisoned").probes.insert(key.clone(), least);
+        });
+    }
+
+    /// Reports channel traffic of `worker`, from its subsequently constructed dataflows.
+    pub(crate) fn attach<A: Allocate>(&self, worker: &Worker<A>) {
+        let state = self.state.clone();
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+            let mut state = state.lock().expect("prometheus state poisoned");
+            for (_time, worker, event) in data.drain(..) {
+                if let TimelyEvent::Messages(event) = event {
+                    let counts = state.channels.entry((worker, event.channel)).or_default();
+                    if event.is_send { counts.0 += event.length as u64; }
+                    else { counts.1 += event.length as u64; }
+                }
+            }
+        });
+    }
+
+    /// Records the time `worker` spent scheduling its dataflows in one step.
+    pub(crate) fn observe_step(&self, worker: usize, elapsed: Duration) {
+        self.lock().steps.entry(worker).or_default().observe(elapsed.as_secs_f64());
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("prometheus state poisoned")
+    }
+}
+
+impl fmt::Debug for Exporter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Exporter").field("address", &self.address).finish()
+    }
+}
+
+/// Answers a request for `path`.
+fn respond(state: &Mutex<State>, path: &str) -> Response {
+    if path == "/metrics" {
+        let body = state.lock().expect("prometheus state poisoned").render();
+        ("200 OK", "text/plain; version=0.0.4", body)
+    }
+    else {
+        ("404 Not Found", "text/plain", "not found\n".to_owned())
+    }
+}
+
+/// The measurements reported by all workers.
+#[derive(Default)]
+struct State {
+    /// Records sent and received, by worker and channel.
+    channels: BTreeMap<(usize, usize), (u64, u64)>,
+    /// Step durations, by worker.
+    steps: BTreeMap<usize, Histogram>,
+    /// The least event time of each probe's frontier, by worker and probe name.
+    probes: BTreeMap<(usize, String), Option<Duration>>,
+}
+
+impl State {
+    fn render(&self) -> String {
+        let mut text = String::new();
+
+        writeln!(text, "# HELP timely_channel_records_sent_total Records sent on a channel.").unwrap();
+        writeln!(text, "# TYPE timely_channel_records_sent_total counter").unwrap();
+        for ((worker, channel), (sent, _)) in self.channels.iter() {
+            writeln!(text, "timely_channel_records_sent_total{{worker=\"{}\",channel=\"{}\"}} {}", worker, channel, sent).unwrap();
+        }
+        writeln!(text, "# HELP timely_channel_records_received_total Records received on a channel.").unwrap();
+        writeln!(text, "# TYPE timely_channel_records_received_total counter").unwrap();
+        for ((worker, channel), (_, received)) in self.channels.iter() {
+            writeln!(text, "timely_channel_records_received_total{{worker=\"{}\",channel=\"{}\"}} {}", worker, channel, received).unwrap();
+        }
+
+        writeln!(text, "# HELP timely_step_duration_seconds Time spent scheduling dataflows in a step.").unwrap();
+        writeln!(text, "# TYPE timely_step_duration_seconds histogram").unwrap();
+        for (worker, histogram) in self.steps.iter() {
+            let mut cumulative = 0;
+            for (bound, count) in STEP_BUCKETS.iter().zip(histogram.buckets.iter()) {
+                cumulative += count;
+                writeln!(text, "timely_step_duration_seconds_bucket{{worker=\"{}\",le=\"{}\"}} {}", worker, bound, cumulative).unwrap();
+            }
+            writeln!(text, "timely_step_duration_seconds_bucket{{worker=\"{}\",le=\"+Inf\"}} {}", worker, histogram.count).unwrap();
+            writeln!(text, "timely_step_duration_seconds_sum{{worker=\"{}\"}} {}", worker, histogram.sum).unwrap();
+            writeln!(text, "timely_step_duration_seconds_count{{worker=\"{}\"}} {}", worker, histogram.count).unwrap();
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        writeln!(text, "# HELP timely_probe_frontier_lag_seconds Time by which a probe's frontier trails the wall clock.").unwrap();
+        writeln!(text, "# TYPE timely_probe_frontier_lag_seconds gauge").unwrap();
+        for ((worker, name), least) in self.probes.iter() {
+            let lag = least.map(|least| now.saturating_sub(least)).unwrap_or_default();
+            writeln!(text, "timely_probe_frontier_lag_seconds{{worker=\"{}\",probe=\"{}\"}} {}", worker, escape(name), lag.as_secs_f64()).unwrap();
+        }
+
+        text
+    }
+}
+
+/// Counts of observations at most each of `STEP_BUCKETS`, exclusive of lesser buckets.
+#[derive(Default)]
+struct Histogram {
+    buckets: [u64; STEP_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        if let Some(index) = STEP_BUCKETS.iter().position(|bound| value <= *bound) {
+            self.buckets[index] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+/// Escapes a label value, as described by the text exposition format.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
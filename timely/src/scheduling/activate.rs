@@ -4,12 +4,14 @@ use std::rc::Rc;
 use std::sync::Arc;
 use std::cell::RefCell;
 use std::thread::Thread;
-use std::collections::BinaryHeap;
-use std::time::{Duration, Instant};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::time::Duration;
 use std::cmp::Reverse;
 use crossbeam_channel::{Sender, Receiver};
 use futures_util::task::ArcWake;
 
+use crate::logging_core::clock::{Clock, Instant};
+
 /// Methods required to act as a timely scheduler.
 ///
 /// The core methods are the activation of "paths", sequences of integers, and
@@ -36,6 +38,9 @@ impl Scheduler for Box<dyn Scheduler> {
     fn extensions(&mut self, path: &[usize], dest: &mut Vec<usize>) { (**self).extensions(path, dest) }
 }
 
+// A delayed activation: its deadline, its path, and the token of its timer, if any.
+type Delayed = (Duration, Vec<usize>, Option<usize>);
+
 /// Allocation-free activation tracker.
 pub struct Activations {
     clean: usize,
@@ -48,15 +53,33 @@ pub struct Activations {
     tx: Sender<Vec<usize>>,
     rx: Receiver<Vec<usize>>,
 
-    // Delayed activations.
+    // Paths activated since the last `advance`, to discard redundant activations.
+    pending: HashSet<Vec<usize>>,
+
+    // Delayed activations, each with its deadline and the token of its timer, if any.
     timer: Instant,
-    queue: BinaryHeap<Reverse<(Duration, Vec<usize>)>>,
+    clock: Arc<dyn Clock>,
+    queue: BinaryHeap<Reverse<Delayed>>,
+    // The earliest deadline of the queued activations of each path without a timer.
+    delayed: HashMap<Vec<usize>, Duration>,
+    // The interval of each live timer, `None` for those that fire once.
+    timers: HashMap<usize, Option<Duration>>,
+    next_token: usize,
 }
 
+/// Identifies a delayed or repeated activation, which may be canceled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ActivationToken(usize);
+
 impl Activations {
 
     /// Creates a new activation tracker.
     pub fn new(timer: Instant) -> Self {
+        Self::with_clock(timer, crate::logging_core::clock::system())
+    }
+
+    /// Creates a new activation tracker, whose delays are measured by `clock` from `timer`.
+    pub fn with_clock(timer: Instant, clock: Arc<dyn Clock>) -> Self {
         let (tx, rx) = crossbeam_channel::unbounded();
         Self {
             clean: 0,
@@ -65,29 +88,73 @@ impl Activations {
             buffer: Vec::new(),
             tx,
             rx,
+            pending: HashSet::new(),
             timer,
+            clock,
             queue: BinaryHeap::new(),
+            delayed: HashMap::new(),
+            timers: HashMap::new(),
+            next_token: 0,
         }
     }
 
     /// Activates the task addressed by `path`.
+    ///
+    /// Activations of a path already activated since the last `advance` are discarded.
     pub fn activate(&mut self, path: &[usize]) {
-        self.bounds.push((self.slices.len(), path.len()));
-        self.slices.extend(path);
+        if !self.pending.contains(path) {
+            self.pending.insert(path.to_vec());
+            self.bounds.push((self.slices.len(), path.len()));
+            self.slices.extend(path);
+        }
     }
 
     /// Schedules a future activation for the task addressed by `path`.
+    ///
+    /// The activation is discarded if the path is already to be activated no later.
     pub fn activate_after(&mut self, path: &[usize], delay: Duration) {
         // TODO: We could have a minimum delay and immediately schedule anything less than that delay.
         if delay == Duration::new(0, 0) {
             self.activate(path);
         }
         else {
-            let moment = self.timer.elapsed() + delay;
-            self.queue.push(Reverse((moment, path.to_vec())));
+            let moment = self.clock.elapsed(self.timer) + delay;
+            if self.delayed.get(path).map(|due| *due > moment).unwrap_or(true) {
+                self.delayed.insert(path.to_vec(), moment);
+                self.queue.push(Reverse((moment, path.to_vec(), None)));
+            }
         }
     }
 
+    /// Schedules a future activation for the task addressed by `path`, which may be canceled.
+    pub fn schedule_after(&mut self, path: &[usize], delay: Duration) -> ActivationToken {
+        self.start_timer(path, delay, None)
+    }
+
+    /// Activates the task addressed by `path` every `interval`, starting one interval from now,
+    /// until canceled.
+    ///
+    /// Activations that fall due while the worker is busy are not accumulated: the next
+    /// activation is scheduled one interval after the most recent.
+    pub fn activate_every(&mut self, path: &[usize], interval: Duration) -> ActivationToken {
+        assert!(interval > Duration::new(0, 0), "activation interval must be positive");
+        self.start_timer(path, interval, Some(interval))
+    }
+
+    /// Cancels the activations identified by `token`, if they have not all occurred.
+    pub fn cancel(&mut self, token: ActivationToken) {
+        self.timers.remove(&token.0);
+    }
+
+    fn start_timer(&mut self, path: &[usize], delay: Duration, interval: Option<Duration>) -> ActivationToken {
+        let token = self.next_token;
+        self.next_token += 1;
+        self.timers.insert(token, interval);
+        let moment = self.clock.elapsed(self.timer) + delay;
+        self.queue.push(Reverse((moment, path.to_vec(), Some(token))));
+        ActivationToken(token)
+    }
+
     /// Discards the current active set and presents the next active set.
     pub fn advance(&mut self) {
 
@@ -97,11 +164,36 @@ impl Activations {
         }
 
         // Drain timer-based activations.
-        let now = self.timer.elapsed();
-        while self.queue.peek().map(|Reverse((t,_))| t <= &now) == Some(true) {
-            let Reverse((_time, path)) = self.queue.pop().unwrap();
-            self.activate(&path[..]);
+        let now = self.clock.elapsed(self.timer);
+        while self.queue.peek().map(|Reverse((t,_,_))| t <= &now) == Some(true) {
+            let Reverse((time, path, token)) = self.queue.pop().unwrap();
+            match token {
+                None => {
+                    if self.delayed.get(&path) == Some(&time) {
+                        self.delayed.remove(&path);
+                    }
+                    self.activate(&path[..]);
+                },
+                Some(token) => match self.timers.get(&token).cloned() {
+                    // Canceled.
+                    None => { },
+                    Some(None) => {
+                        self.timers.remove(&token);
+                        self.activate(&path[..]);
+                    },
+                    Some(Some(interval)) => {
+                        self.activate(&path[..]);
+                        self.queue.push(Reverse((now + interval, path, Some(token))));
+                    },
+                },
+            }
         }
+        // Discard canceled activations, so that they do not determine how long to park.
+        while let Some(Reverse((_, _, Some(token)))) = self.queue.peek() {
+            if self.timers.contains_key(token) { break; }
+            self.queue.pop();
+        }
+        self.pending.clear();
 
         self.bounds.drain(.. self.clean);
 
@@ -122,6 +214,30 @@ impl Activations {
         self.clean = self.bounds.len();
     }
 
+    /// Replaces the active extensions of `path` with those activated since the last `advance`.
+    ///
+    /// This allows `path` to be scheduled again in the same round, presenting only its new
+    /// activations, while the active set of other paths is unchanged. Returns false, and changes
+    /// nothing, if no extension of `path` was activated since the last `advance`.
+    pub(crate) fn refresh(&mut self, path: &[usize]) -> bool {
+        let slices = &self.slices[..];
+        let slice = |&(offset, length): &(usize, usize)| &slices[offset .. (offset + length)];
+        let (mut fresh, rest): (Vec<_>, Vec<_>) = self.bounds[self.clean ..].iter().partition(|bound| slice(bound).starts_with(path));
+        if fresh.is_empty() {
+            return false;
+        }
+        self.bounds.truncate(self.clean);
+        self.bounds.retain(|bound| !slice(bound).starts_with(path));
+        fresh.sort_by_key(|bound| slice(bound));
+        fresh.dedup_by_key(|bound| slice(bound));
+        self.pending.retain(|pending| !pending.starts_with(path));
+        let position = self.bounds.binary_search_by_key(&path, |bound| slice(bound)).unwrap_or_else(|x| x);
+        self.bounds.splice(position .. position, fresh);
+        self.clean = self.bounds.len();
+        self.bounds.extend(rest);
+        true
+    }
+
     /// Maps a function across activated paths.
     pub fn map_active(&self, logic: impl Fn(&[usize])) {
         for (offset, length) in self.bounds.iter() {
@@ -176,8 +292,8 @@ impl Activations {
             Some(Duration::new(0,0))
         }
         else {
-            self.queue.peek().map(|Reverse((t,_a))| {
-                let elapsed = self.timer.elapsed();
+            self.queue.peek().map(|Reverse((t,_a,_))| {
+                let elapsed = self.clock.elapsed(self.timer);
                 if t < &elapsed { Duration::new(0,0) }
                 else { *t - elapsed }
             })
@@ -186,6 +302,7 @@ impl Activations {
 }
 
 /// A thread-safe handle to an `Activations`.
+#[derive(Clone)]
 pub struct SyncActivations {
     tx: Sender<Vec<usize>>,
     thread: Thread,
@@ -247,9 +364,109 @@ impl Activator {
                 .activate_after(&self.path[..], delay);
         }
     }
+
+    /// Activates the associated path after a specified duration, unless canceled.
+    pub fn schedule_after(&self, delay: Duration) -> ActivationToken {
+        self.queue
+            .borrow_mut()
+            .schedule_after(&self.path[..], delay)
+    }
+
+    /// Activates the associated path every `interval`, until canceled.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::operators::generic::source;
+    /// use timely::scheduling::Scheduler;
+    ///
+    /// timely::example(|scope| {
+    ///     source::<_, (), _, _>(scope, "Housekeeping", |capability, info| {
+    ///         let activator = scope.activator_for(&info.address[..]);
+    ///         // Wakes every millisecond, ten times.
+    ///         let token = activator.activate_every(Duration::from_millis(1));
+    ///         let mut capability = Some(capability);
+    ///         let mut wakes = 0;
+    ///         move |_output| {
+    ///             wakes += 1;
+    ///             if wakes > 10 {
+    ///                 activator.cancel(token);
+    ///                 capability = None;
+    ///             }
+    ///         }
+    ///     });
+    /// });
+    /// ```
+    pub fn activate_every(&self, interval: Duration) -> ActivationToken {
+        self.queue
+            .borrow_mut()
+            .activate_every(&self.path[..], interval)
+    }
+
+    /// Cancels the activations identified by `token`.
+    pub fn cancel(&self, token: ActivationToken) {
+        self.queue
+            .borrow_mut()
+            .cancel(token)
+    }
 }
 
 /// A thread-safe version of `Activator`.
+///
+/// A `SyncActivator` may be sent to and cloned by other threads, for example those that read from
+/// the network or an async runtime, which then activate the operator when they have produced data
+/// for it. Activation unparks the worker thread, so that a worker blocked in
+/// [`step_or_park`](crate::worker::Worker::step_or_park) schedules the operator promptly.
+///
+/// # Examples
+/// ```
+/// use std::sync::mpsc;
+/// use timely::dataflow::operators::{Inspect, Probe};
+/// use timely::dataflow::operators::generic::source;
+/// use timely::scheduling::Scheduler;
+///
+/// timely::execute_from_args(std::env::args(), |worker| {
+///     let (send, recv) = mpsc::channel();
+///     let mut producer = None;
+///     let probe = worker.dataflow::<u64,_,_>(|scope| {
+///         source(scope, "Background", |capability, info| {
+///             let activator = scope.sync_activator_for(&info.address[..]);
+///             // A background thread produces records, and activates the operator after each.
+///             producer = Some(std::thread::spawn(move || {
+///                 for record in 0 .. 10u64 {
+///                     send.send(record).unwrap();
+///                     activator.activate().unwrap();
+///                 }
+///                 drop(send);
+///                 activator.activate().unwrap();
+///             }));
+///             let mut capability = Some(capability);
+///             move |output| {
+///                 let mut done = false;
+///                 if let Some(cap) = capability.as_ref() {
+///                     let mut session = output.session(cap);
+///                     loop {
+///                         match recv.try_recv() {
+///                             Ok(record) => session.give(record),
+///                             Err(mpsc::TryRecvError::Empty) => break,
+///                             Err(mpsc::TryRecvError::Disconnected) => { done = true; break; },
+///                         }
+///                     }
+///                 }
+///                 if done { capability = None; }
+///             }
+///         })
+///         .inspect(|x| assert!(*x < 10))
+///         .probe()
+///     });
+///     // The worker parks until the background thread activates the operator.
+///     while !probe.done() {
+///         worker.step_or_park(None);
+///     }
+///     producer.unwrap().join().unwrap();
+/// }).unwrap();
+/// ```
+#[derive(Clone)]
 pub struct SyncActivator {
     path: Vec<usize>,
     queue: SyncActivations,
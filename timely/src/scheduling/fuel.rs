@@ -0,0 +1,124 @@
+//! Budgets of work for one scheduling of an operator.
+//!
+//! An operator whose logic may run for a long time, because it is handed a large batch of input
+//! or has a great deal of output to produce, can keep a [`Fuel`] and consult it as it works. Once
+//! the fuel is exhausted, the operator should stop, retain whatever remains to do, and activate
+//! itself so that it is scheduled again once the worker has given other operators their turn.
+//! The fuel is refilled at the start of each scheduling.
+//!
+//! The worker's [`Config::operator_fuel`](crate::worker::Config::operator_fuel) sets the fuel of
+//! stock operators that do unbounded work in one scheduling, currently
+//! [`aggregate`](crate::dataflow::operators::aggregation::Aggregate::aggregate) and
+//! [`join_by_key`](crate::dataflow::operators::join::JoinByKey::join_by_key), and is available to
+//! operators built with the generic operator builders through `scope.config().fuel()`. By default
+//! fuel is unlimited, and operators do all available work in each scheduling.
+//!
+//! # Examples
+//! ```
+//! use std::collections::VecDeque;
+//! use timely::dataflow::Scope;
+//! use timely::dataflow::channels::pact::Pipeline;
+//! use timely::dataflow::operators::{ToStream, Operator, Inspect};
+//! use timely::scheduling::Scheduler;
+//! use timely::scheduling::fuel::Fuel;
+//!
+//! timely::example(|scope| {
+//!     let stream = (0 .. 1000u64).to_stream(scope);
+//!     let scope = stream.scope();
+//!     stream
+//!         .unary(Pipeline, "Expensive", move |_, info| {
+//!             let activator = scope.activator_for(&info.address[..]);
+//!             let mut fuel = Fuel::records(100);
+//!             let mut pending = VecDeque::new();
+//!             move |input, output| {
+//!                 input.for_each(|time, data| pending.push_back((time.retain(), data.replace(Vec::new()))));
+//!                 fuel.refill();
+//!                 while let Some((time, mut data)) = pending.pop_front() {
+//!                     if fuel.exhausted() {
+//!                         pending.push_front((time, data));
+//!                         activator.activate();
+//!                         break;
+//!                     }
+//!                     fuel.consume(data.len());
+//!                     output.session(&time).give_vec(&mut data);
+//!                 }
+//!             }
+//!         })
+//!         .inspect(|x| assert!(*x < 1000));
+//! });
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::logging_core::clock::{self, Clock, Instant};
+
+/// A budget of records processed, time spent, or both, for one scheduling of an operator.
+///
+/// Time budgets are measured by the system clock, unless the fuel is given another clock with
+/// [`with_clock`](Self::with_clock). Fuel from [`Config::fuel`](crate::worker::Config::fuel)
+/// reads the clock of the worker.
+#[derive(Debug, Clone)]
+pub struct Fuel {
+    records: Option<usize>,
+    duration: Option<Duration>,
+    spent: usize,
+    // When the fuel was last refilled, if it has a time budget.
+    start: Option<Instant>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Fuel {
+    /// Fuel that is never exhausted.
+    pub fn unlimited() -> Self {
+        Fuel { records: None, duration: None, spent: 0, start: None, clock: clock::system() }
+    }
+    /// Fuel that is exhausted once `records` records have been consumed.
+    pub fn records(records: usize) -> Self {
+        Fuel { records: Some(records), ..Fuel::unlimited() }
+    }
+    /// Fuel that is exhausted once `duration` has elapsed since it was refilled.
+    pub fn duration(duration: Duration) -> Self {
+        Fuel { duration: Some(duration), ..Fuel::unlimited() }
+    }
+    /// Additionally exhausts the fuel once `duration` has elapsed since it was refilled.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+    /// Measures time budgets with `clock`.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// Indicates whether the fuel is never exhausted.
+    pub fn is_unlimited(&self) -> bool {
+        self.records.is_none() && self.duration.is_none()
+    }
+    /// Restores the full budget, as at the start of a scheduling.
+    pub fn refill(&mut self) {
+        self.spent = 0;
+        if self.duration.is_some() {
+            self.start = Some(self.clock.now());
+        }
+    }
+    /// Accounts for the processing of `records` records.
+    #[inline]
+    pub fn consume(&mut self, records: usize) {
+        self.spent += records;
+    }
+    /// Indicates whether the budget has been spent, and the operator should yield.
+    ///
+    /// Fuel with a time budget reads the clock, and so should be consulted once per batch of
+    /// records rather than once per record.
+    pub fn exhausted(&self) -> bool {
+        self.records.map(|records| self.spent >= records).unwrap_or(false)
+        || self.duration.zip(self.start).map(|(duration, start)| self.clock.elapsed(start) >= duration).unwrap_or(false)
+    }
+}
+
+impl Default for Fuel {
+    fn default() -> Self {
+        Fuel::unlimited()
+    }
+}
@@ -0,0 +1,123 @@
+//! Hooks invoked around the scheduling of each operator.
+//!
+//! A [`ScheduleHook`] installed by [`Worker::set_schedule_hook`](crate::worker::Worker::set_schedule_hook)
+//! is called before a scope schedules one of its operators, and may decline the schedule, and
+//! after the operator returns, with a description of what it did. This lets a debugger or test
+//! harness single-step operators, delay them in order to provoke unusual interleavings, inject
+//! faults by panicking, and check invariants between schedules.
+//!
+//! Hooks apply to the operators of all dataflows of the worker, including scopes, which are
+//! operators of their parents, and including dataflows built before the hook was installed.
+//! Operators are identified by their addresses, as reported by
+//! [`Worker::operator_name`](crate::worker::Worker::operator_name). A hook must not install or
+//! remove the hook of its worker while it runs.
+//!
+//! # Examples
+//! ```
+//! use std::cell::RefCell;
+//! use std::rc::Rc;
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Map, Probe};
+//! use timely::scheduling::hooks::{Activity, ScheduleHook};
+//!
+//! // Declines each operator's first schedule, and otherwise logs what operators did.
+//! struct Stutter {
+//!     declined: Vec<Vec<usize>>,
+//!     log: Rc<RefCell<Vec<(Vec<usize>, Vec<i64>)>>>,
+//! }
+//!
+//! impl ScheduleHook for Stutter {
+//!     fn on_before_schedule(&mut self, addr: &[usize]) -> bool {
+//!         if self.declined.iter().any(|a| &a[..] == addr) { return true; }
+//!         self.declined.push(addr.to_vec());
+//!         false
+//!     }
+//!     fn on_after_schedule(&mut self, addr: &[usize], activity: &Activity) {
+//!         self.log.borrow_mut().push((addr.to_vec(), activity.produced.clone()));
+//!     }
+//! }
+//!
+//! timely::execute(timely::Config::thread(), |worker| {
+//!     let log = Rc::new(RefCell::new(Vec::new()));
+//!     worker.set_schedule_hook(Stutter { declined: Vec::new(), log: log.clone() });
+//!
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow(|scope| {
+//!         scope.input_from(&mut input)
+//!              .map(|x: u64| x + 1)
+//!              .probe()
+//!     });
+//!     input.send_batch(&mut vec![0, 1, 2]);
+//!     input.advance_to(1);
+//!     worker.step_while(|| probe.less_than(input.time()));
+//!
+//!     // The declined operators were scheduled again, and the map produced its three records.
+//!     let map = worker.dataflow_graph(0).unwrap().operators.into_iter().find(|o| o.name == "Map").unwrap();
+//!     let produced = log.borrow().iter().filter(|(addr, _)| addr == &map.addr).map(|(_, p)| p[0]).sum::<i64>();
+//!     assert_eq!(produced, 3);
+//!     worker.remove_schedule_hook();
+//! }).unwrap();
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::logging::ActivationCause;
+
+/// What an operator did when scheduled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Activity {
+    /// Worker-unique identifier for the operator.
+    pub id: usize,
+    /// The time the operator spent scheduled.
+    pub elapsed: Duration,
+    /// The number of records consumed at each input.
+    pub consumed: Vec<i64>,
+    /// The number of records produced at each output.
+    pub produced: Vec<i64>,
+    /// Why the operator was scheduled.
+    pub cause: ActivationCause,
+    /// Whether the operator reported that it may have more work to do.
+    pub incomplete: bool,
+}
+
+/// Callbacks around the scheduling of operators.
+pub trait ScheduleHook {
+    /// Called before the operator at `addr` is scheduled, and indicates whether to schedule it.
+    ///
+    /// A declined operator remains activated, and its scope will offer it again in the next step
+    /// of the worker, along with any further reasons to schedule it.
+    fn on_before_schedule(&mut self, _addr: &[usize]) -> bool { true }
+    /// Called after the operator at `addr` was scheduled, with a description of what it did.
+    fn on_after_schedule(&mut self, _addr: &[usize], _activity: &Activity) { }
+}
+
+/// A shared handle to the schedule hook of a worker, if any.
+#[derive(Clone, Default)]
+pub struct Hooks {
+    hook: Rc<RefCell<Option<Box<dyn ScheduleHook>>>>,
+}
+
+impl Hooks {
+    /// Installs `hook`, or removes the hook if `None`, returning any previous hook.
+    pub(crate) fn set(&self, hook: Option<Box<dyn ScheduleHook>>) -> Option<Box<dyn ScheduleHook>> {
+        std::mem::replace(&mut *self.hook.borrow_mut(), hook)
+    }
+    /// Indicates whether a hook is installed.
+    pub(crate) fn is_set(&self) -> bool {
+        self.hook.borrow().is_some()
+    }
+    /// Asks the hook whether to schedule the operator at `addr`.
+    pub(crate) fn before(&self, addr: &[usize]) -> bool {
+        self.with(|hook| hook.on_before_schedule(addr)).unwrap_or(true)
+    }
+    /// Informs the hook of what the operator at `addr` did.
+    pub(crate) fn after(&self, addr: &[usize], activity: &Activity) {
+        self.with(|hook| hook.on_after_schedule(addr, activity));
+    }
+    /// Calls `logic` with the hook, if one is installed.
+    fn with<R>(&self, logic: impl FnOnce(&mut dyn ScheduleHook) -> R) -> Option<R> {
+        self.hook.borrow_mut().as_mut().map(|hook| logic(&mut **hook))
+    }
+}
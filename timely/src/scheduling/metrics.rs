@@ -0,0 +1,127 @@
+//! Aggregated measurements of the scheduling of operators.
+//!
+//! A worker configured with [`Config::operator_metrics`](crate::worker::Config::operator_metrics)
+//! measures the scheduling of its operators. Each time a scope schedules one of its operators, it
+//! records the time the operator spent, the numbers of records it consumed at each input and
+//! produced at each output, and why it was scheduled. The measurements are accumulated per operator, and are reported for all installed
+//! operators by [`Worker::metrics`](crate::worker::Worker::metrics). Each scheduling is also
+//! reported to the "timely/metrics" log stream, as a
+//! [`ScheduleMetricsEvent`](crate::logging::ScheduleMetricsEvent).
+//!
+//! Dataflows themselves are scheduled by the worker rather than by a scope, and are not measured.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Map, Probe};
+//!
+//! let mut config = timely::Config::thread();
+//! config.worker = config.worker.operator_metrics(true);
+//! timely::execute(config, |worker| {
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow(|scope| {
+//!         scope.input_from(&mut input)
+//!              .flat_map(|x: u64| vec![x; 3])
+//!              .probe()
+//!     });
+//!     input.send_batch(&mut vec![0, 1, 2, 3]);
+//!     input.advance_to(1);
+//!     worker.step_while(|| probe.less_than(input.time()));
+//!
+//!     let metrics = worker.metrics();
+//!     let flat_map = metrics.iter().find(|m| m.name == "FlatMap").unwrap();
+//!     assert_eq!(flat_map.consumed, vec![4]);
+//!     assert_eq!(flat_map.produced, vec![12]);
+//!     assert!(flat_map.schedules > 0);
+//!     assert!(flat_map.messages > 0);
+//! }).unwrap();
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+use crate::logging::ActivationCause;
+
+/// Accumulated measurements of the scheduling of one operator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperatorMetrics {
+    /// Worker-unique identifier for the operator.
+    pub id: usize,
+    /// Sequence of nested scope identifiers indicating the path from the root to this instance.
+    pub addr: Vec<usize>,
+    /// A helpful name.
+    pub name: String,
+    /// The number of times the operator was scheduled.
+    pub schedules: u64,
+    /// The total time the operator spent scheduled.
+    pub elapsed: Duration,
+    /// The number of records consumed at each input.
+    pub consumed: Vec<i64>,
+    /// The number of records produced at each output.
+    pub produced: Vec<i64>,
+    /// The number of schedulings following an activation of the operator.
+    pub activated: u64,
+    /// The number of schedulings following a change to the frontier of some input.
+    pub frontier: u64,
+    /// The number of schedulings following records sent to some input.
+    pub messages: u64,
+}
+
+impl OperatorMetrics {
+    /// Accumulates the measurements of one scheduling.
+    pub(crate) fn record(&mut self, elapsed: Duration, consumed: &[i64], produced: &[i64], cause: ActivationCause) {
+        self.schedules += 1;
+        self.elapsed += elapsed;
+        for (total, count) in self.consumed.iter_mut().zip(consumed) { *total += count; }
+        for (total, count) in self.produced.iter_mut().zip(produced) { *total += count; }
+        if cause.activated { self.activated += 1; }
+        if cause.frontier { self.frontier += 1; }
+        if cause.messages { self.messages += 1; }
+    }
+}
+
+// Measurements of operators, by address, each shared with the scope scheduling the operator.
+type ByAddress = BTreeMap<Vec<usize>, Rc<RefCell<OperatorMetrics>>>;
+
+/// A shared handle to the measurements of a worker's operators, by address.
+#[derive(Clone, Default)]
+pub struct Metrics {
+    operators: Rc<RefCell<ByAddress>>,
+}
+
+impl Metrics {
+    /// Starts measuring an operator, returning the measurements to update.
+    pub(crate) fn register(&self, id: usize, addr: Vec<usize>, name: String, inputs: usize, outputs: usize) -> Rc<RefCell<OperatorMetrics>> {
+        let metrics = Rc::new(RefCell::new(OperatorMetrics {
+            id,
+            addr: addr.clone(),
+            name,
+            schedules: 0,
+            elapsed: Duration::default(),
+            consumed: vec![0; inputs],
+            produced: vec![0; outputs],
+            activated: 0,
+            frontier: 0,
+            messages: 0,
+        }));
+        self.operators.borrow_mut().insert(addr, metrics.clone());
+        metrics
+    }
+    /// Forgets the measurements of a dataflow's operators.
+    pub(crate) fn forget(&self, dataflow: usize) {
+        self.operators.borrow_mut().retain(|addr, _| addr[0] != dataflow);
+    }
+    /// The measurements of all operators, in address order.
+    pub fn snapshot(&self) -> Vec<OperatorMetrics> {
+        self.operators.borrow().values().map(|metrics| metrics.borrow().clone()).collect()
+    }
+    /// The measurements of the `count` operators that spent longest scheduled, longest first.
+    pub fn top(&self, count: usize) -> Vec<OperatorMetrics> {
+        let mut operators = self.snapshot();
+        operators.sort_by_key(|metrics| std::cmp::Reverse(metrics.elapsed));
+        operators.truncate(count);
+        operators
+    }
+}
@@ -4,8 +4,14 @@ use std::rc::Rc;
 use std::cell::RefCell;
 
 pub mod activate;
+pub mod metrics;
+pub mod hooks;
+pub mod fuel;
+pub mod poison;
+pub mod watchdog;
+pub mod steps;
 
-pub use self::activate::{Activations, Activator, ActivateOnDrop, SyncActivator};
+pub use self::activate::{Activations, ActivationToken, Activator, ActivateOnDrop, SyncActivator};
 
 /// A type that can be scheduled.
 pub trait Schedule {
@@ -18,6 +24,8 @@ pub trait Schedule {
     /// The return value indicates whether `self` has outstanding
     /// work and would be upset if the computation terminated.
     fn schedule(&mut self) -> bool;
+    /// Appends the progress of the operators within `self`, if it is a scope.
+    fn progress_snapshot(&self, _operators: &mut Vec<crate::progress::snapshot::OperatorProgress>) { }
 }
 
 /// Methods for types which schedule fibers.
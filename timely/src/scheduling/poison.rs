@@ -0,0 +1,156 @@
+//! Isolation of operator panics to the dataflows in which they occur.
+//!
+//! By default a panic in an operator unwinds through the worker, and brings down the worker and
+//! with it the computation. A worker configured with
+//! [`Config::isolate_panics`](crate::worker::Config::isolate_panics) instead catches panics that
+//! unwind out of a dataflow's operators, and *poisons* the dataflow: it drops the dataflow, tells
+//! the other workers to drop it too, and reports the panic to the handler installed with
+//! [`Worker::on_dataflow_panic`](crate::worker::Worker::on_dataflow_panic). The other dataflows of
+//! the workers continue to run, which lets a process that hosts the dataflows of several tenants
+//! survive one bad dataflow.
+//!
+//! The other workers drop the dataflow as soon as they learn of the panic, rather than wait on
+//! capabilities that the panicking worker will never release, and report it to their handlers in
+//! turn. Probes of a dropped dataflow retract the frontier they observed, so that loops waiting on
+//! them complete. Panic isolation must be configured identically on all workers, as it allocates a
+//! channel that the workers use to tell each other of panics.
+//!
+//! As with [`Worker::drop_dataflow`](crate::worker::Worker::drop_dataflow), state that a dataflow
+//! shares with the rest of the program is not rolled back, and should be assumed to be in whatever
+//! state the panicking operator left it.
+//!
+//! # Examples
+//! ```
+//! use std::cell::RefCell;
+//! use std::rc::Rc;
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Inspect, Probe};
+//!
+//! let config = timely::Config {
+//!     communication: timely::CommunicationConfig::Thread,
+//!     worker: timely::WorkerConfig::default().isolate_panics(true),
+//! };
+//! timely::execute(config, |worker| {
+//!     let panics = Rc::new(RefCell::new(Vec::new()));
+//!     let sink = panics.clone();
+//!     worker.on_dataflow_panic(move |panic| sink.borrow_mut().push(panic.clone()));
+//!
+//!     let mut good = InputHandle::new();
+//!     let mut bad = InputHandle::new();
+//!     let good_probe = worker.dataflow_named("good", |scope| scope.input_from(&mut good).probe());
+//!     let bad_probe = worker.dataflow_named("bad", |scope| {
+//!         scope.input_from(&mut bad)
+//!              .inspect(|x: &u64| if *x == 3 { panic!("three is right out") })
+//!              .probe()
+//!     });
+//!
+//!     good.send(3);
+//!     bad.send(3);
+//!     good.advance_to(1);
+//!     bad.advance_to(1);
+//!     worker.step_while(|| good_probe.less_than(good.time()) || bad_probe.less_than(bad.time()));
+//!
+//!     // The bad dataflow is gone, and its panic reported; the good dataflow lives on.
+//!     assert_eq!(panics.borrow().len(), 1);
+//!     assert_eq!(panics.borrow()[0].name.as_deref(), Some("bad"));
+//!     assert_eq!(panics.borrow()[0].message, "three is right out");
+//!     assert_eq!(worker.installed_dataflows(), vec![0]);
+//!     assert_eq!(worker.poisoned_dataflows(), vec![1]);
+//! }).unwrap();
+//! ```
+
+use std::any::Any;
+use std::collections::BTreeSet;
+
+use crate::communication::{Message, Push, Pull};
+
+/// A panic that poisoned a dataflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataflowPanic {
+    /// The index of the poisoned dataflow.
+    pub dataflow: usize,
+    /// The name of the poisoned dataflow, if it was still installed at this worker.
+    pub name: Option<String>,
+    /// The index of the worker at which the panic occurred.
+    pub worker: usize,
+    /// The panic message, if the panic was raised with a string.
+    pub message: String,
+}
+
+/// The notice of a panic sent to other workers: the dataflow, the worker, and the message.
+type Notice = (usize, usize, String);
+
+/// A function to call with each panic that poisons a dataflow.
+type Handler = Box<dyn FnMut(&DataflowPanic)>;
+
+/// The poisoned dataflows of a worker, and a channel to inform other workers of panics.
+pub(crate) struct Poison {
+    index: usize,
+    pushers: Vec<Box<dyn Push<Message<Notice>>>>,
+    puller: Box<dyn Pull<Message<Notice>>>,
+    poisoned: BTreeSet<usize>,
+    handler: Option<Handler>,
+}
+
+impl Poison {
+    /// Poison state for worker `index`, and the endpoints of a channel to all workers.
+    pub(crate) fn new(index: usize, pushers: Vec<Box<dyn Push<Message<Notice>>>>, puller: Box<dyn Pull<Message<Notice>>>) -> Self {
+        Poison { index, pushers, puller, poisoned: BTreeSet::new(), handler: None }
+    }
+
+    /// Installs `handler`, to be called with each panic that poisons a dataflow.
+    pub(crate) fn set_handler(&mut self, handler: Handler) {
+        self.handler = Some(handler);
+    }
+
+    /// Marks `dataflow` as poisoned by a panic of this worker, and informs the other workers.
+    ///
+    /// Returns false if the dataflow was already poisoned.
+    pub(crate) fn panicked(&mut self, dataflow: usize, message: &str) -> bool {
+        if !self.poisoned.insert(dataflow) { return false; }
+        for (index, pusher) in self.pushers.iter_mut().enumerate() {
+            if index != self.index {
+                pusher.send(Message::from_typed((dataflow, self.index, message.to_owned())));
+                pusher.done();
+            }
+        }
+        true
+    }
+
+    /// Panics reported by other workers, for dataflows that were not yet poisoned.
+    pub(crate) fn receive(&mut self) -> Vec<Notice> {
+        let mut notices = Vec::new();
+        while let Some(message) = self.puller.recv() {
+            let (dataflow, worker, message) = message.to_typed();
+            if self.poisoned.insert(dataflow) {
+                notices.push((dataflow, worker, message));
+            }
+        }
+        notices
+    }
+
+    /// The poisoned dataflows, in increasing order.
+    pub(crate) fn poisoned(&self) -> Vec<usize> {
+        self.poisoned.iter().cloned().collect()
+    }
+
+    /// Calls the handler, if one is installed, with `panic`.
+    pub(crate) fn report(&mut self, panic: &DataflowPanic) {
+        if let Some(handler) = self.handler.as_mut() {
+            handler(panic);
+        }
+    }
+}
+
+/// The message of a panic payload, if it is a string.
+pub(crate) fn message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    }
+    else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    }
+    else {
+        "<non-string panic payload>".to_owned()
+    }
+}
@@ -0,0 +1,165 @@
+//! Measurements of the phases of worker steps.
+//!
+//! Each step of a worker receives messages from the communication layer, tidies its
+//! activations, then either parks or schedules its active dataflows, flushes its logs, and
+//! releases the messages its operators sent. The worker measures the time each step spends in
+//! each phase, and the time it spends parked, and accumulates the measurements into
+//! [`StepStats`], reported by [`Worker::step_stats`](crate::worker::Worker::step_stats).
+//!
+//! A worker that spends most of its time receiving, releasing, or parked is waiting on
+//! communication, whereas one that spends most of its time stepping dataflows is bound by the
+//! work of its operators. The worker times the phases of its steps only while a logger for the
+//! "timely/schedule" log stream is installed, and otherwise counts its steps and parks without
+//! reading its clock. As with the loggers of operators, the logger is found when the worker is
+//! built and when it builds a dataflow. Summaries of the measurements since the previous summary are also
+//! reported periodically to the "timely/schedule" log stream, as
+//! [`ScheduleSummaryEvent`](crate::logging::ScheduleSummaryEvent)s, as configured by
+//! [`Config::schedule_summary`](crate::worker::Config::schedule_summary).
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Map, Probe};
+//! use timely::logging::ScheduleSummaryEvent;
+//!
+//! timely::execute(timely::Config::thread(), |worker| {
+//!     worker.log_register().insert::<ScheduleSummaryEvent,_>("timely/schedule", |_time, _data| { });
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow(|scope| {
+//!         scope.input_from(&mut input).map(|x: u64| x + 1).probe()
+//!     });
+//!     input.send(0);
+//!     input.advance_to(1);
+//!     worker.step_while(|| probe.less_than(input.time()));
+//!
+//!     let stats = worker.step_stats();
+//!     assert!(stats.steps > 0);
+//!     assert!(stats.longest <= stats.phases.total());
+//! }).unwrap();
+//! ```
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::logging_core::clock::{Clock, Instant};
+
+/// The time spent in each phase of one or more worker steps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StepPhases {
+    /// Receiving messages from the communication layer, and activating their operators.
+    pub receive: Duration,
+    /// Organizing activations, and calling the idle handler.
+    pub tidy: Duration,
+    /// Scheduling active dataflows.
+    pub dataflows: Duration,
+    /// Reporting profiles, stalls, and channel counts, and flushing logs.
+    pub logging: Duration,
+    /// Releasing the messages sent by operators to the communication layer.
+    pub release: Duration,
+}
+
+impl StepPhases {
+    /// The time spent in all phases.
+    pub fn total(&self) -> Duration {
+        self.receive + self.tidy + self.dataflows + self.logging + self.release
+    }
+
+    fn add(&mut self, other: &StepPhases) {
+        self.receive += other.receive;
+        self.tidy += other.tidy;
+        self.dataflows += other.dataflows;
+        self.logging += other.logging;
+        self.release += other.release;
+    }
+}
+
+/// Accumulated measurements of worker steps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StepStats {
+    /// The number of steps.
+    pub steps: u64,
+    /// The number of steps in which the worker parked.
+    pub parks: u64,
+    /// The time spent parked, which is not included in any phase.
+    pub parked: Duration,
+    /// The time spent in each phase.
+    pub phases: StepPhases,
+    /// The longest time spent in the phases of any one step.
+    pub longest: Duration,
+}
+
+impl StepStats {
+    /// Accumulates the measurements of one step, which parked for `parked` if it parked.
+    pub(crate) fn record(&mut self, phases: &StepPhases, parked: Option<Duration>) {
+        self.steps += 1;
+        if let Some(parked) = parked {
+            self.parks += 1;
+            self.parked += parked;
+        }
+        self.phases.add(phases);
+        self.longest = std::cmp::max(self.longest, phases.total());
+    }
+}
+
+/// The measurements of a worker's steps, in total and since they were last logged.
+pub(crate) struct Steps {
+    /// Measurements of all steps.
+    pub(crate) total: StepStats,
+    /// Measurements of the steps since the last summary was logged.
+    pub(crate) recent: StepStats,
+    /// When the last summary was logged.
+    pub(crate) logged: Instant,
+}
+
+impl Steps {
+    /// Measurements of no steps, as of `now`.
+    pub(crate) fn new(now: Instant) -> Self {
+        Steps { total: Default::default(), recent: Default::default(), logged: now }
+    }
+
+    /// Accumulates the measurements of one step, which parked for `parked` if it parked.
+    pub(crate) fn record(&mut self, phases: &StepPhases, parked: Option<Duration>) {
+        self.total.record(phases, parked);
+        self.recent.record(phases, parked);
+    }
+
+    /// Takes the measurements since the last summary, if at least `period` has passed from then
+    /// until `now`.
+    pub(crate) fn summary(&mut self, period: Duration, now: Instant) -> Option<StepStats> {
+        if now.saturating_duration_since(self.logged) >= period {
+            self.logged = now;
+            Some(std::mem::take(&mut self.recent))
+        }
+        else {
+            None
+        }
+    }
+}
+
+/// Times consecutive phases of a step, reading its clock only if it has one.
+pub(crate) struct Stopwatch {
+    clock: Option<Arc<dyn Clock>>,
+    lap: Option<Instant>,
+}
+
+impl Stopwatch {
+    /// Starts the first phase, timed by `clock` if it is supplied.
+    pub(crate) fn start(clock: Option<Arc<dyn Clock>>) -> Self {
+        let lap = clock.as_ref().map(|clock| clock.now());
+        Stopwatch { clock, lap }
+    }
+
+    /// Ends the current phase and starts the next, returning the time the phase took, or zero
+    /// if the phases are not timed.
+    pub(crate) fn lap(&mut self) -> Duration {
+        match (self.clock.as_ref(), self.lap.as_mut()) {
+            (Some(clock), Some(lap)) => {
+                let now = clock.now();
+                let elapsed = now.saturating_duration_since(*lap);
+                *lap = now;
+                elapsed
+            },
+            _ => Duration::from_secs(0),
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! Detection of scopes whose progress has stalled.
+//!
+//! A worker configured with [`Config::watchdog`](crate::worker::Config::watchdog) notes, for each
+//! scope of its dataflows, when the frontier at some port of the scope last changed. A scope whose
+//! frontiers have not changed for the configured timeout is asked to describe itself, and if some
+//! of its operators hold capabilities or have messages in flight towards them, it reports them to
+//! the "timely/stalls" log stream as a [`StallEvent`](crate::logging::StallEvent). Each operator is
+//! described by its address, its input frontiers, the times of messages in flight to each input,
+//! and the times of capabilities held at each output, across all workers. A scope that remains
+//! stalled is reported again once per timeout.
+//!
+//! The operator holding the earliest capability, or with the earliest message in flight, is often
+//! the one that needs attention: an input that was not advanced, a capability retained by operator
+//! logic, or an operator waiting on a frontier that one of the others holds back.
+//!
+//! A dataflow that awaits input holds capabilities at its inputs, and cannot be told apart from a
+//! stalled one; the timeout should exceed the time the dataflow may reasonably await input. The
+//! logger for "timely/stalls" must be registered before dataflows are built.
+//!
+//! # Examples
+//! ```
+//! use std::cell::RefCell;
+//! use std::rc::Rc;
+//! use std::time::Duration;
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Map, Probe};
+//! use timely::logging::StallEvent;
+//!
+//! let mut config = timely::Config::thread();
+//! config.worker = config.worker.watchdog(Duration::from_millis(10));
+//! timely::execute(config, |worker| {
+//!     let stalls = Rc::new(RefCell::new(Vec::new()));
+//!     let sink = stalls.clone();
+//!     worker.log_register().insert::<StallEvent,_>("timely/stalls", move |_time, data| {
+//!         sink.borrow_mut().extend(data.drain(..).map(|(_, _, event)| event));
+//!     });
+//!
+//!     let mut input = InputHandle::new();
+//!     let probe = worker.dataflow::<u64,_,_>(|scope| {
+//!         scope.input_from(&mut input)
+//!              .map(|x: u64| x + 1)
+//!              .probe()
+//!     });
+//!
+//!     // The input is never advanced, and so the probe never passes zero.
+//!     input.send(0);
+//!     while stalls.borrow().is_empty() {
+//!         worker.step_or_park(None);
+//!     }
+//!     assert!(probe.less_equal(&0));
+//!
+//!     let stall = stalls.borrow()[0].clone();
+//!     assert!(stall.stalled_for >= Duration::from_millis(10));
+//!     let input = stall.operators.iter().find(|operator| operator.name == "Input").unwrap();
+//!     assert_eq!(input.capabilities, vec![vec!["0".to_string()]]);
+//!     let map = stall.operators.iter().find(|operator| operator.name == "Map").unwrap();
+//!     assert_eq!(map.frontiers, vec![vec!["0".to_string()]]);
+//! }).unwrap();
+//! ```
+
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::logging_core::clock::{self, Clock, Instant};
+use crate::scheduling::activate::Activations;
+
+/// When the frontiers of a scope last changed, and whether it has been asked to report a stall.
+pub(crate) struct ScopeWatch {
+    path: Vec<usize>,
+    advanced: Instant,
+    reported: Instant,
+    requested: Option<Duration>,
+    clock: Arc<dyn Clock>,
+}
+
+impl ScopeWatch {
+    /// Notes that some frontier of the scope changed.
+    pub(crate) fn advanced(&mut self) {
+        self.advanced = self.clock.now();
+    }
+    /// The time for which the scope has stalled, if it has been asked to report the stall.
+    pub(crate) fn take_request(&mut self) -> Option<Duration> {
+        self.requested.take()
+    }
+    /// Indicates whether the scope has been asked to report a stall.
+    pub(crate) fn is_requested(&self) -> bool {
+        self.requested.is_some()
+    }
+    // The time at which the scope should next be checked.
+    fn deadline(&self, timeout: Duration) -> Instant {
+        std::cmp::max(self.advanced, self.reported) + timeout
+    }
+}
+
+/// A shared handle to the scopes of a worker watched for stalls.
+#[derive(Clone)]
+pub struct Watchdog {
+    scopes: Rc<RefCell<Vec<Weak<RefCell<ScopeWatch>>>>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Watchdog::new(clock::system())
+    }
+}
+
+impl Watchdog {
+    /// A watchdog measuring stalls with `clock`.
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Watchdog { scopes: Default::default(), clock }
+    }
+
+    /// Starts watching the scope at `path`, returning the state for the scope to update.
+    pub(crate) fn register(&self, path: Vec<usize>) -> Rc<RefCell<ScopeWatch>> {
+        let now = self.clock.now();
+        let watch = Rc::new(RefCell::new(ScopeWatch { path, advanced: now, reported: now, requested: None, clock: self.clock.clone() }));
+        self.scopes.borrow_mut().push(Rc::downgrade(&watch));
+        watch
+    }
+
+    /// Asks each scope stalled for at least `timeout` to report, by activating it.
+    ///
+    /// Scopes that have been dropped are forgotten.
+    pub(crate) fn check(&self, timeout: Duration, activations: &RefCell<Activations>) {
+        let now = self.clock.now();
+        self.scopes.borrow_mut().retain(|watch| {
+            if let Some(watch) = watch.upgrade() {
+                let mut watch = watch.borrow_mut();
+                if watch.deadline(timeout) <= now {
+                    watch.requested = Some(now.duration_since(watch.advanced));
+                    watch.reported = now;
+                    activations.borrow_mut().activate(&watch.path[..]);
+                }
+                true
+            }
+            else {
+                false
+            }
+        });
+    }
+
+    /// The time until some scope should next be checked, if any scopes are watched.
+    pub(crate) fn until_next(&self, timeout: Duration) -> Option<Duration> {
+        let now = self.clock.now();
+        self.scopes
+            .borrow()
+            .iter()
+            .filter_map(|watch| watch.upgrade())
+            .map(|watch| watch.borrow().deadline(timeout))
+            .min()
+            .map(|deadline| if deadline > now { deadline.duration_since(now) } else { Duration::new(0, 0) })
+    }
+}
@@ -0,0 +1,204 @@
+//! Configuration read from TOML text and environment variables.
+//!
+//! The format is documented at [`Config::from_toml`](crate::execute::Config::from_toml).
+
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::communication::compression::Compression;
+use crate::communication::networking::{PeerAddress, Transport};
+use crate::execute::Config;
+use crate::worker::ProgressMode;
+use crate::{CommunicationConfig, WorkerConfig};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Settings {
+    threads: Option<usize>,
+    processes: Option<usize>,
+    process: Option<usize>,
+    hosts: Option<Vec<String>>,
+    hostfile: Option<PathBuf>,
+    bind: Option<String>,
+    transport: Option<String>,
+    compression: Option<String>,
+    report: Option<bool>,
+    max_record_bytes: Option<usize>,
+    #[serde(default)]
+    worker: WorkerSettings,
+    #[serde(default)]
+    logging: LoggingSettings,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorkerSettings {
+    progress_mode: Option<String>,
+    batch_size: Option<usize>,
+    track_capabilities: Option<bool>,
+    trace_lineage: Option<bool>,
+    validate_progress: Option<bool>,
+    isolate_panics: Option<bool>,
+    watchdog_ms: Option<u64>,
+    log_file_capacity: Option<usize>,
+    log_file_period_ms: Option<u64>,
+    #[serde(default)]
+    params: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LoggingSettings {
+    worker: Option<String>,
+    communication: Option<String>,
+}
+
+// Parses the value of the environment variable `name`.
+fn parse<T: FromStr>(name: &str, value: &str) -> Result<T, String> where T::Err: ToString {
+    value.parse().map_err(|e: T::Err| format!("invalid value for `{}`: {}", name, e.to_string()))
+}
+
+// Parses the value of the field `name`, which holds the name of one of several choices.
+fn choose<T: FromStr<Err=String>>(name: &str, value: Option<&String>, default: T) -> Result<T, String> {
+    match value {
+        Some(value) => value.parse().map_err(|e| format!("invalid value for `{}`: {}", name, e)),
+        None => Ok(default),
+    }
+}
+
+impl Settings {
+    /// Reads settings from TOML text.
+    pub(crate) fn from_toml(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| format!("invalid configuration: {}", e))
+    }
+
+    /// Overrides settings with those of environment variables in `vars`, ignoring unrelated variables.
+    pub(crate) fn apply_vars<I: IntoIterator<Item=(String, String)>>(&mut self, vars: I) -> Result<(), String> {
+        for (name, value) in vars {
+            match name.as_str() {
+                "TIMELY_THREADS" => self.threads = Some(parse(&name, &value)?),
+                "TIMELY_PROCESSES" => self.processes = Some(parse(&name, &value)?),
+                "TIMELY_PROCESS" => self.process = Some(parse(&name, &value)?),
+                "TIMELY_HOSTS" => self.hosts = Some(value.split(',').map(|host| host.trim().to_owned()).collect()),
+                "TIMELY_HOSTFILE" => self.hostfile = Some(value.into()),
+                "TIMELY_BIND" => self.bind = Some(value),
+                "TIMELY_TRANSPORT" => self.transport = Some(value),
+                "TIMELY_COMPRESSION" => self.compression = Some(value),
+                "TIMELY_REPORT" => self.report = Some(parse(&name, &value)?),
+                "TIMELY_MAX_RECORD_BYTES" => self.max_record_bytes = Some(parse(&name, &value)?),
+                "TIMELY_PROGRESS_MODE" => self.worker.progress_mode = Some(value),
+                "TIMELY_BATCH_SIZE" => self.worker.batch_size = Some(parse(&name, &value)?),
+                "TIMELY_WORKER_LOG_ADDR" => self.logging.worker = Some(value),
+                "TIMELY_COMM_LOG_ADDR" => self.logging.communication = Some(value),
+                _ => { },
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the settings, and assembles the configuration they describe.
+    pub(crate) fn into_config(self) -> Result<Config, String> {
+        let threads = self.threads.unwrap_or(1);
+        let processes = self.processes.unwrap_or(1);
+        let process = self.process.unwrap_or(0);
+        if threads == 0 {
+            return Err("invalid value for `threads`: must be at least 1".to_owned());
+        }
+        if processes == 0 {
+            return Err("invalid value for `processes`: must be at least 1".to_owned());
+        }
+        if process >= processes {
+            return Err(format!("invalid value for `process`: {} is not less than `processes` ({})", process, processes));
+        }
+        let transport = choose("transport", self.transport.as_ref(), Transport::Tcp)?;
+        let compression = choose("compression", self.compression.as_ref(), Compression::None)?;
+        if self.max_record_bytes == Some(0) {
+            return Err("invalid value for `max_record_bytes`: must be at least 1".to_owned());
+        }
+
+        let peers = match (self.hosts, self.hostfile) {
+            (Some(_), Some(_)) => return Err("invalid value for `hostfile`: conflicts with `hosts`".to_owned()),
+            (Some(hosts), None) => {
+                hosts.iter().map(|host| host.parse::<PeerAddress>()).collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("invalid value for `hosts`: {}", e))?
+            },
+            (None, Some(path)) => {
+                let file = std::fs::File::open(&path).map_err(|e| format!("invalid value for `hostfile`: {}: {}", path.display(), e))?;
+                let mut peers = Vec::new();
+                for line in std::io::BufReader::new(file).lines().take(processes) {
+                    let line = line.map_err(|e| format!("invalid value for `hostfile`: {}: {}", path.display(), e))?;
+                    peers.push(line.parse::<PeerAddress>().map_err(|e| format!("invalid value for `hostfile`: {}: {}", path.display(), e))?);
+                }
+                peers
+            },
+            (None, None) => (0 .. processes).map(|index| PeerAddress::new(format!("localhost:{}", 2101 + index))).collect(),
+        };
+        if peers.len() != processes {
+            return Err(format!("invalid value for `hosts`: {} addresses for {} `processes`", peers.len(), processes));
+        }
+
+        let communication = if processes > 1 {
+            let mut communication = CommunicationConfig::cluster(threads, process, peers);
+            if let CommunicationConfig::Cluster { ref mut bind, ref mut report, compression: ref mut c, transport: ref mut t, ref mut max_record, .. } = communication {
+                if self.bind.is_some() {
+                    *bind = self.bind;
+                }
+                *report = self.report.unwrap_or(false);
+                *c = compression;
+                *t = transport;
+                *max_record = self.max_record_bytes;
+            }
+            communication
+        } else if threads > 1 {
+            CommunicationConfig::Process(threads)
+        } else {
+            CommunicationConfig::Thread
+        };
+
+        let settings = self.worker;
+        let mut worker = WorkerConfig::default()
+            .progress_mode(choose("worker.progress_mode", settings.progress_mode.as_ref(), ProgressMode::Eager)?)
+            .track_capabilities(settings.track_capabilities.unwrap_or(false))
+            .trace_lineage(settings.trace_lineage.unwrap_or(false))
+            .validate_progress(settings.validate_progress.unwrap_or(false))
+            .isolate_panics(settings.isolate_panics.unwrap_or(false));
+        if let Some(size) = settings.batch_size {
+            if size == 0 {
+                return Err("invalid value for `worker.batch_size`: must be at least 1".to_owned());
+            }
+            worker = worker.batch_size(size);
+        }
+        if let Some(millis) = settings.watchdog_ms {
+            if millis == 0 {
+                return Err("invalid value for `worker.watchdog_ms`: must be at least 1".to_owned());
+            }
+            worker = worker.watchdog(Duration::from_millis(millis));
+        }
+        match (settings.log_file_capacity, settings.log_file_period_ms) {
+            (Some(capacity), Some(millis)) => worker = worker.log_file_buffering(capacity, Duration::from_millis(millis)),
+            (Some(_), None) => return Err("invalid value for `worker.log_file_capacity`: requires `worker.log_file_period_ms`".to_owned()),
+            (None, Some(_)) => return Err("invalid value for `worker.log_file_period_ms`: requires `worker.log_file_capacity`".to_owned()),
+            (None, None) => { },
+        }
+        if let Some(address) = self.logging.worker {
+            worker = worker.worker_log_address(address);
+        }
+        if let Some(address) = self.logging.communication {
+            worker = worker.communication_log_address(address);
+        }
+        for (key, value) in settings.params {
+            match value {
+                toml::Value::String(value) => { worker.set(key, value); },
+                toml::Value::Integer(value) => { worker.set(key, value); },
+                toml::Value::Float(value) => { worker.set(key, value); },
+                toml::Value::Boolean(value) => { worker.set(key, value); },
+                other => return Err(format!("invalid value for `worker.params.{}`: unsupported {}", key, other.type_str())),
+            }
+        }
+
+        Ok(Config { communication, worker })
+    }
+}
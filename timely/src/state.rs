@@ -0,0 +1,300 @@
+//! Keyed operator state, with pluggable storage.
+//!
+//! An [`OperatorState`] holds the keyed state of an operator, and is constructed by the operator's
+//! constructor in any of the generic operator builders. It offers `get`, `put`, and `remove` of
+//! values by key, and iteration in key order, over a [`Backend`]. The default backend keeps the
+//! state in memory; other backends may keep it elsewhere, for example spilled to disk, by
+//! implementing the `Backend` trait.
+//!
+//! The operator reports its input frontier to its state with [`OperatorState::advance_to`].
+//! Compaction registered with [`OperatorState::on_frontier`] then runs whenever the frontier
+//! changes, and state registered for checkpoints with [`OperatorState::checkpointed`] saves its
+//! contents for each checkpoint the frontier has reached. Operators should report their frontier
+//! before they apply records at times the frontier has passed, as described in the
+//! [`checkpoint`](crate::checkpoint) module.
+//!
+//! Each key may also have a timer, set with [`OperatorState::set_timer`], which fires once the
+//! reported frontier has passed its time. The operator collects fired timers with
+//! [`OperatorState::fired_timers`], for example to evict idle keys or to close sessions. Timers are
+//! indexed by time, so that finding those that fired examines each distinct time rather than
+//! each key.
+//!
+//! # Examples
+//! ```
+//! use timely::dataflow::operators::{ToStream, Map, Inspect};
+//! use timely::dataflow::operators::generic::operator::Operator;
+//! use timely::dataflow::channels::pact::Exchange;
+//! use timely::state::OperatorState;
+//!
+//! timely::example(|scope| {
+//!     (0..10u64).to_stream(scope)
+//!               .map(|x| (x % 3, x))
+//!               .unary_frontier(Exchange::new(|x: &(u64, u64)| x.0), "Sum", |_cap, _info| {
+//!                   let mut state = OperatorState::new();
+//!                   move |input, output| {
+//!                       state.advance_to(&input.frontier().frontier()).expect("failed to save state");
+//!                       input.for_each(|time, data| {
+//!                           let mut session = output.session(&time);
+//!                           for (key, val) in data.iter() {
+//!                               let sum = state.get(key).unwrap_or(0) + val;
+//!                               state.put(*key, sum);
+//!                               session.give((*key, sum));
+//!                           }
+//!                       });
+//!                   }
+//!               })
+//!               .inspect(|x| println!("sum: {:?}", x));
+//! });
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Result;
+use std::ops::Bound;
+
+use crate::ExchangeData;
+use crate::dataflow::Scope;
+use crate::progress::{Antichain, Timestamp};
+
+/// Storage of keyed state.
+pub trait Backend<K, V> {
+    /// The value associated with `key`, if any.
+    fn get(&self, key: &K) -> Option<V>;
+    /// Associates `val` with `key`, replacing any previous value.
+    fn put(&mut self, key: K, val: V);
+    /// Removes and returns the value associated with `key`, if any.
+    fn remove(&mut self, key: &K) -> Option<V>;
+    /// The key-value pairs with keys between `lower` and `upper`, in key order.
+    fn range<'a>(&'a self, lower: Bound<&K>, upper: Bound<&K>) -> Box<dyn Iterator<Item=(K, V)>+'a>;
+    /// The number of keys with values.
+    fn len(&self) -> usize;
+    /// Returns `true` if no key has a value.
+    fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+/// Keyed state held in memory, in a `BTreeMap`.
+pub struct MemoryBackend<K, V> {
+    map: BTreeMap<K, V>,
+}
+
+impl<K: Ord, V> Default for MemoryBackend<K, V> {
+    fn default() -> Self {
+        MemoryBackend { map: BTreeMap::new() }
+    }
+}
+
+impl<K: Ord+Clone, V: Clone> Backend<K, V> for MemoryBackend<K, V> {
+    fn get(&self, key: &K) -> Option<V> { self.map.get(key).cloned() }
+    fn put(&mut self, key: K, val: V) { self.map.insert(key, val); }
+    fn remove(&mut self, key: &K) -> Option<V> { self.map.remove(key) }
+    fn range<'a>(&'a self, lower: Bound<&K>, upper: Bound<&K>) -> Box<dyn Iterator<Item=(K, V)>+'a> {
+        Box::new(self.map.range::<K, _>((lower, upper)).map(|(k, v)| (k.clone(), v.clone())))
+    }
+    fn len(&self) -> usize { self.map.len() }
+}
+
+/// Logic compacting a backend to the frontier it is given.
+type Compaction<T, B> = Box<dyn FnMut(&[T], &mut B)>;
+
+/// The keyed state of an operator.
+pub struct OperatorState<T: Timestamp, K, V, B: Backend<K, V> = MemoryBackend<K, V>> {
+    backend: B,
+    frontier: Option<Antichain<T>>,
+    compaction: Option<Compaction<T, B>>,
+    checkpoint: Option<crate::checkpoint::StateHandle<T>>,
+    timers: Timers<T, K>,
+    phantom: std::marker::PhantomData<(K, V)>,
+}
+
+/// Timers of keys, indexed both by key and by time.
+struct Timers<T, K> {
+    by_key: BTreeMap<K, T>,
+    by_time: BTreeMap<T, BTreeSet<K>>,
+}
+
+impl<T: Timestamp, K: Ord+Clone, V: Clone> OperatorState<T, K, V> {
+    /// Allocates new state held in memory.
+    pub fn new() -> Self {
+        Self::with_backend(MemoryBackend::default())
+    }
+}
+
+impl<T: Timestamp, K: Ord+Clone, V: Clone> Default for OperatorState<T, K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Timestamp, K, V, B: Backend<K, V>> OperatorState<T, K, V, B> {
+    /// Allocates new state held by `backend`.
+    pub fn with_backend(backend: B) -> Self {
+        OperatorState {
+            backend,
+            frontier: None,
+            compaction: None,
+            checkpoint: None,
+            timers: Timers { by_key: BTreeMap::new(), by_time: BTreeMap::new() },
+            phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// The value associated with `key`, if any.
+    pub fn get(&self, key: &K) -> Option<V> { self.backend.get(key) }
+    /// Returns `true` if a value is associated with `key`.
+    pub fn contains_key(&self, key: &K) -> bool { self.backend.get(key).is_some() }
+    /// Associates `val` with `key`, replacing any previous value.
+    pub fn put(&mut self, key: K, val: V) { self.backend.put(key, val) }
+    /// Removes and returns the value associated with `key`, if any.
+    pub fn remove(&mut self, key: &K) -> Option<V> { self.backend.remove(key) }
+    /// The key-value pairs with keys between `lower` and `upper`, in key order.
+    pub fn range(&self, lower: Bound<&K>, upper: Bound<&K>) -> impl Iterator<Item=(K, V)>+'_ {
+        self.backend.range(lower, upper)
+    }
+    /// All key-value pairs, in key order.
+    pub fn iter(&self) -> impl Iterator<Item=(K, V)>+'_ {
+        self.backend.range(Bound::Unbounded, Bound::Unbounded)
+    }
+    /// The number of keys with values.
+    pub fn len(&self) -> usize { self.backend.len() }
+    /// Returns `true` if no key has a value.
+    pub fn is_empty(&self) -> bool { self.backend.is_empty() }
+    /// The backend holding the state.
+    pub fn backend(&mut self) -> &mut B { &mut self.backend }
+
+    /// Calls `compact` with the frontier and backend whenever the frontier changes.
+    ///
+    /// Compaction may, for example, remove state for times the frontier has passed.
+    pub fn on_frontier<F: FnMut(&[T], &mut B)+'static>(&mut self, compact: F) {
+        self.compaction = Some(Box::new(compact));
+    }
+
+    /// Reports the operator's input frontier.
+    ///
+    /// This saves the state for the checkpoints the frontier has reached, and then, if the
+    /// frontier changed, calls the compaction registered with `on_frontier`.
+    pub fn advance_to(&mut self, frontier: &[T]) -> Result<()> where K: ExchangeData, V: ExchangeData {
+        if let Some(checkpoint) = self.checkpoint.as_ref() {
+            while let Some((id, _)) = checkpoint.next_ready(frontier) {
+                let contents = self.backend.range(Bound::Unbounded, Bound::Unbounded).collect::<Vec<_>>();
+                checkpoint.save(id, &contents)?;
+            }
+        }
+        if self.frontier.as_ref().map(|f| f.elements() != frontier).unwrap_or(true) {
+            if let Some(compact) = self.compaction.as_mut() {
+                compact(frontier, &mut self.backend);
+            }
+            self.frontier = Some(frontier.iter().cloned().collect());
+        }
+        Ok(())
+    }
+}
+
+impl<T: Timestamp, K: Ord+Clone, V, B: Backend<K, V>> OperatorState<T, K, V, B> {
+    /// Sets the timer of `key` to fire at `time`, replacing any timer the key already has.
+    ///
+    /// The timer fires once the frontier reported with `advance_to` has passed `time`. To produce
+    /// output at `time` when the timer fires, the operator must retain a capability for it, for
+    /// example by including [`timer_times`](OperatorState::timer_times) when it downgrades its
+    /// capabilities.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay, Inspect, CapabilitySet};
+    /// use timely::dataflow::operators::generic::operator::Operator;
+    /// use timely::dataflow::channels::pact::Exchange;
+    /// use timely::state::OperatorState;
+    ///
+    /// timely::example(|scope| {
+    ///     // Counts the records of each key, and reports the count once the key has been idle for 5.
+    ///     (0..10u64).to_stream(scope)
+    ///               .delay(|x, _| *x)
+    ///               .unary_frontier(Exchange::new(|x: &u64| x % 2), "Sessions", |cap, _info| {
+    ///                   let mut capabilities = CapabilitySet::from_elem(cap);
+    ///                   let mut state = OperatorState::new();
+    ///                   move |input, output| {
+    ///                       input.for_each(|time, data| {
+    ///                           for x in data.iter() {
+    ///                               let key = x % 2;
+    ///                               state.put(key, state.get(&key).unwrap_or(0) + 1);
+    ///                               state.set_timer(key, time.time() + 5);
+    ///                           }
+    ///                       });
+    ///                       state.advance_to(&input.frontier().frontier()).expect("failed to save state");
+    ///                       for (key, time) in state.fired_timers() {
+    ///                           let count = state.remove(&key).unwrap();
+    ///                           output.session(&capabilities.delayed(&time)).give((key, count));
+    ///                       }
+    ///                       let frontier = input.frontier().frontier().to_vec();
+    ///                       capabilities.downgrade(frontier.iter().chain(state.timer_times()));
+    ///                   }
+    ///               })
+    ///               .inspect(|x| assert_eq!(x.1, 5));
+    /// });
+    /// ```
+    pub fn set_timer(&mut self, key: K, time: T) {
+        self.cancel_timer(&key);
+        self.timers.by_time.entry(time.clone()).or_default().insert(key.clone());
+        self.timers.by_key.insert(key, time);
+    }
+
+    /// Cancels the timer of `key`, returning its time if it had one.
+    pub fn cancel_timer(&mut self, key: &K) -> Option<T> {
+        let time = self.timers.by_key.remove(key)?;
+        let keys = self.timers.by_time.get_mut(&time).expect("timer without time");
+        keys.remove(key);
+        if keys.is_empty() {
+            self.timers.by_time.remove(&time);
+        }
+        Some(time)
+    }
+
+    /// The time of the timer of `key`, if it has one.
+    pub fn timer(&self, key: &K) -> Option<&T> {
+        self.timers.by_key.get(key)
+    }
+
+    /// The distinct times of timers that have not fired.
+    pub fn timer_times(&self) -> impl Iterator<Item=&T>+'_ {
+        self.timers.by_time.keys()
+    }
+
+    /// Removes and returns the timers that the frontier reported with `advance_to` has passed,
+    /// as `(key, time)` pairs in order of their times.
+    ///
+    /// No timers fire before the operator has reported a frontier.
+    pub fn fired_timers(&mut self) -> Vec<(K, T)> {
+        let frontier = match self.frontier.as_ref() {
+            Some(frontier) => frontier,
+            None => return Vec::new(),
+        };
+        let passed = self.timers.by_time.keys()
+            .filter(|time| !frontier.less_equal(time))
+            .cloned()
+            .collect::<Vec<_>>();
+        let mut fired = Vec::new();
+        for time in passed {
+            for key in self.timers.by_time.remove(&time).expect("timer time vanished") {
+                self.timers.by_key.remove(&key);
+                fired.push((key, time.clone()));
+            }
+        }
+        fired
+    }
+}
+
+impl<T: Timestamp, K: ExchangeData, V: ExchangeData, B: Backend<K, V>> OperatorState<T, K, V, B> {
+    /// Registers the state as `name` in checkpoints of the dataflow of `scope`, for the operator at
+    /// `address`, and loads any state restored from a checkpoint.
+    ///
+    /// See [`checkpoint::StateHandle::new`](crate::checkpoint::StateHandle::new) for the
+    /// requirements on `scope` and `name`.
+    pub fn checkpointed<G: Scope<Timestamp=T>>(mut self, scope: &G, name: &str, address: &[usize]) -> Result<Self> {
+        let checkpoint = crate::checkpoint::StateHandle::new(scope, name, address);
+        if let Some(contents) = checkpoint.restored::<Vec<(K, V)>>()? {
+            for (key, val) in contents {
+                self.backend.put(key, val);
+            }
+        }
+        self.checkpoint = Some(checkpoint);
+        Ok(self)
+    }
+}
@@ -5,6 +5,29 @@ use crate::dataflow::{InputHandle, ProbeHandle};
 use crate::worker::Worker;
 
 /// A re-usable barrier synchronization mechanism.
+///
+/// Each worker advances the input of a small dataflow as it reaches the barrier, and the barrier
+/// is reached once the probe of that dataflow reports that all workers have advanced it. The
+/// barrier may be used between dataflow installations, or between rounds of a driver loop.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use timely::synchronization::Barrier;
+///
+/// let arrived = Arc::new(AtomicUsize::new(0));
+/// timely::execute(timely::Config::process(3), move |worker| {
+///     let mut barrier = Barrier::new(worker);
+///     for round in 0 .. 3 {
+///         std::thread::sleep(std::time::Duration::from_millis(worker.index() as u64));
+///         arrived.fetch_add(1, Ordering::SeqCst);
+///         barrier.wait();
+///         assert!(arrived.load(Ordering::SeqCst) >= 3 * (round + 1));
+///     }
+/// }).unwrap();
+/// ```
 pub struct Barrier<A: Allocate> {
     input: InputHandle<usize, ()>,
     probe: ProbeHandle<usize>,
@@ -26,11 +49,13 @@ impl<A: Allocate> Barrier<A> {
     /// Blocks until all other workers have reached this barrier.
     ///
     /// This method does *not* block dataflow execution, which continues
-    /// to execute while we await the arrival of the other workers.
+    /// to execute while we await the arrival of the other workers. The
+    /// worker parks when it has nothing to do, and is woken by the arrival
+    /// of progress updates from other workers.
     pub fn wait(&mut self) {
         self.advance();
         while !self.reached() {
-            self.worker.step();
+            self.worker.step_or_park(None);
         }
     }
 
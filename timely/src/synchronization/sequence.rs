@@ -2,10 +2,11 @@
 
 use std::rc::Rc;
 use std::cell::RefCell;
-use std::time::{Instant, Duration};
+use std::time::Duration;
 use std::collections::VecDeque;
 
 use crate::{communication::Allocate, ExchangeData, PartialOrder};
+use crate::logging_core::clock::Instant;
 use crate::scheduling::Scheduler;
 use crate::worker::Worker;
 use crate::dataflow::channels::pact::Exchange;
@@ -45,7 +46,9 @@ impl CatchupActivator {
 /// Orders elements inserted across all workers.
 ///
 /// A Sequencer allows each worker to insert into a consistent ordered
-/// sequence that is seen by all workers in the same order.
+/// sequence that is seen by all workers in the same order. Elements are
+/// ordered by the time at which they were sent, then by the index of the
+/// worker that sent them, and then in the order that worker pushed them.
 pub struct Sequencer<T> {
     activator: Rc<RefCell<Option<CatchupActivator>>>,
     send: Rc<RefCell<VecDeque<T>>>, // proposed items.
@@ -109,11 +112,15 @@ impl<T: ExchangeData> Sequencer<T> {
         let activator_source = activator.clone();
         let activator_sink = activator.clone();
 
+        // the worker's clock, by which `timer` has elapsed.
+        let clock = worker.clock().clone();
+
         // build a dataflow used to serialize and circulate commands
         worker.dataflow::<Duration,_,_>(move |dataflow| {
 
             let scope = dataflow.clone();
             let peers = dataflow.peers();
+            let index = dataflow.index();
 
             let mut recvd = Vec::new();
             let mut vector = Vec::new();
@@ -144,14 +151,14 @@ impl<T: ExchangeData> Sequencer<T> {
                         let capability = capability.as_mut().expect("Capability unavailable");
 
                         // downgrade capability to current time.
-                        capability.downgrade(&timer.elapsed());
+                        capability.downgrade(&clock.elapsed(timer));
 
                         // drain and broadcast `send`.
                         let mut session = output.session(&capability);
                         let mut borrow = send_queue.borrow_mut();
                         for element in borrow.drain(..) {
                             for worker_index in 0 .. peers {
-                                session.give((worker_index, counter, element.clone()));
+                                session.give((worker_index, index, counter, element.clone()));
                             }
                             counter += 1;
                         }
@@ -172,7 +179,7 @@ impl<T: ExchangeData> Sequencer<T> {
                 }
             })
             .sink(
-                Exchange::new(|x: &(usize, usize, T)| x.0 as u64),
+                Exchange::new(|x: &(usize, usize, usize, T)| x.0 as u64),
                 "SequenceOutput",
                 move |input| {
 
@@ -181,8 +188,9 @@ impl<T: ExchangeData> Sequencer<T> {
                         data.swap(&mut vector);
 
                         recvd.reserve(vector.len());
-                        for (worker, counter, element) in vector.drain(..) {
-                            recvd.push(((time.time().clone(), worker, counter), element));
+                        // order by time, then by sending worker and its sequence number.
+                        for (_, sender, counter, element) in vector.drain(..) {
+                            recvd.push(((*time.time(), sender, counter), element));
                         }
                     });
 
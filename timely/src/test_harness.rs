@@ -0,0 +1,350 @@
+//! Helpers for property tests of operators.
+//!
+//! An operator should produce the same results at each time however its input arrives: in one
+//! batch or many, with the input frontier advancing in large or small jumps, and with the worker
+//! stepping between records or not at all. A [`Harness`] builds a dataflow around an operator
+//! in a single-threaded worker, plays a [`Script`] of records, frontier advances, and worker steps
+//! into it, and collects its output into [`Results`], which checks that no output arrives at a time
+//! its frontier has passed. [`Script::generate`] produces scripts from a seed, so that a test may
+//! play the same records under many schedules and compare the results at each epoch against a
+//! model of the operator.
+//!
+//! Timestamps are `u64` epochs, and records are sent at the epoch their script has advanced to.
+//!
+//! # Examples
+//! ```
+//! use std::collections::BTreeMap;
+//! use timely::dataflow::operators::Map;
+//! use timely::test_harness::{Harness, Script};
+//!
+//! let records = (0 .. 100u64).map(|x| (x / 10, x)).collect::<Vec<_>>();
+//! let mut expected = BTreeMap::new();
+//! for (epoch, x) in records.iter() {
+//!     expected.entry(*epoch).or_insert_with(Vec::new).push(x + 1);
+//! }
+//!
+//! for seed in 0 .. 10 {
+//!     let script = Script::generate(records.clone(), seed);
+//!     let mut harness = Harness::new(|stream| stream.map(|x| x + 1));
+//!     harness.play(&script);
+//!     harness.finish().assert_epochs(expected.clone());
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::rc::Rc;
+
+use crate::Data;
+use crate::communication::allocator::Thread;
+use crate::dataflow::channels::pact::Pipeline;
+use crate::dataflow::operators::{Input, Operator};
+use crate::dataflow::scopes::Child;
+use crate::dataflow::{InputHandle, Scope, Stream};
+use crate::progress::Timestamp;
+use crate::progress::frontier::Antichain;
+use crate::worker::Worker;
+
+/// The scope in which a [`Harness`] builds its dataflow.
+pub type HarnessScope<'a> = Child<'a, Worker<Thread>, u64>;
+
+/// An action of a [`Script`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step<D> {
+    /// Sends a record at the current epoch.
+    Send(D),
+    /// Advances the input to an epoch, which must not precede the current epoch.
+    AdvanceTo(u64),
+    /// Steps the worker.
+    Step,
+}
+
+/// A sequence of records, frontier advances, and worker steps to play into a dataflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script<D> {
+    steps: Vec<Step<D>>,
+}
+
+impl<D> Script<D> {
+    /// Creates an empty script.
+    pub fn new() -> Self {
+        Script { steps: Vec::new() }
+    }
+
+    /// Appends the sending of `record` at the current epoch.
+    pub fn send(mut self, record: D) -> Self {
+        self.steps.push(Step::Send(record));
+        self
+    }
+
+    /// Appends an advance of the input to `epoch`.
+    pub fn advance_to(mut self, epoch: u64) -> Self {
+        self.steps.push(Step::AdvanceTo(epoch));
+        self
+    }
+
+    /// Appends a step of the worker.
+    pub fn step(mut self) -> Self {
+        self.steps.push(Step::Step);
+        self
+    }
+
+    /// Generates a script that sends each record at its epoch, determined by `seed`.
+    ///
+    /// The records are sent in order of their epochs, and otherwise in the order supplied. The
+    /// seed determines how far the input advances towards the epoch of the next record, which
+    /// may take several advances or one, and where the worker steps between records and advances.
+    pub fn generate(mut records: Vec<(u64, D)>, seed: u64) -> Self {
+        records.sort_by_key(|(epoch, _)| *epoch);
+        let mut state = seed;
+        let mut script = Script::new();
+        let mut epoch = 0;
+        let mut records = records.into_iter().peekable();
+        while let Some((next, _)) = records.peek() {
+            let next = *next;
+            let choice = splitmix64(&mut state);
+            script = match choice % 4 {
+                0 => script.step(),
+                _ if next == epoch => script.send(records.next().unwrap().1),
+                _ => {
+                    epoch += 1 + (choice >> 2) % (next - epoch);
+                    script.advance_to(epoch)
+                }
+            };
+        }
+        script
+    }
+
+    /// The actions of the script.
+    pub fn steps(&self) -> &[Step<D>] {
+        &self.steps[..]
+    }
+
+    /// The records of the script, each with the epoch at which it is sent.
+    pub fn records(&self) -> Vec<(u64, D)> where D: Clone {
+        let mut epoch = 0;
+        let mut records = Vec::new();
+        for step in self.steps.iter() {
+            match step {
+                Step::Send(record) => records.push((epoch, record.clone())),
+                Step::AdvanceTo(next) => epoch = *next,
+                Step::Step => { },
+            }
+        }
+        records
+    }
+}
+
+impl<D> Default for Script<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The records a stream produced at each time, shared with the operator collecting them.
+pub struct Results<T: Timestamp, D> {
+    state: Rc<RefCell<ResultsState<T, D>>>,
+}
+
+struct ResultsState<T: Timestamp, D> {
+    epochs: BTreeMap<T, Vec<D>>,
+    frontier: Antichain<T>,
+}
+
+impl<T: Timestamp, D: Clone> Results<T, D> {
+    /// The frontier of the collected stream, as of the collecting operator's last invocation.
+    pub fn frontier(&self) -> Antichain<T> {
+        self.state.borrow().frontier.clone()
+    }
+
+    /// True when the collected stream will produce no further records.
+    pub fn done(&self) -> bool {
+        self.state.borrow().frontier.elements().is_empty()
+    }
+
+    /// True when the collected stream will produce no further records at `time`.
+    pub fn complete(&self, time: &T) -> bool {
+        !self.state.borrow().frontier.less_equal(time)
+    }
+
+    /// The records produced at each time, sorted, omitting times without records.
+    pub fn epochs(&self) -> BTreeMap<T, Vec<D>> where D: Ord {
+        let mut epochs = self.state.borrow().epochs.clone();
+        for records in epochs.values_mut() {
+            records.sort();
+        }
+        epochs
+    }
+
+    /// Asserts that the records produced at each time are those of `expected`, in any order.
+    ///
+    /// Times with no expected records must have produced no records.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the records differ at some time.
+    pub fn assert_epochs<I>(&self, expected: I)
+    where
+        I: IntoIterator<Item=(T, Vec<D>)>,
+        D: Ord+Debug,
+    {
+        let mut expect = BTreeMap::new();
+        for (time, mut records) in expected {
+            if !records.is_empty() {
+                records.sort();
+                expect.insert(time, records);
+            }
+        }
+        let epochs = self.epochs();
+        for time in epochs.keys().chain(expect.keys()) {
+            assert_eq!(epochs.get(time), expect.get(time), "records differ at time {:?}", time);
+        }
+    }
+}
+
+impl<T: Timestamp, D> Clone for Results<T, D> {
+    fn clone(&self) -> Self {
+        Results { state: self.state.clone() }
+    }
+}
+
+/// Collects the records of a stream into [`Results`].
+pub trait Collect<G: Scope, D: Data> {
+    /// Collects the records of the stream at each time.
+    ///
+    /// The collecting operator asserts that each record's time is not passed by its input
+    /// frontier, which would mean that the records of a time were reported complete too soon.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Delay};
+    /// use timely::test_harness::Collect;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     let results = worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 4u64).to_stream(scope).delay(|x, _time| *x / 2).collect_epochs()
+    ///     });
+    ///     worker.step_while(|| !results.done());
+    ///     results.assert_epochs(vec![(0, vec![0, 1]), (1, vec![2, 3])]);
+    /// });
+    /// ```
+    fn collect_epochs(&self) -> Results<G::Timestamp, D>;
+}
+
+impl<G: Scope, D: Data> Collect<G, D> for Stream<G, D> {
+    fn collect_epochs(&self) -> Results<G::Timestamp, D> {
+        let results = Results {
+            state: Rc::new(RefCell::new(ResultsState {
+                epochs: BTreeMap::new(),
+                frontier: Antichain::from_elem(G::Timestamp::minimum()),
+            }))
+        };
+        let state = results.state.clone();
+        let mut vector = Vec::new();
+        self.sink(Pipeline, "Collect", move |input| {
+            let mut state = state.borrow_mut();
+            state.frontier = input.frontier().frontier().to_owned();
+            while let Some((time, data)) = input.next() {
+                assert!(
+                    state.frontier.less_equal(time.time()),
+                    "record at time {:?} arrived after the frontier passed it", time.time()
+                );
+                data.swap(&mut vector);
+                state.epochs.entry(time.time().clone()).or_insert_with(Vec::new).append(&mut vector);
+            }
+        });
+        results
+    }
+}
+
+/// A single-threaded dataflow around an operator under test, driven by [`Script`]s.
+pub struct Harness<D: Data, R: Data> {
+    worker: Worker<Thread>,
+    input: Option<InputHandle<u64, D>>,
+    results: Results<u64, R>,
+    max_steps: usize,
+}
+
+impl<D: Data, R: Data> Harness<D, R> {
+    /// Builds a dataflow that applies `build` to the input stream, and collects its output.
+    pub fn new<F>(build: F) -> Self
+    where
+        F: for<'a> FnOnce(&Stream<HarnessScope<'a>, D>) -> Stream<HarnessScope<'a>, R>,
+    {
+        let mut worker = Worker::new(Default::default(), Thread::new());
+        let mut input = InputHandle::new();
+        let results = worker.dataflow(|scope| {
+            let stream = scope.input_from(&mut input);
+            build(&stream).collect_epochs()
+        });
+        Harness {
+            worker,
+            input: Some(input),
+            results,
+            max_steps: 100_000,
+        }
+    }
+
+    /// Sets the number of steps `finish` takes before it concludes an operator holds its
+    /// capabilities indefinitely, by default 100,000.
+    pub fn max_steps(mut self, steps: usize) -> Self {
+        self.max_steps = steps;
+        self
+    }
+
+    /// The worker running the dataflow.
+    pub fn worker(&mut self) -> &mut Worker<Thread> {
+        &mut self.worker
+    }
+
+    /// The results collected so far.
+    pub fn results(&self) -> &Results<u64, R> {
+        &self.results
+    }
+
+    /// Plays `script` into the input, which remains open.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the harness has finished, or the script advances to an epoch preceding the input's.
+    pub fn play(&mut self, script: &Script<D>) {
+        let input = self.input.as_mut().expect("harness input closed");
+        for step in script.steps() {
+            match step {
+                Step::Send(record) => input.send(record.clone()),
+                Step::AdvanceTo(epoch) => input.advance_to(*epoch),
+                Step::Step => { self.worker.step(); },
+            }
+        }
+    }
+
+    /// Closes the input and steps the worker until the output frontier is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the output frontier is not empty after the configured number of steps, as
+    /// happens when an operator holds a capability after its input frontier is empty.
+    pub fn finish(&mut self) -> Results<u64, R> {
+        self.input = None;
+        let mut steps = 0;
+        while !self.results.done() {
+            assert!(
+                steps < self.max_steps,
+                "output frontier {:?} did not become empty after {} steps", self.results.frontier(), steps
+            );
+            self.worker.step();
+            steps += 1;
+        }
+        self.results.clone()
+    }
+}
+
+// The splitmix64 generator, which advances `state` and returns the next value.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,254 @@
+//! Recording and deterministic replay of the messages workers receive.
+//!
+//! A worker configured with [`Config::record_trace`](crate::worker::Config::record_trace) writes,
+//! for each of its steps, the channels whose events activated operators, the contents of each
+//! message it pulled from a channel to other workers (including progress channels), and whether
+//! it parked. A worker configured with [`Config::replay_trace`](crate::worker::Config::replay_trace)
+//! reads the same trace back, and in each step activates the recorded channels, presents pullers
+//! with the recorded messages, and parks (without waiting) exactly when the recorded worker did.
+//! Messages that actually arrive from other workers are discarded.
+//!
+//! As a result the interleaving of messages across channels, which in a live computation depends
+//! on races between workers, is fixed to the recorded one, and a nondeterministic failure can be
+//! reproduced in a debugger. Replay is deterministic as long as the rest of the worker's inputs
+//! are: the same dataflows must be built in the same order, and be fed the same input at the same
+//! steps. Operators that consult the wall clock, for example through delayed activations, may
+//! still diverge.
+//!
+//! Each worker writes to, or reads from, the file `worker-<index>.trace` in the trace directory.
+//! Replay should use the same number of workers as the recording, so that records are routed as
+//! they were, but workers need not run on the same machines.
+//!
+//! # Examples
+//! ```
+//! use std::sync::{Arc, Mutex};
+//! use timely::dataflow::InputHandle;
+//! use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
+//!
+//! let directory = std::env::temp_dir().join(format!("timely-trace-doc-{}", std::process::id()));
+//! std::fs::create_dir_all(&directory).unwrap();
+//!
+//! // Runs the computation, returning the order in which records reached each worker.
+//! let run = |worker_config: timely::WorkerConfig| {
+//!     let orders = Arc::new(Mutex::new(vec![Vec::new(); 2]));
+//!     let shared = orders.clone();
+//!     let config = timely::Config { communication: timely::CommunicationConfig::Process(2), worker: worker_config };
+//!     timely::execute(config, move |worker| {
+//!         let index = worker.index();
+//!         let shared = shared.clone();
+//!         let mut input = InputHandle::new();
+//!         let probe = worker.dataflow(|scope| {
+//!             scope.input_from(&mut input)
+//!                  .exchange(|x: &u64| *x)
+//!                  .inspect(move |x| shared.lock().unwrap()[index].push(*x))
+//!                  .probe()
+//!         });
+//!         for round in 0 .. 10 {
+//!             input.send(round);
+//!             input.advance_to(round + 1);
+//!         }
+//!         worker.step_while(|| probe.less_than(input.time()));
+//!     }).unwrap();
+//!     let orders = orders.lock().unwrap().clone();
+//!     orders
+//! };
+//!
+//! let recorded = run(timely::WorkerConfig::default().record_trace(&directory));
+//! let replayed = run(timely::WorkerConfig::default().replay_trace(&directory));
+//! assert_eq!(recorded, replayed);
+//! # std::fs::remove_dir_all(&directory).unwrap();
+//! ```
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::bytes::arc::Bytes;
+use crate::communication::{Message, Pull};
+use crate::communication::codec::Codec;
+
+/// Whether workers record a trace or replay one, and the directory holding the trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TraceMode {
+    /// Record a trace into the directory.
+    Record(PathBuf),
+    /// Replay the trace in the directory.
+    Replay(PathBuf),
+}
+
+// Kinds of trace entries.
+const ACTIVATE: u8 = 0;
+const PULL: u8 = 1;
+const PARK: u8 = 2;
+
+/// The trace of one worker, being recorded or replayed.
+pub(crate) struct Trace {
+    step: u64,
+    mode: Mode,
+}
+
+enum Mode {
+    Record(BufWriter<File>),
+    Replay {
+        /// Channels activated in each step.
+        activations: BTreeMap<u64, Vec<usize>>,
+        /// Messages pulled from each channel, and the steps in which they were pulled.
+        pulls: HashMap<usize, VecDeque<(u64, Vec<u8>)>>,
+        /// Steps in which the worker parked.
+        parks: BTreeSet<u64>,
+    },
+}
+
+impl Trace {
+    /// Opens the trace of worker `index`, creating the directory of a recorded trace.
+    pub(crate) fn open(mode: &TraceMode, index: usize) -> std::io::Result<Self> {
+        let mode = match mode {
+            TraceMode::Record(directory) => {
+                std::fs::create_dir_all(directory)?;
+                Mode::Record(BufWriter::new(File::create(path(directory, index))?))
+            },
+            TraceMode::Replay(directory) => {
+                let mut reader = BufReader::new(File::open(path(directory, index))?);
+                let mut activations = BTreeMap::<u64, Vec<usize>>::new();
+                let mut pulls = HashMap::<usize, VecDeque<_>>::new();
+                let mut parks = BTreeSet::new();
+                let mut kind = [0u8];
+                while reader.read(&mut kind)? == 1 {
+                    let step = read_u64(&mut reader)?;
+                    let channel = read_u64(&mut reader)? as usize;
+                    let mut bytes = vec![0u8; read_u64(&mut reader)? as usize];
+                    reader.read_exact(&mut bytes)?;
+                    match kind[0] {
+                        ACTIVATE => activations.entry(step).or_default().push(channel),
+                        PULL => pulls.entry(channel).or_default().push_back((step, bytes)),
+                        PARK => { parks.insert(step); },
+                        _ => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "unrecognized trace entry")),
+                    }
+                }
+                Mode::Replay { activations, pulls, parks }
+            },
+        };
+        Ok(Trace { step: 0, mode })
+    }
+
+    /// Indicates whether the trace is being replayed.
+    pub(crate) fn is_replay(&self) -> bool {
+        matches!(self.mode, Mode::Replay { .. })
+    }
+
+    /// Starts the next step of the worker.
+    pub(crate) fn begin_step(&mut self) {
+        self.step += 1;
+    }
+
+    /// Records that events of `channel` activated its operator.
+    pub(crate) fn activated(&mut self, channel: usize) {
+        self.record(ACTIVATE, channel, &[]);
+    }
+
+    /// Records that the worker parked.
+    pub(crate) fn parked(&mut self) {
+        self.record(PARK, 0, &[]);
+    }
+
+    /// The recorded channels whose operators were activated in the current step.
+    pub(crate) fn replay_activations(&mut self) -> Vec<usize> {
+        match &mut self.mode {
+            Mode::Replay { activations, .. } => activations.remove(&self.step).unwrap_or_default(),
+            Mode::Record(_) => Vec::new(),
+        }
+    }
+
+    /// Indicates whether the recorded worker parked in the current step.
+    pub(crate) fn replay_park(&self) -> bool {
+        match &self.mode {
+            Mode::Replay { parks, .. } => parks.contains(&self.step),
+            Mode::Record(_) => false,
+        }
+    }
+
+    /// Makes the entries recorded so far durable, so that they survive a crash of the worker.
+    pub(crate) fn flush(&mut self) {
+        if let Mode::Record(writer) = &mut self.mode {
+            writer.flush().expect("failed to write trace");
+        }
+    }
+
+    /// The next recorded message of `channel`, if it was pulled by the current step.
+    fn replay_pull(&mut self, channel: usize) -> Option<Vec<u8>> {
+        let current = self.step;
+        match &mut self.mode {
+            Mode::Replay { pulls, .. } => {
+                let queue = pulls.get_mut(&channel)?;
+                if queue.front().map(|(step, _)| *step <= current).unwrap_or(false) {
+                    queue.pop_front().map(|(_, bytes)| bytes)
+                }
+                else {
+                    None
+                }
+            },
+            Mode::Record(_) => None,
+        }
+    }
+
+    fn record(&mut self, kind: u8, channel: usize, bytes: &[u8]) {
+        if let Mode::Record(writer) = &mut self.mode {
+            writer.write_all(&[kind]).expect("failed to write trace");
+            writer.write_all(&self.step.to_le_bytes()).expect("failed to write trace");
+            writer.write_all(&(channel as u64).to_le_bytes()).expect("failed to write trace");
+            writer.write_all(&(bytes.len() as u64).to_le_bytes()).expect("failed to write trace");
+            writer.write_all(bytes).expect("failed to write trace");
+        }
+    }
+}
+
+fn path(directory: &Path, index: usize) -> PathBuf {
+    directory.join(format!("worker-{}.trace", index))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> std::io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// A puller that records the messages it pulls, or replays recorded messages instead.
+pub(crate) struct TracePuller<D, C> {
+    channel: usize,
+    trace: Rc<RefCell<Trace>>,
+    puller: Box<dyn Pull<Message<D>>>,
+    current: Option<Message<D>>,
+    phantom: PhantomData<C>,
+}
+
+impl<D, C> TracePuller<D, C> {
+    /// Wraps the puller of `channel`.
+    pub(crate) fn new(channel: usize, trace: Rc<RefCell<Trace>>, puller: Box<dyn Pull<Message<D>>>) -> Self {
+        TracePuller { channel, trace, puller, current: None, phantom: PhantomData }
+    }
+}
+
+impl<D, C: Codec<D>> Pull<Message<D>> for TracePuller<D, C> {
+    fn pull(&mut self) -> &mut Option<Message<D>> {
+        let mut trace = self.trace.borrow_mut();
+        if trace.is_replay() {
+            // Discard live messages, which the recorded messages replace.
+            while self.puller.pull().is_some() { }
+            self.current = trace.replay_pull(self.channel).map(|bytes| C::from_bytes(Bytes::from(bytes)));
+            &mut self.current
+        }
+        else {
+            let message = self.puller.pull();
+            if let Some(message) = message.as_ref() {
+                let mut bytes = Vec::with_capacity(C::length_in_bytes(message));
+                C::into_bytes(message, &mut bytes);
+                trace.record(PULL, self.channel, &bytes);
+            }
+            message
+        }
+    }
+}
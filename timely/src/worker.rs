@@ -1,22 +1,33 @@
 //! The root of each single-threaded worker.
 
 use std::rc::Rc;
-use std::cell::{RefCell, RefMut};
+use std::cell::{Cell, RefCell, RefMut};
 use std::any::Any;
 use std::str::FromStr;
-use std::time::{Instant, Duration};
+use std::time::Duration;
 use std::collections::HashMap;
 use std::collections::hash_map::Entry;
 use std::sync::Arc;
+use std::fmt;
+use std::hash::{BuildHasher, BuildHasherDefault, Hash, Hasher};
+use std::collections::hash_map::DefaultHasher;
 
-use crate::communication::{Allocate, Data, Push, Pull};
+use crate::communication::{Allocate, Data};
+use crate::communication::codec::{Codec, Native};
 use crate::communication::allocator::thread::{ThreadPusher, ThreadPuller};
+use crate::communication::allocator::{BoxedPush, BoxedPull};
 use crate::scheduling::{Schedule, Scheduler, Activations};
 use crate::progress::timestamp::{Refines};
+use crate::progress::{Antichain, Timestamp};
 use crate::progress::SubgraphBuilder;
+use crate::progress::reachability::SummaryCache;
+use crate::progress::validation::GraphError;
+use crate::Error;
 use crate::progress::operate::Operate;
 use crate::dataflow::scopes::Child;
+use crate::dataflow::template::Template;
 use crate::logging::TimelyLogger;
+use crate::logging_core::clock::Instant;
 
 /// Different ways in which timely's progress tracking can work.
 ///
@@ -82,11 +93,191 @@ impl FromStr for ProgressMode {
     }
 }
 
+/// The codec with which exchanges that defer to the configuration serialize their messages.
+///
+/// Such exchanges use the [`Configured`](crate::dataflow::channels::pact::Configured) codec,
+/// and all processes of a computation must select the same codec.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ExchangeCodec {
+    /// The serialization implied by `communication::Data`.
+    #[default]
+    Native,
+    /// Serialization with `serde` and `bincode`.
+    Bincode,
+}
+
+impl FromStr for ExchangeCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ExchangeCodec, String> {
+        match s {
+            "native" => Ok(ExchangeCodec::Native),
+            "bincode" => Ok(ExchangeCodec::Bincode),
+            _ => Err(format!("unknown exchange codec: {}", s)),
+        }
+    }
+}
+
+/// A shareable `BuildHasher` used to route records by their `Hash` implementation.
+///
+/// Records are routed by the worker that sends them, and so every worker in the
+/// computation must produce the same hash for the same record. Avoid randomly seeded
+/// builders (e.g. `RandomState`) unless their seed is shared by all processes.
+///
+/// The default uses `DefaultHasher` with its fixed keys.
+#[derive(Clone)]
+pub struct ExchangeHasher {
+    hash_with: Arc<HashWith>,
+}
+
+// Builds a hasher, offers it to the supplied closure, and reports the result.
+type HashWith = dyn Fn(&mut dyn FnMut(&mut dyn Hasher)) -> u64 + Send + Sync;
+
+impl ExchangeHasher {
+    /// Wraps a `BuildHasher`, whose hashers will be used to route records.
+    pub fn new<B: BuildHasher + Send + Sync + 'static>(builder: B) -> Self {
+        ExchangeHasher {
+            hash_with: Arc::new(move |write| {
+                let mut hasher = builder.build_hasher();
+                write(&mut hasher);
+                hasher.finish()
+            }),
+        }
+    }
+
+    /// Hashes `item` with a freshly built hasher.
+    #[inline]
+    pub fn hash<D: Hash + ?Sized>(&self, item: &D) -> u64 {
+        (self.hash_with)(&mut |mut hasher: &mut dyn Hasher| item.hash(&mut hasher))
+    }
+}
+
+impl Default for ExchangeHasher {
+    fn default() -> Self {
+        ExchangeHasher::new(BuildHasherDefault::<DefaultHasher>::default())
+    }
+}
+
+impl fmt::Debug for ExchangeHasher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExchangeHasher").finish()
+    }
+}
+
+/// The cores on which each worker thread may run, by worker index.
+#[derive(Clone)]
+pub struct Affinity {
+    cores_for: Arc<dyn Fn(usize) -> Vec<usize> + Send + Sync>,
+}
+
+impl Affinity {
+    /// Determines the cores of each worker with `cores_for`, from the worker's index.
+    ///
+    /// A worker whose cores are empty is not pinned.
+    pub fn new<F: Fn(usize) -> Vec<usize> + Send + Sync + 'static>(cores_for: F) -> Self {
+        Affinity { cores_for: Arc::new(cores_for) }
+    }
+
+    /// The cores on which the worker with index `index` may run.
+    pub fn cores(&self, index: usize) -> Vec<usize> {
+        (self.cores_for)(index)
+    }
+
+    /// Restricts the current thread to the cores of worker `index`.
+    fn apply(&self, index: usize) {
+        let cores = self.cores(index);
+        if !cores.is_empty() {
+            if let Err(error) = set_affinity(&cores) {
+                eprintln!("timely: failed to pin worker {} to cores {:?}: {}", index, cores, error);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Affinity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Affinity").finish()
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn set_affinity(cores: &[usize]) -> std::io::Result<()> {
+    let capacity = 8 * std::mem::size_of::<libc::cpu_set_t>();
+    if let Some(core) = cores.iter().find(|core| **core >= capacity) {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("core {} exceeds the supported {}", core, capacity)));
+    }
+    // Safety: the set is initialized before use, and each core is within its bounds.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_ZERO(&mut set);
+        for core in cores {
+            libc::CPU_SET(*core, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_affinity(_cores: &[usize]) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Other, "thread affinity is not supported on this platform"))
+}
+
 /// Worker configuration.
 #[derive(Debug, Default, Clone)]
 pub struct Config {
     /// The progress mode to use.
     pub(crate) progress_mode: ProgressMode,
+    /// The hasher used by hash-routed exchanges.
+    pub(crate) exchange_hasher: ExchangeHasher,
+    /// The codec of exchanges that defer to the configuration.
+    pub(crate) exchange_codec: ExchangeCodec,
+    /// The store to which checkpoints are written.
+    pub(crate) checkpoint_store: Option<Arc<dyn crate::checkpoint::Store>>,
+    /// Whether to track the live capabilities of operators.
+    pub(crate) track_capabilities: bool,
+    /// Whether batches carry the lineage of their records.
+    pub(crate) trace_lineage: bool,
+    /// Whether to check the progress reported by operators, in all builds.
+    pub(crate) validate_progress: bool,
+    /// Whether to measure the scheduling of operators.
+    pub(crate) operator_metrics: bool,
+    /// The Prometheus endpoint to which workers report.
+    #[cfg(feature = "prometheus")]
+    pub(crate) prometheus: Option<crate::prometheus::Exporter>,
+    /// The buffer capacity and flush period of files written by `Worker::log_to_file`.
+    pub(crate) log_file_buffering: Option<(usize, Duration)>,
+    /// The period at which, and number of, longest scheduled operators are logged.
+    pub(crate) profile: Option<(Duration, usize)>,
+    /// The period at which summaries of worker steps are logged.
+    pub(crate) schedule_summary: Option<Duration>,
+    /// The number of progress updates, and the time, for which scopes may hold back updates.
+    pub(crate) progress_batching: Option<(usize, Duration)>,
+    /// The cores to which worker threads are pinned.
+    pub(crate) affinity: Option<Affinity>,
+    /// Whether workers record or replay a trace of the messages they receive.
+    pub(crate) trace: Option<crate::trace::TraceMode>,
+    /// Whether panics of operators poison their dataflows, rather than the worker.
+    pub(crate) isolate_panics: bool,
+    /// The time after which scopes whose frontiers have not changed are reported as stalled.
+    pub(crate) watchdog: Option<Duration>,
+    /// The budget of work of stock operators in each scheduling.
+    pub(crate) operator_fuel: crate::scheduling::fuel::Fuel,
+    /// The default number of records in the messages of channels.
+    pub(crate) batch_size: Option<usize>,
+    /// Where, and past what size, stock operators spill the records they hold.
+    pub(crate) spill: Option<crate::dataflow::operators::epoch_buffer::SpillConfig>,
+    /// The collectors to which `execute` sends the "timely" and communication log streams.
+    pub(crate) log_addresses: (Option<String>, Option<String>),
+    /// The clock from which workers read the time, if not the system clock.
+    pub(crate) clock: Option<Arc<dyn crate::logging_core::clock::Clock>>,
+    /// The queues of stealing channels, shared by the workers built from clones of the configuration.
+    ///
+    /// Each computation `execute` and its relatives start replaces these, so that computations
+    /// started from clones of one configuration do not share queues.
+    pub(crate) steal_queues: crate::dataflow::channels::steal::Queues,
     /// A map from parameter name to typed parameter values.
     registry: HashMap<String, Arc<dyn Any + Send + Sync>>,
 }
@@ -104,6 +295,7 @@ impl Config {
     #[cfg(feature = "getopts")]
     pub fn install_options(opts: &mut getopts_dep::Options) {
         opts.optopt("", "progress-mode", "progress tracking mode (eager or demand)", "MODE");
+        opts.optopt("", "exchange-codec", "codec of configured exchanges (native or bincode)", "CODEC");
     }
 
     /// Instantiates a configuration based upon the parsed options in `matches`.
@@ -119,7 +311,10 @@ impl Config {
         let progress_mode = matches
             .opt_get_default("progress-mode", ProgressMode::Eager)
             .map_err(|e| e.to_string())?;
-        Ok(Config::default().progress_mode(progress_mode))
+        let exchange_codec = matches
+            .opt_get_default("exchange-codec", ExchangeCodec::Native)
+            .map_err(|e| e.to_string())?;
+        Ok(Config::default().progress_mode(progress_mode).exchange_codec(exchange_codec))
     }
 
     /// Sets the progress mode to `progress_mode`.
@@ -128,6 +323,478 @@ impl Config {
         self
     }
 
+    /// Sets the codec of exchanges that defer to the configuration.
+    ///
+    /// Exchanges whose pact uses the [`Configured`](crate::dataflow::channels::pact::Configured)
+    /// codec serialize messages between processes with `codec`, and otherwise with the codec the
+    /// pact names. The default is [`ExchangeCodec::Native`].
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::worker::ExchangeCodec;
+    ///
+    /// let mut config = timely::Config::process(2);
+    /// config.worker = config.worker.exchange_codec(ExchangeCodec::Bincode);
+    /// assert_eq!(config.worker.codec(), ExchangeCodec::Bincode);
+    /// ```
+    pub fn exchange_codec(mut self, codec: ExchangeCodec) -> Self {
+        self.exchange_codec = codec;
+        self
+    }
+
+    /// The codec of exchanges that defer to the configuration.
+    pub fn codec(&self) -> ExchangeCodec {
+        self.exchange_codec
+    }
+
+    /// Sets the hasher used by `exchange_hashed` to route records between workers.
+    ///
+    /// The builder must produce identical hashes on all workers; see [`ExchangeHasher`].
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::hash::BuildHasherDefault;
+    /// use std::collections::hash_map::DefaultHasher;
+    /// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+    ///
+    /// let mut config = timely::Config::process(2);
+    /// config.worker = config.worker.exchange_hasher(BuildHasherDefault::<DefaultHasher>::default());
+    /// timely::execute(config, |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0..10).to_stream(scope)
+    ///                .exchange_hashed()
+    ///                .inspect(|x| println!("seen: {:?}", x));
+    ///     });
+    /// }).unwrap();
+    /// ```
+    pub fn exchange_hasher<B: BuildHasher + Send + Sync + 'static>(mut self, builder: B) -> Self {
+        self.exchange_hasher = ExchangeHasher::new(builder);
+        self
+    }
+
+    /// The hasher used by hash-routed exchanges.
+    pub fn hasher(&self) -> &ExchangeHasher {
+        &self.exchange_hasher
+    }
+
+    /// Sets the store to which checkpoints are written, and from which they are restored.
+    ///
+    /// See the [`checkpoint`](crate::checkpoint) module for an example.
+    pub fn checkpoint_store<S: crate::checkpoint::Store + 'static>(mut self, store: S) -> Self {
+        self.checkpoint_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Sets whether the worker tracks each live capability, for diagnosing stalled progress.
+    ///
+    /// Tracking records where each capability was created, which is expensive, and should be
+    /// enabled only while debugging. See [`Worker::report_capability_holders`].
+    pub fn track_capabilities(mut self, track: bool) -> Self {
+        self.track_capabilities = track;
+        self
+    }
+
+    /// Sets whether each batch carries the lineage of its records: the operator and worker at
+    /// which they entered the dataflow.
+    ///
+    /// Lineage is reported by [`Lineage::current`](crate::dataflow::channels::lineage::Lineage::current)
+    /// and in logged `MessagesEvent`s, and is not sent between processes. See the
+    /// [`lineage`](crate::dataflow::channels::lineage) module for an example.
+    pub fn trace_lineage(mut self, trace: bool) -> Self {
+        self.trace_lineage = trace;
+        self
+    }
+
+    /// Sets whether the worker checks the progress operators report, after each scheduling.
+    ///
+    /// The checks panic, naming the operator and its address, if an operator
+    ///
+    /// * reports a number of records consumed at an input other than the number it received, or
+    /// * produces records, or acquires a capability, at a time not covered by a capability it
+    ///   holds or by a record it consumed.
+    ///
+    /// The checks on times are always made in debug builds. The checks are expensive, and should
+    /// be enabled only while developing or debugging operators.
+    ///
+    /// # Examples
+    /// ```should_panic
+    /// use timely::communication::Pull;
+    /// use timely::dataflow::Scope;
+    /// use timely::dataflow::channels::pact::Pipeline;
+    /// use timely::dataflow::operators::ToStream;
+    /// use timely::dataflow::operators::generic::builder_raw::OperatorBuilder;
+    ///
+    /// let mut config = timely::Config::thread();
+    /// config.worker = config.worker.validate_progress(true);
+    /// timely::execute(config, |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         let stream = (0 .. 10u64).to_stream(scope);
+    ///         let mut builder = OperatorBuilder::new("Leaky".to_owned(), stream.scope());
+    ///         let mut input = builder.new_input(&stream, Pipeline);
+    ///         builder.build(move |_progress| {
+    ///             // Receives records, but does not report them consumed.
+    ///             while input.pull().is_some() { }
+    ///             false
+    ///         });
+    ///     });
+    /// }).unwrap();
+    /// ```
+    pub fn validate_progress(mut self, validate: bool) -> Self {
+        self.validate_progress = validate;
+        self
+    }
+
+    /// Sets whether the worker measures the scheduling of its operators.
+    ///
+    /// The measurements are reported by [`Worker::metrics`] and [`Worker::top_operators`]. The
+    /// worker also measures its operators if it is configured to [`profile`](Self::profile) them
+    /// or to [`validate_progress`](Self::validate_progress), and each scheduling is measured if a
+    /// logger for the "timely/metrics" log stream or a schedule hook is installed. Otherwise the
+    /// worker reads no clock around the scheduling of operators. See the
+    /// [`metrics`](crate::scheduling::metrics) module for an example.
+    pub fn operator_metrics(mut self, measure: bool) -> Self {
+        self.operator_metrics = measure;
+        self
+    }
+
+    /// Indicates whether the worker accumulates measurements of the scheduling of its operators.
+    pub(crate) fn measures_operators(&self) -> bool {
+        self.operator_metrics || self.profile.is_some() || self.validate_progress
+    }
+
+    /// Sets the Prometheus endpoint to which workers report their measurements.
+    ///
+    /// See the [`prometheus`](crate::prometheus) module for an example. This method is only
+    /// available if the `prometheus` feature is enabled.
+    #[cfg(feature = "prometheus")]
+    pub fn prometheus(mut self, exporter: crate::prometheus::Exporter) -> Self {
+        self.prometheus = Some(exporter);
+        self
+    }
+
+    /// Sets the buffer capacity, in bytes, and flush period of files written by
+    /// [`Worker::log_to_file`].
+    ///
+    /// By default files buffer 64KiB and are flushed at least every second.
+    pub fn log_file_buffering(mut self, capacity: usize, period: Duration) -> Self {
+        self.log_file_buffering = Some((capacity, period));
+        self
+    }
+
+    /// Sets the address of a collector to which [`execute`](crate::execute()) sends each worker's
+    /// "timely" log stream, in place of the `TIMELY_WORKER_LOG_ADDR` environment variable.
+    pub fn worker_log_address(mut self, address: String) -> Self {
+        self.log_addresses.0 = Some(address);
+        self
+    }
+
+    /// Sets the address of a collector to which [`execute`](crate::execute()) sends the log stream
+    /// of communication threads, in place of the `TIMELY_COMM_LOG_ADDR` environment variable.
+    pub fn communication_log_address(mut self, address: String) -> Self {
+        self.log_addresses.1 = Some(address);
+        self
+    }
+
+    /// Sets the worker to log the `count` operators that have spent longest scheduled, every
+    /// `period`, to the "timely/profile" log stream.
+    ///
+    /// Each operator is reported as a [`ProfileEvent`](crate::logging::ProfileEvent), with the
+    /// cumulative measurements also reported by [`Worker::top_operators`].
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use std::time::Duration;
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Map, Probe};
+    /// use timely::logging::ProfileEvent;
+    ///
+    /// let mut config = timely::Config::thread();
+    /// config.worker = config.worker.profile(Duration::from_secs(0), 3);
+    /// timely::execute(config, |worker| {
+    ///     let reports = Rc::new(RefCell::new(Vec::new()));
+    ///     let sink = reports.clone();
+    ///     worker.log_register().insert::<ProfileEvent,_>("timely/profile", move |_time, data| {
+    ///         sink.borrow_mut().extend(data.drain(..).map(|(_, _, event)| event));
+    ///     });
+    ///
+    ///     let mut input = InputHandle::new();
+    ///     let probe = worker.dataflow(|scope| {
+    ///         scope.input_from(&mut input).map(|x: u64| x + 1).probe()
+    ///     });
+    ///     input.send(0);
+    ///     input.advance_to(1);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    ///
+    ///     let reports = reports.borrow();
+    ///     assert!(reports.iter().any(|event| event.rank == 0));
+    ///     assert!(reports.iter().all(|event| event.rank < 3));
+    /// }).unwrap();
+    /// ```
+    pub fn profile(mut self, period: Duration, count: usize) -> Self {
+        self.profile = Some((period, count));
+        self
+    }
+
+    /// Sets the worker to log a summary of the phases of its steps since the previous summary,
+    /// every `period`, to the "timely/schedule" log stream.
+    ///
+    /// Each summary is reported as a [`ScheduleSummaryEvent`](crate::logging::ScheduleSummaryEvent),
+    /// and the cumulative measurements are also reported by [`Worker::step_stats`]. The worker
+    /// finds the logger when it builds a dataflow, and so it should be installed before then.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use std::time::Duration;
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Map, Probe};
+    /// use timely::logging::ScheduleSummaryEvent;
+    ///
+    /// let mut config = timely::Config::thread();
+    /// config.worker = config.worker.schedule_summary(Duration::from_secs(0));
+    /// timely::execute(config, |worker| {
+    ///     let summaries = Rc::new(RefCell::new(Vec::new()));
+    ///     let sink = summaries.clone();
+    ///     worker.log_register().insert::<ScheduleSummaryEvent,_>("timely/schedule", move |_time, data| {
+    ///         sink.borrow_mut().extend(data.drain(..).map(|(_, _, event)| event));
+    ///     });
+    ///
+    ///     let mut input = InputHandle::new();
+    ///     let probe = worker.dataflow(|scope| {
+    ///         scope.input_from(&mut input).map(|x: u64| x + 1).probe()
+    ///     });
+    ///     input.send(0);
+    ///     input.advance_to(1);
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    ///
+    ///     let summaries = summaries.borrow();
+    ///     assert!(!summaries.is_empty());
+    ///     assert!(summaries.iter().all(|summary| summary.steps == 1));
+    /// }).unwrap();
+    /// ```
+    pub fn schedule_summary(mut self, period: Duration) -> Self {
+        self.schedule_summary = Some(period);
+        self
+    }
+
+    /// Sets scopes to hold back their progress updates, and send them to other workers in batches.
+    ///
+    /// A scope sends the updates it holds once it holds at least `updates` distinct updates, or
+    /// has held some for `period`, and before the worker parks. This reduces the number of
+    /// progress messages of dataflows whose frontiers advance rapidly, for example through many
+    /// small epochs, at the expense of the latency with which frontiers advance at other workers.
+    /// Updates are also held only when the progress mode would send them.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Exchange, Probe};
+    ///
+    /// let mut config = timely::Config::process(2);
+    /// config.worker = config.worker.progress_batching(64, Duration::from_micros(500));
+    /// timely::execute(config, |worker| {
+    ///     let mut input = InputHandle::new();
+    ///     let probe = worker.dataflow(|scope| {
+    ///         scope.input_from(&mut input).exchange(|x: &u64| *x).probe()
+    ///     });
+    ///     for round in 0 .. 100 {
+    ///         input.send(round);
+    ///         input.advance_to(round + 1);
+    ///     }
+    ///     worker.step_while(|| probe.less_than(input.time()));
+    /// }).unwrap();
+    /// ```
+    pub fn progress_batching(mut self, updates: usize, period: Duration) -> Self {
+        self.progress_batching = Some((updates, period));
+        self
+    }
+
+    /// Pins each worker thread to one of `cores`, the worker with index `i` to `cores[i % cores.len()]`.
+    ///
+    /// With as many cores as there are workers in each process, each worker of a process is
+    /// pinned to a different core. Threads are pinned as their workers are constructed, and
+    /// failures to pin a thread are reported to standard error. Pinning is only supported on Linux.
+    ///
+    /// # Examples
+    /// ```
+    /// let mut config = timely::Config::process(2);
+    /// config.worker = config.worker.pin_cores(vec![0, 1]);
+    /// ```
+    pub fn pin_cores(self, cores: Vec<usize>) -> Self {
+        if cores.is_empty() {
+            return self;
+        }
+        self.affinity(Affinity::new(move |index| vec![cores[index % cores.len()]]))
+    }
+
+    /// Allows all worker threads to run on any of `cores`, and no others.
+    ///
+    /// # Examples
+    /// ```
+    /// // both workers share core zero.
+    /// let mut config = timely::Config::process(2);
+    /// config.worker = config.worker.pin_core_set(vec![0]);
+    /// timely::execute(config, |worker| {
+    ///     println!("worker {} running", worker.index());
+    /// }).unwrap();
+    /// ```
+    pub fn pin_core_set(self, cores: Vec<usize>) -> Self {
+        self.affinity(Affinity::new(move |_index| cores.clone()))
+    }
+
+    /// Pins worker threads to the cores that `affinity` determines from their indexes.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::worker::Affinity;
+    ///
+    /// // use only even cores, as if to avoid hyperthread siblings.
+    /// let mut config = timely::Config::process(2);
+    /// config.worker = config.worker.affinity(Affinity::new(|index| vec![2 * index]));
+    /// ```
+    pub fn affinity(mut self, affinity: Affinity) -> Self {
+        self.affinity = Some(affinity);
+        self
+    }
+
+    /// Records a trace of the messages each worker receives into `directory`.
+    ///
+    /// The trace can be replayed with [`Config::replay_trace`], as described in the
+    /// [`trace`](crate::trace) module.
+    pub fn record_trace<P: AsRef<std::path::Path>>(mut self, directory: P) -> Self {
+        self.trace = Some(crate::trace::TraceMode::Record(directory.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Replays the trace recorded into `directory`, in place of the messages workers receive.
+    ///
+    /// See the [`trace`](crate::trace) module for the guarantees of replay.
+    pub fn replay_trace<P: AsRef<std::path::Path>>(mut self, directory: P) -> Self {
+        self.trace = Some(crate::trace::TraceMode::Replay(directory.as_ref().to_path_buf()));
+        self
+    }
+
+    /// Isolates panics of operators to their dataflows, which are dropped by all workers.
+    ///
+    /// Panics are reported to the handler installed by [`Worker::on_dataflow_panic`], and all
+    /// workers must be configured alike. See the [`poison`](crate::scheduling::poison) module.
+    pub fn isolate_panics(mut self, isolate: bool) -> Self {
+        self.isolate_panics = isolate;
+        self
+    }
+
+    /// Reports scopes whose frontiers have not changed for `timeout`, despite held capabilities
+    /// or messages in flight, to the "timely/stalls" log stream.
+    ///
+    /// Workers wake at least once per `timeout` to check for stalls. See the
+    /// [`watchdog`](crate::scheduling::watchdog) module for an example.
+    pub fn watchdog(mut self, timeout: Duration) -> Self {
+        self.watchdog = Some(timeout);
+        self
+    }
+
+    /// Reads the time from `clock`, rather than from the system clock.
+    ///
+    /// The clock times the worker's [`timer`](Worker::timer), its logged events, its delayed
+    /// activations, and time-based operators such as
+    /// [`to_stream_paced`](crate::dataflow::operators::ToStream::to_stream_paced) and
+    /// [`measure_latency`](crate::dataflow::operators::latency::MeasureLatency::measure_latency).
+    /// It also times the worker's measurements of its own work, such as [`Worker::step_stats`],
+    /// operator fuel, held progress updates, and the watchdog. With a
+    /// [`MockClock`](crate::logging_core::clock::MockClock), tests advance time exactly, and step
+    /// the worker with [`Worker::step`]; a worker that parks waits in real time for delayed
+    /// activations, which a mock clock may never reach.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::logging_core::clock::MockClock;
+    ///
+    /// let clock = MockClock::new();
+    /// let config = timely::Config {
+    ///     communication: timely::CommunicationConfig::Thread,
+    ///     worker: timely::WorkerConfig::default().clock(clock.clone()),
+    /// };
+    /// timely::execute(config, move |worker| {
+    ///     clock.advance(Duration::from_secs(60));
+    ///     assert_eq!(worker.elapsed(), Duration::from_secs(60));
+    /// }).unwrap();
+    /// ```
+    pub fn clock<C: crate::logging_core::clock::Clock>(mut self, clock: C) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Limits the work that stock operators do in one scheduling to `fuel`, after which they
+    /// yield to other operators and resume when next scheduled.
+    ///
+    /// See the [`fuel`](crate::scheduling::fuel) module for the operators affected.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use timely::scheduling::fuel::Fuel;
+    ///
+    /// let mut config = timely::Config::process(2);
+    /// config.worker = config.worker.operator_fuel(Fuel::records(10_000).with_duration(Duration::from_millis(10)));
+    /// ```
+    pub fn operator_fuel(mut self, fuel: crate::scheduling::fuel::Fuel) -> Self {
+        self.operator_fuel = fuel;
+        self
+    }
+
+    /// The budget of work of operators in each scheduling, unlimited unless configured.
+    ///
+    /// Time budgets are measured by the clock of the configuration.
+    pub fn fuel(&self) -> crate::scheduling::fuel::Fuel {
+        match &self.clock {
+            Some(clock) => self.operator_fuel.clone().with_clock(clock.clone()),
+            None => self.operator_fuel.clone(),
+        }
+    }
+
+    /// Sets the default number of records that operator outputs and exchanges collect into each
+    /// message, which is otherwise 1024.
+    ///
+    /// Larger batches amortize the cost of sending small records, while smaller batches bound
+    /// the memory held for large records. Individual exchanges may override the default with
+    /// [`Exchange::new_with_batch`](crate::dataflow::channels::pact::Exchange::new_with_batch).
+    ///
+    /// # Examples
+    /// ```
+    /// let mut config = timely::Config::process(2);
+    /// config.worker = config.worker.batch_size(16384);
+    /// ```
+    pub fn batch_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "batches must hold at least one record");
+        self.batch_size = Some(size);
+        self
+    }
+
+    /// The number of records that operator outputs and exchanges collect into each message.
+    pub fn batch_length(&self) -> usize {
+        self.batch_size.unwrap_or_else(crate::dataflow::channels::Message::<(), ()>::default_length)
+    }
+
+    /// Spills the records that stock operators hold for incomplete times to disk, once they
+    /// exceed the threshold of `spill`.
+    ///
+    /// See the [`epoch_buffer`](crate::dataflow::operators::epoch_buffer) module for the
+    /// operators affected.
+    pub fn spill_to_disk(mut self, spill: crate::dataflow::operators::epoch_buffer::SpillConfig) -> Self {
+        self.spill = Some(spill);
+        self
+    }
+
+    /// Where, and past what size, operators spill the records they hold, if configured.
+    pub fn spill(&self) -> Option<crate::dataflow::operators::epoch_buffer::SpillConfig> {
+        self.spill.clone()
+    }
+
     /// Sets a typed configuration parameter for the given `key`.
     ///
     /// It is recommended to install a single configuration struct using a key
@@ -192,7 +859,14 @@ pub trait AsWorker : Scheduler {
     /// scheduled in response to the receipt of records on the channel.
     /// Most commonly, this would be the address of the *target* of the
     /// channel.
-    fn allocate<T: Data>(&mut self, identifier: usize, address: &[usize]) -> (Vec<Box<dyn Push<Message<T>>>>, Box<dyn Pull<Message<T>>>);
+    fn allocate<T: Data>(&mut self, identifier: usize, address: &[usize]) -> (Vec<BoxedPush<T>>, BoxedPull<T>) {
+        self.allocate_with::<T, Native>(identifier, address)
+    }
+    /// Allocates a new channel whose contents are serialized with `C`.
+    ///
+    /// This method otherwise behaves as `allocate`, and is the way to move types
+    /// that do not implement `communication::Data` between processes.
+    fn allocate_with<T: Any+Send+Sync, C: Codec<T>>(&mut self, identifier: usize, address: &[usize]) -> (Vec<BoxedPush<T>>, BoxedPull<T>);
     /// Constructs a pipeline channel from the worker to itself.
     ///
     /// By default this method uses the native channel allocation mechanism, but the expectation is
@@ -205,6 +879,59 @@ pub trait AsWorker : Scheduler {
     fn log_register(&self) -> ::std::cell::RefMut<crate::logging_core::Registry<crate::logging::WorkerIdentifier>>;
     /// Provides access to the timely logging stream.
     fn logging(&self) -> Option<crate::logging::TimelyLogger> { self.log_register().get("timely") }
+    /// Provides handles to the state the worker shares with its dataflows.
+    fn extensions(&self) -> &Extensions;
+    /// Provides access to the checkpoint state shared by the worker's dataflows.
+    fn checkpoints(&self) -> RefMut<'_, crate::checkpoint::Checkpoints> { self.extensions().checkpoints.borrow_mut() }
+    /// Provides access to streams exported by the worker's dataflows.
+    fn exports(&self) -> RefMut<'_, crate::dataflow::operators::export::Exports> { self.extensions().exports.borrow_mut() }
+    /// Provides a handle to the latency histograms recorded by the worker's dataflows.
+    fn latencies(&self) -> crate::dataflow::operators::latency::Latencies { self.extensions().latencies.clone() }
+    /// Provides access to the names and channels of the worker's operators.
+    fn topology(&self) -> RefMut<'_, crate::dataflow::graph::Topology> { self.extensions().topology.borrow_mut() }
+    /// Provides a handle to the scheduling measurements of the worker's operators.
+    fn operator_metrics(&self) -> crate::scheduling::metrics::Metrics { self.extensions().metrics.clone() }
+    /// Provides a handle to the counts of batches moved by the worker's channels.
+    fn channel_stats(&self) -> crate::dataflow::channels::stats::Stats { self.extensions().channel_stats.clone() }
+    /// Provides a handle to the hook called around the scheduling of the worker's operators.
+    fn schedule_hooks(&self) -> crate::scheduling::hooks::Hooks { self.extensions().hooks.clone() }
+    /// Provides a handle to the scopes of the worker watched for stalls.
+    fn watchdog(&self) -> crate::scheduling::watchdog::Watchdog { self.extensions().watchdog.clone() }
+    /// Provides a handle to the scopes of the worker holding back progress updates.
+    fn progress_batches(&self) -> crate::progress::broadcast::Batches { self.extensions().progress_batches.clone() }
+    /// Provides the clock from which the worker reads the time.
+    fn clock(&self) -> Arc<dyn crate::logging_core::clock::Clock> { self.extensions().clock.clone() }
+}
+
+/// Handles to the state a worker shares with the scopes and operators of its dataflows.
+///
+/// Child scopes present the extensions of their worker, and the methods of [`AsWorker`] that
+/// reach this state are provided in terms of [`AsWorker::extensions`].
+#[derive(Clone)]
+pub struct Extensions {
+    checkpoints: Rc<RefCell<crate::checkpoint::Checkpoints>>,
+    exports: Rc<RefCell<crate::dataflow::operators::export::Exports>>,
+    latencies: crate::dataflow::operators::latency::Latencies,
+    topology: Rc<RefCell<crate::dataflow::graph::Topology>>,
+    metrics: crate::scheduling::metrics::Metrics,
+    channel_stats: crate::dataflow::channels::stats::Stats,
+    hooks: crate::scheduling::hooks::Hooks,
+    watchdog: crate::scheduling::watchdog::Watchdog,
+    progress_batches: crate::progress::broadcast::Batches,
+    clock: Arc<dyn crate::logging_core::clock::Clock>,
+    lineage: Option<crate::dataflow::channels::lineage::Tracer>,
+    capabilities: Option<crate::dataflow::operators::capability_tracking::Tracker>,
+}
+
+impl Extensions {
+    /// The lineage state of the worker, if it traces lineage.
+    pub(crate) fn lineage(&self) -> Option<crate::dataflow::channels::lineage::Tracer> {
+        self.lineage.clone()
+    }
+    /// The capabilities of the worker's operators, if it tracks them.
+    pub(crate) fn capabilities(&self) -> Option<crate::dataflow::operators::capability_tracking::Tracker> {
+        self.capabilities.clone()
+    }
 }
 
 /// A `Worker` is the entry point to a timely dataflow computation. It wraps a `Allocate`,
@@ -219,6 +946,16 @@ pub struct Worker<A: Allocate> {
     dataflows: Rc<RefCell<HashMap<usize, Wrapper>>>,
     dataflow_counter: Rc<RefCell<usize>>,
     logging: Rc<RefCell<crate::logging_core::Registry<crate::logging::WorkerIdentifier>>>,
+    extensions: Extensions,
+    trace: Option<Rc<RefCell<crate::trace::Trace>>>,
+    poison: Option<Rc<RefCell<crate::scheduling::poison::Poison>>>,
+    idle: Rc<RefCell<Option<IdleHandler>>>,
+    // When the longest scheduled operators were last logged.
+    profiled: Rc<Cell<Instant>>,
+    // Measurements of steps, in total and since they were last logged, and when they were last logged.
+    steps: Rc<RefCell<crate::scheduling::steps::Steps>>,
+    // Loggers of each step, found when the worker is built and when it builds a dataflow.
+    step_loggers: Rc<RefCell<StepLoggers>>,
 
     activations: Rc<RefCell<Activations>>,
     active_dataflows: Vec<usize>,
@@ -232,25 +969,18 @@ impl<A: Allocate> AsWorker for Worker<A> {
     fn config(&self) -> &Config { &self.config }
     fn index(&self) -> usize { self.allocator.borrow().index() }
     fn peers(&self) -> usize { self.allocator.borrow().peers() }
-    fn allocate<D: Data>(&mut self, identifier: usize, address: &[usize]) -> (Vec<Box<dyn Push<Message<D>>>>, Box<dyn Pull<Message<D>>>) {
-        if address.len() == 0 { panic!("Unacceptable address: Length zero"); }
-        let mut paths = self.paths.borrow_mut();
-        paths.insert(identifier, address.to_vec());
-        self.temp_channel_ids.borrow_mut().push(identifier);
-        self.allocator.borrow_mut().allocate(identifier)
+    fn allocate_with<D: Any+Send+Sync, C: Codec<D>>(&mut self, identifier: usize, address: &[usize]) -> (Vec<BoxedPush<D>>, BoxedPull<D>) {
+        self.try_allocate_with::<D, C>(identifier, address).unwrap_or_else(|error| panic!("{}", error))
     }
     fn pipeline<T: 'static>(&mut self, identifier: usize, address: &[usize]) -> (ThreadPusher<Message<T>>, ThreadPuller<Message<T>>) {
-        if address.len() == 0 { panic!("Unacceptable address: Length zero"); }
-        let mut paths = self.paths.borrow_mut();
-        paths.insert(identifier, address.to_vec());
-        self.temp_channel_ids.borrow_mut().push(identifier);
-        self.allocator.borrow_mut().pipeline(identifier)
+        self.try_pipeline(identifier, address).unwrap_or_else(|error| panic!("{}", error))
     }
 
     fn new_identifier(&mut self) -> usize { self.new_identifier() }
     fn log_register(&self) -> RefMut<crate::logging_core::Registry<crate::logging::WorkerIdentifier>> {
         self.log_register()
     }
+    fn extensions(&self) -> &Extensions { &self.extensions }
 }
 
 impl<A: Allocate> Scheduler for Worker<A> {
@@ -262,21 +992,63 @@ impl<A: Allocate> Scheduler for Worker<A> {
 impl<A: Allocate> Worker<A> {
     /// Allocates a new `Worker` bound to a channel allocator.
     pub fn new(config: Config, c: A) -> Worker<A> {
-        let now = Instant::now();
+        let clock = config.clock.clone().unwrap_or_else(crate::logging_core::clock::system);
+        let now = clock.now();
         let index = c.index();
-        Worker {
+        if let Some(affinity) = &config.affinity {
+            affinity.apply(index);
+        }
+        let checkpoints = crate::checkpoint::Checkpoints::new(config.checkpoint_store.clone(), index);
+        let capabilities = if config.track_capabilities { Some(Default::default()) } else { None };
+        let trace = config.trace.as_ref().map(|mode| {
+            let trace = crate::trace::Trace::open(mode, index).unwrap_or_else(|error| panic!("failed to open trace of worker {}: {}", index, error));
+            Rc::new(RefCell::new(trace))
+        });
+        let lineage = if config.trace_lineage { Some(crate::dataflow::channels::lineage::Tracer::new(index)) } else { None };
+        let mut worker = Worker {
             config,
-            timer: now.clone(),
+            timer: now,
             paths:  Default::default(),
             allocator: Rc::new(RefCell::new(c)),
             identifiers:  Default::default(),
             dataflows: Default::default(),
             dataflow_counter:  Default::default(),
-            logging: Rc::new(RefCell::new(crate::logging_core::Registry::new(now.clone(), index))),
-            activations: Rc::new(RefCell::new(Activations::new(now.clone()))),
+            logging: Rc::new(RefCell::new(crate::logging_core::Registry::new(now, index).with_clock(clock.clone()))),
+            extensions: Extensions {
+                checkpoints: Rc::new(RefCell::new(checkpoints)),
+                exports: Default::default(),
+                latencies: crate::dataflow::operators::latency::Latencies::new(now, clock.clone()),
+                topology: Default::default(),
+                metrics: Default::default(),
+                channel_stats: Default::default(),
+                hooks: Default::default(),
+                watchdog: crate::scheduling::watchdog::Watchdog::new(clock.clone()),
+                progress_batches: Default::default(),
+                clock: clock.clone(),
+                lineage,
+                capabilities,
+            },
+            trace,
+            poison: None,
+            idle: Default::default(),
+            profiled: Rc::new(Cell::new(now)),
+            steps: Rc::new(RefCell::new(crate::scheduling::steps::Steps::new(now))),
+            step_loggers: Default::default(),
+            activations: Rc::new(RefCell::new(Activations::with_clock(now, clock))),
             active_dataflows: Default::default(),
             temp_channel_ids:  Default::default(),
+        };
+        #[cfg(feature = "prometheus")]
+        if let Some(exporter) = worker.config.prometheus.as_ref() {
+            exporter.attach(&worker);
         }
+        worker.find_step_loggers();
+        if worker.config.isolate_panics {
+            let identifier = worker.new_identifier();
+            let (pushers, puller) = worker.allocator.borrow_mut().allocate(identifier);
+            worker.poison = Some(Rc::new(RefCell::new(crate::scheduling::poison::Poison::new(index, pushers, puller))));
+        }
+        worker
     }
 
     /// Performs one step of the computation.
@@ -333,12 +1105,23 @@ impl<A: Allocate> Worker<A> {
     /// ```
     pub fn step_or_park(&mut self, duration: Option<Duration>) -> bool {
 
+        let mut phases = crate::scheduling::steps::StepPhases::default();
+        let mut parked = None;
+        let clock = self.extensions.clock.clone();
+        let loggers = self.step_loggers.borrow().clone();
+        // Time the phases of the step only if their summaries are logged.
+        let mut phase = crate::scheduling::steps::Stopwatch::start(loggers.schedule.as_ref().map(|_| clock.clone()));
+
         {   // Process channel events. Activate responders.
             let mut allocator = self.allocator.borrow_mut();
             allocator.receive();
             let events = allocator.events().clone();
             let mut borrow = events.borrow_mut();
             let paths = self.paths.borrow();
+            let mut trace = self.trace.as_ref().map(|trace| trace.borrow_mut());
+            if let Some(trace) = trace.as_mut() {
+                trace.begin_step();
+            }
             for (channel, _event) in borrow.drain(..) {
                 // TODO: Pay more attent to `_event`.
                 // Consider tracking whether a channel
@@ -347,18 +1130,51 @@ impl<A: Allocate> Worker<A> {
                 // TODO: This is a sloppy way to deal
                 // with channels that may not be alloc'd.
                 if let Some(path) = paths.get(&channel) {
+                    match trace.as_mut() {
+                        // Replayed steps activate the recorded channels instead.
+                        Some(trace) if trace.is_replay() => continue,
+                        Some(trace) => trace.activated(channel),
+                        None => { },
+                    }
                     self.activations
                         .borrow_mut()
                         .activate(&path[..]);
                 }
             }
+            if let Some(trace) = trace.as_mut() {
+                for channel in trace.replay_activations() {
+                    if let Some(path) = paths.get(&channel) {
+                        self.activations
+                            .borrow_mut()
+                            .activate(&path[..]);
+                    }
+                }
+            }
+        }
+
+        // Drop dataflows that panicked at other workers.
+        let notices = self.poison.as_ref().map(|poison| poison.borrow_mut().receive()).unwrap_or_default();
+        for (dataflow, worker, message) in notices {
+            self.poison_dataflow(dataflow, worker, message);
         }
+        phases.receive = phase.lap();
 
         // Organize activations.
         self.activations
             .borrow_mut()
             .advance();
 
+        // Report quiescence, which the handler may end by introducing work.
+        let quiescent = self.activations.borrow().empty_for();
+        if quiescent != Some(Duration::new(0,0)) {
+            if let Some(handler) = self.idle.borrow_mut().as_mut() {
+                handler(quiescent);
+                self.activations
+                    .borrow_mut()
+                    .advance();
+            }
+        }
+
         // Consider parking only if we have no pending events, some dataflows, and a non-zero duration.
         let empty_for = self.activations.borrow().empty_for();
         // Determine the minimum park duration, where `None` are an absence of a constraint.
@@ -366,19 +1182,45 @@ impl<A: Allocate> Worker<A> {
             (Some(x), Some(y)) => Some(std::cmp::min(x,y)),
             (x, y) => x.or(y),
         };
+        // Wake in time to check for stalls.
+        let delay = match (delay, self.config.watchdog.and_then(|timeout| self.extensions.watchdog.until_next(timeout))) {
+            (Some(x), Some(y)) => Some(std::cmp::min(x,y)),
+            (x, y) => x.or(y),
+        };
+
+        let mut park = !self.dataflows.borrow().is_empty() && delay != Some(Duration::new(0,0));
+        // Send held progress updates rather than park, as workers may wait on them.
+        if park && self.extensions.progress_batches.is_holding() {
+            self.extensions.progress_batches.flush(&self.activations);
+            self.activations.borrow_mut().advance();
+            park = false;
+        }
+        let mut replaying = false;
+        if let Some(trace) = self.trace.as_ref() {
+            let mut trace = trace.borrow_mut();
+            replaying = trace.is_replay();
+            if replaying { park = trace.replay_park(); }
+            else if park { trace.parked(); }
+        }
 
-        if !self.dataflows.borrow().is_empty() && delay != Some(Duration::new(0,0)) {
+        phases.tidy = phase.lap();
+
+        if park {
 
             // Log parking and flush log.
             self.logging().as_mut().map(|l| l.log(crate::logging::ParkEvent::park(delay)));
             self.logging.borrow_mut().flush();
 
-            self.allocator
-                .borrow()
-                .await_events(delay);
+            // Replayed steps park without waiting, as the events they await are in the trace.
+            if !replaying {
+                self.allocator
+                    .borrow()
+                    .await_events(delay);
+            }
 
             // Log return from unpark.
             self.logging().as_mut().map(|l| l.log(crate::logging::ParkEvent::unpark()));
+            parked = Some(phase.lap());
         }
         else {   // Schedule active dataflows.
 
@@ -387,26 +1229,101 @@ impl<A: Allocate> Worker<A> {
                 .borrow_mut()
                 .for_extensions(&[], |index| active_dataflows.push(index));
 
+            #[cfg(feature = "prometheus")]
+            let start = self.config.prometheus.as_ref().map(|_| clock.now());
+
+            let isolate = self.poison.is_some();
+            let mut panicked = Vec::new();
             let mut dataflows = self.dataflows.borrow_mut();
+            // Schedule dataflows by decreasing weight, and otherwise by index.
+            active_dataflows.sort_by_key(|index| std::cmp::Reverse(dataflows.get(index).map(|wrapper| wrapper.weight).unwrap_or(1)));
             for index in active_dataflows.drain(..) {
                 // Step dataflow if it exists, remove if not incomplete.
                 if let Entry::Occupied(mut entry) = dataflows.entry(index) {
                     // TODO: This is a moment at which a scheduling decision is being made.
-                    let incomplete = entry.get_mut().step();
+                    // Weighted dataflows are stepped again while they re-activate themselves.
+                    let mut result = entry.get_mut().try_step(isolate);
+                    for _ in 1 .. entry.get().weight {
+                        if result != Ok(true) || !self.activations.borrow_mut().refresh(&[index]) { break; }
+                        result = entry.get_mut().try_step(isolate);
+                    }
+                    let incomplete = match result {
+                        Ok(incomplete) => incomplete,
+                        Err(message) => {
+                            panicked.push((index, message));
+                            continue;
+                        }
+                    };
                     if !incomplete {
                         let mut paths = self.paths.borrow_mut();
                         for channel in entry.get_mut().channel_ids.drain(..) {
                             paths.remove(&channel);
+                            self.extensions.channel_stats.forget(channel);
                         }
                         entry.remove_entry();
                     }
                 }
             }
+            drop(dataflows);
+
+            // Poison dataflows that panicked, and inform the other workers.
+            for (dataflow, message) in panicked {
+                let index = self.index();
+                if self.poison.as_ref().map(|poison| poison.borrow_mut().panicked(dataflow, &message)).unwrap_or(false) {
+                    self.poison_dataflow(dataflow, index, message);
+                }
+            }
+
+            #[cfg(feature = "prometheus")]
+            if let (Some(exporter), Some(start)) = (self.config.prometheus.as_ref(), start) {
+                exporter.observe_step(self.index(), clock.elapsed(start));
+            }
+            self.extensions.progress_batches.end_flush();
+            phases.dataflows = phase.lap();
+        }
+
+        // Read the clock for the profiler only if the worker is profiling its operators.
+        if let Some((period, count)) = self.config.profile {
+            let now = clock.now();
+            if now.saturating_duration_since(self.profiled.get()) >= period {
+                self.profiled.set(now);
+                self.log_profile(count);
+            }
+        }
+
+        if let Some(timeout) = self.config.watchdog {
+            self.extensions.watchdog.check(timeout, &self.activations);
+        }
+
+        if let Some(logger) = loggers.channels.as_ref() {
+            logger.log_many(self.extensions.channel_stats.changed().into_iter().map(crate::logging::ChannelStatsEvent::from));
+        }
+        if let Some(logger) = loggers.edges.as_ref() {
+            logger.log_many(self.extensions.channel_stats.changed_edges().into_iter().map(crate::logging::EdgeStatsEvent::from));
         }
 
         // Clean up, indicate if dataflows remain.
         self.logging.borrow_mut().flush();
+        if let Some(trace) = self.trace.as_ref() {
+            trace.borrow_mut().flush();
+        }
+        phases.logging = phase.lap();
         self.allocator.borrow_mut().release();
+        phases.release = phase.lap();
+
+        let summary = {
+            let mut steps = self.steps.borrow_mut();
+            steps.record(&phases, parked);
+            match (self.config.schedule_summary, loggers.schedule) {
+                (Some(period), Some(logger)) => steps.summary(period, clock.now()).map(|summary| (summary, logger)),
+                _ => None,
+            }
+        };
+        if let Some((summary, mut logger)) = summary {
+            logger.log(summary);
+            logger.flush();
+        }
+
         !self.dataflows.borrow().is_empty()
     }
 
@@ -468,6 +1385,48 @@ impl<A: Allocate> Worker<A> {
         while func() { self.step_or_park(duration); }
     }
 
+    /// Steps the worker until the frontier of `probe` is no longer less than `time`.
+    ///
+    /// This replaces loops of `worker.step()` that wait on a probe, and parks the worker rather
+    /// than spinning while it awaits messages from other workers, as [`Self::step_or_park`]
+    /// with no timeout. The method also returns once the worker has no dataflows, as the
+    /// frontier can then no longer change.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Exchange, Probe};
+    ///
+    /// timely::execute(timely::Config::process(2), |worker| {
+    ///     let mut input = InputHandle::new();
+    ///     let probe = worker.dataflow(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .exchange(|x: &u64| *x)
+    ///              .probe()
+    ///     });
+    ///     for round in 0 .. 10 {
+    ///         input.send(round);
+    ///         input.advance_to(round + 1);
+    ///         worker.await_frontier(&probe, input.time());
+    ///         assert!(!probe.less_than(&(round + 1)));
+    ///     }
+    ///     input.close();
+    ///     worker.await_done(&probe);
+    ///     assert!(probe.done());
+    /// }).unwrap();
+    /// ```
+    pub fn await_frontier<T: Timestamp>(&mut self, probe: &crate::dataflow::ProbeHandle<T>, time: &T) {
+        while probe.less_than(time) && self.step_or_park(None) { }
+    }
+
+    /// Steps the worker until the frontier of `probe` is empty.
+    ///
+    /// As [`Self::await_frontier`], the worker parks while it awaits messages, and the method
+    /// returns once the worker has no dataflows.
+    pub fn await_done<T: Timestamp>(&mut self, probe: &crate::dataflow::ProbeHandle<T>) {
+        while !probe.done() && self.step_or_park(None) { }
+    }
+
     /// The index of the worker out of its peers.
     ///
     /// # Examples
@@ -515,6 +1474,14 @@ impl<A: Allocate> Worker<A> {
     /// ```
     pub fn timer(&self) -> Instant { self.timer }
 
+    /// The clock from which the worker reads the time, as configured by [`Config::clock`].
+    pub fn clock(&self) -> &Arc<dyn crate::logging_core::clock::Clock> { &self.extensions.clock }
+
+    /// The time elapsed on the worker's clock since its [`timer`](Self::timer) started.
+    ///
+    /// This is `self.timer().elapsed()` when the worker reads the system clock.
+    pub fn elapsed(&self) -> Duration { self.extensions.clock.elapsed(self.timer) }
+
     /// Allocate a new worker-unique identifier.
     ///
     /// This method is public, though it is not expected to be widely used outside
@@ -524,6 +1491,39 @@ impl<A: Allocate> Worker<A> {
         *self.identifiers.borrow() - 1
     }
 
+    /// Allocates channel `identifier` for the operator at `address`, as
+    /// [`AsWorker::allocate_with`] does, but returns an error rather than panicking if the address
+    /// is invalid.
+    #[allow(clippy::type_complexity)]
+    pub fn try_allocate_with<D: Any+Send+Sync, C: Codec<D>>(&mut self, identifier: usize, address: &[usize]) -> Result<(Vec<BoxedPush<D>>, BoxedPull<D>), Error> {
+        self.register_channel(identifier, address, "Exchange")?;
+        let (pushers, puller) = self.allocator.borrow_mut().allocate_with::<D, C>(identifier);
+        Ok(match &self.trace {
+            Some(trace) => (pushers, Box::new(crate::trace::TracePuller::<D, C>::new(identifier, trace.clone(), puller))),
+            None => (pushers, puller),
+        })
+    }
+
+    /// Allocates thread-local channel `identifier` for the operator at `address`, as
+    /// [`AsWorker::pipeline`] does, but returns an error rather than panicking if the address is
+    /// invalid.
+    #[allow(clippy::type_complexity)]
+    pub fn try_pipeline<T: 'static>(&mut self, identifier: usize, address: &[usize]) -> Result<(ThreadPusher<Message<T>>, ThreadPuller<Message<T>>), Error> {
+        self.register_channel(identifier, address, "Pipeline")?;
+        Ok(self.allocator.borrow_mut().pipeline(identifier))
+    }
+
+    // Records the operator and kind of channel `identifier`, whose address must not be empty.
+    fn register_channel(&self, identifier: usize, address: &[usize], pact: &str) -> Result<(), Error> {
+        if address.is_empty() {
+            return Err(Error::Address { identifier, address: address.to_vec() });
+        }
+        self.paths.borrow_mut().insert(identifier, address.to_vec());
+        self.temp_channel_ids.borrow_mut().push(identifier);
+        self.extensions.topology.borrow_mut().set_pact(identifier, pact);
+        Ok(())
+    }
+
     /// Access to named loggers.
     ///
     /// # Examples
@@ -589,6 +1589,63 @@ impl<A: Allocate> Worker<A> {
         self.dataflow_core(name, logging, Box::new(()), |_, child| func(child))
     }
 
+    /// Construct a new dataflow, returning a description of any mistake in its construction.
+    ///
+    /// This method checks the graph of the dataflow and of its nested scopes, as described in
+    /// the [`validation`](crate::progress::validation) module, and returns the first mistake
+    /// found rather than installing the dataflow. [`Worker::dataflow`] makes the same checks,
+    /// and panics with the description of the mistake.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::Scope;
+    /// use timely::dataflow::operators::{ToStream, Feedback, ConnectLoop, Concat, Map};
+    /// use timely::progress::validation::GraphError;
+    ///
+    /// timely::execute_directly(|worker| {
+    ///     // A feedback edge with a zero summary never advances timestamps around the loop.
+    ///     let result = worker.try_dataflow::<u64,_,_>(|scope| {
+    ///         let (handle, cycle) = scope.feedback(0);
+    ///         (0 .. 10).to_stream(scope)
+    ///                  .concat(&cycle)
+    ///                  .map(|x| x + 1)
+    ///                  .connect_loop(handle);
+    ///     });
+    ///     match result {
+    ///         Err(GraphError::CycleWithoutIncrement { operators, .. }) => assert_eq!(operators.len(), 3),
+    ///         other => panic!("unexpected result: {:?}", other),
+    ///     }
+    ///
+    ///     // A feedback stream whose handle is never connected leaves its operator unbuilt.
+    ///     let result = worker.try_dataflow::<u64,_,_>(|scope| {
+    ///         let (_handle, cycle) = scope.feedback::<u64>(1);
+    ///         (0 .. 10).to_stream(scope).concat(&cycle);
+    ///     });
+    ///     assert!(matches!(result, Err(GraphError::MissingOperator { .. })));
+    ///
+    ///     assert!(worker.try_dataflow::<u64,_,_>(|scope| { (0 .. 10).to_stream(scope); }).is_ok());
+    /// });
+    /// ```
+    pub fn try_dataflow<T, R, F>(&mut self, func: F) -> Result<R, GraphError>
+    where
+        T: Refines<()>,
+        F: FnOnce(&mut Child<Self, T>)->R,
+    {
+        let logging = self.logging.borrow_mut().get("timely");
+        self.try_dataflow_core("Dataflow", logging, Box::new(()), |_, child| func(child))
+    }
+
+    /// Construct a new dataflow with a (purely cosmetic) name, returning a description of any
+    /// mistake in its construction, as for [`Worker::try_dataflow`].
+    pub fn try_dataflow_named<T, R, F>(&mut self, name: &str, func: F) -> Result<R, GraphError>
+    where
+        T: Refines<()>,
+        F: FnOnce(&mut Child<Self, T>)->R,
+    {
+        let logging = self.logging.borrow_mut().get("timely");
+        self.try_dataflow_core(name, logging, Box::new(()), |_, child| func(child))
+    }
+
     /// Construct a new dataflow with specific configurations.
     ///
     /// This method constructs a new dataflow, using a name, logger, and additional
@@ -614,7 +1671,48 @@ impl<A: Allocate> Worker<A> {
     ///     );
     /// });
     /// ```
-    pub fn dataflow_core<T, R, F, V>(&mut self, name: &str, mut logging: Option<TimelyLogger>, mut resources: V, func: F) -> R
+    ///
+    /// The dataflow is checked as for [`Worker::try_dataflow`], and the method panics with the
+    /// description of any mistake in its construction.
+    pub fn dataflow_core<T, R, F, V>(&mut self, name: &str, logging: Option<TimelyLogger>, resources: V, func: F) -> R
+    where
+        T: Refines<()>,
+        F: FnOnce(&mut V, &mut Child<Self, T>)->R,
+        V: Any+'static,
+    {
+        self.try_dataflow_core(name, logging, resources, func)
+            .unwrap_or_else(|error| panic!("invalid dataflow: {}", error))
+    }
+
+    /// Construct a new dataflow with specific configurations, returning a description of any
+    /// mistake in its construction, as for [`Worker::try_dataflow`].
+    pub fn try_dataflow_core<T, R, F, V>(&mut self, name: &str, logging: Option<TimelyLogger>, resources: V, func: F) -> Result<R, GraphError>
+    where
+        T: Refines<()>,
+        F: FnOnce(&mut V, &mut Child<Self, T>)->R,
+        V: Any+'static,
+    {
+        self.install_dataflow(name, logging, resources, None, func)
+    }
+
+    /// Constructs a new dataflow from a template, with parameters for this instance.
+    ///
+    /// The dataflow is constructed by the template's function applied to `params`, as with
+    /// `dataflow_named`. Its progress tracking reuses the path summaries compiled for previous
+    /// instances of the template, when their graphs are identical. See the
+    /// [`template`](crate::dataflow::template) module for an example.
+    pub fn instantiate<T, P, R>(&mut self, template: &Template<A, T, P, R>, params: P) -> R
+    where
+        T: Refines<()>,
+    {
+        let logging = self.logging.borrow_mut().get("timely");
+        let summaries = Some(template.summaries().clone());
+        self.install_dataflow(template.name(), logging, Box::new(()), summaries, |_, child| template.build(params, child))
+            .unwrap_or_else(|error| panic!("invalid dataflow: {}", error))
+    }
+
+    // Constructs and installs a new dataflow, optionally sharing the path summaries of its graph.
+    fn install_dataflow<T, R, F, V>(&mut self, name: &str, mut logging: Option<TimelyLogger>, mut resources: V, summaries: Option<Rc<RefCell<SummaryCache<T>>>>, func: F) -> Result<R, GraphError>
     where
         T: Refines<()>,
         F: FnOnce(&mut V, &mut Child<Self, T>)->R,
@@ -625,7 +1723,11 @@ impl<A: Allocate> Worker<A> {
         let identifier = self.new_identifier();
 
         let progress_logging = self.logging.borrow_mut().get("timely/progress");
-        let subscope = SubgraphBuilder::new_from(dataflow_index, addr, logging.clone(), progress_logging.clone(), name);
+        self.find_step_loggers();
+        let mut subscope = SubgraphBuilder::new_from(dataflow_index, addr, logging.clone(), progress_logging.clone(), name);
+        if let Some(summaries) = summaries {
+            subscope.share_summaries(summaries);
+        }
         let subscope = RefCell::new(subscope);
 
         let result = {
@@ -638,7 +1740,21 @@ impl<A: Allocate> Worker<A> {
             func(&mut resources, &mut builder)
         };
 
-        let mut operator = subscope.into_inner().build(self);
+        let subscope = subscope.into_inner();
+        let checked = subscope.validate().and_then(|()| self.check_channels(name, &self.temp_channel_ids.borrow()));
+        if let Err(error) = checked {
+            let channel_ids = self.temp_channel_ids.borrow_mut().drain(..).collect::<Vec<_>>();
+            self.forget_channels(&channel_ids);
+            self.extensions.topology.borrow_mut().forget(dataflow_index);
+            self.extensions.metrics.forget(dataflow_index);
+            return Err(error);
+        }
+
+        let mut operator = subscope.build(self);
+        self.extensions.topology.borrow_mut().describe_operator(crate::dataflow::operators::generic::OperatorInfo {
+            name: operator.name().to_owned(),
+            ..crate::dataflow::operators::generic::OperatorInfo::new(dataflow_index, identifier, operator.path())
+        });
 
         logging.as_mut().map(|l| l.log(crate::logging::OperatesEvent {
             id: identifier,
@@ -651,8 +1767,7 @@ impl<A: Allocate> Worker<A> {
         operator.get_internal_summary();
         operator.set_external_summary();
 
-        let mut temp_channel_ids = self.temp_channel_ids.borrow_mut();
-        let channel_ids = temp_channel_ids.drain(..).collect::<Vec<_>>();
+        let channel_ids = self.temp_channel_ids.borrow_mut().drain(..).collect::<Vec<_>>();
 
         let wrapper = Wrapper {
             logging,
@@ -660,11 +1775,35 @@ impl<A: Allocate> Worker<A> {
             operate: Some(Box::new(operator)),
             resources: Some(Box::new(resources)),
             channel_ids,
+            weight: 1,
         };
         self.dataflows.borrow_mut().insert(dataflow_index, wrapper);
 
-        result
+        Ok(result)
+
+    }
+
+    // Checks that the channels of the dataflow `name` have identifiers distinct from one another
+    // and from those of other dataflows.
+    fn check_channels(&self, name: &str, channel_ids: &[usize]) -> Result<(), GraphError> {
+        let dataflows = self.dataflows.borrow();
+        let mut seen = std::collections::HashSet::new();
+        for identifier in channel_ids.iter() {
+            let existing = dataflows.values().any(|wrapper| wrapper.channel_ids.contains(identifier));
+            if !seen.insert(*identifier) || existing {
+                return Err(GraphError::DuplicateChannel { dataflow: name.to_owned(), identifier: *identifier });
+            }
+        }
+        Ok(())
+    }
 
+    // Discards the paths and statistics of channels no longer in use.
+    fn forget_channels(&self, channel_ids: &[usize]) {
+        let mut paths = self.paths.borrow_mut();
+        for channel in channel_ids.iter() {
+            paths.remove(channel);
+            self.extensions.channel_stats.forget(*channel);
+        }
     }
 
     /// Drops an identified dataflow.
@@ -676,13 +1815,88 @@ impl<A: Allocate> Worker<A> {
     pub fn drop_dataflow(&mut self, dataflow_identifier: usize) {
         if let Some(mut entry) = self.dataflows.borrow_mut().remove(&dataflow_identifier) {
             // Garbage collect channel_id to path information.
-            let mut paths = self.paths.borrow_mut();
-            for channel in entry.channel_ids.drain(..) {
-                paths.remove(&channel);
-            }
+            self.forget_channels(&entry.channel_ids);
+            self.extensions.checkpoints.borrow_mut().forget(dataflow_identifier);
+            self.extensions.topology.borrow_mut().forget(dataflow_identifier);
+            self.extensions.metrics.forget(dataflow_identifier);
         }
     }
 
+    /// The name of the operator or scope at `address`, if it is part of an installed dataflow.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::Scope;
+    /// use timely::dataflow::operators::{ToStream, Map, Enter, Leave};
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let address = worker.dataflow_named::<u64,_,_>("Pipeline", |scope| {
+    ///         let stream = (0 .. 10).to_stream(scope);
+    ///         scope.region_named("stage-one", |inner| {
+    ///             let stream = stream.enter(inner).map(|x| x + 1);
+    ///             let mut address = inner.addr();
+    ///             address.push(stream.name().node);
+    ///             stream.leave();
+    ///             address
+    ///         })
+    ///     });
+    ///
+    ///     assert_eq!(worker.operator_name(&address[..1]), Some("Pipeline".to_string()));
+    ///     assert_eq!(worker.operator_path_name(&address), "Pipeline/stage-one/Map");
+    /// }).unwrap();
+    /// ```
+    pub fn operator_name(&self, address: &[usize]) -> Option<String> {
+        self.extensions.topology.borrow().name(address).map(|name| name.to_owned())
+    }
+
+    /// The operators and channels of the identified dataflow, if it is installed.
+    ///
+    /// See the [`graph`](crate::dataflow::graph) module for an example.
+    pub fn dataflow_graph(&self, dataflow_index: usize) -> Option<crate::dataflow::graph::DataflowGraph> {
+        self.extensions.topology.borrow().graph(dataflow_index)
+    }
+
+    /// The names of the operator or scope at `address` and its enclosing scopes, separated by `/`.
+    ///
+    /// Addresses not part of an installed dataflow are reported by their index.
+    pub fn operator_path_name(&self, address: &[usize]) -> String {
+        let topology = self.extensions.topology.borrow();
+        (1 ..= address.len())
+            .map(|len| topology.name(&address[.. len]).map(|n| n.to_owned()).unwrap_or_else(|| address[len - 1].to_string()))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Describes the operator or scope at `address`, if it is part of an installed dataflow.
+    ///
+    /// Operators built with an `OperatorBuilder` report the types of the records at their ports,
+    /// and the parallelization contracts of their inputs. Other operators and scopes report their
+    /// names and numbers of ports.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::Scope;
+    /// use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let address = worker.dataflow::<u64,_,_>(|scope| {
+    ///         let stream = (0 .. 10u32).to_stream(scope).exchange(|x| *x as u64);
+    ///         let mut address = scope.addr();
+    ///         address.push(stream.name().node);
+    ///         stream.inspect(|x| println!("seen: {:?}", x));
+    ///         address
+    ///     });
+    ///
+    ///     let info = worker.describe_operator(&address).unwrap();
+    ///     assert_eq!(info.name, "Exchange");
+    ///     assert_eq!(info.inputs[0].data, Some("u32"));
+    ///     assert_eq!(info.inputs[0].pact, Some("Exchange"));
+    /// }).unwrap();
+    /// ```
+    pub fn describe_operator(&self, address: &[usize]) -> Option<crate::dataflow::operators::generic::OperatorInfo> {
+        self.extensions.topology.borrow().describe(address).cloned()
+    }
+
     /// Returns the next index to be used for dataflow construction.
     ///
     /// This identifier will appear in the address of contained operators, and can
@@ -691,11 +1905,397 @@ impl<A: Allocate> Worker<A> {
         *self.dataflow_counter.borrow()
     }
 
+    /// Requests a checkpoint of the identified dataflow at `frontier`, and returns its identifier.
+    ///
+    /// The worker commits the checkpoint to the configured store once each operator state of
+    /// the dataflow has been saved, which requires the operators' input frontiers to reach
+    /// `frontier`. All workers must request the same checkpoints, in the same order. See the
+    /// [`checkpoint`](crate::checkpoint) module for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no checkpoint store is configured, or if `T` is not the dataflow's timestamp.
+    pub fn checkpoint<T: Timestamp>(&mut self, dataflow_identifier: usize, frontier: Antichain<T>) -> u64 {
+        self.extensions.checkpoints.borrow_mut().request(dataflow_identifier, frontier)
+    }
+
+    /// Constructs a new dataflow from the state of a committed checkpoint.
+    ///
+    /// The dataflow is constructed by `func` as with `dataflow`, except that each operator
+    /// state registered during construction may retrieve its checkpointed state with
+    /// `StateHandle::restored`, and each input is advanced to the checkpoint's frontier. The
+    /// inputs should then resume by introducing the records at times from that frontier on;
+    /// records at earlier times are reflected in the restored state.
+    ///
+    /// Checkpoints requested afterwards receive identifiers following `checkpoint`. See the
+    /// [`checkpoint`](crate::checkpoint) module for an example.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no checkpoint store is configured, or if `checkpoint` was not committed by
+    /// all workers.
+    pub fn dataflow_restored<T, R, F>(&mut self, checkpoint: u64, func: F) -> R
+    where
+        T: Timestamp+Refines<()>,
+        F: FnOnce(&mut Child<Self, T>)->R,
+    {
+        let dataflow_index = self.next_dataflow_index();
+        let peers = self.peers();
+        self.extensions.checkpoints.borrow_mut().begin_restore::<T>(dataflow_index, checkpoint, peers);
+        let result = self.dataflow(func);
+        self.extensions.checkpoints.borrow_mut().end_restore();
+        result
+    }
+
+    /// Indicates whether this worker has committed the identified checkpoint.
+    ///
+    /// The checkpoint itself is complete once all workers have committed it.
+    pub fn checkpoint_committed(&self, checkpoint: u64) -> bool {
+        self.extensions.checkpoints.borrow().committed(checkpoint)
+    }
+
+    /// Reports the live capabilities of this worker's operators, which may be blocking progress.
+    ///
+    /// Each capability is described by its operator's address and name, its output port, its time, and
+    /// the backtrace of its creation, and is also logged to the "timely" log stream. Operators that
+    /// hold capabilities without `Capability`s, such as input operators and those built with the raw
+    /// `OperatorBuilder`, report each time they hold without a backtrace. Nothing is reported
+    /// unless the worker was configured with `track_capabilities(true)`.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::UnorderedInput;
+    ///
+    /// let mut config = timely::Config::thread();
+    /// config.worker = config.worker.track_capabilities(true);
+    /// timely::execute(config, |worker| {
+    ///     let (_input, mut cap) = worker.dataflow::<u64,_,_>(|scope| scope.new_unordered_input::<u64>().0);
+    ///     cap.downgrade(&3);
+    ///     worker.step();
+    ///
+    ///     // The unordered input holds only the capability for time 3.
+    ///     let holders = worker.report_capability_holders();
+    ///     assert_eq!(holders.len(), 1);
+    ///     assert_eq!(holders[0].time, "3");
+    ///     drop(cap);
+    /// }).unwrap();
+    /// ```
+    pub fn report_capability_holders(&mut self) -> Vec<crate::logging::CapabilityHeldEvent> {
+        let mut holders = self.extensions.capabilities.as_ref().map(|tracker| tracker.holders()).unwrap_or_default();
+        for holder in holders.iter_mut() {
+            if !holder.addr.is_empty() {
+                holder.name = self.operator_path_name(&holder.addr);
+            }
+        }
+        if let Some(logger) = self.logging() {
+            for holder in holders.iter() {
+                logger.log(holder.clone());
+            }
+        }
+        holders
+    }
+
+    /// The latencies recorded by `measure_latency` operators under `name`, if any.
+    ///
+    /// See the [`latency`](crate::dataflow::operators::latency) module for an example.
+    pub fn latency(&self, name: &str) -> Option<crate::dataflow::operators::latency::LatencyHistogram> {
+        self.extensions.latencies.get(name)
+    }
+
+    /// Writes the events of the log stream `name` to the file at `path`, encoded as `format`.
+    ///
+    /// This binds a logger for the log stream, replacing any logger already bound to it. Writes
+    /// are buffered, and flushed periodically as configured by [`Config::log_file_buffering`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::logging::{LogFormat, TimelyEvent};
+    /// use timely::dataflow::operators::{ToStream, Inspect};
+    ///
+    /// let path = std::env::temp_dir().join("timely_log_to_file_example.json");
+    /// let path2 = path.clone();
+    /// timely::execute(timely::Config::thread(), move |worker| {
+    ///     worker.log_to_file::<TimelyEvent, _>("timely", &path2, LogFormat::Json).unwrap();
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 10).to_stream(scope).inspect(|x| println!("seen: {:?}", x));
+    ///     });
+    /// }).unwrap();
+    ///
+    /// let contents = std::fs::read_to_string(&path).unwrap();
+    /// assert!(contents.lines().any(|line| line.contains("\"Operates\"")));
+    /// std::fs::remove_file(&path).unwrap();
+    /// ```
+    pub fn log_to_file<E, P>(&self, name: &str, path: P, format: crate::logging::LogFormat) -> std::io::Result<()>
+    where
+        E: serde::Serialize + abomonation::Abomonation + 'static,
+        P: AsRef<std::path::Path>,
+    {
+        use crate::logging::{BatchLogger, JsonWriter, LogFormat, PeriodicFlush};
+        use crate::dataflow::operators::capture::EventWriter;
+
+        let (capacity, period) = self.config.log_file_buffering.unwrap_or((1 << 16, Duration::from_secs(1)));
+        let stream = PeriodicFlush::new(std::fs::File::create(path)?, capacity, period);
+        let mut register = self.log_register();
+        match format {
+            LogFormat::Json => {
+                let mut writer = JsonWriter::new(stream);
+                register.insert::<E,_>(name, move |_time, data| writer.publish_batch(data));
+            },
+            LogFormat::Binary => {
+                let mut writer = BatchLogger::new(EventWriter::new(stream));
+                register.insert::<E,_>(name, move |time, data| writer.publish_batch(time, data));
+            },
+        }
+        Ok(())
+    }
+
+    /// The scheduling measurements of the operators of installed dataflows, in address order.
+    ///
+    /// Operators are measured only if the worker is configured to measure them, with
+    /// [`Config::operator_metrics`], and otherwise none are reported. See the [`metrics`](crate::scheduling::metrics) module for an example.
+    pub fn metrics(&self) -> Vec<crate::scheduling::metrics::OperatorMetrics> {
+        self.extensions.metrics.snapshot()
+    }
+
+    /// The counts of batches and records moved by the channels of installed dataflows, in order
+    /// of channel identifier, with the counts pushed along each of their edges.
+    ///
+    /// See the [`stats`](crate::dataflow::channels::stats) module for an example.
+    pub fn channel_stats(&self) -> Vec<crate::dataflow::channels::stats::ChannelStats> {
+        self.extensions.channel_stats.snapshot()
+    }
+
+    /// Installs `hook`, to be called around each scheduling of the operators of this worker.
+    ///
+    /// Any previously installed hook is replaced. See the [`hooks`](crate::scheduling::hooks)
+    /// module for an example.
+    pub fn set_schedule_hook<H: crate::scheduling::hooks::ScheduleHook+'static>(&mut self, hook: H) {
+        self.extensions.hooks.set(Some(Box::new(hook)));
+    }
+
+    /// Removes and returns the installed schedule hook, if any.
+    pub fn remove_schedule_hook(&mut self) -> Option<Box<dyn crate::scheduling::hooks::ScheduleHook>> {
+        self.extensions.hooks.set(None)
+    }
+
+    /// The scheduling measurements of the `count` operators of installed dataflows that have spent
+    /// longest scheduled, longest first.
+    ///
+    /// Operators are measured only if the worker is configured to measure them, with
+    /// [`Config::operator_metrics`] or [`Config::profile`].
+    ///
+    /// # Examples
+    /// ```
+    /// use timely::dataflow::operators::{ToStream, Map, Inspect};
+    ///
+    /// let mut config = timely::Config::thread();
+    /// config.worker = config.worker.operator_metrics(true);
+    /// timely::execute(config, |worker| {
+    ///     worker.dataflow::<u64,_,_>(|scope| {
+    ///         (0 .. 10).to_stream(scope)
+    ///                  .map(|x| x + 1)
+    ///                  .inspect(|x| println!("seen: {:?}", x));
+    ///     });
+    ///     while worker.step() { }
+    ///
+    ///     let top = worker.top_operators(2);
+    ///     assert_eq!(top.len(), 2);
+    ///     assert!(top[0].elapsed >= top[1].elapsed);
+    /// }).unwrap();
+    /// ```
+    pub fn top_operators(&self, count: usize) -> Vec<crate::scheduling::metrics::OperatorMetrics> {
+        self.extensions.metrics.top(count)
+    }
+
+    /// The measurements of the phases of the worker's steps, accumulated over all steps.
+    ///
+    /// The phases are timed only while a logger for the "timely/schedule" log stream is
+    /// installed, and otherwise only the steps and parks are counted.
+    ///
+    /// See the [`steps`](crate::scheduling::steps) module for the phases of a step, and an example.
+    pub fn step_stats(&self) -> crate::scheduling::steps::StepStats {
+        self.steps.borrow().total
+    }
+
+    // Finds the loggers of each step, which are then used until the next dataflow is built.
+    fn find_step_loggers(&self) {
+        let register = self.logging.borrow();
+        *self.step_loggers.borrow_mut() = StepLoggers {
+            channels: register.get("timely/channels"),
+            edges: register.get("timely/communication"),
+            schedule: register.get("timely/schedule"),
+        };
+    }
+
+    // Logs the `count` longest scheduled operators to the "timely/profile" log stream.
+    fn log_profile(&self, count: usize) {
+        if let Some(logger) = self.log_register().get::<crate::logging::ProfileEvent>("timely/profile") {
+            logger.log_many(self.top_operators(count).into_iter().enumerate().map(|(rank, metrics)| {
+                crate::logging::ProfileEvent {
+                    rank,
+                    id: metrics.id,
+                    addr: metrics.addr,
+                    name: metrics.name,
+                    schedules: metrics.schedules,
+                    elapsed: metrics.elapsed,
+                }
+            }));
+        }
+    }
+
+    /// Installs `handler`, to be called in each step that finds the worker quiescent.
+    ///
+    /// The worker is quiescent when it has received no messages that its operators have not yet
+    /// been scheduled for, and no operator is activated; until new input arrives, or a delayed
+    /// activation falls due, further steps will do no work. The handler is called with the time
+    /// until the next delayed activation, if any, before the worker decides whether to park, and
+    /// may introduce new work, for example by supplying input, in place of the worker parking.
+    /// Quiescence is local to the worker: other workers may still be at work, and send it more
+    /// messages. Any previously installed handler is replaced.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    /// use timely::dataflow::InputHandle;
+    /// use timely::dataflow::operators::{Input, Exchange, Probe};
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     let mut input = InputHandle::new();
+    ///     let probe = worker.dataflow(|scope| {
+    ///         scope.input_from(&mut input)
+    ///              .exchange(|x: &u64| *x)
+    ///              .probe()
+    ///     });
+    ///
+    ///     let quiescent = Rc::new(Cell::new(false));
+    ///     let flag = quiescent.clone();
+    ///     worker.set_idle_handler(move |_next| flag.set(true));
+    ///
+    ///     // Step until the worker has nothing left to do, rather than for some duration.
+    ///     input.send(0);
+    ///     input.advance_to(1);
+    ///     while !quiescent.replace(false) {
+    ///         worker.step();
+    ///     }
+    ///     assert!(!probe.less_than(&1));
+    /// }).unwrap();
+    /// ```
+    pub fn set_idle_handler<F: FnMut(Option<Duration>)+'static>(&mut self, handler: F) {
+        *self.idle.borrow_mut() = Some(Box::new(handler));
+    }
+
+    /// Sets the weight of the identified dataflow, which is one unless set otherwise.
+    ///
+    /// In each step, the worker schedules active dataflows in order of decreasing weight, and
+    /// steps a dataflow with weight `w` up to `w` times, for as long as it re-activates itself.
+    /// Each active dataflow is stepped at least once, so that dataflows of low weight are not
+    /// starved, while a dataflow of high weight that has plenty to do receives proportionally
+    /// more of the worker's time. Weights only affect the dataflows of this worker, and need not
+    /// agree across workers.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::cell::RefCell;
+    /// use std::rc::Rc;
+    /// use timely::scheduling::Scheduler;
+    /// use timely::dataflow::operators::generic::operator::source;
+    ///
+    /// timely::execute(timely::Config::thread(), |worker| {
+    ///     // Two dataflows that always have more to do, and count their schedules.
+    ///     let counts = Rc::new(RefCell::new(vec![0; 2]));
+    ///     for dataflow in 0 .. 2 {
+    ///         let counts = counts.clone();
+    ///         worker.dataflow::<u64,_,_>(|scope| {
+    ///             source::<_, u64, _, _>(scope, "Busy", |capability, info| {
+    ///                 let activator = scope.activator_for(&info.address[..]);
+    ///                 move |_output| {
+    ///                     let _ = &capability;
+    ///                     counts.borrow_mut()[dataflow] += 1;
+    ///                     activator.activate();
+    ///                 }
+    ///             });
+    ///         });
+    ///     }
+    ///     worker.set_dataflow_weight(0, 3);
+    ///
+    ///     for _ in 0 .. 10 { worker.step(); }
+    ///     // The weighted dataflow was scheduled three times as often.
+    ///     assert_eq!(counts.borrow()[0] - 1, 3 * (counts.borrow()[1] - 1));
+    ///
+    ///     // The dataflows would otherwise run forever.
+    ///     worker.drop_dataflow(0);
+    ///     worker.drop_dataflow(1);
+    /// }).unwrap();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is zero.
+    pub fn set_dataflow_weight(&mut self, dataflow_identifier: usize, weight: usize) {
+        assert!(weight > 0, "dataflow weights must be positive");
+        if let Some(wrapper) = self.dataflows.borrow_mut().get_mut(&dataflow_identifier) {
+            wrapper.weight = weight;
+        }
+    }
+
+    /// Describes the progress of each operator of the dataflow `dataflow_index`, as seen by this
+    /// worker, or `None` if the dataflow is not installed.
+    ///
+    /// See the [`snapshot`](crate::progress::snapshot) module for an example.
+    pub fn progress_snapshot(&self, dataflow_index: usize) -> Option<crate::progress::snapshot::ProgressSnapshot> {
+        let dataflows = self.dataflows.borrow();
+        let operate = dataflows.get(&dataflow_index)?.operate.as_ref()?;
+        let mut operators = Vec::new();
+        operate.progress_snapshot(&mut operators);
+        Some(crate::progress::snapshot::ProgressSnapshot {
+            dataflow: dataflow_index,
+            name: operate.name().to_owned(),
+            operators,
+        })
+    }
+
     /// List the current dataflow indices.
     pub fn installed_dataflows(&self) -> Vec<usize> {
         self.dataflows.borrow().keys().cloned().collect()
     }
 
+    /// Installs `handler`, to be called with each panic that poisons a dataflow of this worker.
+    ///
+    /// The handler is called both for panics at this worker and for panics reported by other
+    /// workers, and must not install a handler itself. See the [`poison`](crate::scheduling::poison)
+    /// module for an example.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the worker was not configured with [`Config::isolate_panics`].
+    pub fn on_dataflow_panic<F: FnMut(&crate::scheduling::poison::DataflowPanic)+'static>(&mut self, handler: F) {
+        self.poison
+            .as_ref()
+            .expect("panic isolation is not enabled; see Config::isolate_panics")
+            .borrow_mut()
+            .set_handler(Box::new(handler));
+    }
+
+    /// The indices of dataflows dropped because they panicked, in increasing order.
+    pub fn poisoned_dataflows(&self) -> Vec<usize> {
+        self.poison.as_ref().map(|poison| poison.borrow().poisoned()).unwrap_or_default()
+    }
+
+    // Drops `dataflow`, which panicked at `worker` with `message`, and reports the panic.
+    fn poison_dataflow(&mut self, dataflow: usize, worker: usize, message: String) {
+        let name = self.operator_name(&[dataflow]);
+        self.drop_dataflow(dataflow);
+        let panic = crate::scheduling::poison::DataflowPanic { dataflow, name, worker, message };
+        if let Some(poison) = self.poison.as_ref() {
+            poison.borrow_mut().report(&panic);
+        }
+    }
+
     // Acquire a new distinct dataflow identifier.
     fn allocate_dataflow_index(&mut self) -> usize {
         *self.dataflow_counter.borrow_mut() += 1;
@@ -705,6 +2305,9 @@ impl<A: Allocate> Worker<A> {
 
 use crate::communication::Message;
 
+/// A function to call when the worker is quiescent, with the time until its next delayed activation.
+type IdleHandler = Box<dyn FnMut(Option<Duration>)>;
+
 impl<A: Allocate> Clone for Worker<A> {
     fn clone(&self) -> Self {
         Worker {
@@ -716,6 +2319,13 @@ impl<A: Allocate> Clone for Worker<A> {
             dataflows: self.dataflows.clone(),
             dataflow_counter: self.dataflow_counter.clone(),
             logging: self.logging.clone(),
+            extensions: self.extensions.clone(),
+            trace: self.trace.clone(),
+            poison: self.poison.clone(),
+            idle: self.idle.clone(),
+            profiled: self.profiled.clone(),
+            steps: self.steps.clone(),
+            step_loggers: self.step_loggers.clone(),
             activations: self.activations.clone(),
             active_dataflows: Vec::new(),
             temp_channel_ids: self.temp_channel_ids.clone(),
@@ -723,15 +2333,36 @@ impl<A: Allocate> Clone for Worker<A> {
     }
 }
 
+// The loggers of each step, looked up once rather than at every step.
+#[derive(Default, Clone)]
+struct StepLoggers {
+    channels: Option<crate::logging::ChannelStatsLogger>,
+    edges: Option<crate::logging::EdgeStatsLogger>,
+    schedule: Option<crate::logging::ScheduleSummaryLogger>,
+}
+
 struct Wrapper {
     logging: Option<TimelyLogger>,
     identifier: usize,
     operate: Option<Box<dyn Schedule>>,
     resources: Option<Box<dyn Any>>,
     channel_ids: Vec<usize>,
+    // The most times the dataflow is stepped in one step of the worker.
+    weight: usize,
 }
 
 impl Wrapper {
+    /// Steps the dataflow, catching any panic if `isolate` is set and returning its message.
+    fn try_step(&mut self, isolate: bool) -> Result<bool, String> {
+        if isolate {
+            std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| self.step()))
+                .map_err(|payload| crate::scheduling::poison::message(&*payload))
+        }
+        else {
+            Ok(self.step())
+        }
+    }
+
     /// Steps the dataflow, indicates if it remains incomplete.
     ///
     /// If the dataflow is incomplete, this call will drop it and its resources,
@@ -0,0 +1,108 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::time::Duration;
+
+use timely::logging_core::clock::Instant;
+use timely::scheduling::Activations;
+
+// Collects the paths active after an advance.
+fn active(activations: &mut Activations) -> Vec<Vec<usize>> {
+    activations.advance();
+    let paths = RefCell::new(Vec::new());
+    activations.map_active(|path| paths.borrow_mut().push(path.to_vec()));
+    paths.into_inner()
+}
+
+// Repeated activations of a path are reported once.
+#[test]
+fn activations_are_deduplicated() {
+    let mut activations = Activations::new(Instant::now());
+    activations.activate(&[0, 1]);
+    activations.activate(&[0, 2]);
+    activations.activate(&[0, 1]);
+    assert_eq!(active(&mut activations), vec![vec![0, 1], vec![0, 2]]);
+    // The path can be activated again once reported.
+    activations.activate(&[0, 1]);
+    assert_eq!(active(&mut activations), vec![vec![0, 1]]);
+}
+
+// Canceled activations do not occur, and do not keep the worker from parking.
+#[test]
+fn canceled_activations_do_not_occur() {
+    let mut activations = Activations::new(Instant::now());
+    let token = activations.schedule_after(&[0, 1], Duration::from_millis(1));
+    activations.schedule_after(&[0, 2], Duration::from_millis(2));
+    activations.cancel(token);
+    std::thread::sleep(Duration::from_millis(5));
+    assert_eq!(active(&mut activations), vec![vec![0, 2]]);
+    assert_eq!(activations.empty_for(), Some(Duration::new(0, 0)));
+    assert_eq!(active(&mut activations), Vec::<Vec<usize>>::new());
+    assert_eq!(activations.empty_for(), None);
+
+    // A canceled activation at the front of the queue does not determine the park duration.
+    let token = activations.schedule_after(&[0, 1], Duration::from_millis(1));
+    activations.schedule_after(&[0, 2], Duration::from_secs(60));
+    activations.cancel(token);
+    assert!(active(&mut activations).is_empty());
+    assert!(activations.empty_for().unwrap() > Duration::from_secs(30));
+}
+
+// Periodic activations recur until canceled.
+#[test]
+fn periodic_activations_recur() {
+    let mut activations = Activations::new(Instant::now());
+    let token = activations.activate_every(&[0, 3], Duration::from_millis(1));
+    let mut count = 0;
+    while count < 3 {
+        std::thread::sleep(Duration::from_millis(2));
+        count += active(&mut activations).len();
+    }
+    activations.cancel(token);
+    std::thread::sleep(Duration::from_millis(2));
+    assert!(active(&mut activations).is_empty());
+    assert_eq!(activations.empty_for(), None);
+}
+
+// Redundant delayed activations of a path are discarded.
+#[test]
+fn delayed_activations_are_deduplicated() {
+    let mut activations = Activations::new(Instant::now());
+    for _ in 0 .. 10 {
+        activations.activate_after(&[0, 4], Duration::from_millis(1));
+    }
+    std::thread::sleep(Duration::from_millis(2));
+    assert_eq!(active(&mut activations), vec![vec![0, 4]]);
+    assert!(active(&mut activations).is_empty());
+    assert_eq!(activations.empty_for(), None);
+}
+
+// A worker parks until its next periodic activation.
+#[test]
+fn worker_honors_periodic_activations() {
+    use timely::dataflow::operators::generic::source;
+    use timely::dataflow::operators::Probe;
+    use timely::scheduling::Scheduler;
+
+    timely::execute(timely::Config::thread(), |worker| {
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            source::<_, (), _, _>(scope, "Periodic", |capability, info| {
+                let activator = scope.activator_for(&info.address[..]);
+                let token = activator.activate_every(Duration::from_millis(1));
+                let mut capability = Some(capability);
+                let mut wakes = 0;
+                move |_output| {
+                    wakes += 1;
+                    if wakes == 5 {
+                        activator.cancel(token);
+                        capability.take();
+                    }
+                }
+            })
+            .probe()
+        });
+        while !probe.done() {
+            worker.step_or_park(None);
+        }
+    }).unwrap();
+}
@@ -0,0 +1,97 @@
+extern crate timely;
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::{Context, Poll, Wake, Waker};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Probe};
+use timely::Config;
+
+// Counts the times it is woken.
+struct Counter(AtomicUsize);
+
+impl Wake for Counter {
+    fn wake(self: Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+// The future is woken once the frontier empties, and not before.
+#[test]
+fn future_wakes_when_done() {
+    let (mut coop, (mut input, probe)) = timely::execute_cooperatively(|worker| {
+        let mut input = InputHandle::<u64, u64>::new();
+        let probe = worker.dataflow(|scope| scope.input_from(&mut input).probe());
+        (input, probe)
+    });
+
+    let counter = Arc::new(Counter(AtomicUsize::new(0)));
+    let waker = Waker::from(counter.clone());
+    let mut context = Context::from_waker(&waker);
+    let mut done = Box::pin(probe.when_done());
+
+    for round in 0 .. 5 {
+        assert_eq!(done.as_mut().poll(&mut context), Poll::Pending);
+        input.send(round);
+        input.advance_to(round + 1);
+        while probe.less_than(input.time()) {
+            coop.worker().step();
+        }
+    }
+    assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+
+    input.close();
+    while !probe.done() {
+        coop.worker().step();
+    }
+    assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+    assert_eq!(done.as_mut().poll(&mut context), Poll::Ready(()));
+}
+
+// A future dropped while pending leaves the probe usable, and wakes nothing.
+#[test]
+fn dropped_future_is_not_woken() {
+    let (mut coop, (input, probe)) = timely::execute_cooperatively(|worker| {
+        let mut input = InputHandle::<u64, u64>::new();
+        let probe = worker.dataflow(|scope| scope.input_from(&mut input).probe());
+        (input, probe)
+    });
+
+    let counter = Arc::new(Counter(AtomicUsize::new(0)));
+    let waker = Waker::from(counter.clone());
+    let mut done = Box::pin(probe.when_done());
+    assert_eq!(done.as_mut().poll(&mut Context::from_waker(&waker)), Poll::Pending);
+    drop(done);
+
+    input.close();
+    while !probe.done() {
+        coop.worker().step();
+    }
+    assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+}
+
+// Workers await frontiers and completion across an exchange, without stepping loops.
+#[test]
+fn await_frontier_and_done() {
+    let guards = timely::execute(Config::process(3), |worker| {
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x: &u64| *x)
+                 .probe()
+        });
+        for round in 0 .. 20u64 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.await_frontier(&probe, input.time());
+            assert!(!probe.less_than(&(round + 1)));
+        }
+        input.close();
+        worker.await_done(&probe);
+        probe.done()
+    }).unwrap();
+
+    assert!(guards.join().into_iter().all(|done| done.unwrap()));
+}
@@ -1,9 +1,14 @@
 extern crate timely;
 
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
 use timely::{Config, CommunicationConfig, WorkerConfig};
 use timely::dataflow::channels::pact::Pipeline;
 use timely::dataflow::operators::{Feedback, ConnectLoop};
 use timely::dataflow::operators::generic::operator::Operator;
+use timely::synchronization::Barrier;
 
 #[test] fn barrier_sync_1w() { barrier_sync_helper(CommunicationConfig::Thread); }
 #[test] fn barrier_sync_2w() { barrier_sync_helper(CommunicationConfig::Process(2)); }
@@ -38,3 +43,25 @@ fn barrier_sync_helper(comm_config: ::timely::CommunicationConfig) {
         });
     }).unwrap(); // asserts error-free execution;
 }
+
+#[test] fn barrier_wait_1w() { barrier_wait_helper(CommunicationConfig::Thread); }
+#[test] fn barrier_wait_3w() { barrier_wait_helper(CommunicationConfig::Process(3)); }
+
+// This method asserts that no worker passes a barrier before all workers have reached it, even
+// as workers arrive late and park while they wait.
+fn barrier_wait_helper(comm_config: ::timely::CommunicationConfig) {
+    let config = Config {
+        communication: comm_config,
+        worker: WorkerConfig::default(),
+    };
+    let arrived = Arc::new(AtomicUsize::new(0));
+    timely::execute(config, move |worker| {
+        let mut barrier = Barrier::new(worker);
+        for round in 0 .. 10 {
+            std::thread::sleep(Duration::from_millis((worker.index() * round % 3) as u64));
+            arrived.fetch_add(1, Ordering::SeqCst);
+            barrier.wait();
+            assert!(arrived.load(Ordering::SeqCst) >= worker.peers() * (round + 1));
+        }
+    }).unwrap();
+}
@@ -0,0 +1,67 @@
+extern crate timely;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::{Input, Inspect, Map, ToStream};
+use timely::dataflow::operators::generic::Operator;
+use timely::{CommunicationConfig, Config, WorkerConfig};
+
+// Exchanges records between two workers, returning the largest batch either received.
+fn largest_batch(config: WorkerConfig, batch: Option<usize>) -> usize {
+    let config = Config { communication: CommunicationConfig::Process(2), worker: config };
+    let results = timely::execute(config, move |worker| {
+        let largest = Rc::new(Cell::new(0));
+        let largest2 = largest.clone();
+        let mut input = InputHandle::<u64, u64>::new();
+        worker.dataflow(|scope| {
+            let route = |x: &u64| *x;
+            let pact = match batch {
+                Some(batch) => Exchange::new_with_batch(route, batch),
+                None => Exchange::new(route),
+            };
+            scope.input_from(&mut input)
+                 .sink(pact, "Largest", move |input| {
+                     input.for_each(|_time, data| {
+                         largest2.set(std::cmp::max(largest2.get(), data.len()));
+                     });
+                 });
+        });
+        for x in 0 .. 100_000u64 { input.send(x); }
+        input.close();
+        while worker.step() { }
+        largest.get()
+    }).unwrap().join();
+    results.into_iter().map(|r| r.unwrap()).max().unwrap()
+}
+
+#[test]
+fn configured_batch_size() {
+    assert_eq!(largest_batch(WorkerConfig::default(), None), 1024);
+    assert_eq!(largest_batch(WorkerConfig::default().batch_size(100), None), 100);
+    assert_eq!(largest_batch(WorkerConfig::default(), Some(16384)), 16384);
+}
+
+// Workers sharing a thread batch records as each is configured to.
+#[test]
+fn batch_size_per_worker() {
+    fn batches(config: WorkerConfig) -> (timely::execute::Cooperative, Rc<RefCell<Vec<usize>>>) {
+        timely::execute_cooperatively_from(config, |worker| {
+            let lengths = Rc::new(RefCell::new(Vec::new()));
+            let sink = lengths.clone();
+            worker.dataflow::<u64,_,_>(|scope| {
+                (0 .. 1000u64).to_stream(scope)
+                              .map(|x| x + 1)
+                              .inspect_batch(move |_time, data| sink.borrow_mut().push(data.len()));
+            });
+            lengths
+        })
+    }
+    let (mut small, small_lengths) = batches(WorkerConfig::default().batch_size(10));
+    let (mut large, large_lengths) = batches(WorkerConfig::default().batch_size(500));
+    while small.tick() | large.tick() { }
+    assert_eq!(*small_lengths.borrow(), vec![10; 100]);
+    assert_eq!(*large_lengths.borrow(), vec![500; 2]);
+}
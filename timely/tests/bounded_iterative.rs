@@ -0,0 +1,77 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::{InputHandle, Scope};
+use timely::dataflow::operators::{Concat, ConnectLoop, Enter, Input, Inspect, Leave, LoopVariable, Map, Probe, ToStream};
+use timely::dataflow::operators::capture::{Capture, Extract};
+use timely::order::Bounded;
+
+// Records that would circulate forever leave the loop at the bound, and the computation completes.
+#[test]
+fn circulation_stops_at_bound() {
+    let captured = timely::example(|scope| {
+        let stream = Some(0u64).to_stream(scope);
+        scope.bounded_iterative::<10,_,_>(|inner| {
+            let (handle, cycle) = inner.loop_variable(Bounded::new(1));
+            let stream = stream.enter(inner).concat(&cycle).map(|x| x + 1);
+            stream.connect_loop(handle);
+            stream.leave()
+        })
+        .capture()
+    });
+    let data = captured.extract().into_iter().flat_map(|(_time, data)| data).collect::<Vec<_>>();
+    assert_eq!(data, (1 .. 12).collect::<Vec<_>>());
+}
+
+// Iterations advance by the summary of the feedback edge, and stop short of exceeding the bound.
+#[test]
+fn summaries_respect_bound() {
+    let captured = timely::example(|scope| {
+        let stream = Some(()).to_stream(scope);
+        scope.bounded_iterative::<10,_,_>(|inner| {
+            let (handle, cycle) = inner.loop_variable(Bounded::new(3));
+            let stream = stream.enter(inner).concat(&cycle);
+            stream.connect_loop(handle);
+            stream.capture()
+        })
+    });
+    let iterations = captured.extract().into_iter().map(|(time, _data)| time.inner.get()).collect::<Vec<_>>();
+    assert_eq!(iterations, vec![0, 3, 6, 9]);
+}
+
+// Frontiers outside the loop advance with the input, although records circulate without end.
+#[test]
+fn outer_frontier_advances() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::new();
+        let results = Rc::new(RefCell::new(Vec::new()));
+        let sink = results.clone();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            let stream = scope.input_from(&mut input);
+            scope.bounded_iterative::<5,_,_>(|inner| {
+                let (handle, cycle) = inner.loop_variable(Bounded::new(1));
+                let stream = stream.enter(inner).concat(&cycle).map(|x: u64| x * 2);
+                stream.connect_loop(handle);
+                stream.leave()
+            })
+            .inspect_time(move |time, x| sink.borrow_mut().push((*time, *x)))
+            .probe()
+        });
+
+        for round in 0 .. 3u64 {
+            input.send(round + 1);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(&(round + 1)));
+            let expected = (1 ..= 6).map(|iteration| (round, (round + 1) << iteration)).collect::<Vec<_>>();
+            assert_eq!(results.borrow_mut().drain(..).collect::<Vec<_>>(), expected);
+        }
+    });
+}
+
+#[test]
+#[should_panic(expected = "exceeds its bound")]
+fn counters_may_not_exceed_bound() {
+    Bounded::<3>::new(4);
+}
@@ -0,0 +1,64 @@
+extern crate timely;
+
+use timely::dataflow::operators::{UnorderedInput, Capture, Probe};
+use timely::dataflow::operators::capture::Extract;
+use timely::dataflow::operators::unordered_input::BoundedLateness;
+
+#[test]
+fn watermark_trails_the_greatest_event_time() {
+    let mut policy = BoundedLateness::new(3u64);
+    assert_eq!(policy.watermark(), 0);
+    assert!(policy.observe(2));
+    assert_eq!(policy.watermark(), 0);
+    assert!(policy.observe(10));
+    assert_eq!(policy.watermark(), 7);
+    // Records behind the greatest event time, but not the watermark, do not move it.
+    assert!(policy.observe(8));
+    assert_eq!(policy.watermark(), 7);
+    assert!(!policy.observe(6));
+    assert_eq!(policy.max_event_time(), Some(10));
+    policy.advance_to(20);
+    assert_eq!(policy.watermark(), 20);
+    assert!(!policy.observe(19));
+    assert_eq!(policy.max_event_time(), Some(10));
+}
+
+#[test]
+fn late_records_go_to_the_side_stream() {
+    let (on_time, late) = timely::execute_directly(|worker| {
+        let (mut input, on_time, late) = worker.dataflow(|scope| {
+            let (input, on_time, late) = scope.new_input_with_lateness(1, |x: &(u64, char)| x.0);
+            (input, on_time.capture(), late.capture())
+        });
+        for record in [(5, 'a'), (4, 'b'), (3, 'c'), (7, 'd'), (5, 'e'), (6, 'f')] {
+            input.give(record);
+        }
+        assert_eq!(input.watermark(), 6);
+        (on_time, late)
+    });
+    assert_eq!(on_time.extract(), vec![(4, vec![(4, 'b')]), (5, vec![(5, 'a')]), (6, vec![(6, 'f')]), (7, vec![(7, 'd')])]);
+    assert_eq!(late.extract(), vec![(4, vec![(3, 'c')]), (6, vec![(5, 'e')])]);
+}
+
+#[test]
+fn streams_advance_with_the_watermark() {
+    timely::execute_directly(|worker| {
+        let (mut input, on_time, late) = worker.dataflow(|scope| {
+            let (input, on_time, late) = scope.new_input_with_lateness(2, |x: &u64| *x);
+            (input, on_time.probe(), late.probe())
+        });
+
+        input.give(10);
+        worker.step_while(|| on_time.less_than(&8) || late.less_than(&8));
+        // Both streams hold at the watermark, of 8.
+        assert!(on_time.less_equal(&8) && late.less_equal(&8));
+
+        // Without records, the watermark advances only when asked.
+        input.advance_to(15);
+        worker.step_while(|| on_time.less_than(&15) || late.less_than(&15));
+        assert_eq!(input.watermark(), 15);
+
+        input.close();
+        worker.step_while(|| !on_time.done() || !late.done());
+    });
+}
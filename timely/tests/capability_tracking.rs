@@ -0,0 +1,91 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::WorkerConfig;
+use timely::dataflow::InputHandle;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Input, Probe, ToStream, UnorderedInput};
+use timely::dataflow::operators::generic::Operator;
+use timely::dataflow::operators::generic::builder_raw::OperatorBuilder;
+
+// Capabilities retained by operators are reported with where they were created.
+#[test]
+fn retained_capabilities_are_reported() {
+    let mut config = timely::Config::thread();
+    config.worker = config.worker.track_capabilities(true);
+    timely::execute(config, |worker| {
+        let mut input = InputHandle::new();
+        let stash = Rc::new(RefCell::new(None));
+        let hoard = stash.clone();
+        worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .unary(Pipeline, "Hoard", move |_cap, _info| move |input, output| {
+                     input.for_each(|time, data| {
+                         output.session(&time).give_vec(&mut data.replace(Vec::new()));
+                         hoard.borrow_mut().get_or_insert_with(|| time.retain());
+                     });
+                 });
+        });
+        input.send(0u64);
+        input.advance_to(5);
+        worker.step_while(|| stash.borrow().is_none());
+
+        // The input handle holds time 5, and the operator the time 0 it retained.
+        let holders = worker.report_capability_holders();
+        let times = holders.iter().map(|h| (h.name.as_str(), h.time.as_str())).collect::<Vec<_>>();
+        assert_eq!(times, vec![("Dataflow/Input", "5"), ("Dataflow/Hoard", "0")]);
+        assert!(holders[0].backtrace.is_empty());
+        assert!(holders[1].backtrace.contains("retained_capabilities_are_reported"));
+        worker.drop_dataflow(worker.installed_dataflows()[0]);
+    }).unwrap();
+}
+
+// Operators built from raw progress counts report the times they hold.
+#[test]
+fn raw_operators_are_reported() {
+    let mut config = timely::Config::thread();
+    config.worker = config.worker.track_capabilities(true);
+    timely::execute(config, |worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            let mut builder = OperatorBuilder::new("Raw".to_owned(), scope.clone());
+            let (_output, _stream) = builder.new_output::<u64>();
+            let mut first = true;
+            builder.build(move |progress| {
+                // Trade the initial capability for one at time 3.
+                if std::mem::replace(&mut first, false) {
+                    progress.internals[0].update(3, 1);
+                    progress.internals[0].update(0, -1);
+                }
+                true
+            });
+        });
+        worker.step();
+        let holders = worker.report_capability_holders();
+        assert_eq!(holders.iter().map(|h| (h.name.as_str(), h.port, h.time.as_str())).collect::<Vec<_>>(), vec![("Dataflow/Raw", 0, "3")]);
+        worker.drop_dataflow(worker.installed_dataflows()[0]);
+    }).unwrap();
+}
+
+// Each worker reports only the capabilities of its own operators, and forgets those of dropped
+// dataflows.
+#[test]
+fn tracked_per_worker() {
+    let (mut tracked, held) = timely::execute_cooperatively_from(WorkerConfig::default().track_capabilities(true), |worker| {
+        worker.dataflow::<u64,_,_>(|scope| scope.new_unordered_input::<u64>().0)
+    });
+    let (mut untracked, _) = timely::execute_cooperatively_from(WorkerConfig::default(), |worker| {
+        worker.dataflow::<u64,_,_>(|scope| (0 .. 10u64).to_stream(scope).probe())
+    });
+    tracked.tick();
+    untracked.tick();
+
+    assert_eq!(tracked.worker().report_capability_holders().len(), 1);
+    assert!(untracked.worker().report_capability_holders().is_empty());
+
+    let dataflow = tracked.worker().installed_dataflows()[0];
+    tracked.worker().drop_dataflow(dataflow);
+    drop(held);
+    assert!(tracked.worker().report_capability_holders().is_empty());
+}
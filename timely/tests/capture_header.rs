@@ -0,0 +1,61 @@
+extern crate abomonation;
+extern crate timely;
+
+use timely::dataflow::operators::capture::{CaptureHeader, Event, EventPusher, EventReader, EventWriter};
+use timely::dataflow::operators::capture::event::EventIterator;
+
+// Reads all events available from `reader`.
+fn read_all<T: abomonation::Abomonation+Clone, D: abomonation::Abomonation+Clone>(reader: &mut EventReader<T, D, &[u8]>) -> Vec<Event<T, D>> {
+    let mut events = Vec::new();
+    for _ in 0 .. 10 {
+        if let Some(event) = reader.next() {
+            events.push(event.clone());
+        }
+    }
+    events
+}
+
+// Streams start with a header describing their types, which the reader checks.
+#[test]
+fn header_round_trip() {
+    let mut bytes = Vec::new();
+    {
+        let mut writer = EventWriter::<u64, (u64, u32), _>::new(&mut bytes);
+        writer.push(Event::Progress(vec![(0, 1)]));
+        writer.push(Event::Messages(0, vec![(1, 2)]));
+    }
+    let (header, length) = CaptureHeader::decode(&bytes).unwrap().unwrap();
+    assert_eq!(header, CaptureHeader::of::<u64, (u64, u32)>());
+    assert_eq!(header.crate_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(header.timestamp, "u64");
+    assert_eq!(header.data, "(u64, u32)");
+    assert_eq!(length % 8, 0);
+
+    // A prefix of a header is incomplete, rather than invalid.
+    assert_eq!(CaptureHeader::decode(&bytes[.. 10]), Ok(None));
+    assert!(CaptureHeader::decode(b"not a header").is_err());
+
+    let mut reader = EventReader::<u64, (u64, u32), _>::new(&bytes[..]);
+    assert_eq!(read_all(&mut reader), vec![Event::Progress(vec![(0, 1)]), Event::Messages(0, vec![(1, 2)])]);
+    assert_eq!(reader.header(), Some(&header));
+}
+
+// Reading events as other types fails before any event is decoded.
+#[test]
+#[should_panic(expected = "but are read as Event<u32, u64>")]
+fn header_mismatch_panics() {
+    let mut bytes = Vec::new();
+    EventWriter::<u64, u64, _>::new(&mut bytes).push(Event::Messages(0, vec![1]));
+    let mut reader = EventReader::<u32, u64, _>::new(&bytes[..]);
+    read_all(&mut reader);
+}
+
+// Streams written before headers were introduced can still be read.
+#[test]
+fn without_header_reads_legacy_streams() {
+    let mut bytes = Vec::new();
+    unsafe { abomonation::encode(&Event::<u64, u64>::Messages(3, vec![4, 5]), &mut bytes).unwrap(); }
+    let mut reader = EventReader::<u64, u64, _>::without_header(&bytes[..]);
+    assert_eq!(read_all(&mut reader), vec![Event::Messages(3, vec![4, 5])]);
+    assert_eq!(reader.header(), None);
+}
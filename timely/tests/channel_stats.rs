@@ -0,0 +1,75 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::channels::pact::Exchange;
+use timely::dataflow::operators::{Input, Probe};
+use timely::dataflow::operators::generic::Operator;
+use timely::logging::EdgeStatsEvent;
+use timely::{CommunicationConfig, Config, WorkerConfig};
+
+#[test]
+fn edge_stats_count_records_by_target() {
+    let logged = Arc::new(Mutex::new(Vec::new()));
+    let logged2 = logged.clone();
+    let config = Config { communication: CommunicationConfig::Process(2), worker: WorkerConfig::default() };
+    timely::execute(config, move |worker| {
+        let logged = logged2.clone();
+        worker.log_register().insert::<EdgeStatsEvent,_>("timely/communication", move |_time, data| {
+            logged.lock().unwrap().extend(data.drain(..).map(|(_, _, event)| event));
+        });
+
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .unary(Exchange::new(|_x: &u64| 0), "ToZero", |_cap, _info| |input, output| {
+                     input.for_each(|time, data| {
+                         output.session(&time).give_vec(&mut data.replace(Vec::new()));
+                     });
+                 })
+                 .probe()
+        });
+        input.send_batch(&mut (0 .. 100u64).collect());
+        input.advance_to(1);
+        worker.step_while(|| probe.less_than(input.time()));
+
+        let index = worker.index();
+        let stats = worker.channel_stats();
+        let edges = stats.iter().flat_map(|s| s.edges.iter()).filter(|e| e.records > 0).collect::<Vec<_>>();
+        assert!(edges.iter().all(|e| e.source == index));
+        let to_zero = edges.iter().find(|e| e.target == 0 && e.records == 100).expect("no edge to worker zero");
+        if index == 0 { assert_eq!(to_zero.bytes, 0); }
+        else { assert!(to_zero.bytes >= 800); }
+        assert!(edges.iter().all(|e| e.target == 0 || e.records == 0));
+    }).unwrap();
+
+    let logged = logged.lock().unwrap();
+    assert!(logged.iter().any(|e| e.source == 1 && e.target == 0 && e.records == 100 && e.bytes >= 800));
+}
+
+// Without a log of edge statistics, records are counted but batches are not measured.
+#[test]
+fn edge_bytes_only_while_logged() {
+    let config = Config { communication: CommunicationConfig::Process(2), worker: WorkerConfig::default() };
+    timely::execute(config, move |worker| {
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .unary(Exchange::new(|_x: &u64| 0), "ToZero", |_cap, _info| |input, output| {
+                     input.for_each(|time, data| {
+                         output.session(&time).give_vec(&mut data.replace(Vec::new()));
+                     });
+                 })
+                 .probe()
+        });
+        input.send_batch(&mut (0 .. 100u64).collect());
+        input.advance_to(1);
+        worker.step_while(|| probe.less_than(input.time()));
+
+        let stats = worker.channel_stats();
+        let edges = stats.iter().flat_map(|s| s.edges.iter()).filter(|e| e.records > 0).collect::<Vec<_>>();
+        assert!(edges.iter().any(|e| e.target == 0 && e.records == 100));
+        assert!(edges.iter().all(|e| e.bytes == 0));
+    }).unwrap();
+}
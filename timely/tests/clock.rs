@@ -0,0 +1,169 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use timely::dataflow::operators::{ToStream, Inspect, Probe};
+use timely::communication::allocator::simulation::SimulationConfig;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::Input;
+use timely::logging::StallEvent;
+use timely::logging_core::clock::MockClock;
+use timely::scheduling::fuel::Fuel;
+use timely::worker::Worker;
+use timely::WorkerConfig;
+
+// A paced source produces records only as the mock clock advances, and is activated when due.
+#[test]
+fn paced_source_follows_mock_clock() {
+    let clock = MockClock::new();
+    let allocator = timely::communication::allocator::Thread::new();
+    let mut worker = Worker::new(WorkerConfig::default().clock(clock.clone()), allocator);
+
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let seen2 = seen.clone();
+    let probe = worker.dataflow::<u64,_,_>(|scope| {
+        (0 .. 30u64).to_stream_paced(scope, 10)
+                    .inspect(move |x| seen2.borrow_mut().push(*x))
+                    .probe()
+    });
+
+    for _ in 0 .. 10 {
+        worker.step();
+    }
+    assert_eq!(seen.borrow().len(), 1);
+    assert_eq!(worker.elapsed(), Duration::from_secs(0));
+
+    clock.advance(Duration::from_millis(500));
+    for _ in 0 .. 10 {
+        worker.step();
+    }
+    assert_eq!(seen.borrow().len(), 6);
+
+    clock.advance(Duration::from_secs(5));
+    while !probe.done() {
+        worker.step();
+    }
+    assert_eq!(*seen.borrow(), (0 .. 30).collect::<Vec<_>>());
+    assert_eq!(worker.elapsed(), Duration::from_millis(5500));
+}
+
+// Logged events carry the time of the mock clock.
+#[test]
+fn logging_reads_mock_clock() {
+    let clock = MockClock::new();
+    let allocator = timely::communication::allocator::Thread::new();
+    let mut worker = Worker::new(WorkerConfig::default().clock(clock.clone()), allocator);
+
+    let times = Rc::new(RefCell::new(Vec::new()));
+    let times2 = times.clone();
+    worker.log_register().insert::<timely::logging::TimelyEvent,_>("timely", move |_time, data| {
+        times2.borrow_mut().extend(data.iter().map(|(time, _, _)| *time));
+    });
+
+    clock.advance(Duration::from_secs(3));
+    worker.dataflow::<u64,_,_>(|scope| {
+        (0 .. 10u64).to_stream(scope).probe();
+    });
+    while worker.step() { }
+    worker.log_register().remove("timely");
+
+    assert!(!times.borrow().is_empty());
+    assert!(times.borrow().iter().all(|time| *time == Duration::from_secs(3)));
+}
+
+// The clock of a simulated cluster advances with its ticks.
+#[test]
+fn simulated_clock_advances_with_ticks() {
+    let (mut cluster, _) = timely::execute_simulated(2, SimulationConfig::new(0), |worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 10u64).to_stream_paced(scope, 1000).probe();
+        });
+    });
+
+    let mut ticks = 0;
+    while cluster.tick() {
+        ticks += 1;
+    }
+    assert!(ticks >= 9);
+    assert_eq!(cluster.worker(0).elapsed(), cluster.clock().elapsed_since_start());
+    assert_eq!(cluster.worker(1).elapsed(), timely::execute::SIMULATED_TICK * (ticks + 1));
+}
+
+// Operator fuel from the configuration spends its time budget by the mock clock.
+#[test]
+fn fuel_reads_mock_clock() {
+    let clock = MockClock::new();
+    let config = WorkerConfig::default().clock(clock.clone()).operator_fuel(Fuel::duration(Duration::from_millis(10)));
+    let mut fuel = config.fuel();
+    fuel.refill();
+    assert!(!fuel.exhausted());
+    clock.advance(Duration::from_millis(9));
+    assert!(!fuel.exhausted());
+    clock.advance(Duration::from_millis(1));
+    assert!(fuel.exhausted());
+    fuel.refill();
+    assert!(!fuel.exhausted());
+}
+
+// The watchdog reports stalls once the mock clock passes the timeout, and not before.
+#[test]
+fn watchdog_reads_mock_clock() {
+    let clock = MockClock::new();
+    let allocator = timely::communication::allocator::Thread::new();
+    let config = WorkerConfig::default().clock(clock.clone()).watchdog(Duration::from_secs(60));
+    let mut worker = Worker::new(config, allocator);
+
+    let stalls = Rc::new(RefCell::new(Vec::new()));
+    let sink = stalls.clone();
+    worker.log_register().insert::<StallEvent,_>("timely/stalls", move |_time, data| {
+        sink.borrow_mut().extend(data.drain(..).map(|(_, _, event)| event.stalled_for));
+    });
+
+    let mut input = InputHandle::<u64, u64>::new();
+    worker.dataflow(|scope| { scope.input_from(&mut input).probe(); });
+    input.send(0);
+
+    for _ in 0 .. 10 {
+        worker.step();
+    }
+    assert!(stalls.borrow().is_empty());
+
+    clock.advance(Duration::from_secs(60));
+    for _ in 0 .. 10 {
+        worker.step();
+    }
+    assert_eq!(stalls.borrow().first(), Some(&Duration::from_secs(60)));
+}
+
+// Operators are measured by the worker clock, and only when the worker is configured to measure them.
+#[test]
+fn operator_metrics_read_mock_clock() {
+    for measure in [false, true] {
+        let clock = MockClock::new();
+        let allocator = timely::communication::allocator::Thread::new();
+        let config = WorkerConfig::default().clock(clock.clone()).operator_metrics(measure);
+        let mut worker = Worker::new(config, allocator);
+
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 10u64).to_stream(scope)
+                        .inspect(|_| { })
+                        .probe()
+        });
+        while !probe.done() {
+            worker.step();
+        }
+
+        let metrics = worker.metrics();
+        if measure {
+            let inspect = metrics.iter().find(|m| m.name == "InspectBatch").unwrap();
+            assert!(inspect.schedules > 0);
+            assert_eq!(inspect.consumed, vec![10]);
+            assert_eq!(inspect.elapsed, Duration::from_secs(0));
+        }
+        else {
+            assert!(metrics.is_empty());
+        }
+    }
+}
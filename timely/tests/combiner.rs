@@ -0,0 +1,81 @@
+extern crate timely;
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::operators::{Capture, Inspect, Map, ToStream};
+use timely::dataflow::operators::aggregation::Aggregate;
+use timely::dataflow::operators::capture::Extract;
+use timely::test_harness::{Harness, Script};
+use timely::Config;
+
+// Each worker exchanges one partial state per key, and the results match those of `aggregate`.
+#[test]
+fn exchanges_one_record_per_key() {
+    let exchanged = Arc::new(Mutex::new(0));
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let (exchanged2, results2) = (exchanged.clone(), results.clone());
+    timely::execute(Config::process(3), move |worker| {
+        let index = worker.index() as u64;
+        let (exchanged, results) = (exchanged2.clone(), results2.clone());
+        worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 10_000u64).map(move |x| x * 3 + index).to_stream(scope)
+                .map(|x| (x % 10, x))
+                .pre_aggregate(1024, |_key, val, sum: &mut u64| *sum += val)
+                .inspect_batch(move |_time, data| *exchanged.lock().unwrap() += data.len())
+                .aggregate(|_key, sum, total: &mut u64| *total += sum, |key, total| (key, total), |key| *key)
+                .inspect(move |x| results.lock().unwrap().push(*x));
+        });
+    }).unwrap();
+
+    assert_eq!(*exchanged.lock().unwrap(), 3 * 10);
+    let mut expected = BTreeMap::new();
+    for x in 0 .. 30_000u64 {
+        *expected.entry(x % 10).or_insert(0) += x;
+    }
+    let mut results = results.lock().unwrap().clone();
+    results.sort();
+    assert_eq!(results, expected.into_iter().collect::<Vec<_>>());
+}
+
+// Partial states are produced once more than `max_keys` are held, and still combine correctly.
+#[test]
+fn bounded_partial_state() {
+    let captured = timely::example(|scope| {
+        (0 .. 100u64).to_stream(scope)
+            .map(|x| (x, 1))
+            .pre_aggregate(10, |_key, val, count: &mut u64| *count += val)
+            .capture()
+    });
+    let batches = captured.extract();
+    assert_eq!(batches[0].1.len(), 100);
+
+    let captured = timely::example(|scope| {
+        (0 .. 1000u64).to_stream(scope)
+            .map(|x| (x % 50, 1))
+            .aggregate_combined(10, |_key, val, count: &mut u64| *count += val, |_key, count, total: &mut u64| *total += count, |key, total| (key, total), |key| *key)
+            .capture()
+    });
+    assert_eq!(captured.extract(), vec![(0, (0 .. 50).map(|key| (key, 20)).collect())]);
+}
+
+// The results do not depend on how records arrive, nor on the bound.
+#[test]
+fn results_under_schedules() {
+    let records = (0 .. 500u64).map(|x| (x % 7, x)).collect::<Vec<_>>();
+    let mut expected = BTreeMap::new();
+    for (epoch, x) in records.iter() {
+        *expected.entry(*epoch).or_insert_with(BTreeMap::new).entry(x % 13).or_insert(0) += x;
+    }
+    let expected = expected.into_iter().map(|(epoch, sums)| (epoch, sums.into_iter().collect::<Vec<_>>())).collect::<Vec<_>>();
+    for seed in 0 .. 10 {
+        for max_keys in [1, 5, 100] {
+            let mut harness = Harness::new(move |stream| {
+                stream.map(|x| (x % 13, x))
+                      .aggregate_combined(max_keys, |_key, val, sum: &mut u64| *sum += val, |_key, sum, total: &mut u64| *total += sum, |key, total| (key, total), |key| *key)
+            });
+            harness.play(&Script::generate(records.clone(), seed));
+            harness.finish().assert_epochs(expected.clone());
+        }
+    }
+}
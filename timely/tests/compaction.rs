@@ -0,0 +1,125 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::{CompactionFrontier, InputHandle, ProbeHandle};
+use timely::dataflow::operators::{Input, Probe};
+use timely::progress::frontier::Antichain;
+
+// Builds a dataflow from an input to a probe.
+fn probed<A: timely::communication::Allocate>(worker: &mut timely::worker::Worker<A>) -> (InputHandle<u64, u64>, ProbeHandle<u64>) {
+    let mut input = InputHandle::new();
+    let probe = worker.dataflow(|scope| scope.input_from(&mut input).probe());
+    (input, probe)
+}
+
+// The frontier is the least of the probed frontiers, and callbacks observe each advance.
+#[test]
+fn follows_least_probe() {
+    timely::execute_directly(|worker| {
+        let (mut input1, probe1) = probed(worker);
+        let (mut input2, probe2) = probed(worker);
+        let mut compaction = CompactionFrontier::new();
+        compaction.track(&probe1);
+        compaction.track(&probe2);
+
+        let advances = Rc::new(RefCell::new(Vec::new()));
+        let seen = advances.clone();
+        compaction.on_advance(move |frontier| seen.borrow_mut().push(frontier.to_vec()));
+
+        input1.advance_to(5);
+        input2.advance_to(3);
+        worker.step_while(|| probe1.less_than(&5) || probe2.less_than(&3));
+        assert_eq!(compaction.frontier(), Antichain::from_elem(3));
+        assert!(compaction.less_equal(&3) && !compaction.less_equal(&2));
+
+        input2.advance_to(8);
+        worker.step_while(|| probe2.less_than(&8));
+        assert_eq!(compaction.frontier(), Antichain::from_elem(5));
+
+        drop(input1);
+        drop(input2);
+        worker.step_while(|| !probe1.done() || !probe2.done());
+        assert_eq!(compaction.frontier(), Antichain::new());
+        assert_eq!(advances.borrow().clone(), vec![vec![3], vec![5], vec![8], vec![]]);
+    });
+}
+
+// Holds keep the frontier back until they advance or are dropped.
+#[test]
+fn holds_keep_frontier_back() {
+    timely::execute_directly(|worker| {
+        let (mut input, probe) = probed(worker);
+        let mut compaction = CompactionFrontier::new();
+        compaction.track(&probe);
+
+        input.advance_to(2);
+        worker.step_while(|| probe.less_than(&2));
+        let mut hold = compaction.hold();
+        let other = compaction.hold();
+        assert_eq!(hold.frontier().to_vec(), vec![2]);
+
+        input.advance_to(10);
+        worker.step_while(|| probe.less_than(&10));
+        assert_eq!(compaction.frontier(), Antichain::from_elem(2));
+
+        hold.advance_to(Antichain::from_elem(6).borrow());
+        assert_eq!(compaction.frontier(), Antichain::from_elem(2));
+        drop(other);
+        assert_eq!(compaction.frontier(), Antichain::from_elem(6));
+        drop(hold);
+        assert_eq!(compaction.frontier(), Antichain::from_elem(10));
+    });
+}
+
+#[test]
+#[should_panic(expected = "may not regress")]
+fn holds_may_not_regress() {
+    let compaction = CompactionFrontier::<u64>::new();
+    let mut hold = compaction.hold();
+    hold.advance_to(Antichain::from_elem(4).borrow());
+    hold.advance_to(Antichain::from_elem(3).borrow());
+}
+
+// A probe tracked after the frontier has advanced past its frontier does not regress it.
+#[test]
+fn never_regresses() {
+    timely::execute_directly(|worker| {
+        let (mut input1, probe1) = probed(worker);
+        let mut compaction = CompactionFrontier::new();
+        compaction.track(&probe1);
+        input1.advance_to(5);
+        worker.step_while(|| probe1.less_than(&5));
+
+        let (mut input2, probe2) = probed(worker);
+        compaction.track(&probe2);
+        worker.step();
+        assert_eq!(compaction.frontier(), Antichain::from_elem(5));
+
+        input1.advance_to(9);
+        input2.advance_to(7);
+        worker.step_while(|| probe1.less_than(&9) || probe2.less_than(&7));
+        assert_eq!(compaction.frontier(), Antichain::from_elem(7));
+    });
+}
+
+// A dropped dataflow no longer holds back the frontier.
+#[test]
+fn dropped_dataflows_release() {
+    timely::execute_directly(|worker| {
+        let dataflow = worker.next_dataflow_index();
+        let (_input1, probe1) = probed(worker);
+        let (mut input2, probe2) = probed(worker);
+        let mut compaction = CompactionFrontier::new();
+        compaction.track(&probe1);
+        compaction.track(&probe2);
+
+        input2.advance_to(4);
+        worker.step_while(|| probe2.less_than(&4));
+        assert_eq!(compaction.frontier(), Antichain::from_elem(0));
+
+        worker.drop_dataflow(dataflow);
+        assert_eq!(compaction.frontier(), Antichain::from_elem(4));
+    });
+}
@@ -0,0 +1,106 @@
+extern crate timely;
+
+use std::io::ErrorKind;
+
+use timely::communication::compression::Compression;
+
+// Bytes that do not repeat within LZ4's window, and so do not compress.
+fn noise(length: usize) -> Vec<u8> {
+    let mut state = 0x2545F4914F6CDD1Du64;
+    (0 .. length).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state >> 56) as u8
+    }).collect()
+}
+
+// Compresses and decompresses `data`, asserting that the bytes survive the round trip.
+fn round_trip(data: &[u8]) -> Vec<u8> {
+    let mut compressed = Vec::new();
+    Compression::Lz4.compress(data, &mut compressed);
+    let mut decompressed = vec![0u8; data.len()];
+    assert_eq!(Compression::Lz4.decompress(&compressed, &mut decompressed).unwrap(), data.len());
+    assert!(decompressed == data);
+    compressed
+}
+
+// Literal runs whose lengths need one or more extension bytes.
+#[test]
+fn long_literal_runs() {
+    for length in [0, 1, 14, 15, 16, 269, 270, 271, 524, 525, 10_000] {
+        round_trip(&noise(length));
+        // Literals that precede a match, rather than end the block.
+        let mut data = noise(length);
+        data.extend(std::iter::repeat_n(7u8, 300));
+        round_trip(&data);
+    }
+}
+
+// Matches that overlap the bytes they produce, at short and long lengths.
+#[test]
+fn overlapping_matches() {
+    for length in [20, 30, 100, 300, 1_000, 70_000] {
+        let compressed = round_trip(&vec![3u8; length]);
+        assert!(compressed.len() < length);
+        let pattern = b"abc".iter().cycle().take(length).cloned().collect::<Vec<_>>();
+        round_trip(&pattern);
+    }
+}
+
+// Incompressible input grows only by its framing.
+#[test]
+fn incompressible_input() {
+    let data = noise(1 << 16);
+    let compressed = round_trip(&data);
+    assert!(compressed.len() <= data.len() + data.len() / 255 + 16);
+}
+
+// Truncated payloads are either rejected or yield fewer bytes, and never panic.
+#[test]
+fn truncated_input() {
+    let mut data = noise(300);
+    data.extend(b"timely ".iter().cycle().take(700));
+    data.extend(noise(40));
+    let mut compressed = Vec::new();
+    Compression::Lz4.compress(&data, &mut compressed);
+    let mut decompressed = vec![0u8; data.len()];
+    for end in 0 .. compressed.len() {
+        match Compression::Lz4.decompress(&compressed[.. end], &mut decompressed) {
+            Ok(written) => assert!(written < data.len()),
+            Err(error) => assert_eq!(error.kind(), ErrorKind::InvalidData),
+        }
+    }
+}
+
+// Malformed payloads are rejected, rather than read out of bounds.
+#[test]
+fn malformed_input() {
+    let malformed: &[(&[u8], usize)] = &[
+        // A match with a zero offset.
+        (&[0x10, b'a', 0, 0], 64),
+        // A match that reaches back before the start of the output.
+        (&[0x10, b'a', 2, 0], 64),
+        // A literal run longer than the input.
+        (&[0xF0, 255, 255, 1], 1024),
+        // A literal run longer than the output.
+        (&[0x50, b'a', b'b', b'c', b'd', b'e'], 4),
+        // A match longer than the output.
+        (&[0x1F, b'a', 1, 0, 255, 0], 64),
+        // A match offset cut short.
+        (&[0x10, b'a', 1], 64),
+    ];
+    for (input, capacity) in malformed {
+        let error = Compression::Lz4.decompress(input, &mut vec![0u8; *capacity]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+    assert!(Compression::None.decompress(&[1, 2, 3], &mut [0u8; 2]).is_err());
+}
+
+// Schemes are named on the command line.
+#[test]
+fn parse_names() {
+    assert_eq!("lz4".parse::<Compression>(), Ok(Compression::Lz4));
+    assert_eq!("none".parse::<Compression>(), Ok(Compression::None));
+    assert!("zstd".parse::<Compression>().is_err());
+}
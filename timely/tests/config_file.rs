@@ -0,0 +1,95 @@
+extern crate timely;
+
+use timely::{Config, CommunicationConfig};
+
+// Settings describe the communication infrastructure.
+#[test]
+fn config_from_toml_builds_cluster() {
+    let config = Config::from_toml(r#"
+        threads = 3
+        processes = 2
+        process = 1
+        hosts = ["host0:2101", "host1:2101"]
+        compression = "lz4"
+        max_record_bytes = 67108864
+    "#).unwrap();
+    match config.communication {
+        CommunicationConfig::Cluster { threads, process, addresses, max_record, .. } => {
+            assert_eq!((threads, process), (3, 1));
+            assert_eq!(max_record, Some(64 << 20));
+            assert_eq!(addresses, vec!["host0:2101".to_owned(), "host1:2101".to_owned()]);
+        },
+        _ => panic!("expected a cluster configuration"),
+    }
+    assert!(matches!(Config::from_toml("threads = 4").unwrap().communication, CommunicationConfig::Process(4)));
+    assert!(matches!(Config::from_toml("").unwrap().communication, CommunicationConfig::Thread));
+}
+
+// Worker parameters are installed with their TOML types.
+#[test]
+fn config_from_toml_sets_worker_params() {
+    let config = Config::from_toml(r#"
+        [worker.params]
+        name = "example"
+        count = 7
+        ratio = 0.5
+        enabled = true
+    "#).unwrap();
+    assert_eq!(config.worker.get::<String>("name").map(|s| s.as_str()), Some("example"));
+    assert_eq!(config.worker.get::<i64>("count"), Some(&7));
+    assert_eq!(config.worker.get::<f64>("ratio"), Some(&0.5));
+    assert_eq!(config.worker.get::<bool>("enabled"), Some(&true));
+}
+
+// Each error names the offending setting.
+#[test]
+fn config_errors_name_the_setting() {
+    let cases = [
+        ("thread = 2", "thread"),
+        ("threads = \"two\"", "threads"),
+        ("threads = 0", "`threads`"),
+        ("processes = 2\nprocess = 2", "`process`"),
+        ("processes = 3\nhosts = [\"a:1\", \"b:1\"]", "`hosts`"),
+        ("transport = \"udp\"", "`transport`"),
+        ("[worker]\nprogress_mode = \"lazy\"", "`worker.progress_mode`"),
+        ("[worker]\nbatch_size = 0", "`worker.batch_size`"),
+        ("[worker]\nlog_file_capacity = 1024", "`worker.log_file_capacity`"),
+        ("[worker.params]\nlist = [1, 2]", "`worker.params.list`"),
+        ("[logging]\nworkers = \"localhost:9000\"", "workers"),
+    ];
+    for (text, setting) in cases.iter() {
+        match Config::from_toml(text) {
+            Ok(_) => panic!("accepted invalid configuration: {}", text),
+            Err(error) => assert!(error.contains(setting), "error for {:?} does not name {}: {}", text, setting, error),
+        }
+    }
+}
+
+// Environment variables override the settings of a file.
+#[test]
+fn config_from_file_applies_environment() {
+    let directory = std::env::temp_dir().join(format!("timely-config-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let hostfile = directory.join("hosts.txt");
+    std::fs::write(&hostfile, "host0:2101\nhost1:2101\nhost2:2101\n").unwrap();
+    let path = directory.join("timely.toml");
+    std::fs::write(&path, format!("threads = 2\nprocesses = 3\nhostfile = {:?}\n", hostfile)).unwrap();
+
+    std::env::set_var("TIMELY_THREADS", "5");
+    std::env::set_var("TIMELY_PROCESS", "2");
+    let config = Config::from_file(&path);
+    std::env::set_var("TIMELY_THREADS", "five");
+    let invalid = Config::from_file(&path);
+    std::env::remove_var("TIMELY_THREADS");
+    std::env::remove_var("TIMELY_PROCESS");
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    match config.unwrap().communication {
+        CommunicationConfig::Cluster { threads, process, addresses, .. } => {
+            assert_eq!((threads, process), (5, 2));
+            assert_eq!(addresses.len(), 3);
+        },
+        _ => panic!("expected a cluster configuration"),
+    }
+    assert!(invalid.err().unwrap().contains("`TIMELY_THREADS`"));
+}
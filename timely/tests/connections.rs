@@ -0,0 +1,159 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::net::{Shutdown, TcpStream};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use timely::WorkerConfig;
+use timely::communication::Allocate;
+use timely::communication::allocator::GenericBuilder;
+use timely::communication::allocator::zero_copy::initialize::{initialize_networking_from_sockets, initialize_networking_from_sockets_with};
+use timely::communication::logging::{CommunicationEvent, PeerFailedEvent};
+use timely::communication::networking::{loopback_sockets, ConnectionOptions, StreamUpgrade, WriteHalf};
+use timely::dataflow::operators::{Exchange, Inspect, ToStream};
+use timely::logging_core::Logger;
+use timely::logging_core::clock;
+
+// A connection that ends without a clean shutdown fails the peer, which is reported to the
+// communication log and then to the workers of the process.
+#[test]
+fn killed_connection_fails_peer() {
+    let failures = Arc::new(Mutex::new(Vec::new()));
+    let mut sockets = loopback_sockets(2).unwrap();
+    let killer = sockets[0][1].as_ref().unwrap().try_clone().unwrap();
+    let handles = sockets.drain(..).enumerate().map(|(index, sockets)| {
+        let failures = failures.clone();
+        std::thread::spawn(move || {
+            initialize_networking_from_sockets(sockets, index, 1, Box::new(move |setup| {
+                let failures = failures.clone();
+                Some(Logger::new(clock::Instant::now(), Duration::default(), setup, move |_time, events| {
+                    for (_, _, event) in events.drain(..) {
+                        if let CommunicationEvent::PeerFailed(failure) = event {
+                            failures.lock().unwrap().push(failure);
+                        }
+                    }
+                }))
+            })).unwrap()
+        })
+    }).collect::<Vec<_>>();
+    let (builders, guards): (Vec<_>, Vec<_>) = handles.into_iter().map(|handle| handle.join().unwrap()).unzip();
+    let mut allocators = builders.into_iter().flatten().map(|builder| builder.build()).collect::<Vec<_>>();
+    let channels = allocators.iter_mut().map(|allocator| allocator.allocate::<u64>(0)).collect::<Vec<_>>();
+
+    killer.shutdown(Shutdown::Both).unwrap();
+
+    // The receive thread of process 1 reports the failure of process 0.
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !failures.lock().unwrap().contains(&PeerFailedEvent { process: 1, remote: 0 }) {
+        assert!(Instant::now() < deadline, "no failure reported");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    // The workers of process 1 then fail, rather than wait for data that will not arrive.
+    loop {
+        if catch_unwind(AssertUnwindSafe(|| allocators[1].receive())).is_err() { break; }
+        assert!(Instant::now() < deadline, "worker did not fail");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    // Poisoned queues panic as they drop, and so the failed processes are leaked, not dropped.
+    std::mem::forget((channels, allocators, guards));
+}
+
+// Scrambles the bytes of a connection, standing in for the encryption of a TLS session.
+struct Scrambled<S> {
+    inner: S,
+    key: u8,
+}
+
+impl<S: std::io::Read> std::io::Read for Scrambled<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        for byte in buf[.. read].iter_mut() { *byte ^= self.key; }
+        Ok(read)
+    }
+}
+
+impl<S: std::io::Write> std::io::Write for Scrambled<S> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let scrambled = buf.iter().map(|byte| byte ^ self.key).collect::<Vec<_>>();
+        self.inner.write_all(&scrambled)?;
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl WriteHalf for Scrambled<TcpStream> {
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        self.inner.shutdown(Shutdown::Write)
+    }
+}
+
+// Upgrades connections to scrambled ones, accepting only peers in `accepted`.
+fn scrambling(key: u8, accepted: Vec<usize>) -> Box<StreamUpgrade> {
+    Box::new(move |socket: TcpStream, _index, remote| {
+        if !accepted.contains(&remote) {
+            return Err(std::io::Error::new(std::io::ErrorKind::PermissionDenied, format!("process {} is not trusted", remote)));
+        }
+        let reader = Scrambled { inner: socket.try_clone()?, key };
+        Ok((Box::new(reader), Box::new(Scrambled { inner: socket, key })))
+    })
+}
+
+// Upgraded connections carry all traffic between processes, as a TLS session would.
+#[test]
+fn upgraded_connections_carry_traffic() {
+    let handles = loopback_sockets(2).unwrap().into_iter().enumerate().map(|(index, sockets)| {
+        std::thread::spawn(move || {
+            let options = ConnectionOptions { upgrade: Some(scrambling(0x5a, vec![0, 1])), ..Default::default() };
+            initialize_networking_from_sockets_with(sockets, index, 2, options, Box::new(|_| None)).unwrap()
+        })
+    }).collect::<Vec<_>>();
+    let guards = handles.into_iter().map(|handle| {
+        let (builders, others) = handle.join().unwrap();
+        let builders = builders.into_iter().map(GenericBuilder::ZeroCopy).collect::<Vec<_>>();
+        timely::execute::execute_from(builders, Box::new(others), WorkerConfig::default(), |worker| {
+            let index = worker.index();
+            let received = Rc::new(RefCell::new(Vec::new()));
+            let sink = received.clone();
+            worker.dataflow::<u64,_,_>(|scope| {
+                (0 .. 100u64).filter(move |x| *x as usize % 4 == index)
+                             .to_stream(scope)
+                             .exchange(|x| *x / 25)
+                             .inspect(move |x| sink.borrow_mut().push(*x));
+            });
+            while worker.step_or_park(None) { }
+            let mut received = received.borrow().clone();
+            received.sort();
+            received
+        }).unwrap()
+    }).collect::<Vec<_>>();
+
+    let received = guards.into_iter().flat_map(|guards| guards.join()).map(|result| result.unwrap()).collect::<Vec<_>>();
+    for (index, records) in received.iter().enumerate() {
+        assert_eq!(*records, (25 * index as u64 .. 25 * (index as u64 + 1)).collect::<Vec<_>>());
+    }
+}
+
+// A process that refuses to upgrade a connection fails to initialize.
+#[test]
+fn refused_upgrade_fails_initialization() {
+    let handles = loopback_sockets(2).unwrap().into_iter().enumerate().map(|(index, sockets)| {
+        std::thread::spawn(move || {
+            // Process 1 trusts process 0, but process 0 trusts no one.
+            let accepted = if index == 0 { vec![] } else { vec![0] };
+            let options = ConnectionOptions { upgrade: Some(scrambling(0x5a, accepted)), ..Default::default() };
+            initialize_networking_from_sockets_with(sockets, index, 1, options, Box::new(|_| None)).map(|_| ())
+        })
+    }).collect::<Vec<_>>();
+    let results = handles.into_iter().map(|handle| handle.join().unwrap()).collect::<Vec<_>>();
+    let error = results[0].as_ref().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::PermissionDenied);
+    // Process 1 observes the connection close before the worker counts arrive.
+    assert!(results[1].is_err());
+}
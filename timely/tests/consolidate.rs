@@ -0,0 +1,95 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe};
+use timely::dataflow::operators::aggregation::{ConsolidateByKey, Semigroup};
+use timely::dataflow::operators::aggregation::consolidate::consolidate;
+use timely::Config;
+
+// Records that retract one another within a time produce nothing.
+#[test]
+fn retractions_cancel() {
+    let mut vec = vec![(3, 1i64), (1, 1), (3, -1), (1, -1), (2, 5), (2, -5)];
+    consolidate(&mut vec);
+    assert!(vec.is_empty());
+
+    let mut empty: Vec<(u64, i32)> = Vec::new();
+    consolidate(&mut empty);
+    assert!(empty.is_empty());
+}
+
+// Diffs sum per key and per time, across workers that each send part of every key's records.
+#[test]
+fn sums_per_key_and_time_across_workers() {
+    let output = Arc::new(Mutex::new(Vec::new()));
+    let output2 = output.clone();
+    timely::execute(Config::process(2), move |worker| {
+        let output = output2.clone();
+        let index = worker.index() as isize;
+        let mut input = InputHandle::<u64, (u64, isize)>::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .consolidate_by_key()
+                 .inspect_time(move |time, record| output.lock().unwrap().push((*time, *record)))
+                 .probe()
+        });
+
+        for round in 0 .. 3u64 {
+            for key in 0 .. 100u64 {
+                // Worker 0 inserts each key, and worker 1 retracts the even keys.
+                if index == 0 || key % 2 == 0 {
+                    input.send((key, 1 - 2 * index));
+                }
+                input.send((key % 4, round as isize));
+            }
+            input.advance_to(round + 1);
+        }
+        input.close();
+        worker.step_while(|| !probe.done());
+    }).unwrap();
+
+    let mut output = output.lock().unwrap().clone();
+    output.sort();
+    let mut expected = Vec::new();
+    for round in 0 .. 3u64 {
+        for key in 0 .. 100u64 {
+            // Each worker adds `round` to keys 0 through 3, 25 times.
+            let mut diff = if key % 2 == 0 { 0 } else { 1 };
+            if key < 4 {
+                diff += 2 * 25 * round as isize;
+            }
+            if diff != 0 {
+                expected.push((round, (key, diff)));
+            }
+        }
+    }
+    assert_eq!(output, expected);
+}
+
+// A diff other than an integer, accumulating a count and a total together.
+#[derive(Clone, Debug, PartialEq)]
+struct CountSum { count: isize, sum: i64 }
+
+impl Semigroup for CountSum {
+    fn plus_equals(&mut self, other: &Self) {
+        self.count += other.count;
+        self.sum += other.sum;
+    }
+    fn is_zero(&self) -> bool {
+        self.count == 0 && self.sum == 0
+    }
+}
+
+#[test]
+fn custom_semigroup() {
+    let mut vec = vec![
+        ("a", CountSum { count: 1, sum: 10 }),
+        ("b", CountSum { count: 1, sum: 5 }),
+        ("a", CountSum { count: -1, sum: -10 }),
+        ("b", CountSum { count: 1, sum: -5 }),
+    ];
+    consolidate(&mut vec);
+    assert_eq!(vec, vec![("b", CountSum { count: 2, sum: 0 })]);
+}
@@ -0,0 +1,78 @@
+extern crate timely;
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use timely::dataflow::channels::credits::{Credits, YieldNow};
+use timely::dataflow::channels::pact::Credited;
+use timely::dataflow::operators::generic::{source, Operator};
+use timely::dataflow::operators::Probe;
+use timely::scheduling::Scheduler;
+
+// A consumer that reads one batch each time it is scheduled holds the producer to its credits,
+// and the producer resumes as the consumer drains, until every record has arrived.
+#[test]
+fn slow_consumer_bounds_producer() {
+    timely::execute_directly(|worker| {
+        let credits = Credits::new(64);
+        let sent = Rc::new(Cell::new(0u64));
+        let received = Rc::new(Cell::new(0u64));
+        let yields = Rc::new(Cell::new(0usize));
+        let (sent2, received2, yields2) = (sent.clone(), received.clone(), yields.clone());
+
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            let producer_credits = credits.clone();
+            let handle = scope.clone();
+            let stream = source(scope, "Producer", move |capability, info| {
+                producer_credits.activate_on_grant(handle.activator_for(&info.address[..]));
+                let mut capability = Some(capability);
+                move |output| {
+                    if let Some(cap) = capability.as_ref() {
+                        let mut session = output.credited_session(cap, &producer_credits);
+                        while sent2.get() < 5_000 {
+                            // A refused record is returned, and offered again once resumed.
+                            if let Err(refused) = session.give(sent2.get()) {
+                                assert_eq!(refused, YieldNow(sent2.get()));
+                                yields2.set(yields2.get() + 1);
+                                return;
+                            }
+                            sent2.set(sent2.get() + 1);
+                        }
+                    }
+                    capability = None;
+                }
+            });
+
+            let handle = scope.clone();
+            stream.unary_frontier(Credited::new(&credits), "Consumer", move |_cap, info| {
+                let activator = handle.activator_for(&info.address[..]);
+                move |input, _output: &mut timely::dataflow::operators::generic::OutputHandle<_, (), _>| {
+                    if let Some((_time, data)) = input.next() {
+                        received2.set(received2.get() + data.len() as u64);
+                        assert!(sent.get() - received2.get() <= 64);
+                        activator.activate();
+                    }
+                }
+            })
+            .probe()
+        });
+
+        while !probe.done() {
+            worker.step();
+            assert!(credits.available() <= 64);
+        }
+        assert_eq!(received.get(), 5_000);
+        assert!(yields.get() > 0);
+    });
+}
+
+#[test]
+fn credits_are_taken_and_granted() {
+    let credits = Credits::new(2);
+    assert!(credits.try_take());
+    assert!(credits.try_take());
+    assert!(!credits.try_take());
+    assert_eq!(credits.available(), 0);
+    credits.grant(5);
+    assert_eq!(credits.available(), credits.limit());
+}
@@ -0,0 +1,22 @@
+extern crate serde_json;
+extern crate timely;
+
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::ToStream;
+use timely::dataflow::operators::generic::operator::Operator;
+
+// Names with quotes, backslashes, and control characters survive rendering as JSON.
+#[test]
+fn names_are_escaped_in_json() {
+    let name = "say \"hi\"\\\n\tbye\u{1}";
+    timely::execute_directly(move |worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 10u64).to_stream(scope).sink(Pipeline, name, |input| input.for_each(|_, _| { }));
+        });
+        let graph = worker.dataflow_graph(0).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&graph.to_json()).unwrap();
+        let names = json["operators"].as_array().unwrap().iter().map(|o| o["name"].as_str().unwrap()).collect::<Vec<_>>();
+        assert!(names.contains(&name));
+        assert_eq!(json["channels"].as_array().unwrap().len(), graph.channels.len());
+    });
+}
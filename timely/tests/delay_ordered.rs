@@ -0,0 +1,39 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::operators::{Delay, Inspect, Probe, UnorderedInput};
+
+// Records arrive at later original times first, and must leave in order of original time.
+#[test]
+fn delay_ordered_by_original_time() {
+    timely::execute(timely::Config::thread(), |worker| {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = seen.clone();
+        let ((mut input, cap), probe) = worker.dataflow::<u64,_,_>(move |scope| {
+            let (input, stream) = scope.new_unordered_input();
+            let probe = stream
+                .delay_ordered(|_, _| 5)
+                .inspect(move |x: &(u64, usize)| sink.borrow_mut().push(*x))
+                .probe();
+            (input, probe)
+        });
+
+        for round in 0 .. 3 {
+            input.session(cap.delayed(&2)).give_iterator((0 .. 3).map(|i| (2, i)));
+            input.session(cap.delayed(&1)).give_iterator((0 .. 3).map(|i| (1, i)));
+            input.session(cap.delayed(&3)).give((3, round));
+            worker.step();
+        }
+        drop(cap);
+        worker.step_while(|| !probe.done());
+
+        let seen = seen.borrow();
+        let mut expected = Vec::new();
+        for _ in 0 .. 3 { expected.extend((0 .. 3).map(|i| (1, i))); }
+        for _ in 0 .. 3 { expected.extend((0 .. 3).map(|i| (2, i))); }
+        expected.extend((0 .. 3).map(|round| (3, round)));
+        assert_eq!(*seen, expected);
+    }).unwrap();
+}
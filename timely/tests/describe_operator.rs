@@ -0,0 +1,48 @@
+extern crate timely;
+
+use timely::dataflow::{InputHandle, Scope};
+use timely::dataflow::operators::{Input, Map, Enter, Leave, Inspect};
+use timely::dataflow::operators::generic::PortInfo;
+
+// Operators and scopes are described by address, until their dataflow is dropped.
+#[test]
+fn describe_operators_and_scopes() {
+    timely::execute(timely::Config::thread(), |worker| {
+        let mut input = InputHandle::<u64, String>::new();
+        let (input_addr, region_addr, map_addr) = worker.dataflow_named::<u64,_,_>("Described", |scope| {
+            let stream = scope.input_from(&mut input);
+            let mut input_addr = scope.addr();
+            input_addr.push(stream.name().node);
+            let (stream, region_addr, map_addr) = scope.region_named("inner", |inner| {
+                let stream = stream.enter(inner).map(|x| x.len());
+                let mut map_addr = inner.addr();
+                map_addr.push(stream.name().node);
+                (stream.leave(), inner.addr(), map_addr)
+            });
+            stream.inspect(|_| { });
+            (input_addr, region_addr, map_addr)
+        });
+
+        let dataflow = worker.describe_operator(&input_addr[..1]).unwrap();
+        assert_eq!(dataflow.name, "Described");
+
+        let input_info = worker.describe_operator(&input_addr).unwrap();
+        assert_eq!(input_info.name, "Input");
+        assert_eq!(input_info.inputs.len(), 0);
+        assert_eq!(input_info.outputs, vec![PortInfo::unknown()]);
+
+        let region = worker.describe_operator(&region_addr).unwrap();
+        assert_eq!(region.name, "inner");
+        assert_eq!(region.inputs.len(), 1);
+        assert_eq!(region.outputs.len(), 1);
+
+        let map = worker.describe_operator(&map_addr).unwrap();
+        assert_eq!(map.name, "Map");
+        assert_eq!(map.address, map_addr);
+        assert_eq!(map.inputs, vec![PortInfo { data: Some("alloc::string::String"), pact: Some("Pipeline") }]);
+        assert_eq!(map.outputs, vec![PortInfo { data: Some("usize"), pact: None }]);
+
+        worker.drop_dataflow(input_addr[0]);
+        assert!(worker.describe_operator(&map_addr).is_none());
+    }).unwrap();
+}
@@ -0,0 +1,47 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use timely::dataflow::operators::{Concat, ConnectLoop, Feedback, Filter, Inspect, Map, ToStream};
+use timely::progress::PathSummary;
+use timely::progress::timestamp::EpochMillis;
+
+// A loop whose summary is less than a millisecond still advances times, and completes.
+#[test]
+fn loop_with_sub_millisecond_summary() {
+    let produced = Arc::new(Mutex::new(Vec::new()));
+    let seen = produced.clone();
+    timely::execute_directly(move |worker| {
+        worker.dataflow::<EpochMillis,_,_>(|scope| {
+            let (handle, cycle) = scope.feedback(Duration::from_micros(600));
+            let records = (0 .. 1u64).to_stream(scope)
+                                     .concat(&cycle)
+                                     .map(|x| x + 1);
+            records.filter(|x| *x < 4).connect_loop(handle);
+            records.inspect_time(move |time, x| seen.lock().unwrap().push((*time, *x)));
+        });
+    });
+    assert_eq!(*produced.lock().unwrap(), vec![
+        (EpochMillis(0), 1),
+        (EpochMillis(1), 2),
+        (EpochMillis(2), 3),
+        (EpochMillis(3), 4),
+    ]);
+}
+
+// Applying composed summaries agrees with applying the summaries one after the other.
+#[test]
+fn composition_agrees_with_application() {
+    let summaries = [Duration::from_micros(600), Duration::from_millis(2), Duration::from_nanos(1), Duration::from_micros(1_999)];
+    for first in summaries.iter() {
+        for second in summaries.iter() {
+            let time = EpochMillis(10);
+            let composed = PathSummary::<EpochMillis>::followed_by(first, second).unwrap();
+            let stepwise = second.results_in(&first.results_in(&time).unwrap());
+            assert_eq!(composed.results_in(&time), stepwise, "{:?} then {:?}", first, second);
+        }
+    }
+    assert_eq!(Duration::from_micros(600).results_in(&EpochMillis(10)), Some(EpochMillis(11)));
+    assert_eq!(Duration::from_millis(3).results_in(&EpochMillis(10)), Some(EpochMillis(13)));
+}
@@ -0,0 +1,84 @@
+extern crate timely;
+
+use std::net::TcpListener;
+
+use timely::{CommunicationConfig, Config, Error};
+use timely::communication::Error as CommunicationError;
+use timely::communication::allocator::zero_copy::initialize::initialize_networking_from_sockets;
+use timely::communication::codec::Native;
+use timely::communication::networking::{loopback_sockets, PeerAddress};
+use timely::dataflow::operators::{Concat, Feedback, ToStream};
+use timely::progress::validation::GraphError;
+
+// A process that cannot accept connections on its address reports the address.
+#[test]
+fn bind_failure() {
+    let occupied = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = occupied.local_addr().unwrap().to_string();
+    let peers = vec![PeerAddress::new(&address), PeerAddress::new("127.0.0.1:1")];
+    let config = Config { communication: CommunicationConfig::cluster(1, 0, peers), worker: Default::default() };
+    match timely::try_execute(config, |_worker| ()).err() {
+        Some(Error::Communication(CommunicationError::Bind { address: bound, .. })) => assert_eq!(bound, address),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+// A process index with no address is a configuration error, rather than a panic.
+#[test]
+fn process_without_address() {
+    let peers = vec![PeerAddress::new("127.0.0.1:1"), PeerAddress::new("127.0.0.1:2")];
+    let config = Config { communication: CommunicationConfig::cluster(1, 2, peers), worker: Default::default() };
+    let error = timely::try_execute(config, |_worker| ()).err().expect("execution should fail");
+    assert!(matches!(error, Error::Communication(CommunicationError::Config(_))), "unexpected error: {:?}", error);
+}
+
+// Processes that disagree on the number of workers both fail to initialize.
+#[test]
+fn worker_count_mismatch() {
+    let handles = loopback_sockets(2).unwrap().into_iter().enumerate().map(|(index, sockets)| {
+        std::thread::spawn(move || {
+            initialize_networking_from_sockets(sockets, index, index + 1, Box::new(|_| None)).map(|_| ())
+        })
+    }).collect::<Vec<_>>();
+    let errors = handles.into_iter().map(|handle| handle.join().unwrap().map_err(CommunicationError::from)).collect::<Vec<_>>();
+    match &errors[..] {
+        [Err(CommunicationError::WorkerCount { process: 1, expected: 1, found: 2 }), Err(CommunicationError::WorkerCount { process: 0, expected: 2, found: 1 })] => { },
+        other => panic!("unexpected results: {:?}", other),
+    }
+}
+
+// Mistakes in the construction of dataflows convert into errors, as do invalid channel addresses.
+#[test]
+fn construction_errors() {
+    fn build<A: timely::communication::Allocate>(worker: &mut timely::worker::Worker<A>) -> Result<(), Error> {
+        worker.try_dataflow::<u64,_,_>(|scope| {
+            let (_handle, cycle) = scope.feedback::<u64>(1);
+            (0 .. 10).to_stream(scope).concat(&cycle);
+        })?;
+        Ok(())
+    }
+
+    timely::execute_directly(|worker| {
+        let error = build(worker).unwrap_err();
+        assert!(matches!(error, Error::Graph(GraphError::MissingOperator { .. })), "unexpected error: {:?}", error);
+
+        let identifier = worker.new_identifier();
+        match worker.try_allocate_with::<u64, Native>(identifier, &[]) {
+            Err(Error::Address { identifier: reported, address }) => assert_eq!((reported, address), (identifier, vec![])),
+            Err(other) => panic!("unexpected error: {:?}", other),
+            Ok(_) => panic!("allocation should fail"),
+        }
+    });
+}
+
+// Errors convert into strings, for callers that report errors as strings.
+#[test]
+fn errors_describe_themselves() {
+    fn execute() -> Result<(), String> {
+        let peers = vec![PeerAddress::new("127.0.0.1:1")];
+        let config = Config { communication: CommunicationConfig::cluster(1, 1, peers), worker: Default::default() };
+        timely::execute(config, |_worker| ())?;
+        Ok(())
+    }
+    assert_eq!(execute(), Err("invalid configuration: process 1 has no address among 1 addresses".to_owned()));
+}
@@ -0,0 +1,45 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::operators::UnorderedInput;
+use timely::dataflow::operators::exactly_once::SinkExactlyOnce;
+use timely::order::Pair;
+use timely::progress::Antichain;
+
+// The frontiers reported to `commit`, for a committed frontier listing `elements` in order.
+fn commits(elements: Vec<Pair<u64, u64>>) -> Vec<Vec<Pair<u64, u64>>> {
+    timely::execute_directly(move |worker| {
+        let reported = Rc::new(RefCell::new(Vec::new()));
+        let sink = reported.clone();
+        let (mut input, capability) = worker.dataflow::<Pair<u64, u64>,_,_>(|scope| {
+            let ((input, capability), stream) = scope.new_unordered_input::<u64>();
+            stream.sink_exactly_once(Antichain::from(elements), move |_batch, frontier| {
+                sink.borrow_mut().push(frontier.to_vec());
+                Ok::<(), ()>(())
+            });
+            (input, capability)
+        });
+        let first = capability.delayed(&Pair::new(0, 1));
+        let second = capability.delayed(&Pair::new(1, 0));
+        drop(capability);
+        input.session(first.clone()).give(1);
+        for _ in 0 .. 10 { worker.step(); }
+        drop(first);
+        drop(second);
+        for _ in 0 .. 10 { worker.step(); }
+        let reported = reported.borrow().clone();
+        reported
+    })
+}
+
+// A frontier equal to the committed frontier is not committed again, whatever the order of their
+// elements.
+#[test]
+fn committed_frontier_compares_as_a_set() {
+    let forward = vec![Pair::new(0, 1), Pair::new(1, 0)];
+    let reverse = vec![Pair::new(1, 0), Pair::new(0, 1)];
+    assert_eq!(commits(forward), vec![Vec::new()]);
+    assert_eq!(commits(reverse), vec![Vec::new()]);
+}
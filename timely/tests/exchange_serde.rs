@@ -0,0 +1,115 @@
+extern crate abomonation;
+extern crate serde;
+extern crate timely;
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+
+use timely::WorkerConfig;
+use timely::worker::ExchangeCodec;
+use timely::dataflow::InputHandle;
+use timely::dataflow::channels::pact::{Exchange as ExchangePact, Configured};
+use timely::dataflow::operators::{Input, Exchange, Inspect, Probe, Map};
+use timely::dataflow::operators::generic::Operator;
+
+// Records that implement only `serde`'s traits move between processes, alongside records that
+// use the zero-copy serialization in the same dataflow.
+#[test]
+fn serde_and_native_channels_in_one_dataflow() {
+    let guards = timely::execute_multiprocess_in_process(2, 2, WorkerConfig::default(), |worker| {
+        let index = worker.index();
+        let peers = worker.peers() as u64;
+        let mut input = InputHandle::<u64, u64>::new();
+        let received = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let received2 = received.clone();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x| *x)
+                 .map(|x| { let mut map = BTreeMap::new(); map.insert(x, vec![x.to_string()]); map })
+                 .exchange_serde(|map| *map.keys().next().unwrap() + 1)
+                 .inspect(move |map| received2.borrow_mut().push(map.clone()))
+                 .probe()
+        });
+        for x in 0 .. 20u64 {
+            if x % peers == index as u64 {
+                input.send(x);
+            }
+        }
+        input.close();
+        while !probe.done() { worker.step(); }
+        let received = received.borrow();
+        for map in received.iter() {
+            let (key, value) = map.iter().next().unwrap();
+            assert_eq!((key + 1) % peers, index as u64);
+            assert_eq!(value, &vec![key.to_string()]);
+        }
+        received.len()
+    }).unwrap();
+
+    let total: usize = guards.into_iter().flat_map(|guards| guards.join()).map(|result| result.unwrap()).sum();
+    assert_eq!(total, 20);
+}
+
+static SERIALIZED: AtomicUsize = AtomicUsize::new(0);
+
+// A record supporting both codecs, which counts the times `serde` serializes it.
+#[derive(Clone)]
+struct Counted(u64);
+
+impl abomonation::Abomonation for Counted { }
+
+impl Serialize for Counted {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SERIALIZED.fetch_add(1, Ordering::SeqCst);
+        serializer.serialize_u64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Counted {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u64::deserialize(deserializer).map(Counted)
+    }
+}
+
+// Exchanges records with the `Configured` codec, returning the number received.
+fn exchange_configured(codec: ExchangeCodec) -> usize {
+    let config = WorkerConfig::default().exchange_codec(codec);
+    let guards = timely::execute_multiprocess_in_process(2, 1, config, |worker| {
+        let index = worker.index();
+        let mut input = InputHandle::<u64, Counted>::new();
+        let received = std::rc::Rc::new(std::cell::RefCell::new(0));
+        let received2 = received.clone();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .unary(ExchangePact::new(|x: &Counted| x.0 + 1).with_codec::<Configured>(), "Configured", |_cap, _info| |input, output| {
+                     input.for_each(|time, data| {
+                         output.session(&time).give_vec(&mut data.replace(Vec::new()));
+                     });
+                 })
+                 .inspect(move |x| { assert_eq!((x.0 + 1) % 2, index as u64); *received2.borrow_mut() += 1; })
+                 .probe()
+        });
+        for x in 0 .. 10u64 {
+            if x % 2 == index as u64 {
+                input.send(Counted(x));
+            }
+        }
+        input.close();
+        while !probe.done() { worker.step(); }
+        let received = *received.borrow();
+        received
+    }).unwrap();
+
+    guards.into_iter().flat_map(|guards| guards.join()).map(|result| result.unwrap()).sum()
+}
+
+// The configuration selects the codec of exchanges using the `Configured` codec.
+#[test]
+fn configured_codec_follows_the_configuration() {
+    assert_eq!(exchange_configured(ExchangeCodec::Native), 10);
+    assert_eq!(SERIALIZED.load(Ordering::SeqCst), 0);
+    assert_eq!(exchange_configured(ExchangeCodec::Bincode), 10);
+    assert!(SERIALIZED.load(Ordering::SeqCst) > 0);
+}
@@ -0,0 +1,62 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe};
+use timely::dataflow::operators::export::{Export, Import};
+
+// An imported stream carries the exported records at their times, and completes with them.
+#[test]
+fn imported_records() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::new();
+        worker.dataflow(|scope| scope.input_from(&mut input).export("numbers"));
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let sink = received.clone();
+        let probe = worker.dataflow(|scope| {
+            scope.import::<u64>("numbers")
+                 .inspect_time(move |time, x| sink.borrow_mut().push((*time, *x)))
+                 .probe()
+        });
+
+        for round in 0 .. 5u64 {
+            input.send(10 * round);
+            input.send(10 * round + 1);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+            // The import's frontier follows the export's.
+            assert!(!probe.less_than(&(round + 1)));
+        }
+        input.close();
+        worker.step_while(|| !probe.done());
+
+        let mut received = received.borrow().clone();
+        received.sort();
+        assert_eq!(received, (0 .. 5u64).flat_map(|r| vec![(r, 10 * r), (r, 10 * r + 1)]).collect::<Vec<_>>());
+    });
+}
+
+// Records exported before the import is constructed are retained for it.
+#[test]
+fn exported_records_are_retained() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::new();
+        worker.dataflow::<u64,_,_>(|scope| scope.input_from(&mut input).export("early"));
+        input.send(7u64);
+        input.advance_to(1);
+        worker.step();
+
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let sink = received.clone();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.import::<u64>("early").inspect(move |x| sink.borrow_mut().push(*x)).probe()
+        });
+        input.close();
+        worker.step_while(|| !probe.done());
+        assert_eq!(*received.borrow(), vec![7]);
+    });
+}
+
@@ -0,0 +1,43 @@
+extern crate timely;
+
+use std::sync::mpsc;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Capture, Probe};
+use timely::dataflow::operators::capture::Event;
+use timely::dataflow::operators::frontier_updates::FrontierUpdates;
+use timely::progress::Antichain;
+
+// Each advance of the input retracts the old frontier and produces the new one, at the old time.
+#[test]
+fn frontier_updates_per_round() {
+    let (send, recv) = mpsc::channel();
+    let send = std::sync::Mutex::new(send);
+    timely::execute(timely::Config::thread(), move |worker| {
+        let send = send.lock().unwrap().clone();
+        let mut input = InputHandle::<u64, u64>::new();
+        let probe = worker.dataflow(|scope| {
+            let updates = scope.input_from(&mut input).frontier_updates();
+            updates.capture_into(send);
+            updates.probe()
+        });
+        for round in 1 .. 4 {
+            input.send(round);
+            input.advance_to(round);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+    }).unwrap();
+
+    let updates = recv.iter().flat_map(|event| match event {
+        Event::Messages(time, data) => data.into_iter().map(|update| (time, update)).collect(),
+        Event::Progress(_) => Vec::new(),
+    }).collect::<Vec<_>>();
+    let mut expected = vec![(0, (Antichain::from_elem(0), 1))];
+    for round in 0 .. 3 {
+        expected.push((round, (Antichain::from_elem(round), -1)));
+        expected.push((round, (Antichain::from_elem(round + 1), 1)));
+    }
+    expected.push((3, (Antichain::from_elem(3), -1)));
+    expected.push((3, (Antichain::new(), 1)));
+    assert_eq!(updates, expected);
+}
@@ -0,0 +1,57 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::operators::{ToStream, Map, Filter, Delay, Capture};
+use timely::dataflow::operators::aggregation::Aggregate;
+use timely::dataflow::operators::capture::Extract;
+use timely::dataflow::operators::join::JoinByKey;
+use timely::scheduling::fuel::Fuel;
+use timely::{CommunicationConfig, Config, WorkerConfig};
+
+// Aggregates and join results captured at each time.
+type Aggregated = Vec<(u64, Vec<(u64, u64)>)>;
+type Joined = Vec<(u64, Vec<(u64, u64, u64)>)>;
+
+// Runs an aggregation and a join with `fuel`, returning their sorted outputs.
+fn run(fuel: Fuel) -> (Aggregated, Joined) {
+    let aggregated = Arc::new(Mutex::new(Vec::new()));
+    let joined = Arc::new(Mutex::new(Vec::new()));
+    let (aggregated2, joined2) = (aggregated.clone(), joined.clone());
+    let config = Config { communication: CommunicationConfig::Process(2), worker: WorkerConfig::default().operator_fuel(fuel) };
+    timely::execute(config, move |worker| {
+        let index = worker.index() as u64;
+        let (aggregates, joins) = worker.dataflow::<u64,_,_>(|scope| {
+            let records = (0 .. 1000u64).filter(move |x| x % 2 == index).to_stream(scope).delay(|x, _| x / 100);
+            let aggregates = records
+                .map(|x| (x % 10, x))
+                .aggregate(|_key, val, agg| { *agg += val; }, |key, agg: u64| (key, agg), |key| *key)
+                .capture();
+            let left = records.map(|x| (x % 50, x));
+            let right = records.filter(|x| x % 3 == 0).map(|x| (x % 50, x));
+            let joins = left.join_by_key(&right, 2).capture();
+            (aggregates, joins)
+        });
+        while worker.step() { }
+        aggregated2.lock().unwrap().extend(aggregates.extract());
+        joined2.lock().unwrap().extend(joins.extract());
+    }).unwrap();
+
+    let mut aggregated = aggregated.lock().unwrap().clone();
+    let mut joined = joined.lock().unwrap().clone();
+    for (_, records) in aggregated.iter_mut() { records.sort(); }
+    for (_, records) in joined.iter_mut() { records.sort(); }
+    aggregated.sort();
+    joined.sort();
+    (aggregated, joined)
+}
+
+// Operators that yield for lack of fuel produce the same results as those that do not.
+#[test]
+fn fuel_2w() {
+    let unlimited = run(Fuel::unlimited());
+    assert!(!unlimited.0.is_empty());
+    assert!(!unlimited.1.is_empty());
+    assert_eq!(run(Fuel::records(1)), unlimited);
+    assert_eq!(run(Fuel::records(7)), unlimited);
+}
@@ -0,0 +1,77 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe, Broadcast};
+use timely::dataflow::operators::gate::Gate;
+use timely::Config;
+
+// Records are released only once every worker's control records for their time are in.
+#[test]
+fn records_wait_for_control_of_their_time() {
+    let released = Arc::new(Mutex::new(Vec::new()));
+    let released2 = released.clone();
+    timely::execute(Config::process(2), move |worker| {
+        let released = released2.clone();
+        let index = worker.index();
+        let mut data = InputHandle::<u64, u64>::new();
+        let mut control = InputHandle::<u64, u64>::new();
+        let models = Arc::new(Mutex::new(Vec::new()));
+        let models2 = models.clone();
+        let probe = worker.dataflow(|scope| {
+            let control = scope.input_from(&mut control)
+                               .broadcast()
+                               .inspect_time(move |time, model| models2.lock().unwrap().push((*time, *model)));
+            let models = models.clone();
+            scope.input_from(&mut data)
+                 .gate(&control)
+                 .inspect_time(move |time, x| {
+                     // Every model at or before the record's time has been seen.
+                     let seen = models.lock().unwrap().iter().filter(|(t, _)| t <= time).count();
+                     assert_eq!(seen as u64, 2 * (time + 1));
+                     released.lock().unwrap().push((*time, *x));
+                 })
+                 .probe()
+        });
+
+        for round in 0 .. 5u64 {
+            data.send(round * 10 + index as u64);
+            data.advance_to(round + 1);
+            for _ in 0 .. 5 {
+                worker.step();
+            }
+            control.send(round);
+            control.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(&(round + 1)));
+        }
+    }).unwrap();
+
+    let mut released = released.lock().unwrap().clone();
+    released.sort();
+    assert_eq!(released, vec![(0, 0), (0, 1), (1, 10), (1, 11), (2, 20), (2, 21), (3, 30), (3, 31), (4, 40), (4, 41)]);
+}
+
+// Records at times the control stream has passed flow through without waiting.
+#[test]
+fn records_after_the_control_frontier_pass_through() {
+    timely::execute_directly(|worker| {
+        let mut data = InputHandle::<u64, u64>::new();
+        let mut control = InputHandle::<u64, ()>::new();
+        let probe = worker.dataflow(|scope| {
+            let control = scope.input_from(&mut control);
+            scope.input_from(&mut data).gate(&control).probe()
+        });
+        control.advance_to(10);
+        worker.step();
+        data.advance_to(3);
+        data.send(3);
+        data.advance_to(4);
+        worker.step_while(|| probe.less_than(&4));
+        assert!(!probe.less_than(&4));
+        // The output frontier is also held back by the control frontier.
+        data.advance_to(11);
+        worker.step_while(|| probe.less_than(&10));
+        assert!(probe.less_equal(&10));
+    });
+}
@@ -0,0 +1,97 @@
+extern crate timely;
+
+use timely::dataflow::Scope;
+use timely::dataflow::operators::{ToStream, Feedback, ConnectLoop, Concat, Map, Filter, Enter, Leave, Probe};
+use timely::progress::validation::GraphError;
+use timely::worker::Worker;
+use timely::WorkerConfig;
+
+fn worker() -> Worker<timely::communication::allocator::Thread> {
+    Worker::new(WorkerConfig::default(), timely::communication::allocator::Thread::new())
+}
+
+// A loop whose feedback edge does not advance timestamps is reported with its operators.
+#[test]
+fn zero_summary_cycle() {
+    let mut worker = worker();
+    let result = worker.try_dataflow::<u64,_,_>(|scope| {
+        let (handle, cycle) = scope.feedback::<u64>(0);
+        (0 .. 10).to_stream(scope)
+                 .concat(&cycle)
+                 .map(|x| x + 1)
+                 .connect_loop(handle);
+    });
+    match result {
+        Err(GraphError::CycleWithoutIncrement { operators, .. }) => {
+            let names = operators.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+            assert_eq!(names, vec!["Feedback", "Concatenate", "Map"]);
+        },
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+// A feedback handle that is never connected leaves an operator unbuilt.
+#[test]
+fn unconnected_feedback() {
+    let mut worker = worker();
+    let result = worker.try_dataflow::<u64,_,_>(|scope| {
+        let (_handle, cycle) = scope.feedback::<u64>(1);
+        (0 .. 10).to_stream(scope).concat(&cycle).probe();
+    });
+    assert!(matches!(result, Err(GraphError::MissingOperator { .. })));
+}
+
+// Mistakes in nested scopes surface from the dataflow containing them.
+#[test]
+fn nested_scope_error() {
+    let mut worker = worker();
+    let result = worker.try_dataflow::<u64,_,_>(|scope| {
+        let stream = (0 .. 10).to_stream(scope);
+        scope.iterative::<u64,_,_>(|inner| {
+            let (handle, cycle) = inner.feedback(Default::default());
+            stream.enter(inner)
+                  .concat(&cycle)
+                  .map(|x| x + 1)
+                  .connect_loop(handle);
+            stream.enter(inner).leave()
+        }).probe();
+    });
+    match result {
+        Err(GraphError::CycleWithoutIncrement { scope, .. }) => assert_eq!(scope.1.len(), 2),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+#[should_panic(expected = "invalid dataflow")]
+fn dataflow_panics() {
+    let mut worker = worker();
+    worker.dataflow::<u64,_,_>(|scope| {
+        let (_handle, cycle) = scope.feedback::<u64>(1);
+        (0 .. 10).to_stream(scope).concat(&cycle).probe();
+    });
+}
+
+// A failed dataflow leaves the worker able to build and run others.
+#[test]
+fn worker_usable_after_error() {
+    let mut worker = worker();
+    for _ in 0 .. 2 {
+        let result = worker.try_dataflow::<u64,_,_>(|scope| {
+            let (handle, cycle) = scope.feedback::<u64>(0);
+            (0 .. 10).to_stream(scope).concat(&cycle).connect_loop(handle);
+        });
+        assert!(result.is_err());
+    }
+
+    let probe = worker.try_dataflow::<u64,_,_>(|scope| {
+        let (handle, cycle) = scope.feedback::<u64>(1);
+        let stream = (0 .. 10).to_stream(scope).concat(&cycle);
+        stream.map(|x| x + 1).filter(|x| *x < 10).connect_loop(handle);
+        stream.probe()
+    }).unwrap();
+    assert_eq!(worker.installed_dataflows().len(), 1);
+    while !probe.done() {
+        worker.step();
+    }
+}
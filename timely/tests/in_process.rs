@@ -0,0 +1,43 @@
+extern crate timely;
+
+use timely::WorkerConfig;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
+
+// Records routed between processes are serialized, and each round completes on every worker.
+#[test]
+fn in_process_cluster_exchanges_records_and_progress() {
+    let guards = timely::execute_multiprocess_in_process(3, 2, WorkerConfig::default(), |worker| {
+        let index = worker.index();
+        let peers = worker.peers();
+        let mut input = InputHandle::<u64, (u64, (usize, u64))>::new();
+        let received = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let received2 = received.clone();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x| x.0)
+                 .inspect(move |x| received2.borrow_mut().push(*x))
+                 .probe()
+        });
+        for round in 0 .. 5u64 {
+            for target in 0 .. peers as u64 {
+                input.send((target, (index, round)));
+            }
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+            // Every worker's records for this round have arrived.
+            let arrived = received.borrow().iter().filter(|(_, (_, r))| *r == round).count();
+            assert_eq!(arrived, peers);
+        }
+        let received = received.borrow();
+        assert!(received.iter().all(|(target, _)| *target as usize == index));
+        (peers, received.len())
+    }).unwrap();
+
+    assert_eq!(guards.len(), 3);
+    for guards in guards {
+        for result in guards.join() {
+            assert_eq!(result.unwrap(), (6, 30));
+        }
+    }
+}
@@ -0,0 +1,118 @@
+extern crate timely;
+
+use std::fs::File;
+use std::io::Cursor;
+
+use timely::dataflow::operators::{Capture, Delay, ToStream};
+use timely::dataflow::operators::capture::{Event, EventPusher, Extract, IndexedEventWriter, Replay, replay_from};
+use timely::dataflow::operators::capture::event::EventIterator;
+use timely::dataflow::operators::capture::indexed::read_index;
+
+// Writes the events of a stream whose frontier advances through times `0 .. 100`, with ten
+// records at each time and a straggler at time 99 sent at the start.
+fn write(period: u64) -> (Vec<u8>, Vec<u8>) {
+    let (mut events, mut index) = (Vec::new(), Vec::new());
+    {
+        let mut writer = IndexedEventWriter::<u64, u64, _, _>::new(&mut events, &mut index).period(period);
+        writer.push(Event::Messages(99, vec![9999]));
+        for time in 0 .. 100u64 {
+            writer.push(Event::Messages(time, (0 .. 10).map(|x| time * 100 + x).collect()));
+            writer.push(Event::Progress(vec![(time + 1, 1), (time, -1)]));
+        }
+        writer.push(Event::Progress(vec![(100, -1)]));
+    }
+    (events, index)
+}
+
+// The accumulated progress and the messages of a replay.
+type Replayed = (Vec<(u64, i64)>, Vec<(u64, u64)>);
+
+// Reads events until the reader has none, accumulating the progress and collecting the messages.
+fn read_all<I: EventIterator<u64, u64>>(reader: &mut I) -> Replayed {
+    let mut progress = timely::progress::ChangeBatch::new_from(0, 1);
+    let mut messages = Vec::new();
+    for _ in 0 .. 1000 {
+        match reader.next() {
+            Some(Event::Progress(updates)) => progress.extend(updates.iter().cloned()),
+            Some(Event::Messages(time, data)) => messages.extend(data.iter().map(|x| (*time, *x))),
+            None => { },
+        }
+    }
+    (progress.into_inner(), messages)
+}
+
+#[test]
+fn replay_seeks_to_requested_time() {
+    let (events, index) = write(256);
+    let checkpoints = read_index::<u64, u64, _>(&index[..]).unwrap();
+    assert!(checkpoints.len() > 10);
+    // Checkpoints lie at increasing offsets, and hold the capabilities of the stream.
+    assert!(checkpoints.windows(2).all(|pair| pair[0].offset < pair[1].offset));
+    assert_eq!(checkpoints[0].counts, vec![(0, 1)]);
+
+    for time in [0, 20, 50, 98, 99, 100] {
+        let mut reader = replay_from::<u64, u64, _, _>(Cursor::new(&events[..]), &index[..], time).unwrap();
+        let (progress, messages) = read_all(&mut reader);
+        assert!(progress.is_empty());
+
+        let mut expected = (time .. 100).flat_map(|t| (0 .. 10).map(move |x| (t, t * 100 + x))).collect::<Vec<_>>();
+        if time <= 99 { expected.insert(0, (99, 9999)); }
+        assert_eq!(messages, expected);
+    }
+
+    // The straggler at time 99 precedes all checkpoints but the first.
+    let reader = replay_from::<u64, u64, _, _>(Cursor::new(&events[..]), &index[..], 60).unwrap();
+    assert_eq!(reader.offset(), checkpoints[0].offset);
+}
+
+#[test]
+fn replay_skips_earlier_events() {
+    let (mut events, mut index) = (Vec::new(), Vec::new());
+    {
+        let mut writer = IndexedEventWriter::<u64, u64, _, _>::new(&mut events, &mut index).period(256);
+        for time in 0 .. 100u64 {
+            writer.push(Event::Messages(time, vec![time; 10]));
+            writer.push(Event::Progress(vec![(time + 1, 1), (time, -1)]));
+        }
+        writer.push(Event::Progress(vec![(100, -1)]));
+    }
+    let reader = replay_from::<u64, u64, _, _>(Cursor::new(&events[..]), &index[..], 90).unwrap();
+    assert!(reader.offset() > events.len() as u64 * 8 / 10);
+    let mut reader = reader;
+    let (progress, messages) = read_all(&mut reader);
+    assert!(progress.is_empty());
+    assert_eq!(messages, (90 .. 100u64).flat_map(|t| vec![(t, t); 10]).collect::<Vec<_>>());
+}
+
+#[test]
+fn replay_checks_types() {
+    let (events, index) = write(256);
+    assert!(replay_from::<u32, u64, _, _>(Cursor::new(&events[..]), &index[..], 0).is_err());
+    assert!(read_index::<u64, u32, _>(&index[..]).is_err());
+}
+
+// A captured dataflow replays into another from a requested time.
+#[test]
+fn dataflow_round_trip() {
+    let directory = std::env::temp_dir();
+    let events = directory.join(format!("timely-indexed-capture-{}.events", std::process::id()));
+    let index = directory.join(format!("timely-indexed-capture-{}.index", std::process::id()));
+
+    let writer = IndexedEventWriter::new(File::create(&events).unwrap(), File::create(&index).unwrap()).period(512);
+    timely::example(move |scope| {
+        (0 .. 1000u64).to_stream(scope)
+                      .delay(|x, _time| *x % 50)
+                      .capture_into(writer);
+    });
+
+    let (events2, index2) = (events.clone(), index.clone());
+    let captured = timely::example(move |scope| {
+        let reader = replay_from::<u64, u64, _, _>(File::open(&events2).unwrap(), File::open(&index2).unwrap(), 40).unwrap();
+        Some(reader).replay_into(scope).capture()
+    });
+    let expected = (40 .. 50u64).map(|t| (t, (0 .. 20).map(|x| x * 50 + t).collect::<Vec<_>>())).collect::<Vec<_>>();
+    assert_eq!(captured.extract(), expected);
+
+    std::fs::remove_file(events).unwrap();
+    std::fs::remove_file(index).unwrap();
+}
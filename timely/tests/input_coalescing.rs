@@ -0,0 +1,85 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
+use timely::logging::TimelyProgressEvent;
+use timely::Config;
+
+// Ticks the input ten times between worker steps, sending a record at each tick, and returns
+// the number of progress updates sent and the times of the records received.
+fn ticker(coalesce: bool) -> (usize, Vec<(u64, u64)>) {
+    let sent = Arc::new(Mutex::new(0));
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let (sent2, received2) = (sent.clone(), received.clone());
+    timely::execute(Config::process(2), move |worker| {
+        let sent = sent2.clone();
+        let received = received2.clone();
+        worker.log_register().insert::<TimelyProgressEvent,_>("timely/progress", move |_time, data| {
+            for (_, _, event) in data.drain(..).filter(|(_, _, event)| event.is_send) {
+                *sent.lock().unwrap() += event.messages.iter().count() + event.internal.iter().count();
+            }
+        });
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x: &u64| *x)
+                 .inspect_time(move |time, x| received.lock().unwrap().push((*time, *x)))
+                 .probe()
+        });
+        for round in 0 .. 50u64 {
+            for tick in 0 .. 10u64 {
+                let time = round * 10 + tick;
+                input.send(time);
+                if coalesce { input.advance_to_coalesced(time + 1); }
+                else { input.advance_to(time + 1); }
+            }
+            worker.step();
+        }
+        input.advance_to(500);
+        worker.step_while(|| probe.less_than(&500));
+    }).unwrap();
+    let sent = *sent.lock().unwrap();
+    let mut received = received.lock().unwrap().clone();
+    received.sort_by_key(|(_, x)| *x);
+    (sent, received)
+}
+
+#[test]
+fn coalescing_reduces_progress_traffic() {
+    let (plain, plain_received) = ticker(false);
+    let (coalesced, coalesced_received) = ticker(true);
+    assert!(coalesced * 2 < plain, "coalesced: {}, plain: {}", coalesced, plain);
+
+    // Without coalescing, each record is at the time it was sent.
+    assert!(plain_received.iter().all(|(time, x)| time == x));
+    // With coalescing, each record is at some earlier time, and none are lost.
+    assert_eq!(coalesced_received.len(), 1000);
+    assert!(coalesced_received.iter().all(|(time, x)| time <= x));
+    assert!(coalesced_received.iter().any(|(time, x)| time < x));
+}
+
+#[test]
+fn coalesced_advances_never_rewind() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::<u64, u64>::new();
+        let probe = worker.dataflow(|scope| scope.input_from(&mut input).probe());
+        input.advance_to_coalesced(5);
+        input.advance_to_coalesced(7);
+        assert_eq!(input.time(), &0);
+        // A plain advance applies at once, and may not precede requested times.
+        input.advance_to(8);
+        assert_eq!(input.time(), &8);
+        worker.step_while(|| probe.less_than(&8));
+        assert!(!probe.less_than(&8));
+    });
+}
+
+#[test]
+#[should_panic]
+fn plain_advance_may_not_precede_coalesced() {
+    let mut input = InputHandle::<u64, u64>::new();
+    input.advance_to_coalesced(5);
+    input.advance_to(3);
+}
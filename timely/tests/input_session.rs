@@ -0,0 +1,66 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::InputSession;
+use timely::dataflow::operators::{Delay, Exchange, Input, Inspect, Probe};
+use timely::Config;
+
+// The session waits for the slower of two probes, and bounds the epochs buffered ahead of it.
+#[test]
+fn waits_for_every_probe() {
+    timely::execute(Config::process(2), |worker| {
+        let mut session = InputSession::new();
+        let (fast, slow) = worker.dataflow(|scope| {
+            let stream = scope.input_from(session.handle());
+            let fast = stream.probe();
+            // The slow branch holds each record back until ten epochs later.
+            let slow = stream.exchange(|x: &u64| *x)
+                             .delay(|x, _time| x + 10)
+                             .probe();
+            (fast, slow)
+        });
+        session.add_probe(fast.clone());
+        session.add_probe(slow.clone());
+
+        for round in 0 .. 50u64 {
+            session.send(round);
+            session.advance_to(round + 1);
+            session.flush_until_within(worker, 12);
+            assert!(session.outstanding() <= 12);
+        }
+        session.flush_until_caught_up(worker);
+        assert!(!fast.less_than(&50));
+        assert!(!slow.less_than(&50));
+        assert!(session.caught_up());
+
+        session.close();
+        worker.step_while(|| !slow.done());
+    }).unwrap();
+}
+
+// Advancing to the current epoch introduces no further epoch.
+#[test]
+fn repeated_advances() {
+    let mut worker = timely::worker::Worker::new(Default::default(), timely::communication::allocator::Thread::new());
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let seen2 = seen.clone();
+    let mut session = InputSession::<u64, u64>::new();
+    let probe = worker.dataflow(|scope| {
+        scope.input_from(session.handle())
+             .inspect_time(move |time, x| seen2.borrow_mut().push((*time, *x)))
+             .probe()
+    });
+    session.add_probe(probe);
+
+    session.send(1);
+    session.advance_to(0);
+    assert_eq!(session.outstanding(), 0);
+    session.advance_to(2);
+    session.advance_to(2);
+    session.flush_until_caught_up(&mut worker);
+    assert_eq!(*seen.borrow(), vec![(0, 1)]);
+    assert_eq!(session.outstanding(), 0);
+    assert_eq!(session.time(), &2);
+}
@@ -0,0 +1,38 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe};
+
+// Each round's records are observed before the frontier that closes the round.
+#[test]
+fn inspect_core_orders_batches_and_frontiers() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let seen = events.clone();
+    timely::execute(timely::Config::thread(), move |worker| {
+        let seen = seen.clone();
+        let mut input = InputHandle::<u64, u64>::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .inspect_core(move |event| seen.lock().unwrap().push(match event {
+                     Ok((time, data)) => Ok((*time, data.to_vec())),
+                     Err(frontier) => Err(frontier.to_vec()),
+                 }))
+                 .probe()
+        });
+        for round in 0 .. 3 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+    }).unwrap();
+
+    let mut expected = vec![Err(vec![0])];
+    for round in 0 .. 3 {
+        expected.push(Ok((round, vec![round])));
+        expected.push(Err(vec![round + 1]));
+    }
+    expected.push(Err(vec![]));
+    assert_eq!(*events.lock().unwrap(), expected);
+}
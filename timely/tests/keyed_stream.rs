@@ -0,0 +1,49 @@
+extern crate timely;
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use timely::Config;
+use timely::worker::AsWorker;
+use timely::dataflow::operators::{ToStream, Inspect, Probe};
+use timely::dataflow::operators::keyed::KeyBy;
+
+// Keyed pipelines fold and join records at the workers their keys were exchanged to.
+#[test]
+fn keyed_pipeline_folds_and_joins_by_key() {
+    let results = Arc::new(Mutex::new(BTreeMap::new()));
+    let results2 = results.clone();
+    timely::execute(Config::process(3), move |worker| {
+        let index = worker.index();
+        let peers = worker.peers() as u64;
+        let results = results2.clone();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            let sums = (0 .. 100u64).filter(move |x| x % peers == index as u64)
+                .to_stream(scope)
+                .key_by(|x| x % 10)
+                .map_values(|x| x * 2)
+                .fold_per_key(|_key, val, sum: &mut u64| *sum += val);
+            let names = (0 .. 10u64).filter(move |x| x % peers == index as u64)
+                .to_stream(scope)
+                .key_by(|x| *x)
+                .map_values(|x| format!("key{}", x));
+            let hasher = scope.config().hasher().clone();
+            sums.join(&names, 0)
+                .into_stream()
+                .inspect(move |(key, (sum, name))| {
+                    assert_eq!(hasher.hash(key) % peers, index as u64);
+                    results.lock().unwrap().insert(*key, (*sum, name.clone()));
+                })
+                .probe()
+        });
+        worker.step_while(|| !probe.done());
+    }).unwrap();
+
+    let results = results.lock().unwrap();
+    assert_eq!(results.len(), 10);
+    for (key, (sum, name)) in results.iter() {
+        let expected = (0 .. 100u64).filter(|x| x % 10 == *key).map(|x| x * 2).sum::<u64>();
+        assert_eq!(*sum, expected);
+        assert_eq!(name, &format!("key{}", key));
+    }
+}
@@ -0,0 +1,78 @@
+extern crate timely;
+
+use timely::WorkerConfig;
+use timely::communication::{Allocate, Message, Push};
+use timely::communication::allocator::zero_copy::initialize::initialize_networking_from_sockets_with;
+use timely::communication::networking::{loopback_sockets, ConnectionOptions};
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
+
+// Records much larger than a message buffer move between processes intact.
+#[test]
+fn large_records_move_between_processes() {
+    let guards = timely::execute_multiprocess_in_process(2, 1, WorkerConfig::default(), |worker| {
+        let index = worker.index();
+        let mut input = InputHandle::<u64, (u64, Vec<u64>)>::new();
+        let received = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let received2 = received.clone();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x| x.0 + 1)
+                 .inspect(move |x| received2.borrow_mut().push(x.clone()))
+                 .probe()
+        });
+        // Records of 3MB, 8B, and 5MB, the first and last of which must be sent in chunks.
+        for length in [3 << 17, 1, 5 << 17].iter() {
+            input.send((index as u64, (0 .. *length as u64).map(|x| x * 3 + index as u64).collect()));
+        }
+        input.close();
+        while !probe.done() { worker.step(); }
+        let received = received.borrow();
+        for (source, record) in received.iter() {
+            assert_eq!(*source as usize, 1 - index);
+            assert!(record.iter().enumerate().all(|(i, x)| *x == i as u64 * 3 + *source));
+        }
+        received.iter().map(|(_, record)| record.len()).collect::<Vec<_>>()
+    }).unwrap();
+
+    for guards in guards {
+        for result in guards.join() {
+            assert_eq!(result.unwrap(), vec![3 << 17, 1, 5 << 17]);
+        }
+    }
+}
+
+// Records larger than the configured limit are dropped, rather than sent or taking down the worker.
+#[test]
+fn records_beyond_the_limit_are_dropped() {
+    let handles = loopback_sockets(2).unwrap().into_iter().enumerate().map(|(index, sockets)| {
+        std::thread::spawn(move || {
+            let options = ConnectionOptions { max_record: Some(1 << 20), ..Default::default() };
+            initialize_networking_from_sockets_with(sockets, index, 1, options, Box::new(|_| None)).unwrap()
+        })
+    }).collect::<Vec<_>>();
+    let (builders, guards): (Vec<_>, Vec<_>) = handles.into_iter().map(|handle| handle.join().unwrap()).unzip();
+    let mut allocators = builders.into_iter().flatten().map(|builder| builder.build()).collect::<Vec<_>>();
+    let (mut pushers, puller) = allocators[0].allocate::<Vec<u64>>(0);
+    let (remote_pushers, mut remote_puller) = allocators[1].allocate::<Vec<u64>>(0);
+
+    // Only the records within the limit are sent.
+    pushers[1].push(&mut Some(Message::from_typed(vec![0u64; 1 << 16])));
+    pushers[1].push(&mut Some(Message::from_typed(vec![1u64; 1 << 18])));
+    pushers[1].push(&mut Some(Message::from_typed(vec![2u64; 1 << 10])));
+    pushers[1].done();
+    allocators[0].release();
+
+    let mut received = Vec::new();
+    while received.len() < 2 {
+        allocators[1].receive();
+        while let Some(message) = remote_puller.recv() {
+            received.push((message[0], message.len()));
+        }
+    }
+    assert_eq!(received, vec![(0, 1 << 16), (2, 1 << 10)]);
+
+    // Workers release their connections before the connections shut down.
+    drop((pushers, puller, remote_pushers, remote_puller, allocators));
+    drop(guards);
+}
@@ -0,0 +1,94 @@
+extern crate timely;
+
+use std::collections::BTreeMap;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Concat, Delay, Input, Map, Probe};
+use timely::dataflow::operators::aggregation::Aggregate;
+use timely::dataflow::operators::windows::Windows;
+use timely::test_harness::{Harness, Script};
+
+// Records sent at epoch `e` carry event times `e - 3 ..= e + 1`.
+fn records() -> Vec<(u64, u64)> {
+    (0 .. 300u64).map(|x| {
+        let epoch = 3 + x % 11;
+        (epoch, epoch + x % 5 - 3)
+    }).collect()
+}
+
+// Each record appears once, at its event time if on time and at its arrival time if late,
+// however the records arrive.
+#[test]
+fn records_are_on_time_or_late() {
+    let mut expected = BTreeMap::new();
+    for (epoch, event) in records() {
+        let entry = if event + 2 >= epoch { (event, (true, event)) } else { (epoch, (false, event)) };
+        expected.entry(entry.0).or_insert_with(Vec::new).push(entry.1);
+    }
+    for seed in 0 .. 20 {
+        let mut harness = Harness::new(|stream| {
+            let (on_time, late) = stream.delay_with_late(2, |x, _time| *x);
+            on_time.map(|x| (true, x)).concat(&late.map(|x| (false, x)))
+        });
+        harness.play(&Script::generate(records(), seed));
+        harness.finish().assert_epochs(expected.clone());
+    }
+}
+
+// The on-time stream lags the input frontier by the allowance, and the late stream does not.
+#[test]
+fn on_time_frontier_lags_by_allowance() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::new();
+        let (on_time, late) = worker.dataflow::<u64,_,_>(|scope| {
+            let (on_time, late) = scope.input_from(&mut input).delay_with_late(3, |x: &u64, _time| *x);
+            (on_time.probe(), late.probe())
+        });
+        input.advance_to(10);
+        worker.step_while(|| late.less_than(&10) || on_time.less_than(&7));
+        for _ in 0 .. 5 { worker.step(); }
+        assert!(on_time.less_equal(&7));
+        drop(input);
+        worker.step_while(|| !on_time.done() || !late.done());
+    });
+}
+
+// Windows and aggregates collect the records of their event times, less late records.
+#[test]
+fn windows_and_aggregates_by_event_time() {
+    let mut windows = BTreeMap::new();
+    let mut sums = BTreeMap::new();
+    let mut late = BTreeMap::new();
+    for (epoch, event) in records() {
+        if event + 1 >= epoch {
+            *windows.entry(event / 4 * 4 + 3).or_insert_with(BTreeMap::new).entry(event / 4 * 4).or_insert(0) += 1;
+            *sums.entry(event).or_insert_with(BTreeMap::new).entry(event % 2).or_insert(0) += event;
+        }
+        else {
+            late.entry(epoch).or_insert_with(Vec::new).push(event);
+        }
+    }
+    let flatten = |map: BTreeMap<u64, BTreeMap<u64, u64>>| map.into_iter().map(|(time, inner)| (time, inner.into_iter().collect::<Vec<_>>())).collect::<Vec<_>>();
+    let windows = flatten(windows);
+    let sums = flatten(sums);
+
+    for seed in 0 .. 10 {
+        let script = Script::generate(records(), seed);
+
+        let mut harness = Harness::new(|stream| stream.tumbling_window_with_late(4, 1, |x| *x, |count: &mut u64, _x| *count += 1).0);
+        harness.play(&script);
+        harness.finish().assert_epochs(windows.clone());
+
+        let mut harness = Harness::new(|stream| stream.tumbling_window_with_late(4, 1, |x| *x, |count: &mut u64, _x| *count += 1).1);
+        harness.play(&script);
+        harness.finish().assert_epochs(late.clone());
+
+        let mut harness = Harness::new(|stream| {
+            stream.map(|x| (x % 2, x))
+                  .aggregate_with_late(1, |(_key, x)| *x, |_key, x, sum: &mut u64| *sum += x, |key, sum| (key, sum), |key| *key)
+                  .0
+        });
+        harness.play(&script);
+        harness.finish().assert_epochs(sums.clone());
+    }
+}
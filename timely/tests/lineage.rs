@@ -0,0 +1,168 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::channels::lineage::Lineage;
+use timely::dataflow::operators::{Enter, Exchange, Input, Inspect, Leave, Map, Probe, ToStream};
+use timely::dataflow::Scope;
+use timely::logging::TimelyEvent;
+
+// Batches carry no lineage unless the worker traces it.
+#[test]
+fn untraced_by_default() {
+    timely::example(|scope| {
+        (0 .. 10u64).to_stream(scope)
+                    .inspect_batch(|_time, _data| assert_eq!(Lineage::current(), None));
+    });
+}
+
+// Records keep the lineage of the input that introduced them, through operators and exchanges.
+#[test]
+fn lineage_follows_records() {
+    let observed = Arc::new(Mutex::new(Vec::new()));
+    let inputs = Arc::new(Mutex::new(Vec::new()));
+    let (seen, operators) = (observed.clone(), inputs.clone());
+    let mut config = timely::Config::process(3);
+    config.worker = config.worker.trace_lineage(true);
+    timely::execute(config, move |worker| {
+        let index = worker.index();
+        let operators = operators.clone();
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+            for (_, worker, event) in data.drain(..) {
+                if let TimelyEvent::Operates(event) = event {
+                    if event.name == "Input" { operators.lock().unwrap().push((worker, event.id)); }
+                }
+            }
+        });
+        let seen = seen.clone();
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .map(|(worker, round): (usize, u64)| (worker, round + 1))
+                 .exchange(|_| 0)
+                 .inspect_batch(move |_time, data| {
+                     let lineage = Lineage::current().expect("lineage is traced");
+                     seen.lock().unwrap().extend(data.iter().map(|(worker, _)| (*worker, lineage)));
+                 })
+                 .probe()
+        });
+        for round in 0 .. 5 {
+            input.send((index, round));
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+    }).unwrap();
+
+    let observed = observed.lock().unwrap();
+    let inputs = inputs.lock().unwrap();
+    assert_eq!(observed.len(), 15);
+    for (worker, lineage) in observed.iter() {
+        assert_eq!(lineage.worker, *worker);
+        assert!(inputs.contains(&(*worker, lineage.operator)), "unexpected lineage {:?}", lineage);
+    }
+    // Each round of each worker is a distinct batch.
+    let mut batches = observed.iter().map(|(_, lineage)| *lineage).collect::<Vec<_>>();
+    batches.sort();
+    batches.dedup();
+    assert_eq!(batches.len(), 15);
+}
+
+// Records keep their lineage as they enter and leave nested scopes.
+#[test]
+fn lineage_crosses_scopes() {
+    let observed = Arc::new(Mutex::new(Vec::new()));
+    let seen = observed.clone();
+    let mut config = timely::Config::thread();
+    config.worker = config.worker.trace_lineage(true);
+    timely::execute(config, move |worker| {
+        let seen = seen.clone();
+        worker.dataflow::<u64,_,_>(|scope| {
+            let outer = seen.clone();
+            let stream = (0 .. 10u64).to_stream(scope)
+                                     .inspect_batch(move |_time, _data| outer.lock().unwrap().push(Lineage::current()));
+            scope.region(|inner| {
+                let seen = seen.clone();
+                stream.enter(inner)
+                      .map(|x| x + 1)
+                      .inspect_batch(move |_time, _data| seen.lock().unwrap().push(Lineage::current()))
+                      .leave()
+            });
+        });
+    }).unwrap();
+
+    let observed = observed.lock().unwrap();
+    assert_eq!(observed.len(), 2);
+    assert!(observed[0].is_some());
+    assert_eq!(observed[0], observed[1]);
+}
+
+// Logged messages report the lineage of their records, when sent and when received.
+#[test]
+fn lineage_is_logged() {
+    let logged = Arc::new(Mutex::new(Vec::new()));
+    let events = logged.clone();
+    let mut config = timely::Config::thread();
+    config.worker = config.worker.trace_lineage(true);
+    timely::execute(config, move |worker| {
+        let events = events.clone();
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| {
+            for (_, _, event) in data.drain(..) {
+                if let TimelyEvent::Messages(event) = event {
+                    events.lock().unwrap().push((event.is_send, event.lineage));
+                }
+            }
+        });
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input).exchange(|x: &u64| *x).probe()
+        });
+        input.send(0);
+        input.advance_to(1);
+        worker.step_while(|| probe.less_than(input.time()));
+    }).unwrap();
+
+    let logged = logged.lock().unwrap();
+    assert!(logged.iter().any(|(is_send, _)| *is_send));
+    assert!(logged.iter().any(|(is_send, _)| !*is_send));
+    assert!(logged.iter().all(|(_, lineage)| lineage.is_some()));
+    assert!(logged.windows(2).all(|pair| pair[0].1 == pair[1].1));
+}
+
+// Lineage is traced by the workers configured to trace it, and not by later workers on the thread.
+#[test]
+fn traced_per_worker() {
+    let (traced, _) = timely::execute_cooperatively_from(timely::WorkerConfig::default().trace_lineage(true), |worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 10u64).to_stream(scope)
+                        .inspect_batch(|_time, _data| assert!(Lineage::current().is_some()));
+        });
+    });
+    let (untraced, _) = timely::execute_cooperatively_from(timely::WorkerConfig::default(), |worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 10u64).to_stream(scope)
+                        .inspect_batch(|_time, _data| assert_eq!(Lineage::current(), None));
+        });
+    });
+    let (mut traced, mut untraced) = (traced, untraced);
+    while untraced.tick() | traced.tick() { }
+    assert_eq!(Lineage::current(), None);
+}
+
+// Lineage is carried alongside batches, and not in their serialized form.
+#[test]
+fn not_serialized() {
+    use timely::communication::Message;
+    use timely::communication::codec::{Codec, Native};
+    use timely::dataflow::channels::Message as Batch;
+    let batch = Batch::new(0u64, vec![1u64, 2, 3], 0, 0);
+    let plain = Message::from_typed(batch.clone());
+    let mut annotated = Message::from_typed(batch);
+    annotated.set_annotation(Some(std::sync::Arc::new(Lineage { operator: 1, worker: 0, seq: 0 })));
+    assert_eq!(Native::length_in_bytes(&annotated), Native::length_in_bytes(&plain));
+    let mut bytes = Vec::new();
+    Native::into_bytes(&annotated, &mut bytes);
+    let received: Message<Batch<u64, u64>> = Native::from_bytes(timely::bytes::arc::Bytes::from(bytes));
+    assert!(received.annotation().is_none());
+    assert_eq!(received.data, vec![1, 2, 3]);
+}
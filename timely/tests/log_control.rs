@@ -0,0 +1,145 @@
+extern crate timely;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Probe};
+use timely::dataflow::ProbeHandle;
+use timely::logging::{LogControl, TimelyEvent};
+use timely::worker::Worker;
+use timely::communication::allocator::Thread;
+
+// Sets a flag when dropped, as an action is when its stream closes.
+struct Closed(Rc<Cell<bool>>);
+impl Drop for Closed {
+    fn drop(&mut self) { self.0.set(true); }
+}
+
+// Records the number of events presented to an action, which reports when it is dropped.
+fn sink(events: &Rc<Cell<usize>>, closed: &Rc<Cell<bool>>) -> impl FnMut(&std::time::Duration, &mut Vec<(std::time::Duration, usize, TimelyEvent)>)+'static {
+    let (events, closed) = (events.clone(), Closed(closed.clone()));
+    move |_time, data| {
+        let _ = &closed;
+        events.set(events.get() + data.len());
+    }
+}
+
+// A worker with a dataflow whose operators hold handles to the "timely" logger.
+fn running() -> (Worker<Thread>, InputHandle<u64, u64>, ProbeHandle<u64>) {
+    let mut worker = Worker::new(Default::default(), Thread::new());
+    let (events, closed) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(false)));
+    worker.log_register().insert::<TimelyEvent,_>("timely", sink(&events, &closed));
+    let mut input = InputHandle::new();
+    let probe = worker.dataflow(|scope| scope.input_from(&mut input).probe());
+    (worker, input, probe)
+}
+
+// Sends a record in a new round, and steps the worker until it is processed.
+fn round(worker: &mut Worker<Thread>, input: &mut InputHandle<u64, u64>, probe: &ProbeHandle<u64>) {
+    let time = *input.time();
+    input.send(time);
+    input.advance_to(time + 1);
+    while probe.less_than(input.time()) { worker.step(); }
+}
+
+// Replacing a logger redirects the handles of running dataflows, after flushing to the old action.
+#[test]
+fn replace_redirects_running_dataflows() {
+    let (mut worker, mut input, probe) = running();
+    let (events, closed) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(false)));
+    assert!(worker.log_register().replace::<TimelyEvent,_>("timely", sink(&events, &closed)));
+    round(&mut worker, &mut input, &probe);
+    worker.log_register().flush();
+    assert!(events.get() > 0);
+    assert!(!closed.get());
+
+    let (replaced, closed_again) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(false)));
+    assert!(worker.log_register().replace::<TimelyEvent,_>("timely", sink(&replaced, &closed_again)));
+    assert!(closed.get());
+    let before = events.get();
+    round(&mut worker, &mut input, &probe);
+    worker.log_register().flush();
+    assert_eq!(events.get(), before);
+    assert!(replaced.get() > 0);
+}
+
+// Replacing an unbound logger binds a new one.
+#[test]
+fn replace_installs_unbound_loggers() {
+    let mut worker = Worker::new(Default::default(), Thread::new());
+    let (events, closed) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(false)));
+    assert!(!worker.log_register().replace::<TimelyEvent,_>("timely", sink(&events, &closed)));
+    assert_eq!(worker.log_register().is_enabled("timely"), Some(true));
+    worker.dataflow::<u64,_,_>(|scope| { scope.new_input::<u64>(); });
+    worker.log_register().flush();
+    assert!(events.get() > 0);
+}
+
+// Paused loggers discard events until resumed.
+#[test]
+fn pause_and_resume() {
+    let (mut worker, mut input, probe) = running();
+    let (events, closed) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(false)));
+    worker.log_register().replace::<TimelyEvent,_>("timely", sink(&events, &closed));
+
+    assert!(worker.log_register().set_enabled("timely", false));
+    let paused = events.get();
+    round(&mut worker, &mut input, &probe);
+    worker.log_register().flush();
+    assert_eq!(events.get(), paused);
+
+    assert!(worker.log_register().set_enabled("timely", true));
+    round(&mut worker, &mut input, &probe);
+    worker.log_register().flush();
+    assert!(events.get() > paused);
+    assert!(!worker.log_register().set_enabled("unbound", false));
+}
+
+// Removing a logger closes its stream, though running dataflows hold handles to it.
+#[test]
+fn remove_closes_running_streams() {
+    let (mut worker, mut input, probe) = running();
+    let (events, closed) = (Rc::new(Cell::new(0)), Rc::new(Cell::new(false)));
+    worker.log_register().replace::<TimelyEvent,_>("timely", sink(&events, &closed));
+    round(&mut worker, &mut input, &probe);
+    worker.log_register().remove("timely");
+    assert!(closed.get());
+    let removed = events.get();
+    assert!(removed > 0);
+    round(&mut worker, &mut input, &probe);
+    assert_eq!(events.get(), removed);
+    assert_eq!(worker.log_register().is_enabled("timely"), None);
+}
+
+// Commands issued by one worker pause and resume streams on all workers.
+#[test]
+fn control_across_workers() {
+    let observed = Arc::new(Mutex::new(Vec::new()));
+    let seen = observed.clone();
+    timely::execute(timely::Config::process(3), move |worker| {
+        let logged = Rc::new(RefCell::new(0));
+        let count = logged.clone();
+        worker.log_register().insert::<TimelyEvent,_>("timely", move |_time, data| *count.borrow_mut() += data.len());
+        let mut control = LogControl::new(worker);
+        if worker.index() == 1 {
+            control.disable("timely");
+            control.disable("unbound");
+        }
+        let mut applied = 0;
+        while applied < 2 {
+            worker.step();
+            applied += control.apply(worker);
+        }
+        let paused = worker.log_register().is_enabled("timely");
+        if worker.index() == 2 {
+            control.enable("timely");
+        }
+        while control.apply(worker) == 0 {
+            worker.step();
+        }
+        seen.lock().unwrap().push((paused, worker.log_register().is_enabled("timely")));
+    }).unwrap();
+    assert_eq!(*observed.lock().unwrap(), vec![(Some(false), Some(true)); 3]);
+}
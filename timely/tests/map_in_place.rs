@@ -0,0 +1,77 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::communication::allocator::Thread;
+use timely::dataflow::{InputHandle, Stream};
+use timely::dataflow::scopes::Child;
+use timely::dataflow::operators::{Filter, Input, Inspect, Map, Probe};
+use timely::worker::Worker;
+
+// The dataflow scope of a single worker.
+type Local<'a> = Child<'a, Worker<Thread>, u64>;
+
+// Sends one batch through operators built by `build`, returning the records that emerge and
+// whether they arrived in the allocation of the batch that was sent.
+fn forwarded<F>(build: F) -> (Vec<i64>, bool)
+where
+    F: for<'a> FnOnce(&Stream<Local<'a>, u64>) -> Stream<Local<'a>, i64> + Send + Sync + 'static,
+{
+    timely::execute_directly(move |worker| {
+        let mut input = InputHandle::new();
+        let seen = Rc::new(RefCell::new((Vec::new(), Vec::new())));
+        let sink = seen.clone();
+        let probe = worker.dataflow(|scope| {
+            build(&scope.input_from(&mut input))
+                .inspect_batch(move |_time, data| {
+                    let mut sink = sink.borrow_mut();
+                    sink.0.extend_from_slice(data);
+                    sink.1.push(data.as_ptr() as usize);
+                })
+                .probe()
+        });
+        let mut batch = (0 .. 100u64).collect::<Vec<_>>();
+        let sent = batch.as_ptr() as usize;
+        input.send_batch(&mut batch);
+        input.close();
+        worker.step_while(|| !probe.done());
+        let seen = seen.borrow();
+        (seen.0.clone(), seen.1 == vec![sent])
+    })
+}
+
+// Records mapped to a type of the same layout reuse the allocation of their batch.
+#[test]
+fn map_reuses_batches() {
+    let (records, reused) = forwarded(|stream| stream.map(|x| -(x as i64)));
+    assert_eq!(records, (0 .. 100).map(|x| -x).collect::<Vec<_>>());
+    assert!(reused);
+}
+
+// Retained records stay in the allocation of their batch.
+#[test]
+fn filter_reuses_batches() {
+    let (records, reused) = forwarded(|stream| stream.filter(|x| x % 3 == 0).map(|x| x as i64));
+    assert_eq!(records, (0 .. 100).filter(|x| x % 3 == 0).collect::<Vec<_>>());
+    assert!(reused);
+}
+
+// Records updated in place, and those filtered in place, are forwarded in their batch.
+#[test]
+fn in_place_reuses_batches() {
+    let (records, reused) = forwarded(|stream| {
+        stream.map_in_place(|x| *x *= 2)
+              .filter_map_in_place(|x| { *x += 1; *x % 4 == 1 })
+              .map(|x| x as i64)
+    });
+    assert_eq!(records, (0 .. 100).map(|x| 2 * x + 1).filter(|x| x % 4 == 1).collect::<Vec<_>>());
+    assert!(reused);
+}
+
+// Records mapped to a type of a different layout are still delivered.
+#[test]
+fn map_to_other_layouts() {
+    let (records, _) = forwarded(|stream| stream.map(|x| (x, x as u8)).map(|(x, y)| x as i64 + y as i64));
+    assert_eq!(records, (0 .. 100).map(|x| 2 * x).collect::<Vec<_>>());
+}
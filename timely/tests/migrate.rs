@@ -0,0 +1,63 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe};
+use timely::dataflow::operators::aggregation::MigratingStateMachine;
+
+// Keys counted across four rounds, whose bins swap owners at `moved`, as `(round, key, count,
+// worker)` in order.
+fn counted(moved: u64) -> Vec<(u64, u64, u64, usize)> {
+    let produced = Arc::new(Mutex::new(Vec::new()));
+    let seen = produced.clone();
+    timely::execute(timely::Config::process(2), move |worker| {
+        let index = worker.index();
+        let seen = seen.clone();
+        let mut input = InputHandle::new();
+        let mut control = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            let control = scope.input_from(&mut control);
+            scope.input_from(&mut input)
+                 .migrating_state_machine(&control, 4, |key: &u64, val: u64, count: &mut u64| {
+                     *count += val;
+                     (false, Some((*key, *count)))
+                 }, |key| *key)
+                 .inspect_time(move |round, (key, count)| seen.lock().unwrap().push((*round, *key, *count, index)))
+                 .probe()
+        });
+        for round in 0 .. 4u64 {
+            if index == 0 {
+                input.send_batch(&mut (0 .. 4).map(|key| (key, 1)).collect());
+                if round == moved {
+                    control.send_batch(&mut (0 .. 4).map(|bin| (bin, (bin + 1) % 2)).collect());
+                }
+            }
+            input.advance_to(round + 1);
+            control.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+    }).unwrap();
+    let mut produced = produced.lock().unwrap().clone();
+    produced.sort();
+    produced
+}
+
+// States move with their bins, and continue from where the old owner left them.
+#[test]
+fn states_move_with_their_bins() {
+    let expected = (0 .. 4u64).flat_map(|round| (0 .. 4u64).map(move |key| {
+        let owner = if round < 2 { key % 2 } else { (key + 1) % 2 };
+        (round, key, round + 1, owner as usize)
+    })).collect::<Vec<_>>();
+    assert_eq!(counted(2), expected);
+}
+
+// Bins reassigned at the first time apply all records at their new owners.
+#[test]
+fn states_move_before_any_records() {
+    let expected = (0 .. 4u64).flat_map(|round| (0 .. 4u64).map(move |key| {
+        (round, key, round + 1, ((key + 1) % 2) as usize)
+    })).collect::<Vec<_>>();
+    assert_eq!(counted(0), expected);
+}
@@ -0,0 +1,81 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use timely::WorkerConfig;
+use timely::communication::allocator::zero_copy::mpi::Communicator;
+use timely::dataflow::operators::{ToStream, Exchange, Inspect, Probe};
+
+// A message between ranks, with its tag.
+type Packet = (i32, Vec<u8>);
+
+// A communicator for ranks in one process, delivering messages in order on each link.
+struct Loopback {
+    rank: usize,
+    size: usize,
+    senders: Vec<Mutex<Sender<Packet>>>,
+    receivers: Vec<Mutex<Receiver<Packet>>>,
+}
+
+impl Loopback {
+    fn world(size: usize) -> Vec<Loopback> {
+        // Links from each source to each target, indexed first by source.
+        let (senders, receivers): (Vec<Vec<_>>, Vec<Vec<_>>) = (0 .. size).map(|_| {
+            (0 .. size).map(|_| {
+                let (send, recv) = channel();
+                (Mutex::new(send), Mutex::new(recv))
+            }).unzip()
+        }).unzip();
+        // Each rank receives from its links indexed first by target.
+        let mut receivers = receivers.into_iter().map(Vec::into_iter).collect::<Vec<_>>();
+        let receivers = (0 .. size).map(|_| receivers.iter_mut().map(|links| links.next().unwrap()).collect::<Vec<_>>());
+        senders.into_iter().zip(receivers).enumerate().map(|(rank, (senders, receivers))| {
+            Loopback { rank, size, senders, receivers }
+        }).collect()
+    }
+}
+
+impl Communicator for Loopback {
+    fn rank(&self) -> usize { self.rank }
+    fn size(&self) -> usize { self.size }
+    fn send(&self, destination: usize, tag: i32, bytes: &[u8]) {
+        self.senders[destination].lock().unwrap().send((tag, bytes.to_vec())).unwrap();
+    }
+    fn receive(&self, source: usize, tag: i32) -> Vec<u8> {
+        let (received, bytes) = self.receivers[source].lock().unwrap().recv().unwrap();
+        assert_eq!(received, tag);
+        bytes
+    }
+}
+
+// Workers of processes connected by MPI messages exchange data.
+#[test]
+fn exchange_over_mpi() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let handles = Loopback::world(3).into_iter().map(|communicator| {
+        let seen = seen.clone();
+        std::thread::spawn(move || {
+            timely::execute_mpi(Arc::new(communicator), 2, WorkerConfig::default(), move |worker| {
+                let index = worker.index();
+                let seen = seen.clone();
+                let probe = worker.dataflow::<u64,_,_>(|scope| {
+                    (0 .. 60u64).filter(move |x| *x as usize % 6 == index)
+                        .to_stream(scope)
+                        .exchange(|x| *x / 10)
+                        .inspect(move |x| seen.lock().unwrap().push((index, *x)))
+                        .probe()
+                });
+                worker.step_while(|| !probe.done());
+            }).unwrap();
+        })
+    }).collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort_by_key(|&(_, x)| x);
+    assert_eq!(seen.iter().map(|&(_, x)| x).collect::<Vec<_>>(), (0 .. 60).collect::<Vec<_>>());
+    assert!(seen.iter().all(|&(index, x)| index == (x / 10) as usize % 6));
+}
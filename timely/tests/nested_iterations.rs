@@ -0,0 +1,56 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::operators::{ToStream, Concat, Feedback, ConnectLoop, Filter, Map, Inspect, Probe};
+use timely::order::{Iterations, IterationsSummary};
+
+// An inner loop of two iterations runs within each of two iterations of an outer loop, with the
+// counters of both loops in one timestamp.
+#[test]
+fn nested_iterations() {
+    timely::execute(timely::Config::thread(), |worker| {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = seen.clone();
+        let probe = worker.dataflow::<Iterations,_,_>(|scope| {
+            let (outer_handle, outer_cycle) = scope.feedback(IterationsSummary::increment(0, 1));
+            let (inner_handle, inner_cycle) = scope.feedback(IterationsSummary::increment(1, 1));
+
+            // Records are (outer, inner) iteration counts, where the inner count restarts in each
+            // outer iteration, unlike the inner counter of the timestamp.
+            let stream = Some((0, 0))
+                .to_stream(scope)
+                .concat(&outer_cycle)
+                .concat(&inner_cycle)
+                .inspect_batch(move |time, data: &[(u32, u32)]| {
+                    sink.borrow_mut().extend(data.iter().map(|x| (time.clone(), *x)))
+                });
+
+            stream
+                .filter(|x| x.1 < 2)
+                .map(|(outer, inner)| (outer, inner + 1))
+                .connect_loop(inner_handle);
+            stream
+                .filter(|x| x.1 == 2 && x.0 < 1)
+                .map(|(outer, _)| (outer + 1, 0))
+                .connect_loop(outer_handle);
+
+            stream.probe()
+        });
+
+        worker.step_while(|| !probe.done());
+
+        let mut seen = seen.borrow().clone();
+        seen.sort();
+        let expected = vec![
+            (Iterations::new(vec![]), (0, 0)),
+            (Iterations::new(vec![0, 1]), (0, 1)),
+            (Iterations::new(vec![0, 2]), (0, 2)),
+            (Iterations::new(vec![1, 2]), (1, 0)),
+            (Iterations::new(vec![1, 3]), (1, 1)),
+            (Iterations::new(vec![1, 4]), (1, 2)),
+        ];
+        assert_eq!(seen, expected);
+    }).unwrap();
+}
@@ -0,0 +1,84 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::Config;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe};
+
+#[derive(Clone)]
+enum Route {
+    Offset(usize),
+    Permutation(Vec<usize>),
+}
+
+// Runs `peers` workers, each sending `(index, sequence)` records in several rounds along
+// `route`, and returns the records received by each worker in the order they were received.
+fn received(peers: usize, route: Route) -> Vec<Vec<(usize, u64)>> {
+    let received = Arc::new(Mutex::new(vec![Vec::new(); peers]));
+    let shared = received.clone();
+    let mut config = Config::process(peers);
+    config.worker = config.worker.batch_size(8);
+    timely::execute(config, move |worker| {
+        let index = worker.index();
+        let received = shared.clone();
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            let stream = scope.input_from(&mut input);
+            let stream = match route.clone() {
+                Route::Offset(offset) => stream.pipeline_across_workers(offset),
+                Route::Permutation(permutation) => stream.permute_workers(permutation),
+            };
+            stream.inspect(move |x| received.lock().unwrap()[index].push(*x))
+                  .probe()
+        });
+        for round in 0 .. 4u64 {
+            for x in 0 .. 100 { input.send((index, round * 100 + x)); }
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(&(round + 1)));
+        }
+    }).unwrap();
+    let received = received.lock().unwrap().clone();
+    received
+}
+
+// Worker `i` receives every record of worker `source(i)`, in the order they were sent.
+fn check(received: &[Vec<(usize, u64)>], source: impl Fn(usize) -> usize) {
+    for (index, records) in received.iter().enumerate() {
+        let expected = (0 .. 400).map(|x| (source(index), x)).collect::<Vec<_>>();
+        assert_eq!(records, &expected, "records received by worker {}", index);
+    }
+}
+
+#[test]
+fn offset_routes_in_order() {
+    let received = received(4, Route::Offset(1));
+    check(&received, |index| (index + 3) % 4);
+}
+
+#[test]
+fn offset_wraps_around() {
+    let received = received(3, Route::Offset(5));
+    check(&received, |index| (index + 1) % 3);
+}
+
+#[test]
+fn offset_of_peers_stays_local() {
+    let received = received(3, Route::Offset(3));
+    check(&received, |index| index);
+}
+
+#[test]
+fn permutation_routes_in_order() {
+    let received = received(4, Route::Permutation(vec![2, 0, 3, 1]));
+    check(&received, |index| [1, 3, 0, 2][index]);
+}
+
+#[test]
+#[should_panic(expected = "is not a permutation")]
+fn non_permutation_panics() {
+    timely::example(|scope| {
+        use timely::dataflow::operators::ToStream;
+        (0 .. 10u64).to_stream(scope).permute_workers(vec![0, 0]);
+    });
+}
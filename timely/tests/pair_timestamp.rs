@@ -0,0 +1,51 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::operators::{Inspect, Probe, UnorderedInput};
+use timely::order::Pair;
+use timely::progress::Antichain;
+
+// Capabilities held at incomparable pairs each hold back the frontier, until each is released.
+#[test]
+fn pair_frontier_incomparable() {
+    timely::execute(timely::Config::thread(), |worker| {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = seen.clone();
+        let ((mut input, cap), probe) = worker.dataflow::<Pair<u64, u64>,_,_>(move |scope| {
+            let (input, stream) = scope.new_unordered_input();
+            let probe = stream
+                .inspect_batch(move |time, data: &[u64]| sink.borrow_mut().extend(data.iter().map(|x| (*time, *x))))
+                .probe();
+            (input, probe)
+        });
+
+        let event = cap.delayed(&Pair::new(2, 0));
+        let processing = cap.delayed(&Pair::new(0, 2));
+        drop(cap);
+        input.session(event.clone()).give(0);
+        input.session(processing.clone()).give(1);
+        worker.step_while(|| probe.less_than(&Pair::new(0, 2)));
+        probe.with_frontier(|frontier| {
+            assert_eq!(frontier.to_owned(), Antichain::from(vec![Pair::new(2, 0), Pair::new(0, 2)]));
+        });
+        assert!(!probe.less_equal(&Pair::new(1, 1)));
+        assert!(probe.less_equal(&Pair::new(2, 2)));
+
+        let joined = event.delayed(&Pair::new(2, 2));
+        drop(event);
+        worker.step_while(|| probe.less_equal(&Pair::new(2, 0)));
+        probe.with_frontier(|frontier| {
+            assert_eq!(frontier.to_owned(), Antichain::from_elem(Pair::new(0, 2)));
+        });
+
+        drop(processing);
+        input.session(joined).give(2);
+        worker.step_while(|| !probe.done());
+
+        let mut seen = seen.borrow().clone();
+        seen.sort();
+        assert_eq!(seen, vec![(Pair::new(0, 2), 1), (Pair::new(2, 0), 0), (Pair::new(2, 2), 2)]);
+    }).unwrap();
+}
@@ -0,0 +1,54 @@
+extern crate timely;
+
+use std::net::TcpListener;
+
+use timely::{Config, CommunicationConfig, WorkerConfig};
+use timely::communication::networking::PeerAddress;
+use timely::dataflow::operators::{ToStream, Exchange, Inspect};
+
+// Processes accept connections on their bind addresses, and are reached at their advertised addresses.
+#[test]
+fn cluster_binds_separately_from_advertised_addresses() {
+    let ports = (0 .. 2).map(|_| TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()).collect::<Vec<_>>();
+    let peers = ports.iter().map(|port| {
+        PeerAddress::new(format!("localhost:{}", port)).bind(format!("127.0.0.1:{}", port))
+    }).collect::<Vec<_>>();
+
+    let processes = (0 .. 2).map(|process| {
+        let peers = peers.clone();
+        std::thread::spawn(move || {
+            let config = Config { communication: CommunicationConfig::cluster(1, process, peers), worker: WorkerConfig::default() };
+            timely::execute(config, |worker| {
+                let index = worker.index();
+                worker.dataflow::<u64,_,_>(|scope| {
+                    (0 .. 10u64).to_stream(scope)
+                                .exchange(|x| *x)
+                                .inspect(move |x| assert_eq!(*x as usize % 2, index));
+                });
+                worker.peers()
+            }).unwrap().join().into_iter().map(|result| result.unwrap()).collect::<Vec<_>>()
+        })
+    }).collect::<Vec<_>>();
+
+    for process in processes {
+        assert_eq!(process.join().unwrap(), vec![2]);
+    }
+}
+
+// Peers parse from hostfile lines, with an optional bind address.
+#[test]
+fn peer_address_parses_bind() {
+    assert_eq!("host:2101".parse::<PeerAddress>(), Ok(PeerAddress::new("host:2101")));
+    assert_eq!("host:2101  0.0.0.0:2101".parse::<PeerAddress>(), Ok(PeerAddress::new("host:2101").bind("0.0.0.0:2101")));
+    assert!("host:2101 0.0.0.0:2101 extra".parse::<PeerAddress>().is_err());
+    assert!("".parse::<PeerAddress>().is_err());
+
+    let config = Config::from_toml("processes = 2\nprocess = 1\nhosts = [\"a:1\", \"b:1 0.0.0.0:1\"]").unwrap();
+    match config.communication {
+        CommunicationConfig::Cluster { addresses, bind, .. } => {
+            assert_eq!(addresses, vec!["a:1".to_owned(), "b:1".to_owned()]);
+            assert_eq!(bind, Some("0.0.0.0:1".to_owned()));
+        },
+        _ => panic!("expected a cluster configuration"),
+    }
+}
@@ -0,0 +1,55 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
+use timely::{CommunicationConfig, Config, WorkerConfig};
+
+// A panic at one worker poisons its dataflow at all workers, while other dataflows continue.
+#[test]
+fn poison_3w() {
+    let config = Config { communication: CommunicationConfig::Process(3), worker: WorkerConfig::default().isolate_panics(true) };
+    let results = timely::execute(config, |worker| {
+        let panics = Rc::new(RefCell::new(Vec::new()));
+        let sink = panics.clone();
+        worker.on_dataflow_panic(move |panic| sink.borrow_mut().push(panic.clone()));
+
+        let mut bad = InputHandle::new();
+        let bad_probe = worker.dataflow_named("bad", |scope| {
+            scope.input_from(&mut bad)
+                 .exchange(|_| 1)
+                 .inspect(|x: &u64| if *x == 7 { panic!("seven") })
+                 .probe()
+        });
+        let mut good = InputHandle::new();
+        let good_probe = worker.dataflow_named("good", |scope| {
+            scope.input_from(&mut good)
+                 .exchange(|x: &u64| *x)
+                 .probe()
+        });
+
+        for round in 0 .. 10u64 {
+            bad.send(round);
+            bad.advance_to(round + 1);
+            good.send(round);
+            good.advance_to(round + 1);
+            worker.step_while(|| bad_probe.less_than(bad.time()) || good_probe.less_than(good.time()));
+        }
+
+        let panics = panics.borrow().clone();
+        (worker.index(), panics, worker.installed_dataflows(), worker.poisoned_dataflows())
+    }).unwrap().join();
+
+    for result in results {
+        let (index, panics, installed, poisoned) = result.unwrap();
+        assert_eq!(panics.len(), 1);
+        assert_eq!(panics[0].dataflow, 0);
+        assert_eq!(panics[0].name.as_deref(), Some("bad"));
+        assert_eq!(panics[0].worker, 1);
+        assert_eq!(panics[0].message, "seven");
+        assert_eq!(installed, vec![1], "worker {}", index);
+        assert_eq!(poisoned, vec![0]);
+    }
+}
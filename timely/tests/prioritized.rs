@@ -0,0 +1,67 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::channels::pact::{ParallelizationContract, Pipeline, Prioritized};
+use timely::dataflow::operators::UnorderedInput;
+use timely::dataflow::operators::generic::Operator;
+use timely::scheduling::Scheduler;
+
+// Delivers batches sent at `rounds` of times to an operator reading through `pact`, which pulls
+// `per_schedule` batches each time it is scheduled, and reports the (time, record) pairs it pulls.
+fn delivered<P>(pact: P, rounds: Vec<Vec<(u64, u64)>>, per_schedule: usize) -> Vec<(u64, u64)>
+where
+    P: ParallelizationContract<u64, u64>+Send+Sync+'static,
+{
+    let pulled = Arc::new(Mutex::new(Vec::new()));
+    let seen = pulled.clone();
+    let pact = Mutex::new(Some(pact));
+    timely::execute(timely::Config::thread(), move |worker| {
+        let seen = seen.clone();
+        let pact = pact.lock().unwrap().take().unwrap();
+        let (mut input, cap) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, stream) = scope.new_unordered_input::<u64>();
+            let handle = scope.clone();
+            stream.unary(pact, "Pull", move |_cap, info| {
+                let activator = handle.activator_for(&info.address[..]);
+                move |input, output| {
+                    for _ in 0 .. per_schedule {
+                        if let Some((time, data)) = input.next() {
+                            seen.lock().unwrap().extend(data.iter().map(|x| (*time.time(), *x)));
+                            output.session(&time).give_vec(&mut data.replace(Vec::new()));
+                        }
+                        else { break; }
+                    }
+                    activator.activate();
+                }
+            });
+            input
+        });
+        for round in rounds.iter() {
+            for (time, record) in round.iter() {
+                input.session(cap.delayed(time)).give(*record);
+            }
+            worker.step();
+        }
+        drop(cap);
+        for _ in 0 .. 10 { worker.step(); }
+    }).unwrap();
+    let pulled = pulled.lock().unwrap();
+    pulled.clone()
+}
+
+// Batches are delivered smallest time first, and in the order they arrived at equal times.
+#[test]
+fn smallest_time_first() {
+    let sent = vec![vec![(3, 0), (1, 1), (2, 2), (1, 3)]];
+    assert_eq!(delivered(Pipeline, sent.clone(), usize::MAX), vec![(3, 0), (1, 1), (2, 2), (1, 3)]);
+    assert_eq!(delivered(Pipeline.prioritized(), sent, usize::MAX), vec![(1, 1), (1, 3), (2, 2), (3, 0)]);
+}
+
+// Batches arriving while others are pending are delivered ahead of them, if at earlier times.
+#[test]
+fn arrivals_overtake_pending() {
+    let sent = vec![vec![(5, 0), (4, 1)], vec![(1, 2)]];
+    assert_eq!(delivered(Pipeline, sent.clone(), 1), vec![(5, 0), (4, 1), (1, 2)]);
+    assert_eq!(delivered(Prioritized::new(Pipeline), sent, 1), vec![(4, 1), (1, 2), (5, 0)]);
+}
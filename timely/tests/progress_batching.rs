@@ -0,0 +1,73 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Probe};
+use timely::logging::TimelyProgressEvent;
+use timely::{CommunicationConfig, Config, WorkerConfig};
+
+// Runs many small epochs through an exchange, returning the number of progress messages sent.
+fn progress_messages(worker_config: WorkerConfig, park: bool) -> usize {
+    let sent = Arc::new(Mutex::new(0));
+    let sent2 = sent.clone();
+    let config = Config { communication: CommunicationConfig::Process(2), worker: worker_config };
+    timely::execute(config, move |worker| {
+        let sent = sent2.clone();
+        worker.log_register().insert::<TimelyProgressEvent,_>("timely/progress", move |_time, data| {
+            *sent.lock().unwrap() += data.drain(..).filter(|(_, _, event)| event.is_send).count();
+        });
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input).exchange(|x: &u64| *x).probe()
+        });
+        for round in 0 .. 200u64 {
+            input.send(round);
+            input.advance_to(round + 1);
+            if park {
+                worker.step_or_park_while(None, || probe.less_than(input.time()));
+            }
+            else {
+                worker.step();
+            }
+        }
+        input.close();
+        worker.step_or_park_while(None, || !probe.done());
+    }).unwrap();
+    let count = *sent.lock().unwrap();
+    count
+}
+
+#[test]
+fn batching_sends_fewer_progress_messages() {
+    let unbatched = progress_messages(WorkerConfig::default(), false);
+    let batched = progress_messages(WorkerConfig::default().progress_batching(1_000, Duration::from_secs(3600)), false);
+    assert!(batched < unbatched, "batched: {}, unbatched: {}", batched, unbatched);
+}
+
+// With limits that are never reached, only the flush before parking lets epochs complete.
+#[test]
+fn held_progress_is_sent_before_parking() {
+    progress_messages(WorkerConfig::default().progress_batching(usize::MAX, Duration::from_secs(3600)), true);
+}
+
+// A short period releases updates even when the worker never parks.
+#[test]
+fn held_progress_is_sent_after_the_period() {
+    let config = Config {
+        communication: CommunicationConfig::Process(2),
+        worker: WorkerConfig::default().progress_batching(usize::MAX, Duration::from_millis(1)),
+    };
+    timely::execute(config, |worker| {
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input).exchange(|x: &u64| *x).probe()
+        });
+        for round in 0 .. 10u64 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+    }).unwrap();
+}
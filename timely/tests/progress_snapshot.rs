@@ -0,0 +1,88 @@
+extern crate timely;
+extern crate serde_json;
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use timely::dataflow::{InputHandle, Scope};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Input, Enter, Leave, Map, Operator, Probe};
+use timely::scheduling::Scheduler;
+use timely::progress::snapshot::ProgressSnapshot;
+
+// Held capabilities, frontiers, and operators of nested scopes appear in the snapshot.
+#[test]
+fn snapshot_describes_operators() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::new();
+        let dataflow = worker.next_dataflow_index();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            let stream = scope.input_from(&mut input);
+            scope.iterative::<u32,_,_>(|inner| stream.enter(inner).map(|x: u64| x + 1).leave())
+                 .probe()
+        });
+        input.send(0);
+        input.advance_to(5);
+        worker.step_while(|| probe.less_than(&5));
+
+        let snapshot = worker.progress_snapshot(dataflow).unwrap();
+        assert_eq!(snapshot.dataflow, dataflow);
+
+        let input_op = snapshot.operators.iter().find(|operator| operator.name == "Input").unwrap();
+        assert_eq!(input_op.outputs[0].capabilities, vec!["5".to_string()]);
+        assert_eq!(input_op.outputs[0].frontier, vec!["5".to_string()]);
+
+        // The map operator lies in the nested scope, and its times are pairs.
+        let map = snapshot.operators.iter().find(|operator| operator.name == "Map").unwrap();
+        assert_eq!(map.addr.len(), 3);
+        assert_eq!(map.inputs[0].frontier, vec![format!("{:?}", timely::order::Product::new(5u64, 0u32))]);
+        let scope_position = snapshot.operators.iter().position(|operator| operator.addr[..] == map.addr[.. 2]).unwrap();
+        let map_position = snapshot.operators.iter().position(|operator| operator.name == "Map").unwrap();
+        assert!(scope_position < map_position);
+
+        // Snapshots are plain data, which serialize.
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(serde_json::from_str::<ProgressSnapshot>(&json).unwrap(), snapshot);
+
+        drop(input);
+        worker.step_while(|| !probe.done());
+        assert!(worker.progress_snapshot(dataflow).is_none());
+    });
+}
+
+// Messages in flight to an operator that has yet to read them appear at its input.
+#[test]
+fn snapshot_reports_messages() {
+    timely::execute_directly(|worker| {
+        let mut input = InputHandle::new();
+        let open = Rc::new(Cell::new(false));
+        let open2 = open.clone();
+        let dataflow = worker.next_dataflow_index();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .unary(Pipeline, "Lazy", move |_cap, _info| move |input, output| {
+                     if open2.get() {
+                         input.for_each(|time, data| output.session(&time).give_vec(&mut data.replace(Vec::new())));
+                     }
+                 })
+                 .map(|x: u64| x)
+                 .probe()
+        });
+        input.send(7);
+        input.advance_to(1);
+
+        let lazy = |worker: &timely::worker::Worker<_>| {
+            let snapshot = worker.progress_snapshot(dataflow).unwrap();
+            snapshot.operators.into_iter().find(|operator| operator.name == "Lazy").unwrap()
+        };
+        for _ in 0 .. 3 { worker.step(); }
+        assert_eq!(lazy(worker).inputs[0].messages, vec!["0".to_string()]);
+        assert_eq!(lazy(worker).inputs[0].frontier, vec!["0".to_string()]);
+
+        open.set(true);
+        worker.activations().borrow_mut().activate(&lazy(worker).addr[..]);
+        worker.step_while(|| probe.less_than(&1));
+        assert!(lazy(worker).inputs[0].messages.is_empty());
+        assert_eq!(lazy(worker).inputs[0].frontier, vec!["1".to_string()]);
+    });
+}
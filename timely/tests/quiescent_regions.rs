@@ -0,0 +1,54 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::{InputHandle, Scope};
+use timely::dataflow::operators::{Input, Exchange, Inspect, Probe, Feedback, ConnectLoop, Concat, Filter, Map, Enter, Leave};
+
+// Idle regions alongside busy ones do not hold back the busy regions, and are resumed when fed.
+#[test]
+fn idle_regions_do_not_disturb_active_ones() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen2 = seen.clone();
+    timely::execute(timely::Config::process(2), move |worker| {
+        let seen = seen2.clone();
+        let mut busy = InputHandle::<u64, u64>::new();
+        let mut idle = (0 .. 50).map(|_| InputHandle::<u64, u64>::new()).collect::<Vec<_>>();
+        let probe = worker.dataflow(|scope| {
+            for input in idle.iter_mut() {
+                let stream = scope.input_from(input);
+                scope.region(|inner| stream.enter(inner).exchange(|x| *x).leave());
+            }
+            let stream = scope.input_from(&mut busy);
+            scope.region(|inner| {
+                // Counts each record down to zero, in a loop.
+                let (handle, cycle) = inner.feedback(1);
+                stream.enter(inner)
+                      .concat(&cycle)
+                      .exchange(|x| *x)
+                      .filter(|x| *x > 0)
+                      .map(|x| x - 1)
+                      .connect_loop(handle);
+                stream.enter(inner).leave()
+            })
+            .inspect(move |x| seen.lock().unwrap().push(*x))
+            .probe()
+        });
+
+        for round in 0 .. 10 {
+            if worker.index() == 0 {
+                busy.send(round);
+            }
+            busy.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(busy.time()));
+        }
+        // The idle regions must still accept input.
+        for input in idle.iter_mut() {
+            input.send(0);
+        }
+    }).unwrap();
+
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort();
+    assert_eq!(seen, (0 .. 10).collect::<Vec<_>>());
+}
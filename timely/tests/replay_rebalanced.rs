@@ -0,0 +1,64 @@
+extern crate timely;
+
+use std::io::{Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe, Capture};
+use timely::dataflow::operators::capture::{EventReader, EventWriter, ReplayRebalanced};
+
+// A writer appending to a shared buffer.
+#[derive(Clone, Default)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(bytes)
+    }
+    fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+}
+
+// Shards captured by three workers are replayed by four, each record at the worker it routes to.
+#[test]
+fn replay_three_shards_into_four_workers() {
+    let shards = (0 .. 3).map(|_| Shared::default()).collect::<Vec<_>>();
+    let writers = shards.clone();
+    timely::execute(timely::Config::process(3), move |worker| {
+        let writer = writers[worker.index()].clone();
+        let mut input = InputHandle::<u64, u64>::new();
+        let probe = worker.dataflow(|scope| {
+            let stream = scope.input_from(&mut input);
+            stream.capture_into(EventWriter::new(writer));
+            stream.probe()
+        });
+        for round in 0 .. 5u64 {
+            input.send(round * 3 + worker.index() as u64);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+    }).unwrap();
+
+    let bytes = shards.iter().map(|shard| shard.0.lock().unwrap().clone()).collect::<Vec<_>>();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen2 = seen.clone();
+    timely::execute(timely::Config::process(4), move |worker| {
+        let bytes = bytes.clone();
+        let seen = seen2.clone();
+        let index = worker.index();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.replay_rebalanced(3, |shard| EventReader::<_,u64,_>::new(Cursor::new(bytes[shard].clone())), |x| *x)
+                 .inspect_time(move |time, x| seen.lock().unwrap().push((index, *time, *x)))
+                 .probe()
+        });
+        worker.step_while(|| !probe.done());
+    }).unwrap();
+
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort_by_key(|&(_, _, x)| x);
+    assert_eq!(seen.len(), 15);
+    for (position, (worker, time, x)) in seen.into_iter().enumerate() {
+        assert_eq!(x, position as u64);
+        assert_eq!(time, x / 3);
+        assert_eq!(worker, (x % 4) as usize);
+    }
+}
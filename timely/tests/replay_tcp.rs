@@ -0,0 +1,32 @@
+extern crate timely;
+
+use std::net::TcpListener;
+use std::time::Duration;
+
+use timely::dataflow::operators::{Probe, ToStream};
+use timely::dataflow::operators::capture::tcp::{CaptureTcp, ReplayTcp};
+use timely::scheduling::Scheduler;
+
+// A replaying worker stops polling once every shard has connected and completed.
+#[test]
+fn polling_stops_once_complete() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addresses = [listener.local_addr().unwrap().to_string()];
+    let producer = std::thread::spawn(move || {
+        timely::execute_directly(move |worker| {
+            worker.dataflow::<u64,_,_>(|scope| (0 .. 10u64).to_stream(scope).capture_tcp(&addresses[..]));
+        });
+    });
+
+    timely::execute_directly(move |worker| {
+        let probe = worker.dataflow::<u64,_,_>(|scope| scope.replay_tcp::<u64>(listener, 1).probe());
+        worker.step_while(|| !probe.done());
+        let mut steps = 0;
+        while worker.activations().borrow().empty_for().is_some() {
+            assert!(steps < 100, "replay continues to poll after completing");
+            worker.step_or_park(Some(Duration::from_millis(10)));
+            steps += 1;
+        }
+    });
+    producer.join().unwrap();
+}
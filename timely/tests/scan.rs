@@ -0,0 +1,62 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::operators::{Exchange, Inspect, Probe, Scan, ToStream, UnorderedInput};
+
+// Times arriving out of order are folded in order, once the frontier passes them.
+#[test]
+fn folds_times_in_order() {
+    let produced = Arc::new(Mutex::new(Vec::new()));
+    let seen = produced.clone();
+    timely::execute(timely::Config::thread(), move |worker| {
+        let seen = seen.clone();
+        let observed = seen.clone();
+        let ((mut input, capability), probe) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, stream) = scope.new_unordered_input::<char>();
+            let probe = stream.scan(String::new(), |state, _time, records| state.extend(records))
+                              .inspect_time(move |time, state| seen.lock().unwrap().push((*time, state.clone())))
+                              .probe();
+            (input, probe)
+        });
+        let (first, second) = (capability.delayed(&1), capability.delayed(&2));
+        input.session(second.clone()).give_iterator("de".chars());
+        input.session(first.clone()).give('b');
+        input.session(capability.clone()).give('a');
+        drop(capability);
+        drop(second);
+        worker.step_while(|| probe.less_than(&1));
+        // Time 2 waits for time 1, which may still receive records.
+        assert_eq!(observed.lock().unwrap().len(), 1);
+        input.session(first.clone()).give('c');
+        drop(first);
+        worker.step_while(|| !probe.done());
+    }).unwrap();
+    assert_eq!(*produced.lock().unwrap(), vec![
+        (0, "a".to_string()),
+        (1, "abc".to_string()),
+        (2, "abcde".to_string()),
+    ]);
+}
+
+// Each worker folds the records it receives into its own state.
+#[test]
+fn state_per_worker() {
+    let produced = Arc::new(Mutex::new(Vec::new()));
+    let seen = produced.clone();
+    timely::execute(timely::Config::process(2), move |worker| {
+        let (index, seen) = (worker.index(), seen.clone());
+        worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 10u64).to_stream(scope)
+                        .exchange(|x| *x)
+                        .scan_with(0, |count, _time, records, output| {
+                            *count += records.len();
+                            output.push(*count);
+                        })
+                        .inspect(move |count| seen.lock().unwrap().push((index, *count)));
+        });
+    }).unwrap();
+    let mut produced = produced.lock().unwrap().clone();
+    produced.sort();
+    assert_eq!(produced, vec![(0, 10), (1, 10)]);
+}
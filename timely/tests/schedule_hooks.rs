@@ -0,0 +1,67 @@
+extern crate timely;
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Filter, Map, Inspect, Probe};
+use timely::scheduling::hooks::{Activity, ScheduleHook};
+
+// Permits one operator schedule per worker step, and checks that no operator produces records
+// at an output that it did not first consume at some input, allowing for the input operator.
+struct SingleStep {
+    permitted: Rc<Cell<bool>>,
+    schedules: Rc<Cell<usize>>,
+    input: Vec<usize>,
+}
+
+impl ScheduleHook for SingleStep {
+    fn on_before_schedule(&mut self, _addr: &[usize]) -> bool {
+        self.permitted.replace(false)
+    }
+    fn on_after_schedule(&mut self, addr: &[usize], activity: &Activity) {
+        self.schedules.set(self.schedules.get() + 1);
+        if addr != &self.input[..] {
+            assert!(activity.produced.iter().sum::<i64>() <= activity.consumed.iter().sum::<i64>());
+        }
+    }
+}
+
+// Single-stepping operators changes the interleaving of schedules, but not the results.
+#[test]
+fn single_step_operators() {
+    timely::execute(timely::Config::process(2), |worker| {
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let sink = seen.clone();
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x: &u64| *x)
+                 .map(|x| x * 3)
+                 .filter(|x| x % 2 == 0)
+                 .inspect(move |x| sink.borrow_mut().push(*x))
+                 .probe()
+        });
+
+        let input_addr = worker.dataflow_graph(0).unwrap().operators.into_iter().find(|o| o.name == "Input").unwrap().addr;
+        let permitted = Rc::new(Cell::new(false));
+        let schedules = Rc::new(Cell::new(0));
+        worker.set_schedule_hook(SingleStep { permitted: permitted.clone(), schedules: schedules.clone(), input: input_addr });
+
+        if worker.index() == 0 {
+            input.send_batch(&mut (0 .. 20).collect());
+        }
+        input.advance_to(1);
+        while probe.less_than(input.time()) {
+            permitted.set(true);
+            worker.step();
+        }
+        assert!(worker.remove_schedule_hook().is_some());
+
+        let mut seen = seen.borrow().clone();
+        seen.sort();
+        let expected = (0 .. 20u64).filter(|x| x % 2 == worker.index() as u64).map(|x| x * 3).filter(|x| x % 2 == 0).collect::<Vec<_>>();
+        assert_eq!(seen, expected);
+        assert!(schedules.get() > 0);
+    }).unwrap();
+}
@@ -0,0 +1,38 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use timely::Config;
+use timely::synchronization::Sequencer;
+
+// Elements pushed by all workers at once are received by every worker in the same order.
+#[test]
+fn sequencer_total_order() {
+    let sequences = Arc::new(Mutex::new(Vec::new()));
+    let sequences2 = sequences.clone();
+    timely::execute(Config::process(3), move |worker| {
+        let timer = Instant::now();
+        let mut sequencer = Sequencer::new(worker, timer);
+        for round in 0 .. 10 {
+            sequencer.push((worker.index(), round));
+        }
+
+        let mut received = Vec::new();
+        while received.len() < 3 * 10 {
+            worker.step();
+            received.extend(&mut sequencer);
+        }
+
+        // Elements of each worker appear in the order that worker pushed them.
+        for index in 0 .. 3 {
+            let rounds = received.iter().filter(|x| x.0 == index).map(|x| x.1).collect::<Vec<_>>();
+            assert_eq!(rounds, (0 .. 10).collect::<Vec<_>>());
+        }
+        sequences2.lock().unwrap().push(received);
+    }).unwrap();
+
+    let sequences = sequences.lock().unwrap();
+    assert_eq!(sequences.len(), 3);
+    assert!(sequences.iter().all(|sequence| sequence == &sequences[0]));
+}
@@ -0,0 +1,48 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::{Config, CommunicationConfig, WorkerConfig};
+use timely::dataflow::operators::{ToStream, Delay, Inspect};
+use timely::dataflow::operators::windows::SessionWindows;
+
+#[test] fn session_windows_1w() { session_windows_helper(CommunicationConfig::Thread); }
+#[test] fn session_windows_2w() { session_windows_helper(CommunicationConfig::Process(2)); }
+#[test] fn session_windows_3w() { session_windows_helper(CommunicationConfig::Process(3)); }
+
+// Records are introduced in reverse time order, and sessions must nonetheless form by time.
+fn session_windows_helper(comm_config: ::timely::CommunicationConfig) {
+    let config = Config {
+        communication: comm_config,
+        worker: WorkerConfig::default(),
+    };
+    let results = Arc::new(Mutex::new(Vec::new()));
+    let sink = results.clone();
+    timely::execute(config, move |worker| {
+        let index = worker.index();
+        let sink = sink.clone();
+        worker.dataflow::<u64,_,_>(move |scope| {
+            let records = if index == 0 {
+                vec![(0u64, 20u64), (1, 15), (0, 12), (1, 11), (0, 5), (0, 3), (1, 1), (0, 0)]
+            }
+            else {
+                Vec::new()
+            };
+            records
+                .to_stream(scope)
+                .delay(|x, _| x.1)
+                .session_windows(4, |_key, times: &mut Vec<u64>, time| times.push(time))
+                .inspect_time(move |time, x| sink.lock().unwrap().push((*time, x.clone())));
+        });
+    }).unwrap();
+
+    let mut results = results.lock().unwrap().clone();
+    results.sort();
+    assert_eq!(results, vec![
+        (5, (1, (1, 1), vec![1])),
+        (9, (0, (0, 5), vec![0, 3, 5])),
+        (16, (0, (12, 12), vec![12])),
+        (19, (1, (11, 15), vec![11, 15])),
+        (24, (0, (20, 20), vec![20])),
+    ]);
+}
@@ -0,0 +1,90 @@
+extern crate timely;
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use timely::communication::allocator::zero_copy::shm::{upgrade, RING_CAPACITY};
+use timely::communication::networking::StreamHalves;
+
+// Connects two threads by a pair of shared-memory rings, returning the halves of each.
+fn connected() -> (StreamHalves, StreamHalves) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let connector = std::thread::spawn(move || upgrade(None)(TcpStream::connect(address).unwrap(), 1, 0).unwrap());
+    let (accepted, _) = listener.accept().unwrap();
+    let halves = upgrade(None)(accepted, 0, 1).unwrap();
+    (halves, connector.join().unwrap())
+}
+
+// Bytes `start .. start + length` of a pattern that does not repeat with the ring's capacity.
+fn pattern(start: usize, length: usize) -> Vec<u8> {
+    (start .. start + length).map(|index| (index % 251) as u8).collect()
+}
+
+// Messages make a round trip between the threads.
+#[test]
+fn round_trip() {
+    let ((mut reader0, mut writer0), (mut reader1, mut writer1)) = connected();
+    let echo = std::thread::spawn(move || {
+        let mut message = [0u8; 5];
+        reader1.read_exact(&mut message).unwrap();
+        writer1.write_all(&message).unwrap();
+    });
+    writer0.write_all(b"hello").unwrap();
+    let mut reply = [0u8; 5];
+    reader0.read_exact(&mut reply).unwrap();
+    assert_eq!(&reply, b"hello");
+    echo.join().unwrap();
+}
+
+// Streams longer than the ring wrap around its end, in writes that straddle it.
+#[test]
+fn wraps_around() {
+    let ((_reader0, mut writer0), (mut reader1, _writer1)) = connected();
+    let length = 3 * RING_CAPACITY + 12345;
+    let writer = std::thread::spawn(move || {
+        let bytes = pattern(0, length);
+        for chunk in bytes.chunks(RING_CAPACITY / 3 + 7) {
+            writer0.write_all(chunk).unwrap();
+        }
+    });
+    let mut received = vec![0u8; length];
+    reader1.read_exact(&mut received).unwrap();
+    assert!(received == pattern(0, length));
+    writer.join().unwrap();
+}
+
+// A writer fills the ring, and then waits until the reader makes space.
+#[test]
+fn full_ring_blocks_writer() {
+    let ((_reader0, mut writer0), (mut reader1, _writer1)) = connected();
+    let reading = Arc::new(AtomicBool::new(false));
+    let started = reading.clone();
+    let writer = std::thread::spawn(move || {
+        let bytes = pattern(0, RING_CAPACITY + 100);
+        let first = writer0.write(&bytes).unwrap();
+        assert_eq!(first, RING_CAPACITY);
+        writer0.write_all(&bytes[first ..]).unwrap();
+        // The remaining bytes only fit once the reader has started.
+        assert!(started.load(Ordering::SeqCst));
+    });
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    reading.store(true, Ordering::SeqCst);
+    let mut received = vec![0u8; RING_CAPACITY + 100];
+    reader1.read_exact(&mut received).unwrap();
+    assert!(received == pattern(0, RING_CAPACITY + 100));
+    writer.join().unwrap();
+}
+
+// A reader observes the end of the stream once the writer shuts down.
+#[test]
+fn shutdown_ends_stream() {
+    let ((_reader0, mut writer0), (mut reader1, _writer1)) = connected();
+    writer0.write_all(b"last").unwrap();
+    writer0.shutdown().unwrap();
+    let mut received = Vec::new();
+    reader1.read_to_end(&mut received).unwrap();
+    assert_eq!(received, b"last");
+}
@@ -0,0 +1,42 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe};
+use timely::dataflow::operators::shed::Shed;
+
+// Records more than the threshold behind the reference frontier are shed and counted.
+#[test]
+fn shed_if_behind_drops_late_records() {
+    let kept = Arc::new(Mutex::new(Vec::new()));
+    let shed = Arc::new(Mutex::new(Vec::new()));
+    let (kept2, shed2) = (kept.clone(), shed.clone());
+    timely::execute(timely::Config::thread(), move |worker| {
+        let (kept, shed) = (kept2.clone(), shed2.clone());
+        let mut events = InputHandle::<u64, u64>::new();
+        let mut clock = InputHandle::<u64, ()>::new();
+        let probe = worker.dataflow(|scope| {
+            let clock = scope.input_from(&mut clock);
+            let (kept_stream, shed_stream) = scope.input_from(&mut events).shed_if_behind(&clock, 5);
+            shed_stream.inspect_time(move |time, count| shed.lock().unwrap().push((*time, *count)));
+            kept_stream.inspect(move |x| kept.lock().unwrap().push(*x)).probe()
+        });
+
+        clock.advance_to(10);
+        worker.step();
+        // Times 0 through 4 lag more than 5 behind 10; time 5 does not.
+        for time in 0 .. 7 {
+            events.send(time);
+            events.send(time + 100);
+            events.advance_to(time + 1);
+        }
+        worker.step_while(|| probe.less_than(events.time()));
+    }).unwrap();
+
+    let mut kept = kept.lock().unwrap().clone();
+    kept.sort();
+    assert_eq!(kept, vec![5, 6, 105, 106]);
+    assert_eq!(shed.lock().unwrap().iter().map(|(_, count)| count).sum::<usize>(), 10);
+    assert!(shed.lock().unwrap().iter().all(|(time, _)| *time < 5));
+}
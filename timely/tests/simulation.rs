@@ -0,0 +1,85 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::communication::allocator::simulation::SimulationConfig;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
+
+// Runs three rounds of an exchange across four workers, and reports the records each worker saw,
+// in the order seen, along with the tick at which the computation completed.
+fn run(config: SimulationConfig) -> (Vec<(u64, usize, u64)>, u64) {
+    let seen = Rc::new(RefCell::new(Vec::new()));
+    let (mut cluster, mut handles) = timely::execute_simulated(4, config, |worker| {
+        let index = worker.index();
+        let seen = seen.clone();
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x: &u64| *x)
+                 .inspect_time(move |time, x| seen.borrow_mut().push((*time, index, *x)))
+                 .probe()
+        });
+        (input, probe)
+    });
+    for round in 0 .. 3u64 {
+        for (input, _) in handles.iter_mut() {
+            for record in 0 .. 8 {
+                input.send(round * 100 + record);
+            }
+            input.advance_to(round + 1);
+        }
+        while handles.iter().any(|(_, probe)| probe.less_than(&(round + 1))) {
+            assert!(cluster.simulation().now() < 10_000, "simulation failed to complete round {}", round);
+            cluster.tick();
+        }
+        let complete = seen.borrow().iter().filter(|(time, _, _)| *time == round).count();
+        assert_eq!(complete, 4 * 8);
+    }
+    handles.clear();
+    while cluster.tick() { }
+    let trace = seen.borrow().clone();
+    (trace, cluster.simulation().now())
+}
+
+// Records reach the worker they are routed to, and rounds complete, under any schedule.
+#[test]
+fn simulation_completes_under_delays() {
+    for seed in 0 .. 20 {
+        let (trace, _) = run(SimulationConfig::new(seed).max_delay(8));
+        assert_eq!(trace.len(), 3 * 4 * 8);
+        assert!(trace.iter().all(|(_, index, x)| *x as usize % 4 == *index));
+    }
+}
+
+// The same seed replays the same execution.
+#[test]
+fn simulation_replays_from_seed() {
+    let config = SimulationConfig::new(7).max_delay(8);
+    assert_eq!(run(config.clone()), run(config));
+}
+
+// Dropped messages are counted, and a computation that loses them does not complete.
+#[test]
+fn simulation_drops_messages() {
+    let (mut cluster, mut handles) = timely::execute_simulated(2, SimulationConfig::new(0).drop_rate(1.0), |worker| {
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x: &u64| *x)
+                 .probe()
+        });
+        (input, probe)
+    });
+    for (input, _) in handles.iter_mut() {
+        input.send(0);
+        input.send(1);
+        input.advance_to(1);
+    }
+    for _ in 0 .. 100 {
+        cluster.tick();
+    }
+    assert!(cluster.simulation().dropped() > 0);
+    assert!(handles.iter().all(|(_, probe)| probe.less_than(&1)));
+}
@@ -0,0 +1,98 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::communication::allocator::Generic;
+use timely::dataflow::Stream;
+use timely::dataflow::scopes::Child;
+use timely::dataflow::operators::{Delay, Inspect, ToStream};
+use timely::dataflow::operators::sketch::{CountMin, HyperLogLog, Merge, Quantiles, Sketch};
+use timely::worker::Worker;
+
+// Runs `logic` on three workers, each sending its records of 0 .. 3000 at times `x / 1000`, and
+// collects the (worker, time, output) triples of the stream it returns.
+fn sketched<D, F>(logic: F) -> Vec<(usize, u64, D)>
+where
+    D: timely::Data+Send,
+    F: for<'a> Fn(Stream<Child<'a, Worker<Generic>, u64>, u64>)->Stream<Child<'a, Worker<Generic>, u64>, D>+Send+Sync+'static,
+{
+    let outputs = Arc::new(Mutex::new(Vec::new()));
+    let seen = outputs.clone();
+    timely::execute(timely::Config::process(3), move |worker| {
+        let (index, seen) = (worker.index(), seen.clone());
+        worker.dataflow::<u64,_,_>(|scope| {
+            let records = (0 .. 3000u64).filter(move |x| *x as usize % 3 == index)
+                                        .to_stream(scope)
+                                        .delay(|x, _| x / 1000);
+            logic(records).inspect_time(move |time, output| seen.lock().unwrap().push((index, *time, output.clone())));
+        });
+    }).unwrap();
+    let mut outputs = outputs.lock().unwrap().clone();
+    outputs.sort_by_key(|(worker, time, _)| (*worker, *time));
+    outputs
+}
+
+// Sketches of all workers are merged, and produced by the first worker once for each time.
+#[test]
+fn merged_on_first_worker() {
+    let estimates = sketched(|records| records.approximate_distinct(12));
+    assert_eq!(estimates.iter().map(|(worker, time, _)| (*worker, *time)).collect::<Vec<_>>(), vec![(0, 0), (0, 1), (0, 2)]);
+    for (_, _, estimate) in estimates.iter() {
+        assert!(950 <= *estimate && *estimate <= 1050, "estimate {} of 1000 distinct records", estimate);
+    }
+}
+
+// Count-min estimates are at least the true counts, with merged totals.
+#[test]
+fn count_min_across_workers() {
+    let sketches = sketched(|records| records.count_min_sketch(256, 4));
+    assert_eq!(sketches.len(), 3);
+    for (_, time, sketch) in sketches.iter() {
+        assert_eq!(sketch.total(), 1000);
+        assert!(sketch.estimate(&(1000 * time + 7)) >= 1);
+        assert!(sketch.estimate(&"absent") < 50);
+    }
+}
+
+// Quantile sketches merge the records of all workers.
+#[test]
+fn quantiles_across_workers() {
+    let sketches = sketched(|records| records.quantile_sketch(64));
+    assert_eq!(sketches.len(), 3);
+    for (_, time, sketch) in sketches.iter() {
+        assert_eq!(sketch.count(), 1000);
+        let median = sketch.quantile(0.5).unwrap();
+        assert!(1000 * time + 400 <= median && median <= 1000 * time + 600, "median {} at {}", median, time);
+    }
+}
+
+// Merging sketches of disjoint records summarizes their union.
+#[test]
+fn merge_sketches() {
+    let (mut distinct, mut other) = (HyperLogLog::new(10), HyperLogLog::new(10));
+    let (mut counts, mut other_counts) = (CountMin::new(128, 3), CountMin::new(128, 3));
+    let (mut quantiles, mut other_quantiles) = (Quantiles::new(32), Quantiles::new(32));
+    for x in 0 .. 500u64 {
+        distinct.insert(&x);
+        other.insert(&(x + 500));
+        counts.insert(&(x % 5));
+        other_counts.add(&(x % 5), 2);
+        quantiles.insert(x);
+        other_quantiles.insert(x + 500);
+    }
+    distinct.merge(&other);
+    counts.merge(&other_counts);
+    quantiles.merge(&other_quantiles);
+    assert!((900 ..= 1100).contains(&distinct.estimate()));
+    assert!(counts.estimate(&3u64) >= 300);
+    assert_eq!(counts.total(), 1500);
+    assert_eq!(quantiles.count(), 1000);
+    assert!((400 ..= 600).contains(&quantiles.quantile(0.5).unwrap()));
+}
+
+// Sketches of different shapes do not merge.
+#[test]
+#[should_panic(expected = "equal precisions")]
+fn merge_requires_equal_shapes() {
+    HyperLogLog::new(8).merge(&HyperLogLog::new(9));
+}
@@ -0,0 +1,127 @@
+extern crate timely;
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::operators::{Exchange, Inspect, Probe};
+use timely::dataflow::operators::socket::{Codec, LengthPrefixed, Lines, SocketSink, SocketSource};
+use timely::Config;
+
+// The frontier of a source follows the times of its records while the connection is open, and
+// empties once it closes.
+#[test]
+fn frontier_follows_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+
+    let (mut coop, probe) = timely::execute_cooperatively(move |worker| {
+        worker.dataflow::<u64,_,_>(|scope| {
+            scope.socket_source("Times", server, Lines, |line| line.parse().unwrap()).probe()
+        })
+    });
+
+    for time in [3u64, 5, 8] {
+        client.write_all(format!("{}\n", time).as_bytes()).unwrap();
+        while probe.less_than(&time) {
+            coop.worker().step_or_park(None);
+        }
+        assert!(probe.less_equal(&time));
+        assert!(!probe.done());
+    }
+
+    drop(client);
+    while !probe.done() {
+        coop.worker().step_or_park(None);
+    }
+}
+
+// Each worker reads and writes its own connection, with records exchanged between workers.
+#[test]
+fn workers_exchange_between_connections() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let mut clients = Vec::new();
+    let mut servers = Vec::new();
+    for client in 0 .. 3u8 {
+        let mut stream = TcpStream::connect(address).unwrap();
+        // Each client sends the records ten times its index plus 0 through 9.
+        for record in 0 .. 10u8 {
+            stream.write_all(&1u32.to_be_bytes()).unwrap();
+            stream.write_all(&[10 * client + record]).unwrap();
+        }
+        stream.shutdown(std::net::Shutdown::Write).unwrap();
+        clients.push(stream);
+        servers.push(Some(listener.accept().unwrap().0));
+    }
+    let servers = Arc::new(Mutex::new(servers));
+
+    timely::execute(Config::process(3), move |worker| {
+        let server = servers.lock().unwrap()[worker.index()].take().unwrap();
+        let writer = server.try_clone().unwrap();
+        let sink = worker.dataflow::<u64,_,_>(|scope| {
+            scope.socket_source("Frames", server, LengthPrefixed::new(), |_| 0)
+                 .exchange(|bytes| bytes[0] as u64)
+                 .socket_sink("Frames", writer, LengthPrefixed::new())
+        });
+        while worker.step_or_park(None) { }
+        sink.join().unwrap().unwrap();
+    }).unwrap();
+
+    let mut received = Vec::new();
+    for mut client in clients {
+        let mut bytes = Vec::new();
+        client.read_to_end(&mut bytes).unwrap();
+        for frame in bytes.chunks(5) {
+            assert_eq!(&frame[.. 4], &1u32.to_be_bytes());
+            received.push(frame[4]);
+        }
+    }
+    received.sort();
+    assert_eq!(received, (0 .. 30).collect::<Vec<_>>());
+}
+
+// A custom framing: records of eight bytes, read as little-endian integers.
+struct Fixed;
+
+impl Codec for Fixed {
+    type Item = u64;
+    fn decode(&mut self, bytes: &[u8]) -> std::io::Result<Option<(u64, usize)>> {
+        if bytes.len() < 8 {
+            return Ok(None);
+        }
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&bytes[.. 8]);
+        Ok(Some((u64::from_le_bytes(word), 8)))
+    }
+    fn encode(&mut self, item: &u64, bytes: &mut Vec<u8>) -> std::io::Result<()> {
+        bytes.extend_from_slice(&item.to_le_bytes());
+        Ok(())
+    }
+}
+
+#[test]
+fn custom_codec() {
+    let mut bytes = Vec::new();
+    for record in 0 .. 1000u64 {
+        Fixed.encode(&record, &mut bytes).unwrap();
+    }
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let seen2 = seen.clone();
+    timely::example(move |scope| {
+        scope.socket_source("Fixed", std::io::Cursor::new(bytes), Fixed, |record| record / 100)
+             .inspect_time(move |time, record| seen2.lock().unwrap().push((*time, *record)));
+    });
+    let expected = (0 .. 1000u64).map(|record| (record / 100, record)).collect::<Vec<_>>();
+    assert_eq!(*seen.lock().unwrap(), expected);
+}
+
+#[test]
+fn codec_errors() {
+    assert_eq!(LengthPrefixed::with_max_length(2).decode(&[0, 0, 0, 3]).unwrap_err().kind(), ErrorKind::InvalidData);
+    assert_eq!(LengthPrefixed::new().decode_eof(&[0, 0, 0, 3, 1]).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+    assert_eq!(Lines.decode(b"a\r\nb").unwrap(), Some(("a".to_string(), 3)));
+    assert_eq!(Lines.decode_eof(b"b").unwrap(), Some(("b".to_string(), 1)));
+    assert_eq!(Lines.decode(&[0xff, b'\n']).unwrap_err().kind(), ErrorKind::InvalidData);
+}
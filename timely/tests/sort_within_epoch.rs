@@ -0,0 +1,56 @@
+extern crate timely;
+extern crate rand;
+
+use rand::{Rng, SeedableRng, StdRng};
+
+use timely::dataflow::operators::{ToStream, Delay, Capture};
+use timely::dataflow::operators::capture::Event;
+use timely::dataflow::operators::sort::{MergeSorter, SortWithinEpoch};
+
+// The sorter produces the records of many batches in stable sorted order.
+#[test]
+fn merge_sorter_sorts_stably() {
+    let seed: &[_] = &[1, 2, 3, 4];
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+    let mut sorter = MergeSorter::new();
+    let mut expected = Vec::new();
+    for round in 0 .. 3 {
+        let mut index = 0;
+        for _ in 0 .. 50 {
+            let length = rng.gen_range(0, 40);
+            let mut batch = (0 .. length).map(|_| { index += 1; (rng.gen_range(0, 20u32), index) }).collect::<Vec<_>>();
+            expected.extend(batch.iter().cloned());
+            sorter.push(&mut batch, &|x: &(u32, usize)| x.0);
+            assert!(batch.is_empty());
+        }
+        expected.sort_by_key(|x| x.0);
+        let mut sorted = Vec::new();
+        sorter.finish_into(&mut sorted, &|x: &(u32, usize)| x.0);
+        assert!(sorter.is_empty());
+        assert_eq!(sorted, expected, "in round {}", round);
+        expected.clear();
+    }
+}
+
+// Each time's records are produced once, in sorted order.
+#[test]
+fn sort_within_epoch_sorts_each_time() {
+    let captured = timely::example(|scope| {
+        (0 .. 100u64).rev()
+                     .to_stream(scope)
+                     .delay(|x, _| x % 3)
+                     .sort_within_epoch(|x| *x)
+                     .capture()
+    });
+    // The captured messages are inspected directly, as `extract` would sort them.
+    let mut times = Vec::new();
+    for event in captured.try_iter() {
+        if let Event::Messages(time, data) = event {
+            let expected = (0 .. 100u64).filter(|x| x % 3 == time).collect::<Vec<_>>();
+            assert_eq!(data, expected);
+            times.push(time);
+        }
+    }
+    times.sort();
+    assert_eq!(times, vec![0, 1, 2]);
+}
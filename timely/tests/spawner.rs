@@ -0,0 +1,83 @@
+extern crate timely;
+
+use std::sync::mpsc;
+
+use timely::communication::WorkerTask;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Probe};
+use timely::Config;
+
+// Workers run on threads the caller started before the computation, which wait for tasks.
+#[test]
+fn workers_run_on_caller_threads() {
+    let mut senders = Vec::new();
+    let mut threads = Vec::new();
+    for _ in 0 .. 3 {
+        let (send, recv) = mpsc::channel::<WorkerTask>();
+        senders.push(send);
+        threads.push(std::thread::spawn(move || {
+            for task in recv {
+                task();
+            }
+        }));
+    }
+
+    let guards = timely::execute_with_spawner(Config::process(3), |index, task| {
+        senders[index].send(task).map_err(|e| e.to_string())
+    }, |worker| {
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x: &u64| *x)
+                 .probe()
+        });
+        for round in 0 .. 10u64 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+        std::thread::current().id()
+    }).unwrap();
+
+    let ids = guards.join().into_iter().map(|result| result.unwrap()).collect::<Vec<_>>();
+    let expected = threads.iter().map(|thread| thread.thread().id()).collect::<Vec<_>>();
+    assert_eq!(ids, expected);
+
+    drop(senders);
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+// A worker that panics produces an error, rather than a panic, when the guards are joined.
+#[test]
+fn panics_are_reported() {
+    let guards = timely::execute_with_spawner(Config::process(2), |_index, task| {
+        std::thread::spawn(task);
+        Ok(())
+    }, |worker| {
+        if worker.index() == 1 {
+            panic!("worker 1 fails");
+        }
+        worker.index()
+    }).unwrap();
+
+    let results = guards.join();
+    assert_eq!(results[0], Ok(0));
+    assert!(results[1].is_err());
+}
+
+// Errors from the spawner are returned, and tasks dropped without running produce errors.
+#[test]
+fn spawner_errors() {
+    let result = timely::execute_with_spawner(Config::thread(), |_index, _task| {
+        Err("no threads".to_owned())
+    }, |worker| worker.index());
+    assert_eq!(result.err(), Some("no threads".to_owned()));
+
+    let guards = timely::execute_with_spawner(Config::thread(), |_index, task| {
+        drop(task);
+        Ok(())
+    }, |worker| worker.index()).unwrap();
+    assert!(guards.join()[0].is_err());
+}
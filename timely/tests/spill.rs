@@ -0,0 +1,53 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::operators::{ToStream, Map, Delay, Capture};
+use timely::dataflow::operators::aggregation::Aggregate;
+use timely::dataflow::operators::capture::Extract;
+use timely::dataflow::operators::epoch_buffer::SpillConfig;
+use timely::{CommunicationConfig, Config, WorkerConfig};
+
+type Results = (Vec<(u64, Vec<u64>)>, Vec<(u64, Vec<(u64, u64)>)>);
+
+// Delays and aggregates records, with `config`, returning the captured outputs.
+fn run(config: WorkerConfig) -> Results {
+    let results = Arc::new(Mutex::new((Vec::new(), Vec::new())));
+    let results2 = results.clone();
+    let config = Config { communication: CommunicationConfig::Process(2), worker: config };
+    timely::execute(config, move |worker| {
+        let index = worker.index() as u64;
+        let (delayed, aggregated) = worker.dataflow::<u64,_,_>(|scope| {
+            let records = (0 .. 10_000u64).filter(move |x| x % 2 == index).to_stream(scope);
+            let delayed = records.delay_spilling(|x, _| x % 7).capture();
+            let aggregated = records
+                .delay_spilling(|x, _| x % 5)
+                .map(|x| (x % 13, x))
+                .aggregate(|_key, val, agg| { *agg += val; }, |key, agg: u64| (key, agg), |key| *key)
+                .capture();
+            (delayed, aggregated)
+        });
+        while worker.step() { }
+        let mut results = results2.lock().unwrap();
+        results.0.extend(delayed.extract());
+        results.1.extend(aggregated.extract());
+    }).unwrap();
+
+    let mut results = results.lock().unwrap().clone();
+    for (_, data) in results.0.iter_mut() { data.sort(); }
+    for (_, data) in results.1.iter_mut() { data.sort(); }
+    results.0.sort();
+    results.1.sort();
+    results
+}
+
+#[test]
+fn spilling_matches_memory() {
+    let directory = std::env::temp_dir().join(format!("timely-spill-test-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let spilled = run(WorkerConfig::default().spill_to_disk(SpillConfig::new(1024, &directory)));
+    assert_eq!(spilled, run(WorkerConfig::default()));
+    // Spilled records are read back, and their files removed.
+    assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 0);
+    std::fs::remove_dir(&directory).unwrap();
+}
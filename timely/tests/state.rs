@@ -0,0 +1,192 @@
+extern crate timely;
+
+use std::collections::HashMap;
+
+use timely::checkpoint::MemoryStore;
+use timely::dataflow::{InputHandle, ProbeHandle, Scope, Stream};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Input, Probe};
+use timely::dataflow::operators::generic::operator::Operator;
+use timely::progress::Antichain;
+use timely::state::OperatorState;
+
+// Counts records by key, applying each time once complete, and dropping keys below the frontier.
+fn count<G: Scope<Timestamp=u64>>(stream: &Stream<G, u64>) -> Stream<G, (u64, u64)> {
+    let scope = stream.scope();
+    stream.unary_frontier(Pipeline, "Count", |_capability, info| {
+        let mut state = OperatorState::new().checkpointed(&scope, "counts", &info.address).expect("failed to restore state");
+        state.on_frontier(|frontier, backend| {
+            use timely::state::Backend;
+            let stale = backend.range(std::ops::Bound::Unbounded, std::ops::Bound::Unbounded)
+                               .map(|(key, _)| key)
+                               .filter(|key: &u64| frontier.iter().all(|time| *key + 3 < *time))
+                               .collect::<Vec<_>>();
+            for key in stale { backend.remove(&key); }
+        });
+        let mut stash = HashMap::new();
+        move |input, output| {
+            input.for_each(|time, data| {
+                stash.entry(*time.time()).or_insert((time.retain(), Vec::new())).1.append(&mut data.replace(Vec::new()));
+            });
+            let mut complete = stash.keys().filter(|t| !input.frontier().less_equal(t)).cloned().collect::<Vec<_>>();
+            complete.sort();
+            for time in complete {
+                state.advance_to(&[time]).expect("failed to save state");
+                let (capability, records) = stash.remove(&time).unwrap();
+                let mut session = output.session(&capability);
+                for key in records {
+                    let count = state.get(&key).unwrap_or(0) + 1;
+                    state.put(key, count);
+                    session.give((key, count));
+                }
+            }
+            state.advance_to(&input.frontier().frontier()).expect("failed to save state");
+        }
+    })
+}
+
+#[test]
+fn keyed_state_checkpoint_and_compaction() {
+    let store = MemoryStore::default();
+    let mut config = timely::Config::thread();
+    config.worker = config.worker.checkpoint_store(store.clone());
+
+    timely::execute(config, move |worker| {
+        let mut input = InputHandle::new();
+        let dataflow = worker.next_dataflow_index();
+        let probe = worker.dataflow(|scope| count(&scope.input_from(&mut input)).probe());
+        let checkpoint = worker.checkpoint(dataflow, Antichain::from_elem(5u64));
+        for round in 0 .. 8u64 {
+            input.send(round);
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+        assert!(worker.checkpoint_committed(checkpoint));
+        let saved: Vec<(u64, u64)> = timely::checkpoint::load(&store, checkpoint, 0, "counts").unwrap().unwrap();
+        // Keys more than three behind the checkpoint frontier were compacted away.
+        assert_eq!(saved, vec![(1, 2), (2, 2), (3, 2), (4, 2)]);
+        worker.drop_dataflow(dataflow);
+
+        let mut input = InputHandle::new();
+        let mut probe = ProbeHandle::new();
+        let output = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let output2 = output.clone();
+        worker.dataflow_restored(checkpoint, |scope| {
+            use timely::dataflow::operators::Inspect;
+            count(&scope.input_from(&mut input))
+                .inspect(move |x| output2.borrow_mut().push(*x))
+                .probe_with(&mut probe);
+        });
+        input.send(4);
+        input.advance_to(6);
+        worker.step_while(|| probe.less_than(input.time()));
+        assert_eq!(*output.borrow(), vec![(4, 3)]);
+    }).unwrap();
+}
+
+// Timers fire once the frontier passes their times, replaced and canceled timers do not fire,
+// and output may be produced at the time of each fired timer.
+#[test]
+fn keyed_timers_fire_as_the_frontier_passes() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use timely::dataflow::operators::{CapabilitySet, Inspect};
+
+    timely::execute(timely::Config::thread(), |worker| {
+        let mut input = InputHandle::new();
+        let fired = Rc::new(RefCell::new(Vec::new()));
+        let fired2 = fired.clone();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .unary_frontier(Pipeline, "Timeouts", |capability, _info| {
+                     let mut capabilities = CapabilitySet::from_elem(capability);
+                     let mut state = OperatorState::<u64, u64, ()>::new();
+                     move |input, output| {
+                         input.for_each(|time, data| {
+                             for &(key, delay) in data.iter() {
+                                 // A delay of zero cancels the key's timer.
+                                 if delay == 0 { state.cancel_timer(&key); }
+                                 else { state.set_timer(key, time.time() + delay); }
+                             }
+                         });
+                         state.advance_to(&input.frontier().frontier()).expect("failed to save state");
+                         for (key, time) in state.fired_timers() {
+                             output.session(&capabilities.delayed(&time)).give((key, time));
+                         }
+                         let frontier = input.frontier().frontier().to_vec();
+                         capabilities.downgrade(frontier.iter().chain(state.timer_times()));
+                     }
+                 })
+                 .inspect_batch(move |time, data| {
+                     for &(key, timer) in data.iter() {
+                         assert_eq!(*time, timer);
+                         fired2.borrow_mut().push((key, timer));
+                     }
+                 })
+                 .probe()
+        });
+
+        input.send((1, 3));
+        input.send((2, 5));
+        input.send((3, 2));
+        input.advance_to(1);
+        // Key 2's timer moves from 5 to 4, and key 3's timer is canceled.
+        input.send((2, 3));
+        input.send((3, 0));
+        input.advance_to(3);
+        worker.step_while(|| probe.less_than(input.time()));
+        assert!(fired.borrow().is_empty());
+
+        input.advance_to(4);
+        worker.step_while(|| probe.less_than(input.time()));
+        assert_eq!(*fired.borrow(), vec![(1, 3)]);
+
+        input.close();
+        worker.step_while(|| !probe.done());
+        assert_eq!(*fired.borrow(), vec![(1, 3), (2, 4)]);
+    }).unwrap();
+}
+
+// A store that keeps checkpoints in memory, relying on the default `discard`.
+#[derive(Debug, Default)]
+struct UndiscardingStore(MemoryStore);
+
+impl timely::checkpoint::Store for UndiscardingStore {
+    fn put(&self, checkpoint: u64, worker: usize, name: &str, bytes: Vec<u8>) -> std::io::Result<()> {
+        self.0.put(checkpoint, worker, name, bytes)
+    }
+    fn get(&self, checkpoint: u64, worker: usize, name: &str) -> std::io::Result<Option<Vec<u8>>> {
+        self.0.get(checkpoint, worker, name)
+    }
+    fn commit(&self, checkpoint: u64, worker: usize) -> std::io::Result<()> {
+        self.0.commit(checkpoint, worker)
+    }
+    fn is_committed(&self, checkpoint: u64, worker: usize) -> std::io::Result<bool> {
+        self.0.is_committed(checkpoint, worker)
+    }
+}
+
+// A checkpoint at the empty frontier reflects all input, and restores its inputs closed.
+#[test]
+fn restored_from_empty_frontier() {
+    let mut config = timely::Config::thread();
+    config.worker = config.worker.checkpoint_store(UndiscardingStore::default());
+
+    timely::execute(config, move |worker| {
+        let mut input = InputHandle::new();
+        let dataflow = worker.next_dataflow_index();
+        let probe = worker.dataflow(|scope| count(&scope.input_from(&mut input)).probe());
+        let checkpoint = worker.checkpoint(dataflow, Antichain::<u64>::new());
+        input.send(1);
+        input.send(1);
+        drop(input);
+        worker.step_while(|| !probe.done());
+        assert!(worker.checkpoint_committed(checkpoint));
+        worker.drop_dataflow(dataflow);
+
+        let mut input = InputHandle::<u64, u64>::new();
+        let probe = worker.dataflow_restored(checkpoint, |scope| count(&scope.input_from(&mut input)).probe());
+        worker.step_while(|| !probe.done());
+    }).unwrap();
+}
@@ -0,0 +1,119 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use timely::Config;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Map, Probe};
+use timely::dataflow::operators::capture::{Capture, Extract};
+
+// Each record is processed once, at its time, by some worker.
+#[test]
+fn records_processed_once() {
+    let (send, recv) = std::sync::mpsc::channel();
+    let send = Arc::new(Mutex::new(send));
+    timely::execute(Config::process(4), move |worker| {
+        let send = send.lock().unwrap().clone();
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            let stream = scope.input_from(&mut input).steal().map(|x: u64| x * 2);
+            stream.capture_into(send);
+            stream.probe()
+        });
+        for round in 0 .. 5u64 {
+            if worker.index() == 0 {
+                for x in 0 .. 5000 { input.send(round * 10_000 + x); }
+            }
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(&(round + 1)));
+        }
+    }).unwrap();
+
+    let mut captured = recv.extract();
+    for (_time, data) in captured.iter_mut() { data.sort(); }
+    let expected = (0 .. 5u64).map(|round| (round, (0 .. 5000).map(|x| (round * 10_000 + x) * 2).collect::<Vec<_>>())).collect::<Vec<_>>();
+    assert_eq!(captured, expected);
+}
+
+// Batches produced by one worker are processed by others that are free to take them.
+#[test]
+fn skewed_input_is_shared() {
+    let processed = Arc::new(Mutex::new(vec![0; 4]));
+    let shared = processed.clone();
+    let mut config = Config::process(4);
+    config.worker = config.worker.batch_size(16);
+    timely::execute(config, move |worker| {
+        let index = worker.index();
+        let processed = shared.clone();
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .steal()
+                 .inspect_batch(move |_time, data| {
+                     std::thread::sleep(Duration::from_millis(2));
+                     processed.lock().unwrap()[index] += data.len();
+                 })
+                 .probe()
+        });
+        if index == 0 {
+            for x in 0 .. 1600u64 { input.send(x); }
+        }
+        input.advance_to(1);
+        worker.step_while(|| probe.less_than(&1));
+    }).unwrap();
+
+    let processed = processed.lock().unwrap();
+    assert_eq!(processed.iter().sum::<usize>(), 1600);
+    assert!(processed.iter().filter(|count| **count > 0).count() > 1, "records processed only by one worker: {:?}", processed);
+}
+
+// Stealing also moves records in single-worker computations, which process them all.
+#[test]
+fn single_worker() {
+    let captured = timely::example(|scope| {
+        use timely::dataflow::operators::ToStream;
+        (0 .. 10_000u64).to_stream(scope).steal().map(|x| x + 1).capture()
+    });
+    let mut data = captured.extract().into_iter().flat_map(|(_time, data)| data).collect::<Vec<_>>();
+    data.sort();
+    assert_eq!(data, (1 .. 10_001).collect::<Vec<_>>());
+}
+
+// Computations started from clones of one configuration do not take each other's records.
+#[test]
+fn cloned_configs_are_separate() {
+    let worker_config = Config::process(2).worker;
+    let (send, recv) = std::sync::mpsc::channel();
+    // Both computations build their dataflows before either sends records.
+    let built = Arc::new(std::sync::Barrier::new(4));
+    let computations = (0 .. 2u64).map(|computation| {
+        let config = Config { worker: worker_config.clone(), ..Config::process(2) };
+        let send = Arc::new(Mutex::new(send.clone()));
+        let built = built.clone();
+        std::thread::spawn(move || {
+            timely::execute(config, move |worker| {
+                let send = send.lock().unwrap().clone();
+                let mut input = InputHandle::new();
+                let probe = worker.dataflow::<u64,_,_>(|scope| {
+                    let stream = scope.input_from(&mut input).steal();
+                    stream.capture_into(send);
+                    stream.probe()
+                });
+                built.wait();
+                for round in 0 .. 5u64 {
+                    for x in 0 .. 1000 { input.send((computation, x)); }
+                    input.advance_to(round + 1);
+                    worker.step_while(|| probe.less_than(&(round + 1)));
+                }
+            }).unwrap();
+        })
+    }).collect::<Vec<_>>();
+    drop(send);
+    for computation in computations { computation.join().unwrap(); }
+
+    let records = recv.extract().into_iter().flat_map(|(_time, data)| data).collect::<Vec<_>>();
+    for computation in 0 .. 2 {
+        assert_eq!(records.iter().filter(|(c, _)| *c == computation).count(), 2 * 5 * 1000);
+    }
+}
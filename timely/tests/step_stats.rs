@@ -0,0 +1,89 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Map, Probe};
+use timely::logging::ScheduleSummaryEvent;
+
+#[test]
+fn parked_time_is_measured_apart_from_phases() {
+    timely::execute(timely::Config::thread(), |worker| {
+        // Phases are timed only while their summaries are logged.
+        worker.log_register().insert::<ScheduleSummaryEvent,_>("timely/schedule", |_time, _data| { });
+        let mut input = InputHandle::<u64, u64>::new();
+        worker.dataflow(|scope| {
+            scope.input_from(&mut input).map(|x| x + 1).probe();
+        });
+        // Settle the dataflow, which then awaits input that will not arrive.
+        for _ in 0 .. 10 {
+            worker.step();
+        }
+        let before = worker.step_stats();
+        worker.step_or_park(Some(Duration::from_millis(50)));
+        let after = worker.step_stats();
+
+        assert_eq!(after.steps, before.steps + 1);
+        assert_eq!(after.parks, before.parks + 1);
+        assert!(after.parked > before.parked);
+        assert_eq!(after.phases.dataflows, before.phases.dataflows);
+        assert!(after.longest >= before.longest);
+    }).unwrap();
+}
+
+#[test]
+fn summaries_add_up_to_the_totals() {
+    let mut config = timely::Config::thread();
+    config.worker = config.worker.schedule_summary(Duration::from_millis(1));
+    timely::execute(config, |worker| {
+        let summaries = Rc::new(RefCell::new(Vec::new()));
+        let sink = summaries.clone();
+        worker.log_register().insert::<ScheduleSummaryEvent,_>("timely/schedule", move |_time, data| {
+            sink.borrow_mut().extend(data.drain(..).map(|(_, _, event)| event));
+        });
+
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input).map(|x: u64| x + 1).probe()
+        });
+        for round in 0 .. 10 {
+            input.send(round);
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        worker.step();
+
+        let totals = worker.step_stats();
+        let summaries = summaries.borrow();
+        assert!(summaries.len() > 1);
+        // The steps since the last summary are yet to be reported.
+        let steps: u64 = summaries.iter().map(|summary| summary.steps).sum();
+        let dataflows: Duration = summaries.iter().map(|summary| summary.dataflows).sum();
+        assert!(steps <= totals.steps);
+        assert!(dataflows <= totals.phases.dataflows);
+        assert!(summaries.iter().all(|summary| summary.longest <= totals.longest));
+    }).unwrap();
+}
+
+#[test]
+fn phases_are_untimed_without_a_logger() {
+    timely::execute(timely::Config::thread(), |worker| {
+        let mut input = InputHandle::<u64, u64>::new();
+        worker.dataflow(|scope| {
+            scope.input_from(&mut input).map(|x| x + 1).probe();
+        });
+        for _ in 0 .. 10 {
+            worker.step();
+        }
+        worker.step_or_park(Some(Duration::from_millis(50)));
+
+        let stats = worker.step_stats();
+        assert_eq!(stats.steps, 11);
+        assert_eq!(stats.parks, 1);
+        assert_eq!(stats.parked, Duration::from_secs(0));
+        assert_eq!(stats.phases.total(), Duration::from_secs(0));
+    }).unwrap();
+}
@@ -0,0 +1,50 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc;
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Probe};
+use timely::dataflow::operators::capture::Event;
+use timely::dataflow::operators::tap::Tap;
+
+// A listener attached late learns the current frontier, and sees the records that follow.
+#[test]
+fn tap_attach_and_detach() {
+    timely::execute(timely::Config::thread(), |worker| {
+        let mut input = InputHandle::new();
+        let (tap, probe) = worker.dataflow::<u64,_,_>(|scope| {
+            let stream = scope.input_from(&mut input);
+            (stream.tee_handle(), stream.probe())
+        });
+
+        for round in 0 .. 3 {
+            input.send(round);
+            input.advance_to(round + 1);
+        }
+        worker.step_while(|| probe.less_than(input.time()));
+
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        let seen2 = seen.clone();
+        let inspector = tap.attach_inspect(move |time, data| seen2.borrow_mut().extend(data.iter().map(|x| (*time, *x))));
+        let (send, recv) = mpsc::channel();
+        tap.attach(send);
+        assert_eq!(tap.listeners(), 2);
+        assert_eq!(recv.try_recv().unwrap(), Event::Progress(vec![(0, -1), (3, 1)]));
+
+        input.send(3);
+        input.advance_to(4);
+        worker.step_while(|| probe.less_than(input.time()));
+        assert!(tap.detach(inspector));
+        assert!(!tap.detach(inspector));
+
+        input.send(4);
+        input.close();
+        worker.step_while(|| !probe.done());
+
+        assert_eq!(*seen.borrow(), vec![(3, 3)]);
+        let events = recv.try_iter().collect::<Vec<_>>();
+        assert_eq!(events.iter().filter_map(|e| if let Event::Messages(t, d) = e { Some((*t, d.clone())) } else { None }).collect::<Vec<_>>(), vec![(3, vec![3]), (4, vec![4])]);
+    }).unwrap();
+}
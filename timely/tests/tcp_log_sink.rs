@@ -0,0 +1,38 @@
+extern crate timely;
+
+use std::io::Read;
+use std::net::TcpListener;
+use std::time::Duration;
+
+use timely::dataflow::operators::capture::{Event, EventPusher};
+use timely::logging::TcpSink;
+
+// The collector goes away and returns, and the sink reconnects without blocking its pusher.
+#[test]
+fn tcp_log_sink_reconnects() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut sink = TcpSink::<Duration, u64>::connect(listener.local_addr().unwrap(), 16);
+
+    sink.push(Event::Messages(Duration::default(), vec![0, 1, 2]));
+    sink.push(Event::Progress(vec![(Duration::from_secs(1), 1), (Duration::default(), -1)]));
+    let (mut first, _) = listener.accept().unwrap();
+    let mut buffer = [0u8; 1024];
+    assert!(first.read(&mut buffer).unwrap() > 0);
+    drop(first);
+
+    listener.set_nonblocking(true).unwrap();
+    let mut second = None;
+    for round in 0 .. 1000u64 {
+        sink.push(Event::Messages(Duration::from_secs(1), vec![round]));
+        if let Ok((stream, _)) = listener.accept() {
+            second = Some(stream);
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let mut second = second.expect("sink did not reconnect");
+    second.set_nonblocking(false).unwrap();
+    second.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    assert!(second.read(&mut buffer).unwrap() > 0);
+}
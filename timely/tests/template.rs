@@ -0,0 +1,101 @@
+extern crate timely;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use timely::dataflow::{InputHandle, ProbeHandle};
+use timely::dataflow::operators::{ConnectLoop, Concat, Feedback, Filter, Input, Inspect, Map, Probe};
+use timely::dataflow::template::Template;
+
+// Many instances of a looping dataflow, each with its own parameters, produce their own results.
+#[test]
+fn instances_with_parameters() {
+    timely::execute_directly(|worker| {
+        let results = Rc::new(RefCell::new(Vec::new()));
+        let shared = results.clone();
+        // Each instance repeatedly adds its step to records, until they reach its limit.
+        let template = Template::new("Count", move |(key, step, limit): (usize, u64, u64), scope| {
+            let mut input = InputHandle::<u64, u64>::new();
+            let (handle, cycle) = scope.feedback(1);
+            let results = shared.clone();
+            scope.input_from(&mut input)
+                 .concat(&cycle)
+                 .map(move |x| x + step)
+                 .inspect(move |x| if *x >= limit { results.borrow_mut().push((key, *x)); })
+                 .filter(move |x| *x < limit)
+                 .connect_loop(handle);
+            input
+        });
+
+        let mut inputs = (0 .. 100).map(|key| worker.instantiate(&template, (key, 1 + key as u64 % 7, 50))).collect::<Vec<_>>();
+        assert_eq!(template.reused(), 99);
+        assert_eq!(worker.installed_dataflows().len(), 100);
+
+        for input in inputs.iter_mut() {
+            input.send(0);
+        }
+        drop(inputs);
+        while !worker.installed_dataflows().is_empty() {
+            worker.step();
+        }
+
+        let mut results = results.borrow().clone();
+        results.sort();
+        let expected = (0 .. 100).map(|key| {
+            let step = 1 + key as u64 % 7;
+            (key, 50u64.div_ceil(step) * step)
+        }).collect::<Vec<_>>();
+        assert_eq!(results, expected);
+    });
+}
+
+// Parameters that change the topology of an instance compile its summaries anew.
+#[test]
+fn parameters_changing_topology() {
+    timely::execute_directly(|worker| {
+        let template = Template::new("Maybe", |extra: bool, scope| {
+            let mut input = InputHandle::<u64, u64>::new();
+            let mut probe = ProbeHandle::new();
+            let stream = scope.input_from(&mut input);
+            let stream = if extra { stream.map(|x| x + 1) } else { stream };
+            stream.probe_with(&mut probe);
+            (input, probe)
+        });
+
+        let mut instances = Vec::new();
+        for extra in [false, false, true, true, false] {
+            instances.push(worker.instantiate(&template, extra));
+        }
+        assert_eq!(template.reused(), 2);
+
+        for (input, _probe) in instances.iter_mut() {
+            input.send(3);
+            input.advance_to(5);
+        }
+        worker.step_while(|| instances.iter().any(|(_input, probe)| probe.less_than(&5)));
+        assert!(instances.iter().all(|(_input, probe)| !probe.less_than(&5) && probe.less_equal(&5)));
+    });
+}
+
+// Instances are distinct dataflows, which may be dropped without affecting one another.
+#[test]
+fn instances_dropped_individually() {
+    timely::execute_directly(|worker| {
+        let template = Template::new("Probe", |_: (), scope| {
+            let mut input = InputHandle::<u64, u64>::new();
+            let probe = scope.input_from(&mut input).probe();
+            (input, probe)
+        });
+        let first = worker.next_dataflow_index();
+        let (mut input1, _probe1) = worker.instantiate(&template, ());
+        let (mut input2, probe2) = worker.instantiate(&template.clone(), ());
+        assert_eq!(template.name(), "Probe");
+        assert_eq!(worker.operator_name(&[first]), Some("Probe".to_owned()));
+
+        worker.drop_dataflow(first);
+        input1.advance_to(3);
+        input2.advance_to(3);
+        worker.step_while(|| probe2.less_than(&3));
+        assert_eq!(worker.installed_dataflows(), vec![first + 1]);
+    });
+}
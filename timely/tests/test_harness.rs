@@ -0,0 +1,155 @@
+extern crate timely;
+
+use std::collections::BTreeMap;
+
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Accumulate, Concat, Concatenate, Delay, Exchange, Filter, Map, Operator, Partition};
+use timely::dataflow::operators::aggregation::Aggregate;
+use timely::dataflow::operators::distinct::{CountBy, Distinct};
+use timely::dataflow::Stream;
+use timely::test_harness::{Harness, HarnessScope, Script, Step};
+
+// Records spread over a few epochs, with repeated values.
+fn records() -> Vec<(u64, u64)> {
+    (0 .. 200u64).map(|x| ((x * 7) % 13, x % 17)).collect()
+}
+
+// Plays `records` under many schedules into `build`, and compares the results with `model`,
+// which maps the records of each epoch to the expected output at that epoch.
+fn check<R, F, M>(build: F, model: M)
+where
+    R: Ord+Clone+std::fmt::Debug+'static,
+    F: for<'a> Fn(&Stream<HarnessScope<'a>, u64>) -> Stream<HarnessScope<'a>, R>,
+    M: Fn(u64, &[u64]) -> Vec<(u64, R)>,
+{
+    let mut by_epoch = BTreeMap::new();
+    for (epoch, x) in records() {
+        by_epoch.entry(epoch).or_insert_with(Vec::new).push(x);
+    }
+    let mut expected = BTreeMap::new();
+    for (epoch, data) in by_epoch.iter() {
+        for (time, result) in model(*epoch, &data[..]) {
+            expected.entry(time).or_insert_with(Vec::new).push(result);
+        }
+    }
+
+    for seed in 0 .. 20 {
+        let script = Script::generate(records(), seed);
+        let mut harness = Harness::new(&build);
+        harness.play(&script);
+        harness.finish().assert_epochs(expected.clone());
+    }
+}
+
+#[test]
+fn map_filter_flat_map() {
+    check(|stream| stream.map(|x| x * 2), |epoch, data| data.iter().map(|x| (epoch, x * 2)).collect());
+    check(|stream| stream.filter(|x| x % 3 == 0), |epoch, data| data.iter().filter(|x| *x % 3 == 0).map(|x| (epoch, *x)).collect());
+    check(|stream| stream.flat_map(|x| 0 .. x % 4), |epoch, data| data.iter().flat_map(|x| 0 .. x % 4).map(|x| (epoch, x)).collect());
+}
+
+#[test]
+fn map_batch_flat_map_batch() {
+    check(|stream| {
+        stream.map_batch(|time, mut batch| {
+            batch.retain(|x| x % 2 == 0);
+            batch.into_iter().map(|x| (*time, x)).collect()
+        })
+    }, |epoch, data| data.iter().filter(|x| *x % 2 == 0).map(|x| (epoch, (epoch, *x))).collect());
+    check(|stream| stream.flat_map_batch(|time, batch| {
+        let time = *time;
+        batch.into_iter().map(move |x| (time, x % 5))
+    }), |epoch, data| {
+        data.iter().map(|x| (epoch, (epoch, x % 5))).collect()
+    });
+}
+
+#[test]
+fn exchange_concat_partition() {
+    check(|stream| stream.exchange(|x| *x), |epoch, data| data.iter().map(|x| (epoch, *x)).collect());
+    check(|stream| stream.concat(&stream.map(|x| x + 100)), |epoch, data| {
+        data.iter().flat_map(|x| vec![(epoch, *x), (epoch, x + 100)]).collect()
+    });
+    check(|stream| {
+        let parts = stream.partition(3, |x| (x % 3, x));
+        stream.scope().concatenate(parts)
+    }, |epoch, data| data.iter().map(|x| (epoch, *x)).collect());
+}
+
+#[test]
+fn delay() {
+    check(|stream| stream.delay(|x, time| time + x % 3), |epoch, data| data.iter().map(|x| (epoch + x % 3, *x)).collect());
+    check(|stream| stream.delay_batch(|time| time + 2), |epoch, data| data.iter().map(|x| (epoch + 2, *x)).collect());
+}
+
+#[test]
+fn accumulate_and_count() {
+    check(|stream| stream.count(), |epoch, data| vec![(epoch, data.len())]);
+    check(|stream| stream.accumulate(0, |sum, data| { for x in data.iter() { *sum += x; } }), |epoch, data| {
+        vec![(epoch, data.iter().sum::<u64>())]
+    });
+}
+
+#[test]
+fn aggregate_distinct_count_by() {
+    check(|stream| {
+        stream.map(|x| (x % 4, x))
+              .aggregate(|_key, val, sum: &mut u64| *sum += val, |key, sum| (key, sum), |key| *key)
+    }, |epoch, data| {
+        let mut sums = BTreeMap::new();
+        for x in data.iter() { *sums.entry(x % 4).or_insert(0) += x; }
+        sums.into_iter().map(|sum| (epoch, sum)).collect()
+    });
+    check(|stream| stream.distinct(), |epoch, data| {
+        let mut data = data.to_vec();
+        data.sort();
+        data.dedup();
+        data.into_iter().map(|x| (epoch, x)).collect()
+    });
+    check(|stream| stream.count_by(|x| x % 5), |epoch, data| {
+        let mut counts = BTreeMap::new();
+        for x in data.iter() { *counts.entry(x % 5).or_insert(0) += 1; }
+        counts.into_iter().map(|count| (epoch, count)).collect()
+    });
+}
+
+// Generated scripts send every record at its epoch, and differ with the seed.
+#[test]
+fn generated_scripts() {
+    let mut expected = records();
+    expected.sort_by_key(|(epoch, _)| *epoch);
+    let script = Script::generate(records(), 0);
+    assert_eq!(script.records(), expected);
+    assert_eq!(script, Script::generate(records(), 0));
+    assert_ne!(script, Script::generate(records(), 1));
+    assert!(script.steps().contains(&Step::Step));
+}
+
+// A scripted input may be played in parts, with results read as epochs complete.
+#[test]
+fn results_as_epochs_complete() {
+    let mut harness = Harness::new(|stream| stream.count());
+    harness.play(&Script::new().send(1).send(2).advance_to(1).send(3));
+    let results = harness.results().clone();
+    harness.worker().step_while(|| !results.complete(&0));
+    assert_eq!(harness.results().epochs().get(&0), Some(&vec![2]));
+    assert!(!harness.results().complete(&1));
+    harness.play(&Script::new().advance_to(4).send(4));
+    harness.finish().assert_epochs(vec![(0, vec![2]), (1, vec![1]), (4, vec![1])]);
+}
+
+// An operator that holds a capability after its input is exhausted never completes.
+#[test]
+#[should_panic(expected = "did not become empty")]
+fn held_capability() {
+    let mut harness = Harness::new(|stream| {
+        stream.unary(Pipeline, "Leak", |capability, _info| {
+            move |input, output| {
+                let _held = &capability;
+                input.for_each(|time, data| output.session(&time).give_vec(&mut data.replace(Vec::new())));
+            }
+        })
+    }).max_steps(100);
+    harness.play(&Script::new().send(1u64));
+    harness.finish();
+}
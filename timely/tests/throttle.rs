@@ -0,0 +1,49 @@
+extern crate timely;
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use timely::communication::allocator::simulation::SimulationConfig;
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Inspect, Probe};
+use timely::dataflow::operators::throttle::Throttle;
+
+// Records pass at the throttled rate, of one each simulated millisecond, however they arrive.
+#[test]
+fn records_pass_at_the_rate() {
+    let (mut cluster, mut handles) = timely::execute_simulated(1, SimulationConfig::new(0), |worker| {
+        let mut input = InputHandle::new();
+        let passed = Rc::new(Cell::new(0));
+        let counter = passed.clone();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .throttle(1000)
+                 .inspect(move |_: &u64| counter.set(counter.get() + 1))
+                 .probe()
+        });
+        (input, passed, probe)
+    });
+
+    // A burst is released one record each tick.
+    let (input, _, _) = &mut handles[0];
+    input.send_batch(&mut (0 .. 50).collect());
+    input.advance_to(1);
+    for tick in 1 ..= 10 {
+        cluster.tick();
+        assert!(handles[0].1.get() <= tick + 1, "{} records passed by tick {}", handles[0].1.get(), tick);
+    }
+
+    // Budget is not accrued while idle, so a burst after an idle period is also throttled.
+    while handles[0].2.less_than(&1) { cluster.tick(); }
+    assert_eq!(handles[0].1.get(), 50);
+    for _ in 0 .. 100 { cluster.tick(); }
+    let (input, _, _) = &mut handles[0];
+    input.send_batch(&mut (0 .. 50).collect());
+    input.advance_to(2);
+    for tick in 1 ..= 10 {
+        cluster.tick();
+        assert!(handles[0].1.get() <= 50 + tick + 1, "{} records passed by tick {}", handles[0].1.get() - 50, tick);
+    }
+    while handles[0].2.less_than(&2) { cluster.tick(); }
+    assert_eq!(handles[0].1.get(), 100);
+}
@@ -0,0 +1,55 @@
+extern crate timely;
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange, Inspect, Probe};
+use timely::{CommunicationConfig, Config, WorkerConfig};
+
+// Runs three workers that all send to worker zero, and returns the order in which it saw records.
+fn run(worker: WorkerConfig) -> Vec<(u64, u64)> {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let shared = seen.clone();
+    let config = Config { communication: CommunicationConfig::Process(3), worker };
+    timely::execute(config, move |worker| {
+        let index = worker.index() as u64;
+        let shared = shared.clone();
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|_| 0)
+                 .inspect_time(move |time, x| shared.lock().unwrap().push((*time, *x)))
+                 .probe()
+        });
+        for round in 0 .. 20u64 {
+            for record in 0 .. 10 {
+                input.send(100 * index + record);
+            }
+            input.advance_to(round + 1);
+            worker.step_or_park_while(None, || probe.less_than(input.time()));
+        }
+    }).unwrap();
+    let seen = seen.lock().unwrap().clone();
+    seen
+}
+
+fn directory(name: &str) -> std::path::PathBuf {
+    let directory = std::env::temp_dir().join(format!("timely-{}-{}", name, std::process::id()));
+    if Path::new(&directory).exists() {
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+    directory
+}
+
+// Replay presents worker zero with records in the order it recorded, whatever the races.
+#[test]
+fn trace_replay_3w() {
+    let directory = directory("trace-replay");
+    let recorded = run(WorkerConfig::default().record_trace(&directory));
+    assert_eq!(recorded.len(), 600);
+    for _ in 0 .. 3 {
+        assert_eq!(run(WorkerConfig::default().replay_trace(&directory)), recorded);
+    }
+    std::fs::remove_dir_all(&directory).unwrap();
+}
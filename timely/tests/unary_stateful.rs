@@ -0,0 +1,84 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::channels::pact::{Exchange, Pipeline};
+use timely::dataflow::operators::{Input, Inspect, Probe, ToStream};
+use timely::dataflow::operators::generic::Operator;
+
+// States are completed once the frontier passes their times, in order, and their output is sent
+// at their times.
+#[test]
+fn completes_as_frontier_advances() {
+    let completed = Arc::new(Mutex::new(Vec::new()));
+    let seen = completed.clone();
+    timely::execute(timely::Config::thread(), move |worker| {
+        let seen = seen.clone();
+        let completed = seen.clone();
+        let mut input = InputHandle::<u64, u64>::new();
+        let probe = worker.dataflow(|scope| {
+            scope.input_from(&mut input)
+                .unary_stateful(Pipeline, "Count",
+                    |input, _output, states| {
+                        input.for_each(|time, data| *states.state(&time) += data.len());
+                    },
+                    |time, count, output| output.push((*time, count)),
+                )
+                .inspect_time(move |time, record| seen.lock().unwrap().push((*time, *record)))
+                .probe()
+        });
+        input.send(0);
+        input.advance_to(1);
+        input.send(1);
+        input.send(2);
+        input.advance_to(2);
+        input.send(3);
+        worker.step_while(|| probe.less_than(&2));
+        assert_eq!(completed.lock().unwrap().len(), 2);
+        input.close();
+        worker.step_while(|| !probe.done());
+    }).unwrap();
+    assert_eq!(*completed.lock().unwrap(), vec![(0, (0, 1)), (1, (1, 2)), (2, (2, 1))]);
+}
+
+// A time registered for many batches holds one capability, which is released once completed.
+#[test]
+fn holds_one_capability_per_time() {
+    let completed = Arc::new(Mutex::new(Vec::new()));
+    let seen = completed.clone();
+    timely::execute(timely::Config::process(2), move |worker| {
+        let seen = seen.clone();
+        let probe = worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 100u64).to_stream(scope)
+                .unary_stateful(Exchange::new(|x: &u64| *x), "Sum",
+                    |input, _output, states| {
+                        input.for_each(|time, data| {
+                            for x in data.iter() { *states.state(&time) += *x; }
+                        });
+                    },
+                    |_time, sum: u64, output| output.push(sum),
+                )
+                .inspect(move |sum| seen.lock().unwrap().push(*sum))
+                .probe()
+        });
+        worker.step_while(|| !probe.done());
+    }).unwrap();
+    let mut completed = completed.lock().unwrap().clone();
+    completed.sort();
+    // Each worker sends 0 .. 100, and each sums the even or the odd records of both.
+    assert_eq!(completed, vec![4900, 5000]);
+}
+
+// Completions that produce nothing send nothing.
+#[test]
+fn empty_completions() {
+    timely::example(|scope| {
+        (0 .. 10u64).to_stream(scope)
+            .unary_stateful(Pipeline, "Drop",
+                |input, _output, states| input.for_each(|time, _data| { states.state_with(&time, || ()); }),
+                |_time, (), _output: &mut Vec<u64>| { },
+            )
+            .inspect(|_| panic!("no records expected"));
+    });
+}
@@ -0,0 +1,44 @@
+extern crate timely;
+
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Input, Exchange};
+use timely::dataflow::operators::watch::{Update, Watch};
+use timely::progress::Antichain;
+
+// Each worker's watcher is drained on the main thread, and together they observe every record.
+#[test]
+fn watch_from_driver_thread() {
+    let watchers = Arc::new(Mutex::new(Vec::new()));
+    let watchers2 = watchers.clone();
+    timely::execute(timely::Config::process(3), move |worker| {
+        let mut input = InputHandle::new();
+        let watcher = worker.dataflow::<u64,_,_>(|scope| {
+            scope.input_from(&mut input)
+                 .exchange(|x: &u64| *x)
+                 .watch()
+        });
+        watchers2.lock().unwrap().push(watcher);
+        for round in 0 .. 5 {
+            input.send(round + 10 * worker.index() as u64);
+            input.advance_to(round + 1);
+        }
+    }).unwrap();
+
+    let mut records = Vec::new();
+    for watcher in watchers.lock().unwrap().drain(..) {
+        let mut last = None;
+        for update in watcher {
+            match update {
+                Update::Data(time, data) => records.extend(data.into_iter().map(|x| (time, x))),
+                Update::Frontier(frontier) => last = Some(frontier),
+            }
+        }
+        assert_eq!(last, Some(Antichain::new()));
+    }
+    records.sort();
+    let mut expected = (0 .. 3).flat_map(|index| (0 .. 5).map(move |round| (round, round + 10 * index))).collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(records, expected);
+}
@@ -0,0 +1,32 @@
+extern crate timely;
+
+use timely::dataflow::operators::{Capture, Delay, ToStream};
+use timely::dataflow::operators::capture::Extract;
+use timely::dataflow::operators::windows::Windows;
+
+// Windows sliding further than their size leave gaps, whose records are dropped.
+#[test]
+fn records_in_gaps_are_dropped() {
+    let captured = timely::example(|scope| {
+        (0 .. 12u64).to_stream(scope)
+                    .delay(|x, _| *x)
+                    .sliding_window(2, 5, |seen: &mut Vec<u64>, x| seen.push(x))
+                    .capture()
+    });
+
+    let mut windows = captured.extract().into_iter().flat_map(|(_, w)| w).collect::<Vec<_>>();
+    for (_, seen) in windows.iter_mut() { seen.sort(); }
+    assert_eq!(windows, vec![(0, vec![0, 1]), (5, vec![5, 6]), (10, vec![10, 11])]);
+}
+
+// A record in the gap alone produces no window.
+#[test]
+fn gap_only_records() {
+    let captured = timely::example(|scope| {
+        vec![3u64].to_stream(scope)
+                  .delay(|x, _| *x)
+                  .sliding_window(2, 5, |count: &mut usize, _x| *count += 1)
+                  .capture()
+    });
+    assert!(captured.extract().is_empty());
+}
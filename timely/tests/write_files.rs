@@ -0,0 +1,139 @@
+extern crate timely;
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use timely::dataflow::InputHandle;
+use timely::dataflow::operators::{Exchange, Input, Inspect, Probe, ToStream, UnorderedInput, WriteFiles};
+
+// A fresh directory for a test's files.
+fn directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("timely_write_files_{}_{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+// The names of the files in `directory`, sorted.
+fn listing(directory: &PathBuf) -> Vec<String> {
+    let mut names = fs::read_dir(directory).unwrap()
+                       .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+                       .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+// Files appear under their names only once their epochs are complete.
+#[test]
+fn commits_when_frontier_passes() {
+    let directory = directory("commit");
+    let template = directory.join("out-{worker}-{epoch}.txt").display().to_string();
+    let listed = directory.clone();
+    timely::execute(timely::Config::thread(), move |worker| {
+        let manifest = Arc::new(Mutex::new(Vec::new()));
+        let reported = manifest.clone();
+        let ((mut input, capability), probe) = worker.dataflow::<u64,_,_>(|scope| {
+            let (input, stream) = scope.new_unordered_input::<u64>();
+            let probe = stream.write_to_files(&template)
+                              .inspect_time(move |time, path| reported.lock().unwrap().push((*time, path.clone())))
+                              .probe();
+            (input, probe)
+        });
+        let later = capability.delayed(&1);
+        input.session(capability.clone()).give_iterator(1 .. 3);
+        input.session(later.clone()).give(3);
+        drop(capability);
+        worker.step_while(|| probe.less_than(&1));
+        assert_eq!(listing(&listed), vec![".out-0-1.txt.partial", "out-0-0.txt"]);
+        assert_eq!(fs::read_to_string(listed.join("out-0-0.txt")).unwrap(), "1\n2\n");
+
+        drop(later);
+        worker.step_while(|| !probe.done());
+        assert_eq!(listing(&listed), vec!["out-0-0.txt", "out-0-1.txt"]);
+        assert_eq!(fs::read_to_string(listed.join("out-0-1.txt")).unwrap(), "3\n");
+        assert_eq!(*manifest.lock().unwrap(), vec![(0, Ok(listed.join("out-0-0.txt"))), (1, Ok(listed.join("out-0-1.txt")))]);
+    }).unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+// Each worker writes the records it receives to files of its own.
+#[test]
+fn one_file_per_worker_and_epoch() {
+    let directory = directory("workers");
+    let template = directory.join("{epoch}").join("part-{worker}").display().to_string();
+    fs::create_dir_all(directory.join("0")).unwrap();
+    timely::execute(timely::Config::process(3), move |worker| {
+        let index = worker.index() as u64;
+        worker.dataflow::<u64,_,_>(|scope| {
+            (0 .. 3u64).map(move |x| 3 * x + index)
+                       .to_stream(scope)
+                       .exchange(|x| *x)
+                       .write_to_files(&template);
+        });
+    }).unwrap();
+    let epoch = directory.join("0");
+    assert_eq!(listing(&epoch), vec!["part-0", "part-1", "part-2"]);
+    for worker in 0 .. 3 {
+        let records = fs::read_to_string(epoch.join(format!("part-{}", worker))).unwrap();
+        let mut records = records.lines().map(|line| line.parse::<u64>().unwrap()).collect::<Vec<_>>();
+        records.sort();
+        assert_eq!(records, (0 .. 9).filter(|x| x % 3 == worker).collect::<Vec<_>>());
+    }
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+// Epochs the frontier passes through without records are marked by empty files.
+#[test]
+fn empty_epochs_have_empty_files() {
+    let directory = directory("empty");
+    let template = directory.join("out-{worker}-{epoch}.txt").display().to_string();
+    let listed = directory.clone();
+    timely::execute(timely::Config::thread(), move |worker| {
+        let mut input = InputHandle::new();
+        let probe = worker.dataflow::<u64,_,_>(|scope| scope.input_from(&mut input).write_to_files(&template).probe());
+        for round in 0 .. 3u64 {
+            if round != 1 {
+                input.send(round);
+            }
+            input.advance_to(round + 1);
+            worker.step_while(|| probe.less_than(input.time()));
+        }
+        input.close();
+        worker.step_while(|| !probe.done());
+        // The input closes at time 3, which the frontier also passes through.
+        assert_eq!(listing(&listed), vec!["out-0-0.txt", "out-0-1.txt", "out-0-2.txt", "out-0-3.txt"]);
+        assert_eq!(fs::read_to_string(listed.join("out-0-1.txt")).unwrap(), "");
+        assert_eq!(fs::read_to_string(listed.join("out-0-3.txt")).unwrap(), "");
+        assert_eq!(fs::read_to_string(listed.join("out-0-2.txt")).unwrap(), "2\n");
+    }).unwrap();
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+// Files that cannot be written are reported as errors, rather than panicking.
+#[test]
+fn errors_are_reported() {
+    let directory = directory("errors");
+    let template = directory.join("missing").join("out-{worker}-{epoch}.txt").display().to_string();
+    let manifest = Arc::new(Mutex::new(Vec::new()));
+    let reported = manifest.clone();
+    timely::example(move |scope| {
+        (0 .. 3u64).to_stream(scope)
+                   .write_to_files(&template)
+                   .inspect(move |result| reported.lock().unwrap().push(result.clone()));
+    });
+    let manifest = manifest.lock().unwrap();
+    assert_eq!(manifest.len(), 1);
+    let error = manifest[0].as_ref().unwrap_err();
+    assert!(error.starts_with("failed to create"), "unexpected error: {}", error);
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+// Templates must name distinct files for distinct workers and epochs.
+#[test]
+#[should_panic(expected = "path template")]
+fn template_requires_placeholders() {
+    timely::example(|scope| {
+        (0 .. 3u64).to_stream(scope).write_to_files("out-{epoch}.txt");
+    });
+}